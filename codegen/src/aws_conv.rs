@@ -1,3 +1,4 @@
+use crate::dto::is_patched_field;
 use crate::dto::RustTypes;
 use crate::ops::is_op_input;
 use crate::ops::Operations;
@@ -66,6 +67,11 @@ pub fn codegen(ops: &Operations, rust_types: &RustTypes) {
                         s => s,
                     };
 
+                    if is_patched_field(&ty.name, s3s_field_name) {
+                        g!("{s3s_field_name}: None,");
+                        continue;
+                    }
+
                     if field.type_ == "SelectObjectContentEventStream" {
                         g!("{s3s_field_name}: Some(crate::event_stream::from_aws(x.{aws_field_name})),");
                         continue;
@@ -148,6 +154,10 @@ pub fn codegen(ops: &Operations, rust_types: &RustTypes) {
                         s => s,
                     };
 
+                    if is_patched_field(&ty.name, s3s_field_name) {
+                        continue;
+                    }
+
                     if field.option_type {
                         g!("y = y.set_{aws_field_name}(try_into_aws(x.{s3s_field_name})?);");
                     } else {
@@ -1,3 +1,4 @@
+use crate::dto::is_patched_field;
 use crate::dto::RustTypes;
 use crate::ops::Operations;
 use crate::rust;
@@ -52,6 +53,10 @@ pub fn codegen(ops: &Operations, rust_types: &RustTypes) {
             };
 
             for field in ty.fields.iter().chain(flattened_fields) {
+                if is_patched_field(&ty.name, &field.name) {
+                    continue;
+                }
+
                 let s3s_field_name = match ty.name.as_str() {
                     "SelectObjectContentInput" if field.name == "request" => continue,
                     "SelectObjectContentInput" if field.position == "xml" => f!("request.{}", field.name),
@@ -199,6 +199,7 @@ pub fn collect_rust_types(model: &smithy::Model, ops: &Operations) -> RustTypes
                         http_query: field.traits.http_query().map(o),
                         xml_name: field.traits.xml_name().map(o),
                         xml_flattened: field.traits.xml_flattened(),
+                        xml_attribute: field.traits.xml_attribute(),
                     };
                     fields.push(field);
                 }
@@ -208,6 +209,7 @@ pub fn collect_rust_types(model: &smithy::Model, ops: &Operations) -> RustTypes
                     doc: shape.traits.doc().map(ToOwned::to_owned),
 
                     xml_name: shape.traits.xml_name().map(o),
+                    xml_namespace: shape.traits.xml_namespace().map(|(uri, prefix)| (o(uri), prefix.map(o))),
                     is_error_type: shape.traits.error().is_some(),
                 });
                 insert(rs_shape_name, ty);
@@ -259,6 +261,7 @@ fn patch_types(space: &mut RustTypes) {
             fields: ty.fields.iter().filter(|x| x.position == "xml").cloned().collect(),
             doc: ty.doc.clone(),
             xml_name: None,
+            xml_namespace: None,
             is_error_type: false,
         };
 
@@ -278,12 +281,76 @@ fn patch_types(space: &mut RustTypes) {
             http_query: None,
             xml_name: Some(request.name.clone()),
             xml_flattened: false,
+            xml_attribute: false,
         });
         ty.name = o("SelectObjectContentInput");
 
         space.insert("SelectObjectContentInput".into(), rust::Type::Struct(ty));
         space.insert("SelectObjectContentRequest".into(), rust::Type::Struct(request));
     }
+
+    // patch PutObject input: conditional writes
+    {
+        let Some(rust::Type::Struct(ty)) = space.get_mut("PutObjectRequest") else { panic!() };
+        assert!(ty.fields.iter().all(|x| x.name != "if_none_match"));
+
+        let field = rust::StructField {
+            name: o("if_none_match"),
+            type_: o("IfNoneMatch"),
+            doc: Some(o(concat!(
+                "<p>Uploads the object only if the object key name does not already exist in the bucket specified.\n",
+                "Otherwise, Amazon S3 returns a <code>412 Precondition Failed</code> error.</p>\n",
+                "<p>Expects the '*' (asterisk) character.</p>",
+            ))),
+            camel_name: o("IfNoneMatch"),
+            option_type: true,
+            default_value: None,
+            is_required: false,
+            position: o("header"),
+            http_header: Some(o("If-None-Match")),
+            http_query: None,
+            xml_name: None,
+            xml_flattened: false,
+            xml_attribute: false,
+        };
+        let idx = ty.fields.iter().position(|x| x.name.as_str() > "if_none_match").unwrap();
+        ty.fields.insert(idx, field);
+    }
+
+    // patch DeleteObject input: conditional deletes
+    {
+        let Some(rust::Type::Struct(ty)) = space.get_mut("DeleteObjectRequest") else { panic!() };
+        assert!(ty.fields.iter().all(|x| x.name != "if_match"));
+
+        let field = rust::StructField {
+            name: o("if_match"),
+            type_: o("IfMatch"),
+            doc: Some(o(concat!(
+                "<p>Deletes the object only if its entity tag (ETag) matches the specified tag.\n",
+                "Otherwise, Amazon S3 returns a <code>412 Precondition Failed</code> error.</p>",
+            ))),
+            camel_name: o("IfMatch"),
+            option_type: true,
+            default_value: None,
+            is_required: false,
+            position: o("header"),
+            http_header: Some(o("If-Match")),
+            http_query: None,
+            xml_name: None,
+            xml_flattened: false,
+            xml_attribute: false,
+        };
+        let idx = ty.fields.iter().position(|x| x.name.as_str() > "if_match").unwrap();
+        ty.fields.insert(idx, field);
+    }
+}
+
+/// Fields which are added by `patch_types` and unknown to the AWS SDK
+pub fn is_patched_field(ty_name: &str, field_name: &str) -> bool {
+    matches!(
+        (ty_name, field_name),
+        ("PutObjectInput", "if_none_match") | ("DeleteObjectInput", "if_match")
+    )
 }
 
 fn unify_operation_types(ops: &Operations, space: &mut RustTypes) {
@@ -298,6 +365,7 @@ fn unify_operation_types(ops: &Operations, space: &mut RustTypes) {
                 fields: default(),
                 doc: None,
                 xml_name: None,
+                xml_namespace: None,
                 is_error_type: false,
             }
         } else {
@@ -317,6 +385,7 @@ fn unify_operation_types(ops: &Operations, space: &mut RustTypes) {
                 fields: default(),
                 doc: None,
                 xml_name: None,
+                xml_namespace: None,
                 is_error_type: false,
             }
         } else {
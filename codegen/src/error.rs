@@ -18,6 +18,37 @@ struct Error {
 
 type Errors = BTreeMap<String, Error>;
 
+/// Error codes which are missing from the documentation of `com.amazonaws.s3#Error`
+const EXTRA_ERRORS: &[(&str, &str, &str)] = &[
+    ("AccessForbidden", "The CORS request is not allowed.", "403 Forbidden"),
+    (
+        "InvalidTag",
+        "The tag provided was not a valid tag. This error can occur if the tag did not pass input validation.",
+        "400 Bad Request",
+    ),
+    (
+        "MalformedPolicy",
+        "The policy document is not well-formed or does not match the policy grammar.",
+        "400 Bad Request",
+    ),
+    ("NoSuchCORSConfiguration", "The CORS configuration does not exist.", "404 Not Found"),
+    (
+        "NoSuchObjectLockConfiguration",
+        "The specified object does not have an Object Lock configuration.",
+        "404 Not Found",
+    ),
+    (
+        "NoSuchWebsiteConfiguration",
+        "The specified bucket does not have a website configuration.",
+        "404 Not Found",
+    ),
+    (
+        "ObjectLockConfigurationNotFoundError",
+        "Object Lock configuration does not exist for this bucket.",
+        "404 Not Found",
+    ),
+];
+
 fn collect_errors(model: &smithy::Model) -> Errors {
     let error_code_doc = {
         let smithy::Shape::Structure(shape) = &model.shapes["com.amazonaws.s3#Error"] else { panic!() };
@@ -35,7 +66,7 @@ fn collect_errors(model: &smithy::Model) -> Errors {
             let Some(cap) = pattern.captures(line) else { continue };
             let tag = cap.get(1).unwrap().as_str();
             assert_eq!(tag, "Code:");
-            o(code_pattern.captures(line).unwrap().get(2).unwrap().as_str())
+            o(code_pattern.captures(line).unwrap().get(2).unwrap().as_str().trim())
         };
 
         let description = loop {
@@ -115,6 +146,15 @@ fn collect_errors(model: &smithy::Model) -> Errors {
         err.status.push(status);
     }
 
+    for &(code, description, status) in EXTRA_ERRORS {
+        let err = Error {
+            code: o(code),
+            description: vec![Some(o(description))],
+            status: vec![Some(o(status))],
+        };
+        assert!(errors.insert(o(code), err).is_none());
+    }
+
     errors
 }
 
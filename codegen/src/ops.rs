@@ -517,12 +517,10 @@ fn codegen_op_http_de_multipart(op: &Operation, rust_types: &RustTypes) {
         "let bucket = http::unwrap_bucket(req);"
         "let key = http::parse_field_value(&m, \"key\")?.ok_or_else(|| invalid_request!(\"missing key\"))?;"
         ""
-        "let vec_stream = req.s3ext.vec_stream.take().expect(\"missing vec stream\");"
+        "let body: Option<StreamingBlob> = req.s3ext.file_stream.take();"
         ""
-        "let content_length = i64::try_from(vec_stream.exact_remaining_length()).map_err(|e|s3_error!(e, InvalidArgument, \"content-length overflow\"))?;"
-        "let content_length = (content_length != 0).then_some(content_length);"
-        ""
-        "let body: Option<StreamingBlob> = Some(StreamingBlob::new(vec_stream));"
+        "// the size of the file is unknown until it is streamed"
+        "let content_length: Option<ContentLength> = None;"
         ""
     ];
 
@@ -619,17 +617,14 @@ fn codegen_op_http_call(op: &Operation) {
         g!("let overrided_headers = super::get_object::extract_overrided_response_headers(&s3_req)?;");
     }
 
-    g!("let result = s3.{method}(s3_req).await;");
-
-    glines![
-        "let s3_resp = match result {"
-        "    Ok(val) => val,"
-        "    Err(err) => return super::serialize_error(err),"
-        "};"
-    ];
+    g!("let s3_resp = s3.{method}(s3_req).await?;");
 
     g!("let mut resp = Self::serialize_http(s3_resp.output)?;");
 
+    g!("if let Some(status) = s3_resp.status {{");
+    g!("resp.status = status;");
+    g!("}}");
+
     if op.name == "GetObject" {
         g!("resp.headers.extend(overrided_headers);");
         g!("super::get_object::merge_custom_headers(&mut resp, s3_resp.headers);");
@@ -70,9 +70,11 @@ pub struct Struct {
     pub doc: Option<String>,
 
     pub xml_name: Option<String>,
+    pub xml_namespace: Option<(String, Option<String>)>,
     pub is_error_type: bool,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
 pub struct StructField {
     pub name: String,
@@ -92,6 +94,7 @@ pub struct StructField {
 
     pub xml_name: Option<String>,
     pub xml_flattened: bool,
+    pub xml_attribute: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -209,6 +209,17 @@ impl Traits {
         self.get("smithy.api#xmlFlattened").is_some()
     }
 
+    pub fn xml_attribute(&self) -> bool {
+        self.get("smithy.api#xmlAttribute").is_some()
+    }
+
+    pub fn xml_namespace(&self) -> Option<(&str, Option<&str>)> {
+        let ns = self.get("smithy.api#xmlNamespace")?;
+        let uri = ns.get("uri")?.as_str()?;
+        let prefix = ns.get("prefix").and_then(Value::as_str);
+        Some((uri, prefix))
+    }
+
     pub fn http_label(&self) -> Option<&Value> {
         self.get("smithy.api#httpLabel")
     }
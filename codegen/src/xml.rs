@@ -126,7 +126,26 @@ fn codegen_xml_ser(ops: &Operations, rust_types: &RustTypes) {
                     if ty.fields.is_empty() { '_' } else { 's' }
                 );
 
-                for field in ty.fields.iter().filter(|x| x.position == "xml") {
+                if let Some((uri, prefix)) = &ty.xml_namespace {
+                    match prefix {
+                        Some(prefix) => g!("s.attribute(\"xmlns:{prefix}\", \"{uri}\");"),
+                        None => g!("s.attribute(\"xmlns\", \"{uri}\");"),
+                    }
+                }
+
+                for field in ty.fields.iter().filter(|x| x.position == "xml" && x.xml_attribute) {
+                    let xml_name = field.xml_name.as_ref().unwrap_or(&field.camel_name);
+                    assert!(matches!(rust_types[field.type_.as_str()], rust::Type::StrEnum(_)));
+                    if field.option_type {
+                        g!("if let Some(ref val) = self.{} {{", field.name);
+                        g!("s.attribute(\"{xml_name}\", val.as_str());");
+                        g!("}}");
+                    } else {
+                        g!("s.attribute(\"{}\", self.{}.as_str());", xml_name, field.name);
+                    }
+                }
+
+                for field in ty.fields.iter().filter(|x| x.position == "xml" && x.xml_attribute.not()) {
                     let xml_name = field.xml_name.as_ref().unwrap_or(&field.camel_name);
 
                     let field_ty = &rust_types[field.type_.as_str()];
@@ -153,6 +172,11 @@ fn codegen_xml_ser(ops: &Operations, rust_types: &RustTypes) {
                         } else {
                             g!("s.timestamp(\"{}\", &self.{}, TimestampFormat::{})?;", xml_name, field.name, fmt);
                         }
+                    } else if field.option_type && ty.name == "GetBucketLocationOutput" {
+                        // the location constraint is the text of the root element
+                        g!("if let Some(ref val) = self.{} {{", field.name);
+                        g!("val.serialize_content(s)?;");
+                        g!("}}");
                     } else if field.option_type {
                         g!("if let Some(ref val) = self.{} {{", field.name);
                         g!("s.content(\"{xml_name}\", val)?;");
@@ -320,12 +344,18 @@ fn codegen_xml_de(ops: &Operations, rust_types: &RustTypes) {
                 }
 
                 for field in &ty.fields {
-                    g!("let mut {}: Option<{}> = None;", field.name, field.type_);
+                    if field.xml_attribute {
+                        let xml_name = field.xml_name.as_ref().unwrap_or(&field.camel_name);
+                        let ty = &field.type_;
+                        g!("let {}: Option<{ty}> = d.attribute(\"{xml_name}\")?.map({ty}::from);", field.name);
+                    } else {
+                        g!("let mut {}: Option<{}> = None;", field.name, field.type_);
+                    }
                 }
 
                 if ty.fields.is_empty().not() {
                     g!("d.for_each_element(|d, x| match x {{");
-                    for field in &ty.fields {
+                    for field in ty.fields.iter().filter(|x| x.xml_attribute.not()) {
                         let xml_name = field.xml_name.as_ref().unwrap_or(&field.camel_name);
                         let field_name = field.name.as_str();
                         let field_type = &rust_types[field.type_.as_str()];
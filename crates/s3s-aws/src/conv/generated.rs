@@ -1758,6 +1758,7 @@ impl AwsConversion for s3s::dto::DeleteObjectInput {
             bucket: unwrap_from_aws(x.bucket, "bucket")?,
             bypass_governance_retention: try_from_aws(x.bypass_governance_retention)?,
             expected_bucket_owner: try_from_aws(x.expected_bucket_owner)?,
+            if_match: None,
             key: unwrap_from_aws(x.key, "key")?,
             mfa: try_from_aws(x.mfa)?,
             request_payer: try_from_aws(x.request_payer)?,
@@ -6775,6 +6776,7 @@ impl AwsConversion for s3s::dto::PutObjectInput {
             grant_read: try_from_aws(x.grant_read)?,
             grant_read_acp: try_from_aws(x.grant_read_acp)?,
             grant_write_acp: try_from_aws(x.grant_write_acp)?,
+            if_none_match: None,
             key: unwrap_from_aws(x.key, "key")?,
             metadata: try_from_aws(x.metadata)?,
             object_lock_legal_hold_status: try_from_aws(x.object_lock_legal_hold_status)?,
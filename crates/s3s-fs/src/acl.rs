@@ -0,0 +1,416 @@
+//! Access control lists of buckets, stored as a sidecar file, and of objects, stored in the internal info
+//!
+//! The grants are stored and returned faithfully, but only `READ` granted to all users is enforced:
+//! such a bucket is added to the [`PublicBuckets`] of the authentication provider, and such an object
+//! to the [`PublicObjects`], which allow anonymous reads. The ACLs of objects are ignored if the bucket disables ACLs
+//! with its object ownership.
+
+use crate::error::*;
+use crate::fs::{load_json, save_json, FileSystem, InternalInfo};
+
+use s3s::auth::{Credentials, PublicBuckets, PublicObjects};
+use s3s::dto::*;
+use s3s::{s3_error, S3Result};
+
+use std::ops::Not;
+
+use serde::{Deserialize, Serialize};
+
+const ALL_USERS: &str = "http://acs.amazonaws.com/groups/global/AllUsers";
+const AUTHENTICATED_USERS: &str = "http://acs.amazonaws.com/groups/global/AuthenticatedUsers";
+const LOG_DELIVERY: &str = "http://acs.amazonaws.com/groups/s3/LogDelivery";
+
+const GROUPS: &[&str] = &[ALL_USERS, AUTHENTICATED_USERS, LOG_DELIVERY];
+
+const PERMISSIONS: &[&str] = &[
+    Permission::FULL_CONTROL,
+    Permission::READ,
+    Permission::READ_ACP,
+    Permission::WRITE,
+    Permission::WRITE_ACP,
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredGrant {
+    grantee_type: String,
+    id: Option<String>,
+    display_name: Option<String>,
+    email_address: Option<String>,
+    uri: Option<String>,
+    permission: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredAcl {
+    owner_id: Option<String>,
+    owner_display_name: Option<String>,
+    grants: Vec<StoredGrant>,
+}
+
+impl StoredAcl {
+    fn new(owner: Option<&Owner>, grants: Vec<Grant>) -> Self {
+        let grants = grants
+            .into_iter()
+            .filter_map(|g| {
+                let grantee = g.grantee?;
+                Some(StoredGrant {
+                    grantee_type: grantee.type_.as_str().to_owned(),
+                    id: grantee.id,
+                    display_name: grantee.display_name,
+                    email_address: grantee.email_address,
+                    uri: grantee.uri,
+                    permission: g.permission?.as_str().to_owned(),
+                })
+            })
+            .collect();
+        Self {
+            owner_id: owner.and_then(|o| o.id.clone()),
+            owner_display_name: owner.and_then(|o| o.display_name.clone()),
+            grants,
+        }
+    }
+
+    fn is_public_read(&self) -> bool {
+        self.grants.iter().any(|g| {
+            g.uri.as_deref() == Some(ALL_USERS) && matches!(g.permission.as_str(), Permission::READ | Permission::FULL_CONTROL)
+        })
+    }
+
+    /// `owner` is used if no owner was recorded
+    fn into_policy(self, owner: Option<&Owner>) -> AccessControlPolicy {
+        let grants = self
+            .grants
+            .into_iter()
+            .map(|g| Grant {
+                grantee: Some(Grantee {
+                    display_name: g.display_name,
+                    email_address: g.email_address,
+                    id: g.id,
+                    type_: Type::from(g.grantee_type),
+                    uri: g.uri,
+                }),
+                permission: Some(Permission::from(g.permission)),
+            })
+            .collect();
+        let owner = match self.owner_id {
+            Some(id) => Some(Owner {
+                display_name: self.owner_display_name,
+                id: Some(id),
+            }),
+            None => owner.map(copy_owner),
+        };
+        AccessControlPolicy {
+            grants: Some(grants),
+            owner,
+        }
+    }
+}
+
+/// The private ACL of a resource which has no stored ACL
+fn private_policy(owner: Option<&Owner>) -> AccessControlPolicy {
+    AccessControlPolicy {
+        grants: Some(private_grants(owner)),
+        owner: owner.map(copy_owner),
+    }
+}
+
+/// The owner of a bucket, which is the access key that created it
+pub(crate) fn owner_of(access_key: &str) -> Owner {
+    Owner {
+        display_name: Some(access_key.to_owned()),
+        id: Some(access_key.to_owned()),
+    }
+}
+
+fn copy_owner(owner: &Owner) -> Owner {
+    Owner {
+        display_name: owner.display_name.clone(),
+        id: owner.id.clone(),
+    }
+}
+
+fn grant(grantee: Grantee, permission: &str) -> Grant {
+    Grant {
+        grantee: Some(grantee),
+        permission: Some(Permission::from(permission.to_owned())),
+    }
+}
+
+fn group(uri: &str) -> Grantee {
+    Grantee {
+        display_name: None,
+        email_address: None,
+        id: None,
+        type_: Type::from_static(Type::GROUP),
+        uri: Some(uri.to_owned()),
+    }
+}
+
+fn canonical_user(id: String, display_name: Option<String>) -> Grantee {
+    Grantee {
+        display_name,
+        email_address: None,
+        id: Some(id),
+        type_: Type::from_static(Type::CANONICAL_USER),
+        uri: None,
+    }
+}
+
+/// The grants of a private ACL, which gives full control to the owner
+pub(crate) fn private_grants(owner: Option<&Owner>) -> Vec<Grant> {
+    let Some(Owner {
+        id: Some(id),
+        display_name,
+    }) = owner
+    else {
+        return Vec::new();
+    };
+    vec![grant(
+        canonical_user(id.clone(), display_name.clone()),
+        Permission::FULL_CONTROL,
+    )]
+}
+
+/// Translates a canned ACL into grants
+pub(crate) fn canned_grants(canned: &str, owner: Option<&Owner>) -> S3Result<Vec<Grant>> {
+    let mut grants = private_grants(owner);
+    match canned {
+        // the grantees of `aws-exec-read` and the `bucket-owner-*` ACLs of objects are not recorded
+        BucketCannedACL::PRIVATE
+        | ObjectCannedACL::AWS_EXEC_READ
+        | ObjectCannedACL::BUCKET_OWNER_READ
+        | ObjectCannedACL::BUCKET_OWNER_FULL_CONTROL => {}
+        BucketCannedACL::PUBLIC_READ => grants.push(grant(group(ALL_USERS), Permission::READ)),
+        BucketCannedACL::PUBLIC_READ_WRITE => {
+            grants.push(grant(group(ALL_USERS), Permission::READ));
+            grants.push(grant(group(ALL_USERS), Permission::WRITE));
+        }
+        BucketCannedACL::AUTHENTICATED_READ => grants.push(grant(group(AUTHENTICATED_USERS), Permission::READ)),
+        "log-delivery-write" => {
+            grants.push(grant(group(LOG_DELIVERY), Permission::WRITE));
+            grants.push(grant(group(LOG_DELIVERY), Permission::READ_ACP));
+        }
+        _ => return Err(s3_error!(InvalidArgument, "Invalid canned ACL: {}", canned)),
+    }
+    Ok(grants)
+}
+
+/// Parses a grant header such as `x-amz-grant-read: id="...", uri="..."`
+fn parse_grant_header(value: &str, permission: &str, grants: &mut Vec<Grant>) -> S3Result<()> {
+    for item in value.split(',') {
+        let Some((key, value)) = item.trim().split_once('=') else {
+            return Err(s3_error!(InvalidArgument, "Invalid grant header: {}", value));
+        };
+        let value = value.trim().trim_matches('"').to_owned();
+        let grantee = match key.trim() {
+            "id" => canonical_user(value, None),
+            "uri" => group(&value),
+            "emailAddress" => Grantee {
+                display_name: None,
+                email_address: Some(value),
+                id: None,
+                type_: Type::from_static(Type::AMAZON_CUSTOMER_BY_EMAIL),
+                uri: None,
+            },
+            key => return Err(s3_error!(InvalidArgument, "Invalid grantee type in grant header: {}", key)),
+        };
+        grants.push(grant(grantee, permission));
+    }
+    Ok(())
+}
+
+/// The `x-amz-grant-*` headers of a request
+pub(crate) struct GrantHeaders<'a> {
+    pub full_control: Option<&'a str>,
+    pub read: Option<&'a str>,
+    pub read_acp: Option<&'a str>,
+    pub write: Option<&'a str>,
+    pub write_acp: Option<&'a str>,
+}
+
+impl GrantHeaders<'_> {
+    fn headers(&self) -> [(Option<&str>, &'static str); 5] {
+        [
+            (self.full_control, Permission::FULL_CONTROL),
+            (self.read, Permission::READ),
+            (self.read_acp, Permission::READ_ACP),
+            (self.write, Permission::WRITE),
+            (self.write_acp, Permission::WRITE_ACP),
+        ]
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.headers().iter().all(|(value, _)| value.is_none())
+    }
+
+    /// Returns the grants of the headers, or `None` if there are no grant headers
+    pub(crate) fn grants(&self) -> S3Result<Option<Vec<Grant>>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let mut grants = Vec::new();
+        for (value, permission) in self.headers() {
+            if let Some(value) = value {
+                parse_grant_header(value, permission, &mut grants)?;
+            }
+        }
+        check_grants(&grants)?;
+        Ok(Some(grants))
+    }
+}
+
+/// Validates the grantees and the permissions of grants
+pub(crate) fn check_grants(grants: &[Grant]) -> S3Result<()> {
+    for grant in grants {
+        let (Some(grantee), Some(permission)) = (&grant.grantee, &grant.permission) else {
+            return Err(s3_error!(MalformedACLError, "Each grant must have a grantee and a permission"));
+        };
+        if PERMISSIONS.contains(&permission.as_str()).not() {
+            return Err(s3_error!(MalformedACLError, "Invalid permission: {}", permission.as_str()));
+        }
+        let is_valid = match grantee.type_.as_str() {
+            Type::CANONICAL_USER => grantee.id.as_deref().is_some_and(|id| id.is_empty().not()),
+            Type::GROUP => grantee.uri.as_deref().is_some_and(|uri| GROUPS.contains(&uri)),
+            Type::AMAZON_CUSTOMER_BY_EMAIL => grantee.email_address.is_some(),
+            _ => false,
+        };
+        if is_valid.not() {
+            return Err(s3_error!(InvalidArgument, "Invalid grantee: {}", grantee.type_.as_str()));
+        }
+    }
+    Ok(())
+}
+
+/// Resolves the grants of a request like `PutBucketAcl`, `CreateBucket`, `PutObjectAcl` or `PutObject`.
+///
+/// Returns `None` if the request has no ACL.
+pub(crate) fn requested_grants(
+    canned: Option<&str>,
+    headers: &GrantHeaders<'_>,
+    policy: Option<AccessControlPolicy>,
+    owner: Option<&Owner>,
+) -> S3Result<Option<Vec<Grant>>> {
+    let header_grants = headers.grants()?;
+    match (canned, header_grants, policy) {
+        (None, None, None) => Ok(None),
+        (Some(canned), None, None) => canned_grants(canned, owner).map(Some),
+        (None, Some(grants), None) => Ok(Some(grants)),
+        (None, None, Some(policy)) => {
+            let grants = policy.grants.unwrap_or_default();
+            check_grants(&grants)?;
+            Ok(Some(grants))
+        }
+        (Some(_), Some(_), _) => Err(s3_error!(InvalidRequest, "Specifying both Canned ACLs and Header Grants is not allowed")),
+        _ => Err(s3_error!(UnexpectedContent, "This request does not support content")),
+    }
+}
+
+impl FileSystem {
+    /// Returns the owner of a bucket, which is the requester if the bucket was created before the owner was recorded
+    pub(crate) async fn bucket_owner(&self, bucket: &str, credentials: Option<&Credentials>) -> Result<Option<Owner>> {
+        let config = self.load_bucket_config(bucket).await?.unwrap_or_default();
+        let owner = config.get("owner").and_then(serde_json::Value::as_str);
+        Ok(owner.or(credentials.map(|c| c.access_key.as_str())).map(owner_of))
+    }
+
+    /// Loads the ACL of a bucket, which is private by default
+    pub(crate) async fn load_bucket_acl(&self, bucket: &str, owner: Option<&Owner>) -> Result<AccessControlPolicy> {
+        let path = self.get_bucket_acl_path(bucket)?;
+        match load_json::<StoredAcl>(&*self.storage, &path).await? {
+            Some(acl) => Ok(acl.into_policy(owner)),
+            None => Ok(private_policy(owner)),
+        }
+    }
+
+    /// Saves the ACL of a bucket, and publishes the bucket if all users can read it
+    pub(crate) async fn save_bucket_acl(&self, bucket: &str, owner: Option<&Owner>, grants: Vec<Grant>) -> Result<()> {
+        let acl = StoredAcl::new(owner, grants);
+        let path = self.get_bucket_acl_path(bucket)?;
+        save_json(&*self.storage, &path, &acl).await?;
+
+        if let Some(ref public_buckets) = self.public_buckets {
+            if acl.is_public_read() {
+                public_buckets.insert(bucket);
+            } else {
+                public_buckets.remove(bucket);
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes the buckets whose stored ACLs allow all users to read
+    pub(crate) async fn publish_public_buckets(&self, public_buckets: &PublicBuckets) -> Result<()> {
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(file_name) = entry.file_name() else { continue };
+            let Some(encoded) = file_name.strip_prefix(".bucket-").and_then(|s| s.strip_suffix(".acl.json")) else {
+                continue;
+            };
+            let Ok(bucket) = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(encoded) else { continue };
+            let Ok(bucket) = String::from_utf8(bucket) else { continue };
+
+            let content = self.storage.read(&self.get_bucket_acl_path(&bucket)?).await?;
+            let acl: StoredAcl = serde_json::from_slice(&content)?;
+            if acl.is_public_read() && self.storage.exists(&self.get_bucket_path(&bucket)?).await {
+                public_buckets.insert(bucket);
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes the current objects whose stored ACLs allow all users to read
+    pub(crate) async fn publish_public_objects(&self, public_objects: &PublicObjects) -> Result<()> {
+        for sidecar in self.list_object_sidecars(None).await? {
+            if sidecar.entry.is_dir || sidecar.suffix != ".internal.json" {
+                continue;
+            }
+            let (bucket, key) = (sidecar.bucket, sidecar.key);
+
+            let content = self.storage.read(&sidecar.entry.path).await?;
+            let info: InternalInfo = serde_json::from_slice(&content)?;
+            let is_public = stored_object_acl(Some(&info)).is_some_and(|acl| acl.is_public_read());
+            let object_path = self.get_object_path(&bucket, &key)?;
+            if is_public && self.storage.is_file(&object_path).await && self.bucket_acls_enabled(&bucket).await? {
+                public_objects.insert(bucket, key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes or withdraws the current version of an object after it is written, deleted or changes its ACL
+    pub(crate) async fn refresh_public_object(&self, bucket: &str, key: &str) -> Result<()> {
+        let Some(ref public_objects) = self.public_objects else { return Ok(()) };
+        let object_path = self.get_object_path(bucket, key)?;
+        let is_public = if self.storage.is_file(&object_path).await && self.bucket_acls_enabled(bucket).await? {
+            let info = self.load_internal_info(bucket, key).await?;
+            stored_object_acl(info.as_ref()).is_some_and(|acl| acl.is_public_read())
+        } else {
+            false
+        };
+        if is_public {
+            public_objects.insert(bucket, key);
+        } else {
+            public_objects.remove(bucket, key);
+        }
+        Ok(())
+    }
+}
+
+fn stored_object_acl(info: Option<&InternalInfo>) -> Option<StoredAcl> {
+    let acl = info?.get("acl")?;
+    serde_json::from_value(acl.clone()).ok()
+}
+
+/// Loads the ACL of an object from its internal info, which is private by default
+pub(crate) fn object_acl(info: Option<&InternalInfo>, owner: Option<&Owner>) -> AccessControlPolicy {
+    match stored_object_acl(info) {
+        Some(acl) => acl.into_policy(owner),
+        None => private_policy(owner),
+    }
+}
+
+/// Stores the ACL of an object in its internal info
+pub(crate) fn set_object_acl(info: &mut InternalInfo, owner: Option<&Owner>, grants: Vec<Grant>) -> Result<()> {
+    let acl = serde_json::to_value(StoredAcl::new(owner, grants))?;
+    info.insert("acl".to_owned(), acl);
+    Ok(())
+}
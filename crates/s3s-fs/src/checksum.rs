@@ -1,6 +1,11 @@
+use s3s::dto::{Checksum, ChecksumAlgorithm};
+use s3s::{s3_error, S3Result, TrailingHeaders};
+
 use std::hash::Hasher;
+use std::ops::Not;
 
 use digest::Digest;
+use hyper::HeaderMap;
 use numeric_cast::TruncatingCast;
 use rust_utils::default::default;
 
@@ -15,6 +20,35 @@ pub struct ChecksumCalculator {
 }
 
 impl ChecksumCalculator {
+    /// Creates a calculator for the algorithm requested by `x-amz-sdk-checksum-algorithm`
+    /// and for every checksum provided by the request.
+    pub fn new(algorithm: Option<&str>, expected: &Checksum) -> S3Result<Self> {
+        let mut this: Self = default();
+        if let Some(algorithm) = algorithm {
+            if this.enable(algorithm).not() {
+                return Err(s3_error!(InvalidRequest, "Unsupported checksum algorithm: {}", algorithm));
+            }
+        }
+        for algorithm in ALGORITHMS {
+            if get(expected, algorithm).is_some() {
+                this.enable(algorithm);
+            }
+        }
+        Ok(this)
+    }
+
+    /// Returns `false` if the algorithm is unknown
+    fn enable(&mut self, algorithm: &str) -> bool {
+        match algorithm {
+            ChecksumAlgorithm::CRC32 => self.crc32 = Some(default()),
+            ChecksumAlgorithm::CRC32C => self.crc32c = Some(default()),
+            ChecksumAlgorithm::SHA1 => self.sha1 = Some(default()),
+            ChecksumAlgorithm::SHA256 => self.sha256 = Some(default()),
+            _ => return false,
+        }
+        true
+    }
+
     pub fn update(&mut self, data: &[u8]) {
         if let Some(crc32) = &mut self.crc32 {
             crc32.update(data);
@@ -52,6 +86,100 @@ impl ChecksumCalculator {
     }
 }
 
+const ALGORITHMS: [&str; 4] = [
+    ChecksumAlgorithm::CRC32,
+    ChecksumAlgorithm::CRC32C,
+    ChecksumAlgorithm::SHA1,
+    ChecksumAlgorithm::SHA256,
+];
+
+/// Returns the checksum value of an algorithm
+pub fn get<'a>(checksum: &'a Checksum, algorithm: &str) -> Option<&'a str> {
+    let value = match algorithm {
+        ChecksumAlgorithm::CRC32 => &checksum.checksum_crc32,
+        ChecksumAlgorithm::CRC32C => &checksum.checksum_crc32c,
+        ChecksumAlgorithm::SHA1 => &checksum.checksum_sha1,
+        ChecksumAlgorithm::SHA256 => &checksum.checksum_sha256,
+        _ => return None,
+    };
+    value.as_deref()
+}
+
+pub fn set(checksum: &mut Checksum, algorithm: &str, value: String) {
+    match algorithm {
+        ChecksumAlgorithm::CRC32 => checksum.checksum_crc32 = Some(value),
+        ChecksumAlgorithm::CRC32C => checksum.checksum_crc32c = Some(value),
+        ChecksumAlgorithm::SHA1 => checksum.checksum_sha1 = Some(value),
+        ChecksumAlgorithm::SHA256 => checksum.checksum_sha256 = Some(value),
+        _ => {}
+    }
+}
+
+/// Compares the calculated checksums with the checksums provided by a request
+pub fn check(calculated: &Checksum, expected: &Checksum) -> S3Result<()> {
+    for algorithm in ALGORITHMS {
+        if let Some(expected) = get(expected, algorithm) {
+            if get(calculated, algorithm) != Some(expected) {
+                return Err(s3_error!(
+                    BadDigest,
+                    "The {} you specified did not match the calculated checksum.",
+                    algorithm
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the algorithm of the only checksum provided by a request
+pub fn provided_algorithm(checksum: &Checksum) -> Option<&'static str> {
+    ALGORITHMS.into_iter().find(|algorithm| get(checksum, algorithm).is_some())
+}
+
+fn header_name(algorithm: &str) -> String {
+    format!("x-amz-checksum-{}", algorithm.to_ascii_lowercase())
+}
+
+/// Returns the algorithm of the trailing checksum declared by `x-amz-trailer`
+pub fn trailer_algorithm(headers: &HeaderMap) -> Option<&'static str> {
+    let trailer = headers.get("x-amz-trailer")?.to_str().ok()?;
+    let mut names = trailer.split(',').map(str::trim);
+    names.find_map(|name| {
+        ALGORITHMS
+            .into_iter()
+            .find(|algorithm| name.eq_ignore_ascii_case(&header_name(algorithm)))
+    })
+}
+
+/// Fills the expected checksums with the trailing checksums of a body which has been read to the end
+pub fn merge_trailing(expected: &mut Checksum, trailing_headers: Option<&TrailingHeaders>) {
+    let Some(headers) = trailing_headers.and_then(TrailingHeaders::take) else { return };
+    for algorithm in ALGORITHMS {
+        if let Some(value) = headers.get(header_name(algorithm)).and_then(|v| v.to_str().ok()) {
+            set(expected, algorithm, value.to_owned());
+        }
+    }
+}
+
+/// Calculates the checksum of a multipart object, which is the checksum of the part checksums
+/// followed by the number of parts.
+///
+/// See <https://docs.aws.amazon.com/AmazonS3/latest/userguide/checking-object-integrity.html#large-object-checksums>
+pub fn composite<'a>(algorithm: &str, part_checksums: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let mut calculator: ChecksumCalculator = default();
+    if calculator.enable(algorithm).not() {
+        return None;
+    }
+    let mut parts: usize = 0;
+    for part_checksum in part_checksums {
+        let raw = base64_simd::STANDARD.decode_to_vec(part_checksum).ok()?;
+        calculator.update(&raw);
+        parts += 1;
+    }
+    let value = get(&calculator.finalize(), algorithm)?.to_owned();
+    Some(format!("{value}-{parts}"))
+}
+
 fn base64(input: &[u8]) -> String {
     base64_simd::STANDARD.encode_to_string(input)
 }
@@ -0,0 +1,100 @@
+use s3s::dto::Timestamp;
+use s3s::{s3_error, S3Error, S3ErrorCode, S3Result};
+
+use std::ops::Not;
+use std::time::SystemTime;
+
+use hyper::StatusCode;
+use time::OffsetDateTime;
+
+/// Conditional headers of a read request (`GetObject`, `HeadObject`)
+#[derive(Debug, Default)]
+#[allow(clippy::struct_field_names)] // named after the headers
+pub(crate) struct ReadConditions<'a> {
+    pub(crate) if_match: Option<&'a str>,
+    pub(crate) if_none_match: Option<&'a str>,
+    pub(crate) if_modified_since: Option<&'a Timestamp>,
+    pub(crate) if_unmodified_since: Option<&'a Timestamp>,
+}
+
+impl ReadConditions<'_> {
+    /// Evaluates the conditions against the current state of an object.
+    ///
+    /// See <https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2>
+    pub(crate) fn check(&self, e_tag: &str, last_modified: SystemTime) -> S3Result<()> {
+        let last_modified = unix_seconds(last_modified.into());
+
+        if let Some(if_match) = self.if_match {
+            if e_tag_list_matches(if_match, e_tag).not() {
+                return Err(precondition_failed("If-Match"));
+            }
+        } else if let Some(t) = self.if_unmodified_since {
+            if last_modified > unix_seconds(t.clone().into()) {
+                return Err(precondition_failed("If-Unmodified-Since"));
+            }
+        }
+
+        if let Some(if_none_match) = self.if_none_match {
+            if e_tag_list_matches(if_none_match, e_tag) {
+                return Err(not_modified());
+            }
+        } else if let Some(t) = self.if_modified_since {
+            if last_modified <= unix_seconds(t.clone().into()) {
+                return Err(not_modified());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Evaluates `If-None-Match` of a write request. Only `*` is supported.
+pub(crate) fn check_if_none_match_write(if_none_match: Option<&str>, exists: bool) -> S3Result<()> {
+    let Some(if_none_match) = if_none_match else { return Ok(()) };
+    if if_none_match.trim() != "*" {
+        return Err(s3_error!(NotImplemented, "If-None-Match only supports the '*' value for write requests"));
+    }
+    if exists {
+        return Err(precondition_failed("If-None-Match"));
+    }
+    Ok(())
+}
+
+/// Evaluates `If-Match` of a write request against the entity tag of the current object
+pub(crate) fn check_if_match_write(if_match: Option<&str>, e_tag: &str) -> S3Result<()> {
+    match if_match {
+        Some(if_match) if e_tag_list_matches(if_match, e_tag).not() => Err(precondition_failed("If-Match")),
+        _ => Ok(()),
+    }
+}
+
+/// Matches a comma-separated list of entity tags, or `*`.
+///
+/// Unquoted tags are accepted because some clients do not quote them.
+fn e_tag_list_matches(header: &str, e_tag: &str) -> bool {
+    let e_tag = trim_quotes(e_tag);
+    header.split(',').map(str::trim).any(|x| x == "*" || trim_quotes(x) == e_tag)
+}
+
+fn trim_quotes(s: &str) -> &str {
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s)
+}
+
+/// HTTP dates have second precision
+fn unix_seconds(t: OffsetDateTime) -> i64 {
+    t.unix_timestamp()
+}
+
+fn precondition_failed(header: &str) -> S3Error {
+    s3_error!(
+        PreconditionFailed,
+        "At least one of the pre-conditions you specified did not hold: {}",
+        header
+    )
+}
+
+fn not_modified() -> S3Error {
+    let mut err = S3Error::with_message(S3ErrorCode::Custom("NotModified".into()), "Not Modified");
+    err.set_status_code(StatusCode::NOT_MODIFIED);
+    err
+}
@@ -0,0 +1,118 @@
+//! Bucket CORS configurations, stored as a sidecar file
+//!
+//! The rules are evaluated by the request router of `s3s`, which fetches them with `GetBucketCors`.
+
+use crate::error::*;
+use crate::fs::{load_json, save_json, FileSystem};
+
+use s3s::dto::{CORSConfiguration, CORSRule};
+use s3s::{s3_error, S3Result};
+
+use std::io;
+use std::ops::Not;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_RULES: usize = 100;
+
+const METHODS: &[&str] = &["GET", "PUT", "POST", "DELETE", "HEAD"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRule {
+    id: Option<String>,
+    allowed_headers: Option<Vec<String>>,
+    allowed_methods: Vec<String>,
+    allowed_origins: Vec<String>,
+    expose_headers: Option<Vec<String>>,
+    max_age_seconds: i32,
+}
+
+/// Validates the rules of a CORS configuration.
+pub(crate) fn check_cors_configuration(config: &CORSConfiguration) -> S3Result<()> {
+    let rules = &config.cors_rules;
+    if rules.is_empty() {
+        return Err(s3_error!(MalformedXML, "The CORS configuration must contain at least one rule"));
+    }
+    if rules.len() > MAX_RULES {
+        return Err(s3_error!(
+            MalformedXML,
+            "The CORS configuration must not contain more than {} rules",
+            MAX_RULES
+        ));
+    }
+    for rule in rules {
+        if rule.allowed_methods.is_empty() || rule.allowed_origins.is_empty() {
+            return Err(s3_error!(MalformedXML, "Each CORS rule must have an AllowedMethod and an AllowedOrigin"));
+        }
+        if let Some(method) = rule.allowed_methods.iter().find(|m| METHODS.contains(&m.as_str()).not()) {
+            return Err(s3_error!(
+                InvalidRequest,
+                "Found unsupported HTTP method in CORS config. Unsupported method is {}",
+                method
+            ));
+        }
+        if let Some(origin) = rule.allowed_origins.iter().find(|o| o.matches('*').count() > 1) {
+            return Err(s3_error!(
+                InvalidRequest,
+                "AllowedOrigin '{}' can not have more than one wildcard.",
+                origin
+            ));
+        }
+        let allowed_headers = rule.allowed_headers.as_deref().unwrap_or_default();
+        if let Some(header) = allowed_headers.iter().find(|h| h.matches('*').count() > 1) {
+            return Err(s3_error!(
+                InvalidRequest,
+                "AllowedHeader '{}' can not have more than one wildcard.",
+                header
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl FileSystem {
+    pub(crate) async fn load_bucket_cors(&self, bucket: &str) -> Result<Option<Vec<CORSRule>>> {
+        let path = self.get_bucket_cors_path(bucket)?;
+        let Some(rules) = load_json::<Vec<StoredRule>>(&*self.storage, &path).await? else {
+            return Ok(None);
+        };
+        let rules = rules
+            .into_iter()
+            .map(|r| CORSRule {
+                id: r.id,
+                allowed_headers: r.allowed_headers,
+                allowed_methods: r.allowed_methods,
+                allowed_origins: r.allowed_origins,
+                expose_headers: r.expose_headers,
+                max_age_seconds: r.max_age_seconds,
+            })
+            .collect();
+        Ok(Some(rules))
+    }
+
+    pub(crate) async fn save_bucket_cors(&self, bucket: &str, config: CORSConfiguration) -> Result<()> {
+        let rules: Vec<StoredRule> = config
+            .cors_rules
+            .into_iter()
+            .map(|r| StoredRule {
+                id: r.id,
+                allowed_headers: r.allowed_headers,
+                allowed_methods: r.allowed_methods,
+                allowed_origins: r.allowed_origins,
+                expose_headers: r.expose_headers,
+                max_age_seconds: r.max_age_seconds,
+            })
+            .collect();
+        let path = self.get_bucket_cors_path(bucket)?;
+        save_json(&*self.storage, &path, &rules).await
+    }
+
+    pub(crate) async fn remove_bucket_cors(&self, bucket: &str) -> Result<()> {
+        let path = self.get_bucket_cors_path(bucket)?;
+        match self.storage.remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Dry runs of mutating requests
+//!
+//! A dry run goes through the same validation as the request itself, such as authentication, policies,
+//! the existence of the bucket, conditional headers, object locks and the checks of parts,
+//! and stops before anything is written. Its response is the one of the request, which describes what would have happened.
+//! Operations which do not support dry runs reject them, so that a dry run never modifies anything.
+
+use crate::fs::FileSystem;
+
+use s3s::{s3_error, S3Response, S3Result};
+
+use std::ops::Not;
+
+use hyper::header::HeaderValue;
+use hyper::HeaderMap;
+
+/// The header of dry runs, whose value is `true`.
+///
+/// `CreateBucket`, `DeleteBucket`, `PutObject`, `CopyObject`, `DeleteObject`, `UploadPart`
+/// and `CompleteMultipartUpload` support dry runs, which must be enabled by [`FileSystem::set_dry_runs`].
+pub const DRY_RUN_HEADER: &str = "x-s3s-dry-run";
+
+fn has_dry_run_header(headers: &HeaderMap) -> bool {
+    headers
+        .get(DRY_RUN_HEADER)
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"true"))
+}
+
+impl FileSystem {
+    /// Whether a request which supports dry runs is one
+    pub(crate) fn is_dry_run(&self, headers: &HeaderMap) -> S3Result<bool> {
+        if has_dry_run_header(headers).not() {
+            return Ok(false);
+        }
+        if self.dry_runs.not() {
+            return Err(s3_error!(InvalidRequest, "Dry runs are not enabled"));
+        }
+        Ok(true)
+    }
+}
+
+/// Rejects a dry run of an operation which does not support dry runs.
+pub(crate) fn reject_dry_run(headers: &HeaderMap) -> S3Result<()> {
+    if has_dry_run_header(headers) {
+        return Err(s3_error!(NotImplemented, "This operation does not support dry runs"));
+    }
+    Ok(())
+}
+
+/// The response of a dry run
+pub(crate) fn dry_run_response<T>(output: T) -> S3Response<T> {
+    let mut resp = S3Response::new(output);
+    resp.headers.insert(DRY_RUN_HEADER, HeaderValue::from_static("true"));
+    resp
+}
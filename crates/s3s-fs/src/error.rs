@@ -38,9 +38,24 @@ where
     }
 }
 
+impl Error {
+    /// Creates an error which is caused by the request, such as an invalid object key.
+    ///
+    /// It is not logged and keeps its code when converted to an [`S3Error`].
+    pub(crate) fn request(code: S3ErrorCode, message: &'static str) -> Self {
+        Self {
+            source: Box::new(S3Error::with_message(code, message)),
+        }
+    }
+}
+
 impl From<Error> for S3Error {
     fn from(e: Error) -> Self {
-        S3Error::with_source(S3ErrorCode::InternalError, e.source)
+        // request errors, including the errors of request bodies
+        match e.source.downcast::<S3Error>() {
+            Ok(e) => *e,
+            Err(source) => S3Error::with_source(S3ErrorCode::InternalError, source),
+        }
     }
 }
 
@@ -1,127 +1,1299 @@
 use crate::error::*;
-use crate::utils::hex;
+use crate::key_lock::KeyLocks;
+use crate::key_mapper::{DefaultKeyMapper, KeyMapper};
+use crate::listing::ListingOrder;
+use crate::storage::{BlockingStorage, DirEntry, Storage, StorageFile, TokioStorage};
+use crate::tagging::TagMap;
+use crate::utils::{hex, modified_time};
 
-use s3s::auth::Credentials;
+use s3s::auth::{Credentials, PublicBuckets, PublicObjects};
 use s3s::dto;
+use s3s::S3ErrorCode;
 
+use std::collections::{BTreeMap, HashSet};
 use std::env;
+use std::io;
 use std::ops::Not;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use tokio::fs;
-use tokio::fs::File;
-use tokio::io::{AsyncReadExt, BufWriter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use tokio::sync::Mutex;
 
+use futures::executor::block_on;
 use md5::{Digest, Md5};
 use path_absolutize::Absolutize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug)]
+/// A file system which stores buckets as directories and objects as files.
+///
+/// Clones share the state of in-progress writes, so that a clone can run background tasks
+/// like [`FileSystem::spawn_lifecycle_sweeper`].
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent options
 pub struct FileSystem {
     pub(crate) root: PathBuf,
-    tmp_file_counter: AtomicU64,
+    /// The directory of sidecar files, which is the root unless a metadata directory is enabled
+    pub(crate) meta_root: PathBuf,
+    pub(crate) region: String,
+    durable: bool,
+    pub(crate) read_only: bool,
+    /// The directory of temporary object files, which is the metadata directory if not set
+    tmp_dir: Option<PathBuf>,
+    tmp_file_counter: Arc<AtomicU64>,
+    upload_info_lock: Arc<Mutex<()>>,
+    /// Serializes the mutations of each object with each other and with its reads,
+    /// so that the data and the sidecar files of an object are always from the same write
+    pub(crate) key_locks: Arc<KeyLocks>,
+    pub(crate) min_part_size: u64,
+    pub(crate) max_object_size: Option<u64>,
+    pub(crate) website_redirects: bool,
+    pub(crate) dry_runs: bool,
+    pub(crate) guess_content_types: bool,
+    /// The buckets which can be read anonymously, which are updated by the bucket ACLs
+    pub(crate) public_buckets: Option<PublicBuckets>,
+    /// The objects which can be read anonymously, which are updated by the object ACLs
+    pub(crate) public_objects: Option<PublicObjects>,
+    /// The object ownership of new buckets which do not specify one
+    pub(crate) default_object_ownership: Option<String>,
+    pub(crate) key_mapper: Arc<dyn KeyMapper>,
+    pub(crate) storage: Arc<dyn Storage>,
+    pub(crate) read_buffer_size: usize,
+    pub(crate) hash_buffer_size: usize,
+    pub(crate) part_verify_concurrency: usize,
+    pub(crate) max_keys: i32,
+    max_key_length: usize,
+    pub(crate) listing_order: ListingOrder,
+    pub(crate) reverse_listings: bool,
 }
 
+/// The metadata directory under the root, see [`FileSystem::enable_meta_dir`]
+const META_DIR: &str = ".s3s-meta";
+
+/// The file of an object whose key ends with `/`, such as the folders of the AWS console,
+/// which is stored in the directory of the key so that the directory can still contain other objects
+pub(crate) const DIRECTORY_MARKER: &str = ".s3s-directory-marker";
+
+/// The region of buckets created without a location constraint
+pub(crate) const DEFAULT_REGION: &str = "us-east-1";
+
+/// The size of the chunks of object reads, see [`FileSystem::set_read_buffer_size`]
+const DEFAULT_READ_BUFFER_SIZE: usize = 256 * 1024;
+
+/// The size of the chunks of entity tag computations, see [`FileSystem::set_hash_buffer_size`]
+const DEFAULT_HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The number of parts verified at once, see [`FileSystem::set_part_verify_concurrency`]
+const DEFAULT_PART_VERIFY_CONCURRENCY: usize = 8;
+
+/// The maximum length of a file name in bytes, which is the limit of most file systems
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// The maximum length of a key in bytes, which is 1024 like AWS
+const DEFAULT_MAX_KEY_LENGTH: usize = 1024;
+
+/// The number of keys of a listing page if the request does not specify one, which is 1000 like AWS
+pub(crate) const DEFAULT_MAX_KEYS: i32 = 1000;
+
+/// The minimum size of each part of a multipart upload except the last one, which is 5 MiB like AWS
+const DEFAULT_MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
 pub(crate) type InternalInfo = serde_json::Map<String, serde_json::Value>;
 
-fn clean_old_tmp_files(root: &Path) -> std::io::Result<()> {
-    let entries = match std::fs::read_dir(root) {
+pub(crate) type BucketConfig = serde_json::Map<String, serde_json::Value>;
+
+/// The record of an in-progress multipart upload
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct UploadInfo {
+    pub(crate) access_key: Option<String>,
+    #[serde(default)]
+    pub(crate) bucket: String,
+    #[serde(default)]
+    pub(crate) key: String,
+    pub(crate) initiated: Option<SystemTime>,
+    #[serde(default)]
+    pub(crate) storage_class: Option<String>,
+    #[serde(default)]
+    pub(crate) checksum_algorithm: Option<String>,
+    #[serde(default)]
+    pub(crate) metadata: Option<dto::Metadata>,
+    #[serde(default)]
+    pub(crate) content_type: Option<String>,
+    #[serde(default)]
+    pub(crate) content_disposition: Option<String>,
+    #[serde(default)]
+    pub(crate) content_encoding: Option<String>,
+    #[serde(default)]
+    pub(crate) website_redirect_location: Option<String>,
+    #[serde(default)]
+    pub(crate) parts: BTreeMap<i32, PartInfo>,
+}
+
+/// An uploaded part of a multipart upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PartInfo {
+    pub(crate) size: u64,
+    pub(crate) e_tag: String,
+    pub(crate) last_modified: SystemTime,
+    /// The checksum of the algorithm of the upload
+    #[serde(default)]
+    pub(crate) checksum: Option<String>,
+}
+
+/// A part of a completed multipart object, which is recorded in the internal info
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ObjectPartInfo {
+    pub(crate) part_number: i32,
+    pub(crate) size: u64,
+    /// The checksum of the algorithm of the upload
+    pub(crate) checksum: Option<String>,
+}
+
+async fn clean_old_tmp_files(storage: &dyn Storage, root: &Path) -> std::io::Result<()> {
+    let entries = match storage.read_dir(root).await {
         Ok(entries) => Ok(entries),
         Err(ref io_err) if io_err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
         Err(io_err) => Err(io_err),
     }?;
+    let mut dirs = Vec::new();
     for entry in entries {
-        let entry = entry?;
-        let file_name = entry.file_name();
-        let Some(file_name) = file_name.to_str() else { continue };
+        let Some(file_name) = entry.file_name() else { continue };
+        if entry.is_dir {
+            // See `object_sidecar_path`
+            if file_name.starts_with(".bucket-") && file_name.ends_with(".object-") {
+                dirs.push(entry.path);
+            }
+            continue;
+        }
         // See `FileSystem::write_file`
-        if file_name.starts_with(".tmp.") && file_name.ends_with(".internal.part") {
-            std::fs::remove_file(entry.path())?;
+        if is_tmp_file_name(file_name) {
+            storage.remove_file(&entry.path).await?;
+        }
+    }
+    while let Some(dir) = dirs.pop() {
+        for entry in storage.read_dir(&dir).await? {
+            let Some(file_name) = entry.file_name() else { continue };
+            if entry.is_dir {
+                if file_name.contains('.').not() {
+                    dirs.push(entry.path);
+                }
+            } else if is_tmp_file_name(file_name) {
+                storage.remove_file(&entry.path).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_tmp_file_name(file_name: &str) -> bool {
+    file_name.starts_with(".tmp.") && file_name.ends_with(".internal.part")
+}
+
+/// The suffixes of the sidecar files of current objects, see `FileSystem::get_metadata_path`
+const OBJECT_SIDECAR_SUFFIXES: &[&str] = &[".metadata.json", ".internal.json", ".tagging.json"];
+
+/// The length of the parts of an encoded key in the directories of long sidecar names, see `object_sidecar_path`
+const SIDECAR_DIR_NAME_LEN: usize = 200;
+
+/// Returns the length of the longest name of the sidecar files of an object, see `FileSystem::get_metadata_path`
+fn sidecar_name_len(bucket: &str, key: &str) -> usize {
+    let encoded_len = |s: &str| (s.len() * 4).div_ceil(3);
+    let suffix_len = OBJECT_SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| suffix.len())
+        .max()
+        .unwrap_or_default();
+    ".bucket-".len() + encoded_len(bucket) + ".object-".len() + encoded_len(key) + suffix_len
+}
+
+/// Returns the path of a sidecar file or directory of an object, relative to the metadata directory.
+///
+/// The name holds the bucket and the key in base64, followed by `suffix`.
+/// If the names of the sidecar files of the object would exceed the length of a file name,
+/// the encoded key is split into nested directories under `.bucket-{bucket}.object-`
+/// and the last part is followed by `suffix`, so that the length of keys is not limited by file names.
+/// Base64 has no `.`, so that the directories can not collide with the names of sidecar files.
+pub(crate) fn object_sidecar_path(bucket: &str, key: &str, suffix: &str) -> PathBuf {
+    let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+    let long = sidecar_name_len(bucket, key) > MAX_FILE_NAME_LEN;
+    let (bucket, key) = (encode(bucket), encode(key));
+    if long.not() {
+        return PathBuf::from(format!(".bucket-{bucket}.object-{key}{suffix}"));
+    }
+    let mut path = PathBuf::from(format!(".bucket-{bucket}.object-"));
+    let mut rest = key.as_str();
+    while rest.len() > SIDECAR_DIR_NAME_LEN {
+        let (part, tail) = rest.split_at(SIDECAR_DIR_NAME_LEN);
+        path.push(part);
+        rest = tail;
+    }
+    path.push(format!("{rest}{suffix}"));
+    path
+}
+
+/// A sidecar file or directory of an object, see `object_sidecar_path`
+pub(crate) struct ObjectSidecar {
+    pub(crate) entry: DirEntry,
+    pub(crate) bucket: String,
+    pub(crate) key: String,
+    /// The suffix of the name, for example `.metadata.json`
+    pub(crate) suffix: String,
+}
+
+impl ObjectSidecar {
+    /// Decodes the bucket and the key of a sidecar, returns `None` if they are not valid.
+    fn parse(entry: DirEntry, bucket: &str, key: &str, suffix: &str) -> Option<Self> {
+        let decode = |s: &str| {
+            let bytes = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(s).ok()?;
+            String::from_utf8(bytes).ok()
+        };
+        Some(Self {
+            entry,
+            bucket: decode(bucket)?,
+            key: decode(key)?,
+            suffix: suffix.to_owned(),
+        })
+    }
+}
+
+/// Checks a bucket name before it is used as a path.
+fn check_bucket(bucket: &str) -> Result<()> {
+    if s3s::path::check_bucket_name(bucket).not() {
+        return Err(Error::request(S3ErrorCode::InvalidBucketName, "The specified bucket is not valid."));
+    }
+    Ok(())
+}
+
+/// Checks a key before it is used as a path.
+///
+/// Keys are relative paths under the bucket, so that they can not escape from the bucket,
+/// and each key maps to a distinct path, unless the key mapper stores any key, see [`KeyMapper::stores_any_key`].
+/// Keys are always valid UTF-8, which s3s checks when it decodes them.
+fn check_key(key: &str, max_len: usize, stores_any_key: bool) -> Result<()> {
+    if s3s::path::check_key(key).not() || key.len() > max_len {
+        return Err(Error::request(S3ErrorCode::KeyTooLongError, "Your key is too long"));
+    }
+    if stores_any_key {
+        return Ok(());
+    }
+    if key.starts_with('/') || key.split('/').any(|segment| segment == "." || segment == "..") {
+        return Err(Error::request(
+            S3ErrorCode::InvalidArgument,
+            "Object key must not be an absolute path or contain '.' or '..' segments",
+        ));
+    }
+    if key.rsplit('/').next() == Some(DIRECTORY_MARKER) {
+        return Err(Error::request(
+            S3ErrorCode::InvalidArgument,
+            "Object key must not end with the name of directory markers",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns whether a character can not be stored in a file name of the target file system.
+fn is_illegal_in_file_name(c: char) -> bool {
+    if cfg!(windows) {
+        c.is_ascii_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*' | '\\')
+    } else {
+        c == '\0'
+    }
+}
+
+/// Checks the file names of the path of an object, which is mapped from its key,
+/// so that a key which the file system can not store is rejected instead of failing with an IO error.
+///
+/// A [`KeyMapper`] which escapes the file names of keys can store any key.
+fn check_file_names(path: &Path) -> Result<()> {
+    for component in path.components() {
+        let Component::Normal(name) = component else { continue };
+        if name.len() > MAX_FILE_NAME_LEN {
+            return Err(Error::request(
+                S3ErrorCode::KeyTooLongError,
+                "Each segment of the key must be at most 255 bytes",
+            ));
+        }
+        if name.to_string_lossy().chars().any(is_illegal_in_file_name) {
+            return Err(Error::request(
+                S3ErrorCode::InvalidArgument,
+                "Object key contains characters which the file system can not store",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// load a json sidecar file, returns `None` if the file does not exist
+pub(crate) async fn load_json<T: DeserializeOwned>(storage: &dyn Storage, path: &Path) -> Result<Option<T>> {
+    if storage.exists(path).await.not() {
+        return Ok(None);
+    }
+    let content = storage.read(path).await?;
+    let value = serde_json::from_slice(&content)?;
+    Ok(Some(value))
+}
+
+/// save a json sidecar file
+pub(crate) async fn save_json<T: Serialize + ?Sized>(storage: &dyn Storage, path: &Path, value: &T) -> Result<()> {
+    let content = serde_json::to_vec(value)?;
+    write_atomic(storage, path, &content).await
+}
+
+/// Writes a file atomically, so that a crash never leaves a truncated file behind.
+///
+/// The content is written to a temporary file in the same directory, which then replaces the previous file.
+/// The directory is created if it does not exist, see `object_sidecar_path`.
+/// See `clean_old_tmp_files` for the temporary files left by a crash.
+pub(crate) async fn write_atomic(storage: &dyn Storage, path: &Path, content: &[u8]) -> Result<()> {
+    let tmp_path = path.with_file_name(format!(".tmp.{}.internal.part", Uuid::new_v4()));
+    let mut result = storage.write(&tmp_path, content).await;
+    if let (Err(err), Some(dir)) = (&result, path.parent()) {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            storage.create_dir_all(dir).await?;
+            result = storage.write(&tmp_path, content).await;
         }
     }
+    let result = match result {
+        Ok(()) => storage.rename(&tmp_path, path).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        let _ = storage.remove_file(&tmp_path).await;
+        return Err(err.into());
+    }
     Ok(())
 }
 
+/// A builder of [`FileSystem`]
+#[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)] // independent options
+pub struct FileSystemBuilder {
+    root: PathBuf,
+    region: String,
+    durable: bool,
+    read_only: bool,
+    meta_dir: bool,
+    tmp_dir: Option<PathBuf>,
+    min_part_size: u64,
+    max_object_size: Option<u64>,
+    website_redirects: bool,
+    dry_runs: bool,
+    guess_content_types: bool,
+    public_buckets: Option<PublicBuckets>,
+    public_objects: Option<PublicObjects>,
+    default_object_ownership: Option<String>,
+    key_mapper: Option<Arc<dyn KeyMapper>>,
+    read_buffer_size: usize,
+    hash_buffer_size: usize,
+    part_verify_concurrency: usize,
+    max_keys: i32,
+    max_key_length: usize,
+    listing_order: ListingOrder,
+    reverse_listings: bool,
+}
+
+impl FileSystemBuilder {
+    /// Creates a builder of a file system which serves `root` with the default options.
+    #[must_use]
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_owned(),
+            region: DEFAULT_REGION.to_owned(),
+            durable: false,
+            read_only: false,
+            meta_dir: false,
+            tmp_dir: None,
+            min_part_size: DEFAULT_MIN_PART_SIZE,
+            max_object_size: None,
+            website_redirects: false,
+            dry_runs: false,
+            guess_content_types: false,
+            public_buckets: None,
+            public_objects: None,
+            default_object_ownership: None,
+            key_mapper: None,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            hash_buffer_size: DEFAULT_HASH_BUFFER_SIZE,
+            part_verify_concurrency: DEFAULT_PART_VERIFY_CONCURRENCY,
+            max_keys: DEFAULT_MAX_KEYS,
+            max_key_length: DEFAULT_MAX_KEY_LENGTH,
+            listing_order: ListingOrder::Key,
+            reverse_listings: false,
+        }
+    }
+
+    /// See [`FileSystem::set_region`]
+    #[must_use]
+    pub fn region(mut self, region: impl Into<String>) -> Self {
+        self.region = region.into();
+        self
+    }
+
+    /// See [`FileSystem::set_durable`]
+    #[must_use]
+    pub fn durable(mut self, durable: bool) -> Self {
+        self.durable = durable;
+        self
+    }
+
+    /// Serves the root without modifying it, see [`FileSystem::new_read_only`]
+    #[must_use]
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// See [`FileSystem::enable_meta_dir`]
+    #[must_use]
+    pub fn meta_dir(mut self, enabled: bool) -> Self {
+        self.meta_dir = enabled;
+        self
+    }
+
+    /// See [`FileSystem::set_tmp_dir`]
+    #[must_use]
+    pub fn tmp_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.tmp_dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// See [`FileSystem::set_min_part_size`]
+    #[must_use]
+    pub fn min_part_size(mut self, size: u64) -> Self {
+        self.min_part_size = size;
+        self
+    }
+
+    /// See [`FileSystem::set_max_object_size`]
+    #[must_use]
+    pub fn max_object_size(mut self, size: u64) -> Self {
+        self.max_object_size = Some(size);
+        self
+    }
+
+    /// See [`FileSystem::set_website_redirects`]
+    #[must_use]
+    pub fn website_redirects(mut self, enabled: bool) -> Self {
+        self.website_redirects = enabled;
+        self
+    }
+
+    /// See [`FileSystem::set_dry_runs`]
+    #[must_use]
+    pub fn dry_runs(mut self, enabled: bool) -> Self {
+        self.dry_runs = enabled;
+        self
+    }
+
+    /// See [`FileSystem::set_guess_content_types`]
+    #[must_use]
+    pub fn guess_content_types(mut self, enabled: bool) -> Self {
+        self.guess_content_types = enabled;
+        self
+    }
+
+    /// See [`FileSystem::set_public_buckets`]
+    #[must_use]
+    pub fn public_buckets(mut self, buckets: PublicBuckets) -> Self {
+        self.public_buckets = Some(buckets);
+        self
+    }
+
+    /// See [`FileSystem::set_public_objects`]
+    #[must_use]
+    pub fn public_objects(mut self, objects: PublicObjects) -> Self {
+        self.public_objects = Some(objects);
+        self
+    }
+
+    /// See [`FileSystem::set_default_object_ownership`]
+    #[must_use]
+    pub fn default_object_ownership(mut self, ownership: impl Into<String>) -> Self {
+        self.default_object_ownership = Some(ownership.into());
+        self
+    }
+
+    /// See [`FileSystem::set_read_buffer_size`]
+    #[must_use]
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size;
+        self
+    }
+
+    /// See [`FileSystem::set_hash_buffer_size`]
+    #[must_use]
+    pub fn hash_buffer_size(mut self, size: usize) -> Self {
+        self.hash_buffer_size = size;
+        self
+    }
+
+    /// See [`FileSystem::set_part_verify_concurrency`]
+    #[must_use]
+    pub fn part_verify_concurrency(mut self, limit: usize) -> Self {
+        self.part_verify_concurrency = limit;
+        self
+    }
+
+    /// See [`FileSystem::set_max_keys`]
+    #[must_use]
+    pub fn max_keys(mut self, limit: i32) -> Self {
+        self.max_keys = limit;
+        self
+    }
+
+    /// See [`FileSystem::set_max_key_length`]
+    #[must_use]
+    pub fn max_key_length(mut self, len: usize) -> Self {
+        self.max_key_length = len;
+        self
+    }
+
+    /// See [`FileSystem::set_listing_order`]
+    #[must_use]
+    pub fn listing_order(mut self, order: ListingOrder) -> Self {
+        self.listing_order = order;
+        self
+    }
+
+    /// See [`FileSystem::set_reverse_listings`]
+    #[must_use]
+    pub fn reverse_listings(mut self, enabled: bool) -> Self {
+        self.reverse_listings = enabled;
+        self
+    }
+
+    /// See [`FileSystem::set_key_mapper`]
+    #[must_use]
+    pub fn key_mapper(mut self, mapper: impl KeyMapper) -> Self {
+        self.key_mapper = Some(Arc::new(mapper));
+        self
+    }
+
+    /// Creates the file system, which stores its files on the local disk with [`TokioStorage`].
+    ///
+    /// The temporary files left by a previous writer are cleaned unless the file system is read-only.
+    ///
+    /// # Errors
+    /// Returns an error if a buffer size or a limit is zero, if the root is not a directory,
+    /// or if the metadata directory or the temporary directory can not be created.
+    pub fn build(mut self) -> Result<FileSystem> {
+        self.validate()?;
+        let root = env::current_dir()?.join(&self.root).canonicalize()?;
+        if root.is_dir().not() {
+            return Err(Error::from_string(format!("{} is not a directory", root.display())));
+        }
+        let tmp_dir = self.tmp_dir.take();
+
+        let mut fs = block_on(self.prepare(root, Arc::new(BlockingStorage)))?;
+        fs.storage = Arc::new(TokioStorage);
+        if let Some(dir) = tmp_dir {
+            fs.set_tmp_dir(dir)?;
+        }
+        Ok(fs)
+    }
+
+    /// Creates the file system, which performs its file operations with `storage` instead of [`TokioStorage`].
+    ///
+    /// The root is prepared with the storage as well, including the metadata directory, the temporary directory
+    /// and the public buckets and objects of the builder. The root and the temporary directory are made absolute
+    /// without resolving symbolic links, because they may not be on the local disk.
+    ///
+    /// # Errors
+    /// Returns an error if a buffer size or a limit is zero, if the root is not a directory,
+    /// or if the metadata directory or the temporary directory can not be created.
+    pub async fn build_with_storage(self, storage: impl Storage) -> Result<FileSystem> {
+        self.validate()?;
+        let storage: Arc<dyn Storage> = Arc::new(storage);
+        let root = env::current_dir()?.join(&self.root).absolutize()?.into_owned();
+        if storage.is_dir(&root).await.not() {
+            return Err(Error::from_string(format!("{} is not a directory", root.display())));
+        }
+        self.prepare(root, storage).await
+    }
+
+    /// Creates the file system at `root`, and prepares the root with `storage`
+    /// Rejects the values which the setters of [`FileSystem`] would panic on.
+    fn validate(&self) -> Result<()> {
+        let invalid = [
+            (self.read_buffer_size == 0, "the read buffer size must not be zero"),
+            (self.hash_buffer_size == 0, "the hash buffer size must not be zero"),
+            (self.part_verify_concurrency == 0, "the part verification concurrency must not be zero"),
+            (self.max_keys <= 0, "the maximum number of keys must be positive"),
+            (self.max_key_length == 0, "the maximum length of keys must not be zero"),
+        ];
+        match invalid.into_iter().find(|&(is_invalid, _)| is_invalid) {
+            Some((_, message)) => Err(io::Error::new(io::ErrorKind::InvalidInput, message).into()),
+            None => Ok(()),
+        }
+    }
+
+    async fn prepare(self, root: PathBuf, storage: Arc<dyn Storage>) -> Result<FileSystem> {
+        if self.read_only.not() {
+            clean_old_tmp_files(&*storage, &root).await?;
+        }
+
+        let mut fs = FileSystem {
+            meta_root: root.clone(),
+            root,
+            region: self.region,
+            durable: self.durable,
+            read_only: self.read_only,
+            tmp_dir: None,
+            tmp_file_counter: Arc::new(AtomicU64::new(0)),
+            upload_info_lock: Arc::new(Mutex::new(())),
+            key_locks: Arc::default(),
+            min_part_size: self.min_part_size,
+            max_object_size: self.max_object_size,
+            website_redirects: self.website_redirects,
+            dry_runs: self.dry_runs,
+            guess_content_types: self.guess_content_types,
+            public_buckets: None,
+            public_objects: None,
+            default_object_ownership: None,
+            key_mapper: self.key_mapper.unwrap_or_else(|| Arc::new(DefaultKeyMapper)),
+            storage,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            hash_buffer_size: DEFAULT_HASH_BUFFER_SIZE,
+            part_verify_concurrency: DEFAULT_PART_VERIFY_CONCURRENCY,
+            max_keys: DEFAULT_MAX_KEYS,
+            max_key_length: DEFAULT_MAX_KEY_LENGTH,
+            listing_order: self.listing_order,
+            reverse_listings: self.reverse_listings,
+        };
+        fs.set_read_buffer_size(self.read_buffer_size);
+        fs.set_hash_buffer_size(self.hash_buffer_size);
+        fs.set_part_verify_concurrency(self.part_verify_concurrency);
+        fs.set_max_keys(self.max_keys);
+        fs.set_max_key_length(self.max_key_length);
+        if self.meta_dir {
+            let meta_root = fs.root.join(META_DIR);
+            if fs.read_only.not() {
+                fs.storage.create_dir_all(&meta_root).await?;
+                clean_old_tmp_files(&*fs.storage, &meta_root).await?;
+            }
+            fs.meta_root = meta_root;
+        }
+        if let Some(dir) = self.tmp_dir {
+            let dir = env::current_dir()?.join(dir).absolutize()?.into_owned();
+            fs.storage.create_dir_all(&dir).await?;
+            clean_old_tmp_files(&*fs.storage, &dir).await?;
+            fs.tmp_dir = Some(dir);
+        }
+        if let Some(buckets) = self.public_buckets {
+            fs.publish_public_buckets(&buckets).await?;
+            fs.public_buckets = Some(buckets);
+        }
+        if let Some(objects) = self.public_objects {
+            fs.publish_public_objects(&objects).await?;
+            fs.public_objects = Some(objects);
+        }
+        if let Some(ownership) = self.default_object_ownership {
+            fs.set_default_object_ownership(ownership)?;
+        }
+        Ok(fs)
+    }
+}
+
 impl FileSystem {
+    /// Creates a file system which serves `root` with the default options, see [`FileSystemBuilder`]
     pub fn new(root: impl AsRef<Path>) -> Result<Self> {
-        let root = env::current_dir()?.join(root).canonicalize()?;
-        clean_old_tmp_files(&root)?;
-        let tmp_file_counter = AtomicU64::new(0);
-        Ok(Self { root, tmp_file_counter })
+        FileSystemBuilder::new(root).build()
+    }
+
+    /// Creates a file system which serves `root` with the default options and performs its file operations
+    /// with `storage`, see [`FileSystemBuilder::build_with_storage`]
+    ///
+    /// # Errors
+    /// Returns an error if the root is not a directory in the storage.
+    pub async fn with_storage(root: impl AsRef<Path>, storage: impl Storage) -> Result<Self> {
+        FileSystemBuilder::new(root).build_with_storage(storage).await
+    }
+
+    /// Creates a file system which serves an existing directory without modifying it.
+    ///
+    /// All mutating operations are rejected with `AccessDenied`,
+    /// and the temporary files left by a previous writer are not cleaned.
+    pub fn new_read_only(root: impl AsRef<Path>) -> Result<Self> {
+        FileSystemBuilder::new(root).read_only(true).build()
+    }
+
+    #[must_use]
+    pub fn builder(root: impl AsRef<Path>) -> FileSystemBuilder {
+        FileSystemBuilder::new(root)
+    }
+
+    /// Sets the region of new buckets which do not specify a location constraint.
+    ///
+    /// The default region is `us-east-1`.
+    pub fn set_region(&mut self, region: impl Into<String>) {
+        self.region = region.into();
+    }
+
+    /// Makes object writes durable.
+    ///
+    /// The data is synced to the disk before a new object replaces the old one,
+    /// and the directory is synced after the rename, so that a completed write survives a crash.
+    /// Syncing adds the latency of the disk to every write, so this is disabled by default.
+    pub fn set_durable(&mut self, durable: bool) {
+        self.durable = durable;
+    }
+
+    /// Makes `GetObject` and `HeadObject` redirect to the website redirect location of an object.
+    ///
+    /// An object with `x-amz-website-redirect-location` is answered with `301 Moved Permanently`,
+    /// like the website endpoint of S3. The location is only returned as a header by default.
+    pub fn set_website_redirects(&mut self, enabled: bool) {
+        self.website_redirects = enabled;
+    }
+
+    /// Accepts dry runs of mutating requests, which have the header `x-s3s-dry-run: true`.
+    ///
+    /// A dry run is validated like the request itself, but nothing is written, and the response has the same header.
+    /// See [`crate::DRY_RUN_HEADER`] for the operations which support dry runs. Dry runs are rejected by default.
+    pub fn set_dry_runs(&mut self, enabled: bool) {
+        self.dry_runs = enabled;
+    }
+
+    /// Makes `GetObject` and `HeadObject` guess the content type of an object stored without one
+    /// from the extension of its key, such as `application/json` for `.json`.
+    ///
+    /// A stored content type always wins. Objects without one are `application/octet-stream` by default.
+    pub fn set_guess_content_types(&mut self, enabled: bool) {
+        self.guess_content_types = enabled;
+    }
+
+    /// Shares the buckets which can be read anonymously with the authentication provider,
+    /// such as [`SimpleAuth::public_buckets`](s3s::auth::SimpleAuth::public_buckets).
+    ///
+    /// The buckets whose ACL grants `READ` to all users are added, and `PutBucketAcl` adds or removes a bucket.
+    ///
+    /// The stored ACLs are read from the local disk.
+    ///
+    /// # Errors
+    /// Returns an error if the stored ACLs can not be read.
+    pub fn set_public_buckets(&mut self, buckets: PublicBuckets) -> Result<()> {
+        block_on(self.on_local_disk().publish_public_buckets(&buckets))?;
+        self.public_buckets = Some(buckets);
+        Ok(())
+    }
+
+    /// Shares the objects which can be read anonymously with the authentication provider,
+    /// such as [`SimpleAuth::public_objects`](s3s::auth::SimpleAuth::public_objects).
+    ///
+    /// The objects whose ACL grants `READ` to all users are added, even if their buckets are private.
+    /// `PutObject` and `PutObjectAcl` add or remove an object.
+    ///
+    /// The stored ACLs are read from the local disk.
+    ///
+    /// # Errors
+    /// Returns an error if the stored ACLs can not be read.
+    pub fn set_public_objects(&mut self, objects: PublicObjects) -> Result<()> {
+        block_on(self.on_local_disk().publish_public_objects(&objects))?;
+        self.public_objects = Some(objects);
+        Ok(())
+    }
+
+    /// Sets the object ownership of new buckets whose `CreateBucket` does not have `x-amz-object-ownership`,
+    /// such as `BucketOwnerEnforced`, which disables ACLs like the default of AWS.
+    ///
+    /// New buckets have no ownership controls by default, which keeps ACLs enabled.
+    ///
+    /// # Errors
+    /// Returns an error if the object ownership is not valid.
+    pub fn set_default_object_ownership(&mut self, ownership: impl Into<String>) -> Result<()> {
+        let ownership = ownership.into();
+        if crate::ownership::is_valid_object_ownership(&ownership).not() {
+            return Err(Error::from_string(format!("invalid object ownership: {ownership:?}")));
+        }
+        self.default_object_ownership = Some(ownership);
+        Ok(())
+    }
+
+    /// Maps the keys of objects to the paths of their files with `mapper` instead of [`DefaultKeyMapper`].
+    ///
+    /// The mapper should be set before serving requests, because the objects stored with another mapper
+    /// may not be found anymore.
+    pub fn set_key_mapper(&mut self, mapper: impl KeyMapper) {
+        self.key_mapper = Arc::new(mapper);
+    }
+
+    /// Returns a clone which accesses the local disk with blocking calls, for the synchronous setters
+    fn on_local_disk(&self) -> Self {
+        Self {
+            storage: Arc::new(BlockingStorage),
+            ..self.clone()
+        }
+    }
+
+    /// Sets the size of the chunks in which object files are read by `GetObject`, `CopyObject`, `UploadPartCopy`
+    /// and `SelectObjectContent`.
+    ///
+    /// Each chunk is read when the previous one has been sent, so a slow client holds one chunk at a time.
+    /// Larger chunks need fewer reads for large objects. The default is 256 KiB.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub fn set_read_buffer_size(&mut self, size: usize) {
+        assert!(size > 0, "the read buffer size must not be zero");
+        self.read_buffer_size = size;
+    }
+
+    /// Sets the size of the chunks in which files are read to compute their MD5 sums,
+    /// such as the objects and parts whose entity tags were not stored by older versions.
+    ///
+    /// Larger chunks need fewer reads on fast storage. The default is 64 KiB.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub fn set_hash_buffer_size(&mut self, size: usize) {
+        assert!(size > 0, "the hash buffer size must not be zero");
+        self.hash_buffer_size = size;
+    }
+
+    /// Sets the number of staged parts which `CompleteMultipartUpload` verifies at once.
+    ///
+    /// The parts are verified concurrently before they are assembled in order,
+    /// which speeds up completing uploads with many parts. The default is 8.
+    ///
+    /// # Panics
+    /// Panics if `limit` is zero.
+    pub fn set_part_verify_concurrency(&mut self, limit: usize) {
+        assert!(limit > 0, "the part verification concurrency must not be zero");
+        self.part_verify_concurrency = limit;
+    }
+
+    /// Sets the maximum number of keys of a page of `ListObjects`, `ListObjectsV2` and `ListObjectVersions`.
+    ///
+    /// A larger `max-keys` is clamped to the maximum, and a request without `max-keys` gets at most 1000 keys.
+    /// The default is 1000 like AWS.
+    ///
+    /// # Panics
+    /// Panics if `limit` is not positive.
+    pub fn set_max_keys(&mut self, limit: i32) {
+        assert!(limit > 0, "the maximum number of keys must be positive");
+        self.max_keys = limit;
+    }
+
+    /// Sets the maximum length of a key in bytes, to which object requests with longer keys fail with `KeyTooLongError`.
+    ///
+    /// s3s rejects keys longer than 1024 bytes, so a larger limit has no effect. The default is 1024 like AWS.
+    ///
+    /// Each segment of the path of a key is a file name, which is limited to 255 bytes.
+    /// The sidecar files of long keys are stored in nested directories, so they do not limit the length.
+    ///
+    /// # Panics
+    /// Panics if `len` is zero.
+    pub fn set_max_key_length(&mut self, len: usize) {
+        assert!(len > 0, "the maximum length of keys must not be zero");
+        self.max_key_length = len;
+    }
+
+    /// Sets the order of the objects of `ListObjects` and `ListObjectsV2`, for tools which need another order than S3.
+    ///
+    /// In another order, a common prefix is listed at its first object, and a marker or `start-after` continues
+    /// the listing after the key or the common prefix it names. One which is not in the listing only skips
+    /// the keys before it in the order of keys. `ListObjectVersions` is always ordered by key.
+    /// The default is [`ListingOrder::Key`] like AWS.
+    pub fn set_listing_order(&mut self, order: ListingOrder) {
+        self.listing_order = order;
+    }
+
+    /// Reverses the order of the objects of `ListObjects` and `ListObjectsV2`, see [`FileSystem::set_listing_order`].
+    pub fn set_reverse_listings(&mut self, enabled: bool) {
+        self.reverse_listings = enabled;
+    }
+
+    /// Sets the minimum size of each part of a multipart upload except the last one.
+    ///
+    /// `CompleteMultipartUpload` fails with `EntityTooSmall` if a smaller part is not the last one.
+    /// The default is 5 MiB like AWS.
+    pub fn set_min_part_size(&mut self, size: u64) {
+        self.min_part_size = size;
+    }
+
+    /// Sets the maximum size of an object.
+    ///
+    /// `PutObject` and `UploadPart` fail with `EntityTooLarge` as soon as the body exceeds the size,
+    /// `UploadPartCopy` if the copied range exceeds it, and `CompleteMultipartUpload` if the parts add up to more.
+    /// There is no limit by default.
+    pub fn set_max_object_size(&mut self, size: u64) {
+        self.max_object_size = Some(size);
+    }
+
+    /// Stages object writes in `dir` instead of the metadata directory.
+    ///
+    /// The directory may be on another filesystem than the root,
+    /// in which case a completed file is copied to its destination instead of being renamed.
+    /// The directory is created on the local disk.
+    ///
+    /// # Errors
+    /// Returns an error if the directory can not be created.
+    pub fn set_tmp_dir(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        let dir = env::current_dir()?.join(dir);
+        std::fs::create_dir_all(&dir)?;
+        let dir = dir.canonicalize()?;
+        block_on(clean_old_tmp_files(&BlockingStorage, &dir))?;
+        self.tmp_dir = Some(dir);
+        Ok(())
+    }
+
+    /// Places all sidecar files under a dedicated `.s3s-meta` directory instead of the root.
+    ///
+    /// The existing sidecar files are not moved. The directory is created on the local disk.
+    ///
+    /// # Errors
+    /// Returns an error if the directory can not be created.
+    pub fn enable_meta_dir(&mut self) -> Result<()> {
+        let meta_root = self.root.join(META_DIR);
+        if self.read_only.not() {
+            std::fs::create_dir_all(&meta_root)?;
+            block_on(clean_old_tmp_files(&BlockingStorage, &meta_root))?;
+        }
+        self.meta_root = meta_root;
+        Ok(())
+    }
+
+    /// Removes the sidecar files of objects which do not exist anymore,
+    /// for example because the object files were deleted by other programs.
+    ///
+    /// Files whose object can not be determined are kept.
+    /// The empty directories of long sidecar names are removed as well.
+    /// This should not run concurrently with requests, because an object may be written between the check and the removal.
+    ///
+    /// Returns the number of removed files.
+    ///
+    /// # Errors
+    /// Returns an `AccessDenied` error if the file system is read-only.
+    pub async fn gc_orphans(&self) -> Result<usize> {
+        self.check_writable()?;
+
+        let mut count = 0;
+        for sidecar in self.list_object_sidecars(None).await? {
+            if sidecar.entry.is_dir || OBJECT_SIDECAR_SUFFIXES.contains(&sidecar.suffix.as_str()).not() {
+                continue;
+            }
+            let Ok(object_path) = self.get_object_path(&sidecar.bucket, &sidecar.key) else { continue };
+
+            match self.storage.symlink_metadata(&object_path).await {
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                _ => continue,
+            }
+
+            self.storage.remove_file(&sidecar.entry.path).await?;
+            count += 1;
+        }
+        self.remove_empty_sidecar_dirs().await?;
+        Ok(count)
+    }
+
+    /// Lists the sidecar files and directories of the objects of `bucket`, or of all buckets,
+    /// including those in the directories of long names, see `object_sidecar_path`.
+    ///
+    /// Entries whose bucket or key can not be decoded are skipped.
+    pub(crate) async fn list_object_sidecars(&self, bucket: Option<&str>) -> Result<Vec<ObjectSidecar>> {
+        let encoded_bucket = bucket.map(|s| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s));
+        let mut sidecars = Vec::new();
+        let mut dirs = Vec::new();
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(file_name) = entry.file_name() else { continue };
+            let Some((bucket, name)) = file_name.strip_prefix(".bucket-").and_then(|s| s.split_once(".object-")) else {
+                continue;
+            };
+            if encoded_bucket.as_ref().is_some_and(|b| b != bucket) {
+                continue;
+            }
+            let bucket = bucket.to_owned();
+            if name.is_empty() {
+                if entry.is_dir {
+                    dirs.push((entry.path, bucket, String::new()));
+                }
+                continue;
+            }
+            let Some((key, suffix)) = name.find('.').map(|pos| name.split_at(pos)) else { continue };
+            let (key, suffix) = (key.to_owned(), suffix.to_owned());
+            sidecars.extend(ObjectSidecar::parse(entry, &bucket, &key, &suffix));
+        }
+        while let Some((dir, bucket, prefix)) = dirs.pop() {
+            for entry in self.storage.read_dir(&dir).await? {
+                let Some(name) = entry.file_name() else { continue };
+                match name.find('.') {
+                    // temporary files, see `write_atomic`
+                    Some(0) => {}
+                    Some(pos) => {
+                        let (part, suffix) = name.split_at(pos);
+                        let (key, suffix) = (format!("{prefix}{part}"), suffix.to_owned());
+                        sidecars.extend(ObjectSidecar::parse(entry, &bucket, &key, &suffix));
+                    }
+                    None if entry.is_dir => {
+                        let prefix = format!("{prefix}{name}");
+                        dirs.push((entry.path, bucket.clone(), prefix));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(sidecars)
+    }
+
+    /// Removes the directories of long sidecar names which are left empty, see `object_sidecar_path`
+    async fn remove_empty_sidecar_dirs(&self) -> Result<()> {
+        let mut dirs = Vec::new();
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(file_name) = entry.file_name() else { continue };
+            if entry.is_dir && file_name.starts_with(".bucket-") && file_name.ends_with(".object-") {
+                dirs.push(entry.path);
+            }
+        }
+        // each directory is visited before its subdirectories, so they are removed in reverse
+        let mut index = 0;
+        while let Some(dir) = dirs.get(index).cloned() {
+            for entry in self.storage.read_dir(&dir).await? {
+                let Some(name) = entry.file_name() else { continue };
+                if entry.is_dir && name.contains('.').not() {
+                    dirs.push(entry.path);
+                }
+            }
+            index += 1;
+        }
+        for dir in dirs.iter().rev() {
+            if self.storage.read_dir(dir).await?.is_empty() {
+                self.storage.remove_dir(dir).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Aborts the multipart uploads which were initiated more than `ttl` ago,
+    /// so that abandoned uploads do not hold their staged parts forever.
+    ///
+    /// The age of an upload whose initiation time is unknown is the time since its record was last modified.
+    /// Staged parts without a record, which are left by a crash while completing or aborting an upload,
+    /// are removed when they were last modified more than `ttl` ago.
+    /// The threshold keeps the uploads of another instance serving the same root.
+    ///
+    /// Returns the number of aborted uploads.
+    ///
+    /// # Errors
+    /// Returns an `AccessDenied` error if the file system is read-only.
+    pub async fn reap_uploads(&self, ttl: Duration) -> Result<usize> {
+        self.check_writable()?;
+
+        let now = SystemTime::now();
+        let is_stale = |time: SystemTime| now.duration_since(time).is_ok_and(|age| age > ttl);
+
+        let mut count = 0;
+        let mut upload_ids = HashSet::new();
+        for (upload_id, info) in self.list_upload_infos().await? {
+            upload_ids.insert(upload_id);
+            let initiated = if let Some(initiated) = info.initiated {
+                initiated
+            } else {
+                let metadata = self.storage.metadata(&self.get_upload_info_path(&upload_id)?).await?;
+                modified_time(&metadata)
+            };
+            if is_stale(initiated) {
+                self.remove_upload(&upload_id).await?;
+                count += 1;
+            }
+        }
+
+        let mut orphaned = HashSet::new();
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(name) = entry.file_name() else { continue };
+            // See `FileSystem::remove_upload`
+            let Some((upload_id, _)) = name.strip_prefix(".upload_id-").and_then(|s| s.split_once('.')) else {
+                continue;
+            };
+            let Ok(upload_id) = Uuid::parse_str(upload_id) else { continue };
+            if upload_ids.contains(&upload_id) {
+                continue;
+            }
+
+            let metadata = self.storage.symlink_metadata(&entry.path).await?;
+            if metadata.is_file() && is_stale(modified_time(&metadata)) {
+                self.storage.remove_file(&entry.path).await?;
+                orphaned.insert(upload_id);
+            }
+        }
+        Ok(count + orphaned.len())
     }
 
     pub(crate) fn resolve_abs_path(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
         Ok(path.as_ref().absolutize_virtually(&self.root)?.into_owned())
     }
 
+    /// resolve sidecar path under the metadata directory
+    pub(crate) fn resolve_meta_path(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        Ok(path.as_ref().absolutize_virtually(&self.meta_root)?.into_owned())
+    }
+
     /// resolve object path under the virtual root
     pub(crate) fn get_object_path(&self, bucket: &str, key: &str) -> Result<PathBuf> {
+        check_bucket(bucket)?;
+        check_key(key, self.max_key_length, self.key_mapper.stores_any_key())?;
         let dir = Path::new(&bucket);
-        let file_path = Path::new(&key);
-        self.resolve_abs_path(dir.join(file_path))
+        let mut file_path = self.key_mapper.object_path(bucket, key);
+        check_file_names(&file_path)?;
+        if key.ends_with('/') && self.key_mapper.stores_any_key().not() {
+            file_path.push(DIRECTORY_MARKER);
+        }
+        let path = self.resolve_abs_path(dir.join(file_path))?;
+
+        // Sidecar files are never stored in bucket directories,
+        // so an object inside its bucket can not collide with a sidecar file.
+        let bucket_path = self.root.join(bucket);
+        if path.starts_with(&bucket_path).not() || path == bucket_path {
+            return Err(Error::request(
+                S3ErrorCode::InvalidArgument,
+                "Object key must resolve to a path in the bucket",
+            ));
+        }
+        Ok(path)
     }
 
     /// resolve bucket path under the virtual root
     pub(crate) fn get_bucket_path(&self, bucket: &str) -> Result<PathBuf> {
+        check_bucket(bucket)?;
         let dir = Path::new(&bucket);
         self.resolve_abs_path(dir)
     }
 
-    /// resolve metadata path under the virtual root (custom format)
+    /// resolve metadata path under the metadata directory (custom format)
     pub(crate) fn get_metadata_path(&self, bucket: &str, key: &str) -> Result<PathBuf> {
-        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
-        let file_path = format!(".bucket-{}.object-{}.metadata.json", encode(bucket), encode(key));
-        self.resolve_abs_path(file_path)
+        self.resolve_meta_path(object_sidecar_path(bucket, key, ".metadata.json"))
+    }
+
+    /// resolve tagging path under the metadata directory (custom format)
+    pub(crate) fn get_tagging_path(&self, bucket: &str, key: &str) -> Result<PathBuf> {
+        self.resolve_meta_path(object_sidecar_path(bucket, key, ".tagging.json"))
     }
 
     pub(crate) fn get_internal_info_path(&self, bucket: &str, key: &str) -> Result<PathBuf> {
+        self.resolve_meta_path(object_sidecar_path(bucket, key, ".internal.json"))
+    }
+
+    /// resolve bucket config path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_config_path(&self, bucket: &str) -> Result<PathBuf> {
         let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
-        let file_path = format!(".bucket-{}.object-{}.internal.json", encode(bucket), encode(key));
-        self.resolve_abs_path(file_path)
+        let file_path = format!(".bucket-{}.config.json", encode(bucket));
+        self.resolve_meta_path(file_path)
     }
 
-    /// load metadata from fs
-    pub(crate) async fn load_metadata(&self, bucket: &str, key: &str) -> Result<Option<dto::Metadata>> {
-        let path = self.get_metadata_path(bucket, key)?;
-        if path.exists().not() {
-            return Ok(None);
+    /// resolve bucket CORS configuration path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_cors_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let file_path = format!(".bucket-{}.cors.json", encode(bucket));
+        self.resolve_meta_path(file_path)
+    }
+
+    /// resolve bucket website configuration path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_website_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let file_path = format!(".bucket-{}.website.json", encode(bucket));
+        self.resolve_meta_path(file_path)
+    }
+
+    /// resolve bucket ACL path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_acl_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let file_path = format!(".bucket-{}.acl.json", encode(bucket));
+        self.resolve_meta_path(file_path)
+    }
+
+    /// resolve bucket lifecycle configuration path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_lifecycle_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let file_path = format!(".bucket-{}.lifecycle.json", encode(bucket));
+        self.resolve_meta_path(file_path)
+    }
+
+    /// resolve bucket policy path under the metadata directory (custom format)
+    pub(crate) fn get_bucket_policy_path(&self, bucket: &str) -> Result<PathBuf> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let file_path = format!(".bucket-{}.policy.json", encode(bucket));
+        self.resolve_meta_path(file_path)
+    }
+
+    /// Returns `true` if the bucket directory contains no object files.
+    ///
+    /// Empty directories left by deleted objects are not objects.
+    pub(crate) async fn is_bucket_empty(&self, bucket: &str) -> Result<bool> {
+        let mut dirs = vec![self.get_bucket_path(bucket)?];
+        while let Some(dir) = dirs.pop() {
+            for entry in self.storage.read_dir(&dir).await? {
+                if entry.is_dir {
+                    dirs.push(entry.path);
+                } else {
+                    return Ok(false);
+                }
+            }
         }
-        let content = fs::read(&path).await?;
-        let map = serde_json::from_slice(&content)?;
-        Ok(Some(map))
+        Ok(true)
     }
 
-    /// save metadata to fs
-    pub(crate) async fn save_metadata(&self, bucket: &str, key: &str, metadata: &dto::Metadata) -> Result<()> {
-        let path = self.get_metadata_path(bucket, key)?;
-        let content = serde_json::to_vec(metadata)?;
-        fs::write(&path, &content).await?;
+    /// Removes all sidecar files of a bucket, including the bucket configurations and the object versions.
+    pub(crate) async fn remove_bucket_sidecars(&self, bucket: &str) -> Result<()> {
+        let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+        let prefix = format!(".bucket-{}.", encode(bucket));
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(file_name) = entry.file_name() else { continue };
+            if file_name.starts_with(&prefix).not() {
+                continue;
+            }
+            if entry.is_dir {
+                self.storage.remove_dir_all(&entry.path).await?;
+            } else {
+                self.storage.remove_file(&entry.path).await?;
+            }
+        }
         Ok(())
     }
 
+    /// save metadata to fs, `None` removes the sidecar file of a previous object
+    pub(crate) async fn save_metadata(&self, bucket: &str, key: &str, metadata: Option<&dto::Metadata>) -> Result<()> {
+        self.current_location(bucket, key)?.save_metadata(metadata).await
+    }
+
+    /// save tagging to fs, an empty tag map removes the sidecar file
+    pub(crate) async fn save_tagging(&self, bucket: &str, key: &str, tagging: &TagMap) -> Result<()> {
+        self.current_location(bucket, key)?.save_tagging(tagging).await
+    }
+
     pub(crate) async fn load_internal_info(&self, bucket: &str, key: &str) -> Result<Option<InternalInfo>> {
         let path = self.get_internal_info_path(bucket, key)?;
-        if path.exists().not() {
-            return Ok(None);
-        }
-        let content = fs::read(&path).await?;
-        let map = serde_json::from_slice(&content)?;
-        Ok(Some(map))
+        load_json(&*self.storage, &path).await
     }
 
     pub(crate) async fn save_internal_info(&self, bucket: &str, key: &str, info: &InternalInfo) -> Result<()> {
         let path = self.get_internal_info_path(bucket, key)?;
-        let content = serde_json::to_vec(info)?;
-        fs::write(&path, &content).await?;
-        Ok(())
+        save_json(&*self.storage, &path, info).await
     }
 
-    /// get md5 sum
-    pub(crate) async fn get_md5_sum(&self, bucket: &str, key: &str) -> Result<String> {
-        let object_path = self.get_object_path(bucket, key)?;
-        let mut file = File::open(&object_path).await?;
-        let mut buf = vec![0; 65536];
+    pub(crate) async fn load_bucket_config(&self, bucket: &str) -> Result<Option<BucketConfig>> {
+        let path = self.get_bucket_config_path(bucket)?;
+        load_json(&*self.storage, &path).await
+    }
+
+    pub(crate) async fn save_bucket_config(&self, bucket: &str, config: &BucketConfig) -> Result<()> {
+        let path = self.get_bucket_config_path(bucket)?;
+        save_json(&*self.storage, &path, config).await
+    }
+
+    /// Buckets created before the location was recorded are in the default region.
+    pub(crate) async fn load_bucket_region(&self, bucket: &str) -> Result<String> {
+        let config = self.load_bucket_config(bucket).await?.unwrap_or_default();
+        let region = config.get("location_constraint").and_then(serde_json::Value::as_str);
+        Ok(region.unwrap_or(self.region.as_str()).to_owned())
+    }
+
+    /// get md5 sum of a file
+    pub(crate) async fn get_file_md5_sum(&self, path: &Path) -> Result<String> {
+        let mut file = self.storage.open(path).await?;
+        let mut buf = vec![0; self.hash_buffer_size];
         let mut md5_hash = Md5::new();
         loop {
             let nread = file.read(&mut buf).await?;
@@ -134,63 +1306,155 @@ impl FileSystem {
     }
 
     fn get_upload_info_path(&self, upload_id: &Uuid) -> Result<PathBuf> {
-        self.resolve_abs_path(format!(".upload-{upload_id}.json"))
+        self.resolve_meta_path(format!(".upload-{upload_id}.json"))
     }
 
-    pub(crate) async fn create_upload_id(&self, cred: Option<&Credentials>) -> Result<Uuid> {
+    /// load the records of all in-progress multipart uploads
+    pub(crate) async fn list_upload_infos(&self) -> Result<Vec<(Uuid, UploadInfo)>> {
+        let mut uploads = Vec::new();
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            let Some(name) = entry.file_name() else { continue };
+            let Some(upload_id) = name.strip_prefix(".upload-").and_then(|s| s.strip_suffix(".json")) else {
+                continue;
+            };
+            let Ok(upload_id) = Uuid::parse_str(upload_id) else { continue };
+
+            if let Some(info) = self.load_upload_info(&upload_id).await? {
+                uploads.push((upload_id, info));
+            }
+        }
+        Ok(uploads)
+    }
+
+    /// The access key of the record is set from the credentials.
+    pub(crate) async fn create_upload_id(&self, cred: Option<&Credentials>, info: UploadInfo) -> Result<Uuid> {
         let upload_id = Uuid::new_v4();
         let upload_info_path = self.get_upload_info_path(&upload_id)?;
 
-        let ak: Option<&str> = cred.map(|c| c.access_key.as_str());
-
-        let content = serde_json::to_vec(&ak)?;
-        fs::write(&upload_info_path, &content).await?;
+        let info = UploadInfo {
+            access_key: cred.map(|c| c.access_key.clone()),
+            initiated: Some(SystemTime::now()),
+            ..info
+        };
+        save_json(&*self.storage, &upload_info_path, &info).await?;
 
         Ok(upload_id)
     }
 
-    pub(crate) async fn verify_upload_id(&self, cred: Option<&Credentials>, upload_id: &Uuid) -> Result<bool> {
+    /// load the record of a multipart upload, returns `None` if the upload does not exist
+    pub(crate) async fn load_upload_info(&self, upload_id: &Uuid) -> Result<Option<UploadInfo>> {
         let upload_info_path = self.get_upload_info_path(upload_id)?;
-        if upload_info_path.exists().not() {
-            return Ok(false);
+        if self.storage.exists(&upload_info_path).await.not() {
+            return Ok(None);
+        }
+
+        let content = self.storage.read(&upload_info_path).await?;
+        if let Ok(info) = serde_json::from_slice(&content) {
+            return Ok(Some(info));
         }
 
-        let content = fs::read(&upload_info_path).await?;
-        let ak: Option<String> = serde_json::from_slice(&content)?;
+        // old format: the access key only
+        let access_key: Option<String> = serde_json::from_slice(&content)?;
+        Ok(Some(UploadInfo {
+            access_key,
+            ..Default::default()
+        }))
+    }
+
+    pub(crate) async fn verify_upload_id(&self, cred: Option<&Credentials>, upload_id: &Uuid) -> Result<bool> {
+        let Some(info) = self.load_upload_info(upload_id).await? else { return Ok(false) };
+        Ok(info.access_key.as_deref() == cred.map(|c| c.access_key.as_str()))
+    }
+
+    /// add an uploaded part to the record of a multipart upload
+    pub(crate) async fn save_upload_part(&self, upload_id: &Uuid, part_number: i32, part: PartInfo) -> Result<()> {
+        let _guard = self.upload_info_lock.lock().await;
+
+        let Some(mut info) = self.load_upload_info(upload_id).await? else { return Ok(()) };
+        info.parts.insert(part_number, part);
 
-        Ok(ak.as_deref() == cred.map(|c| c.access_key.as_str()))
+        let upload_info_path = self.get_upload_info_path(upload_id)?;
+        save_json(&*self.storage, &upload_info_path, &info).await
     }
 
     pub(crate) async fn delete_upload_id(&self, upload_id: &Uuid) -> Result<()> {
         let upload_info_path = self.get_upload_info_path(upload_id)?;
-        if upload_info_path.exists() {
-            fs::remove_file(&upload_info_path).await?;
+        if self.storage.exists(&upload_info_path).await {
+            self.storage.remove_file(&upload_info_path).await?;
         }
         Ok(())
     }
 
+    /// remove the staged parts and the record of a multipart upload
+    pub(crate) async fn remove_upload(&self, upload_id: &Uuid) -> Result<()> {
+        let prefix = format!(".upload_id-{upload_id}.");
+        for entry in self.storage.read_dir(&self.meta_root).await? {
+            if entry.is_dir {
+                continue;
+            }
+            let Some(name) = entry.file_name() else { continue };
+            if name.starts_with(&prefix) {
+                self.storage.remove_file(&entry.path).await?;
+            }
+        }
+        self.delete_upload_id(upload_id).await
+    }
+
     /// Write to the filesystem atomically.
     /// This is done by first writing to a temporary location and then moving the file.
     pub(crate) async fn prepare_file_write(&self, bucket: &str, key: &str) -> Result<FileWriter> {
+        let bucket_path = self.get_bucket_path(bucket)?;
         let final_path = Some(self.get_object_path(bucket, key)?);
         let tmp_name = format!(".tmp.{}.internal.part", self.tmp_file_counter.fetch_add(1, Ordering::SeqCst));
-        let tmp_path = self.resolve_abs_path(tmp_name)?;
-        let file = File::create(&tmp_path).await?;
+        let tmp_path = match self.tmp_dir {
+            Some(ref dir) => dir.join(tmp_name),
+            None => self.resolve_meta_path(tmp_name)?,
+        };
+        let file = self.storage.create(&tmp_path).await?;
         let writer = BufWriter::new(file);
         Ok(FileWriter {
+            storage: Arc::clone(&self.storage),
             tmp_path,
+            meta_root: self.meta_root.clone(),
+            bucket_path,
             final_path,
             writer,
             clean_tmp: true,
+            durable: self.durable,
         })
     }
 }
 
+/// Creates the directories of a key below the bucket directory.
+///
+/// The bucket directory itself is never created, so that objects can only be written to existing buckets.
+pub(crate) async fn create_key_dirs(storage: &dyn Storage, bucket_path: &Path, dir_path: &Path) -> Result<()> {
+    if storage.exists(bucket_path).await.not() {
+        return Err(Error::request(S3ErrorCode::NoSuchBucket, "The specified bucket does not exist"));
+    }
+    let Ok(relative) = dir_path.strip_prefix(bucket_path) else { return Ok(()) };
+    let mut path = bucket_path.to_owned();
+    for component in relative.components() {
+        path.push(component);
+        match storage.create_dir(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(())
+}
+
 pub(crate) struct FileWriter {
+    storage: Arc<dyn Storage>,
     tmp_path: PathBuf,
+    /// The directory where a temporary file on another filesystem is copied to, see `FileWriter::move_across_devices`
+    meta_root: PathBuf,
+    bucket_path: PathBuf,
     final_path: Option<PathBuf>,
-    writer: BufWriter<File>,
+    writer: BufWriter<Box<dyn StorageFile>>,
     clean_tmp: bool,
+    durable: bool,
 }
 
 impl FileWriter {
@@ -202,19 +1466,68 @@ impl FileWriter {
         self.final_path.as_ref().unwrap()
     }
 
-    pub(crate) fn writer(&mut self) -> &mut BufWriter<File> {
+    pub(crate) fn writer(&mut self) -> &mut BufWriter<Box<dyn StorageFile>> {
         &mut self.writer
     }
 
     pub(crate) async fn done(mut self) -> Result<PathBuf> {
         if let Some(final_dir_path) = self.final_path().parent() {
-            fs::create_dir_all(&final_dir_path).await?;
+            create_key_dirs(&*self.storage, &self.bucket_path, final_dir_path).await?;
+        }
+
+        // the object is modified when the write completes, not when the last byte is written
+        self.writer.flush().await?;
+        let modified = SystemTime::now();
+        self.writer.get_ref().set_modified(modified).await?;
+
+        if self.durable {
+            self.writer.get_ref().sync_all().await?;
         }
 
-        fs::rename(&self.tmp_path, &self.final_path()).await?;
+        match self.storage.rename(&self.tmp_path, self.final_path()).await {
+            Ok(()) => {}
+            Err(err) if is_cross_device(&err) => self.move_across_devices(modified).await?,
+            Err(err) => return Err(err.into()),
+        }
         self.clean_tmp = false;
+
+        if self.durable {
+            if let Some(final_dir_path) = self.final_path().parent() {
+                self.storage.sync_dir(final_dir_path).await?;
+            }
+        }
         Ok(self.final_path.take().unwrap())
     }
+
+    /// Copies the temporary file to the metadata directory, so that the final rename stays atomic.
+    ///
+    /// The metadata directory is on the filesystem of the buckets, because writes without a temporary directory
+    /// are renamed from there as well, and a copy left by a crash is removed like their temporary files.
+    async fn move_across_devices(&mut self, modified: SystemTime) -> Result<()> {
+        let final_path = self.final_path().to_owned();
+        let staged_path = self.meta_root.join(format!(".tmp.{}.internal.part", Uuid::new_v4()));
+        let result = async {
+            self.storage.copy(&self.tmp_path, &staged_path).await?;
+            let staged_file = self.storage.open(&staged_path).await?;
+            staged_file.set_modified(modified).await?;
+            if self.durable {
+                staged_file.sync_all().await?;
+            }
+            self.storage.rename(&staged_path, &final_path).await
+        }
+        .await;
+        if let Err(err) = result {
+            let _ = self.storage.remove_file(&staged_path).await;
+            return Err(err.into());
+        }
+        self.storage.remove_file(&self.tmp_path).await?;
+        Ok(())
+    }
+}
+
+/// Returns whether a rename failed because the paths are on different filesystems
+fn is_cross_device(err: &std::io::Error) -> bool {
+    err.kind() == std::io::ErrorKind::CrossesDevices
 }
 
 impl Drop for FileWriter {
@@ -0,0 +1,127 @@
+//! Locks of objects, which serialize the mutations of an object while other objects proceed in parallel
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
+
+/// The number of independent maps, so that locking different objects rarely contends on the same map
+const SHARDS: usize = 16;
+
+type ObjectId = (String, String);
+
+/// A map of read-write locks by bucket and key.
+///
+/// A lock is created when an object is locked and removed when its last guard is dropped,
+/// so the map only holds the objects which are in use.
+#[derive(Debug)]
+pub(crate) struct KeyLocks {
+    shards: Vec<Mutex<HashMap<ObjectId, Arc<RwLock<()>>>>>,
+}
+
+/// The guard of the lock itself, which is only held to be dropped
+enum Guard {
+    Read { _guard: OwnedRwLockReadGuard<()> },
+    Write { _guard: OwnedRwLockWriteGuard<()> },
+}
+
+/// Holds the lock of an object until it is dropped
+pub(crate) struct KeyGuard<'a> {
+    locks: &'a KeyLocks,
+    id: ObjectId,
+    guard: Option<Guard>,
+}
+
+impl Default for KeyLocks {
+    fn default() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+}
+
+impl KeyLocks {
+    fn shard(&self, id: &ObjectId) -> &Mutex<HashMap<ObjectId, Arc<RwLock<()>>>> {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let index = usize::try_from(hasher.finish() % SHARDS as u64).unwrap_or_default();
+        &self.shards[index]
+    }
+
+    fn get_or_insert(&self, id: &ObjectId) -> Arc<RwLock<()>> {
+        let mut map = self.shard(id).lock().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(map.entry(id.clone()).or_default())
+    }
+
+    /// Returns the guard of an object before it is locked,
+    /// so that a wait which is cancelled still removes the lock if it is idle.
+    fn unlocked(&self, bucket: &str, key: &str) -> KeyGuard<'_> {
+        KeyGuard {
+            locks: self,
+            id: (bucket.to_owned(), key.to_owned()),
+            guard: None,
+        }
+    }
+
+    /// Locks an object for reading, which waits for the mutations in progress but not for other reads.
+    pub(crate) async fn read(&self, bucket: &str, key: &str) -> KeyGuard<'_> {
+        let mut key_guard = self.unlocked(bucket, key);
+        let guard = self.get_or_insert(&key_guard.id).read_owned().await;
+        key_guard.guard = Some(Guard::Read { _guard: guard });
+        key_guard
+    }
+
+    /// Locks an object for a mutation, which waits for all other guards of the object.
+    pub(crate) async fn write(&self, bucket: &str, key: &str) -> KeyGuard<'_> {
+        let mut key_guard = self.unlocked(bucket, key);
+        let guard = self.get_or_insert(&key_guard.id).write_owned().await;
+        key_guard.guard = Some(Guard::Write { _guard: guard });
+        key_guard
+    }
+}
+
+impl Drop for KeyGuard<'_> {
+    fn drop(&mut self) {
+        drop(self.guard.take());
+
+        // the lock is idle if the map holds its only reference
+        let mut map = self.locks.shard(&self.id).lock().unwrap_or_else(PoisonError::into_inner);
+        if map.get(&self.id).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            map.remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn len(locks: &KeyLocks) -> usize {
+        locks.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+
+    #[tokio::test]
+    async fn cancelled_wait() {
+        let locks = KeyLocks::default();
+
+        let guard = locks.read("bucket", "key").await;
+        let mut wait = Box::pin(locks.write("bucket", "key"));
+        assert!(futures::poll!(&mut wait).is_pending());
+        assert_eq!(len(&locks), 1);
+
+        // the lock is released while the wait holds it, then the wait is cancelled
+        drop(guard);
+        assert_eq!(len(&locks), 1);
+        drop(wait);
+        assert_eq!(len(&locks), 0);
+
+        let guard = locks.write("bucket", "key").await;
+        let mut wait = Box::pin(locks.read("bucket", "key"));
+        assert!(futures::poll!(&mut wait).is_pending());
+        drop(wait);
+        drop(guard);
+        assert_eq!(len(&locks), 0);
+    }
+}
@@ -0,0 +1,182 @@
+//! Mapping of object keys to files in bucket directories
+
+use std::fmt::{self, Write};
+use std::ops::Not;
+use std::path::{Component, Path, PathBuf};
+
+/// Maps the keys of objects to the paths of their files in the directories of their buckets,
+/// which lets a [`FileSystem`](crate::FileSystem) serve an existing directory layout.
+///
+/// Different keys must be mapped to different paths, and [`KeyMapper::object_key`] must reverse
+/// [`KeyMapper::object_path`], because listings walk the bucket directories.
+/// A path which resolves outside of the directory of the bucket is rejected with `InvalidArgument`.
+///
+/// The file of a key which ends with `/` is a marker in the directory at the path of the key,
+/// unless the mapping stores any key, see [`KeyMapper::stores_any_key`].
+///
+/// The sidecar files of an object, such as its metadata, are named after its bucket and key
+/// instead of its path, so they follow the object wherever its file is.
+pub trait KeyMapper: fmt::Debug + Send + Sync + 'static {
+    /// Returns the path of the file of an object, relative to the directory of its bucket.
+    fn object_path(&self, bucket: &str, key: &str) -> PathBuf;
+
+    /// Returns the key of the object whose file is at `path`, relative to the directory of its bucket,
+    /// or `None` if the file is not an object.
+    fn object_key(&self, bucket: &str, path: &Path) -> Option<String>;
+
+    /// Returns whether the directory at `path`, relative to the directory of the bucket,
+    /// may contain objects whose keys start with `prefix`, so that listings can skip other directories.
+    ///
+    /// The default implementation returns `true`.
+    fn may_contain_prefix(&self, bucket: &str, path: &Path, prefix: &str) -> bool {
+        let _ = (bucket, path, prefix);
+        true
+    }
+
+    /// Returns whether different keys are always mapped to different files, which are not directories of other keys,
+    /// including keys which start with `/`, contain `.` or `..` segments, or end with the name of the markers
+    /// of directories. Such keys are rejected with `InvalidArgument` otherwise.
+    ///
+    /// The file of a key which ends with `/` is then at the path of the key instead of a marker in its directory.
+    ///
+    /// The default implementation returns `false`.
+    fn stores_any_key(&self) -> bool {
+        false
+    }
+}
+
+/// The default mapping, which stores the object `a/b` of `bucket` at `<root>/bucket/a/b`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultKeyMapper;
+
+impl KeyMapper for DefaultKeyMapper {
+    fn object_path(&self, _: &str, key: &str) -> PathBuf {
+        PathBuf::from(key)
+    }
+
+    fn object_key(&self, _: &str, path: &Path) -> Option<String> {
+        normalize_path(path, "/")
+    }
+
+    fn may_contain_prefix(&self, _: &str, path: &Path, prefix: &str) -> bool {
+        let Some(key) = normalize_path(path, "/") else { return false };
+        let dir_key = format!("{key}/");
+        dir_key.starts_with(prefix) || prefix.starts_with(&dir_key)
+    }
+}
+
+/// A mapping which percent-encodes each key as a single file name, so that any key is stored and listed losslessly,
+/// such as keys with control characters, and keys which are prefixes of other keys like `a`, `a/` and `a/b`.
+///
+/// The object `a/b c` of `bucket` is stored at `<root>/bucket/a%2Fb c`. `%`, `/`, control characters,
+/// non-ASCII characters and the characters which Windows does not allow in file names are encoded,
+/// as well as a leading `.` and a trailing `.` or space, so that the keys `.` and `..` are stored as `%2E`
+/// and `%2E%2E`. A key which ends with `/` is stored like other keys instead of as a directory marker.
+/// A key whose encoding is longer than a file name is split into nested directories, whose names end with `%`,
+/// and a file with the last 255 bytes. Files whose names are not escaped are not objects.
+///
+/// The mapping changes the layout on disk, so it should be used for new roots.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EscapedKeyMapper;
+
+impl KeyMapper for EscapedKeyMapper {
+    fn object_path(&self, _: &str, key: &str) -> PathBuf {
+        escape_key(key).into_iter().collect()
+    }
+
+    fn object_key(&self, _: &str, path: &Path) -> Option<String> {
+        let mut names = Vec::new();
+        for component in path.components() {
+            let Component::Normal(name) = component else { return None };
+            names.push(name.to_str()?);
+        }
+        unescape_key(&names)
+    }
+
+    fn may_contain_prefix(&self, _: &str, path: &Path, _: &str) -> bool {
+        // only the directories of long keys contain objects
+        path.components()
+            .all(|component| matches!(component, Component::Normal(name) if name.to_str().is_some_and(is_continued)))
+    }
+
+    fn stores_any_key(&self) -> bool {
+        true
+    }
+}
+
+/// The maximum length of a file name in bytes, see [`EscapedKeyMapper`]
+const MAX_NAME_LEN: usize = 255;
+
+/// Encodes a key as file names, see [`EscapedKeyMapper`]
+///
+/// An escaped name never ends with `%`, so that the names of the directories of a long key end with `%`.
+/// The last name of a long key has the maximum length, so that it is never the name of a directory marker.
+fn escape_key(key: &str) -> Vec<String> {
+    let last = key.len().saturating_sub(1);
+    let mut name = String::with_capacity(key.len());
+    for (i, b) in key.bytes().enumerate() {
+        let keep = match b {
+            b'.' => i != 0 && i != last,
+            b' ' => i != last,
+            b'%' | b'/' | b'<' | b'>' | b':' | b'"' | b'|' | b'?' | b'*' | b'\\' => false,
+            _ => b.is_ascii_graphic(),
+        };
+        if keep {
+            name.push(char::from(b));
+        } else {
+            write!(name, "%{b:02X}").unwrap();
+        }
+    }
+    if name.len() <= MAX_NAME_LEN {
+        return vec![name];
+    }
+    let (mut rest, last) = name.split_at(name.len() - MAX_NAME_LEN);
+    let mut names = Vec::new();
+    while rest.is_empty().not() {
+        let (part, tail) = rest.split_at(rest.len().min(MAX_NAME_LEN - 1));
+        names.push(format!("{part}%"));
+        rest = tail;
+    }
+    names.push(last.to_owned());
+    names
+}
+
+/// Returns whether a file name is a directory of a long key, see [`escape_key`]
+fn is_continued(name: &str) -> bool {
+    name.ends_with('%')
+}
+
+/// Decodes the file names of a key, or returns `None` if they are not escaped by [`escape_key`].
+fn unescape_key(names: &[&str]) -> Option<String> {
+    let (last, dirs) = names.split_last()?;
+    let mut name = String::new();
+    for dir in dirs {
+        name.push_str(dir.strip_suffix('%')?);
+    }
+    name.push_str(last);
+    let key = String::from_utf8(urlencoding::decode_binary(name.as_bytes()).into_owned()).ok()?;
+    // each key has a single path, so that the file of a key is always found at the same path
+    (escape_key(&key) == names).then_some(key)
+}
+
+/// Joins the components of a relative path with `delimiter`
+fn normalize_path(path: &Path, delimiter: &str) -> Option<String> {
+    let mut normalized = String::new();
+    let mut first = true;
+    for component in path.components() {
+        match component {
+            Component::RootDir | Component::CurDir | Component::ParentDir | Component::Prefix(_) => {
+                return None;
+            }
+            Component::Normal(name) => {
+                let name = name.to_str()?;
+                if !first {
+                    normalized.push_str(delimiter);
+                }
+                normalized.push_str(name);
+                first = false;
+            }
+        }
+    }
+    Some(normalized)
+}
@@ -14,10 +14,33 @@
 #[macro_use]
 mod error;
 
+mod acl;
 mod checksum;
+mod conditional;
+mod content_type;
+mod cors;
+mod dry_run;
 mod fs;
+mod key_lock;
+mod key_mapper;
+mod lifecycle;
+mod listing;
+mod object_lock;
+mod ownership;
+mod policy;
+mod request_payment;
 mod s3;
+mod select;
+mod sse;
+mod storage;
+mod tagging;
 mod utils;
+mod versioning;
+mod website;
 
+pub use self::dry_run::DRY_RUN_HEADER;
 pub use self::error::*;
-pub use self::fs::FileSystem;
+pub use self::fs::{FileSystem, FileSystemBuilder};
+pub use self::key_mapper::{DefaultKeyMapper, EscapedKeyMapper, KeyMapper};
+pub use self::listing::ListingOrder;
+pub use self::storage::{DirEntry, FileMetadata, Storage, StorageFile, TokioStorage};
@@ -0,0 +1,544 @@
+//! Bucket lifecycle configurations, stored as a sidecar file
+//!
+//! The rules are stored and returned faithfully. [`FileSystem::apply_lifecycle`] applies the expiration of current objects
+//! and the abortion of incomplete multipart uploads. Transitions and the rules of noncurrent versions are not applied.
+
+use crate::error::{Error, Result};
+use crate::fs::{load_json, save_json, FileSystem};
+use crate::tagging::TagMap;
+use crate::utils::modified_time;
+
+use s3s::dto::*;
+use s3s::{s3_error, S3ErrorCode, S3Result};
+
+use std::collections::HashSet;
+use std::io;
+use std::ops::Not;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{debug, error, info};
+
+const MAX_RULES: usize = 1000;
+
+const MAX_ID_LEN: usize = 255;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTag {
+    key: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StoredFilter {
+    And {
+        prefix: Option<String>,
+        tags: Vec<StoredTag>,
+        object_size_greater_than: i64,
+        object_size_less_than: i64,
+    },
+    ObjectSizeGreaterThan(i64),
+    ObjectSizeLessThan(i64),
+    Prefix(String),
+    Tag(StoredTag),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredExpiration {
+    date: Option<String>,
+    days: Option<i32>,
+    expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredNoncurrentExpiration {
+    newer_noncurrent_versions: i32,
+    noncurrent_days: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTransition {
+    date: Option<String>,
+    days: i32,
+    storage_class: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredNoncurrentTransition {
+    newer_noncurrent_versions: i32,
+    noncurrent_days: i32,
+    storage_class: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRule {
+    id: Option<String>,
+    status: String,
+    prefix: Option<String>,
+    filter: Option<StoredFilter>,
+    expiration: Option<StoredExpiration>,
+    abort_incomplete_multipart_upload_days: Option<i32>,
+    noncurrent_version_expiration: Option<StoredNoncurrentExpiration>,
+    transitions: Option<Vec<StoredTransition>>,
+    noncurrent_version_transitions: Option<Vec<StoredNoncurrentTransition>>,
+}
+
+fn format_date(date: &Timestamp) -> Result<String> {
+    let mut buf = Vec::new();
+    if date.format(TimestampFormat::DateTime, &mut buf).is_err() {
+        return Err(Error::request(S3ErrorCode::InvalidArgument, "The date is out of range"));
+    }
+    Ok(String::from_utf8(buf).expect("timestamps are ASCII"))
+}
+
+fn parse_date(date: &str) -> Option<Timestamp> {
+    Timestamp::parse(TimestampFormat::DateTime, date).ok()
+}
+
+fn is_midnight(date: &Timestamp) -> bool {
+    let t = OffsetDateTime::from(date.clone());
+    t.offset().is_utc() && t.time() == time::Time::MIDNIGHT
+}
+
+fn has_tags(filter: Option<&LifecycleRuleFilter>) -> bool {
+    match filter {
+        Some(LifecycleRuleFilter::Tag(_)) => true,
+        Some(LifecycleRuleFilter::And(and)) => and.tags.as_ref().is_some_and(|tags| tags.is_empty().not()),
+        _ => false,
+    }
+}
+
+/// Validates the rules of a lifecycle configuration.
+pub(crate) fn check_lifecycle_configuration(config: &BucketLifecycleConfiguration) -> S3Result<()> {
+    let rules = &config.rules;
+    if rules.is_empty() {
+        return Err(s3_error!(MalformedXML, "The lifecycle configuration must contain at least one rule"));
+    }
+    if rules.len() > MAX_RULES {
+        return Err(s3_error!(
+            MalformedXML,
+            "The lifecycle configuration must not contain more than {} rules",
+            MAX_RULES
+        ));
+    }
+
+    let mut ids = HashSet::new();
+    for rule in rules {
+        if let Some(ref id) = rule.id {
+            if id.len() > MAX_ID_LEN {
+                return Err(s3_error!(InvalidArgument, "ID length should not exceed allowed limit of {}", MAX_ID_LEN));
+            }
+            if ids.insert(id.as_str()).not() {
+                return Err(s3_error!(InvalidArgument, "Rule ID must be unique. Found same ID for more than one rule"));
+            }
+        }
+        if matches!(rule.status.as_str(), ExpirationStatus::ENABLED | ExpirationStatus::DISABLED).not() {
+            return Err(s3_error!(MalformedXML, "The status of a rule must be Enabled or Disabled"));
+        }
+        if rule.prefix.is_some() && rule.filter.is_some() {
+            return Err(s3_error!(MalformedXML, "A rule can not have both a Prefix and a Filter"));
+        }
+
+        let has_action = rule.expiration.is_some()
+            || rule.abort_incomplete_multipart_upload.is_some()
+            || rule.noncurrent_version_expiration.is_some()
+            || rule.transitions.as_ref().is_some_and(|t| t.is_empty().not())
+            || rule
+                .noncurrent_version_transitions
+                .as_ref()
+                .is_some_and(|t| t.is_empty().not());
+        if has_action.not() {
+            return Err(s3_error!(InvalidRequest, "At least one action needs to be specified in a rule"));
+        }
+
+        if let Some(ref expiration) = rule.expiration {
+            let fields = [
+                expiration.date.is_some(),
+                expiration.days.is_some(),
+                expiration.expired_object_delete_marker.is_some(),
+            ];
+            if fields.iter().filter(|&&f| f).count() != 1 {
+                return Err(s3_error!(
+                    MalformedXML,
+                    "An Expiration must have exactly one of Date, Days and ExpiredObjectDeleteMarker"
+                ));
+            }
+            if expiration.days.is_some_and(|days| days <= 0) {
+                return Err(s3_error!(InvalidArgument, "'Days' for Expiration action must be a positive integer"));
+            }
+            if expiration.date.as_ref().is_some_and(|date| is_midnight(date).not()) {
+                return Err(s3_error!(InvalidArgument, "'Date' must be at midnight GMT"));
+            }
+        }
+        if let Some(ref abort) = rule.abort_incomplete_multipart_upload {
+            if abort.days_after_initiation <= 0 {
+                return Err(s3_error!(
+                    InvalidArgument,
+                    "'DaysAfterInitiation' for AbortIncompleteMultipartUpload action must be a positive integer"
+                ));
+            }
+            if has_tags(rule.filter.as_ref()) {
+                return Err(s3_error!(InvalidRequest, "AbortIncompleteMultipartUpload cannot be specified with Tags."));
+            }
+        }
+        if let Some(ref expiration) = rule.noncurrent_version_expiration {
+            if expiration.noncurrent_days <= 0 {
+                return Err(s3_error!(
+                    InvalidArgument,
+                    "'NoncurrentDays' for NoncurrentVersionExpiration action must be a positive integer"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn store_tag(tag: Tag) -> StoredTag {
+    StoredTag {
+        key: tag.key,
+        value: tag.value,
+    }
+}
+
+fn load_tag(tag: StoredTag) -> Tag {
+    Tag {
+        key: tag.key,
+        value: tag.value,
+    }
+}
+
+fn store_filter(filter: LifecycleRuleFilter) -> Result<StoredFilter> {
+    Ok(match filter {
+        LifecycleRuleFilter::And(and) => StoredFilter::And {
+            prefix: and.prefix,
+            tags: and.tags.unwrap_or_default().into_iter().map(store_tag).collect(),
+            object_size_greater_than: and.object_size_greater_than,
+            object_size_less_than: and.object_size_less_than,
+        },
+        LifecycleRuleFilter::ObjectSizeGreaterThan(size) => StoredFilter::ObjectSizeGreaterThan(size),
+        LifecycleRuleFilter::ObjectSizeLessThan(size) => StoredFilter::ObjectSizeLessThan(size),
+        LifecycleRuleFilter::Prefix(prefix) => StoredFilter::Prefix(prefix),
+        LifecycleRuleFilter::Tag(tag) => StoredFilter::Tag(store_tag(tag)),
+        _ => return Err(Error::request(S3ErrorCode::InvalidArgument, "The lifecycle filter is not supported")),
+    })
+}
+
+fn store_rule(rule: LifecycleRule) -> Result<StoredRule> {
+    let filter = rule.filter.map(store_filter).transpose()?;
+    let expiration = match rule.expiration {
+        Some(e) => Some(StoredExpiration {
+            date: e.date.as_ref().map(format_date).transpose()?,
+            days: e.days,
+            expired_object_delete_marker: e.expired_object_delete_marker,
+        }),
+        None => None,
+    };
+    let transitions = match rule.transitions {
+        Some(transitions) => Some(
+            transitions
+                .into_iter()
+                .map(|t| {
+                    Ok(StoredTransition {
+                        date: t.date.as_ref().map(format_date).transpose()?,
+                        days: t.days,
+                        storage_class: t.storage_class.map(|s| s.as_str().to_owned()),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        ),
+        None => None,
+    };
+    let noncurrent_version_transitions = rule.noncurrent_version_transitions.map(|transitions| {
+        transitions
+            .into_iter()
+            .map(|t| StoredNoncurrentTransition {
+                newer_noncurrent_versions: t.newer_noncurrent_versions,
+                noncurrent_days: t.noncurrent_days,
+                storage_class: t.storage_class.map(|s| s.as_str().to_owned()),
+            })
+            .collect()
+    });
+    Ok(StoredRule {
+        id: rule.id,
+        status: rule.status.as_str().to_owned(),
+        prefix: rule.prefix,
+        filter,
+        expiration,
+        abort_incomplete_multipart_upload_days: rule.abort_incomplete_multipart_upload.map(|a| a.days_after_initiation),
+        noncurrent_version_expiration: rule.noncurrent_version_expiration.map(|e| StoredNoncurrentExpiration {
+            newer_noncurrent_versions: e.newer_noncurrent_versions,
+            noncurrent_days: e.noncurrent_days,
+        }),
+        transitions,
+        noncurrent_version_transitions,
+    })
+}
+
+fn load_rule(rule: StoredRule) -> LifecycleRule {
+    let filter = rule.filter.map(|filter| match filter {
+        StoredFilter::And {
+            prefix,
+            tags,
+            object_size_greater_than,
+            object_size_less_than,
+        } => LifecycleRuleFilter::And(LifecycleRuleAndOperator {
+            prefix,
+            tags: Some(tags.into_iter().map(load_tag).collect()),
+            object_size_greater_than,
+            object_size_less_than,
+        }),
+        StoredFilter::ObjectSizeGreaterThan(size) => LifecycleRuleFilter::ObjectSizeGreaterThan(size),
+        StoredFilter::ObjectSizeLessThan(size) => LifecycleRuleFilter::ObjectSizeLessThan(size),
+        StoredFilter::Prefix(prefix) => LifecycleRuleFilter::Prefix(prefix),
+        StoredFilter::Tag(tag) => LifecycleRuleFilter::Tag(load_tag(tag)),
+    });
+    LifecycleRule {
+        abort_incomplete_multipart_upload: rule
+            .abort_incomplete_multipart_upload_days
+            .map(|days_after_initiation| AbortIncompleteMultipartUpload { days_after_initiation }),
+        expiration: rule.expiration.map(|e| LifecycleExpiration {
+            date: e.date.as_deref().and_then(parse_date),
+            days: e.days,
+            expired_object_delete_marker: e.expired_object_delete_marker,
+        }),
+        filter,
+        id: rule.id,
+        noncurrent_version_expiration: rule.noncurrent_version_expiration.map(|e| NoncurrentVersionExpiration {
+            newer_noncurrent_versions: e.newer_noncurrent_versions,
+            noncurrent_days: e.noncurrent_days,
+        }),
+        noncurrent_version_transitions: rule.noncurrent_version_transitions.map(|transitions| {
+            transitions
+                .into_iter()
+                .map(|t| NoncurrentVersionTransition {
+                    newer_noncurrent_versions: t.newer_noncurrent_versions,
+                    noncurrent_days: t.noncurrent_days,
+                    storage_class: t.storage_class.map(TransitionStorageClass::from),
+                })
+                .collect()
+        }),
+        prefix: rule.prefix,
+        status: ExpirationStatus::from(rule.status),
+        transitions: rule.transitions.map(|transitions| {
+            transitions
+                .into_iter()
+                .map(|t| Transition {
+                    date: t.date.as_deref().and_then(parse_date),
+                    days: t.days,
+                    storage_class: t.storage_class.map(TransitionStorageClass::from),
+                })
+                .collect()
+        }),
+    }
+}
+
+/// Returns the duration of a positive number of days
+fn days(days: i32) -> Option<Duration> {
+    let days = u64::try_from(days).ok().filter(|&d| d > 0)?;
+    Some(Duration::from_secs(days * SECONDS_PER_DAY))
+}
+
+impl StoredRule {
+    fn is_enabled(&self) -> bool {
+        self.status == ExpirationStatus::ENABLED
+    }
+
+    /// The key prefix of the rule, which is empty if the rule applies to all objects
+    fn key_prefix(&self) -> &str {
+        let prefix = match self.filter {
+            Some(StoredFilter::Prefix(ref prefix)) => Some(prefix),
+            Some(StoredFilter::And { ref prefix, .. }) => prefix.as_ref(),
+            _ => self.prefix.as_ref(),
+        };
+        prefix.map_or("", String::as_str)
+    }
+
+    fn has_tags(&self) -> bool {
+        match self.filter {
+            Some(StoredFilter::Tag(_)) => true,
+            Some(StoredFilter::And { ref tags, .. }) => tags.is_empty().not(),
+            _ => false,
+        }
+    }
+
+    fn matches(&self, key: &str, size: u64, tags: &TagMap) -> bool {
+        if key.starts_with(self.key_prefix()).not() {
+            return false;
+        }
+        let size = i64::try_from(size).unwrap_or(i64::MAX);
+        let has_tag = |tag: &StoredTag| tags.get(&tag.key) == Some(&tag.value);
+        match self.filter {
+            Some(StoredFilter::ObjectSizeGreaterThan(min)) => size > min,
+            Some(StoredFilter::ObjectSizeLessThan(max)) => size < max,
+            Some(StoredFilter::Tag(ref tag)) => has_tag(tag),
+            Some(StoredFilter::And {
+                ref tags,
+                object_size_greater_than,
+                object_size_less_than,
+                ..
+            }) => {
+                let min_ok = object_size_greater_than <= 0 || size > object_size_greater_than;
+                let max_ok = object_size_less_than <= 0 || size < object_size_less_than;
+                min_ok && max_ok && tags.iter().all(has_tag)
+            }
+            Some(StoredFilter::Prefix(_)) | None => true,
+        }
+    }
+
+    /// Whether an object created at `created` is expired at `now`
+    fn is_expired(&self, created: SystemTime, now: SystemTime) -> bool {
+        let Some(ref expiration) = self.expiration else { return false };
+        if let Some(days) = expiration.days.and_then(days) {
+            return now.duration_since(created).is_ok_and(|age| age >= days);
+        }
+        if let Some(date) = expiration.date.as_deref().and_then(parse_date) {
+            return now >= SystemTime::from(OffsetDateTime::from(date));
+        }
+        false
+    }
+}
+
+impl FileSystem {
+    pub(crate) async fn load_bucket_lifecycle(&self, bucket: &str) -> Result<Option<Vec<LifecycleRule>>> {
+        let path = self.get_bucket_lifecycle_path(bucket)?;
+        let Some(rules) = load_json::<Vec<StoredRule>>(&*self.storage, &path).await? else {
+            return Ok(None);
+        };
+        Ok(Some(rules.into_iter().map(load_rule).collect()))
+    }
+
+    pub(crate) async fn save_bucket_lifecycle(&self, bucket: &str, config: BucketLifecycleConfiguration) -> Result<()> {
+        let rules = config.rules.into_iter().map(store_rule).collect::<Result<Vec<_>>>()?;
+        let path = self.get_bucket_lifecycle_path(bucket)?;
+        save_json(&*self.storage, &path, &rules).await
+    }
+
+    pub(crate) async fn remove_bucket_lifecycle(&self, bucket: &str) -> Result<()> {
+        let path = self.get_bucket_lifecycle_path(bucket)?;
+        match self.storage.remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Applies the enabled lifecycle rules of all buckets once.
+    ///
+    /// Current objects past their expiration are deleted like `DeleteObject`,
+    /// which adds a delete marker in a versioned bucket. Objects protected by Object Lock are kept.
+    /// Multipart uploads initiated more than `DaysAfterInitiation` days ago are aborted.
+    ///
+    /// The age of an object is the time since its file was last modified.
+    ///
+    /// Returns the number of expired objects and aborted uploads.
+    ///
+    /// # Errors
+    /// Returns an `AccessDenied` error if the file system is read-only.
+    pub async fn apply_lifecycle(&self) -> Result<usize> {
+        self.check_writable()?;
+
+        let now = SystemTime::now();
+        let mut count = 0;
+        for entry in self.storage.read_dir(&self.root).await? {
+            if entry.is_dir.not() {
+                continue;
+            }
+            let Some(bucket) = entry.file_name() else { continue };
+            if s3s::path::check_bucket_name(bucket).not() {
+                continue;
+            }
+
+            let path = self.get_bucket_lifecycle_path(bucket)?;
+            let Some(mut rules) = load_json::<Vec<StoredRule>>(&*self.storage, &path).await? else { continue };
+            rules.retain(StoredRule::is_enabled);
+            if rules.is_empty() {
+                continue;
+            }
+
+            count += self.expire_objects(bucket, &rules, now).await?;
+            count += self.abort_expired_uploads(bucket, &rules, now).await?;
+        }
+        Ok(count)
+    }
+
+    async fn expire_objects(&self, bucket: &str, rules: &[StoredRule], now: SystemTime) -> Result<usize> {
+        let rules: Vec<&StoredRule> = rules.iter().filter(|r| r.expiration.is_some()).collect();
+        if rules.is_empty() {
+            return Ok(0);
+        }
+        let needs_tags = rules.iter().any(|r| r.has_tags());
+
+        let mut count = 0;
+        for (key, metadata) in crate::s3::list_keys(self, bucket, "").await? {
+            let created = modified_time(&metadata);
+            let tags = if needs_tags {
+                self.current_location(bucket, &key)?.load_tagging().await?
+            } else {
+                TagMap::new()
+            };
+            let is_expired = rules
+                .iter()
+                .any(|r| r.matches(&key, metadata.len, &tags) && r.is_expired(created, now));
+            if is_expired.not() {
+                continue;
+            }
+
+            if let Err(err) = crate::s3::delete_object_entry(self, bucket, &key, None, false).await {
+                debug!(%bucket, %key, ?err, "failed to expire object");
+                continue;
+            }
+            self.refresh_public_object(bucket, &key).await?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn abort_expired_uploads(&self, bucket: &str, rules: &[StoredRule], now: SystemTime) -> Result<usize> {
+        let mut count = 0;
+        for (upload_id, info) in self.list_upload_infos().await? {
+            if info.bucket != bucket {
+                continue;
+            }
+            let Some(age) = info.initiated.and_then(|t| now.duration_since(t).ok()) else { continue };
+            let is_expired = rules.iter().any(|r| {
+                let ttl = r.abort_incomplete_multipart_upload_days.and_then(days);
+                ttl.is_some_and(|ttl| age >= ttl) && info.key.starts_with(r.key_prefix())
+            });
+            if is_expired {
+                self.remove_upload(&upload_id).await?;
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Spawns a background task which calls [`FileSystem::apply_lifecycle`] every `interval`.
+    ///
+    /// The task owns a clone of the file system, which shares its state with the original.
+    /// Nothing is spawned for a read-only file system.
+    ///
+    /// # Panics
+    /// Panics if called outside of a tokio runtime.
+    pub fn spawn_lifecycle_sweeper(&self, interval: Duration) {
+        if self.read_only {
+            return;
+        }
+        let fs = self.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                timer.tick().await;
+                match fs.apply_lifecycle().await {
+                    Ok(0) => {}
+                    Ok(count) => info!(count, "applied lifecycle rules"),
+                    Err(err) => error!(?err, "failed to apply lifecycle rules"),
+                }
+            }
+        });
+    }
+}
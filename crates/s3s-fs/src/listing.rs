@@ -0,0 +1,63 @@
+//! Orders of the objects in listings, which are ascending by key in S3
+
+use crate::storage::FileMetadata;
+
+use std::collections::HashSet;
+
+/// The order of the objects of `ListObjects` and `ListObjectsV2`, see [`FileSystem::set_listing_order`](crate::FileSystem::set_listing_order)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ListingOrder {
+    /// Ascending by key, like S3
+    #[default]
+    Key,
+    /// Ascending by the time of the last modification, then by key
+    LastModified,
+}
+
+/// Returns the common prefix which rolls up `key`, if any.
+pub(crate) fn common_prefix<'k>(key: &'k str, prefix: &str, delimiter: Option<&str>) -> Option<&'k str> {
+    let delimiter = delimiter?;
+    let pos = key[prefix.len()..].find(delimiter)?;
+    Some(&key[..prefix.len() + pos + delimiter.len()])
+}
+
+/// Sorts the entries of a listing, which are sorted by key, in another order.
+///
+/// The entries are then removed up to the row of `marker`, which is the key or the common prefix
+/// last listed by the previous page. A marker which is not in the listing only removes the keys
+/// before it in the order of keys. Returns the common prefixes of the removed entries,
+/// which have already been listed.
+pub(crate) fn sort_and_skip(
+    entries: &mut Vec<(String, FileMetadata)>,
+    order: ListingOrder,
+    reverse: bool,
+    marker: &str,
+    prefix: &str,
+    delimiter: Option<&str>,
+) -> HashSet<String> {
+    if order == ListingOrder::LastModified {
+        // the sort is stable, so objects modified at the same time stay sorted by key
+        entries.sort_by_key(|(_, metadata)| metadata.modified);
+    }
+    if reverse {
+        entries.reverse();
+    }
+
+    let mut listed_prefixes = HashSet::new();
+    if marker.is_empty() {
+        return listed_prefixes;
+    }
+    let row = |key: &str| common_prefix(key, prefix, delimiter).map_or(key == marker, |p| p == marker);
+    match entries.iter().position(|(key, _)| row(key)) {
+        Some(pos) => {
+            for (key, _) in entries.drain(..=pos) {
+                if let Some(common_prefix) = common_prefix(&key, prefix, delimiter) {
+                    listed_prefixes.insert(common_prefix.to_owned());
+                }
+            }
+        }
+        None if order == ListingOrder::Key && reverse => entries.retain(|(key, _)| key.as_str() < marker),
+        None => entries.retain(|(key, _)| key.as_str() > marker),
+    }
+    listed_prefixes
+}
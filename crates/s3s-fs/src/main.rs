@@ -1,14 +1,18 @@
 #![forbid(unsafe_code)]
 #![deny(clippy::all, clippy::pedantic)]
 
+use s3s_fs::EscapedKeyMapper;
 use s3s_fs::FileSystem;
+use s3s_fs::ListingOrder;
 use s3s_fs::Result;
 
-use s3s::auth::SimpleAuth;
+use s3s::auth::{PublicBuckets, PublicObjects, SimpleAuth};
 use s3s::service::S3ServiceBuilder;
 
 use std::net::TcpListener;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::Parser;
 use hyper::server::Server;
@@ -33,10 +37,118 @@ struct Opt {
     #[arg(long, requires("access-key"))]
     secret_key: Option<String>,
 
+    /// Bucket which can be read without authentication. Can be specified multiple times.
+    #[arg(long, requires("access-key"))]
+    public_bucket: Vec<String>,
+
     /// Domain name used for virtual-hosted-style requests.
     #[arg(long)]
     domain_name: Option<String>,
 
+    /// Region of buckets created without a location constraint.
+    #[arg(long, default_value = "us-east-1")]
+    region: String,
+
+    /// Sync object data to the disk before acknowledging writes.
+    #[arg(long)]
+    durable: bool,
+
+    /// Store sidecar files under a `.s3s-meta` directory instead of the root.
+    #[arg(long)]
+    meta_dir: bool,
+
+    /// Object ownership of buckets created without `x-amz-object-ownership`.
+    /// `BucketOwnerEnforced` disables ACLs like the default of AWS.
+    #[arg(long, value_parser = ["BucketOwnerEnforced", "BucketOwnerPreferred", "ObjectWriter"])]
+    default_object_ownership: Option<String>,
+
+    /// Size in bytes of the chunks in which object files are read.
+    #[arg(long, default_value = "262144")]
+    read_buffer_size: NonZeroUsize,
+
+    /// Size in bytes of the chunks in which files are read to compute their MD5 sums.
+    #[arg(long, default_value = "65536")]
+    hash_buffer_size: NonZeroUsize,
+
+    /// Number of staged parts which are verified at once when a multipart upload is completed.
+    #[arg(long, default_value = "8")]
+    part_verify_concurrency: NonZeroUsize,
+
+    /// Maximum number of keys of a listing page, to which larger `max-keys` are clamped.
+    #[arg(long, default_value = "1000", value_parser = clap::value_parser!(i32).range(1..))]
+    max_keys: i32,
+
+    /// Maximum length in bytes of object keys, which is 1024 like AWS.
+    #[arg(long, default_value = "1024")]
+    max_key_length: NonZeroUsize,
+
+    /// Order of the objects of listings. S3 lists objects by key.
+    #[arg(long, default_value = "key", value_parser = ["key", "last-modified"])]
+    listing_order: String,
+
+    /// List objects in the reverse of the listing order.
+    #[arg(long)]
+    reverse_listings: bool,
+
+    /// Percent-encode the segments of keys in file names, so that any key can be stored.
+    /// This changes the layout of the root directory.
+    #[arg(long)]
+    escape_keys: bool,
+
+    /// Directory of temporary files of object writes, which may be on another filesystem.
+    #[arg(long)]
+    tmp_dir: Option<PathBuf>,
+
+    /// Reject objects larger than the given number of bytes with `EntityTooLarge`.
+    #[arg(long)]
+    max_object_size: Option<u64>,
+
+    /// Remove the sidecar files of deleted objects before starting.
+    #[arg(long, conflicts_with("read_only"))]
+    gc_orphans: bool,
+
+    /// Abort the multipart uploads initiated more than the given number of seconds ago before starting,
+    /// and remove the staged parts left without a record for as long.
+    #[arg(long, conflicts_with("read_only"))]
+    upload_ttl: Option<u64>,
+
+    /// Apply the lifecycle rules of buckets every given number of seconds.
+    #[arg(long, conflicts_with("read_only"))]
+    lifecycle_interval: Option<u64>,
+
+    /// Serve the root directory without modifying it. All mutating requests are denied.
+    #[arg(long)]
+    read_only: bool,
+
+    /// Redirect reads of objects which have a website redirect location, like the website endpoint of S3.
+    #[arg(long)]
+    website_redirects: bool,
+
+    /// Accept dry runs of mutating requests with the header `x-s3s-dry-run: true`,
+    /// which are validated without writing anything.
+    #[arg(long)]
+    dry_runs: bool,
+
+    /// Guess the content types of objects stored without one from the extensions of their keys.
+    #[arg(long)]
+    guess_content_types: bool,
+
+    /// Emit an access log at the info level when each request completes.
+    #[arg(long)]
+    access_log: bool,
+
+    /// Log the canonical request and the string to sign of requests whose signatures do not match, at the debug level.
+    #[arg(long)]
+    signature_debug: bool,
+
+    /// Reject requests with unsigned payloads which are not sent over TLS.
+    #[arg(long)]
+    unsigned_payload_requires_tls: bool,
+
+    /// Allow CORS requests which no CORS rule of the bucket allows, for local browser testing.
+    #[arg(long)]
+    cors_allow_all: bool,
+
     /// Root directory of stored data.
     root: PathBuf,
 }
@@ -75,8 +187,59 @@ fn main() -> Result {
 
 #[tokio::main]
 async fn run(opt: Opt) -> Result {
+    // The buckets which can be read anonymously, which are also updated by bucket ACLs
+    let public_buckets = PublicBuckets::new();
+    for bucket in opt.public_bucket {
+        public_buckets.insert(bucket);
+    }
+    // The objects which can be read anonymously, which are updated by object ACLs
+    let public_objects = PublicObjects::new();
+
     // Setup S3 provider
-    let fs = FileSystem::new(opt.root)?;
+    let mut builder = FileSystem::builder(opt.root)
+        .region(opt.region)
+        .durable(opt.durable)
+        .read_only(opt.read_only)
+        .meta_dir(opt.meta_dir)
+        .website_redirects(opt.website_redirects)
+        .dry_runs(opt.dry_runs)
+        .guess_content_types(opt.guess_content_types)
+        .read_buffer_size(opt.read_buffer_size.get())
+        .hash_buffer_size(opt.hash_buffer_size.get())
+        .part_verify_concurrency(opt.part_verify_concurrency.get())
+        .max_keys(opt.max_keys)
+        .max_key_length(opt.max_key_length.get())
+        .listing_order(match opt.listing_order.as_str() {
+            "last-modified" => ListingOrder::LastModified,
+            _ => ListingOrder::Key,
+        })
+        .reverse_listings(opt.reverse_listings)
+        .public_buckets(public_buckets.clone())
+        .public_objects(public_objects.clone());
+    if opt.escape_keys {
+        builder = builder.key_mapper(EscapedKeyMapper);
+    }
+    if let Some(ref tmp_dir) = opt.tmp_dir {
+        builder = builder.tmp_dir(tmp_dir);
+    }
+    if let Some(size) = opt.max_object_size {
+        builder = builder.max_object_size(size);
+    }
+    if let Some(ownership) = opt.default_object_ownership {
+        builder = builder.default_object_ownership(ownership);
+    }
+    let fs = builder.build()?;
+    if opt.gc_orphans {
+        let count = fs.gc_orphans().await?;
+        info!("removed {count} orphaned sidecar files");
+    }
+    if let Some(ttl) = opt.upload_ttl {
+        let count = fs.reap_uploads(Duration::from_secs(ttl)).await?;
+        info!("aborted {count} expired multipart uploads");
+    }
+    if let Some(secs) = opt.lifecycle_interval {
+        fs.spawn_lifecycle_sweeper(Duration::from_secs(secs));
+    }
 
     // Setup S3 service
     let service = {
@@ -84,7 +247,10 @@ async fn run(opt: Opt) -> Result {
 
         // Enable authentication
         if let (Some(ak), Some(sk)) = (opt.access_key, opt.secret_key) {
-            b.set_auth(SimpleAuth::from_single(ak, sk));
+            let mut auth = SimpleAuth::from_single(ak, sk);
+            auth.set_public_buckets(public_buckets);
+            auth.set_public_objects(public_objects);
+            b.set_auth(auth);
         }
 
         // Enable parsing virtual-hosted-style requests
@@ -92,6 +258,16 @@ async fn run(opt: Opt) -> Result {
             b.set_base_domain(domain_name);
         }
 
+        if opt.access_log {
+            b.set_access_log(tracing::Level::INFO);
+        }
+
+        b.set_signature_debug(opt.signature_debug);
+        b.set_unsigned_payload_requires_tls(opt.unsigned_payload_requires_tls);
+        if opt.cors_allow_all {
+            b.set_cors_preflight_default(s3s::service::CorsPreflightDefault::AllowAll);
+        }
+
         b.build()
     };
 
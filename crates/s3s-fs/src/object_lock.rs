@@ -0,0 +1,313 @@
+//! Object Lock: retention periods and legal holds of object versions
+//!
+//! The lock of a version is stored in its internal info.
+//! The lock configuration of a bucket is stored in the bucket config.
+
+use crate::fs::{BucketConfig, FileSystem, InternalInfo};
+
+use s3s::dto::*;
+use s3s::{s3_error, S3Error, S3Result};
+
+use std::ops::Not;
+
+use time::{Duration, OffsetDateTime};
+
+/// A retention period of an object version
+#[derive(Debug, Clone)]
+pub(crate) struct Retention {
+    pub(crate) mode: ObjectLockRetentionMode,
+    pub(crate) retain_until: OffsetDateTime,
+}
+
+/// The lock of an object version
+#[derive(Debug, Default)]
+pub(crate) struct ObjectLock {
+    pub(crate) retention: Option<Retention>,
+    /// `None` if a legal hold has never been set
+    pub(crate) legal_hold: Option<bool>,
+}
+
+fn object_locked() -> S3Error {
+    s3_error!(AccessDenied, "Access Denied because object protected by object lock.")
+}
+
+fn parse_mode(mode: &str) -> S3Result<ObjectLockRetentionMode> {
+    match mode {
+        ObjectLockRetentionMode::GOVERNANCE | ObjectLockRetentionMode::COMPLIANCE => {
+            Ok(ObjectLockRetentionMode::from(mode.to_owned()))
+        }
+        _ => Err(s3_error!(InvalidArgument, "Unknown wormMode directive.")),
+    }
+}
+
+fn parse_legal_hold(status: &str) -> S3Result<bool> {
+    match status {
+        ObjectLockLegalHoldStatus::ON => Ok(true),
+        ObjectLockLegalHoldStatus::OFF => Ok(false),
+        _ => Err(s3_error!(InvalidArgument, "Legal Hold must be either of 'ON' or 'OFF'")),
+    }
+}
+
+fn format_date(t: OffsetDateTime) -> S3Result<String> {
+    let mut buf = Vec::new();
+    if Timestamp::from(t).format(TimestampFormat::DateTime, &mut buf).is_err() {
+        return Err(s3_error!(InvalidArgument, "The retain until date is out of range"));
+    }
+    Ok(String::from_utf8(buf).expect("timestamps are ASCII"))
+}
+
+impl Retention {
+    /// Validates a retention period of a request.
+    ///
+    /// The mode and the date must be specified together.
+    pub(crate) fn from_request(mode: Option<&str>, retain_until: Option<&Timestamp>) -> S3Result<Option<Self>> {
+        let (mode, retain_until) = match (mode, retain_until) {
+            (None, None) => return Ok(None),
+            (Some(mode), Some(retain_until)) => (mode, retain_until),
+            _ => {
+                return Err(s3_error!(
+                    InvalidArgument,
+                    "x-amz-object-lock-retain-until-date and x-amz-object-lock-mode must both be supplied"
+                ))
+            }
+        };
+        let mode = parse_mode(mode)?;
+        let retain_until: OffsetDateTime = retain_until.clone().into();
+        if retain_until <= OffsetDateTime::now_utc() {
+            return Err(s3_error!(InvalidArgument, "The retain until date must be in the future!"));
+        }
+        Ok(Some(Self { mode, retain_until }))
+    }
+
+    fn is_active(&self) -> bool {
+        self.retain_until > OffsetDateTime::now_utc()
+    }
+
+    fn is_compliance(&self) -> bool {
+        self.mode.as_str() == ObjectLockRetentionMode::COMPLIANCE
+    }
+
+    /// Whether `other` keeps at least the protection of `self`
+    fn is_covered_by(&self, other: &Self) -> bool {
+        let mode_kept = other.mode.as_str() == self.mode.as_str() || other.is_compliance();
+        mode_kept && other.retain_until >= self.retain_until
+    }
+
+    pub(crate) fn to_dto(&self) -> ObjectLockRetention {
+        ObjectLockRetention {
+            mode: Some(self.mode.clone()),
+            retain_until_date: Some(Timestamp::from(self.retain_until)),
+        }
+    }
+}
+
+impl ObjectLock {
+    pub(crate) fn from_internal_info(info: Option<&InternalInfo>) -> Self {
+        let Some(info) = info else { return Self::default() };
+        let get_str = |name: &str| info.get(name).and_then(|v| v.as_str());
+
+        let retention = (|| {
+            let mode = ObjectLockRetentionMode::from(get_str("object_lock_mode")?.to_owned());
+            let retain_until = Timestamp::parse(TimestampFormat::DateTime, get_str("object_lock_retain_until_date")?).ok()?;
+            Some(Retention {
+                mode,
+                retain_until: retain_until.into(),
+            })
+        })();
+        let legal_hold = get_str("object_lock_legal_hold").map(|s| s == ObjectLockLegalHoldStatus::ON);
+
+        Self { retention, legal_hold }
+    }
+
+    pub(crate) fn modify_internal_info(&self, info: &mut InternalInfo) -> S3Result<()> {
+        info.remove("object_lock_mode");
+        info.remove("object_lock_retain_until_date");
+        info.remove("object_lock_legal_hold");
+
+        if let Some(ref retention) = self.retention {
+            info.insert("object_lock_mode".to_owned(), retention.mode.as_str().into());
+            info.insert("object_lock_retain_until_date".to_owned(), format_date(retention.retain_until)?.into());
+        }
+        if let Some(status) = self.legal_hold_status() {
+            info.insert("object_lock_legal_hold".to_owned(), status.as_str().into());
+        }
+        Ok(())
+    }
+
+    /// Checks whether the version may be deleted or overwritten.
+    ///
+    /// A governance retention can be bypassed by authenticated requests.
+    pub(crate) fn check_mutable(&self, bypass_governance: bool) -> S3Result<()> {
+        if self.legal_hold == Some(true) {
+            return Err(object_locked());
+        }
+        if let Some(ref retention) = self.retention {
+            if retention.is_active() && (retention.is_compliance() || bypass_governance.not()) {
+                return Err(object_locked());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether the retention may be replaced.
+    ///
+    /// An active retention can only be extended, unless a governance retention is bypassed.
+    pub(crate) fn check_retention_change(&self, new: Option<&Retention>, bypass_governance: bool) -> S3Result<()> {
+        let Some(ref old) = self.retention else { return Ok(()) };
+        if old.is_active().not() || new.is_some_and(|new| old.is_covered_by(new)) {
+            return Ok(());
+        }
+        if old.is_compliance() || bypass_governance.not() {
+            return Err(object_locked());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn mode(&self) -> Option<ObjectLockMode> {
+        self.retention
+            .as_ref()
+            .map(|r| ObjectLockMode::from(r.mode.as_str().to_owned()))
+    }
+
+    pub(crate) fn retain_until_date(&self) -> Option<Timestamp> {
+        self.retention.as_ref().map(|r| Timestamp::from(r.retain_until))
+    }
+
+    pub(crate) fn legal_hold_status(&self) -> Option<ObjectLockLegalHoldStatus> {
+        let status = if self.legal_hold? {
+            ObjectLockLegalHoldStatus::ON
+        } else {
+            ObjectLockLegalHoldStatus::OFF
+        };
+        Some(ObjectLockLegalHoldStatus::from(status.to_owned()))
+    }
+}
+
+/// Validates an Object Lock configuration and records it in the bucket config.
+pub(crate) fn set_lock_configuration(config: &mut BucketConfig, lock_config: &ObjectLockConfiguration) -> S3Result<()> {
+    if lock_config.object_lock_enabled.as_ref().map(ObjectLockEnabled::as_str) != Some(ObjectLockEnabled::ENABLED) {
+        return Err(s3_error!(MalformedXML, "ObjectLockEnabled must be Enabled"));
+    }
+
+    config.insert("object_lock_enabled".to_owned(), true.into());
+    config.remove("object_lock_default_mode");
+    config.remove("object_lock_default_days");
+    config.remove("object_lock_default_years");
+
+    let Some(retention) = lock_config.rule.as_ref().and_then(|r| r.default_retention.as_ref()) else {
+        return Ok(());
+    };
+
+    let Some(ref mode) = retention.mode else {
+        return Err(s3_error!(MalformedXML, "The default retention must specify a mode"));
+    };
+    let mode = parse_mode(mode.as_str())?;
+    let (name, value) = match (retention.days, retention.years) {
+        (days, 0) if days > 0 => ("object_lock_default_days", days),
+        (0, years) if years > 0 => ("object_lock_default_years", years),
+        (0, 0) => return Err(s3_error!(MalformedXML, "The default retention must specify either Days or Years")),
+        _ => {
+            return Err(s3_error!(
+                InvalidArgument,
+                "The default retention period must be a positive number of either Days or Years"
+            ))
+        }
+    };
+    config.insert("object_lock_default_mode".to_owned(), mode.as_str().into());
+    config.insert(name.to_owned(), value.into());
+    Ok(())
+}
+
+/// Returns the Object Lock configuration of a bucket, or `None` if Object Lock is not enabled.
+pub(crate) fn get_lock_configuration(config: &BucketConfig) -> Option<ObjectLockConfiguration> {
+    if config.get("object_lock_enabled").and_then(serde_json::Value::as_bool) != Some(true) {
+        return None;
+    }
+
+    let get_i32 = |name: &str| {
+        config
+            .get(name)
+            .and_then(serde_json::Value::as_i64)
+            .and_then(|v| i32::try_from(v).ok())
+    };
+    let rule = config
+        .get("object_lock_default_mode")
+        .and_then(|v| v.as_str())
+        .map(|mode| ObjectLockRule {
+            default_retention: Some(DefaultRetention {
+                mode: Some(ObjectLockRetentionMode::from(mode.to_owned())),
+                days: get_i32("object_lock_default_days").unwrap_or(0),
+                years: get_i32("object_lock_default_years").unwrap_or(0),
+            }),
+        });
+
+    Some(ObjectLockConfiguration {
+        object_lock_enabled: Some(ObjectLockEnabled::from(ObjectLockEnabled::ENABLED.to_owned())),
+        rule,
+    })
+}
+
+fn default_retention(lock_config: &ObjectLockConfiguration) -> Option<Retention> {
+    let retention = lock_config.rule.as_ref()?.default_retention.as_ref()?;
+    let mode = retention.mode.clone()?;
+    let period = if retention.years > 0 {
+        Duration::days(365 * i64::from(retention.years))
+    } else {
+        Duration::days(i64::from(retention.days))
+    };
+    Some(Retention {
+        mode,
+        retain_until: OffsetDateTime::now_utc() + period,
+    })
+}
+
+fn missing_lock_configuration() -> S3Error {
+    s3_error!(InvalidRequest, "Bucket is missing Object Lock Configuration")
+}
+
+impl FileSystem {
+    pub(crate) async fn load_object_lock_configuration(&self, bucket: &str) -> S3Result<Option<ObjectLockConfiguration>> {
+        let config = self.load_bucket_config(bucket).await?.unwrap_or_default();
+        Ok(get_lock_configuration(&config))
+    }
+
+    /// Fails if Object Lock is not enabled for the bucket.
+    pub(crate) async fn require_object_lock(&self, bucket: &str) -> S3Result<ObjectLockConfiguration> {
+        self.load_object_lock_configuration(bucket)
+            .await?
+            .ok_or_else(missing_lock_configuration)
+    }
+
+    /// Resolves the lock of a new object from the request headers and the default retention of the bucket.
+    pub(crate) async fn new_object_lock(
+        &self,
+        bucket: &str,
+        mode: Option<&ObjectLockMode>,
+        retain_until: Option<&Timestamp>,
+        legal_hold: Option<&ObjectLockLegalHoldStatus>,
+    ) -> S3Result<ObjectLock> {
+        let Some(lock_config) = self.load_object_lock_configuration(bucket).await? else {
+            if mode.is_some() || retain_until.is_some() || legal_hold.is_some() {
+                return Err(missing_lock_configuration());
+            }
+            return Ok(ObjectLock::default());
+        };
+
+        let retention = match Retention::from_request(mode.map(ObjectLockMode::as_str), retain_until)? {
+            Some(retention) => Some(retention),
+            None => default_retention(&lock_config),
+        };
+        let legal_hold = match legal_hold {
+            Some(status) => Some(parse_legal_hold(status.as_str())?),
+            None => None,
+        };
+        Ok(ObjectLock { retention, legal_hold })
+    }
+}
+
+pub(crate) fn legal_hold_from_request(legal_hold: Option<&ObjectLockLegalHold>) -> S3Result<bool> {
+    let Some(status) = legal_hold.and_then(|h| h.status.as_ref()) else {
+        return Err(s3_error!(MalformedXML, "The legal hold must specify a status"));
+    };
+    parse_legal_hold(status.as_str())
+}
@@ -0,0 +1,138 @@
+//! Object ownership of buckets, stored in the bucket config
+//!
+//! `BucketOwnerEnforced` disables ACLs: requests which set an ACL other than the one of the bucket owner
+//! are rejected with `AccessControlListNotSupported`, and the stored ACLs of objects do not allow anonymous reads.
+//! `BucketOwnerPreferred` and `ObjectWriter` keep ACLs enabled, which is also the case of buckets without ownership controls.
+
+use crate::error::*;
+use crate::fs::{BucketConfig, FileSystem};
+
+use s3s::dto::*;
+use s3s::{s3_error, S3Error, S3ErrorCode, S3Result};
+
+use std::ops::Not;
+
+use hyper::StatusCode;
+
+const OWNERSHIPS: &[&str] = &[
+    ObjectOwnership::BUCKET_OWNER_ENFORCED,
+    ObjectOwnership::BUCKET_OWNER_PREFERRED,
+    ObjectOwnership::OBJECT_WRITER,
+];
+
+pub(crate) fn is_valid_object_ownership(ownership: &str) -> bool {
+    OWNERSHIPS.contains(&ownership)
+}
+
+pub(crate) fn check_object_ownership(ownership: &ObjectOwnership) -> S3Result<()> {
+    if is_valid_object_ownership(ownership.as_str()) {
+        return Ok(());
+    }
+    Err(s3_error!(
+        InvalidArgument,
+        "Invalid x-amz-object-ownership header: {}",
+        ownership.as_str()
+    ))
+}
+
+/// Validates the rules of `PutBucketOwnershipControls`, which must contain exactly one rule
+pub(crate) fn check_ownership_controls(controls: &OwnershipControls) -> S3Result<()> {
+    let [rule] = controls.rules.as_slice() else {
+        return Err(s3_error!(MalformedXML, "The ownership controls must contain exactly one rule"));
+    };
+    check_object_ownership(&rule.object_ownership)
+}
+
+pub(crate) fn get_object_ownership(config: &BucketConfig) -> Option<&str> {
+    config.get("object_ownership").and_then(serde_json::Value::as_str)
+}
+
+pub(crate) fn set_object_ownership(config: &mut BucketConfig, ownership: Option<&str>) {
+    match ownership {
+        Some(ownership) => {
+            config.insert("object_ownership".to_owned(), serde_json::Value::String(ownership.to_owned()));
+        }
+        None => {
+            config.remove("object_ownership");
+        }
+    }
+}
+
+/// Whether the ACLs of a bucket and its objects are enabled
+pub(crate) fn acls_enabled(config: Option<&BucketConfig>) -> bool {
+    config.and_then(get_object_ownership) != Some(ObjectOwnership::BUCKET_OWNER_ENFORCED)
+}
+
+pub(crate) fn ownership_controls_not_found() -> S3Error {
+    let code = S3ErrorCode::Custom("OwnershipControlsNotFoundError".into());
+    let mut err = S3Error::with_message(code, "The bucket ownership controls were not found");
+    err.set_status_code(StatusCode::NOT_FOUND);
+    err
+}
+
+fn acl_not_supported() -> S3Error {
+    let code = S3ErrorCode::Custom("AccessControlListNotSupported".into());
+    let mut err = S3Error::with_message(code, "The bucket does not allow ACLs");
+    err.set_status_code(StatusCode::BAD_REQUEST);
+    err
+}
+
+/// Rejects the ACL of `CreateBucket` if the new bucket disables ACLs.
+pub(crate) fn check_create_bucket_acl(ownership: Option<&str>, canned: Option<&str>, has_grants: bool) -> S3Result<()> {
+    if ownership != Some(ObjectOwnership::BUCKET_OWNER_ENFORCED) {
+        return Ok(());
+    }
+    if has_grants || canned.is_some_and(|canned| canned != BucketCannedACL::PRIVATE) {
+        let code = S3ErrorCode::Custom("InvalidBucketAclWithObjectOwnership".into());
+        let mut err =
+            S3Error::with_message(code, "Bucket cannot have ACLs set with ObjectOwnership's BucketOwnerEnforced setting");
+        err.set_status_code(StatusCode::BAD_REQUEST);
+        return Err(err);
+    }
+    Ok(())
+}
+
+impl FileSystem {
+    /// Rejects a request which sets an ACL if the bucket disables ACLs.
+    ///
+    /// `allowed` is the canned ACL which is the same as having no ACL, such as `bucket-owner-full-control` for objects.
+    pub(crate) async fn check_acl_allowed(
+        &self,
+        bucket: &str,
+        canned: Option<&str>,
+        has_grants: bool,
+        allowed: &str,
+    ) -> S3Result<()> {
+        if canned.is_none() && has_grants.not() {
+            return Ok(());
+        }
+        let config = self.load_bucket_config(bucket).await?;
+        if acls_enabled(config.as_ref()) {
+            return Ok(());
+        }
+        if has_grants || canned != Some(allowed) {
+            return Err(acl_not_supported());
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn bucket_acls_enabled(&self, bucket: &str) -> Result<bool> {
+        let config = self.load_bucket_config(bucket).await?;
+        Ok(acls_enabled(config.as_ref()))
+    }
+
+    /// Saves the object ownership of a bucket, or removes it if `ownership` is `None`,
+    /// and publishes or withdraws the objects whose ACLs allow anonymous reads.
+    pub(crate) async fn save_object_ownership(&self, bucket: &str, ownership: Option<&str>) -> S3Result<()> {
+        let mut config = self.load_bucket_config(bucket).await?.unwrap_or_default();
+        set_object_ownership(&mut config, ownership);
+        self.save_bucket_config(bucket, &config).await?;
+
+        if self.public_objects.is_some() {
+            for (key, _) in crate::s3::list_keys(self, bucket, "").await? {
+                self.refresh_public_object(bucket, &key).await?;
+            }
+        }
+        Ok(())
+    }
+}
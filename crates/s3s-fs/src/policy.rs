@@ -0,0 +1,251 @@
+//! Bucket policies
+//!
+//! Only explicit `Deny` statements are enforced, because anything else is decided by the authentication provider.
+//! Statements with a `Condition` are stored but never match.
+//! The policy operations themselves are not subject to the policy, so that a bucket can not be locked out.
+
+use crate::error::*;
+use crate::fs::{write_atomic, FileSystem};
+
+use s3s::auth::Credentials;
+use s3s::{s3_error, S3Result};
+
+use std::io;
+use std::ops::Not;
+
+use serde_json::Value;
+
+const VERSIONS: &[&str] = &["2012-10-17", "2008-10-17"];
+
+const ARN_PREFIX: &str = "arn:aws:s3:::";
+
+/// The actions which are allowed in read-only mode
+const READ_ACTIONS: &[&str] = &[
+    "s3:GetBucketAcl",
+    "s3:GetBucketCORS",
+    "s3:GetBucketLocation",
+    "s3:GetBucketObjectLockConfiguration",
+    "s3:GetBucketOwnershipControls",
+    "s3:GetBucketRequestPayment",
+    "s3:GetBucketVersioning",
+    "s3:GetBucketWebsite",
+    "s3:GetLifecycleConfiguration",
+    "s3:GetObject",
+    "s3:GetObjectAcl",
+    "s3:GetObjectAttributes",
+    "s3:GetObjectLegalHold",
+    "s3:GetObjectRetention",
+    "s3:GetObjectTagging",
+    "s3:ListBucket",
+    "s3:ListBucketMultipartUploads",
+    "s3:ListBucketVersions",
+    "s3:ListMultipartUploadParts",
+];
+
+/// A parsed bucket policy
+#[derive(Debug)]
+pub(crate) struct Policy {
+    statements: Vec<Statement>,
+}
+
+#[derive(Debug)]
+struct Statement {
+    is_deny: bool,
+    principals: Vec<String>,
+    actions: Vec<String>,
+    resources: Vec<String>,
+    has_condition: bool,
+}
+
+/// Accepts a string or a non-empty array of strings
+fn string_list(value: Option<&Value>) -> Option<Vec<String>> {
+    let list = match value? {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(values) => values
+            .iter()
+            .map(|v| v.as_str().map(ToOwned::to_owned))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    list.is_empty().not().then_some(list)
+}
+
+/// Matches a pattern with `*` and `?` wildcards
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    let (p, s): (Vec<char>, Vec<char>) = (pattern.chars().collect(), s.chars().collect());
+    let (mut pi, mut si) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while si < s.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == s[si]) {
+            pi += 1;
+            si += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, si));
+            pi += 1;
+        } else if let Some((bp, bs)) = backtrack {
+            pi = bp + 1;
+            si = bs + 1;
+            backtrack = Some((bp, bs + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+impl Statement {
+    fn parse(value: &Value) -> S3Result<Self> {
+        let Some(statement) = value.as_object() else {
+            return Err(s3_error!(MalformedPolicy, "Each statement must be a JSON object"));
+        };
+
+        let is_deny = match statement.get("Effect").and_then(Value::as_str) {
+            Some("Allow") => false,
+            Some("Deny") => true,
+            Some(effect) => return Err(s3_error!(MalformedPolicy, "Invalid effect: {}", effect)),
+            None => return Err(s3_error!(MalformedPolicy, "Missing required field Effect")),
+        };
+
+        let principals = match statement.get("Principal") {
+            Some(Value::String(s)) if s == "*" => vec![s.clone()],
+            Some(Value::Object(map)) => match string_list(map.get("AWS")) {
+                Some(list) => list,
+                None => return Err(s3_error!(MalformedPolicy, "Invalid principal in policy")),
+            },
+            Some(_) => return Err(s3_error!(MalformedPolicy, "Invalid principal in policy")),
+            None => return Err(s3_error!(MalformedPolicy, "Missing required field Principal")),
+        };
+
+        let Some(actions) = string_list(statement.get("Action")) else {
+            return Err(s3_error!(MalformedPolicy, "Missing required field Action"));
+        };
+        if let Some(action) = actions.iter().find(|a| *a != "*" && a.starts_with("s3:").not()) {
+            return Err(s3_error!(MalformedPolicy, "Policy has invalid action: {}", action));
+        }
+
+        let Some(resources) = string_list(statement.get("Resource")) else {
+            return Err(s3_error!(MalformedPolicy, "Missing required field Resource"));
+        };
+        if let Some(resource) = resources.iter().find(|r| *r != "*" && r.starts_with(ARN_PREFIX).not()) {
+            return Err(s3_error!(MalformedPolicy, "Policy has invalid resource: {}", resource));
+        }
+
+        Ok(Self {
+            is_deny,
+            principals,
+            actions,
+            resources,
+            has_condition: statement.contains_key("Condition"),
+        })
+    }
+
+    /// An anonymous request only matches the `*` principal.
+    fn matches(&self, principal: Option<&str>, action: &str, resource: &str) -> bool {
+        if self.has_condition {
+            return false;
+        }
+        let principal_matches = self.principals.iter().any(|p| p == "*" || Some(p.as_str()) == principal);
+        let action = action.to_ascii_lowercase();
+        let action_matches = self.actions.iter().any(|a| wildcard_match(&a.to_ascii_lowercase(), &action));
+        let resource_matches = self.resources.iter().any(|r| wildcard_match(r, resource));
+        principal_matches && action_matches && resource_matches
+    }
+}
+
+impl Policy {
+    /// Parses and validates a policy document.
+    pub(crate) fn parse(text: &str) -> S3Result<Self> {
+        let Ok(doc) = serde_json::from_str::<Value>(text) else {
+            return Err(s3_error!(MalformedPolicy, "This policy contains invalid Json"));
+        };
+        let Some(doc) = doc.as_object() else {
+            return Err(s3_error!(MalformedPolicy, "Policies must be valid JSON and the first byte must be '{'"));
+        };
+
+        if let Some(version) = doc.get("Version") {
+            if version.as_str().is_some_and(|v| VERSIONS.contains(&v)).not() {
+                return Err(s3_error!(MalformedPolicy, "The policy must contain a valid version string"));
+            }
+        }
+
+        let statements = match doc.get("Statement") {
+            Some(Value::Array(values)) if values.is_empty().not() => {
+                values.iter().map(Statement::parse).collect::<S3Result<_>>()?
+            }
+            Some(value @ Value::Object(_)) => vec![Statement::parse(value)?],
+            _ => return Err(s3_error!(MalformedPolicy, "Missing required field Statement")),
+        };
+
+        Ok(Self { statements })
+    }
+
+    /// Returns whether a statement explicitly denies the action
+    pub(crate) fn is_denied(&self, principal: Option<&str>, action: &str, resource: &str) -> bool {
+        self.statements
+            .iter()
+            .any(|s| s.is_deny && s.matches(principal, action, resource))
+    }
+}
+
+impl FileSystem {
+    pub(crate) async fn load_bucket_policy(&self, bucket: &str) -> Result<Option<String>> {
+        let path = self.get_bucket_policy_path(bucket)?;
+        match self.storage.read(&path).await {
+            Ok(policy) => Ok(Some(String::from_utf8(policy)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// The policy is stored as it is, so that it round-trips exactly.
+    pub(crate) async fn save_bucket_policy(&self, bucket: &str, policy: &str) -> Result<()> {
+        let path = self.get_bucket_policy_path(bucket)?;
+        write_atomic(&*self.storage, &path, policy.as_bytes()).await
+    }
+
+    pub(crate) async fn remove_bucket_policy(&self, bucket: &str) -> Result<()> {
+        let path = self.get_bucket_policy_path(bucket)?;
+        match self.storage.remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Rejects mutating operations of a read-only file system.
+    pub(crate) fn check_writable(&self) -> S3Result<()> {
+        if self.read_only {
+            return Err(s3_error!(AccessDenied, "The file system is read-only"));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the bucket policy for a request.
+    ///
+    /// `key` is `None` for bucket operations.
+    pub(crate) async fn check_policy(
+        &self,
+        credentials: Option<&Credentials>,
+        action: &str,
+        bucket: &str,
+        key: Option<&str>,
+    ) -> S3Result<()> {
+        // all other actions are denied in read-only mode
+        if READ_ACTIONS.contains(&action).not() {
+            self.check_writable()?;
+        }
+
+        let Some(policy) = self.load_bucket_policy(bucket).await? else { return Ok(()) };
+        let policy = Policy::parse(&policy)?;
+
+        let resource = match key {
+            Some(key) => format!("{ARN_PREFIX}{bucket}/{key}"),
+            None => format!("{ARN_PREFIX}{bucket}"),
+        };
+        let principal = credentials.map(|c| c.access_key.as_str());
+        if policy.is_denied(principal, action, &resource) {
+            return Err(s3_error!(AccessDenied, "Access Denied by bucket policy"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+//! Request payment of buckets, stored in the bucket config
+//!
+//! Requests are not billed, so the payer of a bucket is only stored and returned.
+//! A request which acknowledges the charges with `x-amz-request-payer: requester`
+//! is answered with `x-amz-request-charged: requester`, which some clients expect.
+
+use crate::fs::BucketConfig;
+
+use s3s::dto::*;
+use s3s::{s3_error, S3Result};
+
+const PAYERS: &[&str] = &[Payer::BUCKET_OWNER, Payer::REQUESTER];
+
+pub(crate) fn check_request_payment(config: &RequestPaymentConfiguration) -> S3Result<()> {
+    if PAYERS.contains(&config.payer.as_str()) {
+        return Ok(());
+    }
+    Err(s3_error!(MalformedXML, "Invalid payer: {}", config.payer.as_str()))
+}
+
+/// Buckets without a stored payer are paid by their owner.
+pub(crate) fn get_payer(config: &BucketConfig) -> &str {
+    let payer = config.get("request_payer").and_then(serde_json::Value::as_str);
+    payer.unwrap_or(Payer::BUCKET_OWNER)
+}
+
+pub(crate) fn set_payer(config: &mut BucketConfig, payer: &str) {
+    config.insert("request_payer".to_owned(), serde_json::Value::String(payer.to_owned()));
+}
+
+/// The `x-amz-request-charged` header of the response to a request with `request_payer`
+pub(crate) fn request_charged(request_payer: Option<&RequestPayer>) -> Option<RequestCharged> {
+    let requester = request_payer.is_some_and(|p| p.as_str() == RequestPayer::REQUESTER);
+    requester.then(|| RequestCharged::from_static(RequestCharged::REQUESTER))
+}
@@ -1,52 +1,629 @@
+use crate::acl::GrantHeaders;
+use crate::checksum::ChecksumCalculator;
+use crate::conditional::{check_if_match_write, check_if_none_match_write, ReadConditions};
+use crate::content_type::guess_content_type;
+use crate::dry_run::{dry_run_response, reject_dry_run};
 use crate::fs::FileSystem;
 use crate::fs::InternalInfo;
+use crate::fs::ObjectPartInfo;
+use crate::fs::PartInfo;
+use crate::fs::UploadInfo;
+use crate::fs::DEFAULT_MAX_KEYS;
+use crate::fs::DEFAULT_REGION;
+use crate::fs::DIRECTORY_MARKER;
+use crate::listing::ListingOrder;
+use crate::object_lock::{ObjectLock, Retention};
+use crate::select::Select;
+use crate::sse::CustomerKey;
+use crate::storage::{FileMetadata, StorageFile};
+use crate::tagging::{parse_tagging_header, tag_map_from_tags, tags_from_tag_map, TagMap};
 use crate::utils::*;
+use crate::versioning::{VersionLocation, VersionLookup, VersioningState, NULL_VERSION_ID};
 
+use s3s::auth::Credentials;
 use s3s::dto::*;
 use s3s::s3_error;
+use s3s::S3Error;
 use s3s::S3Result;
 use s3s::S3;
 use s3s::{S3Request, S3Response};
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::io;
-use std::ops::Neg;
+use std::ops;
 use std::ops::Not;
 use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::time::SystemTime;
 
-use tokio::fs;
 use tokio::io::AsyncSeekExt;
 use tokio::io::BufWriter;
 use tokio_util::io::ReaderStream;
 
+use futures::future::Either;
+use futures::StreamExt;
 use futures::TryStreamExt;
+use hyper::header::CONTENT_RANGE;
+use hyper::http::{HeaderName, HeaderValue};
+use hyper::{HeaderMap, StatusCode};
 use md5::{Digest, Md5};
-use numeric_cast::NumericCast;
 use rust_utils::default::default;
-use std::path::Component;
 use std::string::ToString;
 use tracing::debug;
 use uuid::Uuid;
 
-fn normalize_path(path: &Path, delimiter: &str) -> Option<String> {
-    let mut normalized = String::new();
-    let mut first = true;
-    for component in path.components() {
-        match component {
-            Component::RootDir | Component::CurDir | Component::ParentDir | Component::Prefix(_) => {
-                return None;
-            }
-            Component::Normal(name) => {
-                let name = name.to_str()?;
-                if !first {
-                    normalized.push_str(delimiter);
+/// The region of a bucket, returned by `HeadBucket`
+const X_AMZ_BUCKET_REGION: HeaderName = HeaderName::from_static("x-amz-bucket-region");
+
+/// The maximum number of parts of a multipart upload, which is 10000 like AWS
+const MAX_PARTS: i32 = 10_000;
+
+fn found_version(lookup: VersionLookup) -> S3Result<VersionLocation> {
+    match lookup {
+        VersionLookup::Found(location) => Ok(location),
+        VersionLookup::DeleteMarker => Err(s3_error!(
+            MethodNotAllowed,
+            "The specified method is not allowed against a delete marker."
+        )),
+        VersionLookup::NotFound => Err(s3_error!(NoSuchVersion)),
+    }
+}
+
+/// Returns the version id of a stored object for `x-amz-version-id`, which is `None` if the bucket is unversioned.
+///
+/// Objects written before versioning was enabled are the `null` version.
+async fn object_version_id(fs: &FileSystem, bucket: &str, info: Option<&InternalInfo>) -> S3Result<Option<String>> {
+    if fs.get_versioning_state(bucket).await? == VersioningState::Unversioned {
+        return Ok(None);
+    }
+    let version_id = info
+        .and_then(|info| info.get("version_id"))
+        .and_then(serde_json::Value::as_str);
+    Ok(Some(version_id.unwrap_or(NULL_VERSION_ID).to_owned()))
+}
+
+fn range_not_satisfiable(file_len: u64) -> S3Error {
+    let mut err = s3_error!(InvalidRange, "The requested range is not satisfiable");
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_RANGE, HeaderValue::try_from(format!("bytes */{file_len}")).unwrap());
+    err.set_headers(headers);
+    err
+}
+
+/// Compares the MD5 digest of a request body with the `Content-MD5` header
+fn check_content_md5(content_md5: Option<&str>, md5_digest: &[u8]) -> S3Result<()> {
+    let Some(content_md5) = content_md5 else { return Ok(()) };
+    let expected = base64_simd::STANDARD.decode_to_vec(content_md5).ok();
+    let Some(expected) = expected.filter(|e| e.len() == 16) else {
+        return Err(s3_error!(InvalidDigest, "The Content-MD5 you specified is not valid."));
+    };
+    if expected != md5_digest {
+        return Err(s3_error!(BadDigest, "The Content-MD5 you specified did not match what we received."));
+    }
+    Ok(())
+}
+
+/// Checksums are only returned when `x-amz-checksum-mode` is enabled
+fn is_checksum_mode_enabled(checksum_mode: Option<&ChecksumMode>) -> bool {
+    checksum_mode.is_some_and(|m| m.as_str() == ChecksumMode::ENABLED)
+}
+
+/// A part can only be checked with the checksum algorithm of its upload
+fn check_part_checksum_algorithm(upload_algorithm: Option<&str>, expected: &Checksum) -> S3Result<()> {
+    let (Some(upload_algorithm), Some(provided)) = (upload_algorithm, crate::checksum::provided_algorithm(expected)) else {
+        return Ok(());
+    };
+    if provided != upload_algorithm {
+        return Err(s3_error!(
+            InvalidRequest,
+            "Checksum Type mismatch occurred, expected checksum Type: {}, actual checksum Type: {}",
+            upload_algorithm.to_lowercase(),
+            provided.to_lowercase()
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the storage class of a write request
+fn check_storage_class(storage_class: Option<&StorageClass>) -> S3Result<()> {
+    const KNOWN_STORAGE_CLASSES: &[&str] = &[
+        StorageClass::DEEP_ARCHIVE,
+        StorageClass::GLACIER,
+        StorageClass::GLACIER_IR,
+        StorageClass::INTELLIGENT_TIERING,
+        StorageClass::ONEZONE_IA,
+        StorageClass::OUTPOSTS,
+        StorageClass::REDUCED_REDUNDANCY,
+        StorageClass::SNOW,
+        StorageClass::STANDARD,
+        StorageClass::STANDARD_IA,
+    ];
+    match storage_class {
+        Some(storage_class) if KNOWN_STORAGE_CLASSES.contains(&storage_class.as_str()).not() => {
+            Err(s3_error!(InvalidStorageClass, "The storage class you specified is not valid"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns the stored storage class of an object, which defaults to STANDARD
+fn stored_storage_class(info: Option<&InternalInfo>) -> &str {
+    info.and_then(|info| info.get("storage_class"))
+        .and_then(|v| v.as_str())
+        .unwrap_or(StorageClass::STANDARD)
+}
+
+/// S3 omits the storage class header for STANDARD objects
+fn storage_class_header(info: Option<&InternalInfo>) -> Option<StorageClass> {
+    let storage_class = stored_storage_class(info);
+    (storage_class != StorageClass::STANDARD).then(|| StorageClass::from(storage_class.to_owned()))
+}
+
+/// Objects of these storage classes have to be restored before they can be read in S3
+const ARCHIVED_STORAGE_CLASSES: &[&str] = &[StorageClass::DEEP_ARCHIVE, StorageClass::GLACIER];
+
+/// Returns `x-amz-restore` of a restored object, until the restored copy expires
+fn restore_header(info: Option<&InternalInfo>) -> Option<Restore> {
+    let expiry_date = info?.get("restore_expiry_date")?.as_str()?;
+    let expiry_date = Timestamp::parse(TimestampFormat::DateTime, expiry_date).ok()?;
+    if time::OffsetDateTime::from(expiry_date.clone()) <= time::OffsetDateTime::now_utc() {
+        return None;
+    }
+    let mut buf = Vec::new();
+    expiry_date.format(TimestampFormat::HttpDate, &mut buf).ok()?;
+    let expiry_date = String::from_utf8(buf).ok()?;
+    Some(format!("ongoing-request=\"false\", expiry-date=\"{expiry_date}\""))
+}
+
+/// Records the content headers of an upload, which are returned by `GetObject` and `HeadObject`
+///
+/// The content encoding is stored as it is, and the content is never decoded.
+fn set_content_headers(
+    info: &mut InternalInfo,
+    content_type: Option<&ContentType>,
+    content_disposition: Option<&str>,
+    content_encoding: Option<&str>,
+) {
+    info.remove("content_type");
+    info.remove("content_disposition");
+    info.remove("content_encoding");
+    if let Some(content_type) = content_type {
+        info.insert("content_type".to_owned(), content_type.as_ref().into());
+    }
+    if let Some(content_disposition) = content_disposition {
+        info.insert("content_disposition".to_owned(), content_disposition.into());
+    }
+    if let Some(content_encoding) = content_encoding {
+        info.insert("content_encoding".to_owned(), content_encoding.into());
+    }
+}
+
+/// Validates `x-amz-website-redirect-location` of a write request
+fn check_website_redirect_location(location: Option<&str>) -> S3Result<()> {
+    match location {
+        Some(location) if ["/", "http://", "https://"].iter().any(|p| location.starts_with(p)).not() => Err(s3_error!(
+            InvalidArgument,
+            "The website redirect location must have a prefix of 'http://' or 'https://' or '/'."
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Records `x-amz-website-redirect-location`, which is not copied from the source object like other metadata
+fn set_website_redirect_location(info: &mut InternalInfo, location: Option<&str>) {
+    info.remove("website_redirect_location");
+    if let Some(location) = location {
+        info.insert("website_redirect_location".to_owned(), location.into());
+    }
+}
+
+fn stored_website_redirect_location(info: Option<&InternalInfo>) -> Option<WebsiteRedirectLocation> {
+    let location = info.and_then(|info| info.get("website_redirect_location"))?;
+    location.as_str().map(ToOwned::to_owned)
+}
+
+/// Answers a read with a redirect if website redirects are enabled and the object has a redirect location
+fn website_redirect<T: Default>(fs: &FileSystem, info: Option<&InternalInfo>) -> S3Result<Option<S3Response<T>>> {
+    if fs.website_redirects.not() {
+        return Ok(None);
+    }
+    let Some(location) = stored_website_redirect_location(info) else { return Ok(None) };
+    let mut resp = S3Response::new(T::default());
+    resp.status = Some(StatusCode::MOVED_PERMANENTLY);
+    resp.headers
+        .insert(hyper::header::LOCATION, try_!(HeaderValue::try_from(location)));
+    Ok(Some(resp))
+}
+
+/// Objects uploaded without a content type are binary, unless their content types are guessed
+fn stored_content_type(fs: &FileSystem, key: &str, info: Option<&InternalInfo>) -> ContentType {
+    let stored = info
+        .and_then(|info| info.get("content_type"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok());
+    stored
+        .or_else(|| fs.guess_content_types.then(|| guess_content_type(key)).flatten())
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM)
+}
+
+fn stored_content_disposition(info: Option<&InternalInfo>) -> Option<ContentDisposition> {
+    let content_disposition = info.and_then(|info| info.get("content_disposition"))?;
+    content_disposition.as_str().map(ToOwned::to_owned)
+}
+
+/// The stored encoding of an object is returned as is, and the data is never transformed
+/// according to the `Accept-Encoding` of a request, so objects are read byte for byte.
+fn stored_content_encoding(info: Option<&InternalInfo>) -> Option<ContentEncoding> {
+    let content_encoding = info.and_then(|info| info.get("content_encoding"))?;
+    content_encoding.as_str().map(ToOwned::to_owned)
+}
+
+/// Validates the `response-*` query parameters of `GetObject`, which override the response headers.
+///
+/// Returns the overriding content type.
+fn check_response_overrides(input: &GetObjectInput, credentials: Option<&Credentials>) -> S3Result<Option<ContentType>> {
+    let has_overrides = input.response_cache_control.is_some()
+        || input.response_content_disposition.is_some()
+        || input.response_content_encoding.is_some()
+        || input.response_content_language.is_some()
+        || input.response_content_type.is_some()
+        || input.response_expires.is_some();
+    if has_overrides && credentials.is_none() {
+        return Err(s3_error!(
+            InvalidRequest,
+            "Request specific response headers cannot be used for anonymous GET requests."
+        ));
+    }
+    match input.response_content_type {
+        Some(ref content_type) => match content_type.parse() {
+            Ok(content_type) => Ok(Some(content_type)),
+            Err(_) => Err(s3_error!(InvalidArgument, "Invalid response-content-type: {}", content_type)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Lists the keys of the objects in a bucket which start with `prefix`, sorted by key.
+///
+/// Nested directories on disk are flattened into `/`-separated keys by the default [`KeyMapper`](crate::KeyMapper).
+pub(crate) async fn list_keys(fs: &FileSystem, bucket: &str, prefix: &str) -> S3Result<Vec<(String, FileMetadata)>> {
+    let bucket_path = fs.get_bucket_path(bucket)?;
+    let mut entries: Vec<(String, FileMetadata)> = default();
+    let mut dir_queue: VecDeque<PathBuf> = default();
+    dir_queue.push_back(bucket_path.clone());
+
+    while let Some(dir) = dir_queue.pop_front() {
+        for entry in try_!(fs.storage.read_dir(&dir).await) {
+            let relative = try_!(entry.path.strip_prefix(&bucket_path));
+
+            if entry.is_dir {
+                // skip directories which can not contain any matching key
+                if fs.key_mapper.may_contain_prefix(bucket, relative, prefix) {
+                    dir_queue.push_back(entry.path.clone());
                 }
-                normalized.push_str(name);
-                first = false;
+                continue;
+            }
+            let key = if entry.file_name() == Some(DIRECTORY_MARKER) {
+                // the key of a marker in the bucket directory would be empty
+                let dir = relative.parent().filter(|dir| dir.as_os_str().is_empty().not());
+                let Some(dir_key) = dir.and_then(|dir| fs.key_mapper.object_key(bucket, dir)) else { continue };
+                format!("{dir_key}/")
+            } else {
+                let Some(key) = fs.key_mapper.object_key(bucket, relative) else { continue };
+                key
+            };
+            if key.starts_with(prefix) {
+                entries.push((key, try_!(fs.storage.symlink_metadata(&entry.path).await)));
+            }
+        }
+    }
+
+    entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    Ok(entries)
+}
+
+/// The continuation token is the last returned key, encoded like the sidecar file names
+fn encode_continuation_token(key: &str) -> String {
+    base64_simd::URL_SAFE_NO_PAD.encode_to_string(key)
+}
+
+fn decode_continuation_token(token: &str) -> S3Result<String> {
+    let invalid = || s3_error!(InvalidArgument, "The continuation token provided is incorrect");
+    let bytes = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(token).map_err(|_| invalid())?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Encodes a key for `encoding-type=url`, keeping the `/` separators readable
+fn url_encode_key(key: &str) -> String {
+    key.split('/').map(urlencoding::encode).collect::<Vec<_>>().join("/")
+}
+
+/// An existing bucket is owned by the requester if it was created with the same access key.
+///
+/// Buckets created before the owner was recorded are owned by everyone.
+async fn bucket_conflict(fs: &FileSystem, bucket: &str, credentials: Option<&Credentials>) -> S3Error {
+    let config = fs.load_bucket_config(bucket).await.ok().flatten().unwrap_or_default();
+    let owner = config.get("owner").and_then(serde_json::Value::as_str);
+    if owner.is_none() || owner == credentials.map(|c| c.access_key.as_str()) {
+        s3_error!(BucketAlreadyOwnedByYou)
+    } else {
+        s3_error!(BucketAlreadyExists)
+    }
+}
+
+/// Object operations report a missing bucket before looking up the object
+async fn check_bucket_exists(fs: &FileSystem, bucket: &str) -> S3Result<()> {
+    if fs.storage.exists(&fs.get_bucket_path(bucket)?).await.not() {
+        return Err(s3_error!(NoSuchBucket));
+    }
+    Ok(())
+}
+
+/// Entity tags are returned as quoted strings, including the `-N` suffix of multipart objects.
+fn quoted_e_tag(e_tag: &str) -> String {
+    format!("\"{}\"", e_tag.trim_matches('"'))
+}
+
+/// Checks that a staged part of a multipart upload is present and returns its MD5 sum and size.
+///
+/// Uploads created by older versions do not record their parts, so the MD5 of such a part is computed.
+async fn verify_staged_part(
+    fs: &FileSystem,
+    part_number: i32,
+    part_path: &Path,
+    recorded: Option<&PartInfo>,
+) -> S3Result<(String, u64)> {
+    let Ok(meta) = fs.storage.metadata(part_path).await else {
+        return Err(s3_error!(InvalidPart, "Part {} could not be found.", part_number));
+    };
+    match recorded {
+        Some(p) if p.size == meta.len => Ok((p.e_tag.trim_matches('"').to_owned(), p.size)),
+        Some(_) => Err(s3_error!(InvalidPart, "Part {} does not match the uploaded part.", part_number)),
+        None => Ok((fs.get_file_md5_sum(part_path).await?, meta.len)),
+    }
+}
+
+/// Returns the stored entity tag of an object.
+///
+/// Objects written by older versions have no stored entity tag,
+/// so the MD5 of the data is computed once and stored.
+async fn object_e_tag(fs: &FileSystem, location: &VersionLocation, info: Option<&InternalInfo>) -> S3Result<String> {
+    if let Some(e_tag) = info.and_then(|info| info.get("e_tag")).and_then(|v| v.as_str()) {
+        return Ok(quoted_e_tag(e_tag));
+    }
+    let md5_sum = fs.get_file_md5_sum(&location.data).await?;
+    let e_tag = quoted_e_tag(&md5_sum);
+
+    // the entity tag can be computed again if it can not be stored
+    if fs.read_only.not() {
+        let mut info = info.cloned().unwrap_or_default();
+        info.insert("e_tag".to_owned(), serde_json::Value::String(e_tag.clone()));
+        let _ = location.save_internal_info(&info).await;
+    }
+
+    Ok(e_tag)
+}
+
+/// Governance retention can only be bypassed by authenticated requests
+fn can_bypass_governance(bypass_governance_retention: Option<bool>, credentials: Option<&Credentials>) -> bool {
+    bypass_governance_retention == Some(true) && credentials.is_some()
+}
+
+/// Fails if an object version is protected by Object Lock
+async fn check_object_lock(
+    fs: &FileSystem,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    bypass_governance: bool,
+) -> S3Result<()> {
+    let VersionLookup::Found(location) = fs.locate_version(bucket, key, version_id).await? else {
+        return Ok(());
+    };
+    if fs.storage.is_file(&location.data).await.not() {
+        return Ok(());
+    }
+    let info = location.load_internal_info().await?;
+    ObjectLock::from_internal_info(info.as_ref()).check_mutable(bypass_governance)
+}
+
+/// Deletes an object or one of its versions.
+///
+/// Deleting a key that does not exist succeeds, as in S3.
+pub(crate) async fn delete_object_entry(
+    fs: &FileSystem,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    bypass_governance: bool,
+) -> S3Result<DeletedObject> {
+    let _lock = fs.key_locks.write(bucket, key).await;
+    check_object_lock(fs, bucket, key, version_id, bypass_governance).await?;
+
+    if let Some(version_id) = version_id {
+        let Some(is_delete_marker) = fs.delete_version(bucket, key, version_id).await? else {
+            return Err(s3_error!(NoSuchVersion));
+        };
+        return Ok(DeletedObject {
+            delete_marker: is_delete_marker,
+            delete_marker_version_id: is_delete_marker.then(|| version_id.to_owned()),
+            key: Some(key.to_owned()),
+            version_id: Some(version_id.to_owned()),
+        });
+    }
+
+    if let Some(marker_version_id) = fs.create_delete_marker(bucket, key).await? {
+        return Ok(DeletedObject {
+            delete_marker: true,
+            delete_marker_version_id: Some(marker_version_id),
+            key: Some(key.to_owned()),
+            version_id: None,
+        });
+    }
+
+    let location = fs.current_location(bucket, key)?;
+    location.remove().await?;
+    // the directory of a directory marker is removed with it unless it contains other objects
+    let is_marker = location.data.file_name().is_some_and(|name| name == DIRECTORY_MARKER);
+    if let Some(path) = is_marker.then(|| location.data.parent()).flatten() {
+        if fs.storage.is_dir(path).await {
+            let is_empty = try_!(fs.storage.read_dir(path).await).is_empty();
+            if is_empty {
+                try_!(fs.storage.remove_dir(path).await);
             }
         }
     }
-    Some(normalized)
+
+    Ok(DeletedObject {
+        key: Some(key.to_owned()),
+        ..Default::default()
+    })
+}
+
+/// Lists the parts of a multipart object for `GetObjectAttributes`.
+///
+/// Objects completed by older versions do not record their parts, so only the number of parts is known from the entity tag.
+fn object_parts(
+    info: Option<&InternalInfo>,
+    e_tag: &str,
+    max_parts: Option<i32>,
+    part_number_marker: Option<String>,
+) -> S3Result<Option<GetObjectAttributesParts>> {
+    let recorded = match info.and_then(|info| info.get("parts")) {
+        Some(value) => Some(try_!(serde_json::from_value::<Vec<ObjectPartInfo>>(value.clone()))),
+        None => None,
+    };
+    let total_parts_count = match recorded {
+        Some(ref parts) => try_!(i32::try_from(parts.len())),
+        None => match e_tag.trim_matches('"').rsplit_once('-').map(|(_, n)| n.parse::<i32>()) {
+            Some(Ok(n)) => n,
+            _ => return Ok(None),
+        },
+    };
+
+    let max_parts = max_parts.unwrap_or(1000);
+    let limit = usize::try_from(max_parts).map_err(|_| s3_error!(InvalidArgument, "max-parts must be non-negative"))?;
+    let marker = match part_number_marker {
+        Some(ref marker) => marker
+            .parse::<i32>()
+            .map_err(|_| s3_error!(InvalidArgument, "part-number-marker must be an integer"))?,
+        None => 0,
+    };
+
+    let algorithm = info.and_then(|info| crate::checksum::provided_algorithm(&crate::checksum::from_internal_info(info)));
+    let mut parts: Vec<ObjectPart> = Vec::new();
+    let mut is_truncated = false;
+    for part in recorded.iter().flatten().filter(|p| p.part_number > marker) {
+        if parts.len() == limit {
+            is_truncated = true;
+            break;
+        }
+        let mut checksum: Checksum = default();
+        if let (Some(algorithm), Some(value)) = (algorithm, &part.checksum) {
+            crate::checksum::set(&mut checksum, algorithm, value.clone());
+        }
+        parts.push(ObjectPart {
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            part_number: part.part_number,
+            size: try_!(i64::try_from(part.size)),
+        });
+    }
+
+    let next_part_number_marker = if is_truncated {
+        parts.last().map(|p| p.part_number.to_string())
+    } else {
+        None
+    };
+
+    Ok(Some(GetObjectAttributesParts {
+        is_truncated,
+        max_parts,
+        next_part_number_marker,
+        part_number_marker,
+        parts: recorded.is_some().then_some(parts),
+        total_parts_count,
+    }))
+}
+
+fn check_part_number(part_number: i32) -> S3Result<()> {
+    if (1..=MAX_PARTS).contains(&part_number).not() {
+        return Err(s3_error!(
+            InvalidArgument,
+            "Part number must be an integer between 1 and {}, inclusive",
+            MAX_PARTS
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the `max-keys` of a listing, which is clamped to the configured maximum like AWS,
+/// and the number of keys which it allows
+fn page_size(fs: &FileSystem, max_keys: Option<i32>) -> S3Result<(i32, usize)> {
+    let max_keys = max_keys.unwrap_or(DEFAULT_MAX_KEYS).min(fs.max_keys);
+    let limit = usize::try_from(max_keys).map_err(|_| s3_error!(InvalidArgument, "max-keys must be non-negative"))?;
+    Ok((max_keys, limit))
+}
+
+/// Rejects a body which is known to exceed the maximum object size before reading it
+fn check_content_length(fs: &FileSystem, content_length: Option<i64>) -> S3Result<()> {
+    if let (Some(len), Some(limit)) = (content_length, fs.max_object_size) {
+        if u64::try_from(len).is_ok_and(|len| len > limit) {
+            return Err(entity_too_large(limit));
+        }
+    }
+    Ok(())
+}
+
+fn no_such_key(key: &str) -> S3Error {
+    s3_error!(NoSuchKey, "The specified key does not exist. Key: {}", key)
+}
+
+/// Finds an existing object version
+async fn locate_object(fs: &FileSystem, bucket: &str, key: &str, version_id: Option<&str>) -> S3Result<VersionLocation> {
+    let location = found_version(fs.locate_version(bucket, key, version_id).await?)?;
+    if fs.storage.is_file(&location.data).await.not() {
+        return Err(no_such_key(key));
+    }
+    Ok(location)
+}
+
+/// Opens the data file of an object version, which is `NoSuchKey` if it does not exist
+async fn open_object(fs: &FileSystem, location: &VersionLocation, key: &str) -> S3Result<Box<dyn StorageFile>> {
+    let file = match fs.storage.open(&location.data).await {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(no_such_key(key)),
+        Err(err) => return Err(s3_error!(err, InternalError)),
+    };
+    if try_!(file.metadata().await).is_file().not() {
+        return Err(no_such_key(key));
+    }
+    Ok(file)
+}
+
+/// Parses `x-amz-copy-source-range` (`bytes=first-last`) into a range of the source object
+fn parse_copy_source_range(copy_range: &str, file_len: u64) -> S3Result<ops::Range<u64>> {
+    let invalid_argument =
+        || s3_error!(InvalidArgument, "The x-amz-copy-source-range value must be of the form bytes=first-last");
+    let range = copy_range.strip_prefix("bytes=").ok_or_else(invalid_argument)?;
+    let (first, last) = range.split_once('-').ok_or_else(invalid_argument)?;
+
+    let first: u64 = first.parse().map_err(|_| invalid_argument())?;
+    let last: Option<u64> = match last {
+        "" => None,
+        s => Some(s.parse().map_err(|_| invalid_argument())?),
+    };
+
+    let last = last.unwrap_or_else(|| file_len.saturating_sub(1));
+    if first > last || last >= file_len {
+        return Err(s3_error!(
+            InvalidRange,
+            "The x-amz-copy-source-range value exceeds the size of the source object"
+        ));
+    }
+    Ok(first..last + 1)
 }
 
 #[async_trait::async_trait]
@@ -54,13 +631,75 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn create_bucket(&self, req: S3Request<CreateBucketInput>) -> S3Result<S3Response<CreateBucketOutput>> {
         let input = req.input;
+        self.check_writable()?;
         let path = self.get_bucket_path(&input.bucket)?;
+        let credentials = req.credentials.as_ref();
+
+        if self.storage.exists(&path).await {
+            return Err(bucket_conflict(self, &input.bucket, credentials).await);
+        }
+
+        let location = input
+            .create_bucket_configuration
+            .as_ref()
+            .and_then(|c| c.location_constraint.as_ref())
+            .map(BucketLocationConstraint::as_str)
+            .filter(|s| s.is_empty().not())
+            .unwrap_or(self.region.as_str());
+
+        let mut config = crate::fs::BucketConfig::new();
+        config.insert("location_constraint".to_owned(), serde_json::Value::String(location.to_owned()));
+        config.insert("creation_date".to_owned(), try_!(serde_json::to_value(SystemTime::now())));
+        if let Some(credentials) = credentials {
+            config.insert("owner".to_owned(), serde_json::Value::String(credentials.access_key.clone()));
+        }
 
-        if path.exists() {
-            return Err(s3_error!(BucketAlreadyExists));
+        let owner = credentials.map(|c| crate::acl::owner_of(&c.access_key));
+        let grant_headers = GrantHeaders {
+            full_control: input.grant_full_control.as_deref(),
+            read: input.grant_read.as_deref(),
+            read_acp: input.grant_read_acp.as_deref(),
+            write: input.grant_write.as_deref(),
+            write_acp: input.grant_write_acp.as_deref(),
+        };
+        let canned_acl = input.acl.as_ref().map(BucketCannedACL::as_str);
+        let grants = crate::acl::requested_grants(canned_acl, &grant_headers, None, owner.as_ref())?;
+
+        if let Some(ref ownership) = input.object_ownership {
+            crate::ownership::check_object_ownership(ownership)?;
+        }
+        let ownership = input
+            .object_ownership
+            .as_ref()
+            .map(ObjectOwnership::as_str)
+            .or(self.default_object_ownership.as_deref());
+        crate::ownership::check_create_bucket_acl(ownership, canned_acl, grant_headers.is_empty().not())?;
+        crate::ownership::set_object_ownership(&mut config, ownership);
+
+        // Object Lock requires versioning, so both are enabled together
+        if input.object_lock_enabled_for_bucket == Some(true) {
+            config.insert(
+                "versioning_status".to_owned(),
+                serde_json::Value::String(BucketVersioningStatus::ENABLED.to_owned()),
+            );
+            config.insert("object_lock_enabled".to_owned(), serde_json::Value::Bool(true));
+        }
+
+        if self.is_dry_run(&req.headers)? {
+            return Ok(dry_run_response(CreateBucketOutput::default()));
         }
 
-        try_!(fs::create_dir(&path).await);
+        match self.storage.create_dir(&path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                return Err(bucket_conflict(self, &input.bucket, credentials).await);
+            }
+            Err(err) => return Err(s3_error!(err, InternalError)),
+        }
+        self.save_bucket_config(&input.bucket, &config).await?;
+        if let Some(grants) = grants {
+            self.save_bucket_acl(&input.bucket, owner.as_ref(), grants).await?;
+        }
 
         let output = CreateBucketOutput::default(); // TODO: handle other fields
         Ok(S3Response::new(output))
@@ -69,49 +708,155 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn copy_object(&self, req: S3Request<CopyObjectInput>) -> S3Result<S3Response<CopyObjectOutput>> {
         let input = req.input;
-        let (bucket, key) = match input.copy_source {
-            CopySource::AccessPoint { .. } => return Err(s3_error!(NotImplemented)),
-            CopySource::Bucket { ref bucket, ref key, .. } => (bucket, key),
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        let (bucket, key, version_id) = match input.copy_source {
+            CopySource::AccessPoint { .. } => {
+                return Err(s3_error!(NotImplemented, "Copying from access points is not supported"))
+            }
+            CopySource::Bucket {
+                ref bucket,
+                ref key,
+                ref version_id,
+            } => (bucket, key, version_id.as_deref()),
+        };
+        let credentials = req.credentials.as_ref();
+        self.check_policy(credentials, "s3:PutObject", &input.bucket, Some(&input.key))
+            .await?;
+        self.check_policy(credentials, "s3:GetObject", bucket, Some(key)).await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        check_bucket_exists(self, bucket).await?;
+
+        let replace_metadata = match input.metadata_directive.as_ref().map(MetadataDirective::as_str) {
+            None | Some(MetadataDirective::COPY) => false,
+            Some(MetadataDirective::REPLACE) => true,
+            Some(_) => return Err(s3_error!(InvalidArgument, "Unknown metadata directive.")),
         };
 
-        let src_path = self.get_object_path(bucket, key)?;
-        let dst_path = self.get_object_path(&input.bucket, &input.key)?;
+        check_storage_class(input.storage_class.as_ref())?;
+        check_website_redirect_location(input.website_redirect_location.as_deref())?;
 
-        if src_path.exists().not() {
-            return Err(s3_error!(NoSuchKey));
+        let is_same_object = **bucket == *input.bucket && **key == *input.key;
+        if is_same_object && replace_metadata.not() && input.storage_class.is_none() {
+            let is_current = match version_id {
+                None => true,
+                Some(version_id) => self.get_current_version_id(bucket, key).await?.as_deref() == Some(version_id),
+            };
+            if is_current {
+                return Err(s3_error!(
+                    InvalidRequest,
+                    "This copy request is illegal because it is trying to copy an object to itself without changing the object's metadata."
+                ));
+            }
         }
 
-        if self.get_bucket_path(&input.bucket)?.exists().not() {
-            return Err(s3_error!(NoSuchBucket));
-        }
+        let lookup = self.locate_version(bucket, key, version_id).await?;
+        let src = found_version(lookup)?;
+
+        let src_file = open_object(self, &src, key).await?;
+
+        check_object_lock(self, &input.bucket, &input.key, None, false).await?;
+        let object_lock = self
+            .new_object_lock(
+                &input.bucket,
+                input.object_lock_mode.as_ref(),
+                input.object_lock_retain_until_date.as_ref(),
+                input.object_lock_legal_hold_status.as_ref(),
+            )
+            .await?;
 
-        if let Some(dir_path) = dst_path.parent() {
-            try_!(fs::create_dir_all(&dir_path).await);
+        // read everything of the source before the destination may replace it
+        let metadata = if replace_metadata {
+            input.metadata
+        } else {
+            src.load_metadata().await?
+        };
+        let mut info = src.load_internal_info().await?.unwrap_or_default();
+        if crate::sse::is_encrypted(Some(&info)) {
+            return Err(s3_error!(NotImplemented, "Copying objects encrypted with customer keys is not supported"));
+        }
+        let copy_source_version_id = object_version_id(self, bucket, Some(&info)).await?;
+        info.remove("version_id");
+        info.remove("e_tag");
+        info.remove("storage_class");
+        if replace_metadata {
+            set_content_headers(
+                &mut info,
+                input.content_type.as_ref(),
+                input.content_disposition.as_deref(),
+                input.content_encoding.as_deref(),
+            );
+        }
+        if let Some(storage_class) = input.storage_class {
+            info.insert("storage_class".to_owned(), serde_json::Value::String(storage_class.as_str().to_owned()));
         }
+        set_website_redirect_location(&mut info, input.website_redirect_location.as_deref());
+        object_lock.modify_internal_info(&mut info)?;
+
+        // a dry run reads the source to compute the entity tag of the copy
+        let mut file_writer = if self.is_dry_run(&req.headers)? {
+            None
+        } else {
+            Some(self.prepare_file_write(&input.bucket, &input.key).await?)
+        };
+
+        let mut md5_hash = Md5::new();
+        let body = StreamingBlob::wrap(ReaderStream::with_capacity(src_file, self.read_buffer_size));
+        let stream = body.inspect_ok(|bytes| md5_hash.update(bytes.as_ref()));
+
+        let size = match file_writer {
+            Some(ref mut file_writer) => copy_bytes(stream, file_writer.writer()).await?,
+            None => copy_bytes(stream, &mut tokio::io::sink()).await?,
+        };
+        let md5_sum = hex(md5_hash.finalize());
+
+        let Some(file_writer) = file_writer else {
+            let copy_object_result = CopyObjectResult {
+                e_tag: Some(quoted_e_tag(&md5_sum)),
+                last_modified: Some(Timestamp::from(SystemTime::now())),
+                ..Default::default()
+            };
+            let output = CopyObjectOutput {
+                copy_object_result: Some(copy_object_result),
+                copy_source_version_id,
+                request_charged,
+                ..Default::default()
+            };
+            return Ok(dry_run_response(output));
+        };
 
-        let file_metadata = try_!(fs::metadata(&src_path).await);
-        let last_modified = Timestamp::from(try_!(file_metadata.modified()));
+        // the data is renamed and the sidecar files are written as a unit,
+        // and the object lock is checked again under the lock
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        check_object_lock(self, &input.bucket, &input.key, None, false).await?;
+        let version_id = self.prepare_version_write(&input.bucket, &input.key).await?;
+        let dst_path = file_writer.done().await?;
 
-        let _ = try_!(fs::copy(&src_path, &dst_path).await);
+        debug!(from = %src.data.display(), to = %dst_path.display(), ?size, %md5_sum, "copy file");
 
-        debug!(from = %src_path.display(), to = %dst_path.display(), "copy file");
+        self.save_metadata(&input.bucket, &input.key, metadata.as_ref()).await?;
 
-        let src_metadata_path = self.get_metadata_path(bucket, key)?;
-        if src_metadata_path.exists() {
-            let dst_metadata_path = self.get_metadata_path(&input.bucket, &input.key)?;
-            let _ = try_!(fs::copy(src_metadata_path, dst_metadata_path).await);
+        let e_tag = quoted_e_tag(&md5_sum);
+        info.insert("e_tag".to_owned(), serde_json::Value::String(e_tag.clone()));
+        if let Some(ref version_id) = version_id {
+            info.insert("version_id".to_owned(), serde_json::Value::String(version_id.clone()));
         }
+        self.save_internal_info(&input.bucket, &input.key, &info).await?;
+        self.refresh_public_object(&input.bucket, &input.key).await?;
 
-        let md5_sum = self.get_md5_sum(bucket, key).await?;
+        let file_metadata = try_!(self.storage.metadata(&dst_path).await);
+        let last_modified = Timestamp::from(modified_time(&file_metadata));
 
         let copy_object_result = CopyObjectResult {
-            e_tag: Some(format!("\"{md5_sum}\"")),
+            e_tag: Some(e_tag),
             last_modified: Some(last_modified),
             ..Default::default()
         };
 
         let output = CopyObjectOutput {
             copy_object_result: Some(copy_object_result),
+            copy_source_version_id,
+            version_id,
+            request_charged,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -120,203 +865,915 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn delete_bucket(&self, req: S3Request<DeleteBucketInput>) -> S3Result<S3Response<DeleteBucketOutput>> {
         let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:DeleteBucket", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let has_uploads = self
+            .list_upload_infos()
+            .await?
+            .iter()
+            .any(|(_, info)| info.bucket == input.bucket);
+        if has_uploads || self.is_bucket_empty(&input.bucket).await?.not() {
+            return Err(s3_error!(BucketNotEmpty, "The bucket you tried to delete is not empty"));
+        }
+
+        if self.is_dry_run(&req.headers)? {
+            return Ok(dry_run_response(DeleteBucketOutput {}));
+        }
+
+        // a bucket which was published by its ACL is not public anymore
+        let has_acl = self.storage.exists(&self.get_bucket_acl_path(&input.bucket)?).await;
+
         let path = self.get_bucket_path(&input.bucket)?;
-        try_!(fs::remove_dir_all(path).await);
+        try_!(self.storage.remove_dir_all(&path).await);
+        self.remove_bucket_sidecars(&input.bucket).await?;
+        if let (true, Some(public_buckets)) = (has_acl, &self.public_buckets) {
+            public_buckets.remove(&input.bucket);
+        }
         Ok(S3Response::new(DeleteBucketOutput {}))
     }
 
     #[tracing::instrument]
     async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
         let input = req.input;
-        let path = self.get_object_path(&input.bucket, &input.key)?;
-        if input.key.ends_with('/') {
-            let mut dir = try_!(fs::read_dir(&path).await);
-            let is_empty = try_!(dir.next_entry().await).is_none();
-            if is_empty {
-                try_!(fs::remove_dir(&path).await);
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:DeleteObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        if input.if_match.is_some() {
+            let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+            let info = location.load_internal_info().await?;
+            let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+            check_if_match_write(input.if_match.as_deref(), &e_tag)?;
+        }
+        let bypass_governance = can_bypass_governance(input.bypass_governance_retention, req.credentials.as_ref());
+        if self.is_dry_run(&req.headers)? {
+            check_object_lock(self, &input.bucket, &input.key, input.version_id.as_deref(), bypass_governance).await?;
+            if let Some(ref version_id) = input.version_id {
+                if let VersionLookup::NotFound = self.locate_version(&input.bucket, &input.key, Some(version_id)).await? {
+                    return Err(s3_error!(NoSuchVersion));
+                }
             }
-        } else {
-            try_!(fs::remove_file(path).await);
+            return Ok(dry_run_response(DeleteObjectOutput {
+                request_charged,
+                ..Default::default()
+            }));
         }
-        let output = DeleteObjectOutput::default(); // TODO: handle other fields
+        let deleted =
+            delete_object_entry(self, &input.bucket, &input.key, input.version_id.as_deref(), bypass_governance).await?;
+        self.refresh_public_object(&input.bucket, &input.key).await?;
+
+        // the version id is the one of the deleted version or of the new delete marker
+        let output = DeleteObjectOutput {
+            delete_marker: deleted.delete_marker,
+            version_id: deleted.version_id.or(deleted.delete_marker_version_id),
+            request_charged,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn delete_object_tagging(
+        &self,
+        req: S3Request<DeleteObjectTaggingInput>,
+    ) -> S3Result<S3Response<DeleteObjectTaggingOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:DeleteObjectTagging", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        location.save_tagging(&TagMap::new()).await?;
+
+        let output = DeleteObjectTaggingOutput::default();
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
     async fn delete_objects(&self, req: S3Request<DeleteObjectsInput>) -> S3Result<S3Response<DeleteObjectsOutput>> {
+        const MAX_OBJECTS: usize = 1000;
+
         let input = req.input;
-        let mut objects: Vec<(PathBuf, String)> = Vec::new();
-        for object in input.delete.objects {
-            let path = self.get_object_path(&input.bucket, &object.key)?;
-            if path.exists() {
-                objects.push((path, object.key));
-            }
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        if input.delete.objects.len() > MAX_OBJECTS {
+            return Err(s3_error!(MalformedXML, "The request must not contain more than {} objects", MAX_OBJECTS));
+        }
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
         }
 
+        let bypass_governance = can_bypass_governance(input.bypass_governance_retention, req.credentials.as_ref());
+        let quiet = input.delete.quiet;
         let mut deleted_objects: Vec<DeletedObject> = Vec::new();
-        for (path, key) in objects {
-            try_!(fs::remove_file(path).await);
+        let mut errors: Vec<s3s::dto::Error> = Vec::new();
 
-            let deleted_object = DeletedObject {
-                key: Some(key),
-                ..Default::default()
+        // a failed key is reported in the result without aborting the batch
+        for object in input.delete.objects {
+            let result = async {
+                let credentials = req.credentials.as_ref();
+                self.check_policy(credentials, "s3:DeleteObject", &input.bucket, Some(&object.key))
+                    .await?;
+                let deleted_object =
+                    delete_object_entry(self, &input.bucket, &object.key, object.version_id.as_deref(), bypass_governance)
+                        .await?;
+                self.refresh_public_object(&input.bucket, &object.key).await?;
+                Ok::<_, S3Error>(deleted_object)
             };
-
-            deleted_objects.push(deleted_object);
+            match result.await {
+                Ok(deleted_object) => {
+                    if quiet.not() {
+                        deleted_objects.push(deleted_object);
+                    }
+                }
+                Err(err) => {
+                    debug!(key = %object.key, ?err, "delete object failed");
+                    errors.push(s3s::dto::Error {
+                        code: Some(err.code().as_str().to_owned()),
+                        key: Some(object.key),
+                        message: err.message().map(ToOwned::to_owned),
+                        version_id: object.version_id,
+                    });
+                }
+            }
         }
 
         let output = DeleteObjectsOutput {
             deleted: Some(deleted_objects),
-            ..Default::default()
+            errors: errors.is_empty().not().then_some(errors),
+            request_charged,
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn get_bucket_location(&self, req: S3Request<GetBucketLocationInput>) -> S3Result<S3Response<GetBucketLocationOutput>> {
+    async fn delete_bucket_cors(&self, req: S3Request<DeleteBucketCorsInput>) -> S3Result<S3Response<DeleteBucketCorsOutput>> {
         let input = req.input;
-        let path = self.get_bucket_path(&input.bucket)?;
-
-        if !path.exists() {
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketCORS", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        let output = GetBucketLocationOutput::default();
-        Ok(S3Response::new(output))
+        self.remove_bucket_cors(&input.bucket).await?;
+
+        Ok(S3Response::new(DeleteBucketCorsOutput {}))
     }
 
     #[tracing::instrument]
-    async fn get_object(&self, req: S3Request<GetObjectInput>) -> S3Result<S3Response<GetObjectOutput>> {
+    async fn get_bucket_cors(&self, req: S3Request<GetBucketCorsInput>) -> S3Result<S3Response<GetBucketCorsOutput>> {
         let input = req.input;
-        let object_path = self.get_object_path(&input.bucket, &input.key)?;
-
-        let mut file = fs::File::open(&object_path).await.map_err(|e| s3_error!(e, NoSuchKey))?;
-
-        let file_metadata = try_!(file.metadata().await);
-        let last_modified = Timestamp::from(try_!(file_metadata.modified()));
-        let file_len = file_metadata.len();
-
-        let content_length = match input.range {
-            None => file_len,
-            Some(range) => {
-                let file_range = range.check(file_len)?;
-                file_range.end - file_range.start
-            }
-        };
-        let content_length_usize = try_!(usize::try_from(content_length));
-        let content_length_i64 = try_!(i64::try_from(content_length));
-
-        match input.range {
-            Some(Range::Int { first, .. }) => {
-                try_!(file.seek(io::SeekFrom::Start(first)).await);
-            }
-            Some(Range::Suffix { length }) => {
-                let neg_offset = length.numeric_cast::<i64>().neg();
-                try_!(file.seek(io::SeekFrom::End(neg_offset)).await);
-            }
-            None => {}
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketCORS", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
         }
 
-        let body = bytes_stream(ReaderStream::with_capacity(file, 4096), content_length_usize);
-
-        let object_metadata = self.load_metadata(&input.bucket, &input.key).await?;
-
-        let md5_sum = self.get_md5_sum(&input.bucket, &input.key).await?;
-        let e_tag = format!("\"{md5_sum}\"");
-
-        let info = self.load_internal_info(&input.bucket, &input.key).await?;
-        let checksum = match &info {
-            Some(info) => crate::checksum::from_internal_info(info),
-            None => default(),
+        let Some(cors_rules) = self.load_bucket_cors(&input.bucket).await? else {
+            return Err(s3_error!(NoSuchCORSConfiguration));
         };
 
-        let output = GetObjectOutput {
-            body: Some(StreamingBlob::wrap(body)),
-            content_length: content_length_i64,
-            last_modified: Some(last_modified),
-            metadata: object_metadata,
-            e_tag: Some(e_tag),
-            checksum_crc32: checksum.checksum_crc32,
-            checksum_crc32c: checksum.checksum_crc32c,
-            checksum_sha1: checksum.checksum_sha1,
-            checksum_sha256: checksum.checksum_sha256,
-            ..Default::default()
+        let output = GetBucketCorsOutput {
+            cors_rules: Some(cors_rules),
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn head_bucket(&self, req: S3Request<HeadBucketInput>) -> S3Result<S3Response<HeadBucketOutput>> {
+    async fn get_bucket_location(&self, req: S3Request<GetBucketLocationInput>) -> S3Result<S3Response<GetBucketLocationOutput>> {
         let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketLocation", &input.bucket, None)
+            .await?;
         let path = self.get_bucket_path(&input.bucket)?;
 
-        if !path.exists() {
+        if !self.storage.exists(&path).await {
             return Err(s3_error!(NoSuchBucket));
         }
 
-        Ok(S3Response::new(HeadBucketOutput {}))
+        let location = self.load_bucket_region(&input.bucket).await?;
+
+        // `us-east-1` is represented by an empty location constraint
+        let location_constraint = (location != DEFAULT_REGION).then(|| BucketLocationConstraint::from(location));
+
+        let output = GetBucketLocationOutput { location_constraint };
+        Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn head_object(&self, req: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
+    async fn get_bucket_acl(&self, req: S3Request<GetBucketAclInput>) -> S3Result<S3Response<GetBucketAclOutput>> {
         let input = req.input;
-        let path = self.get_object_path(&input.bucket, &input.key)?;
-
-        if !path.exists() {
-            return Err(s3_error!(NoSuchBucket));
-        }
-
-        let file_metadata = try_!(fs::metadata(path).await);
-        let last_modified = Timestamp::from(try_!(file_metadata.modified()));
-        let file_len = file_metadata.len();
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketAcl", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
 
-        let object_metadata = self.load_metadata(&input.bucket, &input.key).await?;
+        let owner = self.bucket_owner(&input.bucket, req.credentials.as_ref()).await?;
+        let acl = self.load_bucket_acl(&input.bucket, owner.as_ref()).await?;
 
-        // TODO: detect content type
-        let content_type = mime::APPLICATION_OCTET_STREAM;
-
-        let output = HeadObjectOutput {
-            content_length: try_!(i64::try_from(file_len)),
-            content_type: Some(content_type),
-            last_modified: Some(last_modified),
-            metadata: object_metadata,
-            ..Default::default()
+        let output = GetBucketAclOutput {
+            grants: acl.grants,
+            owner: acl.owner,
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn list_buckets(&self, _: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
-        let mut buckets: Vec<Bucket> = Vec::new();
-        let mut iter = try_!(fs::read_dir(&self.root).await);
-        while let Some(entry) = try_!(iter.next_entry().await) {
-            let file_type = try_!(entry.file_type().await);
-            if file_type.is_dir().not() {
-                continue;
-            }
-
-            let file_name = entry.file_name();
-            let Some(name) = file_name.to_str() else { continue };
+    async fn put_bucket_acl(&self, req: S3Request<PutBucketAclInput>) -> S3Result<S3Response<PutBucketAclOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketAcl", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let owner = self.bucket_owner(&input.bucket, req.credentials.as_ref()).await?;
+        let policy = input.access_control_policy;
+        let policy_owner = policy.as_ref().and_then(|p| p.owner.as_ref()).and_then(|o| o.id.as_deref());
+        let owner_id = owner.as_ref().and_then(|o| o.id.as_deref());
+        if policy_owner.is_some_and(|id| owner_id.is_some_and(|owner_id| owner_id != id)) {
+            return Err(s3_error!(AccessDenied, "The owner of the ACL must be the owner of the bucket"));
+        }
+
+        let grant_headers = GrantHeaders {
+            full_control: input.grant_full_control.as_deref(),
+            read: input.grant_read.as_deref(),
+            read_acp: input.grant_read_acp.as_deref(),
+            write: input.grant_write.as_deref(),
+            write_acp: input.grant_write_acp.as_deref(),
+        };
+        let canned_acl = input.acl.as_ref().map(BucketCannedACL::as_str);
+        let has_grants = grant_headers.is_empty().not() || policy.is_some();
+        self.check_acl_allowed(&input.bucket, canned_acl, has_grants, BucketCannedACL::PRIVATE)
+            .await?;
+        let Some(grants) = crate::acl::requested_grants(canned_acl, &grant_headers, policy, owner.as_ref())? else {
+            return Err(s3_error!(MissingSecurityHeader, "Your request was missing a required header"));
+        };
+        self.save_bucket_acl(&input.bucket, owner.as_ref(), grants).await?;
+
+        Ok(S3Response::new(PutBucketAclOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_policy(&self, req: S3Request<GetBucketPolicyInput>) -> S3Result<S3Response<GetBucketPolicyOutput>> {
+        let input = req.input;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(policy) = self.load_bucket_policy(&input.bucket).await? else {
+            return Err(s3_error!(NoSuchBucketPolicy));
+        };
+
+        let output = GetBucketPolicyOutput { policy: Some(policy) };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_policy(&self, req: S3Request<PutBucketPolicyInput>) -> S3Result<S3Response<PutBucketPolicyOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_writable()?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        crate::policy::Policy::parse(&input.policy)?;
+        self.save_bucket_policy(&input.bucket, &input.policy).await?;
+
+        Ok(S3Response::new(PutBucketPolicyOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn delete_bucket_policy(
+        &self,
+        req: S3Request<DeleteBucketPolicyInput>,
+    ) -> S3Result<S3Response<DeleteBucketPolicyOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_writable()?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        self.remove_bucket_policy(&input.bucket).await?;
+
+        Ok(S3Response::new(DeleteBucketPolicyOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_website(&self, req: S3Request<PutBucketWebsiteInput>) -> S3Result<S3Response<PutBucketWebsiteOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketWebsite", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        crate::website::check_website_configuration(&input.website_configuration)?;
+        self.save_bucket_website(&input.bucket, input.website_configuration).await?;
+
+        Ok(S3Response::new(PutBucketWebsiteOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_website(&self, req: S3Request<GetBucketWebsiteInput>) -> S3Result<S3Response<GetBucketWebsiteOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketWebsite", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(config) = self.load_bucket_website(&input.bucket).await? else {
+            return Err(s3_error!(NoSuchWebsiteConfiguration));
+        };
+
+        let output = GetBucketWebsiteOutput {
+            error_document: config.error_document,
+            index_document: config.index_document,
+            redirect_all_requests_to: config.redirect_all_requests_to,
+            routing_rules: config.routing_rules,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn delete_bucket_website(
+        &self,
+        req: S3Request<DeleteBucketWebsiteInput>,
+    ) -> S3Result<S3Response<DeleteBucketWebsiteOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:DeleteBucketWebsite", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        self.remove_bucket_website(&input.bucket).await?;
+
+        Ok(S3Response::new(DeleteBucketWebsiteOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_lifecycle_configuration(
+        &self,
+        req: S3Request<PutBucketLifecycleConfigurationInput>,
+    ) -> S3Result<S3Response<PutBucketLifecycleConfigurationOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutLifecycleConfiguration", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(config) = input.lifecycle_configuration else {
+            return Err(s3_error!(MalformedXML, "missing lifecycle configuration"));
+        };
+        crate::lifecycle::check_lifecycle_configuration(&config)?;
+        self.save_bucket_lifecycle(&input.bucket, config).await?;
+
+        Ok(S3Response::new(PutBucketLifecycleConfigurationOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_lifecycle_configuration(
+        &self,
+        req: S3Request<GetBucketLifecycleConfigurationInput>,
+    ) -> S3Result<S3Response<GetBucketLifecycleConfigurationOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetLifecycleConfiguration", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(rules) = self.load_bucket_lifecycle(&input.bucket).await? else {
+            return Err(s3_error!(NoSuchLifecycleConfiguration));
+        };
+
+        let output = GetBucketLifecycleConfigurationOutput { rules: Some(rules) };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn delete_bucket_lifecycle(
+        &self,
+        req: S3Request<DeleteBucketLifecycleInput>,
+    ) -> S3Result<S3Response<DeleteBucketLifecycleOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutLifecycleConfiguration", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        self.remove_bucket_lifecycle(&input.bucket).await?;
+
+        Ok(S3Response::new(DeleteBucketLifecycleOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_ownership_controls(
+        &self,
+        req: S3Request<PutBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<PutBucketOwnershipControlsOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketOwnershipControls", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        crate::ownership::check_ownership_controls(&input.ownership_controls)?;
+        let ownership = input.ownership_controls.rules[0].object_ownership.as_str();
+        self.save_object_ownership(&input.bucket, Some(ownership)).await?;
+
+        Ok(S3Response::new(PutBucketOwnershipControlsOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_ownership_controls(
+        &self,
+        req: S3Request<GetBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<GetBucketOwnershipControlsOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketOwnershipControls", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        let Some(ownership) = crate::ownership::get_object_ownership(&config) else {
+            return Err(crate::ownership::ownership_controls_not_found());
+        };
+        let rule = OwnershipControlsRule {
+            object_ownership: ObjectOwnership::from(ownership.to_owned()),
+        };
+        let output = GetBucketOwnershipControlsOutput {
+            ownership_controls: Some(OwnershipControls { rules: vec![rule] }),
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn delete_bucket_ownership_controls(
+        &self,
+        req: S3Request<DeleteBucketOwnershipControlsInput>,
+    ) -> S3Result<S3Response<DeleteBucketOwnershipControlsOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketOwnershipControls", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        self.save_object_ownership(&input.bucket, None).await?;
+
+        Ok(S3Response::new(DeleteBucketOwnershipControlsOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_request_payment(
+        &self,
+        req: S3Request<PutBucketRequestPaymentInput>,
+    ) -> S3Result<S3Response<PutBucketRequestPaymentOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketRequestPayment", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let payment = input.request_payment_configuration;
+        crate::request_payment::check_request_payment(&payment)?;
+
+        let mut config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        crate::request_payment::set_payer(&mut config, payment.payer.as_str());
+        self.save_bucket_config(&input.bucket, &config).await?;
+
+        Ok(S3Response::new(PutBucketRequestPaymentOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_request_payment(
+        &self,
+        req: S3Request<GetBucketRequestPaymentInput>,
+    ) -> S3Result<S3Response<GetBucketRequestPaymentOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketRequestPayment", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        let payer = crate::request_payment::get_payer(&config);
+        let output = GetBucketRequestPaymentOutput {
+            payer: Some(Payer::from(payer.to_owned())),
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_bucket_versioning(
+        &self,
+        req: S3Request<GetBucketVersioningInput>,
+    ) -> S3Result<S3Response<GetBucketVersioningOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketVersioning", &input.bucket, None)
+            .await?;
+        let path = self.get_bucket_path(&input.bucket)?;
+
+        if !self.storage.exists(&path).await {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        let get_str = |name: &str| config.get(name).and_then(|v| v.as_str()).map(ToOwned::to_owned);
+
+        let output = GetBucketVersioningOutput {
+            status: get_str("versioning_status").map(BucketVersioningStatus::from),
+            mfa_delete: get_str("mfa_delete").map(MFADeleteStatus::from),
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object(&self, req: S3Request<GetObjectInput>) -> S3Result<S3Response<GetObjectOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:GetObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let response_content_type = check_response_overrides(&input, req.credentials.as_ref())?;
+        // the data and the sidecar files are read without a commit in between
+        let _lock = self.key_locks.read(&input.bucket, &input.key).await;
+        let lookup = self
+            .locate_version(&input.bucket, &input.key, input.version_id.as_deref())
+            .await?;
+        let location = found_version(lookup)?;
+
+        let mut file = open_object(self, &location, &input.key).await?;
+
+        let file_metadata = try_!(file.metadata().await);
+        let modified = modified_time(&file_metadata);
+
+        let info = location.load_internal_info().await?;
+        if let Some(resp) = website_redirect(self, info.as_ref())? {
+            return Ok(resp);
+        }
+        let customer_key = CustomerKey::from_headers(
+            input.sse_customer_algorithm.as_deref(),
+            input.sse_customer_key.as_deref(),
+            input.sse_customer_key_md5.as_deref(),
+        )?;
+        let cipher = crate::sse::object_cipher(info.as_ref(), customer_key.as_ref())?;
+
+        let encrypted_len = file_metadata.len;
+        let file_len = if cipher.is_some() {
+            crate::sse::plaintext_len(encrypted_len)
+        } else {
+            encrypted_len
+        };
+
+        let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+
+        let conditions = ReadConditions {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            if_modified_since: input.if_modified_since.as_ref(),
+            if_unmodified_since: input.if_unmodified_since.as_ref(),
+        };
+        conditions.check(&e_tag, modified)?;
+
+        let last_modified = Timestamp::from(modified);
+
+        let file_range = match input.range {
+            None => None,
+            Some(range) => Some(range.check(file_len).map_err(|_| range_not_satisfiable(file_len))?),
+        };
+
+        let content_length = file_range.as_ref().map_or(file_len, |r| r.end - r.start);
+        let content_length_usize = try_!(usize::try_from(content_length));
+        let content_length_i64 = try_!(i64::try_from(content_length));
+
+        let start = file_range.as_ref().map_or(0, |r| r.start);
+        let body = if let Some(cipher) = cipher {
+            let stream = crate::sse::decrypt_stream(file, cipher, encrypted_len, start);
+            StreamingBlob::wrap(bytes_stream(stream, content_length_usize))
+        } else {
+            try_!(file.seek(io::SeekFrom::Start(start)).await);
+            StreamingBlob::wrap(bytes_stream(
+                ReaderStream::with_capacity(file, self.read_buffer_size),
+                content_length_usize,
+            ))
+        };
+
+        let object_metadata = location.load_metadata().await?;
+        let object_lock = ObjectLock::from_internal_info(info.as_ref());
+        let tag_count = location.load_tagging().await?.len();
+        // the stored checksums cover the whole object, not a partial body
+        let checksum = match &info {
+            Some(info) if file_range.is_none() && is_checksum_mode_enabled(input.checksum_mode.as_ref()) => {
+                crate::checksum::from_internal_info(info)
+            }
+            _ => default(),
+        };
+
+        let content_range = file_range
+            .as_ref()
+            .map(|r| format!("bytes {}-{}/{file_len}", r.start, r.end - 1));
+
+        let output = GetObjectOutput {
+            body: Some(body),
+            content_length: content_length_i64,
+            accept_ranges: Some("bytes".to_owned()),
+            content_range,
+            content_type: Some(response_content_type.unwrap_or_else(|| stored_content_type(self, &input.key, info.as_ref()))),
+            content_disposition: input
+                .response_content_disposition
+                .or_else(|| stored_content_disposition(info.as_ref())),
+            cache_control: input.response_cache_control,
+            content_encoding: input
+                .response_content_encoding
+                .or_else(|| stored_content_encoding(info.as_ref())),
+            content_language: input.response_content_language,
+            expires: input.response_expires,
+            last_modified: Some(last_modified),
+            metadata: object_metadata,
+            tag_count: try_!(i32::try_from(tag_count)),
+            e_tag: Some(e_tag),
+            storage_class: storage_class_header(info.as_ref()),
+            restore: restore_header(info.as_ref()),
+            website_redirect_location: stored_website_redirect_location(info.as_ref()),
+            sse_customer_algorithm: customer_key.as_ref().map(|_| crate::sse::ALGORITHM.to_owned()),
+            sse_customer_key_md5: customer_key.as_ref().map(|k| k.key_md5().to_owned()),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            object_lock_mode: object_lock.mode(),
+            object_lock_retain_until_date: object_lock.retain_until_date(),
+            object_lock_legal_hold_status: object_lock.legal_hold_status(),
+            version_id: object_version_id(self, &input.bucket, info.as_ref()).await?,
+            request_charged,
+            ..Default::default()
+        };
+
+        let mut resp = S3Response::new(output);
+        if file_range.is_some() {
+            resp.status = Some(StatusCode::PARTIAL_CONTENT);
+        }
+        Ok(resp)
+    }
+
+    #[tracing::instrument]
+    async fn get_object_acl(&self, req: S3Request<GetObjectAclInput>) -> S3Result<S3Response<GetObjectAclOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:GetObjectAcl", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let info = location.load_internal_info().await?;
+
+        let requester = req.credentials.as_ref().map(|c| crate::acl::owner_of(&c.access_key));
+        let acl = crate::acl::object_acl(info.as_ref(), requester.as_ref());
+
+        let output = GetObjectAclOutput {
+            grants: acl.grants,
+            owner: acl.owner,
+            request_charged,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object_tagging(&self, req: S3Request<GetObjectTaggingInput>) -> S3Result<S3Response<GetObjectTaggingOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetObjectTagging", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let tagging = location.load_tagging().await?;
+
+        let output = GetObjectTaggingOutput {
+            tag_set: tags_from_tag_map(tagging),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object_attributes(
+        &self,
+        req: S3Request<GetObjectAttributesInput>,
+    ) -> S3Result<S3Response<GetObjectAttributesOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:GetObjectAttributes", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let mut attributes = Vec::with_capacity(input.object_attributes.len());
+        for attribute in &input.object_attributes {
+            match attribute.as_str() {
+                ObjectAttributes::CHECKSUM
+                | ObjectAttributes::ETAG
+                | ObjectAttributes::OBJECT_PARTS
+                | ObjectAttributes::OBJECT_SIZE
+                | ObjectAttributes::STORAGE_CLASS => attributes.push(attribute.as_str()),
+                _ => return Err(s3_error!(InvalidArgument, "Invalid attribute name specified.")),
+            }
+        }
+        let wants = |attribute: &str| attributes.contains(&attribute);
+
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let file_metadata = try_!(self.storage.metadata(&location.data).await);
+        let modified = modified_time(&file_metadata);
+
+        let info = location.load_internal_info().await?;
+        let customer_key = CustomerKey::from_headers(
+            input.sse_customer_algorithm.as_deref(),
+            input.sse_customer_key.as_deref(),
+            input.sse_customer_key_md5.as_deref(),
+        )?;
+        let file_len = match crate::sse::object_cipher(info.as_ref(), customer_key.as_ref())? {
+            Some(_) => crate::sse::plaintext_len(file_metadata.len),
+            None => file_metadata.len,
+        };
+
+        let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+
+        let checksum = match &info {
+            Some(info) if wants(ObjectAttributes::CHECKSUM) => {
+                Some(crate::checksum::from_internal_info(info)).filter(|c| crate::checksum::provided_algorithm(c).is_some())
+            }
+            _ => None,
+        };
+        let object_parts = if wants(ObjectAttributes::OBJECT_PARTS) {
+            object_parts(info.as_ref(), &e_tag, input.max_parts, input.part_number_marker)?
+        } else {
+            None
+        };
+
+        let output = GetObjectAttributesOutput {
+            checksum,
+            // unlike other operations, the entity tag is not quoted
+            e_tag: wants(ObjectAttributes::ETAG).then(|| e_tag.trim_matches('"').to_owned()),
+            last_modified: Some(Timestamp::from(modified)),
+            object_parts,
+            object_size: if wants(ObjectAttributes::OBJECT_SIZE) {
+                try_!(i64::try_from(file_len))
+            } else {
+                0
+            },
+            storage_class: wants(ObjectAttributes::STORAGE_CLASS)
+                .then(|| StorageClass::from(stored_storage_class(info.as_ref()).to_owned())),
+            request_charged,
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn head_bucket(&self, req: S3Request<HeadBucketInput>) -> S3Result<S3Response<HeadBucketOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:ListBucket", &input.bucket, None)
+            .await?;
+        let path = self.get_bucket_path(&input.bucket)?;
+
+        if !self.storage.exists(&path).await {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let region = self.load_bucket_region(&input.bucket).await?;
+
+        let mut resp = S3Response::new(HeadBucketOutput {});
+        if let Ok(region) = HeaderValue::try_from(region) {
+            resp.headers.insert(X_AMZ_BUCKET_REGION, region);
+        }
+        Ok(resp)
+    }
+
+    #[tracing::instrument]
+    async fn head_object(&self, req: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:GetObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let _lock = self.key_locks.read(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+
+        let file_metadata = try_!(self.storage.metadata(&location.data).await);
+        let modified = modified_time(&file_metadata);
+
+        let info = location.load_internal_info().await?;
+        if let Some(resp) = website_redirect(self, info.as_ref())? {
+            return Ok(resp);
+        }
+        let customer_key = CustomerKey::from_headers(
+            input.sse_customer_algorithm.as_deref(),
+            input.sse_customer_key.as_deref(),
+            input.sse_customer_key_md5.as_deref(),
+        )?;
+        let file_len = match crate::sse::object_cipher(info.as_ref(), customer_key.as_ref())? {
+            Some(_) => crate::sse::plaintext_len(file_metadata.len),
+            None => file_metadata.len,
+        };
+
+        let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+
+        let conditions = ReadConditions {
+            if_match: input.if_match.as_deref(),
+            if_none_match: input.if_none_match.as_deref(),
+            if_modified_since: input.if_modified_since.as_ref(),
+            if_unmodified_since: input.if_unmodified_since.as_ref(),
+        };
+        conditions.check(&e_tag, modified)?;
+
+        let last_modified = Timestamp::from(modified);
+
+        let object_metadata = location.load_metadata().await?;
+        let object_lock = ObjectLock::from_internal_info(info.as_ref());
+        let checksum = match &info {
+            Some(info) if is_checksum_mode_enabled(input.checksum_mode.as_ref()) => crate::checksum::from_internal_info(info),
+            _ => default(),
+        };
+
+        let output = HeadObjectOutput {
+            content_length: try_!(i64::try_from(file_len)),
+            accept_ranges: Some("bytes".to_owned()),
+            content_type: Some(stored_content_type(self, &input.key, info.as_ref())),
+            content_disposition: stored_content_disposition(info.as_ref()),
+            content_encoding: stored_content_encoding(info.as_ref()),
+            e_tag: Some(e_tag),
+            last_modified: Some(last_modified),
+            metadata: object_metadata,
+            storage_class: storage_class_header(info.as_ref()),
+            restore: restore_header(info.as_ref()),
+            website_redirect_location: stored_website_redirect_location(info.as_ref()),
+            sse_customer_algorithm: customer_key.as_ref().map(|_| crate::sse::ALGORITHM.to_owned()),
+            sse_customer_key_md5: customer_key.as_ref().map(|k| k.key_md5().to_owned()),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            object_lock_mode: object_lock.mode(),
+            object_lock_retain_until_date: object_lock.retain_until_date(),
+            object_lock_legal_hold_status: object_lock.legal_hold_status(),
+            version_id: object_version_id(self, &input.bucket, info.as_ref()).await?,
+            request_charged,
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_buckets(&self, req: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+        let mut buckets: Vec<Bucket> = Vec::new();
+        for entry in try_!(self.storage.read_dir(&self.root).await) {
+            if entry.is_dir.not() {
+                continue;
+            }
+
+            // reserved names like the metadata directory are not valid bucket names
+            let Some(name) = entry.file_name() else { continue };
             if s3s::path::check_bucket_name(name).not() {
                 continue;
             }
 
-            let file_meta = try_!(entry.metadata().await);
-            // Not all filesystems/mounts provide all file attributes like created timestamp,
-            // therefore we try to fallback to modified if possible.
-            // See https://github.com/Nugine/s3s/pull/22 for more details.
-            let created_or_modified_date = Timestamp::from(try_!(file_meta.created().or(file_meta.modified())));
+            let config = self.load_bucket_config(name).await?.unwrap_or_default();
+            let stored_date = config
+                .get("creation_date")
+                .and_then(|v| serde_json::from_value::<SystemTime>(v.clone()).ok());
+            let creation_date = if let Some(date) = stored_date {
+                date
+            } else {
+                let file_meta = try_!(self.storage.symlink_metadata(&entry.path).await);
+                // Not all filesystems/mounts provide all file attributes like created timestamp,
+                // therefore we try to fallback to modified if possible.
+                // See https://github.com/Nugine/s3s/pull/22 for more details.
+                file_meta.created.unwrap_or(file_meta.modified)
+            };
 
             let bucket = Bucket {
-                creation_date: Some(created_or_modified_date),
+                creation_date: Some(Timestamp::from(creation_date)),
                 name: Some(name.to_owned()),
             };
             buckets.push(bucket);
         }
 
+        buckets.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let owner = req.credentials.map(|c| Owner {
+            display_name: Some(c.access_key.clone()),
+            id: Some(c.access_key),
+        });
+
         let output = ListBucketsOutput {
             buckets: Some(buckets),
-            owner: None,
+            owner,
         };
         Ok(S3Response::new(output))
     }
@@ -325,193 +1782,877 @@ impl S3 for FileSystem {
     async fn list_objects(&self, req: S3Request<ListObjectsInput>) -> S3Result<S3Response<ListObjectsOutput>> {
         let v2_resp = self.list_objects_v2(req.map_input(Into::into)).await?;
 
-        Ok(v2_resp.map_output(|v2| ListObjectsOutput {
-            contents: v2.contents,
-            delimiter: v2.delimiter,
-            encoding_type: v2.encoding_type,
-            name: v2.name,
-            prefix: v2.prefix,
-            max_keys: v2.max_keys,
-            ..Default::default()
+        Ok(v2_resp.map_output(|v2| {
+            // the last key or common prefix of the page, which is also the continuation token
+            let next_marker = v2
+                .next_continuation_token
+                .as_deref()
+                .and_then(|t| decode_continuation_token(t).ok());
+            ListObjectsOutput {
+                common_prefixes: v2.common_prefixes,
+                contents: v2.contents,
+                delimiter: v2.delimiter,
+                encoding_type: v2.encoding_type,
+                name: v2.name,
+                prefix: v2.prefix,
+                max_keys: v2.max_keys,
+                is_truncated: v2.is_truncated,
+                marker: v2.start_after,
+                next_marker,
+                request_charged: v2.request_charged,
+            }
         }))
     }
 
     #[tracing::instrument]
     async fn list_objects_v2(&self, req: S3Request<ListObjectsV2Input>) -> S3Result<S3Response<ListObjectsV2Output>> {
         let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:ListBucket", &input.bucket, None)
+            .await?;
         let path = self.get_bucket_path(&input.bucket)?;
 
-        if path.exists().not() {
+        if self.storage.exists(&path).await.not() {
             return Err(s3_error!(NoSuchBucket));
         }
 
+        // keys can not start with a slash on disk
+        let prefix = input.prefix.as_deref().map_or("", |p| p.trim_start_matches('/'));
+        let delimiter = input.delimiter.as_deref().filter(|d| d.is_empty().not());
+
+        let (max_keys, limit) = page_size(self, input.max_keys)?;
+
+        // the continuation token takes precedence over `start-after`
+        let marker = match input.continuation_token {
+            Some(ref token) => decode_continuation_token(token)?,
+            None => input.start_after.clone().unwrap_or_default(),
+        };
+
+        let mut entries = list_keys(self, &input.bucket, prefix).await?;
+        // the common prefixes which are listed by this page or by the previous pages
+        let mut listed_prefixes = if self.listing_order == ListingOrder::Key && self.reverse_listings.not() {
+            entries.retain(|(key, _)| key.as_str() > marker.as_str());
+            HashSet::new()
+        } else {
+            let (order, reverse) = (self.listing_order, self.reverse_listings);
+            crate::listing::sort_and_skip(&mut entries, order, reverse, &marker, prefix, delimiter)
+        };
+
         let mut objects: Vec<Object> = default();
-        let mut dir_queue: VecDeque<PathBuf> = default();
-        dir_queue.push_back(path.clone());
-
-        while let Some(dir) = dir_queue.pop_front() {
-            let mut iter = try_!(fs::read_dir(dir).await);
-            while let Some(entry) = try_!(iter.next_entry().await) {
-                let file_type = try_!(entry.file_type().await);
-                if file_type.is_dir() {
-                    dir_queue.push_back(entry.path());
-                } else {
-                    let file_path = entry.path();
-                    let key = try_!(file_path.strip_prefix(&path));
-                    let delimiter = input.delimiter.as_ref().map_or("/", |d| d.as_str());
-                    let Some(key_str) = normalize_path(key, delimiter) else {
-                        continue;
-                    };
-
-                    if let Some(ref prefix) = input.prefix {
-                        let prefix_path: PathBuf = prefix.split(delimiter).collect();
-
-                        let key_s = format!("{}", key.display());
-                        let prefix_path_s = format!("{}", prefix_path.display());
-
-                        if !key_s.starts_with(&prefix_path_s) {
-                            continue;
-                        }
-                    }
+        let mut common_prefixes: Vec<String> = default();
+        let mut last_key: Option<String> = None;
+        let mut is_truncated = false;
+
+        for (key, metadata) in entries {
+            let common_prefix = crate::listing::common_prefix(&key, prefix, delimiter);
+
+            if let Some(common_prefix) = common_prefix {
+                if common_prefix == marker || listed_prefixes.contains(common_prefix) {
+                    continue;
+                }
+            }
+
+            if objects.len() + common_prefixes.len() == limit {
+                is_truncated = true;
+                break;
+            }
+
+            if let Some(common_prefix) = common_prefix {
+                listed_prefixes.insert(common_prefix.to_owned());
+                common_prefixes.push(common_prefix.to_owned());
+                last_key = Some(common_prefix.to_owned());
+                continue;
+            }
+            last_key = Some(key.clone());
+
+            let last_modified = Timestamp::from(modified_time(&metadata));
+
+            let info = self.load_internal_info(&input.bucket, &key).await?;
+            let size = if crate::sse::is_encrypted(info.as_ref()) {
+                crate::sse::plaintext_len(metadata.len)
+            } else {
+                metadata.len
+            };
+            let storage_class = stored_storage_class(info.as_ref()).to_owned();
+            let location = self.current_location(&input.bucket, &key)?;
+            let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+
+            objects.push(Object {
+                e_tag: Some(e_tag),
+                key: Some(key),
+                last_modified: Some(last_modified),
+                size: try_!(i64::try_from(size)),
+                storage_class: Some(ObjectStorageClass::from(storage_class)),
+                ..Default::default()
+            });
+        }
+
+        let key_count = try_!(i32::try_from(objects.len() + common_prefixes.len()));
+        let next_continuation_token = last_key.filter(|_| is_truncated).map(|key| encode_continuation_token(&key));
+
+        let url_encode = input.encoding_type.as_ref().is_some_and(|e| e.as_str() == EncodingType::URL);
+        let encode = |s: String| if url_encode { url_encode_key(&s) } else { s };
+
+        for object in &mut objects {
+            object.key = object.key.take().map(encode);
+        }
+        let common_prefixes = common_prefixes
+            .into_iter()
+            .map(|prefix| CommonPrefix {
+                prefix: Some(encode(prefix)),
+            })
+            .collect::<Vec<_>>();
+
+        let output = ListObjectsV2Output {
+            key_count,
+            max_keys,
+            is_truncated,
+            continuation_token: input.continuation_token,
+            next_continuation_token,
+            common_prefixes: common_prefixes.is_empty().not().then_some(common_prefixes),
+            contents: Some(objects),
+            delimiter: input.delimiter.map(encode),
+            encoding_type: input.encoding_type,
+            name: Some(input.bucket),
+            prefix: input.prefix.map(encode),
+            start_after: input.start_after.map(encode),
+            request_charged,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_object_versions(
+        &self,
+        req: S3Request<ListObjectVersionsInput>,
+    ) -> S3Result<S3Response<ListObjectVersionsOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:ListBucketVersions", &input.bucket, None)
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        // keys can not start with a slash on disk
+        let prefix = input.prefix.as_deref().map_or("", |p| p.trim_start_matches('/'));
+        let delimiter = input.delimiter.as_deref().filter(|d| d.is_empty().not());
+
+        let (max_keys, limit) = page_size(self, input.max_keys)?;
+
+        let key_marker = input.key_marker.as_deref().unwrap_or_default();
+        let version_id_marker = input.version_id_marker.as_deref().filter(|v| v.is_empty().not());
+        if version_id_marker.is_some() && key_marker.is_empty() {
+            return Err(s3_error!(
+                InvalidArgument,
+                "A version-id marker cannot be specified without a key marker."
+            ));
+        }
+
+        // the keys of current objects and the keys whose latest version is a delete marker
+        let mut keys: Vec<String> = list_keys(self, &input.bucket, prefix)
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        keys.extend(self.list_versioned_keys(&input.bucket, prefix).await?);
+        keys.sort();
+        keys.dedup();
+        keys.retain(|key| key.as_str() >= key_marker);
+
+        let mut versions: Vec<ObjectVersion> = default();
+        let mut delete_markers: Vec<DeleteMarkerEntry> = default();
+        let mut common_prefixes: Vec<String> = default();
+        let mut count = 0;
+        let mut next_markers: Option<(String, Option<String>)> = None;
+        let mut is_truncated = false;
+
+        'keys: for key in keys {
+            let common_prefix = delimiter.and_then(|delimiter| {
+                let pos = key[prefix.len()..].find(delimiter)?;
+                Some(&key[..prefix.len() + pos + delimiter.len()])
+            });
+
+            if let Some(common_prefix) = common_prefix {
+                if common_prefix == key_marker || common_prefixes.last().map(String::as_str) == Some(common_prefix) {
+                    continue;
+                }
+                if count == limit {
+                    is_truncated = true;
+                    break;
+                }
+                common_prefixes.push(common_prefix.to_owned());
+                next_markers = Some((common_prefix.to_owned(), None));
+                count += 1;
+                continue;
+            }
+
+            let mut entries = self.list_versions(&input.bucket, &key).await?;
 
-                    let metadata = try_!(entry.metadata().await);
-                    let last_modified = Timestamp::from(try_!(metadata.modified()));
-                    let size = metadata.len();
+            // the versions after the marker are the older ones
+            if key == key_marker {
+                let Some(pos) = version_id_marker.and_then(|marker| entries.iter().position(|e| e.version_id == marker)) else {
+                    continue;
+                };
+                entries.drain(..=pos);
+            }
 
-                    let object = Object {
-                        key: Some(key_str),
+            for entry in entries {
+                if count == limit {
+                    is_truncated = true;
+                    break 'keys;
+                }
+                let last_modified = Timestamp::from(entry.last_modified);
+                next_markers = Some((key.clone(), Some(entry.version_id.clone())));
+                count += 1;
+
+                let Some(location) = entry.location else {
+                    delete_markers.push(DeleteMarkerEntry {
+                        is_latest: entry.is_latest,
+                        key: Some(key.clone()),
                         last_modified: Some(last_modified),
-                        size: try_!(i64::try_from(size)),
+                        version_id: Some(entry.version_id),
                         ..Default::default()
-                    };
-                    objects.push(object);
+                    });
+                    continue;
+                };
+
+                let info = location.load_internal_info().await?;
+                let file_len = try_!(self.storage.metadata(&location.data).await).len;
+                let size = if crate::sse::is_encrypted(info.as_ref()) {
+                    crate::sse::plaintext_len(file_len)
+                } else {
+                    file_len
+                };
+                let storage_class = stored_storage_class(info.as_ref()).to_owned();
+                let e_tag = object_e_tag(self, &location, info.as_ref()).await?;
+
+                versions.push(ObjectVersion {
+                    e_tag: Some(e_tag),
+                    is_latest: entry.is_latest,
+                    key: Some(key.clone()),
+                    last_modified: Some(last_modified),
+                    size: try_!(i64::try_from(size)),
+                    storage_class: Some(ObjectVersionStorageClass::from(storage_class)),
+                    version_id: Some(entry.version_id),
+                    ..Default::default()
+                });
+            }
+        }
+
+        let (next_key_marker, next_version_id_marker) = match next_markers.filter(|_| is_truncated) {
+            Some((key, version_id)) => (Some(key), version_id),
+            None => (None, None),
+        };
+
+        let url_encode = input.encoding_type.as_ref().is_some_and(|e| e.as_str() == EncodingType::URL);
+        let encode = |s: String| if url_encode { url_encode_key(&s) } else { s };
+
+        for version in &mut versions {
+            version.key = version.key.take().map(encode);
+        }
+        for marker in &mut delete_markers {
+            marker.key = marker.key.take().map(encode);
+        }
+        let common_prefixes = common_prefixes
+            .into_iter()
+            .map(|prefix| CommonPrefix {
+                prefix: Some(encode(prefix)),
+            })
+            .collect::<Vec<_>>();
+
+        let output = ListObjectVersionsOutput {
+            max_keys,
+            is_truncated,
+            key_marker: input.key_marker.map(encode),
+            version_id_marker: input.version_id_marker,
+            next_key_marker: next_key_marker.map(encode),
+            next_version_id_marker,
+            common_prefixes: common_prefixes.is_empty().not().then_some(common_prefixes),
+            versions: versions.is_empty().not().then_some(versions),
+            delete_markers: delete_markers.is_empty().not().then_some(delete_markers),
+            delimiter: input.delimiter.map(encode),
+            encoding_type: input.encoding_type,
+            name: Some(input.bucket),
+            prefix: input.prefix.map(encode),
+            request_charged,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_cors(&self, req: S3Request<PutBucketCorsInput>) -> S3Result<S3Response<PutBucketCorsOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketCORS", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        crate::cors::check_cors_configuration(&input.cors_configuration)?;
+        self.save_bucket_cors(&input.bucket, input.cors_configuration).await?;
+
+        Ok(S3Response::new(PutBucketCorsOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_bucket_versioning(
+        &self,
+        req: S3Request<PutBucketVersioningInput>,
+    ) -> S3Result<S3Response<PutBucketVersioningOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketVersioning", &input.bucket, None)
+            .await?;
+        let path = self.get_bucket_path(&input.bucket)?;
+
+        if !self.storage.exists(&path).await {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let VersioningConfiguration { status, mfa_delete } = input.versioning_configuration;
+
+        if let Some(ref status) = status {
+            let is_valid = [BucketVersioningStatus::ENABLED, BucketVersioningStatus::SUSPENDED].contains(&status.as_str());
+            if !is_valid {
+                return Err(s3_error!(MalformedXML, "invalid versioning status"));
+            }
+        }
+
+        if let Some(ref mfa_delete) = mfa_delete {
+            match mfa_delete.as_str() {
+                MFADelete::DISABLED => {}
+                MFADelete::ENABLED => {
+                    if input.mfa.is_none() {
+                        return Err(s3_error!(InvalidRequest, "MFA delete can only be enabled with the x-amz-mfa header"));
+                    }
+                    return Err(s3_error!(NotImplemented, "MFA delete is not supported"));
                 }
+                _ => return Err(s3_error!(MalformedXML, "invalid MFA delete status")),
             }
         }
 
-        objects.sort_by(|lhs, rhs| {
-            let lhs_key = lhs.key.as_deref().unwrap_or("");
-            let rhs_key = rhs.key.as_deref().unwrap_or("");
-            lhs_key.cmp(rhs_key)
-        });
+        if status.is_none() && mfa_delete.is_none() {
+            return Err(s3_error!(MalformedXML, "empty versioning configuration"));
+        }
 
-        let objects = if let Some(marker) = &input.start_after {
-            objects
-                .into_iter()
-                .skip_while(|n| n.key.as_deref().unwrap_or("") <= marker.as_str())
-                .collect()
+        if status
+            .as_ref()
+            .is_some_and(|s| s.as_str() == BucketVersioningStatus::SUSPENDED)
+            && self.load_object_lock_configuration(&input.bucket).await?.is_some()
+        {
+            return Err(s3_error!(
+                InvalidBucketState,
+                "An Object Lock configuration is present on this bucket, so the versioning state cannot be changed."
+            ));
+        }
+
+        let mut config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        if let Some(status) = status {
+            config.insert("versioning_status".to_owned(), serde_json::Value::String(status.as_str().to_owned()));
+        }
+        if let Some(mfa_delete) = mfa_delete {
+            config.insert("mfa_delete".to_owned(), serde_json::Value::String(mfa_delete.as_str().to_owned()));
+        }
+        self.save_bucket_config(&input.bucket, &config).await?;
+
+        Ok(S3Response::new(PutBucketVersioningOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:PutObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        check_storage_class(input.storage_class.as_ref())?;
+        check_website_redirect_location(input.website_redirect_location.as_deref())?;
+
+        let PutObjectInput {
+            body,
+            bucket,
+            key,
+            metadata,
+            content_length,
+            ..
+        } = input;
+
+        let Some(body) = body else { return Err(s3_error!(IncompleteBody)) };
+
+        let tagging = match input.tagging {
+            Some(ref header) => parse_tagging_header(header)?,
+            None => TagMap::new(),
+        };
+
+        // the owner of an object is the principal who writes it
+        let owner = req.credentials.as_ref().map(|c| crate::acl::owner_of(&c.access_key));
+        let grant_headers = GrantHeaders {
+            full_control: input.grant_full_control.as_deref(),
+            read: input.grant_read.as_deref(),
+            read_acp: input.grant_read_acp.as_deref(),
+            write: None,
+            write_acp: input.grant_write_acp.as_deref(),
+        };
+        let canned_acl = input.acl.as_ref().map(ObjectCannedACL::as_str);
+        self.check_acl_allowed(
+            &bucket,
+            canned_acl,
+            grant_headers.is_empty().not(),
+            ObjectCannedACL::BUCKET_OWNER_FULL_CONTROL,
+        )
+        .await?;
+        let grants = crate::acl::requested_grants(canned_acl, &grant_headers, None, owner.as_ref())?;
+
+        let customer_key = CustomerKey::from_headers(
+            input.sse_customer_algorithm.as_deref(),
+            input.sse_customer_key.as_deref(),
+            input.sse_customer_key_md5.as_deref(),
+        )?;
+
+        let mut expected_checksum = Checksum {
+            checksum_crc32: input.checksum_crc32,
+            checksum_crc32c: input.checksum_crc32c,
+            checksum_sha1: input.checksum_sha1,
+            checksum_sha256: input.checksum_sha256,
+        };
+        let checksum_algorithm = input.checksum_algorithm.as_ref().map(ChecksumAlgorithm::as_str);
+        let checksum_algorithm = checksum_algorithm.or_else(|| crate::checksum::trailer_algorithm(&req.headers));
+        let mut checksum = ChecksumCalculator::new(checksum_algorithm, &expected_checksum)?;
+
+        if key.ends_with('/') {
+            if let Some(len) = content_length {
+                if len > 0 {
+                    return Err(s3_error!(UnexpectedContent, "Unexpected request body when creating a directory object."));
+                }
+            }
+        }
+
+        check_content_length(self, content_length)?;
+
+        let exists = self.get_current_version_id(&bucket, &key).await?.is_some();
+        check_if_none_match_write(input.if_none_match.as_deref(), exists)?;
+        check_object_lock(self, &bucket, &key, None, false).await?;
+
+        let object_lock = self
+            .new_object_lock(
+                &bucket,
+                input.object_lock_mode.as_ref(),
+                input.object_lock_retain_until_date.as_ref(),
+                input.object_lock_legal_hold_status.as_ref(),
+            )
+            .await?;
+
+        let mut info: InternalInfo = default();
+        let cipher = match customer_key {
+            Some(ref customer_key) => Some(customer_key.new_object_cipher(&mut info)?),
+            None => None,
+        };
+
+        // the body of a dry run is validated without being stored
+        let mut file_writer = if self.is_dry_run(&req.headers)? {
+            None
+        } else {
+            Some(self.prepare_file_write(&bucket, &key).await?)
+        };
+
+        let is_encrypted = cipher.is_some();
+        let mut md5_hash = Md5::new();
+        let mut stored_md5_hash = Md5::new();
+        let size = {
+            let stream = limit_size(body, self.max_object_size).inspect_ok(|bytes| {
+                md5_hash.update(bytes.as_ref());
+                checksum.update(bytes.as_ref());
+            });
+            let stream = match cipher {
+                Some(cipher) => Either::Left(crate::sse::encrypt_stream(stream, cipher)),
+                None => Either::Right(stream),
+            };
+            let stream = pin!(stream.inspect_ok(|bytes| {
+                if is_encrypted {
+                    stored_md5_hash.update(bytes.as_ref());
+                }
+            }));
+            match file_writer {
+                Some(ref mut file_writer) => copy_bytes(stream, file_writer.writer()).await?,
+                None => copy_bytes(stream, &mut tokio::io::sink()).await?,
+            }
+        };
+
+        // the temporary file is removed when the writer is dropped
+        let md5_digest = md5_hash.finalize();
+        check_content_md5(input.content_md5.as_deref(), &md5_digest)?;
+        let checksum = checksum.finalize();
+        crate::checksum::merge_trailing(&mut expected_checksum, req.trailing_headers.as_ref());
+        crate::checksum::check(&checksum, &expected_checksum)?;
+
+        // the ETag of an encrypted object is the MD5 of the stored data
+        let md5_sum = if is_encrypted {
+            hex(stored_md5_hash.finalize())
         } else {
-            objects
+            hex(md5_digest)
+        };
+
+        let Some(file_writer) = file_writer else {
+            let output = PutObjectOutput {
+                e_tag: Some(quoted_e_tag(&md5_sum)),
+                sse_customer_algorithm: customer_key.as_ref().map(|_| crate::sse::ALGORITHM.to_owned()),
+                sse_customer_key_md5: customer_key.as_ref().map(|k| k.key_md5().to_owned()),
+                checksum_crc32: checksum.checksum_crc32,
+                checksum_crc32c: checksum.checksum_crc32c,
+                checksum_sha1: checksum.checksum_sha1,
+                checksum_sha256: checksum.checksum_sha256,
+                request_charged,
+                ..Default::default()
+            };
+            return Ok(dry_run_response(output));
+        };
+
+        // the data is renamed and the sidecar files are written as a unit,
+        // and the checks are repeated under the lock so that concurrent writes can not both pass them
+        let _lock = self.key_locks.write(&bucket, &key).await;
+        let exists = self.get_current_version_id(&bucket, &key).await?.is_some();
+        check_if_none_match_write(input.if_none_match.as_deref(), exists)?;
+        check_object_lock(self, &bucket, &key, None, false).await?;
+        let version_id = self.prepare_version_write(&bucket, &key).await?;
+        let object_path = file_writer.done().await?;
+
+        debug!(path = %object_path.display(), ?size, %md5_sum, ?checksum, "write file");
+
+        self.save_metadata(&bucket, &key, metadata.as_ref()).await?;
+        self.save_tagging(&bucket, &key, &tagging).await?;
+
+        crate::checksum::modify_internal_info(&mut info, &checksum);
+        object_lock.modify_internal_info(&mut info)?;
+        set_content_headers(
+            &mut info,
+            input.content_type.as_ref(),
+            input.content_disposition.as_deref(),
+            input.content_encoding.as_deref(),
+        );
+        set_website_redirect_location(&mut info, input.website_redirect_location.as_deref());
+        if let Some(storage_class) = input.storage_class {
+            info.insert("storage_class".to_owned(), serde_json::Value::String(storage_class.as_str().to_owned()));
+        }
+        let e_tag = quoted_e_tag(&md5_sum);
+        info.insert("e_tag".to_owned(), serde_json::Value::String(e_tag.clone()));
+        if let Some(ref version_id) = version_id {
+            info.insert("version_id".to_owned(), serde_json::Value::String(version_id.clone()));
+        }
+        let grants = grants.unwrap_or_else(|| crate::acl::private_grants(owner.as_ref()));
+        crate::acl::set_object_acl(&mut info, owner.as_ref(), grants)?;
+        self.save_internal_info(&bucket, &key, &info).await?;
+        self.refresh_public_object(&bucket, &key).await?;
+
+        let output = PutObjectOutput {
+            e_tag: Some(e_tag),
+            sse_customer_algorithm: customer_key.as_ref().map(|_| crate::sse::ALGORITHM.to_owned()),
+            sse_customer_key_md5: customer_key.as_ref().map(|k| k.key_md5().to_owned()),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            version_id,
+            request_charged,
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn put_object_acl(&self, req: S3Request<PutObjectAclInput>) -> S3Result<S3Response<PutObjectAclOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutObjectAcl", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let mut info = location.load_internal_info().await?.unwrap_or_default();
+
+        let requester = req.credentials.as_ref().map(|c| crate::acl::owner_of(&c.access_key));
+        let owner = crate::acl::object_acl(Some(&info), requester.as_ref()).owner;
+        let policy = input.access_control_policy;
+        let policy_owner = policy.as_ref().and_then(|p| p.owner.as_ref()).and_then(|o| o.id.as_deref());
+        let owner_id = owner.as_ref().and_then(|o| o.id.as_deref());
+        if policy_owner.is_some_and(|id| owner_id.is_some_and(|owner_id| owner_id != id)) {
+            return Err(s3_error!(AccessDenied, "The owner of the ACL must be the owner of the object"));
+        }
+
+        let grant_headers = GrantHeaders {
+            full_control: input.grant_full_control.as_deref(),
+            read: input.grant_read.as_deref(),
+            read_acp: input.grant_read_acp.as_deref(),
+            write: input.grant_write.as_deref(),
+            write_acp: input.grant_write_acp.as_deref(),
+        };
+        let canned_acl = input.acl.as_ref().map(ObjectCannedACL::as_str);
+        let has_grants = grant_headers.is_empty().not() || policy.is_some();
+        self.check_acl_allowed(&input.bucket, canned_acl, has_grants, ObjectCannedACL::BUCKET_OWNER_FULL_CONTROL)
+            .await?;
+        let Some(grants) = crate::acl::requested_grants(canned_acl, &grant_headers, policy, owner.as_ref())? else {
+            return Err(s3_error!(MissingSecurityHeader, "Your request was missing a required header"));
+        };
+
+        // only the internal info is rewritten, the data file is left untouched
+        crate::acl::set_object_acl(&mut info, owner.as_ref(), grants)?;
+        location.save_internal_info(&info).await?;
+        self.refresh_public_object(&input.bucket, &input.key).await?;
+
+        Ok(S3Response::new(PutObjectAclOutput { request_charged }))
+    }
+
+    #[tracing::instrument]
+    async fn put_object_tagging(&self, req: S3Request<PutObjectTaggingInput>) -> S3Result<S3Response<PutObjectTaggingOutput>> {
+        let input = req.input;
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutObjectTagging", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let tagging = tag_map_from_tags(input.tagging.tag_set.into_iter().map(|tag| (tag.key, tag.value)))?;
+
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        location.save_tagging(&tagging).await?;
+
+        let output = PutObjectTaggingOutput::default();
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object_lock_configuration(
+        &self,
+        req: S3Request<GetObjectLockConfigurationInput>,
+    ) -> S3Result<S3Response<GetObjectLockConfigurationOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetBucketObjectLockConfiguration", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(object_lock_configuration) = self.load_object_lock_configuration(&input.bucket).await? else {
+            return Err(s3_error!(ObjectLockConfigurationNotFoundError));
+        };
+
+        let output = GetObjectLockConfigurationOutput {
+            object_lock_configuration: Some(object_lock_configuration),
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn put_object_lock_configuration(
+        &self,
+        req: S3Request<PutObjectLockConfigurationInput>,
+    ) -> S3Result<S3Response<PutObjectLockConfigurationOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutBucketObjectLockConfiguration", &input.bucket, None)
+            .await?;
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let Some(object_lock_configuration) = input.object_lock_configuration else {
+            return Err(s3_error!(MalformedXML, "missing Object Lock configuration"));
         };
 
-        let key_count = try_!(i32::try_from(objects.len()));
+        if self.get_versioning_state(&input.bucket).await? != VersioningState::Enabled {
+            return Err(s3_error!(
+                InvalidBucketState,
+                "Versioning must be 'Enabled' on the bucket to apply a Object Lock configuration"
+            ));
+        }
+
+        let mut config = self.load_bucket_config(&input.bucket).await?.unwrap_or_default();
+        crate::object_lock::set_lock_configuration(&mut config, &object_lock_configuration)?;
+        self.save_bucket_config(&input.bucket, &config).await?;
+
+        let output = PutObjectLockConfigurationOutput { request_charged };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object_retention(
+        &self,
+        req: S3Request<GetObjectRetentionInput>,
+    ) -> S3Result<S3Response<GetObjectRetentionOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetObjectRetention", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        self.require_object_lock(&input.bucket).await?;
+
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let info = location.load_internal_info().await?;
+        let object_lock = ObjectLock::from_internal_info(info.as_ref());
+        let Some(retention) = object_lock.retention else {
+            return Err(s3_error!(NoSuchObjectLockConfiguration));
+        };
 
-        let output = ListObjectsV2Output {
-            key_count,
-            max_keys: key_count,
-            contents: Some(objects),
-            delimiter: input.delimiter,
-            encoding_type: input.encoding_type,
-            name: Some(input.bucket),
-            prefix: input.prefix,
-            ..Default::default()
+        let output = GetObjectRetentionOutput {
+            retention: Some(retention.to_dto()),
         };
         Ok(S3Response::new(output))
     }
 
     #[tracing::instrument]
-    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+    async fn put_object_retention(
+        &self,
+        req: S3Request<PutObjectRetentionInput>,
+    ) -> S3Result<S3Response<PutObjectRetentionOutput>> {
         let input = req.input;
-        if let Some(ref storage_class) = input.storage_class {
-            let is_valid = ["STANDARD", "REDUCED_REDUNDANCY"].contains(&storage_class.as_str());
-            if !is_valid {
-                return Err(s3_error!(InvalidStorageClass));
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutObjectRetention", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        self.require_object_lock(&input.bucket).await?;
+
+        let retention = match input.retention {
+            Some(ref r) => {
+                Retention::from_request(r.mode.as_ref().map(ObjectLockRetentionMode::as_str), r.retain_until_date.as_ref())?
             }
-        }
-
-        let PutObjectInput {
-            body,
-            bucket,
-            key,
-            metadata,
-            content_length,
-            ..
-        } = input;
-
-        let Some(body) = body else { return Err(s3_error!(IncompleteBody)) };
+            None => None,
+        };
+        let bypass_governance = can_bypass_governance(input.bypass_governance_retention, req.credentials.as_ref());
 
-        let mut checksum: crate::checksum::ChecksumCalculator = default();
-        if input.checksum_crc32.is_some() {
-            checksum.crc32 = Some(default());
-        }
-        if input.checksum_crc32c.is_some() {
-            checksum.crc32c = Some(default());
-        }
-        if input.checksum_sha1.is_some() {
-            checksum.sha1 = Some(default());
-        }
-        if input.checksum_sha256.is_some() {
-            checksum.sha256 = Some(default());
-        }
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let mut info = location.load_internal_info().await?.unwrap_or_default();
+        let mut object_lock = ObjectLock::from_internal_info(Some(&info));
+        object_lock.check_retention_change(retention.as_ref(), bypass_governance)?;
 
-        if key.ends_with('/') {
-            if let Some(len) = content_length {
-                if len > 0 {
-                    return Err(s3_error!(UnexpectedContent, "Unexpected request body when creating a directory object."));
-                }
-            }
-            let object_path = self.get_object_path(&bucket, &key)?;
-            try_!(fs::create_dir_all(&object_path).await);
-            let output = PutObjectOutput::default();
-            return Ok(S3Response::new(output));
-        }
+        object_lock.retention = retention;
+        object_lock.modify_internal_info(&mut info)?;
+        location.save_internal_info(&info).await?;
 
-        let mut file_writer = self.prepare_file_write(&bucket, &key).await?;
+        let output = PutObjectRetentionOutput { request_charged };
+        Ok(S3Response::new(output))
+    }
 
-        let mut md5_hash = Md5::new();
-        let stream = body.inspect_ok(|bytes| {
-            md5_hash.update(bytes.as_ref());
-            checksum.update(bytes.as_ref());
-        });
+    #[tracing::instrument]
+    async fn get_object_legal_hold(
+        &self,
+        req: S3Request<GetObjectLegalHoldInput>,
+    ) -> S3Result<S3Response<GetObjectLegalHoldOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetObjectLegalHold", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        self.require_object_lock(&input.bucket).await?;
+
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let info = location.load_internal_info().await?;
+        let Some(status) = ObjectLock::from_internal_info(info.as_ref()).legal_hold_status() else {
+            return Err(s3_error!(NoSuchObjectLockConfiguration));
+        };
 
-        let size = copy_bytes(stream, file_writer.writer()).await?;
-        let object_path = file_writer.done().await?;
+        let output = GetObjectLegalHoldOutput {
+            legal_hold: Some(ObjectLockLegalHold { status: Some(status) }),
+        };
+        Ok(S3Response::new(output))
+    }
 
-        let md5_sum = hex(md5_hash.finalize());
+    #[tracing::instrument]
+    async fn put_object_legal_hold(
+        &self,
+        req: S3Request<PutObjectLegalHoldInput>,
+    ) -> S3Result<S3Response<PutObjectLegalHoldOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutObjectLegalHold", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        self.require_object_lock(&input.bucket).await?;
+        let legal_hold = crate::object_lock::legal_hold_from_request(input.legal_hold.as_ref())?;
+
+        let _lock = self.key_locks.write(&input.bucket, &input.key).await;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let mut info = location.load_internal_info().await?.unwrap_or_default();
+        let mut object_lock = ObjectLock::from_internal_info(Some(&info));
+        object_lock.legal_hold = Some(legal_hold);
+        object_lock.modify_internal_info(&mut info)?;
+        location.save_internal_info(&info).await?;
+
+        let output = PutObjectLegalHoldOutput { request_charged };
+        Ok(S3Response::new(output))
+    }
 
-        let checksum = checksum.finalize();
-        if checksum.checksum_crc32 != input.checksum_crc32 {
-            return Err(s3_error!(BadDigest, "checksum_crc32 mismatch"));
-        }
-        if checksum.checksum_crc32c != input.checksum_crc32c {
-            return Err(s3_error!(BadDigest, "checksum_crc32c mismatch"));
-        }
-        if checksum.checksum_sha1 != input.checksum_sha1 {
-            return Err(s3_error!(BadDigest, "checksum_sha1 mismatch"));
+    /// Objects are always on the disk, so a restore completes immediately.
+    #[tracing::instrument]
+    async fn restore_object(&self, req: S3Request<RestoreObjectInput>) -> S3Result<S3Response<RestoreObjectOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:RestoreObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+
+        let Some(restore_request) = input.restore_request else { return Err(s3_error!(MalformedXML)) };
+        if restore_request.type_.is_some() {
+            return Err(s3_error!(NotImplemented, "Select restore requests are not supported"));
         }
-        if checksum.checksum_sha256 != input.checksum_sha256 {
-            return Err(s3_error!(BadDigest, "checksum_sha256 mismatch"));
+        if restore_request.days < 1 {
+            return Err(s3_error!(InvalidArgument, "Lifetime days must be a positive integer"));
         }
 
-        debug!(path = %object_path.display(), ?size, %md5_sum, ?checksum, "write file");
-
-        if let Some(ref metadata) = metadata {
-            self.save_metadata(&bucket, &key, metadata).await?;
+        let location = locate_object(self, &input.bucket, &input.key, input.version_id.as_deref()).await?;
+        let mut info = location.load_internal_info().await?.unwrap_or_default();
+        if ARCHIVED_STORAGE_CLASSES.contains(&stored_storage_class(Some(&info))).not() {
+            return Err(s3_error!(
+                InvalidObjectState,
+                "Restore is not allowed for the object's current storage class"
+            ));
         }
 
-        let mut info: InternalInfo = default();
-        crate::checksum::modify_internal_info(&mut info, &checksum);
-        self.save_internal_info(&bucket, &key, &info).await?;
-
-        let e_tag = format!("\"{md5_sum}\"");
+        // restoring a restored object extends the expiry date
+        let is_restored = restore_header(Some(&info)).is_some();
+        let expiry_date = time::OffsetDateTime::now_utc() + time::Duration::days(i64::from(restore_request.days));
+        let mut buf = Vec::new();
+        try_!(Timestamp::from(expiry_date).format(TimestampFormat::DateTime, &mut buf));
+        info.insert("restore_expiry_date".to_owned(), try_!(String::from_utf8(buf)).into());
+        location.save_internal_info(&info).await?;
 
-        let output = PutObjectOutput {
-            e_tag: Some(e_tag),
-            checksum_crc32: checksum.checksum_crc32,
-            checksum_crc32c: checksum.checksum_crc32c,
-            checksum_sha1: checksum.checksum_sha1,
-            checksum_sha256: checksum.checksum_sha256,
+        let mut resp = S3Response::new(RestoreObjectOutput {
+            request_charged,
             ..Default::default()
+        });
+        resp.status = Some(if is_restored { StatusCode::OK } else { StatusCode::ACCEPTED });
+        Ok(resp)
+    }
+
+    #[tracing::instrument]
+    async fn select_object_content(
+        &self,
+        req: S3Request<SelectObjectContentInput>,
+    ) -> S3Result<S3Response<SelectObjectContentOutput>> {
+        let input = req.input;
+        self.check_policy(req.credentials.as_ref(), "s3:GetObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        let select = Select::new(&input.request)?;
+
+        let location = locate_object(self, &input.bucket, &input.key, None).await?;
+        let file = open_object(self, &location, &input.key).await?;
+        let encrypted_len = try_!(file.metadata().await).len;
+
+        let info = location.load_internal_info().await?;
+        let customer_key = CustomerKey::from_headers(
+            input.sse_customer_algorithm.as_deref(),
+            input.sse_customer_key.as_deref(),
+            input.sse_customer_key_md5.as_deref(),
+        )?;
+        let content = match crate::sse::object_cipher(info.as_ref(), customer_key.as_ref())? {
+            Some(cipher) => Either::Left(crate::sse::decrypt_stream(file, cipher, encrypted_len, 0)),
+            None => Either::Right(ReaderStream::with_capacity(file, self.read_buffer_size)),
+        };
+
+        let output = SelectObjectContentOutput {
+            payload: Some(select.run(content)),
         };
         Ok(S3Response::new(output))
     }
@@ -522,12 +2663,42 @@ impl S3 for FileSystem {
         req: S3Request<CreateMultipartUploadInput>,
     ) -> S3Result<S3Response<CreateMultipartUploadOutput>> {
         let input = req.input;
-        let upload_id = self.create_upload_id(req.credentials.as_ref()).await?;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:PutObject", &input.bucket, Some(&input.key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        if input.sse_customer_algorithm.is_some() || input.sse_customer_key.is_some() {
+            return Err(s3_error!(NotImplemented, "Multipart uploads with customer keys are not supported"));
+        }
+        if input.object_lock_mode.is_some() || input.object_lock_legal_hold_status.is_some() {
+            return Err(s3_error!(NotImplemented, "Multipart uploads with Object Lock headers are not supported"));
+        }
+        check_website_redirect_location(input.website_redirect_location.as_deref())?;
+
+        let checksum_algorithm = input.checksum_algorithm.as_ref().map(ChecksumAlgorithm::as_str);
+        ChecksumCalculator::new(checksum_algorithm, &default())?;
+
+        let upload_info = UploadInfo {
+            bucket: input.bucket.clone(),
+            key: input.key.clone(),
+            storage_class: input.storage_class.as_ref().map(|s| s.as_str().to_owned()),
+            checksum_algorithm: checksum_algorithm.map(ToOwned::to_owned),
+            metadata: input.metadata,
+            content_type: input.content_type.as_ref().map(ToString::to_string),
+            content_disposition: input.content_disposition,
+            content_encoding: input.content_encoding,
+            website_redirect_location: input.website_redirect_location,
+            ..Default::default()
+        };
+        let upload_id = self.create_upload_id(req.credentials.as_ref(), upload_info).await?;
 
         let output = CreateMultipartUploadOutput {
             bucket: Some(input.bucket),
+            checksum_algorithm: input.checksum_algorithm,
             key: Some(input.key),
             upload_id: Some(upload_id.to_string()),
+            request_charged,
             ..Default::default()
         };
 
@@ -538,10 +2709,26 @@ impl S3 for FileSystem {
     async fn upload_part(&self, req: S3Request<UploadPartInput>) -> S3Result<S3Response<UploadPartOutput>> {
         let UploadPartInput {
             body,
+            checksum_algorithm,
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+            content_length,
+            content_md5,
             upload_id,
             part_number,
+            bucket,
+            key,
+            request_payer,
             ..
         } = req.input;
+        let request_charged = crate::request_payment::request_charged(request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:PutObject", &bucket, Some(&key))
+            .await?;
+        check_bucket_exists(self, &bucket).await?;
+        check_part_number(part_number)?;
+        check_content_length(self, content_length)?;
 
         let body = body.ok_or_else(|| s3_error!(IncompleteBody))?;
 
@@ -550,21 +2737,100 @@ impl S3 for FileSystem {
             return Err(s3_error!(AccessDenied));
         }
 
-        let file_path = self.resolve_abs_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
+        let mut expected_checksum = Checksum {
+            checksum_crc32,
+            checksum_crc32c,
+            checksum_sha1,
+            checksum_sha256,
+        };
+        let upload_algorithm = self
+            .load_upload_info(&upload_id)
+            .await?
+            .and_then(|info| info.checksum_algorithm);
+        let checksum_algorithm = upload_algorithm
+            .as_deref()
+            .or(checksum_algorithm.as_ref().map(ChecksumAlgorithm::as_str))
+            .or_else(|| crate::checksum::trailer_algorithm(&req.headers));
+        check_part_checksum_algorithm(upload_algorithm.as_deref(), &expected_checksum)?;
+        let mut checksum = ChecksumCalculator::new(checksum_algorithm, &expected_checksum)?;
+
+        let file_path = self.resolve_meta_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
 
         let mut md5_hash = Md5::new();
-        let stream = body.inspect_ok(|bytes| md5_hash.update(bytes.as_ref()));
+        let stream = limit_size(body, self.max_object_size).inspect_ok(|bytes| {
+            md5_hash.update(bytes.as_ref());
+            checksum.update(bytes.as_ref());
+        });
 
-        let file = try_!(fs::File::create(&file_path).await);
-        let mut writer = BufWriter::new(file);
+        // the body of a dry run is validated without replacing the staged part
+        let dry_run = self.is_dry_run(&req.headers)?;
+        let mut writer = if dry_run {
+            None
+        } else {
+            Some(BufWriter::new(try_!(self.storage.create(&file_path).await)))
+        };
 
-        let size = copy_bytes(stream, &mut writer).await?;
-        let md5_sum = hex(md5_hash.finalize());
+        let copied = match writer {
+            Some(ref mut writer) => copy_bytes(stream, writer).await,
+            None => copy_bytes(stream, &mut tokio::io::sink()).await,
+        };
+        let size = match copied {
+            Ok(size) => size,
+            Err(err) => {
+                if let Some(writer) = writer {
+                    drop(writer);
+                    try_!(self.storage.remove_file(&file_path).await);
+                }
+                return Err(err.into());
+            }
+        };
+        let md5_digest = md5_hash.finalize();
+        let checksum = checksum.finalize();
+        crate::checksum::merge_trailing(&mut expected_checksum, req.trailing_headers.as_ref());
+        let verified = check_content_md5(content_md5.as_deref(), &md5_digest)
+            .and_then(|()| crate::checksum::check(&checksum, &expected_checksum));
+        if let Err(err) = verified {
+            if writer.is_some() {
+                try_!(self.storage.remove_file(&file_path).await);
+            }
+            return Err(err);
+        }
+        let md5_sum = hex(md5_digest);
+        let e_tag = quoted_e_tag(&md5_sum);
+
+        if dry_run {
+            let output = UploadPartOutput {
+                e_tag: Some(e_tag),
+                checksum_crc32: checksum.checksum_crc32,
+                checksum_crc32c: checksum.checksum_crc32c,
+                checksum_sha1: checksum.checksum_sha1,
+                checksum_sha256: checksum.checksum_sha256,
+                request_charged,
+                ..Default::default()
+            };
+            return Ok(dry_run_response(output));
+        }
 
         debug!(path = %file_path.display(), ?size, %md5_sum, "write file");
 
+        let last_modified = modified_time(&try_!(self.storage.metadata(&file_path).await));
+        let part = PartInfo {
+            size,
+            e_tag: e_tag.clone(),
+            last_modified,
+            checksum: checksum_algorithm
+                .and_then(|a| crate::checksum::get(&checksum, a))
+                .map(ToOwned::to_owned),
+        };
+        self.save_upload_part(&upload_id, part_number, part).await?;
+
         let output = UploadPartOutput {
-            e_tag: Some(format!("\"{md5_sum}\"")),
+            e_tag: Some(e_tag),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            request_charged,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -573,65 +2839,100 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn upload_part_copy(&self, req: S3Request<UploadPartCopyInput>) -> S3Result<S3Response<UploadPartCopyOutput>> {
         let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
 
         let upload_id = Uuid::parse_str(&input.upload_id).map_err(|_| s3_error!(InvalidRequest))?;
         let part_number = input.part_number;
+        check_part_number(part_number)?;
         if self.verify_upload_id(req.credentials.as_ref(), &upload_id).await?.not() {
             return Err(s3_error!(AccessDenied));
         }
 
-        let (src_bucket, src_key) = match input.copy_source {
-            CopySource::AccessPoint { .. } => return Err(s3_error!(NotImplemented)),
-            CopySource::Bucket { ref bucket, ref key, .. } => (bucket, key),
+        let (src_bucket, src_key, src_version_id) = match input.copy_source {
+            CopySource::AccessPoint { .. } => {
+                return Err(s3_error!(NotImplemented, "Copying from access points is not supported"))
+            }
+            CopySource::Bucket {
+                ref bucket,
+                ref key,
+                ref version_id,
+            } => (bucket, key, version_id.as_deref()),
         };
-        let src_path = self.get_object_path(src_bucket, src_key)?;
-        let dst_path = self.resolve_abs_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
-
-        let mut src_file = fs::File::open(&src_path).await.map_err(|e| s3_error!(e, NoSuchKey))?;
-        let file_len = try_!(src_file.metadata().await).len();
+        let credentials = req.credentials.as_ref();
+        reject_dry_run(&req.headers)?;
+        self.check_policy(credentials, "s3:PutObject", &input.bucket, Some(&input.key))
+            .await?;
+        self.check_policy(credentials, "s3:GetObject", src_bucket, Some(src_key))
+            .await?;
+        check_bucket_exists(self, &input.bucket).await?;
+        check_bucket_exists(self, src_bucket).await?;
+
+        let lookup = self.locate_version(src_bucket, src_key, src_version_id).await?;
+        let src = found_version(lookup)?;
+        if crate::sse::is_encrypted(src.load_internal_info().await?.as_ref()) {
+            return Err(s3_error!(NotImplemented, "Copying objects encrypted with customer keys is not supported"));
+        }
+        let dst_path = self.resolve_meta_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
 
-        let (start, end) = if let Some(copy_range) = &input.copy_source_range {
-            if !copy_range.starts_with("bytes=") {
-                return Err(s3_error!(InvalidArgument));
-            }
-            let range = &copy_range["bytes=".len()..];
-            let parts: Vec<&str> = range.split('-').collect();
-            if parts.len() != 2 {
-                return Err(s3_error!(InvalidArgument));
-            }
+        let mut src_file = open_object(self, &src, src_key).await?;
+        let file_len = try_!(src_file.metadata().await).len;
 
-            let start: u64 = parts[0].parse().map_err(|_| s3_error!(InvalidArgument))?;
-            let mut end = file_len - 1;
-            if parts[1].is_empty().not() {
-                end = parts[1].parse().map_err(|_| s3_error!(InvalidArgument))?;
-            }
-            (start, end)
-        } else {
-            (0, file_len - 1)
+        let file_range = match input.copy_source_range {
+            Some(ref copy_range) => parse_copy_source_range(copy_range, file_len)?,
+            None => 0..file_len,
         };
 
-        let content_length = end - start + 1;
+        let content_length = file_range.end - file_range.start;
+        check_content_length(self, Some(try_!(i64::try_from(content_length))))?;
         let content_length_usize = try_!(usize::try_from(content_length));
 
-        let _ = try_!(src_file.seek(io::SeekFrom::Start(start)).await);
-        let body = StreamingBlob::wrap(bytes_stream(ReaderStream::with_capacity(src_file, 4096), content_length_usize));
+        let _ = try_!(src_file.seek(io::SeekFrom::Start(file_range.start)).await);
+        let body = StreamingBlob::wrap(bytes_stream(
+            ReaderStream::with_capacity(src_file, self.read_buffer_size),
+            content_length_usize,
+        ));
 
-        let dst_file = try_!(fs::File::create(&dst_path).await);
+        let dst_file = try_!(self.storage.create(&dst_path).await);
         let mut writer = BufWriter::new(dst_file);
 
+        let checksum_algorithm = self
+            .load_upload_info(&upload_id)
+            .await?
+            .and_then(|info| info.checksum_algorithm);
+        let mut checksum = ChecksumCalculator::new(checksum_algorithm.as_deref(), &default())?;
+
         let mut md5_hash = Md5::new();
-        let stream = body.inspect_ok(|bytes| md5_hash.update(bytes.as_ref()));
+        let stream = body.inspect_ok(|bytes| {
+            md5_hash.update(bytes.as_ref());
+            checksum.update(bytes.as_ref());
+        });
 
         let size = copy_bytes(stream, &mut writer).await?;
         let md5_sum = hex(md5_hash.finalize());
+        let checksum = checksum.finalize();
 
         debug!(path = %dst_path.display(), ?size, %md5_sum, "write file");
 
+        let e_tag = quoted_e_tag(&md5_sum);
+        let modified = modified_time(&try_!(self.storage.metadata(&dst_path).await));
+        let part = PartInfo {
+            size,
+            e_tag: e_tag.clone(),
+            last_modified: modified,
+            checksum: checksum_algorithm.and_then(|a| crate::checksum::get(&checksum, &a).map(ToOwned::to_owned)),
+        };
+        self.save_upload_part(&upload_id, part_number, part).await?;
+
         let output = UploadPartCopyOutput {
             copy_part_result: Some(CopyPartResult {
-                e_tag: Some(format!("\"{md5_sum}\"")),
-                ..Default::default()
+                e_tag: Some(e_tag),
+                last_modified: Some(Timestamp::from(modified)),
+                checksum_crc32: checksum.checksum_crc32,
+                checksum_crc32c: checksum.checksum_crc32c,
+                checksum_sha1: checksum.checksum_sha1,
+                checksum_sha256: checksum.checksum_sha256,
             }),
+            request_charged,
             ..Default::default()
         };
 
@@ -641,45 +2942,188 @@ impl S3 for FileSystem {
     #[tracing::instrument]
     async fn list_parts(&self, req: S3Request<ListPartsInput>) -> S3Result<S3Response<ListPartsOutput>> {
         let ListPartsInput {
-            bucket, key, upload_id, ..
+            bucket,
+            key,
+            upload_id,
+            max_parts,
+            part_number_marker,
+            request_payer,
+            ..
         } = req.input;
+        let request_charged = crate::request_payment::request_charged(request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:ListMultipartUploadParts", &bucket, Some(&key))
+            .await?;
+        check_bucket_exists(self, &bucket).await?;
+
+        let id = Uuid::parse_str(&upload_id).map_err(|_| s3_error!(NoSuchUpload))?;
+        let Some(info) = self.load_upload_info(&id).await? else { return Err(s3_error!(NoSuchUpload)) };
+        if info.access_key.as_deref() != req.credentials.as_ref().map(|c| c.access_key.as_str()) {
+            return Err(s3_error!(AccessDenied));
+        }
+
+        let max_parts = max_parts.unwrap_or(1000);
+        let limit = usize::try_from(max_parts).map_err(|_| s3_error!(InvalidArgument, "max-parts must be non-negative"))?;
+        let marker = match part_number_marker {
+            Some(ref marker) => marker
+                .parse::<i32>()
+                .map_err(|_| s3_error!(InvalidArgument, "part-number-marker must be an integer"))?,
+            None => 0,
+        };
 
         let mut parts: Vec<Part> = Vec::new();
-        let mut iter = try_!(fs::read_dir(&self.root).await);
+        let mut is_truncated = false;
+        for (&part_number, part) in info.parts.range(marker.saturating_add(1)..) {
+            if parts.len() == limit {
+                is_truncated = true;
+                break;
+            }
+            let mut checksum: Checksum = default();
+            if let (Some(algorithm), Some(value)) = (&info.checksum_algorithm, &part.checksum) {
+                crate::checksum::set(&mut checksum, algorithm, value.clone());
+            }
+            parts.push(Part {
+                checksum_crc32: checksum.checksum_crc32,
+                checksum_crc32c: checksum.checksum_crc32c,
+                checksum_sha1: checksum.checksum_sha1,
+                checksum_sha256: checksum.checksum_sha256,
+                e_tag: Some(quoted_e_tag(&part.e_tag)),
+                last_modified: Some(Timestamp::from(part.last_modified)),
+                part_number,
+                size: try_!(i64::try_from(part.size)),
+            });
+        }
 
-        let prefix = format!(".upload_id-{upload_id}");
+        let next_part_number_marker = if is_truncated {
+            parts.last().map(|p| p.part_number.to_string())
+        } else {
+            None
+        };
 
-        while let Some(entry) = try_!(iter.next_entry().await) {
-            let file_type = try_!(entry.file_type().await);
-            if file_type.is_file().not() {
-                continue;
+        let output = ListPartsOutput {
+            bucket: Some(bucket),
+            key: Some(key),
+            upload_id: Some(upload_id),
+            max_parts,
+            part_number_marker,
+            next_part_number_marker,
+            is_truncated,
+            parts: Some(parts),
+            checksum_algorithm: info.checksum_algorithm.map(ChecksumAlgorithm::from),
+            storage_class: Some(StorageClass::from(
+                info.storage_class.unwrap_or_else(|| StorageClass::STANDARD.to_owned()),
+            )),
+            request_charged,
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_multipart_uploads(
+        &self,
+        req: S3Request<ListMultipartUploadsInput>,
+    ) -> S3Result<S3Response<ListMultipartUploadsOutput>> {
+        let input = req.input;
+        let request_charged = crate::request_payment::request_charged(input.request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:ListBucketMultipartUploads", &input.bucket, None)
+            .await?;
+
+        if self.storage.exists(&self.get_bucket_path(&input.bucket)?).await.not() {
+            return Err(s3_error!(NoSuchBucket));
+        }
+
+        let max_uploads = input.max_uploads.unwrap_or(1000);
+        let limit = usize::try_from(max_uploads).map_err(|_| s3_error!(InvalidArgument, "max-uploads must be non-negative"))?;
+        let prefix = input.prefix.as_deref().unwrap_or("");
+        let delimiter = input.delimiter.as_deref().filter(|d| d.is_empty().not());
+        let key_marker = input.key_marker.as_deref().unwrap_or("");
+
+        let mut uploads = self.list_upload_infos().await?;
+        uploads.retain(|(_, info)| info.bucket == input.bucket && info.key.starts_with(prefix));
+        uploads.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| (&lhs.key, lhs.initiated, lhs_id).cmp(&(&rhs.key, rhs.initiated, rhs_id)));
+
+        // skip everything up to the markers
+        let start = match input.upload_id_marker.as_deref().filter(|_| key_marker.is_empty().not()) {
+            Some(upload_id_marker) => {
+                let is_marker = |(id, info): &(Uuid, UploadInfo)| info.key == key_marker && id.to_string() == upload_id_marker;
+                match uploads.iter().position(is_marker) {
+                    Some(pos) => pos + 1,
+                    None => uploads.partition_point(|(_, info)| info.key.as_str() <= key_marker),
+                }
             }
+            None => uploads.partition_point(|(_, info)| info.key.as_str() <= key_marker),
+        };
 
-            let file_name = entry.file_name();
-            let Some(name) = file_name.to_str() else { continue };
+        let mut multipart_uploads: Vec<MultipartUpload> = Vec::new();
+        let mut common_prefixes: Vec<String> = Vec::new();
+        let mut next_markers: Option<(String, Option<String>)> = None;
+        let mut is_truncated = false;
 
-            let Some(part_segment) = name.strip_prefix(&prefix) else { continue };
-            let Some(part_number) = part_segment.strip_prefix(".part-") else { continue };
-            let part_number = part_number.parse::<i32>().unwrap();
+        for (upload_id, info) in &uploads[start..] {
+            let common_prefix = delimiter.and_then(|delimiter| {
+                let pos = info.key[prefix.len()..].find(delimiter)?;
+                Some(&info.key[..prefix.len() + pos + delimiter.len()])
+            });
 
-            let file_meta = try_!(entry.metadata().await);
-            let last_modified = Timestamp::from(try_!(file_meta.modified()));
-            let size = try_!(i64::try_from(file_meta.len()));
+            if let Some(common_prefix) = common_prefix {
+                if common_prefix == key_marker || common_prefixes.last().map(String::as_str) == Some(common_prefix) {
+                    continue;
+                }
+            }
 
-            let part = Part {
-                last_modified: Some(last_modified),
-                part_number,
-                size,
+            if multipart_uploads.len() + common_prefixes.len() == limit {
+                is_truncated = true;
+                break;
+            }
+
+            if let Some(common_prefix) = common_prefix {
+                common_prefixes.push(common_prefix.to_owned());
+                next_markers = Some((common_prefix.to_owned(), None));
+                continue;
+            }
+
+            let initiator = info.access_key.as_ref().map(|ak| Initiator {
+                display_name: Some(ak.clone()),
+                id: Some(ak.clone()),
+            });
+            multipart_uploads.push(MultipartUpload {
+                initiated: info.initiated.map(Timestamp::from),
+                initiator,
+                key: Some(info.key.clone()),
+                storage_class: Some(StorageClass::from(
+                    info.storage_class
+                        .clone()
+                        .unwrap_or_else(|| StorageClass::STANDARD.to_owned()),
+                )),
+                upload_id: Some(upload_id.to_string()),
                 ..Default::default()
-            };
-            parts.push(part);
+            });
+            next_markers = Some((info.key.clone(), Some(upload_id.to_string())));
         }
 
-        let output = ListPartsOutput {
-            bucket: Some(bucket),
-            key: Some(key),
-            upload_id: Some(upload_id),
-            parts: Some(parts),
+        let (next_key_marker, next_upload_id_marker) = match next_markers {
+            Some((key, upload_id)) if is_truncated => (Some(key), upload_id),
+            _ => (None, None),
+        };
+
+        let common_prefixes = common_prefixes
+            .into_iter()
+            .map(|prefix| CommonPrefix { prefix: Some(prefix) })
+            .collect::<Vec<_>>();
+
+        let output = ListMultipartUploadsOutput {
+            bucket: Some(input.bucket),
+            common_prefixes: common_prefixes.is_empty().not().then_some(common_prefixes),
+            delimiter: input.delimiter,
+            is_truncated,
+            key_marker: input.key_marker,
+            max_uploads,
+            next_key_marker,
+            next_upload_id_marker,
+            prefix: input.prefix,
+            upload_id_marker: input.upload_id_marker,
+            uploads: Some(multipart_uploads),
+            request_charged,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -695,8 +3139,13 @@ impl S3 for FileSystem {
             bucket,
             key,
             upload_id,
+            request_payer,
             ..
         } = req.input;
+        let request_charged = crate::request_payment::request_charged(request_payer.as_ref());
+        self.check_policy(req.credentials.as_ref(), "s3:PutObject", &bucket, Some(&key))
+            .await?;
+        check_bucket_exists(self, &bucket).await?;
 
         let Some(multipart_upload) = multipart_upload else { return Err(s3_error!(InvalidPart)) };
 
@@ -705,37 +3154,204 @@ impl S3 for FileSystem {
             return Err(s3_error!(AccessDenied));
         }
 
-        self.delete_upload_id(&upload_id).await?;
-
-        let mut file_writer = self.prepare_file_write(&bucket, &key).await?;
+        let upload_info = self.load_upload_info(&upload_id).await?.unwrap_or_default();
+        let parts = multipart_upload.parts.unwrap_or_default();
+        if parts.len() > MAX_PARTS as usize {
+            return Err(s3_error!(
+                InvalidArgument,
+                "Your proposed upload has more than the maximum allowed number of {} parts.",
+                MAX_PARTS
+            ));
+        }
 
-        let mut cnt: i32 = 0;
-        for part in multipart_upload.parts.into_iter().flatten() {
+        // the listed parts must be in ascending order
+        let mut listed_parts: Vec<(i32, PathBuf)> = Vec::with_capacity(parts.len());
+        for part in &parts {
             let part_number = part.part_number;
-            cnt += 1;
-            if part_number != cnt {
-                return Err(s3_error!(InvalidRequest, "invalid part order"));
+            if listed_parts.last().is_some_and(|&(prev, _)| part_number <= prev) {
+                return Err(s3_error!(
+                    InvalidPartOrder,
+                    "The list of parts was not in ascending order. Parts must be ordered by part number."
+                ));
+            }
+            let part_path = self.resolve_meta_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
+            listed_parts.push((part_number, part_path));
+        }
+
+        // the staged parts are verified concurrently, and the results are kept in the listed order
+        let mut verified = pin!(futures::stream::iter(listed_parts)
+            .map(|(part_number, part_path)| {
+                let fs = self.clone();
+                let recorded = upload_info.parts.get(&part_number).cloned();
+                tokio::spawn(async move {
+                    let (e_tag, size) = verify_staged_part(&fs, part_number, &part_path, recorded.as_ref()).await?;
+                    Ok::<_, S3Error>((part_number, part_path, e_tag, size))
+                })
+            })
+            .buffered(self.part_verify_concurrency));
+
+        // the listed parts must match the uploaded parts
+        let mut staged_parts: Vec<(i32, PathBuf, String, u64)> = Vec::with_capacity(parts.len());
+        for part in &parts {
+            let Some(result) = verified.next().await else { break };
+            let (part_number, part_path, e_tag, size) = try_!(result)?;
+            if part.e_tag.as_deref().map(|s| s.trim_matches('"')) != Some(e_tag.as_str()) {
+                return Err(s3_error!(
+                    InvalidPart,
+                    "The entity tag of part {part_number} does not match the uploaded part."
+                ));
+            }
+            staged_parts.push((part_number, part_path, e_tag, size));
+        }
+
+        // every part except the last one must not be smaller than the minimum part size
+        if let Some((_, init)) = staged_parts.split_last() {
+            for &(part_number, _, _, size) in init {
+                if size < self.min_part_size {
+                    return Err(s3_error!(
+                        EntityTooSmall,
+                        "Your proposed upload is smaller than the minimum allowed size. \
+                         Part {part_number} has size {size}, but the minimum is {}.",
+                        self.min_part_size
+                    ));
+                }
+            }
+        }
+
+        if let Some(limit) = self.max_object_size {
+            let total = staged_parts.iter().fold(0_u64, |acc, &(.., size)| acc.saturating_add(size));
+            if total > limit {
+                return Err(entity_too_large(limit));
+            }
+        }
+
+        // the checksum of a multipart object is calculated from the checksums of its parts
+        let mut checksum: Checksum = default();
+        if let Some(ref algorithm) = upload_info.checksum_algorithm {
+            let mut part_checksums: Vec<&str> = Vec::with_capacity(parts.len());
+            for part in &parts {
+                let expected = Checksum {
+                    checksum_crc32: part.checksum_crc32.clone(),
+                    checksum_crc32c: part.checksum_crc32c.clone(),
+                    checksum_sha1: part.checksum_sha1.clone(),
+                    checksum_sha256: part.checksum_sha256.clone(),
+                };
+                let stored = upload_info.parts.get(&part.part_number).and_then(|p| p.checksum.as_deref());
+                let provided = crate::checksum::get(&expected, algorithm);
+                let Some(stored) = stored.filter(|&stored| provided.is_none() || provided == Some(stored)) else {
+                    return Err(s3_error!(
+                        InvalidPart,
+                        "The checksum of part {} does not match the uploaded part.",
+                        part.part_number
+                    ));
+                };
+                part_checksums.push(stored);
             }
+            if let Some(value) = crate::checksum::composite(algorithm, part_checksums) {
+                crate::checksum::set(&mut checksum, algorithm, value);
+            }
+        }
+
+        check_object_lock(self, &bucket, &key, None, false).await?;
+        let object_lock = self.new_object_lock(&bucket, None, None, None).await?;
+
+        // the ETag of a multipart object is the MD5 of the part MD5s followed by the number of parts
+        let mut md5_of_md5s = Md5::new();
+        for (part_number, _, e_tag, _) in &staged_parts {
+            let Ok(part_md5) = hex_simd::decode_to_vec(e_tag) else {
+                return Err(s3_error!(InternalError, "invalid ETag of part {}", part_number));
+            };
+            md5_of_md5s.update(part_md5);
+        }
+        let cnt = staged_parts.len();
+        let e_tag = quoted_e_tag(&format!("{}-{cnt}", hex(md5_of_md5s.finalize())));
+
+        if self.is_dry_run(&req.headers)? {
+            let output = CompleteMultipartUploadOutput {
+                bucket: Some(bucket),
+                key: Some(key),
+                e_tag: Some(e_tag),
+                checksum_crc32: checksum.checksum_crc32,
+                checksum_crc32c: checksum.checksum_crc32c,
+                checksum_sha1: checksum.checksum_sha1,
+                checksum_sha256: checksum.checksum_sha256,
+                request_charged,
+                ..Default::default()
+            };
+            return Ok(dry_run_response(output));
+        }
 
-            let part_path = self.resolve_abs_path(format!(".upload_id-{upload_id}.part-{part_number}"))?;
+        let mut file_writer = self.prepare_file_write(&bucket, &key).await?;
 
-            let mut reader = try_!(fs::File::open(&part_path).await);
+        // the parts are streamed into the object one by one, so that a large object is never loaded into memory
+        for (_, part_path, _, _) in &staged_parts {
+            let mut reader = try_!(self.storage.open(part_path).await);
             let size = try_!(tokio::io::copy(&mut reader, &mut file_writer.writer()).await);
 
             debug!(from = %part_path.display(), tmp = %file_writer.tmp_path().display(), to = %file_writer.final_path().display(), ?size, "write file");
-            try_!(fs::remove_file(&part_path).await);
         }
+        // the data is renamed and the sidecar files are written as a unit,
+        // and the object lock is checked again under the lock
+        let _lock = self.key_locks.write(&bucket, &key).await;
+        check_object_lock(self, &bucket, &key, None, false).await?;
+        let version_id = self.prepare_version_write(&bucket, &key).await?;
         let object_path = file_writer.done().await?;
 
-        let file_size = try_!(fs::metadata(&object_path).await).len();
-        let md5_sum = self.get_md5_sum(&bucket, &key).await?;
+        // the staged parts which are not listed are discarded
+        self.remove_upload(&upload_id).await?;
+
+        self.save_tagging(&bucket, &key, &TagMap::new()).await?;
+        self.save_metadata(&bucket, &key, upload_info.metadata.as_ref()).await?;
+
+        let object_parts: Vec<ObjectPartInfo> = staged_parts
+            .iter()
+            .map(|&(part_number, _, _, size)| ObjectPartInfo {
+                part_number,
+                size,
+                checksum: upload_info
+                    .checksum_algorithm
+                    .as_ref()
+                    .and(upload_info.parts.get(&part_number))
+                    .and_then(|p| p.checksum.clone()),
+            })
+            .collect();
+
+        let mut info: InternalInfo = default();
+        info.insert("e_tag".to_owned(), serde_json::Value::String(e_tag.clone()));
+        info.insert("parts".to_owned(), try_!(serde_json::to_value(object_parts)));
+        crate::checksum::modify_internal_info(&mut info, &checksum);
+        object_lock.modify_internal_info(&mut info)?;
+        let content_type = upload_info.content_type.and_then(|s| s.parse::<ContentType>().ok());
+        set_content_headers(
+            &mut info,
+            content_type.as_ref(),
+            upload_info.content_disposition.as_deref(),
+            upload_info.content_encoding.as_deref(),
+        );
+        set_website_redirect_location(&mut info, upload_info.website_redirect_location.as_deref());
+        if let Some(storage_class) = upload_info.storage_class {
+            info.insert("storage_class".to_owned(), serde_json::Value::String(storage_class));
+        }
+        if let Some(ref version_id) = version_id {
+            info.insert("version_id".to_owned(), serde_json::Value::String(version_id.clone()));
+        }
+        self.save_internal_info(&bucket, &key, &info).await?;
+        self.refresh_public_object(&bucket, &key).await?;
+
+        let file_size = try_!(self.storage.metadata(&object_path).await).len;
 
-        debug!(?md5_sum, path = %object_path.display(), size = ?file_size, "file md5 sum");
+        debug!(%e_tag, path = %object_path.display(), size = ?file_size, "complete multipart upload");
 
         let output = CompleteMultipartUploadOutput {
             bucket: Some(bucket),
             key: Some(key),
-            e_tag: Some(format!("\"{md5_sum}\"")),
+            e_tag: Some(e_tag),
+            checksum_crc32: checksum.checksum_crc32,
+            checksum_crc32c: checksum.checksum_crc32c,
+            checksum_sha1: checksum.checksum_sha1,
+            checksum_sha256: checksum.checksum_sha256,
+            version_id,
+            request_charged,
             ..Default::default()
         };
         Ok(S3Response::new(output))
@@ -747,34 +3363,63 @@ impl S3 for FileSystem {
         req: S3Request<AbortMultipartUploadInput>,
     ) -> S3Result<S3Response<AbortMultipartUploadOutput>> {
         let AbortMultipartUploadInput {
-            bucket, key, upload_id, ..
+            bucket,
+            key,
+            upload_id,
+            request_payer,
+            ..
         } = req.input;
-
-        let upload_id = Uuid::parse_str(&upload_id).map_err(|_| s3_error!(InvalidRequest))?;
+        let request_charged = crate::request_payment::request_charged(request_payer.as_ref());
+        reject_dry_run(&req.headers)?;
+        self.check_policy(req.credentials.as_ref(), "s3:AbortMultipartUpload", &bucket, Some(&key))
+            .await?;
+        check_bucket_exists(self, &bucket).await?;
+
+        let upload_id = Uuid::parse_str(&upload_id).map_err(|_| s3_error!(NoSuchUpload))?;
+        if self.load_upload_info(&upload_id).await?.is_none() {
+            return Err(s3_error!(NoSuchUpload));
+        }
         if self.verify_upload_id(req.credentials.as_ref(), &upload_id).await?.not() {
             return Err(s3_error!(AccessDenied));
         }
 
-        let prefix = format!(".upload_id-{upload_id}");
-        let mut iter = try_!(fs::read_dir(&self.root).await);
-        while let Some(entry) = try_!(iter.next_entry().await) {
-            let file_type = try_!(entry.file_type().await);
-            if file_type.is_file().not() {
-                continue;
-            }
+        self.remove_upload(&upload_id).await?;
+
+        debug!(bucket = %bucket, key = %key, upload_id = %upload_id, "multipart upload aborted");
+
+        Ok(S3Response::new(AbortMultipartUploadOutput { request_charged }))
+    }
 
-            let file_name = entry.file_name();
-            let Some(name) = file_name.to_str() else { continue };
+    /// Accepts the response of an Object Lambda function.
+    ///
+    /// There are no Object Lambda access points, so no `GetObject` waits for the response:
+    /// the request is validated and its body is consumed, but nothing is stored.
+    #[tracing::instrument]
+    async fn write_get_object_response(
+        &self,
+        req: S3Request<WriteGetObjectResponseInput>,
+    ) -> S3Result<S3Response<WriteGetObjectResponseOutput>> {
+        let input = req.input;
 
-            if name.starts_with(&prefix) {
-                try_!(fs::remove_file(entry.path()).await);
+        if input.request_route.is_empty() || input.request_token.is_empty() {
+            return Err(s3_error!(InvalidRequest, "The request route and the request token must not be empty"));
+        }
+        if let Some(status_code) = input.status_code {
+            if (200..600).contains(&status_code).not() {
+                return Err(s3_error!(InvalidArgument, "Invalid status code: {}", status_code));
             }
         }
 
-        self.delete_upload_id(&upload_id).await?;
+        let Some(body) = input.body else { return Err(s3_error!(IncompleteBody)) };
+        let len = copy_bytes(body, &mut tokio::io::sink()).await?;
+        if let Some(content_length) = input.content_length {
+            if i64::try_from(len).ok() != Some(content_length) {
+                return Err(s3_error!(IncompleteBody));
+            }
+        }
 
-        debug!(bucket = %bucket, key = %key, upload_id = %upload_id, "multipart upload aborted");
+        debug!(request_route = %input.request_route, status_code = ?input.status_code, len, "object lambda response received");
 
-        Ok(S3Response::new(AbortMultipartUploadOutput { ..Default::default() }))
+        Ok(S3Response::new(WriteGetObjectResponseOutput {}))
     }
 }
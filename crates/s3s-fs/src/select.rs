@@ -0,0 +1,800 @@
+//! A minimal `SelectObjectContent`, which runs simple SQL expressions over CSV and JSON objects
+//!
+//! The supported expressions are `SELECT <columns> FROM S3Object [alias] [WHERE <condition>] [LIMIT <n>]`,
+//! where a condition compares columns and literals, and is combined with `AND`, `OR` and `NOT`.
+//!
+//! The matching records are streamed back in `Records` events, followed by a `Stats` event and an `End` event.
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/userguide/s3-select-sql-reference-select.html>
+
+use s3s::dto::*;
+use s3s::{s3_error, S3Error, S3Result};
+
+use std::cmp::Ordering;
+use std::io;
+use std::ops::Not;
+
+use bytes::Bytes;
+use futures::{pin_mut, Stream, StreamExt};
+use serde_json::Value;
+use transform_stream::AsyncTryStream;
+
+/// The size of the payload of a `Records` event, which is flushed when it is exceeded
+const RECORDS_CHUNK_SIZE: usize = 64 * 1024;
+
+fn parse_error(msg: &str) -> S3Error {
+    s3_error!(InvalidArgument, "Invalid SQL expression: {}", msg)
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    QuotedIdent(String),
+    Str(String),
+    Num(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: &[&str] = &["<=", ">=", "<>", "!=", "*", ",", ".", "(", ")", "=", "<", ">"];
+
+/// Reads a quoted string, where the quote is escaped by doubling it
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>, quote: char) -> Option<String> {
+    let mut s = String::new();
+    loop {
+        let (_, c) = chars.next()?;
+        if c == quote && chars.next_if(|&(_, c)| c == quote).is_none() {
+            return Some(s);
+        }
+        s.push(c);
+    }
+}
+
+fn tokenize(sql: &str) -> S3Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = i;
+            while let Some((j, c)) = chars.next_if(|&(_, c)| c.is_ascii_alphanumeric() || c == '_') {
+                end = j + c.len_utf8();
+            }
+            tokens.push(Token::Ident(sql[i..end].to_owned()));
+        } else if c.is_ascii_digit() || (c == '-' && sql[i + 1..].starts_with(|c: char| c.is_ascii_digit())) {
+            chars.next();
+            let mut end = i + 1;
+            while let Some((j, _)) = chars.next_if(|&(_, c)| c.is_ascii_digit() || c == '.') {
+                end = j + 1;
+            }
+            tokens.push(Token::Num(sql[i..end].to_owned()));
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let s = read_quoted(&mut chars, c).ok_or_else(|| parse_error("unterminated quote"))?;
+            tokens.push(if c == '"' { Token::QuotedIdent(s) } else { Token::Str(s) });
+        } else {
+            let Some(&symbol) = SYMBOLS.iter().find(|s| sql[i..].starts_with(**s)) else {
+                return Err(s3_error!(InvalidArgument, "Invalid SQL expression: unexpected character '{}'", c));
+            };
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A column, which is a name or a position such as `_1`
+#[derive(Debug)]
+struct Column {
+    name: String,
+    /// Quoted names are case-sensitive
+    quoted: bool,
+}
+
+#[derive(Debug)]
+enum Operand {
+    Column(Column),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Compare(Operand, CmpOp, Operand),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Projection {
+    All,
+    Columns(Vec<(Column, Option<String>)>),
+}
+
+#[derive(Debug)]
+struct Query {
+    projection: Projection,
+    filter: Option<Expr>,
+    limit: Option<u64>,
+}
+
+const KEYWORDS: &[&str] = &["select", "from", "where", "and", "or", "not", "limit", "as"];
+
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    /// The qualifiers of the columns, which are checked against the alias of `S3Object`
+    qualifiers: Vec<String>,
+}
+
+impl Parser {
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        self.tokens
+            .next_if(|t| matches!(t, Token::Ident(s) if s.eq_ignore_ascii_case(keyword)))
+            .is_some()
+    }
+
+    fn eat_symbol(&mut self, symbol: &'static str) -> bool {
+        self.tokens.next_if(|t| *t == Token::Symbol(symbol)).is_some()
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> S3Result<()> {
+        if self.eat_keyword(keyword) {
+            return Ok(());
+        }
+        Err(s3_error!(
+            InvalidArgument,
+            "Invalid SQL expression: expected {}",
+            keyword.to_ascii_uppercase()
+        ))
+    }
+
+    /// Parses an identifier which is not a keyword
+    fn ident(&mut self) -> Option<(String, bool)> {
+        match self.tokens.peek()? {
+            Token::Ident(s) if KEYWORDS.iter().any(|k| s.eq_ignore_ascii_case(k)).not() => {}
+            Token::QuotedIdent(_) => {}
+            _ => return None,
+        }
+        match self.tokens.next()? {
+            Token::Ident(s) => Some((s, false)),
+            Token::QuotedIdent(s) => Some((s, true)),
+            _ => None,
+        }
+    }
+
+    fn column(&mut self) -> S3Result<Column> {
+        let (name, quoted) = self.ident().ok_or_else(|| parse_error("expected a column"))?;
+        if self.eat_symbol(".") {
+            self.qualifiers.push(name);
+            let (name, quoted) = self.ident().ok_or_else(|| parse_error("expected a column"))?;
+            return Ok(Column { name, quoted });
+        }
+        Ok(Column { name, quoted })
+    }
+
+    fn operand(&mut self) -> S3Result<Operand> {
+        match self.tokens.peek() {
+            Some(Token::Str(_)) => {
+                let Some(Token::Str(s)) = self.tokens.next() else { unreachable!() };
+                Ok(Operand::Literal(Value::String(s)))
+            }
+            Some(Token::Num(_)) => {
+                let Some(Token::Num(s)) = self.tokens.next() else { unreachable!() };
+                let value = match s.parse::<i64>() {
+                    Ok(n) => Value::from(n),
+                    Err(_) => s.parse::<f64>().map(Value::from).map_err(|_| parse_error("invalid number"))?,
+                };
+                Ok(Operand::Literal(value))
+            }
+            _ => self.column().map(Operand::Column),
+        }
+    }
+
+    fn comparison(&mut self) -> S3Result<Expr> {
+        if self.eat_symbol("(") {
+            let expr = self.or_expr()?;
+            if self.eat_symbol(")").not() {
+                return Err(parse_error("expected ')'"));
+            }
+            return Ok(expr);
+        }
+        let lhs = self.operand()?;
+        let op = match self.tokens.next() {
+            Some(Token::Symbol("=")) => CmpOp::Eq,
+            Some(Token::Symbol("!=" | "<>")) => CmpOp::Ne,
+            Some(Token::Symbol("<")) => CmpOp::Lt,
+            Some(Token::Symbol("<=")) => CmpOp::Le,
+            Some(Token::Symbol(">")) => CmpOp::Gt,
+            Some(Token::Symbol(">=")) => CmpOp::Ge,
+            _ => return Err(parse_error("expected a comparison operator")),
+        };
+        let rhs = self.operand()?;
+        Ok(Expr::Compare(lhs, op, rhs))
+    }
+
+    fn not_expr(&mut self) -> S3Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not_expr()?)));
+        }
+        self.comparison()
+    }
+
+    fn and_expr(&mut self) -> S3Result<Expr> {
+        let mut expr = self.not_expr()?;
+        while self.eat_keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn or_expr(&mut self) -> S3Result<Expr> {
+        let mut expr = self.and_expr()?;
+        while self.eat_keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and_expr()?));
+        }
+        Ok(expr)
+    }
+
+    fn projection(&mut self) -> S3Result<Projection> {
+        if self.eat_symbol("*") {
+            return Ok(Projection::All);
+        }
+        let mut columns = Vec::new();
+        loop {
+            let column = self.column()?;
+            let alias = if self.eat_keyword("as") {
+                Some(self.ident().ok_or_else(|| parse_error("expected an alias"))?.0)
+            } else {
+                self.ident().map(|(alias, _)| alias)
+            };
+            columns.push((column, alias));
+            if self.eat_symbol(",").not() {
+                return Ok(Projection::Columns(columns));
+            }
+        }
+    }
+
+    fn query(mut self) -> S3Result<Query> {
+        self.expect_keyword("select")?;
+        let projection = self.projection()?;
+
+        self.expect_keyword("from")?;
+        match self.tokens.next() {
+            Some(Token::Ident(s)) if s.eq_ignore_ascii_case("s3object") => {}
+            _ => return Err(parse_error("expected S3Object")),
+        }
+        let _ = self.eat_keyword("as");
+        let alias = self.ident().map(|(alias, _)| alias);
+
+        let filter = if self.eat_keyword("where") {
+            Some(self.or_expr()?)
+        } else {
+            None
+        };
+
+        let limit = if self.eat_keyword("limit") {
+            match self.tokens.next() {
+                Some(Token::Num(n)) => Some(n.parse::<u64>().map_err(|_| parse_error("invalid LIMIT"))?),
+                _ => return Err(parse_error("invalid LIMIT")),
+            }
+        } else {
+            None
+        };
+
+        if self.tokens.next().is_some() {
+            return Err(parse_error("unexpected token"));
+        }
+
+        for qualifier in &self.qualifiers {
+            let is_table = qualifier.eq_ignore_ascii_case("s3object") || alias.as_deref() == Some(qualifier.as_str());
+            if is_table.not() {
+                return Err(s3_error!(InvalidArgument, "Invalid SQL expression: unknown table '{}'", qualifier));
+            }
+        }
+
+        Ok(Query {
+            projection,
+            filter,
+            limit,
+        })
+    }
+}
+
+impl Query {
+    fn parse(sql: &str) -> S3Result<Self> {
+        let parser = Parser {
+            tokens: tokenize(sql)?.into_iter().peekable(),
+            qualifiers: Vec::new(),
+        };
+        parser.query()
+    }
+}
+
+/// A record, whose fields are named by the header of a CSV object, or by the keys of a JSON object
+#[derive(Debug)]
+struct Record(Vec<(String, Value)>);
+
+impl Record {
+    fn get(&self, column: &Column) -> Option<&Value> {
+        let found = self.0.iter().find(|(name, _)| {
+            if column.quoted {
+                *name == column.name
+            } else {
+                name.eq_ignore_ascii_case(&column.name)
+            }
+        });
+        if let Some((_, value)) = found {
+            return Some(value);
+        }
+        // `_1` is the first field
+        let index = column.name.strip_prefix('_')?.parse::<usize>().ok()?.checked_sub(1)?;
+        self.0.get(index).map(|(_, value)| value)
+    }
+}
+
+/// Compares two values. A number and a string are compared as numbers.
+fn compare(lhs: &Value, rhs: &Value) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::Number(a), Value::String(b)) => a.as_f64()?.partial_cmp(&b.trim().parse().ok()?),
+        (Value::String(a), Value::Number(b)) => a.trim().parse::<f64>().ok()?.partial_cmp(&b.as_f64()?),
+        _ => None,
+    }
+}
+
+impl Expr {
+    /// Evaluates the condition, which is unknown if a compared field is missing
+    fn eval(&self, record: &Record) -> Option<bool> {
+        match self {
+            Expr::Compare(lhs, op, rhs) => {
+                let value = |operand: &Operand| match operand {
+                    Operand::Column(column) => record.get(column).cloned(),
+                    Operand::Literal(value) => Some(value.clone()),
+                };
+                let ordering = compare(&value(lhs)?, &value(rhs)?)?;
+                Some(match op {
+                    CmpOp::Eq => ordering.is_eq(),
+                    CmpOp::Ne => ordering.is_ne(),
+                    CmpOp::Lt => ordering.is_lt(),
+                    CmpOp::Le => ordering.is_le(),
+                    CmpOp::Gt => ordering.is_gt(),
+                    CmpOp::Ge => ordering.is_ge(),
+                })
+            }
+            Expr::And(lhs, rhs) => match (lhs.eval(record), rhs.eval(record)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Expr::Or(lhs, rhs) => match (lhs.eval(record), rhs.eval(record)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Expr::Not(expr) => expr.eval(record).map(Not::not),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum FileHeader {
+    Use,
+    Ignore,
+    None,
+}
+
+#[derive(Debug)]
+struct CsvFormat {
+    field_delimiter: Vec<u8>,
+    record_delimiter: Vec<u8>,
+    quote: Vec<u8>,
+    quote_escape: Vec<u8>,
+    comments: Vec<u8>,
+}
+
+fn delimiter(value: Option<&str>, default: &str, name: &str) -> S3Result<Vec<u8>> {
+    let value = value.unwrap_or(default);
+    if value.is_empty() {
+        return Err(s3_error!(InvalidArgument, "The {} cannot be empty", name));
+    }
+    Ok(value.as_bytes().to_vec())
+}
+
+impl CsvFormat {
+    fn from_input(input: &CSVInput) -> S3Result<Self> {
+        let quote = delimiter(input.quote_character.as_deref(), "\"", "QuoteCharacter")?;
+        Ok(Self {
+            field_delimiter: delimiter(input.field_delimiter.as_deref(), ",", "FieldDelimiter")?,
+            record_delimiter: delimiter(input.record_delimiter.as_deref(), "\n", "RecordDelimiter")?,
+            quote_escape: input
+                .quote_escape_character
+                .as_deref()
+                .map_or_else(|| quote.clone(), |s| s.as_bytes().to_vec()),
+            quote,
+            comments: input.comments.as_deref().unwrap_or("#").as_bytes().to_vec(),
+        })
+    }
+
+    fn from_output(output: &CSVOutput) -> S3Result<Self> {
+        let quote = delimiter(output.quote_character.as_deref(), "\"", "QuoteCharacter")?;
+        Ok(Self {
+            field_delimiter: delimiter(output.field_delimiter.as_deref(), ",", "FieldDelimiter")?,
+            record_delimiter: delimiter(output.record_delimiter.as_deref(), "\n", "RecordDelimiter")?,
+            quote_escape: output
+                .quote_escape_character
+                .as_deref()
+                .map_or_else(|| quote.clone(), |s| s.as_bytes().to_vec()),
+            quote,
+            comments: Vec::new(),
+        })
+    }
+
+    /// Parses a record at the start of `buf`, returning its fields and the number of consumed bytes.
+    ///
+    /// Returns `None` if the record may continue after `buf`.
+    fn parse(&self, buf: &[u8], eof: bool) -> Option<(Vec<Vec<u8>>, usize)> {
+        let mut fields = Vec::new();
+        let mut field = Vec::new();
+        let mut in_quotes = false;
+        let mut i = 0;
+        while i < buf.len() {
+            let rest = &buf[i..];
+            if in_quotes {
+                if rest.starts_with(&self.quote_escape) && rest[self.quote_escape.len()..].starts_with(&self.quote) {
+                    field.extend_from_slice(&self.quote);
+                    i += self.quote_escape.len() + self.quote.len();
+                } else if rest.starts_with(&self.quote) {
+                    in_quotes = false;
+                    i += self.quote.len();
+                } else {
+                    field.push(buf[i]);
+                    i += 1;
+                }
+            } else if rest.starts_with(&self.record_delimiter) {
+                // tolerates CRLF line endings
+                if self.record_delimiter == b"\n" && field.last() == Some(&b'\r') {
+                    field.pop();
+                }
+                fields.push(field);
+                return Some((fields, i + self.record_delimiter.len()));
+            } else if rest.starts_with(&self.field_delimiter) {
+                fields.push(std::mem::take(&mut field));
+                i += self.field_delimiter.len();
+            } else if field.is_empty() && rest.starts_with(&self.quote) {
+                in_quotes = true;
+                i += self.quote.len();
+            } else {
+                field.push(buf[i]);
+                i += 1;
+            }
+        }
+        if eof.not() || buf.is_empty() {
+            return None;
+        }
+        fields.push(field);
+        Some((fields, buf.len()))
+    }
+
+    fn write_field(&self, out: &mut Vec<u8>, field: &str, always_quote: bool) {
+        let needs_quotes = always_quote
+            || [&self.field_delimiter, &self.record_delimiter, &self.quote]
+                .iter()
+                .any(|d| field.as_bytes().windows(d.len()).any(|w| w == d.as_slice()))
+            || field.contains(['\r', '\n']);
+        if needs_quotes.not() {
+            out.extend_from_slice(field.as_bytes());
+            return;
+        }
+        out.extend_from_slice(&self.quote);
+        let mut rest = field.as_bytes();
+        while let Some(&b) = rest.first() {
+            if rest.starts_with(&self.quote) {
+                out.extend_from_slice(&self.quote_escape);
+                out.extend_from_slice(&self.quote);
+                rest = &rest[self.quote.len()..];
+            } else {
+                out.push(b);
+                rest = &rest[1..];
+            }
+        }
+        out.extend_from_slice(&self.quote);
+    }
+}
+
+#[derive(Debug)]
+enum InputFormat {
+    Csv { format: CsvFormat, header: FileHeader },
+    Json,
+}
+
+#[derive(Debug)]
+enum OutputFormat {
+    Csv { format: CsvFormat, always_quote: bool },
+    Json { record_delimiter: Vec<u8> },
+}
+
+fn utf8(bytes: Vec<u8>) -> S3Result<String> {
+    String::from_utf8(bytes).map_err(|_| s3_error!(InvalidRequest, "The object is not UTF-8 encoded"))
+}
+
+/// Reads the records of an object
+#[derive(Debug)]
+struct Reader {
+    input: InputFormat,
+    /// The names of the CSV fields, which are read from the header
+    names: Option<Vec<String>>,
+    is_first: bool,
+}
+
+impl Reader {
+    /// Reads a record at the start of `buf`, returning it with the number of consumed bytes.
+    ///
+    /// The record is `None` when it is skipped, such as a header or a comment.
+    fn read(&mut self, buf: &[u8], eof: bool) -> S3Result<Option<(Option<Record>, usize)>> {
+        match self.input {
+            InputFormat::Csv { ref format, ref header } => {
+                let Some((fields, consumed)) = format.parse(buf, eof) else { return Ok(None) };
+                let is_first = std::mem::replace(&mut self.is_first, false);
+
+                let is_comment = format.comments.is_empty().not() && buf.starts_with(&format.comments);
+                let is_blank = fields.len() == 1 && fields[0].is_empty();
+                if is_comment || is_blank {
+                    self.is_first = is_first;
+                    return Ok(Some((None, consumed)));
+                }
+
+                let fields = fields.into_iter().map(utf8).collect::<S3Result<Vec<_>>>()?;
+                if is_first && *header != FileHeader::None {
+                    if *header == FileHeader::Use {
+                        self.names = Some(fields);
+                    }
+                    return Ok(Some((None, consumed)));
+                }
+
+                let record = fields
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let name = self.names.as_ref().and_then(|names| names.get(i));
+                        let name = name.cloned().unwrap_or_else(|| format!("_{}", i + 1));
+                        (name, Value::String(field))
+                    })
+                    .collect();
+                Ok(Some((Some(Record(record)), consumed)))
+            }
+            InputFormat::Json => {
+                let start = buf.iter().position(|b| b.is_ascii_whitespace().not()).unwrap_or(buf.len());
+                let mut iter = serde_json::Deserializer::from_slice(&buf[start..]).into_iter::<Value>();
+                match iter.next() {
+                    None => Ok(None),
+                    Some(Err(err)) if err.is_eof() && eof.not() => Ok(None),
+                    Some(Err(err)) => Err(s3_error!(InvalidRequest, "The object is not valid JSON: {}", err)),
+                    Some(Ok(Value::Object(map))) => {
+                        Ok(Some((Some(Record(map.into_iter().collect())), start + iter.byte_offset())))
+                    }
+                    Some(Ok(value)) => {
+                        let record = Record(vec![("_1".to_owned(), value)]);
+                        Ok(Some((Some(record), start + iter.byte_offset())))
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        _ => value.to_string(),
+    }
+}
+
+impl OutputFormat {
+    /// Writes the projected fields, where a missing field is `None`
+    fn write(&self, out: &mut Vec<u8>, fields: &[(&str, Option<&Value>)]) {
+        match self {
+            OutputFormat::Csv { format, always_quote } => {
+                for (i, (_, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.extend_from_slice(&format.field_delimiter);
+                    }
+                    let text = value.map(value_text).unwrap_or_default();
+                    format.write_field(out, &text, *always_quote);
+                }
+                out.extend_from_slice(&format.record_delimiter);
+            }
+            OutputFormat::Json { record_delimiter } => {
+                out.push(b'{');
+                let present = fields.iter().filter_map(|(name, value)| Some((name, (*value)?)));
+                for (i, (name, value)) in present.enumerate() {
+                    if i > 0 {
+                        out.push(b',');
+                    }
+                    out.extend_from_slice(Value::from(*name).to_string().as_bytes());
+                    out.push(b':');
+                    out.extend_from_slice(value.to_string().as_bytes());
+                }
+                out.push(b'}');
+                out.extend_from_slice(record_delimiter);
+            }
+        }
+    }
+}
+
+/// A validated `SelectObjectContent` request
+#[derive(Debug)]
+pub(crate) struct Select {
+    query: Query,
+    reader: Reader,
+    output: OutputFormat,
+    progress: bool,
+}
+
+impl Select {
+    pub(crate) fn new(request: &SelectObjectContentRequest) -> S3Result<Self> {
+        if request.expression_type.as_str() != ExpressionType::SQL {
+            return Err(s3_error!(
+                InvalidArgument,
+                "The expression type is not supported: {}",
+                request.expression_type.as_str()
+            ));
+        }
+        if request.scan_range.is_some() {
+            return Err(s3_error!(NotImplemented, "ScanRange is not supported"));
+        }
+
+        let input = &request.input_serialization;
+        match input.compression_type.as_ref().map(CompressionType::as_str) {
+            None | Some(CompressionType::NONE) => {}
+            Some(_) => return Err(s3_error!(NotImplemented, "Compressed objects are not supported")),
+        }
+        if input.parquet.is_some() {
+            return Err(s3_error!(NotImplemented, "Parquet objects are not supported"));
+        }
+        let input = match (&input.csv, &input.json) {
+            (Some(csv), None) => {
+                let header = match csv.file_header_info.as_ref().map(FileHeaderInfo::as_str) {
+                    Some(FileHeaderInfo::USE) => FileHeader::Use,
+                    Some(FileHeaderInfo::IGNORE) => FileHeader::Ignore,
+                    _ => FileHeader::None,
+                };
+                let format = CsvFormat::from_input(csv)?;
+                InputFormat::Csv { format, header }
+            }
+            (None, Some(_)) => InputFormat::Json,
+            _ => return Err(s3_error!(InvalidArgument, "Exactly one input serialization must be specified")),
+        };
+
+        let output = &request.output_serialization;
+        let output = match (&output.csv, &output.json) {
+            (Some(csv), None) => OutputFormat::Csv {
+                format: CsvFormat::from_output(csv)?,
+                always_quote: csv.quote_fields.as_ref().is_some_and(|q| q.as_str() == QuoteFields::ALWAYS),
+            },
+            (None, Some(json)) => OutputFormat::Json {
+                record_delimiter: delimiter(json.record_delimiter.as_deref(), "\n", "RecordDelimiter")?,
+            },
+            _ => return Err(s3_error!(InvalidArgument, "Exactly one output serialization must be specified")),
+        };
+
+        Ok(Self {
+            query: Query::parse(&request.expression)?,
+            reader: Reader {
+                input,
+                names: None,
+                is_first: true,
+            },
+            output,
+            progress: request.request_progress.as_ref().is_some_and(|p| p.enabled),
+        })
+    }
+
+    /// Writes a matching record, returning `false` if it does not match
+    fn write_record(&self, out: &mut Vec<u8>, record: &Record) -> bool {
+        if let Some(ref filter) = self.query.filter {
+            if filter.eval(record) != Some(true) {
+                return false;
+            }
+        }
+        let fields: Vec<_> = match self.query.projection {
+            Projection::All => record.0.iter().map(|(name, value)| (name.as_str(), Some(value))).collect(),
+            Projection::Columns(ref columns) => columns
+                .iter()
+                .map(|(column, alias)| (alias.as_deref().unwrap_or(&column.name), record.get(column)))
+                .collect(),
+        };
+        self.output.write(out, &fields);
+        true
+    }
+
+    /// Runs the query over the content of an object
+    pub(crate) fn run<S>(mut self, content: S) -> SelectObjectContentEventStream
+    where
+        S: Stream<Item = io::Result<Bytes>> + Send + Sync + 'static,
+    {
+        let stream = AsyncTryStream::<SelectObjectContentEvent, S3Error, _>::new(|mut y| async move {
+            pin_mut!(content);
+            let mut buf: Vec<u8> = Vec::new();
+            let mut pos = 0;
+            let mut eof = false;
+            let mut out = Vec::new();
+            let mut bytes_scanned: u64 = 0;
+            let mut bytes_returned: u64 = 0;
+            let mut matched: u64 = 0;
+
+            'read: loop {
+                while let Some((record, consumed)) = self.reader.read(&buf[pos..], eof)? {
+                    pos += consumed;
+                    let Some(record) = record else { continue };
+                    if self.query.limit.is_some_and(|limit| matched >= limit) {
+                        break 'read;
+                    }
+                    if self.write_record(&mut out, &record) {
+                        matched += 1;
+                    }
+                    if out.len() >= RECORDS_CHUNK_SIZE {
+                        bytes_returned += out.len() as u64;
+                        let payload = Some(Bytes::from(std::mem::take(&mut out)));
+                        y.yield_ok(SelectObjectContentEvent::Records(RecordsEvent { payload })).await;
+                    }
+                }
+                if eof {
+                    break;
+                }
+                match content.next().await {
+                    Some(chunk) => {
+                        let chunk = chunk.map_err(S3Error::internal_error)?;
+                        bytes_scanned += chunk.len() as u64;
+                        buf.drain(..pos);
+                        pos = 0;
+                        buf.extend_from_slice(&chunk);
+                    }
+                    None => eof = true,
+                }
+            }
+
+            if out.is_empty().not() {
+                bytes_returned += out.len() as u64;
+                let payload = Some(Bytes::from(out));
+                y.yield_ok(SelectObjectContentEvent::Records(RecordsEvent { payload })).await;
+            }
+
+            let bytes_scanned = i64::try_from(bytes_scanned).map_err(S3Error::internal_error)?;
+            let bytes_returned = i64::try_from(bytes_returned).map_err(S3Error::internal_error)?;
+            if self.progress {
+                let details = Progress {
+                    bytes_processed: bytes_scanned,
+                    bytes_returned,
+                    bytes_scanned,
+                };
+                let event = ProgressEvent { details: Some(details) };
+                y.yield_ok(SelectObjectContentEvent::Progress(event)).await;
+            }
+            let details = Stats {
+                bytes_processed: bytes_scanned,
+                bytes_returned,
+                bytes_scanned,
+            };
+            y.yield_ok(SelectObjectContentEvent::Stats(StatsEvent { details: Some(details) }))
+                .await;
+            y.yield_ok(SelectObjectContentEvent::End(EndEvent {})).await;
+            Ok(())
+        });
+        SelectObjectContentEventStream::new(stream)
+    }
+}
@@ -0,0 +1,240 @@
+//! Server-side encryption with customer-provided keys (SSE-C)
+//!
+//! The object is split into chunks which are sealed with AES-256-GCM.
+//! Each object has a random salt, and the actual key is derived from the customer key and the salt,
+//! so that nonces are never reused across objects.
+//! The customer key itself is never stored.
+
+use crate::fs::InternalInfo;
+
+use s3s::{s3_error, S3Result, StdError};
+
+use std::io;
+use std::ops::Not;
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures::pin_mut;
+use futures::{Stream, StreamExt};
+use md5::{Digest, Md5};
+use ring::aead;
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use transform_stream::AsyncTryStream;
+
+/// The only supported algorithm
+pub(crate) const ALGORITHM: &str = "AES256";
+
+/// The size of a plaintext chunk
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const TAG_LEN: usize = 16;
+
+const ENCRYPTED_CHUNK_SIZE: usize = CHUNK_SIZE + TAG_LEN;
+
+/// The customer key of a request
+pub(crate) struct CustomerKey {
+    key: [u8; 32],
+    key_md5: String,
+}
+
+impl CustomerKey {
+    /// Parses the `x-amz-server-side-encryption-customer-*` headers.
+    ///
+    /// Returns `None` if none of the headers is present.
+    pub(crate) fn from_headers(algorithm: Option<&str>, key: Option<&str>, key_md5: Option<&str>) -> S3Result<Option<Self>> {
+        let (algorithm, key, key_md5) = match (algorithm, key, key_md5) {
+            (None, None, None) => return Ok(None),
+            (Some(algorithm), Some(key), Some(key_md5)) => (algorithm, key, key_md5),
+            _ => {
+                return Err(s3_error!(
+                    InvalidArgument,
+                    "Requests specifying Server Side Encryption with Customer provided keys must provide the algorithm, the key and the key MD5."
+                ))
+            }
+        };
+
+        if algorithm != ALGORITHM {
+            return Err(s3_error!(
+                InvalidEncryptionAlgorithmError,
+                "The encryption request you specified is not valid. The valid value is AES256."
+            ));
+        }
+
+        let key = base64_simd::STANDARD.decode_to_vec(key).ok();
+        let Some(key) = key.and_then(|k| <[u8; 32]>::try_from(k).ok()) else {
+            return Err(s3_error!(InvalidArgument, "The secret key was invalid for the specified algorithm."));
+        };
+
+        let calculated_md5 = base64_simd::STANDARD.encode_to_string(Md5::digest(key));
+        if calculated_md5 != key_md5 {
+            return Err(s3_error!(
+                InvalidArgument,
+                "The calculated MD5 hash of the key did not match the hash that was provided."
+            ));
+        }
+
+        Ok(Some(Self {
+            key,
+            key_md5: calculated_md5,
+        }))
+    }
+
+    pub(crate) fn key_md5(&self) -> &str {
+        &self.key_md5
+    }
+
+    /// Creates the cipher of a new object and records the encryption in its internal info.
+    pub(crate) fn new_object_cipher(&self, info: &mut InternalInfo) -> S3Result<ObjectCipher> {
+        let mut salt = [0; 32];
+        if SystemRandom::new().fill(&mut salt).is_err() {
+            return Err(s3_error!(InternalError, "failed to generate a random salt"));
+        }
+
+        info.insert("sse_customer_algorithm".to_owned(), ALGORITHM.into());
+        info.insert("sse_customer_key_md5".to_owned(), self.key_md5.clone().into());
+        info.insert("sse_customer_salt".to_owned(), base64_simd::STANDARD.encode_to_string(salt).into());
+
+        Ok(ObjectCipher::new(&self.key, &salt))
+    }
+}
+
+/// Returns whether an object is encrypted with a customer key
+pub(crate) fn is_encrypted(info: Option<&InternalInfo>) -> bool {
+    info.is_some_and(|info| info.contains_key("sse_customer_key_md5"))
+}
+
+/// Checks the customer key of a read request against the stored object.
+///
+/// Returns the cipher to decrypt the object, or `None` if the object is not encrypted.
+pub(crate) fn object_cipher(info: Option<&InternalInfo>, customer_key: Option<&CustomerKey>) -> S3Result<Option<ObjectCipher>> {
+    let stored = info.and_then(|info| {
+        let key_md5 = info.get("sse_customer_key_md5")?.as_str()?;
+        let salt = info.get("sse_customer_salt")?.as_str()?;
+        Some((key_md5, salt))
+    });
+    match (stored, customer_key) {
+        (None, None) => Ok(None),
+        (None, Some(_)) => Err(s3_error!(InvalidRequest, "The encryption parameters are not applicable to this object.")),
+        (Some(_), None) => Err(s3_error!(
+            InvalidRequest,
+            "The object was stored using a form of Server Side Encryption. The correct parameters must be provided to retrieve the object."
+        )),
+        (Some((key_md5, salt)), Some(customer_key)) => {
+            if key_md5 != customer_key.key_md5 {
+                return Err(s3_error!(AccessDenied, "The provided key does not match the key of the object."));
+            }
+            let Ok(salt) = base64_simd::STANDARD.decode_to_vec(salt) else {
+                return Err(s3_error!(InternalError, "invalid salt of an encrypted object"));
+            };
+            Ok(Some(ObjectCipher::new(&customer_key.key, &salt)))
+        }
+    }
+}
+
+/// Encrypts and decrypts the chunks of an object
+pub(crate) struct ObjectCipher {
+    key: aead::LessSafeKey,
+}
+
+impl ObjectCipher {
+    fn new(customer_key: &[u8; 32], salt: &[u8]) -> Self {
+        let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, customer_key), salt);
+        let key = aead::UnboundKey::new(&aead::AES_256_GCM, tag.as_ref()).expect("the derived key has a valid length");
+        Self {
+            key: aead::LessSafeKey::new(key),
+        }
+    }
+
+    /// The nonce is the chunk index, and the last chunk is authenticated as such to detect truncation.
+    fn nonce_and_aad(index: u64, is_last: bool) -> (aead::Nonce, aead::Aad<[u8; 1]>) {
+        let mut nonce = [0; aead::NONCE_LEN];
+        nonce[..8].copy_from_slice(&index.to_be_bytes());
+        (aead::Nonce::assume_unique_for_key(nonce), aead::Aad::from([u8::from(is_last)]))
+    }
+
+    fn seal(&self, index: u64, is_last: bool, chunk: &[u8]) -> Bytes {
+        let (nonce, aad) = Self::nonce_and_aad(index, is_last);
+        let mut buf = Vec::with_capacity(chunk.len() + TAG_LEN);
+        buf.extend_from_slice(chunk);
+        self.key
+            .seal_in_place_append_tag(nonce, aad, &mut buf)
+            .expect("the chunk is not too large");
+        buf.into()
+    }
+
+    fn open(&self, index: u64, is_last: bool, mut chunk: Vec<u8>) -> Option<Bytes> {
+        let (nonce, aad) = Self::nonce_and_aad(index, is_last);
+        let len = self.key.open_in_place(nonce, aad, &mut chunk).ok()?.len();
+        chunk.truncate(len);
+        Some(chunk.into())
+    }
+}
+
+/// Returns the plaintext length of an encrypted file
+pub(crate) fn plaintext_len(encrypted_len: u64) -> u64 {
+    let chunks = encrypted_len.div_ceil(ENCRYPTED_CHUNK_SIZE as u64);
+    encrypted_len.saturating_sub(chunks * TAG_LEN as u64)
+}
+
+pub(crate) fn encrypt_stream<S>(stream: S, cipher: ObjectCipher) -> impl Stream<Item = Result<Bytes, StdError>>
+where
+    S: Stream<Item = Result<Bytes, StdError>>,
+{
+    AsyncTryStream::<Bytes, StdError, _>::new(|mut y| async move {
+        pin_mut!(stream);
+        let mut buf = BytesMut::new();
+        let mut index: u64 = 0;
+        while let Some(result) = stream.next().await {
+            buf.extend_from_slice(&result?);
+            // a full chunk is only sealed when more data follows, because the last chunk is marked
+            while buf.len() > CHUNK_SIZE {
+                let chunk = buf.split_to(CHUNK_SIZE);
+                y.yield_ok(cipher.seal(index, false, &chunk)).await;
+                index += 1;
+            }
+        }
+        y.yield_ok(cipher.seal(index, true, &buf)).await;
+        Ok(())
+    })
+}
+
+/// Decrypts an encrypted file, starting at the plaintext position `start`
+pub(crate) fn decrypt_stream<R>(
+    mut reader: R,
+    cipher: ObjectCipher,
+    encrypted_len: u64,
+    start: u64,
+) -> impl Stream<Item = io::Result<Bytes>>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    AsyncTryStream::<Bytes, io::Error, _>::new(move |mut y| async move {
+        let chunk_count = encrypted_len.div_ceil(ENCRYPTED_CHUNK_SIZE as u64);
+        let mut index = start / CHUNK_SIZE as u64;
+        let mut skip = usize::try_from(start % CHUNK_SIZE as u64).expect("smaller than the chunk size");
+
+        reader.seek(io::SeekFrom::Start(index * ENCRYPTED_CHUNK_SIZE as u64)).await?;
+
+        while index < chunk_count {
+            let mut chunk = Vec::with_capacity(ENCRYPTED_CHUNK_SIZE);
+            (&mut reader)
+                .take(ENCRYPTED_CHUNK_SIZE as u64)
+                .read_to_end(&mut chunk)
+                .await?;
+
+            let is_last = index + 1 == chunk_count;
+            let Some(mut plaintext) = cipher.open(index, is_last, chunk) else {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "failed to decrypt the object"));
+            };
+            plaintext.advance(skip.min(plaintext.len()));
+            skip = 0;
+
+            if plaintext.is_empty().not() {
+                y.yield_ok(plaintext).await;
+            }
+            index += 1;
+        }
+        Ok(())
+    })
+}
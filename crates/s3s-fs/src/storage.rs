@@ -0,0 +1,343 @@
+//! Access to the files of a [`FileSystem`](crate::FileSystem)
+
+use std::fmt;
+use std::io;
+use std::ops::Not;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use tokio::fs;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite};
+
+/// The file operations of a [`FileSystem`](crate::FileSystem), which lets it store its files in another backend.
+///
+/// The paths are absolute paths below the root of the file system.
+/// The methods follow the semantics of their counterparts in [`tokio::fs`],
+/// and a missing file is reported with [`io::ErrorKind::NotFound`].
+///
+/// The root of a file system with another storage is prepared with the storage as well,
+/// see [`FileSystemBuilder::build_with_storage`](crate::FileSystemBuilder::build_with_storage),
+/// which also removes the temporary files of interrupted writes.
+#[async_trait]
+pub trait Storage: fmt::Debug + Send + Sync + 'static {
+    /// Opens a file for reading.
+    async fn open(&self, path: &Path) -> io::Result<Box<dyn StorageFile>>;
+
+    /// Creates a file for writing, truncating it if it exists.
+    async fn create(&self, path: &Path) -> io::Result<Box<dyn StorageFile>>;
+
+    /// Reads the whole content of a file.
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Writes the whole content of a file, replacing it if it exists.
+    async fn write(&self, path: &Path, content: &[u8]) -> io::Result<()>;
+
+    /// Copies the content of a file, returning the number of copied bytes.
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<u64>;
+
+    /// Renames a file or a directory, replacing the destination file if it exists.
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates a directory, which is [`io::ErrorKind::AlreadyExists`] if it exists.
+    async fn create_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates a directory and all of its missing parents.
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes an empty directory.
+    async fn remove_dir(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes a directory and all of its content.
+    async fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Lists the entries of a directory, in no particular order.
+    async fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>>;
+
+    /// Returns the metadata of a file, following symbolic links.
+    async fn metadata(&self, path: &Path) -> io::Result<FileMetadata>;
+
+    /// Returns the metadata of a file without following symbolic links.
+    ///
+    /// The default implementation calls [`Storage::metadata`].
+    async fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        self.metadata(path).await
+    }
+
+    /// Persists the renames in a directory, when durable writes are enabled.
+    ///
+    /// The default implementation does nothing.
+    async fn sync_dir(&self, path: &Path) -> io::Result<()> {
+        let _ = path;
+        Ok(())
+    }
+
+    /// Returns whether a file or a directory exists.
+    ///
+    /// The default implementation calls [`Storage::metadata`].
+    async fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).await.is_ok()
+    }
+
+    /// Returns whether a file exists.
+    ///
+    /// The default implementation calls [`Storage::metadata`].
+    async fn is_file(&self, path: &Path) -> bool {
+        self.metadata(path).await.is_ok_and(|m| m.is_file())
+    }
+
+    /// Returns whether a directory exists.
+    ///
+    /// The default implementation calls [`Storage::metadata`].
+    async fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).await.is_ok_and(|m| m.is_dir)
+    }
+}
+
+/// An open file of a [`Storage`]
+#[async_trait]
+pub trait StorageFile: AsyncRead + AsyncWrite + AsyncSeek + Send + Sync + Unpin {
+    async fn metadata(&self) -> io::Result<FileMetadata>;
+
+    /// Sets the modification time of the file.
+    async fn set_modified(&self, modified: SystemTime) -> io::Result<()>;
+
+    /// Persists the content of the file, when durable writes are enabled.
+    async fn sync_all(&self) -> io::Result<()>;
+}
+
+/// The metadata of a file or a directory
+#[derive(Debug, Clone, Copy)]
+pub struct FileMetadata {
+    pub len: u64,
+    pub is_dir: bool,
+    pub modified: SystemTime,
+    /// The creation time, which is not provided by all filesystems
+    pub created: Option<SystemTime>,
+}
+
+impl FileMetadata {
+    #[must_use]
+    pub fn is_file(&self) -> bool {
+        self.is_dir.not()
+    }
+
+    fn from_std(metadata: &std::fs::Metadata) -> io::Result<Self> {
+        Ok(Self {
+            len: metadata.len(),
+            is_dir: metadata.is_dir(),
+            modified: metadata.modified()?,
+            created: metadata.created().ok(),
+        })
+    }
+}
+
+/// An entry of a directory
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    pub path: PathBuf,
+    /// Whether the entry is a directory, without following symbolic links
+    pub is_dir: bool,
+}
+
+impl DirEntry {
+    pub(crate) fn file_name(&self) -> Option<&str> {
+        self.path.file_name().and_then(|name| name.to_str())
+    }
+}
+
+/// The default storage, which uses the local disk through [`tokio::fs`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioStorage;
+
+#[async_trait]
+impl Storage for TokioStorage {
+    async fn open(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        Ok(Box::new(fs::File::open(path).await?))
+    }
+
+    async fn create(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        Ok(Box::new(fs::File::create(path).await?))
+    }
+
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        fs::write(path, content).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        fs::copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to).await
+    }
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path).await
+    }
+
+    async fn create_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir(path).await
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path).await
+    }
+
+    async fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path).await
+    }
+
+    async fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path).await
+    }
+
+    async fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+        let mut iter = fs::read_dir(path).await?;
+        while let Some(entry) = iter.next_entry().await? {
+            let is_dir = entry.file_type().await?.is_dir();
+            entries.push(DirEntry {
+                path: entry.path(),
+                is_dir,
+            });
+        }
+        Ok(entries)
+    }
+
+    async fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        FileMetadata::from_std(&fs::metadata(path).await?)
+    }
+
+    async fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        FileMetadata::from_std(&fs::symlink_metadata(path).await?)
+    }
+
+    #[cfg(unix)]
+    async fn sync_dir(&self, path: &Path) -> io::Result<()> {
+        fs::File::open(path).await?.sync_all().await
+    }
+
+    // the checks are single syscalls, which are not worth a blocking task
+
+    async fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    async fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+}
+
+/// The local disk through [`std::fs`], which prepares the root for the synchronous constructors and setters
+/// of the file system.
+///
+/// Its futures complete without waiting, so they can be run with [`futures::executor::block_on`] without a runtime.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct BlockingStorage;
+
+#[async_trait]
+impl Storage for BlockingStorage {
+    async fn open(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        Ok(Box::new(fs::File::from_std(std::fs::File::open(path)?)))
+    }
+
+    async fn create(&self, path: &Path) -> io::Result<Box<dyn StorageFile>> {
+        Ok(Box::new(fs::File::from_std(std::fs::File::create(path)?)))
+    }
+
+    async fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> io::Result<()> {
+        std::fs::write(path, content)
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        std::fs::copy(from, to)
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        std::fs::rename(from, to)
+    }
+
+    async fn remove_file(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+
+    async fn create_dir(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir(path)
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    async fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir(path)
+    }
+
+    async fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::remove_dir_all(path)
+    }
+
+    async fn read_dir(&self, path: &Path) -> io::Result<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let is_dir = entry.file_type()?.is_dir();
+            entries.push(DirEntry {
+                path: entry.path(),
+                is_dir,
+            });
+        }
+        Ok(entries)
+    }
+
+    async fn metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        FileMetadata::from_std(&std::fs::metadata(path)?)
+    }
+
+    async fn symlink_metadata(&self, path: &Path) -> io::Result<FileMetadata> {
+        FileMetadata::from_std(&std::fs::symlink_metadata(path)?)
+    }
+
+    async fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    async fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    async fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+}
+
+#[async_trait]
+impl StorageFile for fs::File {
+    async fn metadata(&self) -> io::Result<FileMetadata> {
+        FileMetadata::from_std(&fs::File::metadata(self).await?)
+    }
+
+    /// Setting the times is a single syscall, which is not worth a blocking task
+    async fn set_modified(&self, modified: SystemTime) -> io::Result<()> {
+        self.try_clone().await?.into_std().await.set_modified(modified)
+    }
+
+    async fn sync_all(&self) -> io::Result<()> {
+        fs::File::sync_all(self).await
+    }
+}
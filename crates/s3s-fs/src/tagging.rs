@@ -0,0 +1,61 @@
+use s3s::dto::Tag;
+use s3s::{s3_error, S3Result};
+
+use std::collections::BTreeMap;
+use std::ops::Not;
+
+/// The tags of an object, stored as a sidecar file
+pub(crate) type TagMap = BTreeMap<String, String>;
+
+const MAX_TAGS: usize = 10;
+const MAX_TAG_KEY_LEN: usize = 128;
+const MAX_TAG_VALUE_LEN: usize = 256;
+
+/// Validates a tag set and converts it to a tag map.
+pub(crate) fn tag_map_from_tags(tags: impl IntoIterator<Item = (String, String)>) -> S3Result<TagMap> {
+    let mut map = TagMap::new();
+    for (key, value) in tags {
+        if key.is_empty() {
+            return Err(s3_error!(InvalidTag, "The TagKey you have provided is invalid"));
+        }
+        if key.chars().count() > MAX_TAG_KEY_LEN {
+            return Err(s3_error!(InvalidTag, "The TagKey you have provided is too long, max {}", MAX_TAG_KEY_LEN));
+        }
+        if value.chars().count() > MAX_TAG_VALUE_LEN {
+            return Err(s3_error!(
+                InvalidTag,
+                "The TagValue you have provided is too long, max {}",
+                MAX_TAG_VALUE_LEN
+            ));
+        }
+        if map.insert(key, value).is_some() {
+            return Err(s3_error!(InvalidTag, "Cannot provide multiple Tags with the same key"));
+        }
+        if map.len() > MAX_TAGS {
+            return Err(s3_error!(InvalidTag, "Object tags cannot be greater than {}", MAX_TAGS));
+        }
+    }
+    Ok(map)
+}
+
+/// Parses the `x-amz-tagging` header, which is encoded as URL query parameters.
+pub(crate) fn parse_tagging_header(header: &str) -> S3Result<TagMap> {
+    let decode = |s: &str| {
+        let s = s.replace('+', " ");
+        match urlencoding::decode(&s) {
+            Ok(s) => Ok(s.into_owned()),
+            Err(e) => Err(s3_error!(e, InvalidArgument, "The x-amz-tagging header is not URL encoded")),
+        }
+    };
+
+    let mut tags = Vec::new();
+    for pair in header.split('&').filter(|s| s.is_empty().not()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        tags.push((decode(key)?, decode(value)?));
+    }
+    tag_map_from_tags(tags)
+}
+
+pub(crate) fn tags_from_tag_map(map: TagMap) -> Vec<Tag> {
+    map.into_iter().map(|(key, value)| Tag { key, value }).collect()
+}
@@ -1,7 +1,12 @@
 use crate::error::*;
+use crate::storage::FileMetadata;
 
+use s3s::s3_error;
+use s3s::S3Error;
 use s3s::StdError;
 
+use std::time::{Duration, SystemTime};
+
 use tokio::io::AsyncWrite;
 use tokio::io::AsyncWriteExt;
 
@@ -28,6 +33,31 @@ where
     Ok(nwritten)
 }
 
+/// Fails the stream with `EntityTooLarge` once it yields more than `limit` bytes in total
+pub fn limit_size<S>(stream: S, limit: Option<u64>) -> impl Stream<Item = Result<Bytes, StdError>>
+where
+    S: Stream<Item = Result<Bytes, StdError>>,
+{
+    let limit = limit.unwrap_or(u64::MAX);
+    let mut total: u64 = 0;
+    stream.map(move |result| {
+        let bytes = result?;
+        total = total.saturating_add(bytes.len() as u64);
+        if total > limit {
+            return Err(entity_too_large(limit).into());
+        }
+        Ok(bytes)
+    })
+}
+
+pub fn entity_too_large(limit: u64) -> S3Error {
+    s3_error!(
+        EntityTooLarge,
+        "Your proposed upload exceeds the maximum allowed size of {} bytes.",
+        limit
+    )
+}
+
 pub fn bytes_stream<S, E>(stream: S, content_length: usize) -> impl Stream<Item = Result<Bytes, E>> + Send + 'static
 where
     S: Stream<Item = Result<Bytes, E>> + Send + 'static,
@@ -36,7 +66,9 @@ where
     AsyncTryStream::<Bytes, E, _>::new(|mut y| async move {
         pin_mut!(stream);
         let mut remaining: usize = content_length;
-        while let Some(result) = stream.next().await {
+        // the stream is not read beyond the content, such as the rest of the file after a range
+        while remaining > 0 {
+            let Some(result) = stream.next().await else { break };
             let mut bytes = result?;
             if bytes.len() > remaining {
                 bytes.truncate(remaining);
@@ -51,3 +83,13 @@ where
 pub fn hex(input: impl AsRef<[u8]>) -> String {
     hex_simd::encode_to_string(input.as_ref(), hex_simd::AsciiCase::Lower)
 }
+
+/// Returns the modification time of a file, truncated to seconds.
+///
+/// The `Last-Modified` header has a precision of seconds,
+/// so the listings report the same value as `HeadObject`.
+pub fn modified_time(metadata: &FileMetadata) -> SystemTime {
+    let modified = metadata.modified;
+    let Ok(since_epoch) = modified.duration_since(SystemTime::UNIX_EPOCH) else { return modified };
+    SystemTime::UNIX_EPOCH + Duration::from_secs(since_epoch.as_secs())
+}
@@ -0,0 +1,401 @@
+use crate::error::*;
+use crate::fs::{load_json, object_sidecar_path, save_json};
+use crate::fs::{FileSystem, InternalInfo};
+use crate::storage::Storage;
+use crate::tagging::TagMap;
+use crate::utils::modified_time;
+
+use s3s::dto;
+use s3s::dto::BucketVersioningStatus;
+
+use std::io;
+use std::ops::Not;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The version id of objects written while versioning is not enabled
+pub(crate) const NULL_VERSION_ID: &str = "null";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VersioningState {
+    Unversioned,
+    Enabled,
+    Suspended,
+}
+
+/// An entry of the version index of an object, ordered from oldest to latest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct VersionEntry {
+    pub(crate) version_id: String,
+    #[serde(default)]
+    pub(crate) delete_marker: bool,
+    pub(crate) last_modified: SystemTime,
+}
+
+/// A version of an object in `ListObjectVersions`
+#[derive(Debug)]
+pub(crate) struct ListedVersion {
+    pub(crate) version_id: String,
+    pub(crate) is_latest: bool,
+    pub(crate) last_modified: SystemTime,
+    /// The files of the version, which is `None` for a delete marker
+    pub(crate) location: Option<VersionLocation>,
+}
+
+/// The files of an object version
+#[derive(Debug)]
+pub(crate) struct VersionLocation {
+    storage: Arc<dyn Storage>,
+    pub(crate) data: PathBuf,
+    pub(crate) metadata: PathBuf,
+    pub(crate) internal_info: PathBuf,
+    pub(crate) tagging: PathBuf,
+}
+
+pub(crate) enum VersionLookup {
+    Found(VersionLocation),
+    DeleteMarker,
+    NotFound,
+}
+
+impl VersionLocation {
+    pub(crate) async fn load_metadata(&self) -> Result<Option<dto::Metadata>> {
+        load_json(&*self.storage, &self.metadata).await
+    }
+
+    /// `None` removes the sidecar file
+    pub(crate) async fn save_metadata(&self, metadata: Option<&dto::Metadata>) -> Result<()> {
+        match metadata {
+            Some(metadata) => save_json(&*self.storage, &self.metadata, metadata).await,
+            None => remove_if_exists(&*self.storage, &self.metadata).await,
+        }
+    }
+
+    pub(crate) async fn load_internal_info(&self) -> Result<Option<InternalInfo>> {
+        load_json(&*self.storage, &self.internal_info).await
+    }
+
+    pub(crate) async fn save_internal_info(&self, info: &InternalInfo) -> Result<()> {
+        save_json(&*self.storage, &self.internal_info, info).await
+    }
+
+    pub(crate) async fn load_tagging(&self) -> Result<TagMap> {
+        Ok(load_json(&*self.storage, &self.tagging).await?.unwrap_or_default())
+    }
+
+    /// an empty tag map removes the sidecar file
+    pub(crate) async fn save_tagging(&self, tagging: &TagMap) -> Result<()> {
+        if tagging.is_empty() {
+            return remove_if_exists(&*self.storage, &self.tagging).await;
+        }
+        save_json(&*self.storage, &self.tagging, tagging).await
+    }
+
+    async fn move_to(&self, dst: &VersionLocation) -> Result<()> {
+        if let Some(dir) = dst.data.parent() {
+            self.storage.create_dir_all(dir).await?;
+        }
+        // the sidecar files of long keys are in their own directories, see `object_sidecar_path`
+        if let Some(dir) = dst.metadata.parent() {
+            self.storage.create_dir_all(dir).await?;
+        }
+        rename_if_exists(&*self.storage, &self.data, &dst.data).await?;
+        rename_if_exists(&*self.storage, &self.metadata, &dst.metadata).await?;
+        rename_if_exists(&*self.storage, &self.internal_info, &dst.internal_info).await?;
+        rename_if_exists(&*self.storage, &self.tagging, &dst.tagging).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn remove(&self) -> Result<()> {
+        remove_if_exists(&*self.storage, &self.data).await?;
+        remove_if_exists(&*self.storage, &self.metadata).await?;
+        remove_if_exists(&*self.storage, &self.internal_info).await?;
+        remove_if_exists(&*self.storage, &self.tagging).await?;
+        Ok(())
+    }
+}
+
+async fn rename_if_exists(storage: &dyn Storage, src: &Path, dst: &Path) -> Result<()> {
+    match storage.rename(src, dst).await {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+async fn remove_if_exists(storage: &dyn Storage, path: &Path) -> Result<()> {
+    match storage.remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+impl FileSystem {
+    pub(crate) async fn get_versioning_state(&self, bucket: &str) -> Result<VersioningState> {
+        let config = self.load_bucket_config(bucket).await?;
+        let status = config
+            .as_ref()
+            .and_then(|c| c.get("versioning_status"))
+            .and_then(|v| v.as_str());
+        let state = match status {
+            Some(BucketVersioningStatus::ENABLED) => VersioningState::Enabled,
+            Some(BucketVersioningStatus::SUSPENDED) => VersioningState::Suspended,
+            _ => VersioningState::Unversioned,
+        };
+        Ok(state)
+    }
+
+    /// resolve the version store of an object under the metadata directory (custom format)
+    fn get_versions_dir(&self, bucket: &str, key: &str) -> Result<PathBuf> {
+        self.resolve_meta_path(object_sidecar_path(bucket, key, ".versions"))
+    }
+
+    pub(crate) fn current_location(&self, bucket: &str, key: &str) -> Result<VersionLocation> {
+        Ok(VersionLocation {
+            storage: Arc::clone(&self.storage),
+            data: self.get_object_path(bucket, key)?,
+            metadata: self.get_metadata_path(bucket, key)?,
+            internal_info: self.get_internal_info_path(bucket, key)?,
+            tagging: self.get_tagging_path(bucket, key)?,
+        })
+    }
+
+    fn archived_location(&self, bucket: &str, key: &str, version_id: &str) -> Result<VersionLocation> {
+        let dir = self.get_versions_dir(bucket, key)?;
+        Ok(VersionLocation {
+            storage: Arc::clone(&self.storage),
+            data: dir.join(version_id),
+            metadata: dir.join(format!("{version_id}.metadata.json")),
+            internal_info: dir.join(format!("{version_id}.internal.json")),
+            tagging: dir.join(format!("{version_id}.tagging.json")),
+        })
+    }
+
+    pub(crate) async fn load_version_index(&self, bucket: &str, key: &str) -> Result<Vec<VersionEntry>> {
+        let path = self.get_versions_dir(bucket, key)?.join("index.json");
+        Ok(load_json(&*self.storage, &path).await?.unwrap_or_default())
+    }
+
+    async fn save_version_index(&self, bucket: &str, key: &str, index: &[VersionEntry]) -> Result<()> {
+        let dir = self.get_versions_dir(bucket, key)?;
+        if index.is_empty() {
+            if self.storage.exists(&dir).await {
+                self.storage.remove_dir_all(&dir).await?;
+            }
+            return Ok(());
+        }
+        self.storage.create_dir_all(&dir).await?;
+        save_json(&*self.storage, &dir.join("index.json"), index).await
+    }
+
+    /// Returns the version id of the current object, or `None` if there is no current object.
+    ///
+    /// Objects written before versioning was enabled have the "null" version id.
+    pub(crate) async fn get_current_version_id(&self, bucket: &str, key: &str) -> Result<Option<String>> {
+        let location = self.current_location(bucket, key)?;
+        match self.storage.metadata(&location.data).await {
+            Ok(m) if m.is_file() => {}
+            Ok(_) => return Ok(None),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        let info = self.load_internal_info(bucket, key).await?;
+        let version_id = info.as_ref().and_then(|i| i.get("version_id")).and_then(|v| v.as_str());
+        Ok(Some(version_id.unwrap_or(NULL_VERSION_ID).to_owned()))
+    }
+
+    /// Finds the files of an object version. `None` means the current version.
+    pub(crate) async fn locate_version(&self, bucket: &str, key: &str, version_id: Option<&str>) -> Result<VersionLookup> {
+        let Some(version_id) = version_id else {
+            return Ok(VersionLookup::Found(self.current_location(bucket, key)?));
+        };
+
+        if self.get_current_version_id(bucket, key).await?.as_deref() == Some(version_id) {
+            return Ok(VersionLookup::Found(self.current_location(bucket, key)?));
+        }
+
+        let index = self.load_version_index(bucket, key).await?;
+        match index.iter().find(|e| e.version_id == version_id) {
+            Some(entry) if entry.delete_marker => Ok(VersionLookup::DeleteMarker),
+            Some(_) => Ok(VersionLookup::Found(self.archived_location(bucket, key, version_id)?)),
+            None => Ok(VersionLookup::NotFound),
+        }
+    }
+
+    /// Moves the current object out of the way so that it does not get overwritten.
+    ///
+    /// In a suspended bucket, a "null" current version is discarded instead,
+    /// because the next "null" version replaces it.
+    async fn retire_current_version(
+        &self,
+        bucket: &str,
+        key: &str,
+        state: VersioningState,
+        index: &mut Vec<VersionEntry>,
+    ) -> Result<()> {
+        if state == VersioningState::Suspended {
+            index.retain(|e| e.version_id != NULL_VERSION_ID);
+            self.archived_location(bucket, key, NULL_VERSION_ID)?.remove().await?;
+        }
+
+        let Some(version_id) = self.get_current_version_id(bucket, key).await? else { return Ok(()) };
+        let current = self.current_location(bucket, key)?;
+
+        if state == VersioningState::Suspended && version_id == NULL_VERSION_ID {
+            return current.remove().await;
+        }
+
+        if index.iter().any(|e| e.version_id == version_id).not() {
+            let last_modified = modified_time(&self.storage.metadata(&current.data).await?);
+            index.push(VersionEntry {
+                version_id: version_id.clone(),
+                delete_marker: false,
+                last_modified,
+            });
+        }
+
+        current.move_to(&self.archived_location(bucket, key, &version_id)?).await
+    }
+
+    fn new_version_id(state: VersioningState) -> String {
+        match state {
+            VersioningState::Enabled => Uuid::new_v4().to_string(),
+            VersioningState::Unversioned | VersioningState::Suspended => NULL_VERSION_ID.to_owned(),
+        }
+    }
+
+    /// Prepares the version store before a new current object is written.
+    ///
+    /// Returns the version id of the new object, or `None` if the bucket is unversioned.
+    pub(crate) async fn prepare_version_write(&self, bucket: &str, key: &str) -> Result<Option<String>> {
+        let state = self.get_versioning_state(bucket).await?;
+        if state == VersioningState::Unversioned {
+            return Ok(None);
+        }
+
+        let mut index = self.load_version_index(bucket, key).await?;
+        self.retire_current_version(bucket, key, state, &mut index).await?;
+
+        let version_id = Self::new_version_id(state);
+        index.push(VersionEntry {
+            version_id: version_id.clone(),
+            delete_marker: false,
+            last_modified: SystemTime::now(),
+        });
+        self.save_version_index(bucket, key, &index).await?;
+
+        Ok(Some(version_id))
+    }
+
+    /// Hides the current object behind a new delete marker.
+    ///
+    /// Returns the version id of the delete marker, or `None` if the bucket is unversioned.
+    pub(crate) async fn create_delete_marker(&self, bucket: &str, key: &str) -> Result<Option<String>> {
+        let state = self.get_versioning_state(bucket).await?;
+        if state == VersioningState::Unversioned {
+            return Ok(None);
+        }
+
+        let mut index = self.load_version_index(bucket, key).await?;
+        self.retire_current_version(bucket, key, state, &mut index).await?;
+
+        let version_id = Self::new_version_id(state);
+        index.push(VersionEntry {
+            version_id: version_id.clone(),
+            delete_marker: true,
+            last_modified: SystemTime::now(),
+        });
+        self.save_version_index(bucket, key, &index).await?;
+
+        Ok(Some(version_id))
+    }
+
+    /// Permanently deletes a specific version of an object.
+    ///
+    /// If the latest version is deleted, the previous version becomes the current object.
+    /// Returns `None` if the version does not exist, otherwise whether it was a delete marker.
+    pub(crate) async fn delete_version(&self, bucket: &str, key: &str, version_id: &str) -> Result<Option<bool>> {
+        let mut index = self.load_version_index(bucket, key).await?;
+        let current = self.current_location(bucket, key)?;
+
+        let is_delete_marker = if self.get_current_version_id(bucket, key).await?.as_deref() == Some(version_id) {
+            current.remove().await?;
+            index.retain(|e| e.version_id != version_id);
+            false
+        } else if let Some(pos) = index.iter().position(|e| e.version_id == version_id) {
+            let entry = index.remove(pos);
+            if entry.delete_marker.not() {
+                self.archived_location(bucket, key, version_id)?.remove().await?;
+            }
+            entry.delete_marker
+        } else {
+            return Ok(None);
+        };
+
+        if self.storage.exists(&current.data).await.not() {
+            if let Some(latest) = index.last() {
+                if latest.delete_marker.not() {
+                    let archived = self.archived_location(bucket, key, &latest.version_id)?;
+                    archived.move_to(&current).await?;
+                }
+            }
+        }
+
+        self.save_version_index(bucket, key, &index).await?;
+        Ok(Some(is_delete_marker))
+    }
+
+    /// Lists the keys which start with `prefix` and have a version store, including keys whose latest version
+    /// is a delete marker, in no particular order.
+    pub(crate) async fn list_versioned_keys(&self, bucket: &str, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        for sidecar in self.list_object_sidecars(Some(bucket)).await? {
+            if sidecar.entry.is_dir && sidecar.suffix == ".versions" && sidecar.key.starts_with(prefix) {
+                keys.push(sidecar.key);
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Lists the versions and delete markers of an object, from the latest to the oldest.
+    pub(crate) async fn list_versions(&self, bucket: &str, key: &str) -> Result<Vec<ListedVersion>> {
+        let mut index = self.load_version_index(bucket, key).await?;
+
+        // the current object is not in the index if it was written before versioning was enabled
+        let current_version_id = self.get_current_version_id(bucket, key).await?;
+        if let Some(ref version_id) = current_version_id {
+            if index.iter().any(|e| e.version_id == *version_id).not() {
+                let current = self.current_location(bucket, key)?;
+                let last_modified = modified_time(&self.storage.metadata(&current.data).await?);
+                index.push(VersionEntry {
+                    version_id: version_id.clone(),
+                    delete_marker: false,
+                    last_modified,
+                });
+            }
+        }
+
+        let mut versions = Vec::with_capacity(index.len());
+        for (i, entry) in index.into_iter().rev().enumerate() {
+            let location = if entry.delete_marker {
+                None
+            } else if current_version_id.as_deref() == Some(entry.version_id.as_str()) {
+                Some(self.current_location(bucket, key)?)
+            } else {
+                Some(self.archived_location(bucket, key, &entry.version_id)?)
+            };
+            versions.push(ListedVersion {
+                version_id: entry.version_id,
+                is_latest: i == 0,
+                last_modified: entry.last_modified,
+                location,
+            });
+        }
+        Ok(versions)
+    }
+}
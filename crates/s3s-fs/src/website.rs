@@ -0,0 +1,206 @@
+//! Bucket website configurations, stored as a sidecar file
+//!
+//! The configuration is stored and returned faithfully. Website requests are not served by `s3s-fs`.
+
+use crate::error::*;
+use crate::fs::{load_json, save_json, FileSystem};
+
+use s3s::dto::*;
+use s3s::{s3_error, S3Result};
+
+use std::io;
+use std::ops::Not;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_ROUTING_RULES: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRedirectAll {
+    host_name: String,
+    protocol: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredCondition {
+    http_error_code_returned_equals: Option<String>,
+    key_prefix_equals: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredRoutingRule {
+    condition: Option<StoredCondition>,
+    host_name: Option<String>,
+    http_redirect_code: Option<String>,
+    protocol: Option<String>,
+    replace_key_prefix_with: Option<String>,
+    replace_key_with: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredWebsite {
+    index_suffix: Option<String>,
+    error_key: Option<String>,
+    redirect_all_requests_to: Option<StoredRedirectAll>,
+    routing_rules: Option<Vec<StoredRoutingRule>>,
+}
+
+fn check_protocol(protocol: Option<&Protocol>) -> S3Result<()> {
+    match protocol.map(Protocol::as_str) {
+        None | Some(Protocol::HTTP | Protocol::HTTPS) => Ok(()),
+        Some(protocol) => Err(s3_error!(
+            InvalidArgument,
+            "Invalid protocol, protocol can be http or https. {}",
+            protocol
+        )),
+    }
+}
+
+fn check_routing_rule(rule: &RoutingRule) -> S3Result<()> {
+    if let Some(ref condition) = rule.condition {
+        if condition.http_error_code_returned_equals.is_none() && condition.key_prefix_equals.is_none() {
+            return Err(s3_error!(InvalidRequest, "Condition cannot be empty. To redirect all requests without a condition, the condition element shouldn't be present."));
+        }
+    }
+    let redirect = &rule.redirect;
+    if redirect.host_name.is_none()
+        && redirect.http_redirect_code.is_none()
+        && redirect.protocol.is_none()
+        && redirect.replace_key_prefix_with.is_none()
+        && redirect.replace_key_with.is_none()
+    {
+        return Err(s3_error!(InvalidRequest, "Redirect cannot be empty"));
+    }
+    if redirect.replace_key_prefix_with.is_some() && redirect.replace_key_with.is_some() {
+        return Err(s3_error!(
+            InvalidRequest,
+            "You can only define ReplaceKeyPrefix or ReplaceKey but not both."
+        ));
+    }
+    if let Some(ref code) = redirect.http_redirect_code {
+        if code.parse::<u16>().map_or(true, |code| (300..400).contains(&code).not()) {
+            return Err(s3_error!(InvalidRequest, "The provided HTTP redirect code ({}) is not valid.", code));
+        }
+    }
+    check_protocol(redirect.protocol.as_ref())
+}
+
+/// Validates a website configuration.
+///
+/// A configuration either redirects all requests or has an index document.
+pub(crate) fn check_website_configuration(config: &WebsiteConfiguration) -> S3Result<()> {
+    if let Some(ref redirect_all) = config.redirect_all_requests_to {
+        if config.index_document.is_some() || config.error_document.is_some() || config.routing_rules.is_some() {
+            return Err(s3_error!(
+                InvalidArgument,
+                "RedirectAllRequestsTo cannot be provided in conjunction with other Routing Rules."
+            ));
+        }
+        if redirect_all.host_name.is_empty() {
+            return Err(s3_error!(InvalidArgument, "The HostName of RedirectAllRequestsTo cannot be empty."));
+        }
+        return check_protocol(redirect_all.protocol.as_ref());
+    }
+
+    let Some(ref index_document) = config.index_document else {
+        return Err(s3_error!(
+            InvalidArgument,
+            "A value for IndexDocument Suffix must be provided if RedirectAllRequestsTo is empty"
+        ));
+    };
+    if index_document.suffix.is_empty() || index_document.suffix.contains('/') {
+        return Err(s3_error!(
+            InvalidArgument,
+            "The IndexDocument Suffix is not well formed: {}",
+            index_document.suffix
+        ));
+    }
+    if config.error_document.as_ref().is_some_and(|d| d.key.is_empty()) {
+        return Err(s3_error!(InvalidArgument, "The ErrorDocument Key cannot be empty."));
+    }
+
+    let rules = config.routing_rules.as_deref().unwrap_or_default();
+    if rules.len() > MAX_ROUTING_RULES {
+        return Err(s3_error!(
+            InvalidArgument,
+            "The website configuration must not contain more than {} routing rules",
+            MAX_ROUTING_RULES
+        ));
+    }
+    rules.iter().try_for_each(check_routing_rule)
+}
+
+impl FileSystem {
+    pub(crate) async fn load_bucket_website(&self, bucket: &str) -> Result<Option<WebsiteConfiguration>> {
+        let path = self.get_bucket_website_path(bucket)?;
+        let Some(website) = load_json::<StoredWebsite>(&*self.storage, &path).await? else {
+            return Ok(None);
+        };
+        let routing_rules = website.routing_rules.map(|rules| {
+            rules
+                .into_iter()
+                .map(|r| RoutingRule {
+                    condition: r.condition.map(|c| Condition {
+                        http_error_code_returned_equals: c.http_error_code_returned_equals,
+                        key_prefix_equals: c.key_prefix_equals,
+                    }),
+                    redirect: Redirect {
+                        host_name: r.host_name,
+                        http_redirect_code: r.http_redirect_code,
+                        protocol: r.protocol.map(Protocol::from),
+                        replace_key_prefix_with: r.replace_key_prefix_with,
+                        replace_key_with: r.replace_key_with,
+                    },
+                })
+                .collect()
+        });
+        Ok(Some(WebsiteConfiguration {
+            error_document: website.error_key.map(|key| ErrorDocument { key }),
+            index_document: website.index_suffix.map(|suffix| IndexDocument { suffix }),
+            redirect_all_requests_to: website.redirect_all_requests_to.map(|r| RedirectAllRequestsTo {
+                host_name: r.host_name,
+                protocol: r.protocol.map(Protocol::from),
+            }),
+            routing_rules,
+        }))
+    }
+
+    pub(crate) async fn save_bucket_website(&self, bucket: &str, config: WebsiteConfiguration) -> Result<()> {
+        let routing_rules = config.routing_rules.map(|rules| {
+            rules
+                .into_iter()
+                .map(|r| StoredRoutingRule {
+                    condition: r.condition.map(|c| StoredCondition {
+                        http_error_code_returned_equals: c.http_error_code_returned_equals,
+                        key_prefix_equals: c.key_prefix_equals,
+                    }),
+                    host_name: r.redirect.host_name,
+                    http_redirect_code: r.redirect.http_redirect_code,
+                    protocol: r.redirect.protocol.map(|p| p.as_str().to_owned()),
+                    replace_key_prefix_with: r.redirect.replace_key_prefix_with,
+                    replace_key_with: r.redirect.replace_key_with,
+                })
+                .collect()
+        });
+        let website = StoredWebsite {
+            index_suffix: config.index_document.map(|d| d.suffix),
+            error_key: config.error_document.map(|d| d.key),
+            redirect_all_requests_to: config.redirect_all_requests_to.map(|r| StoredRedirectAll {
+                host_name: r.host_name,
+                protocol: r.protocol.map(|p| p.as_str().to_owned()),
+            }),
+            routing_rules,
+        };
+        let path = self.get_bucket_website_path(bucket)?;
+        save_json(&*self.storage, &path, &website).await
+    }
+
+    pub(crate) async fn remove_bucket_website(&self, bucket: &str) -> Result<()> {
+        let path = self.get_bucket_website_path(bucket)?;
+        match self.storage.remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
@@ -5,24 +5,93 @@
 )]
 
 use s3s::auth::SimpleAuth;
-use s3s::service::S3ServiceBuilder;
+use s3s::service::{CorsPreflightDefault, S3ServiceBuilder};
 use s3s_fs::FileSystem;
+use s3s_fs::ListingOrder;
 
 use std::env;
 use std::fs;
+use std::ops::Not;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use aws_config::SdkConfig;
 use aws_credential_types::provider::SharedCredentialsProvider;
 use aws_sdk_s3::config::Credentials;
 use aws_sdk_s3::config::Region;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::get_bucket_acl::GetBucketAclOutput;
+use aws_sdk_s3::operation::select_object_content::SelectObjectContentError;
 use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::primitives::DateTime;
 use aws_sdk_s3::Client;
 
+use aws_sdk_s3::types::AbortIncompleteMultipartUpload;
+use aws_sdk_s3::types::AccessControlPolicy;
+use aws_sdk_s3::types::BucketCannedAcl;
+use aws_sdk_s3::types::BucketLifecycleConfiguration;
 use aws_sdk_s3::types::BucketLocationConstraint;
+use aws_sdk_s3::types::BucketVersioningStatus;
+use aws_sdk_s3::types::ChecksumAlgorithm;
 use aws_sdk_s3::types::ChecksumMode;
 use aws_sdk_s3::types::CompletedMultipartUpload;
 use aws_sdk_s3::types::CompletedPart;
+use aws_sdk_s3::types::Condition;
+use aws_sdk_s3::types::CorsConfiguration;
+use aws_sdk_s3::types::CorsRule;
 use aws_sdk_s3::types::CreateBucketConfiguration;
+use aws_sdk_s3::types::CsvInput;
+use aws_sdk_s3::types::CsvOutput;
+use aws_sdk_s3::types::Delete;
+use aws_sdk_s3::types::EncodingType;
+use aws_sdk_s3::types::ErrorDocument;
+use aws_sdk_s3::types::ExpirationStatus;
+use aws_sdk_s3::types::ExpressionType;
+use aws_sdk_s3::types::FileHeaderInfo;
+use aws_sdk_s3::types::Grant;
+use aws_sdk_s3::types::Grantee;
+use aws_sdk_s3::types::IndexDocument;
+use aws_sdk_s3::types::InputSerialization;
+use aws_sdk_s3::types::JsonInput;
+use aws_sdk_s3::types::JsonOutput;
+use aws_sdk_s3::types::JsonType;
+use aws_sdk_s3::types::LifecycleExpiration;
+use aws_sdk_s3::types::LifecycleRule;
+use aws_sdk_s3::types::LifecycleRuleFilter;
+use aws_sdk_s3::types::MetadataDirective;
+use aws_sdk_s3::types::ObjectAttributes;
+use aws_sdk_s3::types::ObjectCannedAcl;
+use aws_sdk_s3::types::ObjectIdentifier;
+use aws_sdk_s3::types::ObjectLockConfiguration;
+use aws_sdk_s3::types::ObjectLockEnabled;
+use aws_sdk_s3::types::ObjectLockLegalHold;
+use aws_sdk_s3::types::ObjectLockLegalHoldStatus;
+use aws_sdk_s3::types::ObjectLockMode;
+use aws_sdk_s3::types::ObjectLockRetention;
+use aws_sdk_s3::types::ObjectLockRetentionMode;
+use aws_sdk_s3::types::ObjectOwnership;
+use aws_sdk_s3::types::OutputSerialization;
+use aws_sdk_s3::types::Owner;
+use aws_sdk_s3::types::OwnershipControls;
+use aws_sdk_s3::types::OwnershipControlsRule;
+use aws_sdk_s3::types::Payer;
+use aws_sdk_s3::types::Permission;
+use aws_sdk_s3::types::Protocol;
+use aws_sdk_s3::types::Redirect;
+use aws_sdk_s3::types::RedirectAllRequestsTo;
+use aws_sdk_s3::types::RequestCharged;
+use aws_sdk_s3::types::RequestPayer;
+use aws_sdk_s3::types::RequestPaymentConfiguration;
+use aws_sdk_s3::types::RestoreRequest;
+use aws_sdk_s3::types::RoutingRule;
+use aws_sdk_s3::types::SelectObjectContentEventStream;
+use aws_sdk_s3::types::StorageClass;
+use aws_sdk_s3::types::Tag;
+use aws_sdk_s3::types::Tagging;
+use aws_sdk_s3::types::Type;
+use aws_sdk_s3::types::VersioningConfiguration;
+use aws_sdk_s3::types::WebsiteConfiguration;
 
 use anyhow::Result;
 use once_cell::sync::Lazy;
@@ -58,7 +127,8 @@ fn config() -> &'static SdkConfig {
 
         // Setup S3 provider
         fs::create_dir_all(FS_ROOT).unwrap();
-        let fs = FileSystem::new(FS_ROOT).unwrap();
+        let mut fs = FileSystem::new(FS_ROOT).unwrap();
+        fs.set_min_part_size(0); // the tests upload small parts
 
         // Setup S3 service
         let service = {
@@ -82,6 +152,24 @@ fn config() -> &'static SdkConfig {
     &CONFIG
 }
 
+/// Creates a client of a file system which is not shared with other tests
+fn local_client(fs: FileSystem) -> Client {
+    let cred = Credentials::for_tests();
+    let service = {
+        let mut b = S3ServiceBuilder::new(fs);
+        b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+        b.build()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred)
+        .http_connector(s3s_aws::Connector::from(service.into_shared()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    Client::from_conf(conf)
+}
+
 async fn serial() -> MutexGuard<'static, ()> {
     static LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
     LOCK.lock().await
@@ -111,6 +199,10 @@ async fn delete_bucket(c: &Client, bucket: &str) -> Result<()> {
     Ok(())
 }
 
+fn http_status<E>(err: &SdkError<E>) -> Option<u16> {
+    err.raw_response().map(|res| res.status().as_u16())
+}
+
 macro_rules! log_and_unwrap {
     ($result:expr) => {
         match $result {
@@ -198,6 +290,210 @@ async fn test_list_objects_v2() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_common_prefixes() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-list-common-prefixes-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    let keys = [
+        "photos/2023/a.jpg",
+        "photos/2023/b.jpg",
+        "photos/2024/c.jpg",
+        "photos/top.jpg",
+        "readme.txt",
+        "with space/x.txt",
+    ];
+    for key in keys {
+        let body = ByteStream::from_static(b"data");
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    {
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, keys);
+        assert!(ans.common_prefixes().is_none());
+    }
+
+    {
+        let ans = c.list_objects_v2().bucket(bucket).delimiter("/").send().await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(contents, ["readme.txt"]);
+        assert_eq!(prefixes, ["photos/", "with space/"]);
+        assert_eq!(ans.key_count(), 3);
+    }
+
+    {
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix("photos/")
+            .delimiter("/")
+            .send()
+            .await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(contents, ["photos/top.jpg"]);
+        assert_eq!(prefixes, ["photos/2023/", "photos/2024/"]);
+    }
+
+    {
+        let ans = c
+            .list_objects()
+            .bucket(bucket)
+            .prefix("photos/20")
+            .delimiter("/")
+            .send()
+            .await?;
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(prefixes, ["photos/2023/", "photos/2024/"]);
+        assert!(ans.contents().unwrap_or_default().is_empty());
+    }
+
+    {
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .delimiter("/")
+            .encoding_type(EncodingType::Url)
+            .send()
+            .await?;
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(prefixes, ["photos/", "with%20space/"]);
+
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix("with space/")
+            .encoding_type(EncodingType::Url)
+            .send()
+            .await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, ["with%20space/x.txt"]);
+        assert_eq!(ans.prefix(), Some("with%20space/"));
+    }
+
+    {
+        for key in keys {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_objects_pagination() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-list-pagination-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    let keys = ["e.txt", "b/2.txt", "a.txt", "d/x/y.txt", "b/1.txt", "c.txt"];
+    for key in keys {
+        let body = ByteStream::from_static(b"data");
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    {
+        let mut listed = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let ans = c
+                .list_objects_v2()
+                .bucket(bucket)
+                .max_keys(2)
+                .set_continuation_token(token.clone())
+                .send()
+                .await?;
+            assert!(ans.key_count() <= 2);
+            assert_eq!(ans.max_keys(), 2);
+            listed.extend(ans.contents().unwrap().iter().map(|o| o.key().unwrap().to_owned()));
+            if ans.is_truncated().not() {
+                assert!(ans.next_continuation_token().is_none());
+                break;
+            }
+            token = ans.next_continuation_token().map(ToOwned::to_owned);
+            assert!(token.is_some());
+        }
+        assert_eq!(listed, ["a.txt", "b/1.txt", "b/2.txt", "c.txt", "d/x/y.txt", "e.txt"]);
+    }
+
+    {
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .start_after("b/1.txt")
+            .max_keys(2)
+            .send()
+            .await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, ["b/2.txt", "c.txt"]);
+        assert!(ans.is_truncated());
+    }
+
+    {
+        let ans = c.list_objects_v2().bucket(bucket).delimiter("/").max_keys(2).send().await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(contents, ["a.txt"]);
+        assert_eq!(prefixes, ["b/"]);
+        assert!(ans.is_truncated());
+
+        let token = ans.next_continuation_token().unwrap();
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .delimiter("/")
+            .continuation_token(token)
+            .send()
+            .await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().filter_map(|p| p.prefix()).collect();
+        assert_eq!(contents, ["c.txt", "e.txt"]);
+        assert_eq!(prefixes, ["d/"]);
+        assert!(ans.is_truncated().not());
+    }
+
+    {
+        let ans = c.list_objects().bucket(bucket).max_keys(3).send().await?;
+        assert!(ans.is_truncated());
+        assert_eq!(ans.next_marker(), Some("b/2.txt"));
+
+        let ans = c.list_objects().bucket(bucket).marker("b/2.txt").send().await?;
+        let contents: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(contents, ["c.txt", "d/x/y.txt", "e.txt"]);
+        assert!(ans.is_truncated().not());
+    }
+
+    {
+        let result = c.list_objects_v2().bucket(bucket).continuation_token("!!!").send().await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        for key in keys {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 #[tracing::instrument]
 async fn test_single_object() -> Result<()> {
@@ -251,71 +547,64 @@ async fn test_single_object() -> Result<()> {
 
 #[tokio::test]
 #[tracing::instrument]
-async fn test_multipart() -> Result<()> {
+async fn test_delete_objects() -> Result<()> {
     let _guard = serial().await;
 
     let c = Client::new(config());
-
-    let bucket = format!("test-multipart-{}", Uuid::new_v4());
+    let bucket = format!("test-delete-objects-{}", Uuid::new_v4());
     let bucket = bucket.as_str();
-    create_bucket(&c, bucket).await?;
-
-    let key = "sample.txt";
-    let content = "abcdefghijklmnopqrstuvwxyz/0123456789/!@#$%^&*();\n";
-
-    let upload_id = {
-        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
-        ans.upload_id.unwrap()
-    };
-    let upload_id = upload_id.as_str();
 
-    let upload_parts = {
-        let body = ByteStream::from_static(content.as_bytes());
-        let part_number = 1;
+    create_bucket(&c, bucket).await?;
 
-        let ans = c
-            .upload_part()
+    let put = |key: &'static str| {
+        c.put_object()
             .bucket(bucket)
             .key(key)
-            .upload_id(upload_id)
-            .body(body)
-            .part_number(part_number)
+            .body(ByteStream::from_static(b"data"))
+            .metadata("k", "v")
             .send()
-            .await?;
+    };
+    let object_id = |key: &str| ObjectIdentifier::builder().key(key).build();
 
-        let part = CompletedPart::builder()
-            .e_tag(ans.e_tag.unwrap_or_default())
-            .part_number(part_number)
+    {
+        put("a.txt").await?;
+        put("dir/b.txt").await?;
+
+        let delete = Delete::builder()
+            .objects(object_id("a.txt"))
+            .objects(object_id("dir/b.txt"))
+            .objects(object_id("missing.txt"))
+            .objects(ObjectIdentifier::builder().key("a.txt").version_id("no-such-version").build())
             .build();
+        let ans = c.delete_objects().bucket(bucket).delete(delete).send().await?;
 
-        vec![part]
-    };
+        let deleted: Vec<_> = ans.deleted().unwrap().iter().map(|d| d.key().unwrap()).collect();
+        assert_eq!(deleted, ["a.txt", "dir/b.txt", "missing.txt"]);
 
-    {
-        let upload = CompletedMultipartUpload::builder().set_parts(Some(upload_parts)).build();
+        let errors = ans.errors().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].key(), Some("a.txt"));
+        assert_eq!(errors[0].code(), Some("NoSuchVersion"));
 
-        let _ = c
-            .complete_multipart_upload()
-            .bucket(bucket)
-            .key(key)
-            .multipart_upload(upload)
-            .upload_id(upload_id)
-            .send()
-            .await?;
+        for key in ["a.txt", "dir/b.txt"] {
+            let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+            assert_eq!(http_status(&err), Some(404));
+        }
     }
 
     {
-        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        put("c.txt").await?;
 
-        let content_length: usize = ans.content_length().try_into().unwrap();
-        let body = ans.body.collect().await?.into_bytes();
+        let delete = Delete::builder().objects(object_id("c.txt")).quiet(true).build();
+        let ans = c.delete_objects().bucket(bucket).delete(delete).send().await?;
+        assert!(ans.deleted().unwrap_or_default().is_empty());
+        assert!(ans.errors().is_none());
 
-        assert_eq!(content_length, content.len());
-        assert_eq!(body.as_ref(), content.as_bytes());
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert!(ans.contents().unwrap_or_default().is_empty());
     }
 
     {
-        delete_object(&c, bucket, key).await?;
         delete_bucket(&c, bucket).await?;
     }
 
@@ -324,84 +613,7784 @@ async fn test_multipart() -> Result<()> {
 
 #[tokio::test]
 #[tracing::instrument]
-async fn test_upload_part_copy() -> Result<()> {
+async fn test_content_md5() -> Result<()> {
+    use md5::{Digest, Md5};
+
     let _guard = serial().await;
 
     let c = Client::new(config());
-    let src_bucket = format!("test-copy{}", Uuid::new_v4());
-    let src_bucket = src_bucket.as_str();
-    let src_key = "copied.txt";
-    let src_content = "hello world\nनमस्ते दुनिया\n";
-    let crc32c = base64_simd::STANDARD.encode_to_string(crc32c::crc32c(src_content.as_bytes()).to_be_bytes());
+    let bucket = format!("test-content-md5-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "checked.txt";
+    let content = b"hello content md5";
+    let content_md5 = base64_simd::STANDARD.encode_to_string(Md5::digest(content));
+    let other_md5 = base64_simd::STANDARD.encode_to_string(Md5::digest(b"something else"));
 
-    create_bucket(&c, src_bucket).await?;
+    create_bucket(&c, bucket).await?;
 
-    {
-        let src_body = ByteStream::from_static(src_content.as_bytes());
+    let put = |content_md5: &str| {
         c.put_object()
-            .bucket(src_bucket)
-            .key(src_key)
-            .body(src_body)
-            .checksum_crc32_c(crc32c.as_str())
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content))
+            .content_md5(content_md5)
+            .send()
+    };
+
+    {
+        let err = put(&other_md5).await.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("BadDigest"));
+
+        let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+
+        let err = put("not-an-md5").await.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidDigest"));
+
+        put(&content_md5).await?;
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), content);
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key("multipart.txt").send().await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let result = c
+            .upload_part()
+            .bucket(bucket)
+            .key("multipart.txt")
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(content))
+            .content_md5(&other_md5)
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("BadDigest"));
+
+        let ans = c
+            .list_parts()
+            .bucket(bucket)
+            .key("multipart.txt")
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        assert!(ans.parts().unwrap_or_default().is_empty());
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key("multipart.txt")
+            .upload_id(upload_id)
             .send()
             .await?;
     }
 
-    let bucket = format!("test-uploadpartcopy-{}", Uuid::new_v4());
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_checksums() -> Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-checksums-{}", Uuid::new_v4());
     let bucket = bucket.as_str();
+    let key = "checked.txt";
+
+    let crc32 = |data: &[u8]| crc32fast::hash(data).to_be_bytes();
+    let b64 = |data: &[u8]| base64_simd::STANDARD.encode_to_string(data);
+
     create_bucket(&c, bucket).await?;
 
-    let key = "sample.txt";
+    {
+        let content = b"object with a checksum";
+        let sha256 = b64(&Sha256::digest(content));
 
-    let upload_id = {
-        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
-        ans.upload_id.unwrap()
-    };
-    let upload_id = upload_id.as_str();
-    let src_path = format!("{src_bucket}/{src_key}");
-    let upload_parts = {
-        let part_number = 1;
-        let _ans = c
-            .upload_part_copy()
+        let result = c
+            .put_object()
             .bucket(bucket)
             .key(key)
-            .copy_source(src_path)
-            .upload_id(upload_id)
-            .part_number(part_number)
+            .body(ByteStream::from_static(content))
+            .checksum_sha256(b64(&Sha256::digest(b"other")))
             .send()
-            .await?;
-        let part = CompletedPart::builder().part_number(part_number).build();
-        vec![part]
-    };
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("BadDigest"));
 
-    {
-        let upload = CompletedMultipartUpload::builder().set_parts(Some(upload_parts)).build();
+        let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
 
-        let _ = c
-            .complete_multipart_upload()
+        let ans = c
+            .put_object()
             .bucket(bucket)
             .key(key)
-            .multipart_upload(upload)
-            .upload_id(upload_id)
+            .body(ByteStream::from_static(content))
+            .checksum_sha256(&sha256)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_sha256(), Some(sha256.as_str()));
+
+        let ans = c
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .checksum_mode(ChecksumMode::Enabled)
             .send()
             .await?;
+        assert_eq!(ans.checksum_sha256(), Some(sha256.as_str()));
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert!(ans.checksum_sha256().is_none());
     }
 
     {
-        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let mp_key = "multipart.txt";
+        let ans = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .checksum_algorithm(ChecksumAlgorithm::Crc32)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_algorithm(), Some(&ChecksumAlgorithm::Crc32));
+        let upload_id = ans.upload_id().unwrap();
 
-        let content_length: usize = ans.content_length().try_into().unwrap();
-        let body = ans.body.collect().await?.into_bytes();
+        let result = c
+            .upload_part()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(b"part"))
+            .checksum_sha256(b64(&Sha256::digest(b"part")))
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidRequest"));
 
-        assert_eq!(content_length, src_content.len());
+        let part_contents: [&'static [u8]; 2] = [b"first part", b"second part"];
+        let mut part_crcs = Vec::new();
+        let mut completed_parts = Vec::new();
+        for (part_number, content) in (1..).zip(part_contents) {
+            let part_crc = crc32(content);
+            let ans = c
+                .upload_part()
+                .bucket(bucket)
+                .key(mp_key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from_static(content))
+                .checksum_crc32(b64(&part_crc))
+                .send()
+                .await?;
+            assert_eq!(ans.checksum_crc32(), Some(b64(&part_crc).as_str()));
+            completed_parts.push(
+                CompletedPart::builder()
+                    .e_tag(ans.e_tag().unwrap())
+                    .checksum_crc32(b64(&part_crc))
+                    .part_number(part_number)
+                    .build(),
+            );
+            part_crcs.extend_from_slice(&part_crc);
+        }
+
+        let ans = c.list_parts().bucket(bucket).key(mp_key).upload_id(upload_id).send().await?;
+        assert_eq!(ans.checksum_algorithm(), Some(&ChecksumAlgorithm::Crc32));
+        assert_eq!(ans.parts().unwrap()[1].checksum_crc32(), Some(b64(&crc32(b"second part")).as_str()));
+
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        let composite = format!("{}-2", b64(&crc32(&part_crcs)));
+        assert_eq!(ans.checksum_crc32(), Some(composite.as_str()));
+
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(mp_key)
+            .checksum_mode(ChecksumMode::Enabled)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_crc32(), Some(composite.as_str()));
+
+        delete_object(&c, bucket, mp_key).await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_trailing_checksums() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-trailing-checksums-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "trailing.txt";
+
+    let content = b"object with a trailing checksum".repeat(100);
+    let crc32 = base64_simd::STANDARD.encode_to_string(crc32fast::hash(&content).to_be_bytes());
+
+    // the sdk sends a file body as aws-chunked with a trailing checksum
+    let file_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{bucket}.txt"));
+    fs::write(&file_path, &content)?;
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let body = ByteStream::read_from().path(&file_path).buffer_size(1024).build().await?;
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .checksum_algorithm(ChecksumAlgorithm::Crc32)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_crc32(), Some(crc32.as_str()));
+
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .checksum_mode(ChecksumMode::Enabled)
+            .send()
+            .await?;
+        assert_eq!(ans.checksum_crc32(), Some(crc32.as_str()));
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), content.as_slice());
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_file(&file_path)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_request_ids() -> Result<()> {
+    use aws_sdk_s3::operation::{RequestId, RequestIdExt};
+
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-request-ids-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "sample.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let first = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        let second = c.head_object().bucket(bucket).key(key).send().await?;
+
+        for request_id in [first.request_id(), second.request_id()] {
+            assert_eq!(request_id.map(str::len), Some(16));
+        }
+        assert_ne!(first.request_id(), second.request_id());
+        assert!(first.extended_request_id().is_some_and(|id| id.is_empty().not()));
+        assert_ne!(first.extended_request_id(), second.extended_request_id());
+    }
+
+    {
+        let err = c.get_object().bucket(bucket).key("missing").send().await.unwrap_err();
+        let err = err.into_service_error();
+        assert_eq!(err.meta().code(), Some("NoSuchKey"));
+        assert_eq!(err.request_id().map(str::len), Some(16));
+        assert!(err.extended_request_id().is_some());
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_range_get() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-range-get-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "range.txt";
+    let content = "0123456789";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let body = ByteStream::from_static(content.as_bytes());
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    let cases = [
+        ("bytes=2-5", "2345", "bytes 2-5/10"),
+        ("bytes=7-", "789", "bytes 7-9/10"),
+        ("bytes=-3", "789", "bytes 7-9/10"),
+        ("bytes=8-100", "89", "bytes 8-9/10"),
+        ("bytes=-100", "0123456789", "bytes 0-9/10"),
+    ];
+
+    for (range, expected_body, expected_content_range) in cases {
+        let ans = c.get_object().bucket(bucket).key(key).range(range).send().await?;
+
+        let content_length: usize = ans.content_length().try_into().unwrap();
+        let content_range = ans.content_range().unwrap().to_owned();
+        let body = ans.body.collect().await?.into_bytes();
+
+        assert_eq!(content_length, expected_body.len());
+        assert_eq!(content_range, expected_content_range);
+        assert_eq!(body.as_ref(), expected_body.as_bytes());
+    }
+
+    {
+        let result = c.get_object().bucket(bucket).key(key).range("bytes=10-").send().await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidRange"));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_accept_ranges() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-accept-ranges-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    for (key, content) in [("full.txt", "0123456789"), ("empty.txt", "")] {
+        let body = ByteStream::from_static(content.as_bytes());
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(head.accept_ranges(), Some("bytes"));
+        assert_eq!(head.content_length(), content.len() as i64);
+
+        let get = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(get.accept_ranges(), Some("bytes"));
+        assert_eq!(get.content_length(), content.len() as i64);
+
+        delete_object(&c, bucket, key).await?;
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_no_such_key() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-no-such-key-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "dir/missing.txt";
+
+    create_bucket(&c, bucket).await?;
+    c.put_object()
+        .bucket(bucket)
+        .key("dir/present.txt")
+        .body(ByteStream::from_static(b"content"))
+        .send()
+        .await?;
+
+    // "dir" exists as a directory, which is not an object
+    for key in [key, "dir"] {
+        let err = c.get_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        let err = err.into_service_error();
+        assert_eq!(err.meta().code(), Some("NoSuchKey"));
+        assert!(err.meta().message().is_some_and(|msg| msg.contains(key)));
+
+        let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+    }
+
+    {
+        let err = c
+            .copy_object()
+            .bucket(bucket)
+            .key("copy.txt")
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchKey"));
+    }
+
+    delete_object(&c, bucket, "dir/present.txt").await?;
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_delete_object_if_match() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-delete-if-match-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "conditional.txt";
+
+    create_bucket(&c, bucket).await?;
+    c.put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from_static(b"hello"))
+        .send()
+        .await?;
+    let e_tag = c.head_object().bucket(bucket).key(key).send().await?.e_tag.unwrap();
+
+    let delete_if_match = |key: &'static str, if_match: String| {
+        let c = c.clone();
+        async move {
+            c.delete_object()
+                .bucket(bucket)
+                .key(key)
+                .customize()
+                .await
+                .unwrap()
+                .mutate_request(move |req| drop(req.headers_mut().insert("if-match", if_match.parse().unwrap())))
+                .send()
+                .await
+        }
+    };
+
+    {
+        let err = delete_if_match(key, "\"0123456789abcdef0123456789abcdef\"".to_owned())
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(412));
+        assert_eq!(err.into_service_error().meta().code(), Some("PreconditionFailed"));
+
+        // the object is kept
+        c.head_object().bucket(bucket).key(key).send().await?;
+    }
+
+    {
+        let err = delete_if_match("missing.txt", e_tag.clone()).await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+    }
+
+    {
+        delete_if_match(key, e_tag).await?;
+
+        let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_delete_bucket() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-delete-bucket-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "dir/nested.txt";
+
+    {
+        let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+    }
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let rule = CorsRule::builder().allowed_methods("GET").allowed_origins("*").build();
+        let cors = CorsConfiguration::builder().cors_rules(rule).build();
+        c.put_bucket_cors().bucket(bucket).cors_configuration(cors).send().await?;
+    }
+
+    {
+        let body = ByteStream::from_static(b"hello");
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+
+        let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("BucketNotEmpty"));
+
+        // the empty directory left by the object is not an object
+        delete_object(&c, bucket, key).await?;
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("BucketNotEmpty"));
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    {
+        let err = c.head_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+
+        // the configurations of the deleted bucket are removed
+        create_bucket(&c, bucket).await?;
+        let err = c.get_bucket_cors().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchCORSConfiguration"));
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_conditional_requests() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-conditional-requests-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "conditional.txt";
+    let missing_key = "missing.txt";
+    let content = "hello conditional";
+
+    create_bucket(&c, bucket).await?;
+
+    let put_if_absent = |key: &'static str| {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .customize()
+    };
+
+    {
+        let ans = put_if_absent(key)
+            .await?
+            .mutate_request(|req| drop(req.headers_mut().insert("if-none-match", "*".parse().unwrap())))
+            .send()
+            .await?;
+        assert!(ans.e_tag().is_some());
+    }
+
+    {
+        let result = put_if_absent(key)
+            .await?
+            .mutate_request(|req| drop(req.headers_mut().insert("if-none-match", "*".parse().unwrap())))
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(412));
+    }
+
+    let e_tag = c.head_object().bucket(bucket).key(key).send().await?.e_tag.unwrap();
+    let past = DateTime::from_secs(0);
+    let future = DateTime::from_secs(i64::from(u32::MAX));
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).if_match(&e_tag).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+
+        let result = c.get_object().bucket(bucket).key(key).if_match("\"mismatch\"").send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(412));
+
+        let result = c.get_object().bucket(bucket).key(key).if_none_match(&e_tag).send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(304));
+
+        c.get_object()
+            .bucket(bucket)
+            .key(key)
+            .if_none_match("\"mismatch\"")
+            .send()
+            .await?;
+
+        let result = c.get_object().bucket(bucket).key(key).if_modified_since(future).send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(304));
+
+        let result = c.get_object().bucket(bucket).key(key).if_unmodified_since(past).send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(412));
+    }
+
+    {
+        c.head_object().bucket(bucket).key(key).if_match(&e_tag).send().await?;
+
+        let result = c.head_object().bucket(bucket).key(key).if_match("\"mismatch\"").send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(412));
+
+        let result = c.head_object().bucket(bucket).key(key).if_none_match(&e_tag).send().await;
+        assert_eq!(http_status(&result.unwrap_err()), Some(304));
+
+        c.head_object().bucket(bucket).key(key).if_modified_since(past).send().await?;
+    }
+
+    {
+        let result = c.get_object().bucket(bucket).key(missing_key).if_match(&e_tag).send().await;
+        assert!(result.unwrap_err().into_service_error().is_no_such_key());
+
+        put_if_absent(missing_key)
+            .await?
+            .mutate_request(|req| drop(req.headers_mut().insert("if-none-match", "*".parse().unwrap())))
+            .send()
+            .await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_object(&c, bucket, missing_key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_copy_object() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-copy-object-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let src_key = "src/hello.txt";
+    let dst_key = "dst/hello world.txt";
+    let content = "hello copy";
+
+    create_bucket(&c, bucket).await?;
+
+    let put_e_tag = {
+        let body = ByteStream::from_static(content.as_bytes());
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(src_key)
+            .body(body)
+            .metadata("color", "red")
+            .send()
+            .await?;
+        ans.e_tag.unwrap()
+    };
+
+    {
+        let ans = c
+            .copy_object()
+            .bucket(bucket)
+            .key(dst_key)
+            .copy_source(format!("{bucket}/src/hello.txt"))
+            .send()
+            .await?;
+        let result = ans.copy_object_result.unwrap();
+        assert_eq!(result.e_tag(), Some(put_e_tag.as_str()));
+        assert!(result.last_modified().is_some());
+
+        let ans = c.get_object().bucket(bucket).key(dst_key).send().await?;
+        assert_eq!(ans.metadata().unwrap()["color"], "red");
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), content.as_bytes());
+    }
+
+    {
+        c.copy_object()
+            .bucket(bucket)
+            .key(dst_key)
+            .copy_source(format!("{bucket}/src/hello.txt"))
+            .metadata_directive(MetadataDirective::Replace)
+            .metadata("color", "blue")
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(dst_key).send().await?;
+        assert_eq!(ans.metadata().unwrap()["color"], "blue");
+    }
+
+    {
+        let result = c
+            .copy_object()
+            .bucket(bucket)
+            .key(src_key)
+            .copy_source(format!("{bucket}/src/hello.txt"))
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidRequest"));
+
+        c.copy_object()
+            .bucket(bucket)
+            .key(src_key)
+            .copy_source(format!("{bucket}/src/hello.txt"))
+            .metadata_directive(MetadataDirective::Replace)
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(src_key).send().await?;
+        assert!(ans.metadata().filter(|m| m.is_empty().not()).is_none());
+    }
+
+    {
+        let result = c
+            .copy_object()
+            .bucket(bucket)
+            .key(dst_key)
+            .copy_source(format!("{bucket}/missing.txt"))
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("NoSuchKey"));
+    }
+
+    {
+        delete_object(&c, bucket, src_key).await?;
+        delete_object(&c, bucket, dst_key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_object_tagging() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-object-tagging-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "tagged.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let get_tags = |key: &'static str| c.get_object_tagging().bucket(bucket).key(key).send();
+    let tag_pairs = |tags: &[Tag]| -> Vec<(String, String)> {
+        tags.iter()
+            .map(|t| (t.key().unwrap().to_owned(), t.value().unwrap().to_owned()))
+            .collect()
+    };
+
+    {
+        let body = ByteStream::from_static(b"tagged");
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .tagging("color=red&note=two%20words")
+            .send()
+            .await?;
+
+        let ans = get_tags(key).await?;
+        let expected = [
+            ("color".to_owned(), "red".to_owned()),
+            ("note".to_owned(), "two words".to_owned()),
+        ];
+        assert_eq!(tag_pairs(ans.tag_set().unwrap()), expected);
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.tag_count(), 2);
+    }
+
+    {
+        let tagging = Tagging::builder().tag_set(Tag::builder().key("k").value("v").build()).build();
+        c.put_object_tagging().bucket(bucket).key(key).tagging(tagging).send().await?;
+
+        let ans = get_tags(key).await?;
+        assert_eq!(tag_pairs(ans.tag_set().unwrap()), [("k".to_owned(), "v".to_owned())]);
+
+        c.delete_object_tagging().bucket(bucket).key(key).send().await?;
+
+        let ans = get_tags(key).await?;
+        assert!(ans.tag_set().unwrap_or_default().is_empty());
+    }
+
+    {
+        let too_many = (0..11).map(|i| Tag::builder().key(format!("k{i}")).value("v").build());
+        let tagging = Tagging::builder().set_tag_set(Some(too_many.collect())).build();
+        let result = c.put_object_tagging().bucket(bucket).key(key).tagging(tagging).send().await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidTag"));
+
+        let long_key = "k".repeat(129);
+        let tagging = Tagging::builder()
+            .tag_set(Tag::builder().key(long_key).value("v").build())
+            .build();
+        let result = c.put_object_tagging().bucket(bucket).key(key).tagging(tagging).send().await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidTag"));
+
+        let body = ByteStream::from_static(b"tagged");
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .tagging("a=1&a=2")
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidTag"));
+    }
+
+    {
+        let err = get_tags("missing.txt").await.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("NoSuchKey"));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_sse_customer_key() -> Result<()> {
+    use md5::{Digest, Md5};
+
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-sse-customer-key-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "secret.bin";
+
+    // spans a few encrypted chunks
+    let content: Vec<u8> = (0..200_000_u32).map(|i| (i % 251) as u8).collect();
+
+    let customer_key = |seed: u8| {
+        let raw = [seed; 32];
+        let key = base64_simd::STANDARD.encode_to_string(raw);
+        let key_md5 = base64_simd::STANDARD.encode_to_string(Md5::digest(raw));
+        (key, key_md5)
+    };
+    let (sse_key, sse_key_md5) = customer_key(1);
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(content.clone()))
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5(&sse_key_md5)
+            .send()
+            .await?;
+        assert_eq!(ans.sse_customer_key_md5(), Some(sse_key_md5.as_str()));
+
+        let stored = fs::read(format!("{FS_ROOT}/{bucket}/{key}"))?;
+        assert!(stored.windows(64).any(|w| w == &content[..64]).not());
+    }
+
+    {
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5(&sse_key_md5)
+            .send()
+            .await?;
+        assert_eq!(ans.content_length(), 200_000);
+        assert_eq!(ans.sse_customer_algorithm(), Some("AES256"));
+        let body = ans.body.collect().await?.into_bytes();
+        assert!(body.as_ref() == content.as_slice());
+
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .range("bytes=65530-131080")
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5(&sse_key_md5)
+            .send()
+            .await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert!(body.as_ref() == &content[65530..=131_080]);
+
+        let ans = c
+            .head_object()
+            .bucket(bucket)
+            .key(key)
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5(&sse_key_md5)
+            .send()
+            .await?;
+        assert_eq!(ans.content_length(), 200_000);
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert_eq!(ans.contents().unwrap()[0].size(), 200_000);
+    }
+
+    {
+        let result = c.get_object().bucket(bucket).key(key).send().await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidRequest"));
+
+        let (other_key, other_key_md5) = customer_key(2);
+        let result = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&other_key)
+            .sse_customer_key_md5(&other_key_md5)
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        let result = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5(&other_key_md5)
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+    }
+
+    {
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key("mismatch.bin")
+            .body(ByteStream::from_static(b"data"))
+            .sse_customer_algorithm("AES256")
+            .sse_customer_key(&sse_key)
+            .sse_customer_key_md5("AAAAAAAAAAAAAAAAAAAAAA==")
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_storage_class() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-storage-class-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let body = ByteStream::from_static(b"reduced");
+        c.put_object()
+            .bucket(bucket)
+            .key("reduced.txt")
+            .body(body)
+            .storage_class(StorageClass::ReducedRedundancy)
+            .send()
+            .await?;
+
+        let body = ByteStream::from_static(b"standard");
+        c.put_object().bucket(bucket).key("standard.txt").body(body).send().await?;
+
+        let ans = c.head_object().bucket(bucket).key("reduced.txt").send().await?;
+        assert_eq!(ans.storage_class(), Some(&StorageClass::ReducedRedundancy));
+
+        let ans = c.get_object().bucket(bucket).key("reduced.txt").send().await?;
+        assert_eq!(ans.storage_class(), Some(&StorageClass::ReducedRedundancy));
+
+        let ans = c.head_object().bucket(bucket).key("standard.txt").send().await?;
+        assert_eq!(ans.storage_class(), None);
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let classes: Vec<_> = ans
+            .contents()
+            .unwrap()
+            .iter()
+            .map(|o| (o.key().unwrap(), o.storage_class().unwrap().as_str()))
+            .collect();
+        assert_eq!(classes, [("reduced.txt", "REDUCED_REDUNDANCY"), ("standard.txt", "STANDARD")]);
+    }
+
+    {
+        let body = ByteStream::from_static(b"invalid");
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key("invalid.txt")
+            .body(body)
+            .storage_class(StorageClass::from("FOO"))
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidStorageClass"));
+    }
+
+    {
+        c.copy_object()
+            .bucket(bucket)
+            .key("standard.txt")
+            .copy_source(format!("{bucket}/standard.txt"))
+            .storage_class(StorageClass::StandardIa)
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key("standard.txt").send().await?;
+        assert_eq!(ans.storage_class(), Some(&StorageClass::StandardIa));
+    }
+
+    {
+        let key = "multipart.txt";
+        let ans = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .storage_class(StorageClass::OnezoneIa)
+            .send()
+            .await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let ans = c.list_multipart_uploads().bucket(bucket).send().await?;
+        let upload = &ans.uploads().unwrap()[0];
+        assert_eq!(upload.storage_class(), Some(&StorageClass::OnezoneIa));
+
+        let body = ByteStream::from_static(b"part");
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(body)
+            .send()
+            .await?;
+        let part = CompletedPart::builder().e_tag(ans.e_tag().unwrap()).part_number(1).build();
+
+        let ans = c.list_parts().bucket(bucket).key(key).upload_id(upload_id).send().await?;
+        assert_eq!(ans.storage_class(), Some(&StorageClass::OnezoneIa));
+
+        let upload = CompletedMultipartUpload::builder().parts(part).build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.storage_class(), Some(&StorageClass::OnezoneIa));
+    }
+
+    {
+        for key in ["reduced.txt", "standard.txt", "multipart.txt"] {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_last_modified() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-last-modified-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    let now_secs = || {
+        let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap();
+        i64::try_from(since_epoch.as_secs()).unwrap()
+    };
+    let pause = || tokio::time::sleep(std::time::Duration::from_millis(1100));
+
+    create_bucket(&c, bucket).await?;
+
+    let put_time = {
+        c.put_object()
+            .bucket(bucket)
+            .key("put.txt")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key("put.txt").send().await?;
+        let get = c.get_object().bucket(bucket).key("put.txt").send().await?;
+        let list = c.list_objects_v2().bucket(bucket).send().await?;
+
+        let last_modified = *head.last_modified().unwrap();
+        assert_eq!(last_modified.subsec_nanos(), 0);
+        assert_eq!(get.last_modified(), Some(&last_modified));
+        assert_eq!(list.contents().unwrap()[0].last_modified(), Some(&last_modified));
+        last_modified
+    };
+
+    {
+        let key = "multipart.txt";
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .body(ByteStream::from_static(b"hello"))
+            .part_number(1)
+            .send()
+            .await?;
+        let part = CompletedPart::builder().e_tag(ans.e_tag().unwrap()).part_number(1).build();
+
+        pause().await;
+        let completed_after = now_secs();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .multipart_upload(CompletedMultipartUpload::builder().parts(part).build())
+            .upload_id(upload_id)
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert!(head.last_modified().unwrap().secs() >= completed_after);
+    }
+
+    {
+        pause().await;
+        let ans = c
+            .copy_object()
+            .bucket(bucket)
+            .key("copy.txt")
+            .copy_source(format!("{bucket}/put.txt"))
+            .send()
+            .await?;
+        let copied = *ans.copy_object_result().unwrap().last_modified().unwrap();
+
+        let head = c.head_object().bucket(bucket).key("copy.txt").send().await?;
+        assert_eq!(head.last_modified(), Some(&copied));
+        assert!(copied.secs() > put_time.secs());
+    }
+
+    {
+        for key in ["put.txt", "multipart.txt", "copy.txt"] {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_object_metadata() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-object-metadata-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let key = "report.csv";
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .metadata("Color", "red")
+            .metadata("owner", "data team")
+            .content_type("text/csv")
+            .content_disposition("attachment; filename=\"report.csv\"")
+            .body(ByteStream::from_static(b"a,b\n1,2\n"))
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        let metadata = head.metadata().unwrap();
+        assert_eq!(metadata["color"], "red");
+        assert_eq!(metadata["owner"], "data team");
+        assert_eq!(head.content_type(), Some("text/csv"));
+        assert_eq!(head.content_disposition(), Some("attachment; filename=\"report.csv\""));
+
+        let get = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(get.metadata().unwrap()["color"], "red");
+        assert_eq!(get.content_type(), Some("text/csv"));
+        assert_eq!(get.content_disposition(), Some("attachment; filename=\"report.csv\""));
+
+        // an overwrite replaces everything of the previous object
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"plain"))
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert!(head.metadata().filter(|m| m.is_empty().not()).is_none());
+        assert_eq!(head.content_type(), Some("application/octet-stream"));
+        assert_eq!(head.content_disposition(), None);
+
+        delete_object(&c, bucket, key).await?;
+    }
+
+    {
+        let key = "multipart.json";
+        let ans = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .metadata("color", "blue")
+            .content_type("application/json")
+            .content_disposition("inline")
+            .send()
+            .await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(b"{}"))
+            .send()
+            .await?;
+        let part = CompletedPart::builder().e_tag(ans.e_tag().unwrap()).part_number(1).build();
+        let upload = CompletedMultipartUpload::builder().parts(part).build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+
+        let get = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(get.metadata().unwrap()["color"], "blue");
+        assert_eq!(get.content_type(), Some("application/json"));
+        assert_eq!(get.content_disposition(), Some("inline"));
+
+        // the content headers are copied with the metadata
+        c.copy_object()
+            .bucket(bucket)
+            .key("copy.json")
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await?;
+        let head = c.head_object().bucket(bucket).key("copy.json").send().await?;
+        assert_eq!(head.metadata().unwrap()["color"], "blue");
+        assert_eq!(head.content_type(), Some("application/json"));
+
+        c.copy_object()
+            .bucket(bucket)
+            .key("copy.json")
+            .copy_source(format!("{bucket}/{key}"))
+            .metadata_directive(MetadataDirective::Replace)
+            .content_type("text/plain")
+            .send()
+            .await?;
+        let head = c.head_object().bucket(bucket).key("copy.json").send().await?;
+        assert!(head.metadata().filter(|m| m.is_empty().not()).is_none());
+        assert_eq!(head.content_type(), Some("text/plain"));
+        assert_eq!(head.content_disposition(), None);
+
+        delete_object(&c, bucket, key).await?;
+        delete_object(&c, bucket, "copy.json").await?;
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_response_overrides() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-response-overrides-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "data.bin";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .content_type("text/plain")
+            .content_disposition("inline")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+    }
+
+    {
+        let expires = DateTime::from_secs(1_700_000_000);
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .response_content_type("application/x-download")
+            .response_content_disposition("attachment; filename=\"hello.txt\"")
+            .response_cache_control("no-cache")
+            .response_content_language("en-US")
+            .response_expires(expires)
+            .send()
+            .await?;
+        assert_eq!(ans.content_type(), Some("application/x-download"));
+        assert_eq!(ans.content_disposition(), Some("attachment; filename=\"hello.txt\""));
+        assert_eq!(ans.cache_control(), Some("no-cache"));
+        assert_eq!(ans.content_language(), Some("en-US"));
+        assert_eq!(ans.expires(), Some(&expires));
+    }
+
+    {
+        // the stored headers are not changed
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.content_type(), Some("text/plain"));
+        assert_eq!(ans.content_disposition(), Some("inline"));
+        assert_eq!(ans.cache_control(), None);
+    }
+
+    {
+        let result = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .response_content_type("not a type")
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_multipart() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+
+    let bucket = format!("test-multipart-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    create_bucket(&c, bucket).await?;
+
+    let key = "sample.txt";
+    let content = "abcdefghijklmnopqrstuvwxyz/0123456789/!@#$%^&*();\n";
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id.unwrap()
+    };
+    let upload_id = upload_id.as_str();
+
+    let upload_parts = {
+        let body = ByteStream::from_static(content.as_bytes());
+        let part_number = 1;
+
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .body(body)
+            .part_number(part_number)
+            .send()
+            .await?;
+
+        let part = CompletedPart::builder()
+            .e_tag(ans.e_tag.unwrap_or_default())
+            .part_number(part_number)
+            .build();
+
+        vec![part]
+    };
+
+    {
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(upload_parts)).build();
+
+        let _ = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .multipart_upload(upload)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+
+        let content_length: usize = ans.content_length().try_into().unwrap();
+        let body = ans.body.collect().await?.into_bytes();
+
+        assert_eq!(content_length, content.len());
+        assert_eq!(body.as_ref(), content.as_bytes());
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_multipart_e_tag() -> Result<()> {
+    use md5::{Digest, Md5};
+
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-multipart-e-tag-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "three-parts.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let part_contents: [&'static [u8]; 3] = [b"part one", b"part two", b"part three"];
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id.unwrap()
+    };
+    let upload_id = upload_id.as_str();
+
+    let mut completed_parts = Vec::new();
+    let mut md5_of_md5s = Md5::new();
+    for (part_number, content) in (1..).zip(part_contents) {
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from_static(content))
+            .send()
+            .await?;
+        let part_md5 = Md5::digest(content);
+        assert_eq!(
+            ans.e_tag(),
+            Some(format!("\"{}\"", hex_simd::encode_to_string(part_md5, hex_simd::AsciiCase::Lower)).as_str())
+        );
+        md5_of_md5s.update(part_md5);
+
+        let part = CompletedPart::builder()
+            .e_tag(ans.e_tag().unwrap())
+            .part_number(part_number)
+            .build();
+        completed_parts.push(part);
+    }
+
+    let expected = format!("\"{}-3\"", hex_simd::encode_to_string(md5_of_md5s.finalize(), hex_simd::AsciiCase::Lower));
+
+    {
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .multipart_upload(upload)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(expected.as_str()));
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(expected.as_str()));
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(expected.as_str()));
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), part_contents.concat().as_slice());
+    }
+
+    {
+        let copy_key = "copied.txt";
+        c.copy_object()
+            .bucket(bucket)
+            .key(copy_key)
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(copy_key).send().await?;
+        let plain_md5 = hex_simd::encode_to_string(Md5::digest(part_contents.concat()), hex_simd::AsciiCase::Lower);
+        assert_eq!(ans.e_tag(), Some(format!("\"{plain_md5}\"").as_str()));
+
+        delete_object(&c, bucket, copy_key).await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_parts() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+
+    let bucket = format!("test-list-parts-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    create_bucket(&c, bucket).await?;
+
+    let key = "sample.txt";
+    let contents = ["part one", "part two!", "part three"];
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id.unwrap()
+    };
+    let upload_id = upload_id.as_str();
+
+    let mut e_tags = Vec::new();
+    for (part_number, content) in (1..).zip(contents) {
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .part_number(part_number)
+            .send()
+            .await?;
+        e_tags.push(ans.e_tag.unwrap());
+    }
+
+    {
+        let ans = c
+            .list_parts()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .max_parts(2)
+            .send()
+            .await?;
+        assert!(ans.is_truncated());
+        assert_eq!(ans.next_part_number_marker(), Some("2"));
+
+        let parts = ans.parts().unwrap();
+        assert_eq!(parts.len(), 2);
+        for (part, (content, e_tag)) in parts.iter().zip(contents.iter().zip(&e_tags)) {
+            assert_eq!(part.size(), i64::try_from(content.len())?);
+            assert_eq!(part.e_tag(), Some(e_tag.as_str()));
+            assert!(part.last_modified().is_some());
+        }
+
+        let ans = c
+            .list_parts()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .max_parts(2)
+            .part_number_marker("2")
+            .send()
+            .await?;
+        assert!(ans.is_truncated().not());
+        assert_eq!(ans.next_part_number_marker(), None);
+
+        let parts = ans.parts().unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].part_number(), 3);
+        assert_eq!(parts[0].e_tag(), Some(e_tags[2].as_str()));
+    }
+
+    {
+        let missing_upload_id = Uuid::new_v4().to_string();
+        let result = c
+            .list_parts()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(missing_upload_id)
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("NoSuchUpload"));
+    }
+
+    {
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_multipart_uploads() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+
+    let bucket = format!("test-list-uploads-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    create_bucket(&c, bucket).await?;
+
+    let keys = ["a/1.txt", "a/2.txt", "b.txt", "b.txt", "c.txt"];
+    let mut uploads = Vec::new();
+    for key in keys {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        uploads.push((key, ans.upload_id.unwrap()));
+    }
+
+    {
+        let ans = c.list_multipart_uploads().bucket(bucket).send().await?;
+        assert!(ans.is_truncated().not());
+
+        let listed = ans.uploads().unwrap();
+        let listed_keys: Vec<_> = listed.iter().map(|u| u.key().unwrap()).collect();
+        assert_eq!(listed_keys, keys);
+        for upload in listed {
+            assert!(uploads.iter().any(|(_, id)| Some(id.as_str()) == upload.upload_id()));
+            assert!(upload.initiated().is_some());
+            assert!(upload.initiator().is_some());
+        }
+    }
+
+    {
+        let ans = c.list_multipart_uploads().bucket(bucket).delimiter("/").send().await?;
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().map(|p| p.prefix().unwrap()).collect();
+        assert_eq!(prefixes, ["a/"]);
+        let listed_keys: Vec<_> = ans.uploads().unwrap().iter().map(|u| u.key().unwrap()).collect();
+        assert_eq!(listed_keys, ["b.txt", "b.txt", "c.txt"]);
+
+        let ans = c.list_multipart_uploads().bucket(bucket).prefix("a/").send().await?;
+        let listed_keys: Vec<_> = ans.uploads().unwrap().iter().map(|u| u.key().unwrap()).collect();
+        assert_eq!(listed_keys, ["a/1.txt", "a/2.txt"]);
+    }
+
+    {
+        let mut listed = Vec::new();
+        let mut key_marker = None;
+        let mut upload_id_marker = None;
+        loop {
+            let ans = c
+                .list_multipart_uploads()
+                .bucket(bucket)
+                .max_uploads(2)
+                .set_key_marker(key_marker)
+                .set_upload_id_marker(upload_id_marker)
+                .send()
+                .await?;
+            let page = ans.uploads().unwrap();
+            assert!(page.len() <= 2);
+            listed.extend(
+                page.iter()
+                    .map(|u| (u.key().unwrap().to_owned(), u.upload_id().unwrap().to_owned())),
+            );
+            if ans.is_truncated().not() {
+                break;
+            }
+            key_marker = ans.next_key_marker.clone();
+            upload_id_marker = ans.next_upload_id_marker.clone();
+        }
+        assert_eq!(listed.len(), uploads.len());
+        for (key, upload_id) in &uploads {
+            assert!(listed.iter().any(|(k, id)| k == key && id == upload_id));
+        }
+    }
+
+    {
+        for (key, upload_id) in &uploads {
+            c.abort_multipart_upload()
+                .bucket(bucket)
+                .key(*key)
+                .upload_id(upload_id)
+                .send()
+                .await?;
+        }
+        let ans = c.list_multipart_uploads().bucket(bucket).send().await?;
+        assert!(ans.uploads().filter(|u| u.is_empty().not()).is_none());
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_upload_part_copy() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let src_bucket = format!("test-copy{}", Uuid::new_v4());
+    let src_bucket = src_bucket.as_str();
+    let src_key = "copied.txt";
+    let src_content = "hello world\nनमस्ते दुनिया\n";
+    let crc32c = base64_simd::STANDARD.encode_to_string(crc32c::crc32c(src_content.as_bytes()).to_be_bytes());
+
+    create_bucket(&c, src_bucket).await?;
+
+    {
+        let src_body = ByteStream::from_static(src_content.as_bytes());
+        c.put_object()
+            .bucket(src_bucket)
+            .key(src_key)
+            .body(src_body)
+            .checksum_crc32_c(crc32c.as_str())
+            .send()
+            .await?;
+    }
+
+    let bucket = format!("test-uploadpartcopy-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    create_bucket(&c, bucket).await?;
+
+    let key = "sample.txt";
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id.unwrap()
+    };
+    let upload_id = upload_id.as_str();
+    let src_path = format!("{src_bucket}/{src_key}");
+    let upload_parts = {
+        let part_number = 1;
+        let ans = c
+            .upload_part_copy()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(src_path)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .send()
+            .await?;
+        let e_tag = ans.copy_part_result().and_then(|r| r.e_tag()).unwrap();
+        let part = CompletedPart::builder().part_number(part_number).e_tag(e_tag).build();
+        vec![part]
+    };
+
+    {
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(upload_parts)).build();
+
+        let _ = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .multipart_upload(upload)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+
+        let content_length: usize = ans.content_length().try_into().unwrap();
+        let body = ans.body.collect().await?.into_bytes();
+
+        assert_eq!(content_length, src_content.len());
         assert_eq!(body.as_ref(), src_content.as_bytes());
     }
-    println!("{} CK3", key);
+    println!("{} CK3", key);
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        delete_object(&c, src_bucket, src_key).await?;
+        delete_bucket(&c, src_bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_upload_part_copy_range() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-uploadpartcopy-range-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let src_key = "source.txt";
+    let key = "assembled.txt";
+    let src_content = "0123456789";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let body = ByteStream::from_static(src_content.as_bytes());
+        c.put_object().bucket(bucket).key(src_key).body(body).send().await?;
+    }
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id.unwrap()
+    };
+    let upload_id = upload_id.as_str();
+    let src_path = format!("{bucket}/{src_key}");
+
+    for range in ["bytes=5-10", "bytes=6-5", "bytes=10-"] {
+        let result = c
+            .upload_part_copy()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(&src_path)
+            .copy_source_range(range)
+            .upload_id(upload_id)
+            .part_number(1)
+            .send()
+            .await;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("InvalidRange"), "{range}");
+    }
+
+    let mut upload_parts = Vec::new();
+    for (part_number, range) in [(1, "bytes=7-9"), (2, "bytes=0-2")] {
+        let ans = c
+            .upload_part_copy()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(&src_path)
+            .copy_source_range(range)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .send()
+            .await?;
+        let e_tag = ans.copy_part_result.unwrap().e_tag.unwrap();
+        upload_parts.push(CompletedPart::builder().part_number(part_number).e_tag(e_tag).build());
+    }
+
+    {
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(upload_parts)).build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .multipart_upload(upload)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"789012");
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_object(&c, bucket, src_key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_versioning() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-bucket-versioning-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "sample.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ans = c.get_bucket_versioning().bucket(bucket).send().await?;
+        assert!(ans.status().is_none());
+    }
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        let ans = c.get_bucket_versioning().bucket(bucket).send().await?;
+        assert_eq!(ans.status(), Some(&BucketVersioningStatus::Enabled));
+    }
+
+    for content in ["version 1", "version 2"] {
+        let body = ByteStream::from_static(content.as_bytes());
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"version 2");
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        let result = c.get_object().bucket(bucket).key(key).send().await;
+        assert!(result.is_err());
+    }
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Suspended)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        let ans = c.get_bucket_versioning().bucket(bucket).send().await?;
+        assert_eq!(ans.status(), Some(&BucketVersioningStatus::Suspended));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_object_lock() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-object-lock-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    let put = |key: &'static str| c.put_object().bucket(bucket).key(key).body(ByteStream::from_static(b"data"));
+    let days_later = |days: u64| DateTime::from(std::time::SystemTime::now() + std::time::Duration::from_secs(days * 86400));
+    let lock_config = ObjectLockConfiguration::builder()
+        .object_lock_enabled(ObjectLockEnabled::Enabled)
+        .build();
+
+    {
+        let err = c
+            .put_object_lock_configuration()
+            .bucket(bucket)
+            .object_lock_configuration(lock_config.clone())
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidBucketState"));
+
+        let err = put("a.txt")
+            .object_lock_mode(ObjectLockMode::Governance)
+            .object_lock_retain_until_date(days_later(1))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidRequest"));
+    }
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        c.put_object_lock_configuration()
+            .bucket(bucket)
+            .object_lock_configuration(lock_config)
+            .send()
+            .await?;
+
+        let ans = c.get_object_lock_configuration().bucket(bucket).send().await?;
+        let cfg = ans.object_lock_configuration().unwrap();
+        assert_eq!(cfg.object_lock_enabled(), Some(&ObjectLockEnabled::Enabled));
+
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Suspended)
+            .build();
+        let err = c
+            .put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidBucketState"));
+    }
+
+    {
+        let key = "compliance.txt";
+        put(key)
+            .object_lock_mode(ObjectLockMode::Compliance)
+            .object_lock_retain_until_date(days_later(2))
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.object_lock_mode(), Some(&ObjectLockMode::Compliance));
+        assert!(ans.object_lock_retain_until_date().is_some());
+
+        let err = c.delete_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        let err = put(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        let err = c
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .bypass_governance_retention(true)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        let retention = |days: u64| {
+            ObjectLockRetention::builder()
+                .mode(ObjectLockRetentionMode::Compliance)
+                .retain_until_date(days_later(days))
+                .build()
+        };
+
+        let err = c
+            .put_object_retention()
+            .bucket(bucket)
+            .key(key)
+            .retention(retention(1))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        c.put_object_retention()
+            .bucket(bucket)
+            .key(key)
+            .retention(retention(3))
+            .send()
+            .await?;
+
+        let ans = c.get_object_retention().bucket(bucket).key(key).send().await?;
+        let retention = ans.retention().unwrap();
+        assert_eq!(retention.mode(), Some(&ObjectLockRetentionMode::Compliance));
+        let retain_until = retention.retain_until_date().unwrap().secs();
+        assert!(retain_until > days_later(2).secs());
+    }
+
+    {
+        let key = "governance.txt";
+        put(key).send().await?;
+
+        let err = c.get_object_retention().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchObjectLockConfiguration"));
+
+        let retention = ObjectLockRetention::builder()
+            .mode(ObjectLockRetentionMode::Governance)
+            .retain_until_date(days_later(1))
+            .build();
+        c.put_object_retention()
+            .bucket(bucket)
+            .key(key)
+            .retention(retention)
+            .send()
+            .await?;
+
+        let err = c.delete_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        c.delete_object()
+            .bucket(bucket)
+            .key(key)
+            .bypass_governance_retention(true)
+            .send()
+            .await?;
+    }
+
+    {
+        let key = "legal-hold.txt";
+        put(key).send().await?;
+
+        let hold = |status: ObjectLockLegalHoldStatus| ObjectLockLegalHold::builder().status(status).build();
+        c.put_object_legal_hold()
+            .bucket(bucket)
+            .key(key)
+            .legal_hold(hold(ObjectLockLegalHoldStatus::On))
+            .send()
+            .await?;
+
+        let ans = c.get_object_legal_hold().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.legal_hold().unwrap().status(), Some(&ObjectLockLegalHoldStatus::On));
+
+        let err = c
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .bypass_governance_retention(true)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        c.put_object_legal_hold()
+            .bucket(bucket)
+            .key(key)
+            .legal_hold(hold(ObjectLockLegalHoldStatus::Off))
+            .send()
+            .await?;
+
+        delete_object(&c, bucket, key).await?;
+    }
+
+    {
+        // The object under compliance retention can not be deleted
+        let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("BucketNotEmpty"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_policy() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-bucket-policy-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    for key in ["public.txt", "secret/a.txt"] {
+        let body = ByteStream::from_static(b"data");
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    {
+        let err = c.get_bucket_policy().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucketPolicy"));
+
+        for policy in ["not json", r#"{"Statement": []}"#, r#"{"Statement": {"Effect": "Maybe"}}"#] {
+            let err = c.put_bucket_policy().bucket(bucket).policy(policy).send().await.unwrap_err();
+            assert_eq!(err.into_service_error().meta().code(), Some("MalformedPolicy"));
+        }
+    }
+
+    let policy = format!(
+        r#"{{
+            "Version": "2012-10-17",
+            "Statement": [{{
+                "Effect": "Deny",
+                "Principal": "*",
+                "Action": ["s3:GetObject", "s3:DeleteObject"],
+                "Resource": "arn:aws:s3:::{bucket}/secret/*"
+            }}]
+        }}"#
+    );
+
+    {
+        c.put_bucket_policy().bucket(bucket).policy(&policy).send().await?;
+
+        let ans = c.get_bucket_policy().bucket(bucket).send().await?;
+        assert_eq!(ans.policy(), Some(policy.as_str()));
+
+        c.get_object().bucket(bucket).key("public.txt").send().await?;
+
+        let err = c.get_object().bucket(bucket).key("secret/a.txt").send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+
+        let err = c.delete_object().bucket(bucket).key("secret/a.txt").send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+    }
+
+    {
+        c.delete_bucket_policy().bucket(bucket).send().await?;
+        c.get_object().bucket(bucket).key("secret/a.txt").send().await?;
+    }
+
+    {
+        delete_object(&c, bucket, "public.txt").await?;
+        delete_object(&c, bucket, "secret/a.txt").await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_cors() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-bucket-cors-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let err = c.get_bucket_cors().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchCORSConfiguration"));
+    }
+
+    {
+        let rule = CorsRule::builder()
+            .allowed_origins("https://example.com")
+            .allowed_methods("GET")
+            .allowed_methods("PUT")
+            .allowed_headers("*")
+            .expose_headers("ETag")
+            .max_age_seconds(3000)
+            .build();
+        let cfg = CorsConfiguration::builder().cors_rules(rule).build();
+        c.put_bucket_cors().bucket(bucket).cors_configuration(cfg).send().await?;
+
+        let ans = c.get_bucket_cors().bucket(bucket).send().await?;
+        let rules = ans.cors_rules().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].allowed_methods(), Some(["GET".to_owned(), "PUT".to_owned()].as_slice()));
+        assert_eq!(rules[0].max_age_seconds(), 3000);
+    }
+
+    {
+        // browsers do not sign preflight requests
+        let service = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap()).build();
+        let request = |method: &str, origin: &str| {
+            hyper::Request::builder()
+                .method(method)
+                .uri(format!("http://localhost/{bucket}/a.txt"))
+                .header("origin", origin)
+                .header("access-control-request-method", "PUT")
+                .header("access-control-request-headers", "Content-Type")
+                .body(s3s::Body::empty())
+                .unwrap()
+        };
+
+        let res = service.call(request("OPTIONS", "https://example.com")).await?;
+        assert_eq!(res.status(), 200);
+        let headers = res.headers();
+        assert_eq!(headers["access-control-allow-origin"], "https://example.com");
+        assert_eq!(headers["access-control-allow-methods"], "GET, PUT");
+        assert_eq!(headers["access-control-allow-headers"], "content-type");
+        assert_eq!(headers["access-control-max-age"], "3000");
+
+        let res = service.call(request("OPTIONS", "https://other.example.com")).await?;
+        assert_eq!(res.status(), 403);
+
+        let res = service.call(request("GET", "https://example.com")).await?;
+        assert_eq!(res.status(), 404);
+        assert_eq!(res.headers()["access-control-expose-headers"], "ETag");
+    }
+
+    {
+        c.delete_bucket_cors().bucket(bucket).send().await?;
+
+        let err = c.get_bucket_cors().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchCORSConfiguration"));
+    }
+
+    {
+        let request = |method: &str| {
+            hyper::Request::builder()
+                .method(method)
+                .uri(format!("http://localhost/{bucket}/a.txt"))
+                .header("origin", "http://localhost:3000")
+                .header("access-control-request-method", "PUT")
+                .header("access-control-request-headers", "Content-Type")
+                .body(s3s::Body::empty())
+                .unwrap()
+        };
+
+        let service = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap()).build();
+        let res = service.call(request("OPTIONS")).await?;
+        assert_eq!(res.status(), 403);
+
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap());
+            b.set_cors_preflight_default(CorsPreflightDefault::AllowAll);
+            b.build()
+        };
+        let res = service.call(request("OPTIONS")).await?;
+        assert_eq!(res.status(), 200);
+        let headers = res.headers();
+        assert_eq!(headers["access-control-allow-origin"], "http://localhost:3000");
+        assert_eq!(headers["access-control-allow-methods"], "PUT");
+        assert_eq!(headers["access-control-allow-headers"], "content-type");
+
+        let res = service.call(request("GET")).await?;
+        assert_eq!(res.headers()["access-control-allow-origin"], "http://localhost:3000");
+
+        // the rules are looked up anonymously, and a failed lookup falls back to the default
+        let policy = format!(
+            r#"{{
+                "Version": "2012-10-17",
+                "Statement": [{{
+                    "Effect": "Deny",
+                    "Principal": "*",
+                    "Action": "s3:GetBucketCORS",
+                    "Resource": "arn:aws:s3:::{bucket}"
+                }}]
+            }}"#
+        );
+        c.put_bucket_policy().bucket(bucket).policy(&policy).send().await?;
+
+        let res = service.call(request("OPTIONS")).await?;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["access-control-allow-origin"], "http://localhost:3000");
+
+        c.delete_bucket_policy().bucket(bucket).send().await?;
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_location() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-location-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let bucket_east = format!("test-location-east-{}", Uuid::new_v4());
+    let bucket_east = bucket_east.as_str();
+
+    {
+        create_bucket(&c, bucket).await?;
+
+        let ans = c.get_bucket_location().bucket(bucket).send().await?;
+        assert_eq!(ans.location_constraint().map(BucketLocationConstraint::as_str), Some(REGION));
+    }
+
+    {
+        // buckets without a location constraint are in the default region
+        c.create_bucket().bucket(bucket_east).send().await?;
+
+        let ans = c.get_bucket_location().bucket(bucket_east).send().await?;
+        let location = ans.location_constraint().map(BucketLocationConstraint::as_str);
+        assert!(location.is_none() || location == Some(""), "{location:?}");
+    }
+
+    {
+        let err = c
+            .get_bucket_location()
+            .bucket("test-location-missing")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+        delete_bucket(&c, bucket_east).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_create_bucket_conflict() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-create-bucket-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    {
+        let before = DateTime::from(std::time::SystemTime::now()).secs();
+        create_bucket(&c, bucket).await?;
+
+        let err = c.create_bucket().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(409));
+        assert_eq!(err.into_service_error().meta().code(), Some("BucketAlreadyOwnedByYou"));
+
+        let ans = c.list_buckets().send().await?;
+        let created = ans.buckets().unwrap().iter().find(|b| b.name() == Some(bucket)).unwrap();
+        assert!(created.creation_date().unwrap().secs() >= before);
+    }
+
+    for name in ["Test-Invalid", "test_invalid", "test..invalid", "192.168.5.4", "ab"] {
+        let err = c.create_bucket().bucket(name).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(400), "{name}");
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidBucketName"), "{name}");
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_buckets_out_of_band() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-list-buckets-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let mut fs = FileSystem::new(&root).unwrap();
+    fs.enable_meta_dir().unwrap();
+    let c = local_client(fs);
+
+    {
+        create_bucket(&c, "test-created").await?;
+
+        // directories created by other programs are buckets too
+        fs::create_dir(Path::new(&root).join("test-out-of-band"))?;
+        fs::create_dir(Path::new(&root).join(".hidden"))?;
+        fs::write(Path::new(&root).join(".tmp.0.internal.part"), b"")?;
+
+        let ans = c.list_buckets().send().await?;
+        let names: Vec<_> = ans.buckets().unwrap().iter().map(|b| b.name().unwrap()).collect();
+        assert_eq!(names, ["test-created", "test-out-of-band"]);
+        assert!(ans.buckets().unwrap().iter().all(|b| b.creation_date().is_some()));
+
+        let owner = ans.owner().unwrap();
+        assert_eq!(owner.id(), Some(Credentials::for_tests().access_key_id()));
+    }
+
+    {
+        delete_bucket(&c, "test-created").await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_head_bucket() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-head-bucket-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let missing = format!("test-head-bucket-missing-{}", Uuid::new_v4());
+    let missing = missing.as_str();
+
+    {
+        create_bucket(&c, bucket).await?;
+        c.head_bucket().bucket(bucket).send().await?;
+    }
+
+    {
+        let err = c.head_bucket().bucket(missing).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+    }
+
+    {
+        let err = c.get_object().bucket(missing).key("a.txt").send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+
+        let err = c.delete_object().bucket(missing).key("a.txt").send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+
+        let err = c
+            .create_multipart_upload()
+            .bucket(missing)
+            .key("a.txt")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_put_object_missing_bucket() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-missing-bucket-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::new(&root).unwrap());
+    let bucket = "test-missing-bucket";
+
+    for key in ["sample.txt", "dir/sample.txt", "dir/"] {
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b""))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+    }
+    assert!(Path::new(&root).join(bucket).exists().not());
+
+    {
+        create_bucket(&c, bucket).await?;
+        c.put_object()
+            .bucket(bucket)
+            .key("dir/sample.txt")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        delete_object(&c, bucket, "dir/sample.txt").await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_public_bucket() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-public-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let mut auth = SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key());
+        auth.add_public_bucket("test-public");
+        let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+        b.set_auth(auth);
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred)
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+
+    let anonymous = |method: &str, path: &str| {
+        let req = hyper::Request::builder()
+            .method(method)
+            .uri(format!("http://{DOMAIN_NAME}{path}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let service = service.clone();
+        async move {
+            service
+                .as_ref()
+                .call(req)
+                .await
+                .map_or_else(|e| e.status_code(), |r| Some(r.status()))
+        }
+    };
+
+    {
+        for bucket in ["test-public", "test-private"] {
+            create_bucket(&c, bucket).await?;
+            c.put_object()
+                .bucket(bucket)
+                .key("hello.txt")
+                .body(ByteStream::from_static(b"hello"))
+                .send()
+                .await?;
+        }
+    }
+
+    {
+        let ok = Some(hyper::StatusCode::OK);
+        assert_eq!(anonymous("GET", "/test-public/hello.txt").await, ok);
+        assert_eq!(anonymous("HEAD", "/test-public/hello.txt").await, ok);
+        assert_eq!(anonymous("GET", "/test-public?list-type=2&prefix=h").await, ok);
+        assert_eq!(anonymous("GET", "/test-public").await, ok);
+
+        let denied = Some(hyper::StatusCode::FORBIDDEN);
+        assert_eq!(anonymous("PUT", "/test-public/new.txt").await, denied);
+        assert_eq!(anonymous("DELETE", "/test-public/hello.txt").await, denied);
+        assert_eq!(anonymous("GET", "/test-public?policy").await, denied);
+        assert_eq!(anonymous("GET", "/test-private/hello.txt").await, denied);
+        assert_eq!(anonymous("GET", "/").await, denied);
+
+        let bad_request = Some(hyper::StatusCode::BAD_REQUEST);
+        let path = "/test-public/hello.txt?response-content-type=text%2Fhtml";
+        assert_eq!(anonymous("GET", path).await, bad_request);
+    }
+
+    {
+        for bucket in ["test-public", "test-private"] {
+            delete_object(&c, bucket, "hello.txt").await?;
+            delete_bucket(&c, bucket).await?;
+        }
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_presigned_url() -> Result<()> {
+    use aws_sdk_s3::presigning::{PresignedRequest, PresigningConfig};
+    use std::time::{Duration, SystemTime};
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-presigned-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+        b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred)
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+    let bucket = "test-presigned";
+    let key = "hello.txt";
+
+    let send = |presigned: PresignedRequest, path: Option<&str>, body: &'static [u8]| {
+        let mut req = presigned
+            .to_http_request(s3s::Body::from(bytes::Bytes::from_static(body)))
+            .unwrap();
+        if let Some(path) = path {
+            let query = req.uri().query().unwrap();
+            *req.uri_mut() = format!("http://{DOMAIN_NAME}{path}?{query}").parse().unwrap();
+        }
+        req.headers_mut().insert("host", DOMAIN_NAME.parse().unwrap());
+        let service = service.clone();
+        async move {
+            service
+                .as_ref()
+                .call(req)
+                .await
+                .map_or_else(|e| e.status_code(), |r| Some(r.status()))
+        }
+    };
+    let expires_in = |start_time: SystemTime| {
+        PresigningConfig::builder()
+            .start_time(start_time)
+            .expires_in(Duration::from_secs(3600))
+            .build()
+            .unwrap()
+    };
+    let now = SystemTime::now();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ok = Some(hyper::StatusCode::OK);
+
+        let put = c.put_object().bucket(bucket).key(key).presigned(expires_in(now)).await?;
+        assert_eq!(send(put, None, b"hello").await, ok);
+
+        let get = c.get_object().bucket(bucket).key(key).presigned(expires_in(now)).await?;
+        assert_eq!(send(get, None, b"").await, ok);
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+    }
+
+    {
+        let denied = Some(hyper::StatusCode::FORBIDDEN);
+
+        let tampered = c.get_object().bucket(bucket).key(key).presigned(expires_in(now)).await?;
+        assert_eq!(send(tampered, Some("/test-presigned/other.txt"), b"").await, denied);
+
+        let expired = now - Duration::from_secs(7200);
+        let expired = c.get_object().bucket(bucket).key(key).presigned(expires_in(expired)).await?;
+        assert_eq!(send(expired, None, b"").await, denied);
+
+        let future = now + Duration::from_secs(3600);
+        let future = c.get_object().bucket(bucket).key(key).presigned(expires_in(future)).await?;
+        assert_eq!(send(future, None, b"").await, denied);
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_clock_skew() -> Result<()> {
+    use aws_smithy_async::time::StaticTimeSource;
+    use std::time::{Duration, SystemTime};
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-clock-skew-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let client = |clock_skew: Option<Duration>, behind: Duration| {
+        let cred = Credentials::for_tests();
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+            b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+            if let Some(clock_skew) = clock_skew {
+                b.set_clock_skew(clock_skew);
+            }
+            b.build()
+        };
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.into_shared()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .time_source(StaticTimeSource::new(SystemTime::now() - behind))
+            .build();
+        Client::from_conf(conf)
+    };
+
+    let minutes = |n: u64| Duration::from_secs(n * 60);
+
+    {
+        let c = client(None, minutes(10));
+        c.list_buckets().send().await?;
+
+        let c = client(None, minutes(60));
+        let err = c.list_buckets().send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+        let err = err.into_service_error();
+        assert_eq!(err.meta().code(), Some("RequestTimeTooSkewed"));
+        assert!(err.meta().message().unwrap().contains("ServerTime"));
+    }
+
+    {
+        let c = client(Some(minutes(1)), minutes(10));
+        let err = c.list_buckets().send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("RequestTimeTooSkewed"));
+
+        let c = client(Some(minutes(120)), minutes(60));
+        c.list_buckets().send().await?;
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_multiple_access_keys() -> Result<()> {
+    use s3s::auth::SecretKey;
+    use std::collections::HashMap;
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-multiple-keys-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let service = {
+        let keys: HashMap<String, SecretKey> = [("AKIDTENANT1", "secret1"), ("AKIDTENANT2", "secret2")]
+            .into_iter()
+            .map(|(ak, sk)| (ak.to_owned(), SecretKey::from(sk)))
+            .collect();
+        let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+        b.set_auth(SimpleAuth::from_map(keys));
+        b.build().into_shared()
+    };
+    let client = |access_key: &str, secret_key: &str| {
+        let cred = Credentials::new(access_key, secret_key, None, None, "test");
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.clone()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    };
+
+    {
+        client("AKIDTENANT1", "secret1").list_buckets().send().await?;
+        client("AKIDTENANT2", "secret2").list_buckets().send().await?;
+
+        let err = client("AKIDTENANT2", "secret1").list_buckets().send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("SignatureDoesNotMatch"));
+
+        let err = client("AKIDUNKNOWN", "secret1").list_buckets().send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidAccessKeyId"));
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_signature_debug() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-signature-debug-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let service = {
+        let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+        b.set_auth(SimpleAuth::from_single("AKIDDEBUG", "secret"));
+        b.set_signature_debug(true);
+        b.build().into_shared()
+    };
+    let client = |secret_key: &str| {
+        let cred = Credentials::new("AKIDDEBUG", secret_key, None, None, "test");
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.clone()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    };
+
+    {
+        client("secret").list_buckets().send().await?;
+
+        let err = client("wrong").list_buckets().send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+        assert_eq!(err.into_service_error().meta().code(), Some("SignatureDoesNotMatch"));
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_session_token() -> Result<()> {
+    use s3s::auth::{S3Auth, SecretKey};
+
+    /// Accepts temporary credentials with a single session token
+    struct SessionAuth;
+
+    #[async_trait::async_trait]
+    impl S3Auth for SessionAuth {
+        async fn get_secret_key(&self, _access_key: &str) -> s3s::S3Result<SecretKey> {
+            Err(s3s::s3_error!(InvalidAccessKeyId))
+        }
+
+        async fn get_secret_key_with_session_token(&self, access_key: &str, session_token: &str) -> s3s::S3Result<SecretKey> {
+            match (access_key, session_token) {
+                ("ASIATEMPORARY", "good-token") => Ok(SecretKey::from("temporary-secret")),
+                _ => Err(s3s::s3_error!(InvalidToken)),
+            }
+        }
+    }
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-session-token-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    fn client(root: &str, auth: impl S3Auth, session_token: &str) -> Client {
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(root).unwrap());
+            b.set_auth(auth);
+            b.build()
+        };
+        let cred = Credentials::new("ASIATEMPORARY", "temporary-secret", Some(session_token.to_owned()), None, "test");
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.into_shared()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    }
+
+    {
+        let c = client(&root, SessionAuth, "good-token");
+        c.list_buckets().send().await?;
+
+        let c = client(&root, SessionAuth, "bad-token");
+        let err = c.list_buckets().send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidToken"));
+    }
+
+    {
+        // session tokens are ignored unless the authentication provider validates them
+        let simple = SimpleAuth::from_single("ASIATEMPORARY", "temporary-secret");
+        let c = client(&root, simple, "any-token");
+        c.list_buckets().send().await?;
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_meta_dir() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-meta-dir-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let mut fs = FileSystem::new(&root).unwrap();
+    fs.enable_meta_dir().unwrap();
+    let c = local_client(fs);
+    let bucket = "test-meta-dir";
+    let key = "sample.txt";
+
+    {
+        create_bucket(&c, bucket).await?;
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .metadata("color", "red")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.metadata().unwrap()["color"], "red");
+    }
+
+    {
+        // only the bucket and the metadata directory are in the root
+        let mut names: Vec<String> = fs::read_dir(&root)?
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(names, [".s3s-meta", bucket]);
+
+        // sidecar files are written through temporary files which must not be left behind
+        let meta_dir = format!("{root}/.s3s-meta");
+        for entry in fs::read_dir(meta_dir)? {
+            let name = entry?.file_name().into_string().unwrap();
+            assert!(name.starts_with(".tmp.").not(), "{name}");
+        }
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_durable_writes() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-durable-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let mut fs = FileSystem::new(&root).unwrap();
+    fs.set_durable(true);
+    let c = local_client(fs);
+    let bucket = "test-durable";
+    let key = "dir/sample.txt";
+
+    {
+        create_bucket(&c, bucket).await?;
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_tmp_dir() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-tmp-dir-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    // a tmpfs is on another filesystem than the root, which exercises the cross-device fallback
+    let tmp_dir = if Path::new("/dev/shm").is_dir() {
+        format!("/dev/shm/s3s-fs-tests-tmp-{}", Uuid::new_v4())
+    } else {
+        format!("{root}-staging")
+    };
+
+    let mut fs = FileSystem::new(&root).unwrap();
+    fs.set_tmp_dir(&tmp_dir).unwrap();
+    let c = local_client(fs);
+    let bucket = "test-tmp-dir";
+    let key = "dir/sample.txt";
+
+    {
+        create_bucket(&c, bucket).await?;
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+
+        assert_eq!(fs::read_dir(&tmp_dir)?.count(), 0);
+        let staged = fs::read_dir(Path::new(&root).join(bucket).join("dir"))?
+            .filter(|e| e.as_ref().unwrap().file_name().to_string_lossy().starts_with(".tmp."))
+            .count();
+        assert_eq!(staged, 0);
+    }
+
+    {
+        // the copy of a cross-device write is staged in the metadata directory instead of the bucket,
+        // so that a copy left by a crash is never listed and is removed when the root is served again
+        let storage = CrossDeviceStorage {
+            tmp_dir: Path::new(&tmp_dir).canonicalize()?,
+            copies: Arc::default(),
+        };
+        let fs = FileSystem::builder(&root)
+            .tmp_dir(&tmp_dir)
+            .build_with_storage(storage.clone())
+            .await
+            .unwrap();
+        let c = local_client(fs);
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"copied"))
+            .send()
+            .await?;
+
+        let copies = storage.copies.lock().unwrap().clone();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].parent(), Some(Path::new(&root).canonicalize()?.as_path()));
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"copied");
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+
+    Ok(())
+}
+
+/// Delegates to the default storage, but fails the renames from the temporary directory like another filesystem
+#[derive(Debug, Clone)]
+struct CrossDeviceStorage {
+    tmp_dir: PathBuf,
+    copies: Arc<std::sync::Mutex<Vec<PathBuf>>>,
+}
+
+#[async_trait::async_trait]
+impl s3s_fs::Storage for CrossDeviceStorage {
+    async fn open(&self, path: &Path) -> std::io::Result<Box<dyn s3s_fs::StorageFile>> {
+        s3s_fs::TokioStorage.open(path).await
+    }
+
+    async fn create(&self, path: &Path) -> std::io::Result<Box<dyn s3s_fs::StorageFile>> {
+        s3s_fs::TokioStorage.create(path).await
+    }
+
+    async fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        s3s_fs::TokioStorage.read(path).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.write(path, content).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        self.copies.lock().unwrap().push(to.to_owned());
+        s3s_fs::TokioStorage.copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        if from.starts_with(&self.tmp_dir) {
+            return Err(std::io::ErrorKind::CrossesDevices.into());
+        }
+        s3s_fs::TokioStorage.rename(from, to).await
+    }
+
+    async fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.remove_file(path).await
+    }
+
+    async fn create_dir(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.create_dir(path).await
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.create_dir_all(path).await
+    }
+
+    async fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.remove_dir(path).await
+    }
+
+    async fn remove_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.remove_dir_all(path).await
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<s3s_fs::DirEntry>> {
+        s3s_fs::TokioStorage.read_dir(path).await
+    }
+
+    async fn metadata(&self, path: &Path) -> std::io::Result<s3s_fs::FileMetadata> {
+        s3s_fs::TokioStorage.metadata(path).await
+    }
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_path_traversal() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-path-traversal-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    for key in ["../../etc/passwd", "a/../../b", "a/./b", "nul\0key"] {
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"evil"))
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(http_status(&err), Some(400), "{key:?}");
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"), "{key:?}");
+
+        let err = c.get_object().bucket(bucket).key(key).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(400), "{key:?}");
+    }
+
+    {
+        let src = format!("{bucket}/../../etc/passwd");
+        let result = c.copy_object().bucket(bucket).key("copied").copy_source(src).send().await;
+        let err = result.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        // nothing is written outside of the bucket
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert!(ans.contents().unwrap_or_default().is_empty());
+        assert!(fs::metadata(format!("{FS_ROOT}/b")).is_err());
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_key_length() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-key-length-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::builder(&root).max_key_length(16).build().unwrap());
+    let bucket = "test-key-length";
+
+    create_bucket(&c, bucket).await?;
+
+    let put = |key: String| {
+        let c = c.clone();
+        async move {
+            let body = ByteStream::from_static(b"abc");
+            c.put_object().bucket(bucket).key(key).body(body).send().await
+        }
+    };
+
+    {
+        put("a".repeat(16)).await?;
+
+        // the length is in bytes
+        for key in ["a".repeat(17), "é".repeat(9)] {
+            let err = put(key.clone()).await.unwrap_err();
+            assert_eq!(http_status(&err), Some(400), "{key:?}");
+            assert_eq!(err.into_service_error().meta().code(), Some("KeyTooLongError"), "{key:?}");
+
+            let err = c.get_object().bucket(bucket).key(&key).send().await.unwrap_err();
+            assert_eq!(http_status(&err), Some(400), "{key:?}");
+        }
+    }
+
+    {
+        // the sidecar files of long keys are stored in nested directories
+        let fs = FileSystem::new(&root).unwrap();
+        let c = local_client(fs.clone());
+        let key = ["a", "b", "c", "d", "e"].map(|s| s.repeat(204)).join("/");
+        assert_eq!(key.len(), 1024);
+
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(
+                VersioningConfiguration::builder()
+                    .status(BucketVersioningStatus::Enabled)
+                    .build(),
+            )
+            .send()
+            .await?;
+
+        for content in ["first", "second"] {
+            c.put_object()
+                .bucket(bucket)
+                .key(&key)
+                .metadata("content", content)
+                .tagging("content=long")
+                .body(ByteStream::from_static(content.as_bytes()))
+                .send()
+                .await?;
+        }
+
+        let ans = c.get_object().bucket(bucket).key(&key).send().await?;
+        assert_eq!(ans.metadata().and_then(|m| m.get("content")).map(String::as_str), Some("second"));
+        assert_eq!(ans.body.collect().await?.into_bytes().as_ref(), b"second");
+
+        let ans = c.get_object_tagging().bucket(bucket).key(&key).send().await?;
+        assert_eq!(ans.tag_set().unwrap()[0].key(), Some("content"));
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let keys: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, ["a".repeat(16).as_str(), key.as_str()]);
+
+        let ans = c.list_object_versions().bucket(bucket).prefix("a").send().await?;
+        let versions: Vec<_> = ans.versions().unwrap().iter().filter_map(|v| v.key()).collect();
+        assert_eq!(versions, ["a".repeat(16).as_str(), key.as_str(), key.as_str()]);
+
+        assert_eq!(fs.gc_orphans().await.unwrap(), 0);
+        fs::remove_file(Path::new(&root).join(bucket).join(&key))?;
+        assert_eq!(fs.gc_orphans().await.unwrap(), 3);
+
+        let err = c.get_object().bucket(bucket).key(&key).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchKey"));
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_sidecar_collision() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    // short names, so that the sidecar files of the sidecar-like key do not exceed the file name limit
+    let bucket = "test-sidecar-collision";
+    let key = "a.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .metadata("color", "red")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+    }
+
+    let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+    let sidecar = format!(".bucket-{}.object-{}.metadata.json", encode(bucket), encode(key));
+
+    {
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(format!("../{sidecar}"))
+            .body(ByteStream::from_static(b"{\"color\":\"blue\"}"))
+            .send()
+            .await;
+        let err = result.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        // a key with the name of a sidecar file is an ordinary object
+        c.put_object()
+            .bucket(bucket)
+            .key(&sidecar)
+            .body(ByteStream::from_static(b"{\"color\":\"blue\"}"))
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.metadata().unwrap()["color"], "red");
+
+        let ans = c.get_object().bucket(bucket).key(&sidecar).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"{\"color\":\"blue\"}");
+    }
+
+    {
+        delete_object(&c, bucket, &sidecar).await?;
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_gc_orphans() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-gc-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::new(&root).unwrap());
+    let bucket = "test-gc-orphans";
+
+    {
+        create_bucket(&c, bucket).await?;
+        for key in ["deleted.txt", "kept.txt"] {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .metadata("color", "red")
+                .body(ByteStream::from_static(b"hello"))
+                .send()
+                .await?;
+        }
+    }
+
+    {
+        // delete an object by another program
+        fs::remove_file(format!("{root}/{bucket}/deleted.txt"))?;
+
+        // a file which looks like a sidecar file but can not be decoded
+        let undecodable = format!("{root}/.bucket-!.object-!.metadata.json");
+        fs::write(&undecodable, b"{}")?;
+
+        let count = FileSystem::new(&root).unwrap().gc_orphans().await.unwrap();
+        assert_eq!(count, 2); // metadata and internal info
+
+        assert!(fs::metadata(&undecodable).is_ok());
+        let ans = c.head_object().bucket(bucket).key("kept.txt").send().await?;
+        assert_eq!(ans.metadata().unwrap()["color"], "red");
+    }
+
+    {
+        delete_object(&c, bucket, "kept.txt").await?;
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_cached_e_tag() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-cached-e-tag-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "sample.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+    let info_path = format!("{FS_ROOT}/.bucket-{}.object-{}.internal.json", encode(bucket), encode(key));
+
+    let e_tag = {
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        ans.e_tag().unwrap().to_owned()
+    };
+
+    {
+        // the data is not hashed again
+        fs::write(format!("{FS_ROOT}/{bucket}/{key}"), b"HELLO")?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+    }
+
+    {
+        // a missing entity tag is computed and stored
+        let mut info: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&fs::read(&info_path)?)?;
+        info.remove("e_tag");
+        fs::write(&info_path, serde_json::to_vec(&info)?)?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        let new_e_tag = ans.e_tag().unwrap();
+        assert_ne!(new_e_tag, e_tag);
+
+        let info: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&fs::read(&info_path)?)?;
+        assert_eq!(info["e_tag"], new_e_tag);
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+/// Returns the peak resident set size of the process
+#[cfg(target_os = "linux")]
+fn peak_memory() -> u64 {
+    let status = fs::read_to_string("/proc/self/status").unwrap();
+    let line = status.lines().find(|line| line.starts_with("VmHWM:")).unwrap();
+    let kb: u64 = line.split_whitespace().nth(1).unwrap().parse().unwrap();
+    kb * 1024
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+#[tracing::instrument]
+async fn test_streaming_upload() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-streaming-upload-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "large.bin";
+
+    const SIZE: usize = 256 * 1024 * 1024;
+    const BUDGET: u64 = 64 * 1024 * 1024;
+
+    create_bucket(&c, bucket).await?;
+
+    let src_path = format!("{FS_ROOT}-streaming-upload.bin");
+    {
+        let chunk = vec![0x5a_u8; 1024 * 1024];
+        let mut file = fs::File::create(&src_path)?;
+        for _ in 0..SIZE / chunk.len() {
+            std::io::Write::write_all(&mut file, &chunk)?;
+        }
+    }
+
+    {
+        let before = peak_memory();
+
+        let body = ByteStream::from_path(&src_path).await?;
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+
+        let after = peak_memory();
+        assert!(after - before < BUDGET, "peak memory grew by {} bytes", after - before);
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.content_length(), SIZE as i64);
+    }
+
+    {
+        fs::remove_file(&src_path)?;
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+#[tracing::instrument]
+async fn test_streaming_complete_multipart_upload() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-streaming-complete-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "large.bin";
+
+    const PART_SIZE: usize = 64 * 1024 * 1024;
+    const PARTS: i32 = 3;
+    const BUDGET: u64 = 64 * 1024 * 1024;
+
+    create_bucket(&c, bucket).await?;
+
+    let src_path = format!("{FS_ROOT}-streaming-complete.bin");
+    {
+        let chunk = vec![0x5a_u8; 1024 * 1024];
+        let mut file = fs::File::create(&src_path)?;
+        for _ in 0..PART_SIZE / chunk.len() {
+            std::io::Write::write_all(&mut file, &chunk)?;
+        }
+    }
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id().unwrap().to_owned()
+    };
+
+    let mut parts = Vec::new();
+    for part_number in 1..=PARTS {
+        let body = ByteStream::from_path(&src_path).await?;
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .part_number(part_number)
+            .body(body)
+            .send()
+            .await?;
+        parts.push(
+            CompletedPart::builder()
+                .e_tag(ans.e_tag().unwrap())
+                .part_number(part_number)
+                .build(),
+        );
+    }
+
+    {
+        let before = peak_memory();
+
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        assert!(ans.e_tag().unwrap().ends_with(&format!("-{PARTS}\"")));
+
+        let after = peak_memory();
+        assert!(after - before < BUDGET, "peak memory grew by {} bytes", after - before);
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.content_length(), i64::from(PARTS) * PART_SIZE as i64);
+    }
+
+    {
+        fs::remove_file(&src_path)?;
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_access_log() -> Result<()> {
+    use std::io;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-access-log-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let buffer = Buffer::default();
+    let subscriber = {
+        let buffer = buffer.clone();
+        tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(move || buffer.clone())
+            .finish()
+    };
+    let _subscriber_guard = tracing::subscriber::set_default(subscriber);
+
+    let c = {
+        let cred = Credentials::for_tests();
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+            b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+            b.set_access_log(tracing::Level::INFO);
+            b.build()
+        };
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.into_shared()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    };
+
+    let bucket = format!("test-access-log-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+    c.put_object()
+        .bucket(bucket)
+        .key("logged.txt")
+        .body(ByteStream::from_static(b"hello"))
+        .send()
+        .await?;
+    let _ = c.get_object().bucket(bucket).key("missing.txt").send().await.unwrap_err();
+
+    let logs = String::from_utf8(buffer.0.lock().unwrap().clone())?;
+    let lines: Vec<&str> = logs.lines().filter(|line| line.contains("request completed")).collect();
+    assert_eq!(lines.len(), 3);
+
+    assert!(lines[0].contains("op=\"CreateBucket\"") && lines[0].contains("status=200"));
+    assert!(lines[1].contains("method=PUT") && lines[1].contains("key=\"logged.txt\""));
+    assert!(lines[1].contains(bucket) && lines[1].contains("error_code").not());
+    assert!(lines[2].contains("status=404") && lines[2].contains("error_code=\"NoSuchKey\""));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_metrics() -> Result<()> {
+    use s3s::metrics::{RequestMetrics, S3Metrics};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    type Record = (Option<&'static str>, u16, Option<String>);
+
+    #[derive(Default)]
+    struct Recorder {
+        started: AtomicUsize,
+        ended: std::sync::Mutex<Vec<Record>>,
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedRecorder(Arc<Recorder>);
+
+    impl S3Metrics for SharedRecorder {
+        fn on_request_start(&self, _: &hyper::Method) {
+            self.0.started.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_request_end(&self, metrics: &RequestMetrics<'_>) {
+            let error_code = metrics.error_code().map(|code| code.as_str().to_owned());
+            let record = (metrics.op_name(), metrics.status().as_u16(), error_code);
+            self.0.ended.lock().unwrap().push(record);
+        }
+    }
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-metrics-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let recorder = SharedRecorder::default();
+    let c = {
+        let cred = Credentials::for_tests();
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+            b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+            b.set_metrics(recorder.clone());
+            b.build()
+        };
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.into_shared()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    };
+
+    let bucket = format!("test-metrics-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+    c.list_objects_v2().bucket(bucket).send().await?;
+    let _ = c.head_bucket().bucket("missing-bucket").send().await.unwrap_err();
+
+    assert_eq!(recorder.0.started.load(Ordering::SeqCst), 3);
+    let ended = recorder.0.ended.lock().unwrap();
+    assert_eq!(ended[0], (Some("CreateBucket"), 200, None));
+    assert_eq!(ended[1], (Some("ListObjectsV2"), 200, None));
+    assert_eq!(ended[2], (Some("HeadBucket"), 404, Some("NoSuchBucket".to_owned())));
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_abort_multipart_upload() -> Result<()> {
+    use std::time::Duration;
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-abort-upload-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = {
+        let cred = Credentials::for_tests();
+        let service = {
+            let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+            b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+            b.build()
+        };
+        let conf = aws_sdk_s3::Config::builder()
+            .credentials_provider(cred)
+            .http_connector(s3s_aws::Connector::from(service.into_shared()))
+            .region(Region::new(REGION))
+            .endpoint_url(format!("http://{DOMAIN_NAME}"))
+            .force_path_style(true)
+            .build();
+        Client::from_conf(conf)
+    };
+
+    let upload_files = || -> Result<usize> {
+        let mut count = 0;
+        for entry in fs::read_dir(&root)? {
+            let name = entry?.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(".upload-") || name.starts_with(".upload_id-") {
+                count += 1;
+            }
+        }
+        Ok(count)
+    };
+
+    let bucket = format!("test-abort-upload-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "aborted.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let err = c
+            .abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(Uuid::new_v4().to_string())
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchUpload"));
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+        for part_number in [1, 2] {
+            c.upload_part()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from_static(b"part"))
+                .send()
+                .await?;
+        }
+        assert_eq!(upload_files()?, 3);
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        assert_eq!(upload_files()?, 0);
+
+        let result = c.list_parts().bucket(bucket).key(key).upload_id(upload_id).send().await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("NoSuchUpload"));
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+        c.upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(b"part"))
+            .send()
+            .await?;
+
+        let fs = FileSystem::new(&root).unwrap();
+        assert_eq!(fs.reap_uploads(Duration::from_secs(3600)).await.unwrap(), 0);
+        assert_eq!(upload_files()?, 2);
+
+        assert_eq!(fs.reap_uploads(Duration::ZERO).await.unwrap(), 1);
+        assert_eq!(upload_files()?, 0);
+    }
+
+    {
+        // a record in the old format, which has no initiation time, and the staged part of a removed record
+        fs::write(format!("{root}/.upload-{}.json", Uuid::new_v4()), b"null")?;
+        fs::write(format!("{root}/.upload_id-{}.part-1", Uuid::new_v4()), b"part")?;
+        assert_eq!(upload_files()?, 2);
+
+        let fs = FileSystem::new(&root).unwrap();
+        assert_eq!(fs.reap_uploads(Duration::from_secs(3600)).await.unwrap(), 0);
+        assert_eq!(upload_files()?, 2);
+
+        assert_eq!(fs.reap_uploads(Duration::ZERO).await.unwrap(), 2);
+        assert_eq!(upload_files()?, 0);
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_min_part_size() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-min-part-size-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+    let c = local_client(FileSystem::new(&root).unwrap());
+
+    let bucket = format!("test-min-part-size-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "parts.bin";
+
+    create_bucket(&c, bucket).await?;
+
+    let upload = |part_sizes: Vec<usize>| {
+        let c = c.clone();
+        async move {
+            let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+            let upload_id = ans.upload_id().unwrap().to_owned();
+
+            let mut completed_parts = Vec::new();
+            for (part_number, size) in (1..).zip(part_sizes) {
+                let ans = c
+                    .upload_part()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .part_number(part_number)
+                    .body(ByteStream::from(vec![b'x'; size]))
+                    .send()
+                    .await?;
+                let part = CompletedPart::builder()
+                    .e_tag(ans.e_tag().unwrap())
+                    .part_number(part_number)
+                    .build();
+                completed_parts.push(part);
+            }
+
+            let upload = CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build();
+            let result = c
+                .complete_multipart_upload()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(&upload_id)
+                .multipart_upload(upload)
+                .send()
+                .await;
+            anyhow::Ok((upload_id, result))
+        }
+    };
+
+    const MIB: usize = 1024 * 1024;
+
+    {
+        let (upload_id, result) = upload(vec![MIB, 5 * MIB]).await?;
+        let err = result.unwrap_err().into_service_error();
+        assert_eq!(err.meta().code(), Some("EntityTooSmall"));
+        assert!(err.meta().message().unwrap().contains("Part 1 has size 1048576"));
+
+        // the upload is kept, so that the client can retry or abort it
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        let (_, result) = upload(vec![5 * MIB, 1]).await?;
+        result?;
+
+        let (_, result) = upload(vec![1]).await?;
+        result?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_complete_multipart_upload_validation() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-complete-validation-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "assembled.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let upload_id = {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        ans.upload_id().unwrap().to_owned()
+    };
+    let upload_id = upload_id.as_str();
+
+    let mut e_tags = Vec::new();
+    for (part_number, content) in (1..).zip(["one,", "two,", "three"]) {
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .send()
+            .await?;
+        e_tags.push(ans.e_tag().unwrap().to_owned());
+    }
+
+    let complete = |parts: &[(i32, &str)]| {
+        let parts = parts
+            .iter()
+            .map(|&(part_number, e_tag)| CompletedPart::builder().part_number(part_number).e_tag(e_tag).build())
+            .collect();
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+    };
+
+    let cases: [(&[(i32, &str)], &str); 4] = [
+        (&[(1, &e_tags[0]), (4, &e_tags[2])], "InvalidPart"),
+        (&[(1, &e_tags[0]), (2, &e_tags[2])], "InvalidPart"),
+        (&[(2, &e_tags[1]), (1, &e_tags[0])], "InvalidPartOrder"),
+        (&[(1, &e_tags[0]), (1, &e_tags[0])], "InvalidPartOrder"),
+    ];
+    for (parts, code) in cases {
+        let err = complete(parts).await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some(code));
+    }
+
+    {
+        // the upload is kept after the failures, and the unlisted part is discarded
+        let ans = complete(&[(1, &e_tags[0]), (3, &e_tags[2])]).await?;
+        assert!(ans.e_tag().unwrap().ends_with("-2\""));
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"one,three");
+
+        let result = c.list_parts().bucket(bucket).key(key).upload_id(upload_id).send().await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("NoSuchUpload"));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_read_only() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-read-only-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let bucket = format!("test-read-only-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "mirrored.txt";
+
+    {
+        let c = local_client(FileSystem::new(&root).unwrap());
+        create_bucket(&c, bucket).await?;
+        let body = ByteStream::from_static(b"hello");
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+    }
+
+    let read_only = FileSystem::new_read_only(&root).unwrap();
+    let c = local_client(read_only.clone());
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello");
+
+        c.head_object().bucket(bucket).key(key).send().await?;
+        c.head_bucket().bucket(bucket).send().await?;
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert_eq!(ans.contents().unwrap().len(), 1);
+        c.list_buckets().send().await?;
+    }
+
+    {
+        macro_rules! assert_denied {
+            ($request:expr) => {
+                let err = $request.send().await.unwrap_err().into_service_error();
+                assert_eq!(err.meta().code(), Some("AccessDenied"));
+            };
+        }
+
+        let tagging = Tagging::builder().tag_set(Tag::builder().key("k").value("v").build()).build();
+        assert_denied!(c.put_object().bucket(bucket).key(key));
+        assert_denied!(c.delete_object().bucket(bucket).key(key));
+        assert_denied!(c.create_bucket().bucket("another-bucket"));
+        assert_denied!(c.delete_bucket().bucket(bucket));
+        assert_denied!(c.create_multipart_upload().bucket(bucket).key(key));
+        assert_denied!(c.put_object_tagging().bucket(bucket).key(key).tagging(tagging));
+
+        // the denied requests do not modify the object
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.content_length(), 5);
+
+        // the maintenance tasks are denied as well
+        fs::remove_file(format!("{root}/{bucket}/{key}"))?;
+        assert!(read_only.gc_orphans().await.is_err());
+        assert!(read_only.reap_uploads(std::time::Duration::ZERO).await.is_err());
+        assert_eq!(FileSystem::new(&root).unwrap().gc_orphans().await.unwrap(), 1);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_content_encoding() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-content-encoding-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "encoded.gz";
+
+    // the content is stored verbatim, so any bytes work
+    let content = b"\x1f\x8b\x08\x00 pretend this is gzip".repeat(50);
+
+    create_bucket(&c, bucket).await?;
+
+    let check = |key: &'static str| {
+        let c = c.clone();
+        let content = content.clone();
+        async move {
+            let ans = c.head_object().bucket(bucket).key(key).send().await?;
+            assert_eq!(ans.content_encoding(), Some("gzip"));
+
+            let ans = c.get_object().bucket(bucket).key(key).send().await?;
+            assert_eq!(ans.content_encoding(), Some("gzip"));
+            let body = ans.body.collect().await?.into_bytes();
+            assert_eq!(body.as_ref(), content.as_slice());
+
+            // the body is not transformed according to the accepted encodings
+            for accept_encoding in ["identity", "gzip, deflate", "br;q=1.0, identity;q=0"] {
+                let ans = c
+                    .get_object()
+                    .bucket(bucket)
+                    .key(key)
+                    .customize()
+                    .await?
+                    .mutate_request(move |req| {
+                        drop(req.headers_mut().insert("accept-encoding", accept_encoding.parse().unwrap()));
+                    })
+                    .send()
+                    .await?;
+                assert_eq!(ans.content_encoding(), Some("gzip"));
+                assert_eq!(ans.content_length(), i64::try_from(content.len())?);
+                let body = ans.body.collect().await?.into_bytes();
+                assert_eq!(body.as_ref(), content.as_slice());
+            }
+            anyhow::Ok(())
+        }
+    };
+
+    {
+        let body = ByteStream::from(content.clone());
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(body)
+            .content_encoding("gzip")
+            .send()
+            .await?;
+        check(key).await?;
+    }
+
+    {
+        // the sdk sends a file body as aws-chunked, whose framing is not a content encoding of the object
+        let chunked_key = "chunked.txt";
+        let file_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{bucket}.txt"));
+        fs::write(&file_path, &content)?;
+        let body = ByteStream::read_from().path(&file_path).buffer_size(1024).build().await?;
+        c.put_object()
+            .bucket(bucket)
+            .key(chunked_key)
+            .body(body)
+            .checksum_algorithm(ChecksumAlgorithm::Crc32)
+            .send()
+            .await?;
+        fs::remove_file(&file_path)?;
+
+        let ans = c.head_object().bucket(bucket).key(chunked_key).send().await?;
+        assert_eq!(ans.content_encoding(), None);
+        delete_object(&c, bucket, chunked_key).await?;
+    }
+
+    {
+        let mp_key = "multipart.gz";
+        let ans = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .content_encoding("gzip")
+            .send()
+            .await?;
+        let upload_id = ans.upload_id().unwrap();
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from(content.clone()))
+            .send()
+            .await?;
+        let part = CompletedPart::builder().e_tag(ans.e_tag().unwrap()).part_number(1).build();
+        let upload = CompletedMultipartUpload::builder().parts(part).build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        check(mp_key).await?;
+        delete_object(&c, bucket, mp_key).await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_max_object_size() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-max-object-size-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+    let new_fs = || {
+        let mut fs = FileSystem::new(&root).unwrap();
+        fs.set_min_part_size(0);
+        fs.set_max_object_size(10);
+        fs
+    };
+    let c = local_client(new_fs());
+
+    let bucket = format!("test-max-object-size-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "limited.bin";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let body = ByteStream::from(vec![b'x'; 10]);
+        c.put_object().bucket(bucket).key(key).body(body).send().await?;
+
+        let body = ByteStream::from(vec![b'y'; 11]);
+        let err = c.put_object().bucket(bucket).key(key).body(body).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("EntityTooLarge"));
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(head.content_length(), 10);
+    }
+
+    {
+        // a chunked body does not have a content length, so the size is checked while streaming
+        let service = S3ServiceBuilder::new(new_fs()).build();
+        let chunks = (0..3).map(|_| Ok::<_, std::io::Error>(bytes::Bytes::from_static(b"zzzzzzzz")));
+        let req = hyper::Request::builder()
+            .method("PUT")
+            .uri(format!("http://localhost/{bucket}/chunked.bin"))
+            .header("transfer-encoding", "chunked")
+            .body(s3s::Body::from(hyper::Body::wrap_stream(futures::stream::iter(chunks))))
+            .unwrap();
+        let res = service.call(req).await?;
+        assert_eq!(res.status(), 400);
+
+        let err = c.head_object().bucket(bucket).key("chunked.bin").send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+
+        // the temporary file of the aborted write is removed
+        let leftovers = fs::read_dir(&root)?
+            .filter(|e| e.as_ref().is_ok_and(|e| e.file_name().to_string_lossy().starts_with(".tmp.")))
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let upload_part = |part_number: i32| {
+            c.upload_part()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(vec![b'p'; 6]))
+                .send()
+        };
+
+        for part_number in [0, 10001] {
+            let err = upload_part(part_number).await.unwrap_err();
+            assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+        }
+
+        let mut parts = Vec::new();
+        for part_number in [1, 2] {
+            let ans = upload_part(part_number).await?;
+            let part = CompletedPart::builder()
+                .e_tag(ans.e_tag().unwrap())
+                .part_number(part_number)
+                .build();
+            parts.push(part);
+        }
+
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+        let err = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("EntityTooLarge"));
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        // a copied part is limited like an uploaded one, so the source is written without the limit
+        let large = "large.bin";
+        let unlimited = local_client(FileSystem::new(&root).unwrap());
+        let body = ByteStream::from(vec![b'l'; 20]);
+        unlimited.put_object().bucket(bucket).key(large).body(body).send().await?;
+
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+        let upload_part_copy = |range: &str| {
+            c.upload_part_copy()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(1)
+                .copy_source(format!("{bucket}/{large}"))
+                .copy_source_range(range)
+                .send()
+        };
+
+        let err = upload_part_copy("bytes=0-19").await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("EntityTooLarge"));
+        upload_part_copy("bytes=0-9").await?;
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        delete_object(&c, bucket, large).await?;
+    }
+
+    delete_object(&c, bucket, key).await?;
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_get_object_attributes() -> Result<()> {
+    use sha2::{Digest, Sha256};
+
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-get-object-attributes-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    let crc32 = |data: &[u8]| crc32fast::hash(data).to_be_bytes();
+    let b64 = |data: &[u8]| base64_simd::STANDARD.encode_to_string(data);
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let key = "single.txt";
+        let content = b"hello attributes";
+        let sha256 = b64(&Sha256::digest(content));
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content))
+            .checksum_sha256(&sha256)
+            .storage_class(StorageClass::StandardIa)
+            .send()
+            .await?;
+        let e_tag = ans.e_tag().unwrap().trim_matches('"').to_owned();
+
+        let ans = c
+            .get_object_attributes()
+            .bucket(bucket)
+            .key(key)
+            .object_attributes(ObjectAttributes::Etag)
+            .object_attributes(ObjectAttributes::Checksum)
+            .object_attributes(ObjectAttributes::ObjectSize)
+            .object_attributes(ObjectAttributes::StorageClass)
+            .object_attributes(ObjectAttributes::ObjectParts)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        assert_eq!(ans.object_size(), content.len() as i64);
+        assert_eq!(ans.storage_class(), Some(&StorageClass::StandardIa));
+        assert_eq!(ans.checksum().and_then(|c| c.checksum_sha256()), Some(sha256.as_str()));
+        assert!(ans.object_parts().is_none());
+        assert!(ans.last_modified().is_some());
+
+        // only the selected attributes are returned
+        let ans = c
+            .get_object_attributes()
+            .bucket(bucket)
+            .key(key)
+            .object_attributes(ObjectAttributes::Etag)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        assert!(ans.checksum().is_none());
+        assert!(ans.storage_class().is_none());
+
+        let err = c
+            .get_object_attributes()
+            .bucket(bucket)
+            .key("missing.txt")
+            .object_attributes(ObjectAttributes::Etag)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+
+        delete_object(&c, bucket, key).await?;
+    }
+
+    {
+        let key = "multipart.txt";
+        let ans = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .checksum_algorithm(ChecksumAlgorithm::Crc32)
+            .send()
+            .await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let part_contents: [&'static [u8]; 3] = [b"first part", b"second part", b"third part"];
+        let mut completed_parts = Vec::new();
+        for (part_number, content) in (1..).zip(part_contents) {
+            let ans = c
+                .upload_part()
+                .bucket(bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from_static(content))
+                .checksum_crc32(b64(&crc32(content)))
+                .send()
+                .await?;
+            completed_parts.push(
+                CompletedPart::builder()
+                    .e_tag(ans.e_tag().unwrap())
+                    .part_number(part_number)
+                    .build(),
+            );
+        }
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        let composite = ans.checksum_crc32().unwrap().to_owned();
+
+        let get_parts = |max_parts: i32, marker: Option<&'static str>| {
+            c.get_object_attributes()
+                .bucket(bucket)
+                .key(key)
+                .object_attributes(ObjectAttributes::ObjectParts)
+                .object_attributes(ObjectAttributes::Checksum)
+                .max_parts(max_parts)
+                .set_part_number_marker(marker.map(ToOwned::to_owned))
+                .send()
+        };
+
+        let ans = get_parts(2, None).await?;
+        assert_eq!(ans.checksum().and_then(|c| c.checksum_crc32()), Some(composite.as_str()));
+        let parts = ans.object_parts().unwrap();
+        assert_eq!(parts.total_parts_count(), 3);
+        assert!(parts.is_truncated());
+        assert_eq!(parts.next_part_number_marker(), Some("2"));
+        let list = parts.parts().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].part_number(), 2);
+        assert_eq!(list[1].size(), b"second part".len() as i64);
+        assert_eq!(list[1].checksum_crc32(), Some(b64(&crc32(b"second part")).as_str()));
+
+        let ans = get_parts(2, Some("2")).await?;
+        let parts = ans.object_parts().unwrap();
+        assert!(parts.is_truncated().not());
+        let list = parts.parts().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].part_number(), 3);
+
+        delete_object(&c, bucket, key).await?;
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_restore_object() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-restore-object-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let archived_key = "archived.txt";
+    let standard_key = "standard.txt";
+
+    create_bucket(&c, bucket).await?;
+    for (key, storage_class) in [(archived_key, StorageClass::Glacier), (standard_key, StorageClass::Standard)] {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"cold data"))
+            .storage_class(storage_class)
+            .send()
+            .await?;
+    }
+
+    let restore = |key: &'static str, days: i32| {
+        c.restore_object()
+            .bucket(bucket)
+            .key(key)
+            .restore_request(RestoreRequest::builder().days(days).build())
+            .send()
+    };
+
+    {
+        let head = c.head_object().bucket(bucket).key(archived_key).send().await?;
+        assert!(head.restore().is_none());
+
+        let err = restore(archived_key, 0).await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+
+        let err = restore(standard_key, 1).await.unwrap_err();
+        assert_eq!(http_status(&err), Some(403));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidObjectState"));
+    }
+
+    {
+        // the SDK does not expose the status, which tells whether the object was restored before
+        let service = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap()).build();
+        let restore_status = |days: i32| {
+            let req = hyper::Request::builder()
+                .method("POST")
+                .uri(format!("http://localhost/{bucket}/{archived_key}?restore"))
+                .body(s3s::Body::from(format!("<RestoreRequest><Days>{days}</Days></RestoreRequest>")))
+                .unwrap();
+            let service = &service;
+            async move { service.call(req).await.map(|res| res.status()) }
+        };
+
+        assert_eq!(restore_status(1).await?, 202);
+
+        let head = c.head_object().bucket(bucket).key(archived_key).send().await?;
+        let restore_header = head.restore().unwrap();
+        assert!(restore_header.starts_with("ongoing-request=\"false\", expiry-date=\""));
+
+        // a restored object can be restored again to extend its expiry date
+        assert_eq!(restore_status(2).await?, 200);
+        restore(archived_key, 3).await?;
+
+        let get = c.get_object().bucket(bucket).key(archived_key).send().await?;
+        assert!(get.restore().is_some_and(|r| r != restore_header));
+    }
+
+    for key in [archived_key, standard_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_write_get_object_response() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+
+    {
+        c.write_get_object_response()
+            .request_route("io-example-route")
+            .request_token("example-token")
+            .status_code(200)
+            .body(ByteStream::from_static(b"transformed"))
+            .send()
+            .await?;
+    }
+
+    {
+        let err = c
+            .write_get_object_response()
+            .request_route("io-example-route")
+            .request_token("example-token")
+            .status_code(100)
+            .body(ByteStream::from_static(b"transformed"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_select_object_content() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-select-object-content-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let csv_key = "people.csv";
+    let json_key = "people.json";
+
+    create_bucket(&c, bucket).await?;
+    let csv = "name,age,city\r\nalice,30,\"Paris, France\"\n# a comment\nbob,25,Berlin\ncarol,41,\"New \"\"York\"\"\"\n";
+    c.put_object()
+        .bucket(bucket)
+        .key(csv_key)
+        .body(ByteStream::from_static(csv.as_bytes()))
+        .send()
+        .await?;
+    let json = "{\"name\":\"alice\",\"age\":30}\n{\"name\":\"bob\",\"age\":25}\n";
+    c.put_object()
+        .bucket(bucket)
+        .key(json_key)
+        .body(ByteStream::from_static(json.as_bytes()))
+        .send()
+        .await?;
+
+    let csv_input = |header: FileHeaderInfo| {
+        InputSerialization::builder()
+            .csv(CsvInput::builder().file_header_info(header).build())
+            .build()
+    };
+    let csv_output = OutputSerialization::builder().csv(CsvOutput::builder().build()).build();
+    let json_output = OutputSerialization::builder().json(JsonOutput::builder().build()).build();
+
+    let select = |key: &'static str, sql: &'static str, input: InputSerialization, output: OutputSerialization| {
+        let c = c.clone();
+        async move {
+            let mut out = c
+                .select_object_content()
+                .bucket(bucket)
+                .key(key)
+                .expression(sql)
+                .expression_type(ExpressionType::Sql)
+                .input_serialization(input)
+                .output_serialization(output)
+                .send()
+                .await?;
+
+            let mut records = Vec::new();
+            let mut stats = None;
+            let mut is_end = false;
+            while let Some(event) = out.payload.recv().await? {
+                match event {
+                    SelectObjectContentEventStream::Records(ev) => {
+                        records.extend_from_slice(ev.payload().unwrap().as_ref());
+                    }
+                    SelectObjectContentEventStream::Stats(ev) => stats = ev.details().cloned(),
+                    SelectObjectContentEventStream::End(_) => is_end = true,
+                    _ => {}
+                }
+            }
+            assert!(is_end);
+            let stats = stats.unwrap();
+            assert_eq!(stats.bytes_returned(), i64::try_from(records.len())?);
+            anyhow::Ok((String::from_utf8(records)?, stats))
+        }
+    };
+
+    {
+        let sql = "SELECT * FROM s3object";
+        let (records, stats) = select(csv_key, sql, csv_input(FileHeaderInfo::Use), csv_output.clone()).await?;
+        assert_eq!(records, "alice,30,\"Paris, France\"\nbob,25,Berlin\ncarol,41,\"New \"\"York\"\"\"\n");
+        assert_eq!(stats.bytes_scanned(), i64::try_from(csv.len())?);
+    }
+
+    {
+        let sql = "SELECT s.name, s.city FROM S3Object s WHERE s.age > 28 AND NOT s.name = 'carol'";
+        let (records, _) = select(csv_key, sql, csv_input(FileHeaderInfo::Use), csv_output.clone()).await?;
+        assert_eq!(records, "alice,\"Paris, France\"\n");
+    }
+
+    {
+        let sql = "SELECT _1 FROM S3Object WHERE _2 < 30 OR _1 = 'carol' LIMIT 1";
+        let (records, _) = select(csv_key, sql, csv_input(FileHeaderInfo::Ignore), json_output.clone()).await?;
+        assert_eq!(records, "{\"_1\":\"bob\"}\n");
+    }
+
+    {
+        let sql = "SELECT s.name FROM S3Object s WHERE s.age >= 30";
+        let input = InputSerialization::builder()
+            .json(JsonInput::builder().r#type(JsonType::Lines).build())
+            .build();
+        let (records, _) = select(json_key, sql, input, json_output.clone()).await?;
+        assert_eq!(records, "{\"name\":\"alice\"}\n");
+    }
+
+    {
+        let sql = "SELECT * FROM s3object WHERE";
+        let err = select(csv_key, sql, csv_input(FileHeaderInfo::Use), csv_output.clone())
+            .await
+            .unwrap_err();
+        let err = err.downcast::<SdkError<SelectObjectContentError>>()?;
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        delete_object(&c, bucket, csv_key).await?;
+        delete_object(&c, bucket, json_key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_website_redirect_location() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-website-redirect-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "page.html";
+    let copied_key = "copied.html";
+    let multipart_key = "multipart.html";
+    let location = "/new-page.html";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"old page"))
+            .website_redirect_location("new-page.html")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"old page"))
+            .website_redirect_location(location)
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(head.website_redirect_location(), Some(location));
+
+        let get = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(get.website_redirect_location(), Some(location));
+        let body = get.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"old page");
+    }
+
+    {
+        // the redirect location is not copied from the source object
+        c.copy_object()
+            .bucket(bucket)
+            .key(copied_key)
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await?;
+        let head = c.head_object().bucket(bucket).key(copied_key).send().await?;
+        assert!(head.website_redirect_location().is_none());
+    }
+
+    {
+        let upload = c
+            .create_multipart_upload()
+            .bucket(bucket)
+            .key(multipart_key)
+            .website_redirect_location("https://example.com/")
+            .send()
+            .await?;
+        let upload_id = upload.upload_id().unwrap();
+        let part = c
+            .upload_part()
+            .bucket(bucket)
+            .key(multipart_key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(b"multipart page"))
+            .send()
+            .await?;
+        let completed = CompletedMultipartUpload::builder()
+            .parts(CompletedPart::builder().part_number(1).e_tag(part.e_tag().unwrap()).build())
+            .build();
+        c.complete_multipart_upload()
+            .bucket(bucket)
+            .key(multipart_key)
+            .upload_id(upload_id)
+            .multipart_upload(completed)
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(multipart_key).send().await?;
+        assert_eq!(head.website_redirect_location(), Some("https://example.com/"));
+    }
+
+    {
+        let mut fs = FileSystem::new(FS_ROOT).unwrap();
+        fs.set_website_redirects(true);
+        let service = S3ServiceBuilder::new(fs).build();
+
+        for method in ["GET", "HEAD"] {
+            let req = hyper::Request::builder()
+                .method(method)
+                .uri(format!("http://localhost/{bucket}/{key}"))
+                .body(s3s::Body::empty())
+                .unwrap();
+            let res = service.call(req).await?;
+            assert_eq!(res.status(), 301);
+            assert_eq!(res.headers()[hyper::header::LOCATION], location);
+        }
+
+        // objects without a redirect location are served as usual
+        let req = hyper::Request::builder()
+            .method("GET")
+            .uri(format!("http://localhost/{bucket}/{copied_key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let res = service.call(req).await?;
+        assert_eq!(res.status(), 200);
+    }
+
+    for key in [key, copied_key, multipart_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_website() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-bucket-website-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let err = c.get_bucket_website().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchWebsiteConfiguration"));
+    }
+
+    {
+        // a configuration must have an index document unless it redirects all requests
+        let config = WebsiteConfiguration::builder()
+            .error_document(ErrorDocument::builder().key("error.html").build())
+            .build();
+        let err = c
+            .put_bucket_website()
+            .bucket(bucket)
+            .website_configuration(config)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        let rule = RoutingRule::builder()
+            .condition(Condition::builder().key_prefix_equals("docs/").build())
+            .redirect(
+                Redirect::builder()
+                    .replace_key_prefix_with("documents/")
+                    .http_redirect_code("302")
+                    .protocol(Protocol::Https)
+                    .build(),
+            )
+            .build();
+        let config = WebsiteConfiguration::builder()
+            .index_document(IndexDocument::builder().suffix("index.html").build())
+            .error_document(ErrorDocument::builder().key("error.html").build())
+            .routing_rules(rule)
+            .build();
+        c.put_bucket_website()
+            .bucket(bucket)
+            .website_configuration(config)
+            .send()
+            .await?;
+
+        let website = c.get_bucket_website().bucket(bucket).send().await?;
+        assert_eq!(website.index_document().and_then(IndexDocument::suffix), Some("index.html"));
+        assert_eq!(website.error_document().and_then(ErrorDocument::key), Some("error.html"));
+        assert!(website.redirect_all_requests_to().is_none());
+
+        let Some([rule]) = website.routing_rules() else { panic!() };
+        let condition = rule.condition().unwrap();
+        assert_eq!(condition.key_prefix_equals(), Some("docs/"));
+        assert!(condition.http_error_code_returned_equals().is_none());
+        let redirect = rule.redirect().unwrap();
+        assert_eq!(redirect.replace_key_prefix_with(), Some("documents/"));
+        assert_eq!(redirect.http_redirect_code(), Some("302"));
+        assert_eq!(redirect.protocol(), Some(&Protocol::Https));
+        assert!(redirect.host_name().is_none());
+    }
+
+    {
+        // a new configuration replaces the previous one
+        let config = WebsiteConfiguration::builder()
+            .redirect_all_requests_to(RedirectAllRequestsTo::builder().host_name("example.com").build())
+            .build();
+        c.put_bucket_website()
+            .bucket(bucket)
+            .website_configuration(config)
+            .send()
+            .await?;
+
+        let website = c.get_bucket_website().bucket(bucket).send().await?;
+        let redirect_all = website.redirect_all_requests_to().unwrap();
+        assert_eq!(redirect_all.host_name(), Some("example.com"));
+        assert!(redirect_all.protocol().is_none());
+        assert!(website.index_document().is_none());
+        assert!(website.routing_rules().is_none());
+    }
+
+    {
+        c.delete_bucket_website().bucket(bucket).send().await?;
+        let err = c.get_bucket_website().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchWebsiteConfiguration"));
+
+        // deleting a missing configuration succeeds
+        c.delete_bucket_website().bucket(bucket).send().await?;
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_file_system_builder() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-builder-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    {
+        assert!(FileSystem::builder(format!("{root}-missing")).build().is_err());
+
+        let file = format!("{root}-file");
+        fs::write(&file, b"not a directory")?;
+        assert!(FileSystem::builder(&file).build().is_err());
+        fs::remove_file(&file)?;
+    }
+
+    {
+        // the values which the setters panic on are rejected
+        let builder = || FileSystem::builder(&root);
+        let builders = [
+            builder().read_buffer_size(0),
+            builder().hash_buffer_size(0),
+            builder().part_verify_concurrency(0),
+            builder().max_keys(0),
+            builder().max_keys(-1),
+            builder().max_key_length(0),
+        ];
+        for builder in builders {
+            let err = builder.build().unwrap_err();
+            assert!(format!("{err:?}").contains("InvalidInput"));
+        }
+    }
+
+    {
+        // a read-only file system keeps the temporary files of a previous writer
+        let tmp_file = format!("{root}/.tmp.0.internal.part");
+        fs::write(&tmp_file, b"leftover")?;
+        FileSystem::builder(&root).read_only(true).meta_dir(true).build().unwrap();
+        assert!(Path::new(&tmp_file).exists());
+        assert!(Path::new(&format!("{root}/.s3s-meta")).exists().not());
+
+        FileSystem::builder(&root).build().unwrap();
+        assert!(Path::new(&tmp_file).exists().not());
+    }
+
+    let fs = FileSystem::builder(&root)
+        .region("eu-west-1")
+        .meta_dir(true)
+        .max_object_size(4)
+        .build()
+        .unwrap();
+    let c = local_client(fs);
+    let bucket = "test-builder";
+
+    {
+        // a bucket without a location constraint is in the region of the file system
+        c.create_bucket().bucket(bucket).send().await?;
+        let ans = c.get_bucket_location().bucket(bucket).send().await?;
+        assert_eq!(ans.location_constraint().map(BucketLocationConstraint::as_str), Some("eu-west-1"));
+        assert!(Path::new(&format!("{root}/.s3s-meta")).is_dir());
+
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key("large.txt")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("EntityTooLarge"));
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_post_object() -> Result<()> {
+    use ring::hmac;
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-post-object-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let auth = SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key());
+        let mut b = S3ServiceBuilder::new(FileSystem::new(&root).unwrap());
+        b.set_auth(auth);
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred.clone())
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+    let bucket = "test-post-object";
+
+    let now = chrono::Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let credential = format!("{}/{}/{REGION}/s3/aws4_request", cred.access_key_id(), now.format("%Y%m%d"));
+    let sign = |policy: &str| {
+        let mut key = hmac::Key::new(hmac::HMAC_SHA256, format!("AWS4{}", cred.secret_access_key()).as_bytes());
+        for msg in [now.format("%Y%m%d").to_string().as_str(), REGION, "s3", "aws4_request"] {
+            key = hmac::Key::new(hmac::HMAC_SHA256, hmac::sign(&key, msg.as_bytes()).as_ref());
+        }
+        hex_simd::encode_to_string(hmac::sign(&key, policy.as_bytes()), hex_simd::AsciiCase::Lower)
+    };
+
+    // signs a policy of the given conditions and posts a form of the given fields and file
+    let post = |conditions: serde_json::Value, fields: &[(&str, &str)], file: &'static [u8], expires_in: i64| {
+        let expiration = now + chrono::Duration::seconds(expires_in);
+        let expiration = expiration.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let mut conditions = conditions.as_array().unwrap().clone();
+        conditions.extend([
+            serde_json::json!({ "x-amz-algorithm": "AWS4-HMAC-SHA256" }),
+            serde_json::json!({ "x-amz-credential": credential }),
+            serde_json::json!({ "x-amz-date": amz_date }),
+        ]);
+        let policy = serde_json::json!({ "expiration": expiration, "conditions": conditions });
+        let policy = base64_simd::STANDARD.encode_to_string(policy.to_string());
+        let signature = sign(&policy);
+
+        let boundary = "s3s-post-object-boundary";
+        let mut body = Vec::new();
+        let common = [
+            ("policy", policy.as_str()),
+            ("x-amz-algorithm", "AWS4-HMAC-SHA256"),
+            ("x-amz-credential", credential.as_str()),
+            ("x-amz-date", amz_date.as_str()),
+            ("x-amz-signature", signature.as_str()),
+        ];
+        for (name, value) in fields.iter().chain(&common) {
+            let part = format!("--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n");
+            body.extend_from_slice(part.as_bytes());
+        }
+        let part = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"photo.txt\"\r\nContent-Type: text/plain\r\n\r\n"
+        );
+        body.extend_from_slice(part.as_bytes());
+        body.extend_from_slice(file);
+        body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+        let req = hyper::Request::builder()
+            .method("POST")
+            .uri(format!("http://{DOMAIN_NAME}/{bucket}"))
+            .header("content-type", format!("multipart/form-data; boundary={boundary}"))
+            .body(s3s::Body::from(body))
+            .unwrap();
+        let service = service.clone();
+        async move { service.as_ref().call(req).await }
+    };
+    let body_string = |mut res: hyper::Response<s3s::Body>| async move {
+        let body = res.body_mut().store_all_unlimited().await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    };
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let conditions = serde_json::json!([
+            { "bucket": bucket },
+            ["starts-with", "$key", "uploads/"],
+            { "success_action_status": "201" },
+        ]);
+        let fields = [("key", "uploads/${filename}"), ("success_action_status", "201")];
+        let res = post(conditions, &fields, b"hello form", 3600).await?;
+        assert_eq!(res.status(), 201);
+        let xml = body_string(res).await;
+        assert!(xml.contains("<Bucket>test-post-object</Bucket><Key>uploads/photo.txt</Key>"), "{xml}");
+
+        let ans = c.get_object().bucket(bucket).key("uploads/photo.txt").send().await?;
+        assert_eq!(ans.content_type(), Some("text/plain"));
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"hello form");
+    }
+
+    {
+        let conditions = serde_json::json!([
+            ["eq", "$key", "redirected.txt"],
+            ["eq", "$success_action_redirect", "http://example.com/done"],
+        ]);
+        let fields = [
+            ("key", "redirected.txt"),
+            ("success_action_redirect", "http://example.com/done"),
+        ];
+        let res = post(conditions, &fields, b"redirect", 3600).await?;
+        assert_eq!(res.status(), 303);
+        let location = res.headers()[hyper::header::LOCATION].to_str()?;
+        assert!(location.starts_with("http://example.com/done?bucket=test-post-object&key=redirected.txt&etag="));
+
+        // the default response has no content
+        let conditions = serde_json::json!([["starts-with", "$key", ""]]);
+        let res = post(conditions, &[("key", "default.txt")], b"default", 3600).await?;
+        assert_eq!(res.status(), 204);
+    }
+
+    {
+        let conditions = serde_json::json!([["starts-with", "$key", "uploads/"]]);
+        let res = post(conditions, &[("key", "other/denied.txt")], b"denied", 3600).await?;
+        assert_eq!(res.status(), 403);
+        assert!(body_string(res).await.contains("Policy Condition failed"));
+
+        // every form field must be covered by the policy
+        let conditions = serde_json::json!([["starts-with", "$key", ""]]);
+        let fields = [("key", "extra.txt"), ("x-amz-meta-color", "red")];
+        let res = post(conditions, &fields, b"extra", 3600).await?;
+        assert_eq!(res.status(), 403);
+        assert!(body_string(res).await.contains("Extra input fields: x-amz-meta-color"));
+
+        let conditions = serde_json::json!([["starts-with", "$key", ""]]);
+        let res = post(conditions, &[("key", "expired.txt")], b"expired", -60).await?;
+        assert_eq!(res.status(), 403);
+        assert!(body_string(res).await.contains("Policy expired."));
+
+        let conditions = serde_json::json!([["starts-with", "$key", ""], ["content-length-range", 1, 4]]);
+        let res = post(conditions, &[("key", "large.txt")], b"too large", 3600).await?;
+        assert_eq!(res.status(), 400);
+        assert!(body_string(res).await.contains("<Code>EntityTooLarge</Code>"));
+
+        for key in ["other/denied.txt", "extra.txt", "expired.txt", "large.txt"] {
+            let err = c.head_object().bucket(bucket).key(key).send().await.unwrap_err();
+            assert_eq!(http_status(&err), Some(404));
+        }
+    }
+
+    {
+        for key in ["uploads/photo.txt", "redirected.txt", "default.txt"] {
+            delete_object(&c, bucket, key).await?;
+        }
+        delete_bucket(&c, bucket).await?;
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_acl() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-bucket-acl-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let auth = SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key());
+        let fs = FileSystem::builder(&root)
+            .public_buckets(auth.public_buckets().clone())
+            .build()
+            .unwrap();
+        let mut b = S3ServiceBuilder::new(fs);
+        b.set_auth(auth);
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred.clone())
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+    let bucket = "test-bucket-acl";
+    let key = "sample.txt";
+
+    create_bucket(&c, bucket).await?;
+    c.put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from_static(b"hello"))
+        .send()
+        .await?;
+
+    let anonymous_get = || {
+        let req = hyper::Request::builder()
+            .method("GET")
+            .uri(format!("http://{DOMAIN_NAME}/{bucket}/{key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let service = &service;
+        async move { service.as_ref().call(req).await.map(|res| res.status()) }
+    };
+    let permissions = |acl: &GetBucketAclOutput| {
+        let mut permissions: Vec<_> = (acl.grants().unwrap().iter())
+            .map(|g| {
+                let grantee = g.grantee().unwrap();
+                let grantee = grantee.id().or(grantee.uri()).unwrap().to_owned();
+                (grantee, g.permission().unwrap().as_str().to_owned())
+            })
+            .collect();
+        permissions.sort();
+        permissions
+    };
+    let owner = (cred.access_key_id().to_owned(), "FULL_CONTROL".to_owned());
+    let all_users_read = ("http://acs.amazonaws.com/groups/global/AllUsers".to_owned(), "READ".to_owned());
+
+    {
+        // the owner has full control by default
+        let acl = c.get_bucket_acl().bucket(bucket).send().await?;
+        assert_eq!(acl.owner().and_then(|o| o.id()), Some(cred.access_key_id()));
+        assert_eq!(permissions(&acl), std::slice::from_ref(&owner));
+        assert_eq!(anonymous_get().await?, 403);
+    }
+
+    {
+        c.put_bucket_acl()
+            .bucket(bucket)
+            .acl(BucketCannedAcl::PublicRead)
+            .send()
+            .await?;
+        let acl = c.get_bucket_acl().bucket(bucket).send().await?;
+        assert_eq!(permissions(&acl), [owner.clone(), all_users_read.clone()]);
+        assert_eq!(anonymous_get().await?, 200);
+    }
+
+    {
+        let policy = AccessControlPolicy::builder()
+            .owner(Owner::builder().id(cred.access_key_id()).build())
+            .grants(
+                Grant::builder()
+                    .grantee(Grantee::builder().r#type(Type::CanonicalUser).id("someone").build())
+                    .permission(Permission::Read)
+                    .build(),
+            )
+            .build();
+        c.put_bucket_acl().bucket(bucket).access_control_policy(policy).send().await?;
+        let acl = c.get_bucket_acl().bucket(bucket).send().await?;
+        assert_eq!(permissions(&acl), [("someone".to_owned(), "READ".to_owned())]);
+        assert_eq!(anonymous_get().await?, 403);
+    }
+
+    {
+        let err = c
+            .put_bucket_acl()
+            .bucket(bucket)
+            .acl(BucketCannedAcl::Private)
+            .grant_read("uri=\"http://acs.amazonaws.com/groups/global/AllUsers\"")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidRequest"));
+
+        c.put_bucket_acl()
+            .bucket(bucket)
+            .grant_read("uri=\"http://acs.amazonaws.com/groups/global/AllUsers\"")
+            .send()
+            .await?;
+        assert_eq!(anonymous_get().await?, 200);
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+        assert_eq!(anonymous_get().await?, 403);
+    }
+
+    {
+        // the stored ACLs publish the buckets when a file system is created
+        c.create_bucket()
+            .bucket(bucket)
+            .acl(BucketCannedAcl::PublicRead)
+            .send()
+            .await?;
+        let public_buckets = s3s::auth::PublicBuckets::new();
+        let _ = FileSystem::builder(&root)
+            .public_buckets(public_buckets.clone())
+            .build()
+            .unwrap();
+        assert!(public_buckets.contains(bucket));
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_object_acl() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-object-acl-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let auth = SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key());
+        let fs = FileSystem::builder(&root)
+            .public_objects(auth.public_objects().clone())
+            .build()
+            .unwrap();
+        let mut b = S3ServiceBuilder::new(fs);
+        b.set_auth(auth);
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred.clone())
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+    let bucket = "test-object-acl";
+    let key = "sample.txt";
+
+    let anonymous_get = || {
+        let req = hyper::Request::builder()
+            .method("GET")
+            .uri(format!("http://{DOMAIN_NAME}/{bucket}/{key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let service = &service;
+        async move { service.as_ref().call(req).await.map(|res| res.status()) }
+    };
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        // a public object can be read anonymously even if the bucket is private
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::PublicRead)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        assert_eq!(anonymous_get().await?, 200);
+
+        let acl = c.get_object_acl().bucket(bucket).key(key).send().await?;
+        assert_eq!(acl.owner().and_then(|o| o.id()), Some(cred.access_key_id()));
+        let uris: Vec<_> = acl.grants().unwrap().iter().filter_map(|g| g.grantee()?.uri()).collect();
+        assert_eq!(uris, ["http://acs.amazonaws.com/groups/global/AllUsers"]);
+    }
+
+    {
+        // changing the ACL keeps the data
+        let before = c.head_object().bucket(bucket).key(key).send().await?;
+        c.put_object_acl()
+            .bucket(bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::Private)
+            .send()
+            .await?;
+        let after = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(before.e_tag(), after.e_tag());
+        assert_eq!(before.last_modified(), after.last_modified());
+        assert_eq!(anonymous_get().await?, 403);
+
+        let acl = c.get_object_acl().bucket(bucket).key(key).send().await?;
+        assert_eq!(acl.grants().unwrap().len(), 1);
+    }
+
+    {
+        // the stored ACLs publish the objects when a file system is created
+        c.put_object_acl()
+            .bucket(bucket)
+            .key(key)
+            .grant_read("uri=\"http://acs.amazonaws.com/groups/global/AllUsers\"")
+            .send()
+            .await?;
+        assert_eq!(anonymous_get().await?, 200);
+
+        let public_objects = s3s::auth::PublicObjects::new();
+        let _ = FileSystem::builder(&root)
+            .public_objects(public_objects.clone())
+            .build()
+            .unwrap();
+        assert!(public_objects.contains(bucket, key));
+
+        // overwriting a public object without an ACL makes it private
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"world"))
+            .send()
+            .await?;
+        assert_eq!(anonymous_get().await?, 403);
+    }
+
+    {
+        c.put_object_acl()
+            .bucket(bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::PublicRead)
+            .send()
+            .await?;
+        assert_eq!(anonymous_get().await?, 200);
+        delete_object(&c, bucket, key).await?;
+        assert_eq!(anonymous_get().await?, 403);
+    }
+
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_lifecycle() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-lifecycle-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::new(&root).unwrap();
+    let sweeper = fs.clone();
+    let c = local_client(fs);
+    let bucket = "test-bucket-lifecycle";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let err = c
+            .get_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchLifecycleConfiguration"));
+    }
+
+    {
+        let rule = LifecycleRule::builder()
+            .id("not-midnight")
+            .filter(LifecycleRuleFilter::Prefix("logs/".to_owned()))
+            .status(ExpirationStatus::Enabled)
+            .expiration(
+                LifecycleExpiration::builder()
+                    .date(DateTime::from_secs(1_577_836_801))
+                    .build(),
+            )
+            .build();
+        let config = BucketLifecycleConfiguration::builder().rules(rule).build();
+        let err = c
+            .put_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .lifecycle_configuration(config)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        let expire_logs = LifecycleRule::builder()
+            .id("expire-logs")
+            .filter(LifecycleRuleFilter::Prefix("logs/".to_owned()))
+            .status(ExpirationStatus::Enabled)
+            .expiration(
+                LifecycleExpiration::builder()
+                    .date(DateTime::from_secs(1_577_836_800))
+                    .build(),
+            )
+            .build();
+        let disabled = LifecycleRule::builder()
+            .id("disabled")
+            .filter(LifecycleRuleFilter::Prefix(String::new()))
+            .status(ExpirationStatus::Disabled)
+            .expiration(LifecycleExpiration::builder().days(1).build())
+            .abort_incomplete_multipart_upload(AbortIncompleteMultipartUpload::builder().days_after_initiation(1).build())
+            .build();
+        let config = BucketLifecycleConfiguration::builder()
+            .rules(expire_logs)
+            .rules(disabled)
+            .build();
+        c.put_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .lifecycle_configuration(config)
+            .send()
+            .await?;
+
+        let ans = c.get_bucket_lifecycle_configuration().bucket(bucket).send().await?;
+        let rules = ans.rules().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].id(), Some("expire-logs"));
+        assert_eq!(rules[0].filter(), Some(&LifecycleRuleFilter::Prefix("logs/".to_owned())));
+        let expiration = rules[0].expiration().unwrap();
+        assert_eq!(expiration.date().map(DateTime::secs), Some(1_577_836_800));
+        assert_eq!(rules[1].status(), Some(&ExpirationStatus::Disabled));
+        assert_eq!(rules[1].abort_incomplete_multipart_upload().unwrap().days_after_initiation(), 1);
+    }
+
+    {
+        for key in ["logs/a.txt", "logs/b.txt", "data.txt"] {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from_static(b"lifecycle"))
+                .send()
+                .await?;
+        }
+
+        // a read-only file system does not expire anything
+        let read_only = FileSystem::new_read_only(&root).unwrap();
+        assert!(read_only.apply_lifecycle().await.is_err());
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert_eq!(ans.contents().unwrap().len(), 3);
+
+        assert_eq!(sweeper.apply_lifecycle().await.unwrap(), 2);
+        assert_eq!(sweeper.apply_lifecycle().await.unwrap(), 0);
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let keys: Vec<_> = ans.contents().unwrap_or_default().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, ["data.txt"]);
+    }
+
+    {
+        c.delete_bucket_lifecycle().bucket(bucket).send().await?;
+        let err = c
+            .get_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("NoSuchLifecycleConfiguration"));
+    }
+
+    delete_object(&c, bucket, "data.txt").await?;
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_e_tag_quoting() -> Result<()> {
+    let c = Client::new(config());
+    let bucket = format!("test-e-tag-quoting-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "sample.txt";
+    let copy_key = "copy.txt";
+    let multipart_key = "multipart.bin";
+
+    // MD5 of "hello"
+    let e_tag = "\"5d41402abc4b2a76b9719d911017c592\"";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(e_tag));
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag));
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag));
+
+        let ans = c
+            .copy_object()
+            .bucket(bucket)
+            .key(copy_key)
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await?;
+        assert_eq!(ans.copy_object_result().and_then(|r| r.e_tag()), Some(e_tag));
+    }
+
+    let multipart_e_tag = {
+        let part = vec![b'a'; 5 * 1024 * 1024];
+        let ans = c.create_multipart_upload().bucket(bucket).key(multipart_key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(multipart_key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from(part))
+            .send()
+            .await?;
+        let part_e_tag = ans.e_tag().unwrap().to_owned();
+        assert!(part_e_tag.len() == 34 && part_e_tag.starts_with('"') && part_e_tag.ends_with('"'));
+
+        let ans = c
+            .list_parts()
+            .bucket(bucket)
+            .key(multipart_key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+        assert_eq!(ans.parts().unwrap()[0].e_tag(), Some(part_e_tag.as_str()));
+
+        let upload = CompletedMultipartUpload::builder()
+            .parts(CompletedPart::builder().part_number(1).e_tag(part_e_tag).build())
+            .build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(multipart_key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        let e_tag = ans.e_tag().unwrap().to_owned();
+        assert!(e_tag.starts_with('"') && e_tag.ends_with("-1\""), "{e_tag}");
+
+        let ans = c.head_object().bucket(bucket).key(multipart_key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        e_tag
+    };
+
+    {
+        let expected = [(copy_key, e_tag), (multipart_key, multipart_e_tag.as_str()), (key, e_tag)];
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let e_tags: Vec<_> = ans
+            .contents()
+            .unwrap()
+            .iter()
+            .map(|o| (o.key().unwrap(), o.e_tag().unwrap()))
+            .collect();
+        assert_eq!(e_tags, expected);
+
+        let ans = c.list_objects().bucket(bucket).send().await?;
+        let e_tags: Vec<_> = ans
+            .contents()
+            .unwrap()
+            .iter()
+            .map(|o| (o.key().unwrap(), o.e_tag().unwrap()))
+            .collect();
+        assert_eq!(e_tags, expected);
+    }
+
+    for key in [key, copy_key, multipart_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_non_ascii_metadata() -> Result<()> {
+    let c = Client::new(config());
+    let bucket = format!("test-non-ascii-metadata-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "menu.txt";
+    let raw_key = "raw.txt";
+
+    // "café", encoded as a MIME encoded-word
+    let encoded = "=?UTF-8?B?Y2Fmw6k=?=";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .metadata("title", encoded)
+            .metadata("owner", "data team")
+            .body(ByteStream::from_static(b"menu"))
+            .send()
+            .await?;
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        let metadata = head.metadata().unwrap();
+        assert_eq!(metadata["title"], encoded);
+        assert_eq!(metadata["owner"], "data team");
+    }
+
+    {
+        let service = S3ServiceBuilder::new(FileSystem::new(FS_ROOT).unwrap()).build();
+        let put = |key: &str, value: &[u8]| {
+            hyper::Request::builder()
+                .method("PUT")
+                .uri(format!("http://localhost/{bucket}/{key}"))
+                .header("x-amz-meta-title", hyper::header::HeaderValue::from_bytes(value).unwrap())
+                .body(s3s::Body::from(bytes::Bytes::from_static(b"raw")))
+                .unwrap()
+        };
+
+        // UTF-8 values which are sent as is are stored as they are and encoded in responses
+        let res = service.call(put(raw_key, "東京".as_bytes())).await?;
+        assert_eq!(res.status(), 200);
+
+        let req = hyper::Request::builder()
+            .method("HEAD")
+            .uri(format!("http://localhost/{bucket}/{raw_key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let res = service.call(req).await?;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["x-amz-meta-title"], "=?UTF-8?B?5p2x5Lqs?=");
+
+        let req = hyper::Request::builder()
+            .method("GET")
+            .uri(format!("http://localhost/{bucket}/{key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let res = service.call(req).await?;
+        assert_eq!(res.headers()["x-amz-meta-title"], encoded);
+
+        // control characters are rejected, including encoded ones
+        let res = service.call(put("control.txt", b"=?UTF-8?B?YQpi?=")).await?;
+        assert_eq!(res.status(), 400);
+        let res = service.call(put("control.txt", b"=?UTF-8?Q?a=00b?=")).await?;
+        assert_eq!(res.status(), 400);
+    }
+
+    for key in [key, raw_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_bucket_ownership_controls() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-ownership-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let cred = Credentials::for_tests();
+    let service = {
+        let auth = SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key());
+        let fs = FileSystem::builder(&root)
+            .public_objects(auth.public_objects().clone())
+            .build()
+            .unwrap();
+        let mut b = S3ServiceBuilder::new(fs);
+        b.set_auth(auth);
+        b.build().into_shared()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred.clone())
+        .http_connector(s3s_aws::Connector::from(service.clone()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    let c = Client::from_conf(conf);
+    let bucket = "test-ownership";
+    let key = "public.txt";
+    let other_key = "other.txt";
+
+    let anonymous_get = |key: &str| {
+        let req = hyper::Request::builder()
+            .method("GET")
+            .uri(format!("http://{DOMAIN_NAME}/{bucket}/{key}"))
+            .body(s3s::Body::empty())
+            .unwrap();
+        let service = &service;
+        async move { service.as_ref().call(req).await.map(|res| res.status()) }
+    };
+    let location = || {
+        CreateBucketConfiguration::builder()
+            .location_constraint(BucketLocationConstraint::from(REGION))
+            .build()
+    };
+    let ownership_controls = |ownership: ObjectOwnership| {
+        let rule = OwnershipControlsRule::builder().object_ownership(ownership).build();
+        OwnershipControls::builder().rules(rule).build()
+    };
+
+    {
+        // a bucket which disables ACLs can not be created with an ACL
+        let err = c
+            .create_bucket()
+            .bucket(bucket)
+            .create_bucket_configuration(location())
+            .object_ownership(ObjectOwnership::BucketOwnerEnforced)
+            .acl(BucketCannedAcl::PublicRead)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidBucketAclWithObjectOwnership"));
+        assert!(c.head_bucket().bucket(bucket).send().await.is_err());
+    }
+
+    {
+        c.create_bucket()
+            .bucket(bucket)
+            .create_bucket_configuration(location())
+            .object_ownership(ObjectOwnership::ObjectWriter)
+            .send()
+            .await?;
+
+        let ans = c.get_bucket_ownership_controls().bucket(bucket).send().await?;
+        let rules = ans.ownership_controls().unwrap().rules().unwrap();
+        assert_eq!(rules[0].object_ownership(), Some(&ObjectOwnership::ObjectWriter));
+
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::PublicRead)
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        assert_eq!(anonymous_get(key).await?, 200);
+    }
+
+    {
+        c.put_bucket_ownership_controls()
+            .bucket(bucket)
+            .ownership_controls(ownership_controls(ObjectOwnership::BucketOwnerEnforced))
+            .send()
+            .await?;
+
+        // the stored ACL is ignored
+        assert_eq!(anonymous_get(key).await?, 403);
+
+        let err = c
+            .put_object_acl()
+            .bucket(bucket)
+            .key(key)
+            .acl(ObjectCannedAcl::PublicRead)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(http_status(&err), Some(400));
+        assert_eq!(err.into_service_error().meta().code(), Some("AccessControlListNotSupported"));
+
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key(other_key)
+            .acl(ObjectCannedAcl::PublicRead)
+            .body(ByteStream::from_static(b"world"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("AccessControlListNotSupported"));
+
+        let err = c
+            .put_bucket_acl()
+            .bucket(bucket)
+            .acl(BucketCannedAcl::PublicRead)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("AccessControlListNotSupported"));
+
+        // the ACL of the bucket owner is the same as no ACL
+        c.put_object()
+            .bucket(bucket)
+            .key(other_key)
+            .acl(ObjectCannedAcl::BucketOwnerFullControl)
+            .body(ByteStream::from_static(b"world"))
+            .send()
+            .await?;
+        c.get_object_acl().bucket(bucket).key(key).send().await?;
+
+        let err = c
+            .put_bucket_ownership_controls()
+            .bucket(bucket)
+            .ownership_controls(ownership_controls(ObjectOwnership::from("Nobody")))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        c.delete_bucket_ownership_controls().bucket(bucket).send().await?;
+
+        let err = c.get_bucket_ownership_controls().bucket(bucket).send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        assert_eq!(err.into_service_error().meta().code(), Some("OwnershipControlsNotFoundError"));
+
+        // the stored ACL is enforced again
+        assert_eq!(anonymous_get(key).await?, 200);
+    }
+
+    for key in [key, other_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    {
+        assert!(FileSystem::builder(&root).default_object_ownership("Nobody").build().is_err());
+
+        let fs = FileSystem::builder(&root)
+            .default_object_ownership(ObjectOwnership::BucketOwnerEnforced.as_str())
+            .build()
+            .unwrap();
+        let c = local_client(fs);
+        create_bucket(&c, bucket).await?;
+
+        let ans = c.get_bucket_ownership_controls().bucket(bucket).send().await?;
+        let rules = ans.ownership_controls().unwrap().rules().unwrap();
+        assert_eq!(rules[0].object_ownership(), Some(&ObjectOwnership::BucketOwnerEnforced));
+
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+/// Stores the object `a/b` at `data/a+b` in the bucket directory
+#[derive(Debug)]
+struct FlatKeyMapper;
+
+impl s3s_fs::KeyMapper for FlatKeyMapper {
+    fn object_path(&self, _: &str, key: &str) -> std::path::PathBuf {
+        if key == "escape" {
+            return "../escaped".into();
+        }
+        Path::new("data").join(key.replace('/', "+"))
+    }
+
+    fn object_key(&self, _: &str, path: &Path) -> Option<String> {
+        let name = path.strip_prefix("data").ok()?.to_str()?;
+        Some(name.replace('+', "/"))
+    }
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_key_mapper() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-key-mapper-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::builder(&root).key_mapper(FlatKeyMapper).build().unwrap();
+    let c = local_client(fs);
+    let bucket = "test-key-mapper";
+    let key = "reports/2023/summary.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .metadata("owner", "data team")
+            .body(ByteStream::from_static(b"summary"))
+            .send()
+            .await?;
+        let content = fs::read(format!("{root}/{bucket}/data/reports+2023+summary.txt"))?;
+        assert_eq!(content, b"summary");
+
+        let head = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(head.metadata().unwrap()["owner"], "data team");
+    }
+
+    {
+        // existing files are served with the keys of the mapper
+        fs::write(format!("{root}/{bucket}/data/legacy+notes.txt"), b"notes")?;
+        fs::create_dir_all(format!("{root}/{bucket}/unmapped"))?;
+        fs::write(format!("{root}/{bucket}/unmapped/hidden.txt"), b"hidden")?;
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let keys: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, ["legacy/notes.txt", key]);
+
+        let ans = c.list_objects_v2().bucket(bucket).prefix("legacy/").send().await?;
+        let keys: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, ["legacy/notes.txt"]);
+
+        let ans = c.get_object().bucket(bucket).key("legacy/notes.txt").send().await?;
+        assert_eq!(ans.body.collect().await?.into_bytes().as_ref(), b"notes");
+    }
+
+    {
+        // paths outside of the bucket directory are rejected
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key("escape")
+            .body(ByteStream::from_static(b"escape"))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+        assert!(fs::metadata(format!("{root}/escaped")).is_err());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_escaped_key_mapper() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-escaped-key-mapper-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::builder(&root)
+        .key_mapper(s3s_fs::EscapedKeyMapper)
+        .build()
+        .unwrap();
+    let c = local_client(fs);
+    let bucket = "test-escaped-key-mapper";
+
+    create_bucket(&c, bucket).await?;
+
+    let mut keys = vec![
+        "control\u{1}\u{1f}/tab\tkey",
+        "nul\0key",
+        "space key ",
+        "trailing.",
+        ".hidden",
+        "100%.txt",
+        "a//b",
+        "dir/",
+        "unicode/日本語/ü.txt",
+        "windows<>:\"|?*\\.txt",
+        "/leading",
+        "/",
+        ".",
+        "..",
+        "dots/./and/../segments",
+        "marker/.s3s-directory-marker",
+        // keys which are prefixes of each other
+        "nested",
+        "nested/",
+        "nested/key",
+    ];
+
+    // keys whose names would be longer than a file name, and the longest key
+    let long_unicode = format!("unicode/{}", "é".repeat(100));
+    let long_key = format!("long/{}", "x".repeat(1019));
+    keys.extend([long_unicode.as_str(), long_key.as_str()]);
+
+    // directory markers are empty
+    let content = |key: &str| {
+        if key.ends_with('/') {
+            Vec::new()
+        } else {
+            key.as_bytes().to_vec()
+        }
+    };
+
+    {
+        for key in &keys {
+            c.put_object()
+                .bucket(bucket)
+                .key(*key)
+                .body(ByteStream::from(content(key)))
+                .send()
+                .await?;
+        }
+        for name in [
+            "100%25.txt",
+            "%2Ehidden",
+            "space key%20",
+            "trailing%2E",
+            "a%2F%2Fb",
+            "dir%2F",
+            "unicode%2F%E6%97%A5%E6%9C%AC%E8%AA%9E%2F%C3%BC.txt",
+            "%2Fleading",
+            "%2F",
+            "%2E",
+            "%2E%2E",
+            "dots%2F.%2Fand%2F..%2Fsegments",
+            "marker%2F.s3s-directory-marker",
+            "nested",
+            "nested%2F",
+            "nested%2Fkey",
+        ] {
+            assert!(fs::metadata(format!("{root}/{bucket}/{name}"))?.is_file(), "{name:?}");
+        }
+
+        // the directories of a long key have the first parts of its name
+        let x = |n: usize| "x".repeat(n);
+        let long_path = format!("long%2F{}%/{}%/{}%/{}%/{}", x(247), x(254), x(254), x(9), x(255));
+        assert!(fs::metadata(format!("{root}/{bucket}/{long_path}"))?.is_file());
+    }
+
+    {
+        // the keys are decoded back exactly
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .encoding_type(EncodingType::Url)
+            .send()
+            .await?;
+        let listed: Vec<_> = ans
+            .contents()
+            .unwrap()
+            .iter()
+            .map(|o| urlencoding::decode(o.key().unwrap()).unwrap().into_owned())
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(listed, keys);
+
+        let ans = c.list_objects_v2().bucket(bucket).prefix("unicode/").send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(listed, [long_unicode.as_str(), "unicode/日本語/ü.txt"]);
+
+        let ans = c.list_objects_v2().bucket(bucket).prefix("nested").send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(listed, ["nested", "nested/", "nested/key"]);
+
+        // the directories of a long key are listed by the prefix of the key
+        let prefix = format!("long/{}", "x".repeat(300));
+        let ans = c.list_objects_v2().bucket(bucket).prefix(prefix).send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(listed, [long_key.as_str()]);
+
+        for key in &keys {
+            let ans = c.get_object().bucket(bucket).key(*key).send().await?;
+            assert_eq!(ans.body.collect().await?.into_bytes().as_ref(), content(key), "{key:?}");
+        }
+    }
+
+    {
+        // deleting a key which ends with `/` keeps the keys which it is a prefix of
+        delete_object(&c, bucket, "nested/").await?;
+        let ans = c.list_objects_v2().bucket(bucket).prefix("nested").send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(listed, ["nested", "nested/key"]);
+
+        // the directory of the bucket is not a directory of the key
+        let bucket = "test-escaped-key-mapper-dir";
+        create_bucket(&c, bucket).await?;
+        c.put_object().bucket(bucket).key("dir/").send().await?;
+        delete_object(&c, bucket, "dir/").await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    {
+        // files whose names are not escaped are not objects
+        fs::write(format!("{root}/{bucket}/unescaped%41"), b"unescaped")?;
+        let ans = c.list_objects_v2().bucket(bucket).prefix("unescaped").send().await?;
+        assert!(ans.contents().unwrap_or_default().is_empty());
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_read_buffer_size() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-read-buffer-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::builder(&root).read_buffer_size(1000).build().unwrap();
+    let c = local_client(fs);
+    let bucket = "test-read-buffer-size";
+    let key = "large.bin";
+    let copy_key = "copy.bin";
+
+    #[allow(clippy::cast_possible_truncation)]
+    let content: Vec<u8> = (0..3 * 1024 * 1024_u32).map(|i| (i % 251) as u8).collect();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(content.clone()))
+            .send()
+            .await?;
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.content_length(), 3 * 1024 * 1024);
+        assert!(ans.body.collect().await?.into_bytes() == content);
+
+        let ans = c.get_object().bucket(bucket).key(key).range("bytes=1500-2499").send().await?;
+        assert_eq!(ans.content_length(), 1000);
+        assert_eq!(ans.body.collect().await?.into_bytes().as_ref(), &content[1500..2500]);
+
+        c.copy_object()
+            .bucket(bucket)
+            .key(copy_key)
+            .copy_source(format!("{bucket}/{key}"))
+            .send()
+            .await?;
+        let ans = c.get_object().bucket(bucket).key(copy_key).send().await?;
+        assert!(ans.body.collect().await?.into_bytes() == content);
+    }
+
+    for key in [key, copy_key] {
+        delete_object(&c, bucket, key).await?;
+    }
+    delete_bucket(&c, bucket).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_hash_buffer_size() -> Result<()> {
+    use md5::{Digest, Md5};
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-hash-buffer-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::builder(&root).hash_buffer_size(7).build().unwrap();
+    let c = local_client(fs);
+    let bucket = "test-hash-buffer-size";
+    let key = "hashed.txt";
+    let encode = |s: &str| base64_simd::URL_SAFE_NO_PAD.encode_to_string(s);
+    let info_path = format!("{root}/.bucket-{}.object-{}.internal.json", encode(bucket), encode(key));
+
+    let content = b"hashed in small chunks".repeat(50);
+    let e_tag = format!("\"{}\"", hex_simd::encode_to_string(Md5::digest(&content), hex_simd::AsciiCase::Lower));
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from(content.clone()))
+            .send()
+            .await?;
+
+        // the missing entity tag is computed from the data
+        let mut info: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&fs::read(&info_path)?)?;
+        info.remove("e_tag");
+        fs::write(&info_path, serde_json::to_vec(&info)?)?;
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_part_verify_concurrency() -> Result<()> {
+    use md5::{Digest, Md5};
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-part-verify-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let fs = FileSystem::builder(&root)
+        .min_part_size(0)
+        .part_verify_concurrency(2)
+        .build()
+        .unwrap();
+    let c = local_client(fs);
+    let bucket = "test-part-verify-concurrency";
+    let key = "many-parts.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let part_contents: Vec<Vec<u8>> = (1..=6).map(|i| format!("part {i},").repeat(i).into_bytes()).collect();
+
+    let upload = |parts: Vec<Vec<u8>>| {
+        let c = &c;
+        async move {
+            let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+            let upload_id = ans.upload_id.unwrap();
+            let mut completed_parts = Vec::new();
+            for (part_number, content) in (1..).zip(parts) {
+                let ans = c
+                    .upload_part()
+                    .bucket(bucket)
+                    .key(key)
+                    .upload_id(&upload_id)
+                    .part_number(part_number)
+                    .body(ByteStream::from(content))
+                    .send()
+                    .await?;
+                let part = CompletedPart::builder()
+                    .e_tag(ans.e_tag().unwrap())
+                    .part_number(part_number)
+                    .build();
+                completed_parts.push(part);
+            }
+            anyhow::Ok((upload_id, CompletedMultipartUpload::builder().set_parts(Some(completed_parts)).build()))
+        }
+    };
+
+    {
+        let (upload_id, completed) = upload(part_contents.clone()).await?;
+
+        // the parts which are not recorded are hashed
+        let info_path = format!("{root}/.upload-{upload_id}.json");
+        let mut info: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&fs::read(&info_path)?)?;
+        let parts = info["parts"].as_object_mut().unwrap();
+        parts.remove("2");
+        parts.remove("5");
+        fs::write(&info_path, serde_json::to_vec(&info)?)?;
+
+        let mut md5_of_md5s = Md5::new();
+        for content in &part_contents {
+            md5_of_md5s.update(Md5::digest(content));
+        }
+        let expected = format!("\"{}-6\"", hex_simd::encode_to_string(md5_of_md5s.finalize(), hex_simd::AsciiCase::Lower));
+
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(completed)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(expected.as_str()));
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), part_contents.concat().as_slice());
+    }
+
+    {
+        let (upload_id, completed) = upload(part_contents.clone()).await?;
+
+        // a staged part which does not match its record is rejected
+        fs::write(format!("{root}/.upload_id-{upload_id}.part-4"), b"truncated")?;
+
+        let result = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .multipart_upload(completed)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("InvalidPart"));
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(&upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_dry_runs() -> Result<()> {
+    use md5::{Digest, Md5};
+    use s3s_fs::DRY_RUN_HEADER;
+
+    fn dry_run<B>(req: &mut hyper::Request<B>) {
+        req.headers_mut()
+            .insert(DRY_RUN_HEADER, hyper::header::HeaderValue::from_static("true"));
+    }
+
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-dry-runs-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::builder(&root).dry_runs(true).build().unwrap());
+    let bucket = "test-dry-runs";
+    let key = "sample.txt";
+    let content = b"hello dry runs";
+    let e_tag = format!("\"{}\"", hex_simd::encode_to_string(Md5::digest(content), hex_simd::AsciiCase::Lower));
+
+    {
+        c.create_bucket()
+            .bucket(bucket)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        assert!(c.head_bucket().bucket(bucket).send().await.is_err());
+
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content))
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("NoSuchBucket"));
+    }
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        // the body is validated but not stored
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content))
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        assert!(c.head_object().bucket(bucket).key(key).send().await.is_err());
+
+        let other_md5 = base64_simd::STANDARD.encode_to_string(Md5::digest(b"something else"));
+        let result = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content))
+            .content_md5(other_md5)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("BadDigest"));
+    }
+
+    c.put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from_static(content))
+        .send()
+        .await?;
+
+    {
+        let ans = c
+            .copy_object()
+            .bucket(bucket)
+            .key("copy.txt")
+            .copy_source(format!("{bucket}/{key}"))
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        assert_eq!(ans.copy_object_result().and_then(|r| r.e_tag()), Some(e_tag.as_str()));
+        assert!(c.head_object().bucket(bucket).key("copy.txt").send().await.is_err());
+
+        c.delete_object()
+            .bucket(bucket)
+            .key(key)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        c.head_object().bucket(bucket).key(key).send().await?;
+
+        let result = c
+            .delete_bucket()
+            .bucket(bucket)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("BucketNotEmpty"));
+    }
+
+    {
+        // operations which do not support dry runs reject them
+        let tagging = Tagging::builder().tag_set(Tag::builder().key("k").value("v").build()).build();
+        let result = c
+            .put_object_tagging()
+            .bucket(bucket)
+            .key(key)
+            .tagging(tagging)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("NotImplemented"));
+        let ans = c.get_object_tagging().bucket(bucket).key(key).send().await?;
+        assert!(ans.tag_set().unwrap_or_default().is_empty());
+    }
+
+    {
+        // the parts are checked like in a real completion
+        let mp_key = "multipart.txt";
+        let ans = c.create_multipart_upload().bucket(bucket).key(mp_key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(content))
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        assert_eq!(ans.e_tag(), Some(e_tag.as_str()));
+        let ans = c.list_parts().bucket(bucket).key(mp_key).upload_id(upload_id).send().await?;
+        assert!(ans.parts().unwrap_or_default().is_empty());
+
+        let mut parts = Vec::new();
+        for part_number in 1..=2 {
+            let ans = c
+                .upload_part()
+                .bucket(bucket)
+                .key(mp_key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from_static(content))
+                .send()
+                .await?;
+            parts.push(
+                CompletedPart::builder()
+                    .e_tag(ans.e_tag().unwrap())
+                    .part_number(part_number)
+                    .build(),
+            );
+        }
+        let upload = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+        let result = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("EntityTooSmall"));
+
+        c.abort_multipart_upload()
+            .bucket(bucket)
+            .key(mp_key)
+            .upload_id(upload_id)
+            .send()
+            .await?;
+    }
+
+    {
+        // dry runs are rejected unless they are enabled
+        let c = local_client(FileSystem::new(&root).unwrap());
+        let result = c
+            .delete_object()
+            .bucket(bucket)
+            .key(key)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("InvalidRequest"));
+        c.head_object().bucket(bucket).key(key).send().await?;
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        c.delete_bucket()
+            .bucket(bucket)
+            .customize()
+            .await?
+            .mutate_request(dry_run)
+            .send()
+            .await?;
+        c.head_bucket().bucket(bucket).send().await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_version_id_headers() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-version-id-headers-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "sample.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    let put = |content: &'static str| {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(content.as_bytes()))
+            .send()
+    };
+
+    {
+        // objects of unversioned buckets have no version id
+        let ans = put("unversioned").await?;
+        assert!(ans.version_id().is_none());
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert!(ans.version_id().is_none());
+    }
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+
+        // the object written before versioning is the null version
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.version_id(), Some("null"));
+    }
+
+    let v1 = put("version 1").await?.version_id().unwrap().to_owned();
+    assert_ne!(v1, "null");
+
+    {
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.version_id(), Some(v1.as_str()));
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.version_id(), Some(v1.as_str()));
+
+        let ans = c.get_object().bucket(bucket).key(key).version_id("null").send().await?;
+        assert_eq!(ans.version_id(), Some("null"));
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), b"unversioned");
+    }
+
+    {
+        let ans = c
+            .copy_object()
+            .bucket(bucket)
+            .key("copy.txt")
+            .copy_source(format!("{bucket}/{key}?versionId=null"))
+            .send()
+            .await?;
+        assert_eq!(ans.copy_source_version_id(), Some("null"));
+        let copy_version_id = ans.version_id().unwrap();
+        assert_ne!(copy_version_id, v1);
+
+        let ans = c.head_object().bucket(bucket).key("copy.txt").send().await?;
+        assert_eq!(ans.version_id(), Some(copy_version_id));
+    }
+
+    {
+        let ans = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+        let upload_id = ans.upload_id().unwrap();
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(1)
+            .body(ByteStream::from_static(b"version 2"))
+            .send()
+            .await?;
+        let part = CompletedPart::builder().e_tag(ans.e_tag().unwrap()).part_number(1).build();
+        let upload = CompletedMultipartUpload::builder().parts(part).build();
+        let ans = c
+            .complete_multipart_upload()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .multipart_upload(upload)
+            .send()
+            .await?;
+        let v2 = ans.version_id().unwrap();
+        assert_ne!(v2, v1);
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.version_id(), Some(v2));
+    }
+
+    {
+        let ans = c.delete_object().bucket(bucket).key(key).send().await?;
+        assert!(ans.delete_marker());
+        let marker = ans.version_id().unwrap().to_owned();
+
+        // deleting a delete marker removes it
+        let ans = c.delete_object().bucket(bucket).key(key).version_id(&marker).send().await?;
+        assert!(ans.delete_marker());
+        assert_eq!(ans.version_id(), Some(marker.as_str()));
+
+        let ans = c.delete_object().bucket(bucket).key(key).version_id(&v1).send().await?;
+        assert!(ans.delete_marker().not());
+        assert_eq!(ans.version_id(), Some(v1.as_str()));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_list_object_versions() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-list-object-versions-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+    }
+
+    let put = |key: &'static str, content: &'static str| {
+        let c = c.clone();
+        async move {
+            let body = ByteStream::from_static(content.as_bytes());
+            let ans = c.put_object().bucket(bucket).key(key).body(body).send().await?;
+            anyhow::Ok(ans.version_id().unwrap().to_owned())
+        }
+    };
+
+    let a1 = put("a.txt", "a1").await?;
+    let a2 = put("a.txt", "a2").await?;
+    let b1 = put("b.txt", "b1").await?;
+    let b_marker = {
+        let ans = c.delete_object().bucket(bucket).key("b.txt").send().await?;
+        ans.version_id().unwrap().to_owned()
+    };
+    let c1 = put("dir/c.txt", "c1").await?;
+
+    {
+        let ans = c.list_object_versions().bucket(bucket).send().await?;
+        assert!(ans.is_truncated().not());
+
+        let versions: Vec<_> = ans
+            .versions()
+            .unwrap()
+            .iter()
+            .map(|v| (v.key().unwrap(), v.version_id().unwrap(), v.is_latest()))
+            .collect();
+        let expected = [
+            ("a.txt", a2.as_str(), true),
+            ("a.txt", a1.as_str(), false),
+            ("b.txt", b1.as_str(), false),
+            ("dir/c.txt", c1.as_str(), true),
+        ];
+        assert_eq!(versions, expected);
+
+        let version = &ans.versions().unwrap()[0];
+        assert_eq!(version.size(), 2);
+        assert!(version.e_tag().is_some());
+
+        let markers: Vec<_> = ans
+            .delete_markers()
+            .unwrap()
+            .iter()
+            .map(|m| (m.key().unwrap(), m.version_id().unwrap(), m.is_latest()))
+            .collect();
+        assert_eq!(markers, [("b.txt", b_marker.as_str(), true)]);
+    }
+
+    {
+        let ans = c.list_object_versions().bucket(bucket).delimiter("/").send().await?;
+        let prefixes: Vec<_> = ans.common_prefixes().unwrap().iter().map(|p| p.prefix().unwrap()).collect();
+        assert_eq!(prefixes, ["dir/"]);
+        assert_eq!(ans.versions().unwrap().len(), 3);
+
+        let ans = c.list_object_versions().bucket(bucket).prefix("dir/").send().await?;
+        assert_eq!(ans.versions().unwrap().len(), 1);
+        assert!(ans.delete_markers().is_none());
+    }
+
+    {
+        // pages continue after the last returned version
+        let mut pages = Vec::new();
+        let mut markers: Option<(String, Option<String>)> = None;
+        loop {
+            let mut req = c.list_object_versions().bucket(bucket).max_keys(2);
+            if let Some((key_marker, version_id_marker)) = markers.take() {
+                req = req.key_marker(key_marker).set_version_id_marker(version_id_marker);
+            }
+            let ans = req.send().await?;
+            let mut page: Vec<String> = ans
+                .versions()
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.version_id().unwrap().to_owned())
+                .collect();
+            page.extend(
+                ans.delete_markers()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|m| m.version_id().unwrap().to_owned()),
+            );
+            pages.push(page);
+            if ans.is_truncated().not() {
+                break;
+            }
+            markers = Some((
+                ans.next_key_marker().unwrap().to_owned(),
+                ans.next_version_id_marker().map(ToOwned::to_owned),
+            ));
+        }
+        assert_eq!(
+            pages,
+            [
+                vec![a2.clone(), a1.clone()],
+                vec![b1.clone(), b_marker.clone()],
+                vec![c1.clone()]
+            ]
+        );
+    }
+
+    {
+        let result = c.list_object_versions().bucket(bucket).version_id_marker(&a1).send().await;
+        assert_eq!(result.unwrap_err().into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_max_keys() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-max-keys-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::builder(&root).max_keys(2).build().unwrap());
+    let bucket = "test-max-keys";
+
+    create_bucket(&c, bucket).await?;
+    for key in ["a.txt", "b.txt", "c.txt"] {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"abc"))
+            .send()
+            .await?;
+    }
+
+    {
+        // the maximum applies when max-keys is omitted
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        assert_eq!(ans.max_keys(), 2);
+        assert_eq!(ans.key_count(), 2);
+        assert!(ans.is_truncated());
+
+        // a larger max-keys is clamped
+        let ans = c.list_objects_v2().bucket(bucket).max_keys(10).send().await?;
+        assert_eq!(ans.max_keys(), 2);
+        assert_eq!(ans.key_count(), 2);
+
+        let ans = c.list_objects().bucket(bucket).max_keys(10).send().await?;
+        assert_eq!(ans.max_keys(), 2);
+        assert_eq!(ans.contents().unwrap_or_default().len(), 2);
+
+        let ans = c.list_object_versions().bucket(bucket).max_keys(10).send().await?;
+        assert_eq!(ans.max_keys(), 2);
+        assert!(ans.is_truncated());
+
+        let ans = c.list_objects_v2().bucket(bucket).max_keys(1).send().await?;
+        assert_eq!(ans.max_keys(), 1);
+        assert_eq!(ans.key_count(), 1);
+    }
+
+    {
+        // the default maximum is 1000 like AWS
+        let c = Client::new(config());
+        let bucket = format!("test-max-keys-{}", Uuid::new_v4());
+        create_bucket(&c, &bucket).await?;
+
+        let ans = c.list_objects_v2().bucket(&bucket).send().await?;
+        assert_eq!(ans.max_keys(), 1000);
+        let ans = c.list_objects_v2().bucket(&bucket).max_keys(5000).send().await?;
+        assert_eq!(ans.max_keys(), 1000);
+
+        delete_bucket(&c, &bucket).await?;
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_listing_order() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-listing-order-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+    let bucket = "test-listing-order";
+
+    // the keys are written out of order, with pauses so that their modification times differ
+    let keys = ["c.txt", "dir/x.txt", "a.txt", "b.txt", "dir/y.txt"];
+    {
+        let c = local_client(FileSystem::new(&root).unwrap());
+        create_bucket(&c, bucket).await?;
+        for key in keys {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from_static(b"abc"))
+                .send()
+                .await?;
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+    }
+
+    // returns the keys and the common prefixes of the pages of two rows
+    let list_pages = |c: Client, delimiter: Option<&'static str>| async move {
+        let mut pages = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let ans = c
+                .list_objects_v2()
+                .bucket(bucket)
+                .set_delimiter(delimiter.map(ToOwned::to_owned))
+                .max_keys(2)
+                .set_continuation_token(token.take())
+                .send()
+                .await?;
+            let mut page: Vec<String> = ans
+                .contents()
+                .unwrap_or_default()
+                .iter()
+                .map(|o| o.key().unwrap().to_owned())
+                .collect();
+            page.extend(
+                ans.common_prefixes()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|p| p.prefix().unwrap().to_owned()),
+            );
+            pages.push(page);
+            if ans.is_truncated().not() {
+                break;
+            }
+            token = ans.next_continuation_token().map(ToOwned::to_owned);
+        }
+        anyhow::Ok(pages)
+    };
+
+    {
+        // the default order is ascending by key
+        let c = local_client(FileSystem::new(&root).unwrap());
+        let pages = list_pages(c, None).await?;
+        assert_eq!(pages, [vec!["a.txt", "b.txt"], vec!["c.txt", "dir/x.txt"], vec!["dir/y.txt"]]);
+    }
+
+    {
+        let c = local_client(FileSystem::builder(&root).reverse_listings(true).build().unwrap());
+        let pages = list_pages(c.clone(), None).await?;
+        assert_eq!(pages, [vec!["dir/y.txt", "dir/x.txt"], vec!["c.txt", "b.txt"], vec!["a.txt"]]);
+
+        let pages = list_pages(c.clone(), Some("/")).await?;
+        assert_eq!(pages, [vec!["c.txt", "dir/"], vec!["b.txt", "a.txt"]]);
+
+        // a missing marker skips the keys which sort after it
+        let ans = c.list_objects().bucket(bucket).marker("bb").send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().map(|o| o.key().unwrap()).collect();
+        assert_eq!(listed, ["b.txt", "a.txt"]);
+    }
+
+    {
+        let c = local_client(
+            FileSystem::builder(&root)
+                .listing_order(ListingOrder::LastModified)
+                .build()
+                .unwrap(),
+        );
+        let pages = list_pages(c.clone(), None).await?;
+        assert_eq!(pages, [vec!["c.txt", "dir/x.txt"], vec!["a.txt", "b.txt"], vec!["dir/y.txt"]]);
+
+        // a common prefix is listed at its first object
+        let pages = list_pages(c.clone(), Some("/")).await?;
+        assert_eq!(pages, [vec!["c.txt", "dir/"], vec!["a.txt", "b.txt"]]);
+
+        // the next marker of ListObjects continues the order
+        let ans = c.list_objects().bucket(bucket).max_keys(3).send().await?;
+        assert_eq!(ans.next_marker(), Some("a.txt"));
+        let ans = c.list_objects().bucket(bucket).marker("a.txt").send().await?;
+        let listed: Vec<_> = ans.contents().unwrap().iter().map(|o| o.key().unwrap()).collect();
+        assert_eq!(listed, ["b.txt", "dir/y.txt"]);
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_guess_content_types() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-guess-content-types-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+
+    let c = local_client(FileSystem::builder(&root).guess_content_types(true).build().unwrap());
+    let bucket = "test-guess-content-types";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        // the SDK sends `application/octet-stream` if no content type is set
+        for key in ["assets/data.json", "index.HTML", "noext", ".hidden"] {
+            c.put_object()
+                .bucket(bucket)
+                .key(key)
+                .body(ByteStream::from_static(b"{}"))
+                .customize()
+                .await?
+                .mutate_request(|req| drop(req.headers_mut().remove("content-type")))
+                .send()
+                .await?;
+        }
+        c.put_object()
+            .bucket(bucket)
+            .key("typed.json")
+            .content_type("text/plain")
+            .body(ByteStream::from_static(b"{}"))
+            .send()
+            .await?;
+
+        let cases = [
+            ("assets/data.json", "application/json"),
+            ("index.HTML", "text/html"),
+            ("noext", "application/octet-stream"),
+            (".hidden", "application/octet-stream"),
+            // the stored content type wins
+            ("typed.json", "text/plain"),
+        ];
+        for (key, expected) in cases {
+            let ans = c.get_object().bucket(bucket).key(key).send().await?;
+            assert_eq!(ans.content_type(), Some(expected), "key: {key}");
+            let ans = c.head_object().bucket(bucket).key(key).send().await?;
+            assert_eq!(ans.content_type(), Some(expected), "key: {key}");
+        }
+    }
+
+    {
+        // content types are not guessed by default
+        let c = local_client(FileSystem::new(&root).unwrap());
+        let ans = c.head_object().bucket(bucket).key("assets/data.json").send().await?;
+        assert_eq!(ans.content_type(), Some("application/octet-stream"));
+    }
+
+    {
+        fs::remove_dir_all(&root)?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_directory_markers() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-directory-markers-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key("folder/")
+            .body(ByteStream::from_static(b""))
+            .send()
+            .await?;
+        c.put_object()
+            .bucket(bucket)
+            .key("folder/a.txt")
+            .body(ByteStream::from_static(b"hello"))
+            .send()
+            .await?;
+        c.put_object()
+            .bucket(bucket)
+            .key("folder/sub/")
+            .body(ByteStream::from_static(b""))
+            .send()
+            .await?;
+
+        let ans = c.head_object().bucket(bucket).key("folder/").send().await?;
+        assert_eq!(ans.content_length(), 0);
+        assert_eq!(ans.e_tag(), Some("\"d41d8cd98f00b204e9800998ecf8427e\""));
+
+        let ans = c.get_object().bucket(bucket).key("folder/sub/").send().await?;
+        assert!(ans.body.collect().await?.into_bytes().is_empty());
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let keys: Vec<_> = ans.contents().unwrap_or_default().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, ["folder/", "folder/a.txt", "folder/sub/"]);
+
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix("folder/")
+            .delimiter("/")
+            .send()
+            .await?;
+        let keys: Vec<_> = ans.contents().unwrap_or_default().iter().filter_map(|o| o.key()).collect();
+        let prefixes: Vec<_> = ans
+            .common_prefixes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| p.prefix())
+            .collect();
+        assert_eq!(keys, ["folder/", "folder/a.txt"]);
+        assert_eq!(prefixes, ["folder/sub/"]);
+
+        let ans = c.list_objects().bucket(bucket).delimiter("/").send().await?;
+        let prefixes: Vec<_> = ans
+            .common_prefixes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| p.prefix())
+            .collect();
+        assert_eq!(prefixes, ["folder/"]);
+    }
+
+    {
+        // the directory marker does not remove the objects in the folder
+        delete_object(&c, bucket, "folder/").await?;
+        let err = c.head_object().bucket(bucket).key("folder/").send().await.unwrap_err();
+        assert_eq!(http_status(&err), Some(404));
+        c.head_object().bucket(bucket).key("folder/a.txt").send().await?;
+
+        let err = c
+            .put_object()
+            .bucket(bucket)
+            .key("folder/.s3s-directory-marker")
+            .body(ByteStream::from_static(b""))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.into_service_error().meta().code(), Some("InvalidArgument"));
+    }
+
+    {
+        delete_object(&c, bucket, "folder/sub/").await?;
+        delete_object(&c, bucket, "folder/a.txt").await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_request_payment() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-request-payment-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+    let key = "report.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        let ans = c.get_bucket_request_payment().bucket(bucket).send().await?;
+        assert_eq!(ans.payer(), Some(&Payer::BucketOwner));
+
+        let config = RequestPaymentConfiguration::builder().payer(Payer::Requester).build();
+        c.put_bucket_request_payment()
+            .bucket(bucket)
+            .request_payment_configuration(config)
+            .send()
+            .await?;
+        let ans = c.get_bucket_request_payment().bucket(bucket).send().await?;
+        assert_eq!(ans.payer(), Some(&Payer::Requester));
+    }
+
+    {
+        // the charges are acknowledged by the requests and echoed by the responses
+        let ans = c
+            .put_object()
+            .bucket(bucket)
+            .key(key)
+            .request_payer(RequestPayer::Requester)
+            .body(ByteStream::from_static(b"report"))
+            .send()
+            .await?;
+        assert_eq!(ans.request_charged(), Some(&RequestCharged::Requester));
+
+        let ans = c
+            .get_object()
+            .bucket(bucket)
+            .key(key)
+            .request_payer(RequestPayer::Requester)
+            .send()
+            .await?;
+        assert_eq!(ans.request_charged(), Some(&RequestCharged::Requester));
+
+        let ans = c
+            .list_objects()
+            .bucket(bucket)
+            .request_payer(RequestPayer::Requester)
+            .send()
+            .await?;
+        assert_eq!(ans.request_charged(), Some(&RequestCharged::Requester));
+
+        let ans = c.head_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.request_charged(), None);
+    }
+
     {
         delete_object(&c, bucket, key).await?;
         delete_bucket(&c, bucket).await?;
-        delete_object(&c, src_bucket, src_key).await?;
-        delete_bucket(&c, src_bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_concurrent_writes() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-concurrent-writes-{}", Uuid::new_v4());
+    let key = "shared.txt";
+
+    create_bucket(&c, &bucket).await?;
+
+    // the data and the metadata of every read are from the same write
+    let check = |ans: aws_sdk_s3::operation::get_object::GetObjectOutput| async move {
+        let writer = ans.metadata().unwrap()["writer"].clone();
+        let body = ans.body.collect().await?.into_bytes();
+        assert_eq!(body.as_ref(), writer.as_bytes());
+        anyhow::Ok(())
+    };
+
+    let mut tasks = Vec::new();
+    for i in 0..8 {
+        let (c, bucket) = (c.clone(), bucket.clone());
+        tasks.push(tokio::spawn(async move {
+            for round in 0..5 {
+                let value = format!("writer-{i}-round-{round}");
+                c.put_object()
+                    .bucket(&bucket)
+                    .key(key)
+                    .metadata("writer", &value)
+                    .body(ByteStream::from(value.clone().into_bytes()))
+                    .send()
+                    .await?;
+            }
+            anyhow::Ok(())
+        }));
+    }
+    for _ in 0..4 {
+        let (c, bucket) = (c.clone(), bucket.clone());
+        tasks.push(tokio::spawn(async move {
+            for _ in 0..10 {
+                match c.get_object().bucket(&bucket).key(key).send().await {
+                    Ok(ans) => check(ans).await?,
+                    Err(err) if http_status(&err) == Some(404) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            anyhow::Ok(())
+        }));
+    }
+    for task in tasks {
+        task.await??;
+    }
+
+    {
+        let ans = c.get_object().bucket(&bucket).key(key).send().await?;
+        check(ans).await?;
+    }
+
+    {
+        delete_object(&c, &bucket, key).await?;
+        delete_bucket(&c, &bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_key_locks() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-key-locks-{}", Uuid::new_v4());
+    let key = "shared.txt";
+
+    create_bucket(&c, &bucket).await?;
+    {
+        let cfg = VersioningConfiguration::builder()
+            .status(BucketVersioningStatus::Enabled)
+            .build();
+        c.put_bucket_versioning()
+            .bucket(&bucket)
+            .versioning_configuration(cfg)
+            .send()
+            .await?;
+    }
+
+    {
+        let body = ByteStream::from_static(b"shared");
+        c.put_object().bucket(&bucket).key(key).body(body).send().await?;
+    }
+
+    {
+        // every concurrent delete creates its own delete marker
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
+            let (c, bucket) = (c.clone(), bucket.clone());
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..3 {
+                    c.delete_object().bucket(&bucket).key(key).send().await?;
+                }
+                anyhow::Ok(())
+            }));
+        }
+        for task in tasks {
+            task.await??;
+        }
+
+        let ans = c.list_object_versions().bucket(&bucket).send().await?;
+        let markers = ans.delete_markers().unwrap();
+        assert_eq!(markers.len(), 24);
+        assert_eq!(markers.iter().filter(|m| m.is_latest()).count(), 1);
+        assert_eq!(ans.versions().unwrap().len(), 1);
+    }
+
+    {
+        // the tag sets of concurrent updates are never mixed
+        let tagged = "tagged.txt";
+        let body = ByteStream::from_static(b"tagged");
+        c.put_object().bucket(&bucket).key(tagged).body(body).send().await?;
+
+        let mut tasks = Vec::new();
+        for i in 0..8 {
+            let (c, bucket) = (c.clone(), bucket.clone());
+            tasks.push(tokio::spawn(async move {
+                let value = format!("writer-{i}");
+                let tagging = Tagging::builder()
+                    .tag_set(Tag::builder().key("a").value(&value).build())
+                    .tag_set(Tag::builder().key("b").value(&value).build())
+                    .build();
+                c.put_object_tagging()
+                    .bucket(&bucket)
+                    .key(tagged)
+                    .tagging(tagging)
+                    .send()
+                    .await?;
+                anyhow::Ok(())
+            }));
+        }
+        for task in tasks {
+            task.await??;
+        }
+
+        let ans = c.get_object_tagging().bucket(&bucket).key(tagged).send().await?;
+        let values: Vec<_> = ans.tag_set().unwrap().iter().map(|t| t.value()).collect();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], values[1]);
+    }
+
+    {
+        // only one of concurrent conditional writes creates the object
+        let absent = "absent.txt";
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
+            let (c, bucket) = (c.clone(), bucket.clone());
+            tasks.push(tokio::spawn(async move {
+                let result = c
+                    .put_object()
+                    .bucket(&bucket)
+                    .key(absent)
+                    .body(ByteStream::from(vec![b'a'; 64 * 1024]))
+                    .customize()
+                    .await?
+                    .mutate_request(|req| drop(req.headers_mut().insert("if-none-match", "*".parse().unwrap())))
+                    .send()
+                    .await;
+                match result {
+                    Ok(_) => anyhow::Ok(true),
+                    Err(err) => {
+                        assert_eq!(http_status(&err), Some(412));
+                        anyhow::Ok(false)
+                    }
+                }
+            }));
+        }
+        let mut created = 0;
+        for task in tasks {
+            if task.await?? {
+                created += 1;
+            }
+        }
+        assert_eq!(created, 1);
+
+        let ans = c.list_object_versions().bucket(&bucket).prefix(absent).send().await?;
+        assert_eq!(ans.versions().unwrap().len(), 1);
+    }
+
+    Ok(())
+}
+
+/// Delegates to the default storage and counts the opened, created and removed files
+#[derive(Debug, Default, Clone)]
+struct CountingStorage {
+    opened: Arc<AtomicUsize>,
+    created: Arc<AtomicUsize>,
+    removed: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl s3s_fs::Storage for CountingStorage {
+    async fn open(&self, path: &Path) -> std::io::Result<Box<dyn s3s_fs::StorageFile>> {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+        s3s_fs::TokioStorage.open(path).await
+    }
+
+    async fn create(&self, path: &Path) -> std::io::Result<Box<dyn s3s_fs::StorageFile>> {
+        self.created.fetch_add(1, Ordering::SeqCst);
+        s3s_fs::TokioStorage.create(path).await
+    }
+
+    async fn read(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        s3s_fs::TokioStorage.read(path).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.write(path, content).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> std::io::Result<u64> {
+        s3s_fs::TokioStorage.copy(from, to).await
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.rename(from, to).await
+    }
+
+    async fn remove_file(&self, path: &Path) -> std::io::Result<()> {
+        self.removed.fetch_add(1, Ordering::SeqCst);
+        s3s_fs::TokioStorage.remove_file(path).await
+    }
+
+    async fn create_dir(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.create_dir(path).await
+    }
+
+    async fn create_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.create_dir_all(path).await
+    }
+
+    async fn remove_dir(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.remove_dir(path).await
+    }
+
+    async fn remove_dir_all(&self, path: &Path) -> std::io::Result<()> {
+        s3s_fs::TokioStorage.remove_dir_all(path).await
+    }
+
+    async fn read_dir(&self, path: &Path) -> std::io::Result<Vec<s3s_fs::DirEntry>> {
+        s3s_fs::TokioStorage.read_dir(path).await
+    }
+
+    async fn metadata(&self, path: &Path) -> std::io::Result<s3s_fs::FileMetadata> {
+        s3s_fs::TokioStorage.metadata(path).await
+    }
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_storage() -> Result<()> {
+    let _guard = serial().await;
+
+    let root = format!("{FS_ROOT}-storage-{}", Uuid::new_v4());
+    fs::create_dir_all(&root)?;
+    let tmp_file = format!("{root}/.tmp.{}.internal.part", Uuid::new_v4());
+    fs::write(&tmp_file, b"interrupted")?;
+
+    // the root is prepared with the storage
+    let storage = CountingStorage::default();
+    let fs = FileSystem::with_storage(&root, storage.clone()).await.unwrap();
+    assert_eq!(storage.removed.load(Ordering::SeqCst), 1);
+    assert!(!Path::new(&tmp_file).exists());
+    let c = local_client(fs);
+    let bucket = "test-storage";
+    let key = "reports/summary.txt";
+
+    create_bucket(&c, bucket).await?;
+
+    {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"summary"))
+            .send()
+            .await?;
+        assert_eq!(storage.created.load(Ordering::SeqCst), 1);
+        assert_eq!(fs::read(format!("{root}/{bucket}/{key}"))?, b"summary");
+
+        let ans = c.get_object().bucket(bucket).key(key).send().await?;
+        assert_eq!(ans.body.collect().await?.into_bytes().as_ref(), b"summary");
+        assert_eq!(storage.opened.load(Ordering::SeqCst), 1);
+
+        let ans = c.list_objects_v2().bucket(bucket).send().await?;
+        let keys: Vec<_> = ans.contents().unwrap().iter().filter_map(|o| o.key()).collect();
+        assert_eq!(keys, [key]);
+    }
+
+    {
+        delete_object(&c, bucket, key).await?;
+        delete_bucket(&c, bucket).await?;
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[tracing::instrument]
+async fn test_unimplemented_operations() -> Result<()> {
+    let _guard = serial().await;
+
+    let c = Client::new(config());
+    let bucket = format!("test-unimplemented-{}", Uuid::new_v4());
+    let bucket = bucket.as_str();
+
+    create_bucket(&c, bucket).await?;
+
+    // the operations which are not implemented fail cleanly with `501 Not Implemented`
+    let expected = (Some(501), Some("NotImplemented".to_owned()));
+
+    {
+        let err = c.get_bucket_encryption().bucket(bucket).send().await.unwrap_err();
+        let status = http_status(&err);
+        let code = err.into_service_error().meta().code().map(ToOwned::to_owned);
+        assert_eq!((status, code), expected);
+    }
+
+    {
+        let err = c.get_bucket_logging().bucket(bucket).send().await.unwrap_err();
+        let status = http_status(&err);
+        let code = err.into_service_error().meta().code().map(ToOwned::to_owned);
+        assert_eq!((status, code), expected);
+    }
+
+    {
+        let tagging = Tagging::builder().tag_set(Tag::builder().key("k").value("v").build()).build();
+        let err = c
+            .put_bucket_tagging()
+            .bucket(bucket)
+            .tagging(tagging)
+            .send()
+            .await
+            .unwrap_err();
+        let status = http_status(&err);
+        let code = err.into_service_error().meta().code().map(ToOwned::to_owned);
+        assert_eq!((status, code), expected);
+    }
+
+    {
+        delete_bucket(&c, bucket).await?;
     }
 
     Ok(())
@@ -0,0 +1,17 @@
+//! An in-memory implementation of the S3 API
+//!
+//! [`Memory`] follows the semantics of `s3s-fs` for buckets, objects, metadata, entity tags and multipart uploads,
+//! but never touches the disk, so that tests which use it are fast and hermetic.
+
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::all, //
+    clippy::cargo, //
+    clippy::pedantic, //
+)]
+#![allow(clippy::wildcard_imports, clippy::module_name_repetitions)]
+
+mod memory;
+mod s3;
+
+pub use self::memory::Memory;
@@ -0,0 +1,105 @@
+use s3s::dto::{ContentType, Metadata};
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use md5::{Digest, Md5};
+
+/// An S3 service which stores everything in memory
+///
+/// The content is dropped with the service.
+#[derive(Debug, Default)]
+pub struct Memory {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct State {
+    pub(crate) buckets: BTreeMap<String, Bucket>,
+    pub(crate) uploads: HashMap<String, Upload>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Bucket {
+    pub(crate) creation_date: SystemTime,
+    pub(crate) objects: BTreeMap<String, Object>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Object {
+    pub(crate) data: Bytes,
+    pub(crate) e_tag: String,
+    pub(crate) last_modified: SystemTime,
+    pub(crate) content_type: Option<ContentType>,
+    pub(crate) metadata: Option<Metadata>,
+}
+
+/// An in-progress multipart upload
+#[derive(Debug)]
+pub(crate) struct Upload {
+    pub(crate) bucket: String,
+    pub(crate) key: String,
+    pub(crate) content_type: Option<ContentType>,
+    pub(crate) metadata: Option<Metadata>,
+    pub(crate) parts: BTreeMap<i32, Part>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Part {
+    pub(crate) data: Bytes,
+    pub(crate) md5: [u8; 16],
+    pub(crate) last_modified: SystemTime,
+}
+
+impl Memory {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A panic while the state is locked can not leave it inconsistent,
+    /// because every operation checks its preconditions before it modifies anything.
+    pub(crate) fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Object {
+    pub(crate) fn new(data: Bytes, content_type: Option<ContentType>, metadata: Option<Metadata>) -> Self {
+        let e_tag = format!("\"{}\"", hex(Md5::digest(&data)));
+        Self {
+            data,
+            e_tag,
+            last_modified: now(),
+            content_type,
+            metadata,
+        }
+    }
+}
+
+impl Part {
+    pub(crate) fn new(data: Bytes) -> Self {
+        Self {
+            md5: Md5::digest(&data).into(),
+            data,
+            last_modified: now(),
+        }
+    }
+
+    pub(crate) fn e_tag(&self) -> String {
+        format!("\"{}\"", hex(self.md5))
+    }
+}
+
+pub(crate) fn hex(input: impl AsRef<[u8]>) -> String {
+    hex_simd::encode_to_string(input.as_ref(), hex_simd::AsciiCase::Lower)
+}
+
+/// The current time, truncated to seconds like the modification times of `s3s-fs`
+pub(crate) fn now() -> SystemTime {
+    let now = SystemTime::now();
+    let Ok(since_epoch) = now.duration_since(SystemTime::UNIX_EPOCH) else { return now };
+    SystemTime::UNIX_EPOCH + Duration::from_secs(since_epoch.as_secs())
+}
@@ -0,0 +1,592 @@
+use crate::memory::{hex, now, Bucket, Memory, Object, Part, Upload};
+
+use s3s::dto::*;
+use s3s::s3_error;
+use s3s::S3Result;
+use s3s::S3;
+use s3s::{S3Error, S3ErrorCode};
+use s3s::{S3Request, S3Response};
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Not;
+
+use bytes::{Bytes, BytesMut};
+use futures::TryStreamExt;
+use hyper::StatusCode;
+use md5::{Digest, Md5};
+use tracing::debug;
+use uuid::Uuid;
+
+/// Collects a request body.
+///
+/// The errors of the body, like a signature mismatch, keep their codes.
+async fn collect_body(body: Option<StreamingBlob>) -> S3Result<Bytes> {
+    let Some(body) = body else { return Err(s3_error!(IncompleteBody)) };
+    let chunks: Vec<Bytes> = body.try_collect().await.map_err(|e| match e.downcast::<S3Error>() {
+        Ok(e) => *e,
+        Err(e) => S3Error::with_source(S3ErrorCode::InternalError, e),
+    })?;
+    let mut data = BytesMut::with_capacity(chunks.iter().map(Bytes::len).sum());
+    for chunk in chunks {
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data.freeze())
+}
+
+fn size_of(data: &Bytes) -> S3Result<i64> {
+    i64::try_from(data.len()).map_err(|e| S3Error::with_source(S3ErrorCode::InternalError, Box::new(e)))
+}
+
+fn no_such_bucket() -> S3Error {
+    s3_error!(NoSuchBucket)
+}
+
+/// The continuation token is the last returned key, like in `s3s-fs`
+fn encode_continuation_token(key: &str) -> String {
+    base64_simd::URL_SAFE_NO_PAD.encode_to_string(key)
+}
+
+fn decode_continuation_token(token: &str) -> S3Result<String> {
+    let key = base64_simd::URL_SAFE_NO_PAD.decode_to_vec(token).ok();
+    key.and_then(|k| String::from_utf8(k).ok())
+        .ok_or_else(|| s3_error!(InvalidArgument, "The continuation token provided is incorrect"))
+}
+
+fn upload_of<'a>(uploads: &'a HashMap<String, Upload>, upload_id: &str, bucket: &str, key: &str) -> S3Result<&'a Upload> {
+    match uploads.get(upload_id) {
+        Some(upload) if upload.bucket == bucket && upload.key == key => Ok(upload),
+        _ => Err(s3_error!(NoSuchUpload)),
+    }
+}
+
+#[async_trait::async_trait]
+impl S3 for Memory {
+    #[tracing::instrument]
+    async fn create_bucket(&self, req: S3Request<CreateBucketInput>) -> S3Result<S3Response<CreateBucketOutput>> {
+        let input = req.input;
+        let mut state = self.state();
+        if state.buckets.contains_key(&input.bucket) {
+            return Err(s3_error!(BucketAlreadyOwnedByYou));
+        }
+        let bucket = Bucket {
+            creation_date: now(),
+            objects: BTreeMap::default(),
+        };
+        state.buckets.insert(input.bucket, bucket);
+        Ok(S3Response::new(CreateBucketOutput::default()))
+    }
+
+    #[tracing::instrument]
+    async fn delete_bucket(&self, req: S3Request<DeleteBucketInput>) -> S3Result<S3Response<DeleteBucketOutput>> {
+        let input = req.input;
+        let mut state = self.state();
+        let bucket = state.buckets.get(&input.bucket).ok_or_else(no_such_bucket)?;
+        let has_uploads = state.uploads.values().any(|upload| upload.bucket == input.bucket);
+        if has_uploads || bucket.objects.is_empty().not() {
+            return Err(s3_error!(BucketNotEmpty, "The bucket you tried to delete is not empty"));
+        }
+        state.buckets.remove(&input.bucket);
+        Ok(S3Response::new(DeleteBucketOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn head_bucket(&self, req: S3Request<HeadBucketInput>) -> S3Result<S3Response<HeadBucketOutput>> {
+        let input = req.input;
+        if self.state().buckets.contains_key(&input.bucket).not() {
+            return Err(no_such_bucket());
+        }
+        Ok(S3Response::new(HeadBucketOutput {}))
+    }
+
+    #[tracing::instrument]
+    async fn list_buckets(&self, req: S3Request<ListBucketsInput>) -> S3Result<S3Response<ListBucketsOutput>> {
+        let buckets = self
+            .state()
+            .buckets
+            .iter()
+            .map(|(name, bucket)| s3s::dto::Bucket {
+                creation_date: Some(Timestamp::from(bucket.creation_date)),
+                name: Some(name.clone()),
+            })
+            .collect();
+        let owner = req.credentials.map(|c| Owner {
+            display_name: Some(c.access_key.clone()),
+            id: Some(c.access_key),
+        });
+        let output = ListBucketsOutput {
+            buckets: Some(buckets),
+            owner,
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+        let input = req.input;
+        if self.state().buckets.contains_key(&input.bucket).not() {
+            return Err(no_such_bucket());
+        }
+
+        let data = collect_body(input.body).await?;
+        if let Some(ref content_md5) = input.content_md5 {
+            let expected = base64_simd::STANDARD.decode_to_vec(content_md5).ok();
+            if expected.as_deref() != Some(Md5::digest(&data).as_slice()) {
+                return Err(s3_error!(BadDigest, "The Content-MD5 you specified did not match what we received."));
+            }
+        }
+
+        let object = Object::new(data, input.content_type, input.metadata);
+        let e_tag = object.e_tag.clone();
+
+        let mut state = self.state();
+        let Some(bucket) = state.buckets.get_mut(&input.bucket) else { return Err(no_such_bucket()) };
+        bucket.objects.insert(input.key, object);
+
+        let output = PutObjectOutput {
+            e_tag: Some(e_tag),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn get_object(&self, req: S3Request<GetObjectInput>) -> S3Result<S3Response<GetObjectOutput>> {
+        let input = req.input;
+        let object = {
+            let state = self.state();
+            let Some(bucket) = state.buckets.get(&input.bucket) else { return Err(no_such_bucket()) };
+            let Some(object) = bucket.objects.get(&input.key) else { return Err(s3_error!(NoSuchKey)) };
+            object.clone()
+        };
+
+        let full_len = object.data.len() as u64;
+        let range = match input.range {
+            None => None,
+            Some(range) => {
+                let Ok(range) = range.check(full_len) else {
+                    return Err(s3_error!(InvalidRange, "The requested range is not satisfiable"));
+                };
+                Some(range)
+            }
+        };
+        let data = match range {
+            Some(ref r) => object
+                .data
+                .slice(usize::try_from(r.start).unwrap()..usize::try_from(r.end).unwrap()),
+            None => object.data.clone(),
+        };
+        let content_range = range.as_ref().map(|r| format!("bytes {}-{}/{full_len}", r.start, r.end - 1));
+
+        let output = GetObjectOutput {
+            content_length: size_of(&data)?,
+            body: Some(StreamingBlob::from(s3s::Body::from(data))),
+            content_range,
+            content_type: Some(object.content_type.unwrap_or(mime::APPLICATION_OCTET_STREAM)),
+            e_tag: Some(object.e_tag),
+            last_modified: Some(Timestamp::from(object.last_modified)),
+            metadata: object.metadata,
+            ..Default::default()
+        };
+        let mut resp = S3Response::new(output);
+        if range.is_some() {
+            resp.status = Some(StatusCode::PARTIAL_CONTENT);
+        }
+        Ok(resp)
+    }
+
+    #[tracing::instrument]
+    async fn head_object(&self, req: S3Request<HeadObjectInput>) -> S3Result<S3Response<HeadObjectOutput>> {
+        let input = req.input;
+        let state = self.state();
+        let Some(bucket) = state.buckets.get(&input.bucket) else { return Err(no_such_bucket()) };
+        let Some(object) = bucket.objects.get(&input.key) else { return Err(s3_error!(NoSuchKey)) };
+
+        let output = HeadObjectOutput {
+            content_length: size_of(&object.data)?,
+            content_type: Some(object.content_type.clone().unwrap_or(mime::APPLICATION_OCTET_STREAM)),
+            e_tag: Some(object.e_tag.clone()),
+            last_modified: Some(Timestamp::from(object.last_modified)),
+            metadata: object.metadata.clone(),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn delete_object(&self, req: S3Request<DeleteObjectInput>) -> S3Result<S3Response<DeleteObjectOutput>> {
+        let input = req.input;
+        let mut state = self.state();
+        let Some(bucket) = state.buckets.get_mut(&input.bucket) else { return Err(no_such_bucket()) };
+        bucket.objects.remove(&input.key);
+        Ok(S3Response::new(DeleteObjectOutput::default()))
+    }
+
+    #[tracing::instrument]
+    async fn delete_objects(&self, req: S3Request<DeleteObjectsInput>) -> S3Result<S3Response<DeleteObjectsOutput>> {
+        const MAX_OBJECTS: usize = 1000;
+
+        let input = req.input;
+        if input.delete.objects.len() > MAX_OBJECTS {
+            return Err(s3_error!(MalformedXML, "The request must not contain more than {} objects", MAX_OBJECTS));
+        }
+
+        let mut state = self.state();
+        let Some(bucket) = state.buckets.get_mut(&input.bucket) else { return Err(no_such_bucket()) };
+
+        let mut deleted_objects = Vec::new();
+        for object in input.delete.objects {
+            bucket.objects.remove(&object.key);
+            if input.delete.quiet.not() {
+                deleted_objects.push(DeletedObject {
+                    key: Some(object.key),
+                    version_id: object.version_id,
+                    ..Default::default()
+                });
+            }
+        }
+
+        let output = DeleteObjectsOutput {
+            deleted: Some(deleted_objects),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn copy_object(&self, req: S3Request<CopyObjectInput>) -> S3Result<S3Response<CopyObjectOutput>> {
+        let input = req.input;
+        let (src_bucket, src_key) = match input.copy_source {
+            CopySource::AccessPoint { .. } => return Err(s3_error!(NotImplemented)),
+            CopySource::Bucket { ref bucket, ref key, .. } => (bucket, key),
+        };
+
+        let replace_metadata = match input.metadata_directive.as_ref().map(MetadataDirective::as_str) {
+            None | Some(MetadataDirective::COPY) => false,
+            Some(MetadataDirective::REPLACE) => true,
+            Some(_) => return Err(s3_error!(InvalidArgument, "Unknown metadata directive.")),
+        };
+        let is_same_object = **src_bucket == *input.bucket && **src_key == *input.key;
+        if is_same_object && replace_metadata.not() {
+            return Err(s3_error!(
+                InvalidRequest,
+                "This copy request is illegal because it is trying to copy an object to itself without changing the object's metadata."
+            ));
+        }
+
+        let mut state = self.state();
+        let src = {
+            let Some(bucket) = state.buckets.get(&**src_bucket) else { return Err(no_such_bucket()) };
+            let Some(object) = bucket.objects.get(&**src_key) else { return Err(s3_error!(NoSuchKey)) };
+            object.clone()
+        };
+        let Some(bucket) = state.buckets.get_mut(&input.bucket) else { return Err(no_such_bucket()) };
+
+        let object = if replace_metadata {
+            Object::new(src.data, input.content_type, input.metadata)
+        } else {
+            Object::new(src.data, src.content_type, src.metadata)
+        };
+        let copy_object_result = CopyObjectResult {
+            e_tag: Some(object.e_tag.clone()),
+            last_modified: Some(Timestamp::from(object.last_modified)),
+            ..Default::default()
+        };
+        bucket.objects.insert(input.key, object);
+
+        let output = CopyObjectOutput {
+            copy_object_result: Some(copy_object_result),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_objects(&self, req: S3Request<ListObjectsInput>) -> S3Result<S3Response<ListObjectsOutput>> {
+        let v2_resp = self.list_objects_v2(req.map_input(Into::into)).await?;
+
+        Ok(v2_resp.map_output(|v2| {
+            // the last key or common prefix of the page, whichever sorts last
+            let next_marker = if v2.is_truncated {
+                let last_key = v2.contents.as_ref().and_then(|c| c.last()).and_then(|o| o.key.clone());
+                let last_prefix = v2
+                    .common_prefixes
+                    .as_ref()
+                    .and_then(|c| c.last())
+                    .and_then(|p| p.prefix.clone());
+                last_key.max(last_prefix)
+            } else {
+                None
+            };
+            ListObjectsOutput {
+                common_prefixes: v2.common_prefixes,
+                contents: v2.contents,
+                delimiter: v2.delimiter,
+                encoding_type: v2.encoding_type,
+                name: v2.name,
+                prefix: v2.prefix,
+                max_keys: v2.max_keys,
+                is_truncated: v2.is_truncated,
+                marker: v2.start_after,
+                next_marker,
+                ..Default::default()
+            }
+        }))
+    }
+
+    #[tracing::instrument]
+    async fn list_objects_v2(&self, req: S3Request<ListObjectsV2Input>) -> S3Result<S3Response<ListObjectsV2Output>> {
+        let input = req.input;
+        let prefix = input.prefix.as_deref().unwrap_or_default();
+        let delimiter = input.delimiter.as_deref().filter(|d| d.is_empty().not());
+
+        let max_keys = input.max_keys.unwrap_or(1000);
+        let limit = usize::try_from(max_keys).map_err(|_| s3_error!(InvalidArgument, "max-keys must be non-negative"))?;
+
+        // the continuation token takes precedence over `start-after`
+        let marker = match input.continuation_token {
+            Some(ref token) => decode_continuation_token(token)?,
+            None => input.start_after.clone().unwrap_or_default(),
+        };
+
+        let mut objects: Vec<s3s::dto::Object> = Vec::new();
+        let mut common_prefixes: Vec<String> = Vec::new();
+        let mut last_key: Option<String> = None;
+        let mut is_truncated = false;
+
+        {
+            let state = self.state();
+            let Some(bucket) = state.buckets.get(&input.bucket) else { return Err(no_such_bucket()) };
+
+            let entries = bucket
+                .objects
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix) && **key > marker);
+            for (key, object) in entries {
+                let common_prefix = delimiter.and_then(|delimiter| {
+                    let pos = key[prefix.len()..].find(delimiter)?;
+                    Some(&key[..prefix.len() + pos + delimiter.len()])
+                });
+
+                if let Some(common_prefix) = common_prefix {
+                    if common_prefix == marker || common_prefixes.last().map(String::as_str) == Some(common_prefix) {
+                        continue;
+                    }
+                }
+
+                if objects.len() + common_prefixes.len() == limit {
+                    is_truncated = true;
+                    break;
+                }
+
+                if let Some(common_prefix) = common_prefix {
+                    common_prefixes.push(common_prefix.to_owned());
+                    last_key = Some(common_prefix.to_owned());
+                    continue;
+                }
+                last_key = Some(key.clone());
+
+                objects.push(s3s::dto::Object {
+                    key: Some(key.clone()),
+                    e_tag: Some(object.e_tag.clone()),
+                    last_modified: Some(Timestamp::from(object.last_modified)),
+                    size: size_of(&object.data)?,
+                    storage_class: Some(ObjectStorageClass::from(ObjectStorageClass::STANDARD.to_owned())),
+                    ..Default::default()
+                });
+            }
+        }
+
+        let key_count = i32::try_from(objects.len() + common_prefixes.len()).unwrap_or(i32::MAX);
+        let next_continuation_token = last_key.filter(|_| is_truncated).map(|key| encode_continuation_token(&key));
+        let common_prefixes = common_prefixes
+            .into_iter()
+            .map(|prefix| CommonPrefix { prefix: Some(prefix) })
+            .collect::<Vec<_>>();
+
+        let output = ListObjectsV2Output {
+            key_count,
+            max_keys,
+            is_truncated,
+            continuation_token: input.continuation_token,
+            next_continuation_token,
+            common_prefixes: common_prefixes.is_empty().not().then_some(common_prefixes),
+            contents: Some(objects),
+            delimiter: input.delimiter,
+            encoding_type: input.encoding_type,
+            name: Some(input.bucket),
+            prefix: input.prefix,
+            start_after: input.start_after,
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn create_multipart_upload(
+        &self,
+        req: S3Request<CreateMultipartUploadInput>,
+    ) -> S3Result<S3Response<CreateMultipartUploadOutput>> {
+        let input = req.input;
+        let mut state = self.state();
+        if state.buckets.contains_key(&input.bucket).not() {
+            return Err(no_such_bucket());
+        }
+
+        let upload_id = Uuid::new_v4().to_string();
+        let upload = Upload {
+            bucket: input.bucket.clone(),
+            key: input.key.clone(),
+            content_type: input.content_type,
+            metadata: input.metadata,
+            parts: BTreeMap::default(),
+        };
+        state.uploads.insert(upload_id.clone(), upload);
+
+        let output = CreateMultipartUploadOutput {
+            bucket: Some(input.bucket),
+            key: Some(input.key),
+            upload_id: Some(upload_id),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn upload_part(&self, req: S3Request<UploadPartInput>) -> S3Result<S3Response<UploadPartOutput>> {
+        let input = req.input;
+        upload_of(&self.state().uploads, &input.upload_id, &input.bucket, &input.key)?;
+
+        let part = Part::new(collect_body(input.body).await?);
+        let e_tag = part.e_tag();
+
+        // the upload may have been completed or aborted in the meantime
+        let mut state = self.state();
+        let Some(upload) = state.uploads.get_mut(&input.upload_id) else {
+            return Err(s3_error!(NoSuchUpload));
+        };
+        upload.parts.insert(input.part_number, part);
+
+        let output = UploadPartOutput {
+            e_tag: Some(e_tag),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn list_parts(&self, req: S3Request<ListPartsInput>) -> S3Result<S3Response<ListPartsOutput>> {
+        let input = req.input;
+        let state = self.state();
+        let upload = upload_of(&state.uploads, &input.upload_id, &input.bucket, &input.key)?;
+
+        let marker = match input.part_number_marker.as_deref() {
+            Some(marker) => marker
+                .parse::<i32>()
+                .map_err(|_| s3_error!(InvalidArgument, "invalid part number marker"))?,
+            None => 0,
+        };
+        let max_parts = input.max_parts.unwrap_or(1000);
+        let limit = usize::try_from(max_parts).map_err(|_| s3_error!(InvalidArgument, "max-parts must be non-negative"))?;
+
+        let mut parts = Vec::new();
+        for (&part_number, part) in upload.parts.range(marker + 1..).take(limit) {
+            parts.push(s3s::dto::Part {
+                e_tag: Some(part.e_tag()),
+                last_modified: Some(Timestamp::from(part.last_modified)),
+                part_number,
+                size: size_of(&part.data)?,
+                ..Default::default()
+            });
+        }
+        let next_marker = parts.last().map(|p| p.part_number);
+        let is_truncated = next_marker.is_some_and(|n| upload.parts.range(n + 1..).next().is_some());
+
+        let output = ListPartsOutput {
+            bucket: Some(input.bucket),
+            key: Some(input.key),
+            upload_id: Some(input.upload_id),
+            max_parts,
+            is_truncated,
+            part_number_marker: input.part_number_marker,
+            next_part_number_marker: next_marker.filter(|_| is_truncated).map(|n| n.to_string()),
+            parts: Some(parts),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn complete_multipart_upload(
+        &self,
+        req: S3Request<CompleteMultipartUploadInput>,
+    ) -> S3Result<S3Response<CompleteMultipartUploadOutput>> {
+        let input = req.input;
+        let Some(completed) = input.multipart_upload else { return Err(s3_error!(InvalidPart)) };
+        let completed_parts = completed.parts.unwrap_or_default();
+        if completed_parts.is_empty() {
+            return Err(s3_error!(MalformedXML, "You must specify at least one part"));
+        }
+
+        let mut state = self.state();
+        let upload = upload_of(&state.uploads, &input.upload_id, &input.bucket, &input.key)?;
+        if state.buckets.contains_key(&input.bucket).not() {
+            return Err(no_such_bucket());
+        }
+
+        let mut data = BytesMut::new();
+        let mut md5_of_md5s = Md5::new();
+        for (cnt, completed_part) in (1..).zip(&completed_parts) {
+            let part_number = completed_part.part_number;
+            if part_number != cnt {
+                return Err(s3_error!(InvalidRequest, "invalid part order"));
+            }
+            let Some(part) = upload.parts.get(&part_number) else { return Err(s3_error!(InvalidPart)) };
+            if completed_part
+                .e_tag
+                .as_deref()
+                .is_some_and(|e| e.trim_matches('"') != hex(part.md5))
+            {
+                return Err(s3_error!(InvalidPart));
+            }
+            data.extend_from_slice(&part.data);
+            md5_of_md5s.update(part.md5);
+        }
+
+        // the ETag of a multipart object is the MD5 of the part MD5s followed by the number of parts
+        let e_tag = format!("\"{}-{}\"", hex(md5_of_md5s.finalize()), completed_parts.len());
+        debug!(upload_id = %input.upload_id, %e_tag, "complete multipart upload");
+
+        let Some(upload) = state.uploads.remove(&input.upload_id) else {
+            return Err(s3_error!(NoSuchUpload));
+        };
+        let object = Object {
+            data: data.freeze(),
+            e_tag: e_tag.clone(),
+            last_modified: now(),
+            content_type: upload.content_type,
+            metadata: upload.metadata,
+        };
+        let Some(bucket) = state.buckets.get_mut(&input.bucket) else { return Err(no_such_bucket()) };
+        bucket.objects.insert(input.key.clone(), object);
+
+        let output = CompleteMultipartUploadOutput {
+            bucket: Some(input.bucket),
+            key: Some(input.key),
+            e_tag: Some(e_tag),
+            ..Default::default()
+        };
+        Ok(S3Response::new(output))
+    }
+
+    #[tracing::instrument]
+    async fn abort_multipart_upload(
+        &self,
+        req: S3Request<AbortMultipartUploadInput>,
+    ) -> S3Result<S3Response<AbortMultipartUploadOutput>> {
+        let input = req.input;
+        let mut state = self.state();
+        upload_of(&state.uploads, &input.upload_id, &input.bucket, &input.key)?;
+        state.uploads.remove(&input.upload_id);
+        Ok(S3Response::new(AbortMultipartUploadOutput::default()))
+    }
+}
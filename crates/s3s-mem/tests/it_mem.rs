@@ -0,0 +1,322 @@
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::all, //
+    clippy::must_use_candidate, //
+)]
+
+use s3s::auth::SimpleAuth;
+use s3s::service::S3ServiceBuilder;
+use s3s_mem::Memory;
+
+use aws_sdk_s3::config::Credentials;
+use aws_sdk_s3::config::Region;
+use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::CompletedMultipartUpload;
+use aws_sdk_s3::types::CompletedPart;
+use aws_sdk_s3::types::MetadataDirective;
+use aws_sdk_s3::Client;
+
+use anyhow::Result;
+
+const DOMAIN_NAME: &str = "localhost:8014";
+const REGION: &str = "us-west-2";
+
+/// Every test has its own memory, so the tests can run in parallel.
+fn client() -> Client {
+    let cred = Credentials::for_tests();
+    let service = {
+        let mut b = S3ServiceBuilder::new(Memory::new());
+        b.set_auth(SimpleAuth::from_single(cred.access_key_id(), cred.secret_access_key()));
+        b.build()
+    };
+    let conf = aws_sdk_s3::Config::builder()
+        .credentials_provider(cred)
+        .http_connector(s3s_aws::Connector::from(service.into_shared()))
+        .region(Region::new(REGION))
+        .endpoint_url(format!("http://{DOMAIN_NAME}"))
+        .force_path_style(true)
+        .build();
+    Client::from_conf(conf)
+}
+
+fn http_status<E>(err: &SdkError<E>) -> Option<u16> {
+    err.raw_response().map(|res| res.status().as_u16())
+}
+
+async fn get_body(c: &Client, bucket: &str, key: &str) -> Result<Vec<u8>> {
+    let ans = c.get_object().bucket(bucket).key(key).send().await?;
+    Ok(ans.body.collect().await?.into_bytes().to_vec())
+}
+
+#[tokio::test]
+async fn test_put_get_head() -> Result<()> {
+    let c = client();
+    c.create_bucket().bucket("test-put-get").send().await?;
+
+    let put = c
+        .put_object()
+        .bucket("test-put-get")
+        .key("a/b.txt")
+        .content_type("text/plain")
+        .metadata("color", "blue")
+        .body(ByteStream::from_static(b"hello world"))
+        .send()
+        .await?;
+    assert_eq!(put.e_tag(), Some("\"5eb63bbbe01eeed093cb22bb8f5acdc3\""));
+
+    assert_eq!(get_body(&c, "test-put-get", "a/b.txt").await?, b"hello world");
+
+    let head = c.head_object().bucket("test-put-get").key("a/b.txt").send().await?;
+    assert_eq!(head.content_length(), 11);
+    assert_eq!(head.content_type(), Some("text/plain"));
+    assert_eq!(head.e_tag(), put.e_tag());
+    assert_eq!(head.metadata().and_then(|m| m.get("color")).map(String::as_str), Some("blue"));
+
+    let range = c
+        .get_object()
+        .bucket("test-put-get")
+        .key("a/b.txt")
+        .range("bytes=6-")
+        .send()
+        .await?;
+    assert_eq!(range.content_range(), Some("bytes 6-10/11"));
+    assert_eq!(range.body.collect().await?.into_bytes().as_ref(), b"world");
+
+    let err = c
+        .get_object()
+        .bucket("test-put-get")
+        .key("a/b.txt")
+        .range("bytes=20-")
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(http_status(&err), Some(416));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_missing_bucket_and_key() -> Result<()> {
+    let c = client();
+
+    let err = c
+        .put_object()
+        .bucket("test-missing")
+        .key("k")
+        .body(ByteStream::from_static(b"x"))
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+
+    c.create_bucket().bucket("test-missing").send().await?;
+
+    let err = c.get_object().bucket("test-missing").key("k").send().await.unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("NoSuchKey"));
+
+    let err = c.head_object().bucket("test-missing").key("k").send().await.unwrap_err();
+    assert_eq!(http_status(&err), Some(404));
+
+    let err = c.create_bucket().bucket("test-missing").send().await.unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("BucketAlreadyOwnedByYou"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_bucket() -> Result<()> {
+    let c = client();
+    let bucket = "test-delete-bucket";
+    let key = "k";
+
+    let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("NoSuchBucket"));
+
+    c.create_bucket().bucket(bucket).send().await?;
+    c.put_object()
+        .bucket(bucket)
+        .key(key)
+        .body(ByteStream::from_static(b"x"))
+        .send()
+        .await?;
+
+    let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("BucketNotEmpty"));
+
+    // an in-progress upload keeps the bucket as well
+    c.delete_object().bucket(bucket).key(key).send().await?;
+    let upload = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+    let upload_id = upload.upload_id().unwrap();
+
+    let err = c.delete_bucket().bucket(bucket).send().await.unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("BucketNotEmpty"));
+
+    c.abort_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .send()
+        .await?;
+    c.delete_bucket().bucket(bucket).send().await?;
+
+    let err = c.head_bucket().bucket(bucket).send().await.unwrap_err();
+    assert_eq!(http_status(&err), Some(404));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_objects() -> Result<()> {
+    let c = client();
+    let bucket = "test-list";
+    c.create_bucket().bucket(bucket).send().await?;
+
+    for key in ["a", "b/1", "b/2", "c"] {
+        c.put_object()
+            .bucket(bucket)
+            .key(key)
+            .body(ByteStream::from_static(b"x"))
+            .send()
+            .await?;
+    }
+
+    let ans = c.list_objects_v2().bucket(bucket).delimiter("/").send().await?;
+    let keys: Vec<_> = ans.contents().unwrap_or_default().iter().filter_map(|o| o.key()).collect();
+    let prefixes: Vec<_> = ans
+        .common_prefixes()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|p| p.prefix())
+        .collect();
+    assert_eq!(keys, ["a", "c"]);
+    assert_eq!(prefixes, ["b/"]);
+
+    let mut keys = Vec::new();
+    let mut token = None;
+    loop {
+        let ans = c
+            .list_objects_v2()
+            .bucket(bucket)
+            .max_keys(1)
+            .set_continuation_token(token)
+            .send()
+            .await?;
+        keys.extend(
+            ans.contents()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|o| o.key().map(ToOwned::to_owned)),
+        );
+        token = ans.next_continuation_token().map(ToOwned::to_owned);
+        if token.is_none() {
+            break;
+        }
+    }
+    assert_eq!(keys, ["a", "b/1", "b/2", "c"]);
+
+    let ans = c.list_objects().bucket(bucket).max_keys(2).send().await?;
+    assert!(ans.is_truncated());
+    assert_eq!(ans.next_marker(), Some("b/1"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_copy_object() -> Result<()> {
+    let c = client();
+    let bucket = "test-copy";
+    c.create_bucket().bucket(bucket).send().await?;
+
+    c.put_object()
+        .bucket(bucket)
+        .key("src")
+        .metadata("color", "blue")
+        .body(ByteStream::from_static(b"content"))
+        .send()
+        .await?;
+
+    c.copy_object()
+        .bucket(bucket)
+        .key("copied")
+        .copy_source(format!("{bucket}/src"))
+        .send()
+        .await?;
+    let head = c.head_object().bucket(bucket).key("copied").send().await?;
+    assert_eq!(head.metadata().and_then(|m| m.get("color")).map(String::as_str), Some("blue"));
+
+    c.copy_object()
+        .bucket(bucket)
+        .key("replaced")
+        .copy_source(format!("{bucket}/src"))
+        .metadata_directive(MetadataDirective::Replace)
+        .metadata("color", "red")
+        .send()
+        .await?;
+    let head = c.head_object().bucket(bucket).key("replaced").send().await?;
+    assert_eq!(head.metadata().and_then(|m| m.get("color")).map(String::as_str), Some("red"));
+    assert_eq!(get_body(&c, bucket, "replaced").await?, b"content");
+
+    c.delete_object().bucket(bucket).key("src").send().await?;
+    let err = c.head_object().bucket(bucket).key("src").send().await.unwrap_err();
+    assert_eq!(http_status(&err), Some(404));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_multipart_upload() -> Result<()> {
+    let c = client();
+    let bucket = "test-multipart";
+    let key = "large";
+    c.create_bucket().bucket(bucket).send().await?;
+
+    let upload = c.create_multipart_upload().bucket(bucket).key(key).send().await?;
+    let upload_id = upload.upload_id().unwrap();
+
+    let mut parts = Vec::new();
+    for (part_number, content) in (1..).zip([&b"hello "[..], &b"world"[..]]) {
+        let ans = c
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(content.to_vec()))
+            .send()
+            .await?;
+        let part = CompletedPart::builder()
+            .e_tag(ans.e_tag().unwrap())
+            .part_number(part_number)
+            .build();
+        parts.push(part);
+    }
+
+    let ans = c.list_parts().bucket(bucket).key(key).upload_id(upload_id).send().await?;
+    assert_eq!(ans.parts().map(<[_]>::len), Some(2));
+
+    let completed = CompletedMultipartUpload::builder().set_parts(Some(parts)).build();
+    let ans = c
+        .complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(completed)
+        .send()
+        .await?;
+    assert!(ans.e_tag().unwrap().ends_with("-2\""));
+
+    assert_eq!(get_body(&c, bucket, key).await?, b"hello world");
+
+    let err = c
+        .list_parts()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .send()
+        .await
+        .unwrap_err();
+    assert_eq!(err.into_service_error().meta().code(), Some("NoSuchUpload"));
+
+    Ok(())
+}
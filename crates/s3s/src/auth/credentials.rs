@@ -4,4 +4,6 @@ use super::SecretKey;
 pub struct Credentials {
     pub access_key: String,
     pub secret_key: SecretKey,
+    /// The session token of temporary credentials (`x-amz-security-token`)
+    pub session_token: Option<String>,
 }
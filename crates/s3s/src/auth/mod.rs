@@ -4,7 +4,7 @@ mod secret_key;
 pub use self::secret_key::SecretKey;
 
 mod simple_auth;
-pub use self::simple_auth::SimpleAuth;
+pub use self::simple_auth::{PublicBuckets, PublicObjects, SimpleAuth};
 
 mod credentials;
 pub use self::credentials::Credentials;
@@ -22,6 +22,18 @@ pub trait S3Auth: Send + Sync + 'static {
     /// This method is usually implemented as a database query.
     async fn get_secret_key(&self, access_key: &str) -> S3Result<SecretKey>;
 
+    /// Gets the corresponding secret key of temporary credentials,
+    /// which are identified by the access key and the session token (`x-amz-security-token`).
+    ///
+    /// By default, this method ignores the session token and calls [`get_secret_key`](S3Auth::get_secret_key).
+    ///
+    /// An authentication provider can override this method to validate temporary credentials,
+    /// or to reject session tokens with [`InvalidToken`](crate::S3ErrorCode::InvalidToken) error.
+    async fn get_secret_key_with_session_token(&self, access_key: &str, session_token: &str) -> S3Result<SecretKey> {
+        let _ = session_token;
+        self.get_secret_key(access_key).await
+    }
+
     /// Checks if the current request can access the resource.
     ///
     /// By default, this method rejects all anonymous requests
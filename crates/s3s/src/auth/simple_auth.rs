@@ -1,22 +1,127 @@
 use super::S3Auth;
 
-use crate::auth::SecretKey;
+use crate::auth::{S3AuthContext, SecretKey};
 use crate::error::S3Result;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Not;
+use std::sync::{Arc, PoisonError, RwLock};
 
-/// A simple authentication provider
+use hyper::Method;
+
+/// The query parameters of anonymous reads: `GetObject`, `HeadObject`, `HeadBucket` and `ListObjects`
+///
+/// Any other parameter selects a subresource like `?acl` or `?policy`, which is not public.
+const PUBLIC_READ_PARAMS: &[&str] = &[
+    "continuation-token",
+    "delimiter",
+    "encoding-type",
+    "fetch-owner",
+    "list-type",
+    "marker",
+    "max-keys",
+    "partNumber",
+    "prefix",
+    "response-cache-control",
+    "response-content-disposition",
+    "response-content-encoding",
+    "response-content-language",
+    "response-content-type",
+    "response-expires",
+    "start-after",
+    "versionId",
+];
+
+fn is_public_read(cx: &S3AuthContext<'_>) -> bool {
+    if matches!(*cx.method(), Method::GET | Method::HEAD).not() {
+        return false;
+    }
+    let query = cx.uri().query().unwrap_or_default();
+    query.split('&').filter(|p| p.is_empty().not()).all(|p| {
+        let name = p.split_once('=').map_or(p, |(name, _)| name);
+        PUBLIC_READ_PARAMS.contains(&name)
+    })
+}
+
+/// A shared set of buckets which can be read anonymously
+///
+/// A storage backend can hold a clone of the set and update it, for example when the ACL of a bucket changes.
+#[derive(Debug, Clone, Default)]
+pub struct PublicBuckets(Arc<RwLock<HashSet<String>>>);
+
+impl PublicBuckets {
+    /// Constructs an empty set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a bucket, returning `true` if it was not public
+    pub fn insert(&self, bucket: impl Into<String>) -> bool {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).insert(bucket.into())
+    }
+
+    /// Removes a bucket, returning `true` if it was public
+    pub fn remove(&self, bucket: &str) -> bool {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).remove(bucket)
+    }
+
+    /// Returns `true` if the bucket can be read anonymously
+    #[must_use]
+    pub fn contains(&self, bucket: &str) -> bool {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).contains(bucket)
+    }
+}
+
+/// A shared set of objects which can be read anonymously, even if their buckets are private
+///
+/// A storage backend can hold a clone of the set and update it, for example when the ACL of an object changes.
+#[derive(Debug, Clone, Default)]
+pub struct PublicObjects(Arc<RwLock<HashSet<(String, String)>>>);
+
+impl PublicObjects {
+    /// Constructs an empty set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an object, returning `true` if it was not public
+    pub fn insert(&self, bucket: impl Into<String>, key: impl Into<String>) -> bool {
+        let mut set = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        set.insert((bucket.into(), key.into()))
+    }
+
+    /// Removes an object, returning `true` if it was public
+    pub fn remove(&self, bucket: &str, key: &str) -> bool {
+        let mut set = self.0.write().unwrap_or_else(PoisonError::into_inner);
+        set.remove(&(bucket.to_owned(), key.to_owned()))
+    }
+
+    /// Returns `true` if the object can be read anonymously
+    #[must_use]
+    pub fn contains(&self, bucket: &str, key: &str) -> bool {
+        let set = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        set.contains(&(bucket.to_owned(), key.to_owned()))
+    }
+}
+
+/// A simple authentication provider, which looks up the secret key of any registered access key
 #[derive(Debug, Default)]
 pub struct SimpleAuth {
     /// key map
     map: HashMap<String, SecretKey>,
+    /// buckets which can be read anonymously
+    public_buckets: PublicBuckets,
+    /// objects which can be read anonymously
+    public_objects: PublicObjects,
 }
 
 impl SimpleAuth {
     /// Constructs a new `SimpleAuth`
     #[must_use]
     pub fn new() -> Self {
-        Self { map: HashMap::new() }
+        Self::default()
     }
 
     #[must_use]
@@ -24,7 +129,17 @@ impl SimpleAuth {
         let access_key = access_key.into();
         let secret_key = secret_key.into();
         let map = [(access_key, secret_key)].into_iter().collect();
-        Self { map }
+        Self::from_map(map)
+    }
+
+    /// Constructs a `SimpleAuth` from a map of access keys to secret keys
+    #[must_use]
+    pub fn from_map(map: HashMap<String, SecretKey>) -> Self {
+        Self {
+            map,
+            public_buckets: PublicBuckets::new(),
+            public_objects: PublicObjects::new(),
+        }
     }
 
     /// register a pair of keys
@@ -32,6 +147,35 @@ impl SimpleAuth {
         self.map.insert(access_key, secret_key)
     }
 
+    /// Allows anonymous requests to read the objects of a bucket and to list them.
+    ///
+    /// Writes to the bucket still require a signature.
+    pub fn add_public_bucket(&mut self, bucket: impl Into<String>) {
+        self.public_buckets.insert(bucket);
+    }
+
+    /// Replaces the buckets which can be read anonymously with a shared set
+    pub fn set_public_buckets(&mut self, buckets: PublicBuckets) {
+        self.public_buckets = buckets;
+    }
+
+    /// Returns the buckets which can be read anonymously
+    #[must_use]
+    pub fn public_buckets(&self) -> &PublicBuckets {
+        &self.public_buckets
+    }
+
+    /// Replaces the objects which can be read anonymously with a shared set
+    pub fn set_public_objects(&mut self, objects: PublicObjects) {
+        self.public_objects = objects;
+    }
+
+    /// Returns the objects which can be read anonymously
+    #[must_use]
+    pub fn public_objects(&self) -> &PublicObjects {
+        &self.public_objects
+    }
+
     /// lookup a secret key
     #[must_use]
     pub fn lookup(&self, access_key: &str) -> Option<&SecretKey> {
@@ -43,8 +187,29 @@ impl SimpleAuth {
 impl S3Auth for SimpleAuth {
     async fn get_secret_key(&self, access_key: &str) -> S3Result<SecretKey> {
         match self.lookup(access_key) {
-            None => Err(s3_error!(NotSignedUp, "Your account is not signed up")),
+            None => Err(s3_error!(
+                InvalidAccessKeyId,
+                "The AWS access key Id you provided does not exist in our records."
+            )),
             Some(s) => Ok(s.clone()),
         }
     }
+
+    async fn check_access(&self, cx: &mut S3AuthContext<'_>) -> S3Result<()> {
+        if cx.credentials().is_some() {
+            return Ok(());
+        }
+        let bucket = cx
+            .s3_path()
+            .as_bucket()
+            .or_else(|| cx.s3_path().as_object().map(|(bucket, _)| bucket));
+        if bucket.is_some_and(|b| self.public_buckets.contains(b)) && is_public_read(cx) {
+            return Ok(());
+        }
+        let object = cx.s3_path().as_object();
+        if object.is_some_and(|(b, k)| self.public_objects.contains(b, k)) && is_public_read(cx) {
+            return Ok(());
+        }
+        Err(s3_error!(AccessDenied, "Signature is required"))
+    }
 }
@@ -98,8 +98,13 @@ impl CopySource {
                     write!(&mut buf, "?versionId={version_id}").unwrap();
                 }
             }
-            CopySource::AccessPoint { .. } => {
-                unimplemented!()
+            CopySource::AccessPoint {
+                region,
+                account_id,
+                access_point_name,
+                key,
+            } => {
+                write!(&mut buf, "arn:aws:s3:{region}:{account_id}:accesspoint/{access_point_name}/object/{key}").unwrap();
             }
         }
         buf
@@ -147,4 +152,16 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn access_point_format() {
+        let val = CopySource::AccessPoint {
+            region: "us-west-2".into(),
+            account_id: "123456789012".into(),
+            access_point_name: "my-access-point".into(),
+            key: "reports/january.pdf".into(),
+        };
+        let expected = "arn:aws:s3:us-west-2:123456789012:accesspoint/my-access-point/object/reports/january.pdf";
+        assert_eq!(val.format_to_string(), expected);
+    }
 }
@@ -1,23 +1,22 @@
 use super::SelectObjectContentEvent;
 use super::{ContinuationEvent, EndEvent, ProgressEvent, RecordsEvent, StatsEvent};
 
+use crate::event_stream::{EncodeError, Header, HeaderValue, Message};
 use crate::stream::ByteStream;
 use crate::stream::DynByteStream;
+use crate::xml;
 use crate::S3Error;
 use crate::S3Result;
 use crate::StdError;
-use crate::{xml, S3ErrorCode};
 
+use std::borrow::Cow;
 use std::fmt;
-use std::num::TryFromIntError;
 use std::pin::Pin;
 use std::task::ready;
 use std::task::{Context, Poll};
 
-use bytes::BufMut;
 use bytes::Bytes;
 use futures::Stream;
-use smallvec::SmallVec;
 use tracing::debug;
 
 pub struct SelectObjectContentEventStream {
@@ -81,88 +80,16 @@ impl Stream for Wrapper {
 
 impl ByteStream for Wrapper {}
 
-fn event_into_bytes(ev: S3Result<SelectObjectContentEvent>) -> Result<Bytes, SerError> {
+fn event_into_bytes(ev: S3Result<SelectObjectContentEvent>) -> Result<Bytes, EncodeError> {
     match ev {
-        Ok(event) => event.into_message().serialize(),
+        Ok(event) => event.into_message().encode(),
         Err(err) => {
             debug!(?err, "SelectObjectContentEventStream: Request Level Error");
-            request_level_error(&err).serialize()
+            request_level_error(&err).encode()
         }
     }
 }
 
-struct Message {
-    headers: SmallVec<[Header; 4]>,
-    payload: Option<Bytes>,
-}
-
-struct Header {
-    name: Bytes,
-    value: Bytes,
-}
-
-#[derive(Debug, thiserror::Error)]
-enum SerError {
-    #[error("Message Serialization: LengthOverflow")]
-    LengthOverflow,
-
-    #[error("Message Serialization: IntOverflow: {0}")]
-    IntOverflow(#[from] TryFromIntError),
-}
-
-impl Message {
-    /// <https://docs.aws.amazon.com/AmazonS3/latest/API/RESTSelectObjectAppendix.html>
-    fn serialize(self) -> Result<Bytes, SerError> {
-        let total_byte_length: u32;
-        let headers_byte_length: u32;
-        {
-            let headers_len = self.headers.iter().try_fold(0, |mut acc: usize, h| {
-                acc = acc.checked_add(1 + 1 + 2)?;
-                acc = acc.checked_add(h.name.len())?;
-                acc = acc.checked_add(h.value.len())?;
-                Some(acc)
-            });
-
-            let payload_len = self.payload.as_ref().map_or(0, Bytes::len);
-
-            let total_len = headers_len
-                .and_then(|acc| acc.checked_add(4 + 4 + 4 + 4))
-                .and_then(|acc| acc.checked_add(payload_len));
-
-            total_byte_length = u32::try_from(total_len.ok_or(SerError::LengthOverflow)?)?;
-            headers_byte_length = u32::try_from(headers_len.ok_or(SerError::LengthOverflow)?)?;
-        }
-
-        let mut buf: Vec<u8> = Vec::with_capacity(total_byte_length as usize);
-        buf.put_u32(total_byte_length);
-        buf.put_u32(headers_byte_length);
-
-        let prelude_crc = crc32fast::hash(&buf);
-        buf.put_u32(prelude_crc);
-
-        for h in &self.headers {
-            let header_name_byte_length = u8::try_from(h.name.len())?;
-            let value_string_byte_length = u16::try_from(h.value.len())?;
-
-            buf.put_u8(header_name_byte_length);
-            buf.put(&*h.name);
-
-            buf.put_u8(7);
-            buf.put_u16(value_string_byte_length);
-            buf.put(&*h.value);
-        }
-
-        if let Some(payload) = self.payload.as_deref() {
-            buf.put(payload);
-        }
-
-        let message_crc = crc32fast::hash(&buf);
-        buf.put_u32(message_crc);
-
-        Ok(buf.into())
-    }
-}
-
 impl SelectObjectContentEvent {
     fn into_message(self) -> Message {
         match self {
@@ -186,8 +113,10 @@ impl ContinuationEvent {
             (EVENT_TYPE, "Cont"),    //
             (MESSAGE_TYPE, "event"), //
         ]);
-        let payload = None;
-        Message { headers, payload }
+        Message {
+            headers,
+            payload: Bytes::new(),
+        }
     }
 }
 
@@ -198,8 +127,10 @@ impl EndEvent {
             (EVENT_TYPE, "End"),     //
             (MESSAGE_TYPE, "event"), //
         ]);
-        let payload = None;
-        Message { headers, payload }
+        Message {
+            headers,
+            payload: Bytes::new(),
+        }
     }
 }
 
@@ -210,7 +141,7 @@ impl ProgressEvent {
             (CONTENT_TYPE, "text/xml"), //
             (MESSAGE_TYPE, "event"),    //
         ]);
-        let payload = self.details.as_ref().map(xml_payload);
+        let payload = self.details.as_ref().map(xml_payload).unwrap_or_default();
         Message { headers, payload }
     }
 }
@@ -222,7 +153,7 @@ impl RecordsEvent {
             (CONTENT_TYPE, "application/octet-stream"), //
             (MESSAGE_TYPE, "event"),                    //
         ]);
-        let payload = self.payload;
+        let payload = self.payload.unwrap_or_default();
         Message { headers, payload }
     }
 }
@@ -234,17 +165,15 @@ impl StatsEvent {
             (CONTENT_TYPE, "text/xml"), //
             (MESSAGE_TYPE, "event"),    //
         ]);
-        let payload = self.details.as_ref().map(xml_payload);
+        let payload = self.details.as_ref().map(xml_payload).unwrap_or_default();
         Message { headers, payload }
     }
 }
 
-fn const_headers(hs: &'static [(&'static str, &'static str)]) -> SmallVec<[Header; 4]> {
-    let mut ans = SmallVec::with_capacity(hs.len());
-    for (name, value) in hs {
-        ans.push(header(static_str(name), static_str(value)));
-    }
-    ans
+fn const_headers(hs: &'static [(&'static str, &'static str)]) -> Vec<Header> {
+    hs.iter()
+        .map(|&(name, value)| Header::new(name, HeaderValue::String(value.into())))
+        .collect()
 }
 
 fn xml_payload<T: xml::Serialize>(val: &T) -> Bytes {
@@ -260,28 +189,18 @@ fn xml_payload<T: xml::Serialize>(val: &T) -> Bytes {
 
 fn request_level_error(e: &S3Error) -> Message {
     let code = match e.code().as_static_str() {
-        Some(s) => static_str(s),
-        None => match e.code() {
-            S3ErrorCode::Custom(s) => s.as_bytes().clone(),
-            _ => unreachable!(),
-        },
+        Some(s) => Cow::Borrowed(s),
+        None => Cow::Owned(e.code().as_str().to_owned()),
     };
+    let message = e.message().unwrap_or_default().to_owned();
 
-    let message = e.message().map_or_else(Bytes::new, |s| Bytes::copy_from_slice(s.as_bytes()));
-
-    let mut headers = SmallVec::with_capacity(3);
-    headers.push(header(static_str(":error-code"), code));
-    headers.push(header(static_str(":error-message"), message));
-    headers.push(header(static_str(MESSAGE_TYPE), static_str("error")));
-    Message { headers, payload: None }
-}
-
-#[inline]
-fn static_str(s: &'static str) -> Bytes {
-    Bytes::from_static(s.as_bytes())
-}
-
-#[inline]
-fn header(name: Bytes, value: Bytes) -> Header {
-    Header { name, value }
+    let headers = vec![
+        Header::new(":error-code", HeaderValue::String(code)),
+        Header::new(":error-message", HeaderValue::String(message.into())),
+        Header::new(MESSAGE_TYPE, HeaderValue::String("error".into())),
+    ];
+    Message {
+        headers,
+        payload: Bytes::new(),
+    }
 }
@@ -2968,6 +2968,9 @@ pub struct DeleteObjectInput {
     pub bypass_governance_retention: Option<BypassGovernanceRetention>,
     /// <p>The account ID of the expected bucket owner. If the bucket is owned by a different account, the request fails with the HTTP status code <code>403 Forbidden</code> (access denied).</p>
     pub expected_bucket_owner: Option<AccountId>,
+    /// <p>Deletes the object only if its entity tag (ETag) matches the specified tag.
+    /// Otherwise, Amazon S3 returns a <code>412 Precondition Failed</code> error.</p>
+    pub if_match: Option<IfMatch>,
     /// <p>Key name of the object to delete.</p>
     pub key: ObjectKey,
     /// <p>The concatenation of the authentication device's serial number, a space, and the value
@@ -2989,6 +2992,9 @@ impl fmt::Debug for DeleteObjectInput {
         if let Some(ref val) = self.expected_bucket_owner {
             d.field("expected_bucket_owner", val);
         }
+        if let Some(ref val) = self.if_match {
+            d.field("if_match", val);
+        }
         d.field("key", &self.key);
         if let Some(ref val) = self.mfa {
             d.field("mfa", val);
@@ -12995,6 +13001,10 @@ pub struct PutObjectInput {
     /// <p>Allows grantee to write the ACL for the applicable object.</p>
     /// <p>This action is not supported by Amazon S3 on Outposts.</p>
     pub grant_write_acp: Option<GrantWriteACP>,
+    /// <p>Uploads the object only if the object key name does not already exist in the bucket specified.
+    /// Otherwise, Amazon S3 returns a <code>412 Precondition Failed</code> error.</p>
+    /// <p>Expects the '*' (asterisk) character.</p>
+    pub if_none_match: Option<IfNoneMatch>,
     /// <p>Object key for which the PUT action was initiated.</p>
     pub key: ObjectKey,
     /// <p>A map of metadata to store with the object in S3.</p>
@@ -13133,6 +13143,9 @@ impl fmt::Debug for PutObjectInput {
         if let Some(ref val) = self.grant_write_acp {
             d.field("grant_write_acp", val);
         }
+        if let Some(ref val) = self.if_none_match {
+            d.field("if_none_match", val);
+        }
         d.field("key", &self.key);
         if let Some(ref val) = self.metadata {
             d.field("metadata", val);
@@ -18666,6 +18679,8 @@ pub mod builders {
 
         expected_bucket_owner: Option<AccountId>,
 
+        if_match: Option<IfMatch>,
+
         key: Option<ObjectKey>,
 
         mfa: Option<MFA>,
@@ -18691,6 +18706,11 @@ pub mod builders {
             self
         }
 
+        pub fn set_if_match(&mut self, field: Option<IfMatch>) -> &mut Self {
+            self.if_match = field;
+            self
+        }
+
         pub fn set_key(&mut self, field: ObjectKey) -> &mut Self {
             self.key = Some(field);
             self
@@ -18729,6 +18749,12 @@ pub mod builders {
             self
         }
 
+        #[must_use]
+        pub fn if_match(mut self, field: Option<IfMatch>) -> Self {
+            self.if_match = field;
+            self
+        }
+
         #[must_use]
         pub fn key(mut self, field: ObjectKey) -> Self {
             self.key = Some(field);
@@ -18757,6 +18783,7 @@ pub mod builders {
             let bucket = self.bucket.ok_or_else(|| BuildError::missing_field("bucket"))?;
             let bypass_governance_retention = self.bypass_governance_retention;
             let expected_bucket_owner = self.expected_bucket_owner;
+            let if_match = self.if_match;
             let key = self.key.ok_or_else(|| BuildError::missing_field("key"))?;
             let mfa = self.mfa;
             let request_payer = self.request_payer;
@@ -18765,6 +18792,7 @@ pub mod builders {
                 bucket,
                 bypass_governance_retention,
                 expected_bucket_owner,
+                if_match,
                 key,
                 mfa,
                 request_payer,
@@ -23786,6 +23814,8 @@ pub mod builders {
 
         grant_write_acp: Option<GrantWriteACP>,
 
+        if_none_match: Option<IfNoneMatch>,
+
         key: Option<ObjectKey>,
 
         metadata: Option<Metadata>,
@@ -23928,6 +23958,11 @@ pub mod builders {
             self
         }
 
+        pub fn set_if_none_match(&mut self, field: Option<IfNoneMatch>) -> &mut Self {
+            self.if_none_match = field;
+            self
+        }
+
         pub fn set_key(&mut self, field: ObjectKey) -> &mut Self {
             self.key = Some(field);
             self
@@ -24135,6 +24170,12 @@ pub mod builders {
             self
         }
 
+        #[must_use]
+        pub fn if_none_match(mut self, field: Option<IfNoneMatch>) -> Self {
+            self.if_none_match = field;
+            self
+        }
+
         #[must_use]
         pub fn key(mut self, field: ObjectKey) -> Self {
             self.key = Some(field);
@@ -24248,6 +24289,7 @@ pub mod builders {
             let grant_read = self.grant_read;
             let grant_read_acp = self.grant_read_acp;
             let grant_write_acp = self.grant_write_acp;
+            let if_none_match = self.if_none_match;
             let key = self.key.ok_or_else(|| BuildError::missing_field("key"))?;
             let metadata = self.metadata;
             let object_lock_legal_hold_status = self.object_lock_legal_hold_status;
@@ -24286,6 +24328,7 @@ pub mod builders {
                 grant_read,
                 grant_read_acp,
                 grant_write_acp,
+                if_none_match,
                 key,
                 metadata,
                 object_lock_legal_hold_status,
@@ -111,24 +111,26 @@ impl Range {
         match *self {
             Range::Int { first, last } => match last {
                 Some(last) => {
-                    if first > last || last >= full_length {
+                    if first > last || first >= full_length {
                         return Err(err());
                     }
-                    // first <= last < full_length
-                    Ok(first..last + 1)
+                    // first <= last, first < full_length
+                    // a last position beyond the representation is clamped
+                    Ok(first..last.min(full_length - 1) + 1)
                 }
                 None => {
-                    if first > full_length {
+                    if first >= full_length {
                         return Err(err());
                     }
                     Ok(first..full_length)
                 }
             },
             Range::Suffix { length } => {
-                if length > full_length {
+                if length == 0 || full_length == 0 {
                     return Err(err());
                 }
-                Ok((full_length - length)..full_length)
+                // a suffix longer than the representation selects the whole representation
+                Ok(full_length.saturating_sub(length)..full_length)
             }
         }
     }
@@ -193,6 +195,7 @@ mod tests {
             ("bytes=-500 ", Err(())),
             ("bytes=-+500", Err(())),
             ("bytes=-1000000000000000000000000", Err(())),
+            ("bytes=0-1,5-6", Err(())),
         ];
 
         for (input, expected) in &cases {
@@ -203,4 +206,27 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn check_range() {
+        let cases = [
+            (range_int_inclusive(0, 499), 10000, Ok(0..500)),
+            (range_int_inclusive(9500, 10999), 10000, Ok(9500..10000)),
+            (range_int_inclusive(10000, 10999), 10000, Err(())),
+            (range_int_from(9500), 10000, Ok(9500..10000)),
+            (range_int_from(10000), 10000, Err(())),
+            (range_suffix(500), 10000, Ok(9500..10000)),
+            (range_suffix(20000), 10000, Ok(0..10000)),
+            (range_suffix(0), 10000, Err(())),
+            (range_suffix(500), 0, Err(())),
+        ];
+
+        for (range, full_length, expected) in cases {
+            let output = range.check(full_length);
+            match expected {
+                Ok(expected) => assert_eq!(output.unwrap(), expected),
+                Err(()) => assert!(output.is_err()),
+            }
+        }
+    }
 }
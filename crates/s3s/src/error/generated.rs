@@ -12,6 +12,12 @@ pub enum S3ErrorCode {
     ///
     AccessDenied,
 
+    /// The CORS request is not allowed.
+    ///
+    /// HTTP Status Code: 403 Forbidden
+    ///
+    AccessForbidden,
+
     /// There is a problem with your Amazon Web Services account that prevents the action from completing successfully. Contact Amazon Web Services Support for further assistance.
     ///
     /// HTTP Status Code: 403 Forbidden
@@ -233,6 +239,12 @@ pub enum S3ErrorCode {
     ///
     InvalidStorageClass,
 
+    /// The tag provided was not a valid tag. This error can occur if the tag did not pass input validation.
+    ///
+    /// HTTP Status Code: 400 Bad Request
+    ///
+    InvalidTag,
+
     /// The target bucket for logging does not exist, is not owned by you, or does not have the appropriate grants for the log-delivery group.
     ///
     /// HTTP Status Code: 400 Bad Request
@@ -269,6 +281,12 @@ pub enum S3ErrorCode {
     ///
     MalformedPOSTRequest,
 
+    /// The policy document is not well-formed or does not match the policy grammar.
+    ///
+    /// HTTP Status Code: 400 Bad Request
+    ///
+    MalformedPolicy,
+
     /// This happens when the user sends malformed XML (XML that doesn't conform to the published XSD) for the configuration. The error message is, "The XML you provided was not well-formed or did not validate against our published schema."
     ///
     /// HTTP Status Code: 400 Bad Request
@@ -345,6 +363,12 @@ pub enum S3ErrorCode {
     ///
     NoSuchBucketPolicy,
 
+    /// The CORS configuration does not exist.
+    ///
+    /// HTTP Status Code: 404 Not Found
+    ///
+    NoSuchCORSConfiguration,
+
     /// The specified key does not exist.
     ///
     /// HTTP Status Code: 404 Not Found
@@ -357,6 +381,12 @@ pub enum S3ErrorCode {
     ///
     NoSuchLifecycleConfiguration,
 
+    /// The specified object does not have an Object Lock configuration.
+    ///
+    /// HTTP Status Code: 404 Not Found
+    ///
+    NoSuchObjectLockConfiguration,
+
     /// The specified multipart upload does not exist. The upload ID might be invalid, or the multipart upload might have been aborted or completed.
     ///
     /// HTTP Status Code: 404 Not Found
@@ -369,6 +399,12 @@ pub enum S3ErrorCode {
     ///
     NoSuchVersion,
 
+    /// The specified bucket does not have a website configuration.
+    ///
+    /// HTTP Status Code: 404 Not Found
+    ///
+    NoSuchWebsiteConfiguration,
+
     /// A header you provided implies functionality that is not implemented.
     ///
     /// HTTP Status Code: 501 Not Implemented
@@ -381,6 +417,12 @@ pub enum S3ErrorCode {
     ///
     NotSignedUp,
 
+    /// Object Lock configuration does not exist for this bucket.
+    ///
+    /// HTTP Status Code: 404 Not Found
+    ///
+    ObjectLockConfigurationNotFoundError,
+
     /// A conflicting conditional action is currently in progress against this resource. Try again.
     ///
     /// HTTP Status Code: 409 Conflict
@@ -494,7 +536,8 @@ pub enum S3ErrorCode {
 
 impl S3ErrorCode {
     const STATIC_CODE_LIST: &'static [&'static str] = &[
-        "AccessDenied ",
+        "AccessDenied",
+        "AccessForbidden",
         "AccountProblem",
         "AllAccessDisabled",
         "AmbiguousGrantByEmailAddress",
@@ -508,7 +551,7 @@ impl S3ErrorCode {
         "EntityTooLarge",
         "EntityTooSmall",
         "ExpiredToken",
-        "IllegalVersioningConfigurationException ",
+        "IllegalVersioningConfigurationException",
         "IncompleteBody",
         "IncorrectNumberOfFilesInPostRequest",
         "InlineDataTooLarge",
@@ -531,12 +574,14 @@ impl S3ErrorCode {
         "InvalidSOAPRequest",
         "InvalidSecurity",
         "InvalidStorageClass",
+        "InvalidTag",
         "InvalidTargetBucketForLogging",
         "InvalidToken",
         "InvalidURI",
         "KeyTooLongError",
         "MalformedACLError",
-        "MalformedPOSTRequest ",
+        "MalformedPOSTRequest",
+        "MalformedPolicy",
         "MalformedXML",
         "MaxMessageLengthExceeded",
         "MaxPostPreDataLengthExceededError",
@@ -550,12 +595,16 @@ impl S3ErrorCode {
         "NoLoggingStatusForKey",
         "NoSuchBucket",
         "NoSuchBucketPolicy",
+        "NoSuchCORSConfiguration",
         "NoSuchKey",
         "NoSuchLifecycleConfiguration",
+        "NoSuchObjectLockConfiguration",
         "NoSuchUpload",
-        "NoSuchVersion ",
+        "NoSuchVersion",
+        "NoSuchWebsiteConfiguration",
         "NotImplemented",
         "NotSignedUp",
+        "ObjectLockConfigurationNotFoundError",
         "OperationAborted",
         "PermanentRedirect",
         "PreconditionFailed",
@@ -580,85 +629,92 @@ impl S3ErrorCode {
     fn as_enum_tag(&self) -> usize {
         match self {
             Self::AccessDenied => 0,
-            Self::AccountProblem => 1,
-            Self::AllAccessDisabled => 2,
-            Self::AmbiguousGrantByEmailAddress => 3,
-            Self::AuthorizationHeaderMalformed => 4,
-            Self::BadDigest => 5,
-            Self::BucketAlreadyExists => 6,
-            Self::BucketAlreadyOwnedByYou => 7,
-            Self::BucketNotEmpty => 8,
-            Self::CredentialsNotSupported => 9,
-            Self::CrossLocationLoggingProhibited => 10,
-            Self::EntityTooLarge => 11,
-            Self::EntityTooSmall => 12,
-            Self::ExpiredToken => 13,
-            Self::IllegalVersioningConfigurationException => 14,
-            Self::IncompleteBody => 15,
-            Self::IncorrectNumberOfFilesInPostRequest => 16,
-            Self::InlineDataTooLarge => 17,
-            Self::InternalError => 18,
-            Self::InvalidAccessKeyId => 19,
-            Self::InvalidAddressingHeader => 20,
-            Self::InvalidArgument => 21,
-            Self::InvalidBucketName => 22,
-            Self::InvalidBucketState => 23,
-            Self::InvalidDigest => 24,
-            Self::InvalidEncryptionAlgorithmError => 25,
-            Self::InvalidLocationConstraint => 26,
-            Self::InvalidObjectState => 27,
-            Self::InvalidPart => 28,
-            Self::InvalidPartOrder => 29,
-            Self::InvalidPayer => 30,
-            Self::InvalidPolicyDocument => 31,
-            Self::InvalidRange => 32,
-            Self::InvalidRequest => 33,
-            Self::InvalidSOAPRequest => 34,
-            Self::InvalidSecurity => 35,
-            Self::InvalidStorageClass => 36,
-            Self::InvalidTargetBucketForLogging => 37,
-            Self::InvalidToken => 38,
-            Self::InvalidURI => 39,
-            Self::KeyTooLongError => 40,
-            Self::MalformedACLError => 41,
-            Self::MalformedPOSTRequest => 42,
-            Self::MalformedXML => 43,
-            Self::MaxMessageLengthExceeded => 44,
-            Self::MaxPostPreDataLengthExceededError => 45,
-            Self::MetadataTooLarge => 46,
-            Self::MethodNotAllowed => 47,
-            Self::MissingAttachment => 48,
-            Self::MissingContentLength => 49,
-            Self::MissingRequestBodyError => 50,
-            Self::MissingSecurityElement => 51,
-            Self::MissingSecurityHeader => 52,
-            Self::NoLoggingStatusForKey => 53,
-            Self::NoSuchBucket => 54,
-            Self::NoSuchBucketPolicy => 55,
-            Self::NoSuchKey => 56,
-            Self::NoSuchLifecycleConfiguration => 57,
-            Self::NoSuchUpload => 58,
-            Self::NoSuchVersion => 59,
-            Self::NotImplemented => 60,
-            Self::NotSignedUp => 61,
-            Self::OperationAborted => 62,
-            Self::PermanentRedirect => 63,
-            Self::PreconditionFailed => 64,
-            Self::Redirect => 65,
-            Self::RequestIsNotMultiPartContent => 66,
-            Self::RequestTimeTooSkewed => 67,
-            Self::RequestTimeout => 68,
-            Self::RequestTorrentOfBucketError => 69,
-            Self::RestoreAlreadyInProgress => 70,
-            Self::ServiceUnavailable => 71,
-            Self::SignatureDoesNotMatch => 72,
-            Self::SlowDown => 73,
-            Self::TemporaryRedirect => 74,
-            Self::TokenRefreshRequired => 75,
-            Self::TooManyBuckets => 76,
-            Self::UnexpectedContent => 77,
-            Self::UnresolvableGrantByEmailAddress => 78,
-            Self::UserKeyMustBeSpecified => 79,
+            Self::AccessForbidden => 1,
+            Self::AccountProblem => 2,
+            Self::AllAccessDisabled => 3,
+            Self::AmbiguousGrantByEmailAddress => 4,
+            Self::AuthorizationHeaderMalformed => 5,
+            Self::BadDigest => 6,
+            Self::BucketAlreadyExists => 7,
+            Self::BucketAlreadyOwnedByYou => 8,
+            Self::BucketNotEmpty => 9,
+            Self::CredentialsNotSupported => 10,
+            Self::CrossLocationLoggingProhibited => 11,
+            Self::EntityTooLarge => 12,
+            Self::EntityTooSmall => 13,
+            Self::ExpiredToken => 14,
+            Self::IllegalVersioningConfigurationException => 15,
+            Self::IncompleteBody => 16,
+            Self::IncorrectNumberOfFilesInPostRequest => 17,
+            Self::InlineDataTooLarge => 18,
+            Self::InternalError => 19,
+            Self::InvalidAccessKeyId => 20,
+            Self::InvalidAddressingHeader => 21,
+            Self::InvalidArgument => 22,
+            Self::InvalidBucketName => 23,
+            Self::InvalidBucketState => 24,
+            Self::InvalidDigest => 25,
+            Self::InvalidEncryptionAlgorithmError => 26,
+            Self::InvalidLocationConstraint => 27,
+            Self::InvalidObjectState => 28,
+            Self::InvalidPart => 29,
+            Self::InvalidPartOrder => 30,
+            Self::InvalidPayer => 31,
+            Self::InvalidPolicyDocument => 32,
+            Self::InvalidRange => 33,
+            Self::InvalidRequest => 34,
+            Self::InvalidSOAPRequest => 35,
+            Self::InvalidSecurity => 36,
+            Self::InvalidStorageClass => 37,
+            Self::InvalidTag => 38,
+            Self::InvalidTargetBucketForLogging => 39,
+            Self::InvalidToken => 40,
+            Self::InvalidURI => 41,
+            Self::KeyTooLongError => 42,
+            Self::MalformedACLError => 43,
+            Self::MalformedPOSTRequest => 44,
+            Self::MalformedPolicy => 45,
+            Self::MalformedXML => 46,
+            Self::MaxMessageLengthExceeded => 47,
+            Self::MaxPostPreDataLengthExceededError => 48,
+            Self::MetadataTooLarge => 49,
+            Self::MethodNotAllowed => 50,
+            Self::MissingAttachment => 51,
+            Self::MissingContentLength => 52,
+            Self::MissingRequestBodyError => 53,
+            Self::MissingSecurityElement => 54,
+            Self::MissingSecurityHeader => 55,
+            Self::NoLoggingStatusForKey => 56,
+            Self::NoSuchBucket => 57,
+            Self::NoSuchBucketPolicy => 58,
+            Self::NoSuchCORSConfiguration => 59,
+            Self::NoSuchKey => 60,
+            Self::NoSuchLifecycleConfiguration => 61,
+            Self::NoSuchObjectLockConfiguration => 62,
+            Self::NoSuchUpload => 63,
+            Self::NoSuchVersion => 64,
+            Self::NoSuchWebsiteConfiguration => 65,
+            Self::NotImplemented => 66,
+            Self::NotSignedUp => 67,
+            Self::ObjectLockConfigurationNotFoundError => 68,
+            Self::OperationAborted => 69,
+            Self::PermanentRedirect => 70,
+            Self::PreconditionFailed => 71,
+            Self::Redirect => 72,
+            Self::RequestIsNotMultiPartContent => 73,
+            Self::RequestTimeTooSkewed => 74,
+            Self::RequestTimeout => 75,
+            Self::RequestTorrentOfBucketError => 76,
+            Self::RestoreAlreadyInProgress => 77,
+            Self::ServiceUnavailable => 78,
+            Self::SignatureDoesNotMatch => 79,
+            Self::SlowDown => 80,
+            Self::TemporaryRedirect => 81,
+            Self::TokenRefreshRequired => 82,
+            Self::TooManyBuckets => 83,
+            Self::UnexpectedContent => 84,
+            Self::UnresolvableGrantByEmailAddress => 85,
+            Self::UserKeyMustBeSpecified => 86,
             Self::Custom(_) => usize::MAX,
         }
     }
@@ -670,7 +726,8 @@ impl S3ErrorCode {
     #[must_use]
     pub fn from_bytes(s: &[u8]) -> Option<Self> {
         match s {
-            b"AccessDenied " => Some(Self::AccessDenied),
+            b"AccessDenied" => Some(Self::AccessDenied),
+            b"AccessForbidden" => Some(Self::AccessForbidden),
             b"AccountProblem" => Some(Self::AccountProblem),
             b"AllAccessDisabled" => Some(Self::AllAccessDisabled),
             b"AmbiguousGrantByEmailAddress" => Some(Self::AmbiguousGrantByEmailAddress),
@@ -684,7 +741,7 @@ impl S3ErrorCode {
             b"EntityTooLarge" => Some(Self::EntityTooLarge),
             b"EntityTooSmall" => Some(Self::EntityTooSmall),
             b"ExpiredToken" => Some(Self::ExpiredToken),
-            b"IllegalVersioningConfigurationException " => Some(Self::IllegalVersioningConfigurationException),
+            b"IllegalVersioningConfigurationException" => Some(Self::IllegalVersioningConfigurationException),
             b"IncompleteBody" => Some(Self::IncompleteBody),
             b"IncorrectNumberOfFilesInPostRequest" => Some(Self::IncorrectNumberOfFilesInPostRequest),
             b"InlineDataTooLarge" => Some(Self::InlineDataTooLarge),
@@ -707,12 +764,14 @@ impl S3ErrorCode {
             b"InvalidSOAPRequest" => Some(Self::InvalidSOAPRequest),
             b"InvalidSecurity" => Some(Self::InvalidSecurity),
             b"InvalidStorageClass" => Some(Self::InvalidStorageClass),
+            b"InvalidTag" => Some(Self::InvalidTag),
             b"InvalidTargetBucketForLogging" => Some(Self::InvalidTargetBucketForLogging),
             b"InvalidToken" => Some(Self::InvalidToken),
             b"InvalidURI" => Some(Self::InvalidURI),
             b"KeyTooLongError" => Some(Self::KeyTooLongError),
             b"MalformedACLError" => Some(Self::MalformedACLError),
-            b"MalformedPOSTRequest " => Some(Self::MalformedPOSTRequest),
+            b"MalformedPOSTRequest" => Some(Self::MalformedPOSTRequest),
+            b"MalformedPolicy" => Some(Self::MalformedPolicy),
             b"MalformedXML" => Some(Self::MalformedXML),
             b"MaxMessageLengthExceeded" => Some(Self::MaxMessageLengthExceeded),
             b"MaxPostPreDataLengthExceededError" => Some(Self::MaxPostPreDataLengthExceededError),
@@ -726,12 +785,16 @@ impl S3ErrorCode {
             b"NoLoggingStatusForKey" => Some(Self::NoLoggingStatusForKey),
             b"NoSuchBucket" => Some(Self::NoSuchBucket),
             b"NoSuchBucketPolicy" => Some(Self::NoSuchBucketPolicy),
+            b"NoSuchCORSConfiguration" => Some(Self::NoSuchCORSConfiguration),
             b"NoSuchKey" => Some(Self::NoSuchKey),
             b"NoSuchLifecycleConfiguration" => Some(Self::NoSuchLifecycleConfiguration),
+            b"NoSuchObjectLockConfiguration" => Some(Self::NoSuchObjectLockConfiguration),
             b"NoSuchUpload" => Some(Self::NoSuchUpload),
-            b"NoSuchVersion " => Some(Self::NoSuchVersion),
+            b"NoSuchVersion" => Some(Self::NoSuchVersion),
+            b"NoSuchWebsiteConfiguration" => Some(Self::NoSuchWebsiteConfiguration),
             b"NotImplemented" => Some(Self::NotImplemented),
             b"NotSignedUp" => Some(Self::NotSignedUp),
+            b"ObjectLockConfigurationNotFoundError" => Some(Self::ObjectLockConfigurationNotFoundError),
             b"OperationAborted" => Some(Self::OperationAborted),
             b"PermanentRedirect" => Some(Self::PermanentRedirect),
             b"PreconditionFailed" => Some(Self::PreconditionFailed),
@@ -759,6 +822,7 @@ impl S3ErrorCode {
     pub fn status_code(&self) -> Option<StatusCode> {
         match self {
             Self::AccessDenied => Some(StatusCode::FORBIDDEN),
+            Self::AccessForbidden => Some(StatusCode::FORBIDDEN),
             Self::AccountProblem => Some(StatusCode::FORBIDDEN),
             Self::AllAccessDisabled => Some(StatusCode::FORBIDDEN),
             Self::AmbiguousGrantByEmailAddress => Some(StatusCode::BAD_REQUEST),
@@ -795,12 +859,14 @@ impl S3ErrorCode {
             Self::InvalidSOAPRequest => Some(StatusCode::BAD_REQUEST),
             Self::InvalidSecurity => Some(StatusCode::FORBIDDEN),
             Self::InvalidStorageClass => Some(StatusCode::BAD_REQUEST),
+            Self::InvalidTag => Some(StatusCode::BAD_REQUEST),
             Self::InvalidTargetBucketForLogging => Some(StatusCode::BAD_REQUEST),
             Self::InvalidToken => Some(StatusCode::BAD_REQUEST),
             Self::InvalidURI => Some(StatusCode::BAD_REQUEST),
             Self::KeyTooLongError => Some(StatusCode::BAD_REQUEST),
             Self::MalformedACLError => Some(StatusCode::BAD_REQUEST),
             Self::MalformedPOSTRequest => Some(StatusCode::BAD_REQUEST),
+            Self::MalformedPolicy => Some(StatusCode::BAD_REQUEST),
             Self::MalformedXML => Some(StatusCode::BAD_REQUEST),
             Self::MaxMessageLengthExceeded => Some(StatusCode::BAD_REQUEST),
             Self::MaxPostPreDataLengthExceededError => Some(StatusCode::BAD_REQUEST),
@@ -814,12 +880,16 @@ impl S3ErrorCode {
             Self::NoLoggingStatusForKey => Some(StatusCode::BAD_REQUEST),
             Self::NoSuchBucket => Some(StatusCode::NOT_FOUND),
             Self::NoSuchBucketPolicy => Some(StatusCode::NOT_FOUND),
+            Self::NoSuchCORSConfiguration => Some(StatusCode::NOT_FOUND),
             Self::NoSuchKey => Some(StatusCode::NOT_FOUND),
             Self::NoSuchLifecycleConfiguration => Some(StatusCode::NOT_FOUND),
+            Self::NoSuchObjectLockConfiguration => Some(StatusCode::NOT_FOUND),
             Self::NoSuchUpload => Some(StatusCode::NOT_FOUND),
             Self::NoSuchVersion => Some(StatusCode::NOT_FOUND),
+            Self::NoSuchWebsiteConfiguration => Some(StatusCode::NOT_FOUND),
             Self::NotImplemented => Some(StatusCode::NOT_IMPLEMENTED),
             Self::NotSignedUp => Some(StatusCode::FORBIDDEN),
+            Self::ObjectLockConfigurationNotFoundError => Some(StatusCode::NOT_FOUND),
             Self::OperationAborted => Some(StatusCode::CONFLICT),
             Self::PermanentRedirect => Some(StatusCode::MOVED_PERMANENTLY),
             Self::PreconditionFailed => Some(StatusCode::PRECONDITION_FAILED),
@@ -9,6 +9,8 @@ use std::fmt;
 use std::io::Write;
 use std::str::FromStr;
 
+use hyper::http::HeaderValue;
+use hyper::HeaderMap;
 use hyper::StatusCode;
 
 pub type StdError = Box<dyn std::error::Error + Send + Sync + 'static>;
@@ -22,9 +24,10 @@ pub struct S3Error(Box<Inner>);
 struct Inner {
     code: S3ErrorCode,
     message: Option<Cow<'static, str>>,
-    // resource: Option<String>,
+    resource: Option<String>,
     request_id: Option<String>,
     status_code: Option<StatusCode>,
+    headers: Option<HeaderMap<HeaderValue>>,
     source: Option<StdError>,
 }
 
@@ -34,9 +37,10 @@ impl S3Error {
         Self(Box::new(Inner {
             code,
             message: None,
-            // resource: None,
+            resource: None,
             request_id: None,
             status_code: None,
+            headers: None,
             source: None,
         }))
     }
@@ -63,6 +67,10 @@ impl S3Error {
         self.0.message = Some(val.into());
     }
 
+    pub fn set_resource(&mut self, val: impl Into<String>) {
+        self.0.resource = Some(val.into());
+    }
+
     pub fn set_request_id(&mut self, val: impl Into<String>) {
         self.0.request_id = Some(val.into());
     }
@@ -75,6 +83,11 @@ impl S3Error {
         self.0.status_code = Some(val);
     }
 
+    /// Sets extra headers of the error response.
+    pub fn set_headers(&mut self, val: HeaderMap<HeaderValue>) {
+        self.0.headers = Some(val);
+    }
+
     #[must_use]
     pub fn code(&self) -> &S3ErrorCode {
         &self.0.code
@@ -85,6 +98,11 @@ impl S3Error {
         self.0.message.as_deref()
     }
 
+    #[must_use]
+    pub fn resource(&self) -> Option<&str> {
+        self.0.resource.as_deref()
+    }
+
     #[must_use]
     pub fn request_id(&self) -> Option<&str> {
         self.0.request_id.as_deref()
@@ -100,6 +118,11 @@ impl S3Error {
         self.0.status_code.or_else(|| self.0.code.status_code())
     }
 
+    #[must_use]
+    pub fn headers(&self) -> Option<&HeaderMap<HeaderValue>> {
+        self.0.headers.as_ref()
+    }
+
     #[must_use]
     pub fn internal_error<E>(source: E) -> Self
     where
@@ -121,9 +144,9 @@ impl xml::SerializeContent for S3Error {
         if let Some(val) = self.0.message.as_deref() {
             s.content("Message", val)?;
         }
-        // if let Some(val) = self.0.resource.as_deref() {
-        //     s.content("Resource", val)?;
-        // }
+        if let Some(val) = self.0.resource.as_deref() {
+            s.content("Resource", val)?;
+        }
         if let Some(val) = self.0.request_id.as_deref() {
             s.content("RequestId", val)?;
         }
@@ -150,6 +173,9 @@ impl fmt::Display for S3Error {
         if let Some(ref message) = self.0.message {
             d.field("message", &message);
         }
+        if let Some(ref resource) = self.0.resource {
+            d.field("resource", &resource);
+        }
         if let Some(ref request_id) = self.0.request_id {
             d.field("request_id", &request_id);
         }
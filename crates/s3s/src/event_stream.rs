@@ -0,0 +1,445 @@
+#![deny(missing_docs)]
+
+//! The binary framing of event streams (`application/vnd.amazon.eventstream`).
+//!
+//! A message consists of a prelude, headers, a payload and checksums:
+//!
+//! ```text
+//! [total length: u32] [headers length: u32] [prelude crc: u32] [headers] [payload] [message crc: u32]
+//! ```
+//!
+//! + [Event stream encoding](https://docs.aws.amazon.com/AmazonS3/latest/API/RESTSelectObjectAppendix.html)
+//! + [Event stream encoding (Transcribe)](https://docs.aws.amazon.com/transcribe/latest/dg/event-stream.html)
+
+use std::borrow::Cow;
+use std::num::TryFromIntError;
+use std::ops::Not;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+/// The length of the prelude, including its checksum
+const PRELUDE_LENGTH: usize = 4 + 4 + 4;
+
+/// The length of the prelude and the message checksum
+const MIN_MESSAGE_LENGTH: usize = PRELUDE_LENGTH + 4;
+
+/// The maximum length of a message, which is 16 MiB
+const MAX_MESSAGE_LENGTH: usize = 16 * 1024 * 1024;
+
+/// The maximum length of the headers of a message, which is 128 KiB
+const MAX_HEADERS_LENGTH: usize = 128 * 1024;
+
+/// A message of an event stream
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    /// The headers, which are ordered
+    pub headers: Vec<Header>,
+    /// The payload
+    pub payload: Bytes,
+}
+
+/// A header of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The name, which is at most 255 bytes
+    pub name: Cow<'static, str>,
+    /// The value
+    pub value: HeaderValue,
+}
+
+/// A typed header value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderValue {
+    /// A boolean, whose type is `0` (true) or `1` (false)
+    Bool(bool),
+    /// A byte, whose type is `2`
+    Byte(i8),
+    /// A 16-bit integer, whose type is `3`
+    Int16(i16),
+    /// A 32-bit integer, whose type is `4`
+    Int32(i32),
+    /// A 64-bit integer, whose type is `5`
+    Int64(i64),
+    /// A byte array, whose type is `6`
+    ByteArray(Bytes),
+    /// A UTF-8 string, whose type is `7`
+    String(Cow<'static, str>),
+    /// A timestamp in milliseconds since the Unix epoch, whose type is `8`
+    Timestamp(i64),
+    /// A UUID, whose type is `9`
+    Uuid([u8; 16]),
+}
+
+/// An error which can be returned when encoding a message
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    /// The message is too long
+    #[error("Message Serialization: LengthOverflow")]
+    LengthOverflow,
+
+    /// A header name or value is too long
+    #[error("Message Serialization: IntOverflow: {0}")]
+    IntOverflow(#[from] TryFromIntError),
+}
+
+/// An error which can be returned when decoding a message
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    /// The lengths in the prelude are invalid
+    #[error("Message Deserialization: InvalidLength")]
+    InvalidLength,
+
+    /// The checksum of the prelude does not match
+    #[error("Message Deserialization: PreludeChecksumMismatch: expected {expected:#010x}, actual {actual:#010x}")]
+    PreludeChecksumMismatch {
+        /// The checksum in the message
+        expected: u32,
+        /// The checksum of the received bytes
+        actual: u32,
+    },
+
+    /// The checksum of the message does not match
+    #[error("Message Deserialization: MessageChecksumMismatch: expected {expected:#010x}, actual {actual:#010x}")]
+    MessageChecksumMismatch {
+        /// The checksum in the message
+        expected: u32,
+        /// The checksum of the received bytes
+        actual: u32,
+    },
+
+    /// A header is truncated
+    #[error("Message Deserialization: InvalidHeader")]
+    InvalidHeader,
+
+    /// The type of a header value is unknown
+    #[error("Message Deserialization: InvalidHeaderValueType: {0}")]
+    InvalidHeaderValueType(u8),
+
+    /// A header name or string value is not UTF-8
+    #[error("Message Deserialization: InvalidUtf8")]
+    InvalidUtf8,
+}
+
+impl Header {
+    /// Creates a header
+    #[must_use]
+    pub fn new(name: impl Into<Cow<'static, str>>, value: HeaderValue) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+impl HeaderValue {
+    fn type_id(&self) -> u8 {
+        match self {
+            HeaderValue::Bool(true) => 0,
+            HeaderValue::Bool(false) => 1,
+            HeaderValue::Byte(_) => 2,
+            HeaderValue::Int16(_) => 3,
+            HeaderValue::Int32(_) => 4,
+            HeaderValue::Int64(_) => 5,
+            HeaderValue::ByteArray(_) => 6,
+            HeaderValue::String(_) => 7,
+            HeaderValue::Timestamp(_) => 8,
+            HeaderValue::Uuid(_) => 9,
+        }
+    }
+
+    /// Returns the encoded length, excluding the type
+    fn encoded_len(&self) -> usize {
+        match self {
+            HeaderValue::Bool(_) => 0,
+            HeaderValue::Byte(_) => 1,
+            HeaderValue::Int16(_) => 2,
+            HeaderValue::Int32(_) => 4,
+            HeaderValue::Int64(_) | HeaderValue::Timestamp(_) => 8,
+            HeaderValue::ByteArray(b) => 2 + b.len(),
+            HeaderValue::String(s) => 2 + s.len(),
+            HeaderValue::Uuid(_) => 16,
+        }
+    }
+
+    /// Returns the string value
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            HeaderValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl Message {
+    /// Creates a message without headers
+    #[must_use]
+    pub fn new(payload: impl Into<Bytes>) -> Self {
+        Self {
+            headers: Vec::new(),
+            payload: payload.into(),
+        }
+    }
+
+    /// Appends a header
+    #[must_use]
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.headers.push(header);
+        self
+    }
+
+    /// Returns the value of the first header with the name
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&HeaderValue> {
+        self.headers.iter().find(|h| h.name == name).map(|h| &h.value)
+    }
+
+    /// Encodes the message
+    ///
+    /// # Errors
+    /// Returns an error if the message, a header name or a header value is too long
+    pub fn encode(&self) -> Result<Bytes, EncodeError> {
+        let headers_len = self.headers.iter().try_fold(0, |acc: usize, h| {
+            acc.checked_add(1 + h.name.len() + 1)?.checked_add(h.value.encoded_len())
+        });
+        let headers_len = headers_len.ok_or(EncodeError::LengthOverflow)?;
+        let total_len = headers_len
+            .checked_add(MIN_MESSAGE_LENGTH)
+            .and_then(|acc| acc.checked_add(self.payload.len()))
+            .ok_or(EncodeError::LengthOverflow)?;
+
+        let total_byte_length = u32::try_from(total_len)?;
+        let headers_byte_length = u32::try_from(headers_len)?;
+
+        let mut buf: Vec<u8> = Vec::with_capacity(total_len);
+        buf.put_u32(total_byte_length);
+        buf.put_u32(headers_byte_length);
+
+        let prelude_crc = crc32fast::hash(&buf);
+        buf.put_u32(prelude_crc);
+
+        for h in &self.headers {
+            buf.put_u8(u8::try_from(h.name.len())?);
+            buf.put(h.name.as_bytes());
+
+            buf.put_u8(h.value.type_id());
+            match h.value {
+                HeaderValue::Bool(_) => {}
+                HeaderValue::Byte(v) => buf.put_i8(v),
+                HeaderValue::Int16(v) => buf.put_i16(v),
+                HeaderValue::Int32(v) => buf.put_i32(v),
+                HeaderValue::Int64(v) | HeaderValue::Timestamp(v) => buf.put_i64(v),
+                HeaderValue::ByteArray(ref b) => {
+                    buf.put_u16(u16::try_from(b.len())?);
+                    buf.put(&**b);
+                }
+                HeaderValue::String(ref s) => {
+                    buf.put_u16(u16::try_from(s.len())?);
+                    buf.put(s.as_bytes());
+                }
+                HeaderValue::Uuid(ref v) => buf.put(&v[..]),
+            }
+        }
+
+        buf.put(&*self.payload);
+
+        let message_crc = crc32fast::hash(&buf);
+        buf.put_u32(message_crc);
+
+        Ok(buf.into())
+    }
+
+    /// Decodes a message from the start of `buf`, and advances `buf` past it.
+    ///
+    /// Returns `Ok(None)` if `buf` does not contain a whole message yet.
+    ///
+    /// # Errors
+    /// Returns an error if the message is malformed
+    pub fn decode(buf: &mut BytesMut) -> Result<Option<Self>, DecodeError> {
+        if buf.len() < PRELUDE_LENGTH {
+            return Ok(None);
+        }
+
+        let mut prelude = &buf[..PRELUDE_LENGTH];
+        let total_len = prelude.get_u32() as usize;
+        let headers_len = prelude.get_u32() as usize;
+        let prelude_crc = prelude.get_u32();
+
+        let actual = crc32fast::hash(&buf[..8]);
+        if actual != prelude_crc {
+            return Err(DecodeError::PreludeChecksumMismatch {
+                expected: prelude_crc,
+                actual,
+            });
+        }
+        if (MIN_MESSAGE_LENGTH..=MAX_MESSAGE_LENGTH).contains(&total_len).not()
+            || headers_len > MAX_HEADERS_LENGTH
+            || headers_len > total_len - MIN_MESSAGE_LENGTH
+        {
+            return Err(DecodeError::InvalidLength);
+        }
+
+        if buf.len() < total_len {
+            return Ok(None);
+        }
+
+        let mut frame = buf.split_to(total_len).freeze();
+        let message_crc = (&frame[total_len - 4..]).get_u32();
+        let actual = crc32fast::hash(&frame[..total_len - 4]);
+        if actual != message_crc {
+            return Err(DecodeError::MessageChecksumMismatch {
+                expected: message_crc,
+                actual,
+            });
+        }
+
+        frame.advance(PRELUDE_LENGTH);
+        let mut headers_buf = frame.split_to(headers_len);
+        let payload = frame.split_to(frame.len() - 4);
+
+        let mut headers = Vec::new();
+        while headers_buf.has_remaining() {
+            headers.push(decode_header(&mut headers_buf)?);
+        }
+
+        Ok(Some(Self { headers, payload }))
+    }
+}
+
+fn take(buf: &mut Bytes, len: usize) -> Result<Bytes, DecodeError> {
+    if buf.len() < len {
+        return Err(DecodeError::InvalidHeader);
+    }
+    Ok(buf.split_to(len))
+}
+
+fn take_str(buf: &mut Bytes, len: usize) -> Result<Cow<'static, str>, DecodeError> {
+    let bytes = take(buf, len)?;
+    match String::from_utf8(bytes.into()) {
+        Ok(s) => Ok(Cow::Owned(s)),
+        Err(_) => Err(DecodeError::InvalidUtf8),
+    }
+}
+
+fn decode_header(buf: &mut Bytes) -> Result<Header, DecodeError> {
+    let name_len = usize::from(take(buf, 1)?[0]);
+    let name = take_str(buf, name_len)?;
+    let value_type = take(buf, 1)?[0];
+    let value = match value_type {
+        0 => HeaderValue::Bool(true),
+        1 => HeaderValue::Bool(false),
+        2 => HeaderValue::Byte(take(buf, 1)?.get_i8()),
+        3 => HeaderValue::Int16(take(buf, 2)?.get_i16()),
+        4 => HeaderValue::Int32(take(buf, 4)?.get_i32()),
+        5 => HeaderValue::Int64(take(buf, 8)?.get_i64()),
+        6 | 7 => {
+            let len = usize::from(take(buf, 2)?.get_u16());
+            if value_type == 6 {
+                HeaderValue::ByteArray(take(buf, len)?)
+            } else {
+                HeaderValue::String(take_str(buf, len)?)
+            }
+        }
+        8 => HeaderValue::Timestamp(take(buf, 8)?.get_i64()),
+        9 => HeaderValue::Uuid(take(buf, 16)?[..].try_into().expect("16 bytes")),
+        _ => return Err(DecodeError::InvalidHeaderValueType(value_type)),
+    };
+    Ok(Header { name, value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test messages taken from the CRT:
+    // https://github.com/awslabs/aws-c-event-stream/blob/main/tests/message_deserializer_test.c
+
+    const NO_HEADERS: &[u8] = &[
+        0x00, 0x00, 0x00, 0x1D, 0x00, 0x00, 0x00, 0x00, 0xfd, 0x52, 0x8c, 0x5a, 0x7b, 0x27, 0x66, 0x6f, 0x6f, 0x27, 0x3a, 0x27,
+        0x62, 0x61, 0x72, 0x27, 0x7d, 0xc3, 0x65, 0x39, 0x36,
+    ];
+
+    const ONE_HEADER: &[u8] = &[
+        0x00, 0x00, 0x00, 0x3D, 0x00, 0x00, 0x00, 0x20, 0x07, 0xFD, 0x83, 0x96, 0x0C, b'c', b'o', b'n', b't', b'e', b'n', b't',
+        b'-', b't', b'y', b'p', b'e', 0x07, 0x00, 0x10, b'a', b'p', b'p', b'l', b'i', b'c', b'a', b't', b'i', b'o', b'n', b'/',
+        b'j', b's', b'o', b'n', 0x7b, 0x27, 0x66, 0x6f, 0x6f, 0x27, 0x3a, 0x27, 0x62, 0x61, 0x72, 0x27, 0x7d, 0x8D, 0x9C, 0x08,
+        0xB1,
+    ];
+
+    fn decode_all(bytes: &[u8]) -> Result<Option<Message>, DecodeError> {
+        let mut buf = BytesMut::from(bytes);
+        let message = Message::decode(&mut buf)?;
+        assert!(message.is_none() || buf.is_empty());
+        Ok(message)
+    }
+
+    #[test]
+    fn known_frames() {
+        let message = decode_all(NO_HEADERS).unwrap().unwrap();
+        assert!(message.headers.is_empty());
+        assert_eq!(message.payload.as_ref(), b"{'foo':'bar'}");
+        assert_eq!(message.encode().unwrap().as_ref(), NO_HEADERS);
+
+        let message = decode_all(ONE_HEADER).unwrap().unwrap();
+        let expected = Message::new(&b"{'foo':'bar'}"[..])
+            .with_header(Header::new("content-type", HeaderValue::String("application/json".into())));
+        assert_eq!(message, expected);
+        assert_eq!(message.encode().unwrap().as_ref(), ONE_HEADER);
+    }
+
+    #[test]
+    fn round_trip_all_headers() {
+        let message = Message::new(&b"some payload"[..])
+            .with_header(Header::new("true", HeaderValue::Bool(true)))
+            .with_header(Header::new("false", HeaderValue::Bool(false)))
+            .with_header(Header::new("byte", HeaderValue::Byte(50)))
+            .with_header(Header::new("short", HeaderValue::Int16(20_000)))
+            .with_header(Header::new("int", HeaderValue::Int32(500_000)))
+            .with_header(Header::new("long", HeaderValue::Int64(50_000_000_000)))
+            .with_header(Header::new("bytes", HeaderValue::ByteArray(Bytes::from_static(b"some bytes"))))
+            .with_header(Header::new("str", HeaderValue::String("some str".into())))
+            .with_header(Header::new("time", HeaderValue::Timestamp(5_000_000_000)))
+            .with_header(Header::new(
+                "uuid",
+                HeaderValue::Uuid(0xb79b_c914_de21_4e13_b8b2_bc47_e85b_7f0b_u128.to_be_bytes()),
+            ));
+
+        let bytes = message.encode().unwrap();
+        assert_eq!(decode_all(&bytes).unwrap().unwrap(), message);
+        assert_eq!(message.header("str").and_then(HeaderValue::as_str), Some("some str"));
+    }
+
+    #[test]
+    fn partial_frames() {
+        let mut buf = BytesMut::new();
+        let mut messages = Vec::new();
+        let stream = [ONE_HEADER, NO_HEADERS].concat();
+        for chunk in stream.chunks(7) {
+            buf.extend_from_slice(chunk);
+            while let Some(message) = Message::decode(&mut buf).unwrap() {
+                messages.push(message);
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].payload.as_ref(), b"{'foo':'bar'}");
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let mut bytes = NO_HEADERS.to_vec();
+        bytes[8] ^= 1;
+        assert!(matches!(decode_all(&bytes), Err(DecodeError::PreludeChecksumMismatch { .. })));
+
+        let mut bytes = NO_HEADERS.to_vec();
+        bytes[15] ^= 1;
+        assert!(matches!(decode_all(&bytes), Err(DecodeError::MessageChecksumMismatch { .. })));
+
+        let mut bytes = ONE_HEADER.to_vec();
+        bytes[25] = 42;
+        let crc = crc32fast::hash(&bytes[..bytes.len() - 4]);
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&crc.to_be_bytes());
+        assert_eq!(decode_all(&bytes), Err(DecodeError::InvalidHeaderValueType(42)));
+    }
+}
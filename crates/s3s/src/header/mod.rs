@@ -1,2 +1,24 @@
 mod generated;
 pub use self::generated::*;
+
+use hyper::header::HeaderName;
+
+/// The trailing headers of an `aws-chunked` payload, which are not in the S3 model
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_AMZ_TRAILER: HeaderName = HeaderName::from_static("x-amz-trailer");
+
+/// The id of a request, which is not in the S3 model
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_AMZ_REQUEST_ID: HeaderName = HeaderName::from_static("x-amz-request-id");
+
+/// The extended id of a request, which is not in the S3 model
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_AMZ_ID_2: HeaderName = HeaderName::from_static("x-amz-id-2");
+
+/// The session token of temporary credentials, which is not in the S3 model
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_AMZ_SECURITY_TOKEN: HeaderName = HeaderName::from_static("x-amz-security-token");
+
+/// The protocol of a request received by a proxy, which is not in the S3 model
+#[allow(clippy::declare_interior_mutable_const)]
+pub const X_FORWARDED_PROTO: HeaderName = HeaderName::from_static("x-forwarded-proto");
@@ -2,6 +2,8 @@
 
 use crate::auth::SecretKey;
 use crate::error::StdError;
+use crate::error::{S3Error, S3ErrorCode};
+use crate::http::TrailingHeaders;
 use crate::sig_v4;
 use crate::sig_v4::AmzDate;
 use crate::stream::{ByteStream, DynByteStream, RemainingLength};
@@ -15,6 +17,8 @@ use std::task::{Context, Poll};
 use futures::pin_mut;
 use futures::stream::{Stream, StreamExt};
 use hyper::body::{Buf, Bytes};
+use hyper::header::{HeaderName, HeaderValue};
+use hyper::HeaderMap;
 use memchr::memchr;
 use transform_stream::AsyncTryStream;
 
@@ -34,7 +38,7 @@ impl Debug for AwsChunkedStream {
 
 /// signature ctx
 #[derive(Debug)]
-struct SignatureCtx {
+pub(crate) struct SignatureCtx {
     /// date
     amz_date: AmzDate,
 
@@ -48,6 +52,35 @@ struct SignatureCtx {
     prev_signature: Box<str>,
 }
 
+impl SignatureCtx {
+    pub(crate) fn new(seed_signature: Box<str>, amz_date: AmzDate, region: Box<str>, secret_key: SecretKey) -> Self {
+        Self {
+            amz_date,
+            region,
+            secret_key,
+            prev_signature: seed_signature,
+        }
+    }
+}
+
+/// trailer ctx
+#[derive(Debug)]
+pub(crate) struct TrailerCtx {
+    /// the header names declared by `x-amz-trailer`
+    declared: Vec<HeaderName>,
+
+    /// where the trailing headers are stored
+    headers: TrailingHeaders,
+}
+
+impl TrailerCtx {
+    pub(crate) fn new(declared: Vec<HeaderName>, headers: TrailingHeaders) -> Self {
+        Self { declared, headers }
+    }
+}
+
+const X_AMZ_TRAILER_SIGNATURE: &str = "x-amz-trailer-signature";
+
 /// [`AwsChunkedStream`]
 #[derive(Debug, thiserror::Error)]
 pub enum AwsChunkedStreamError {
@@ -63,14 +96,43 @@ pub enum AwsChunkedStreamError {
     /// Incomplete stream
     #[error("AwsChunkedStreamError: Incomplete")]
     Incomplete,
+    /// Malformed or missing trailing headers
+    #[error("AwsChunkedStreamError: MalformedTrailer")]
+    MalformedTrailer,
+}
+
+impl From<AwsChunkedStreamError> for S3Error {
+    fn from(e: AwsChunkedStreamError) -> Self {
+        match e {
+            AwsChunkedStreamError::Underlying(source) => match source.downcast::<S3Error>() {
+                Ok(e) => *e,
+                Err(source) => S3Error::with_source(S3ErrorCode::IncompleteBody, source),
+            },
+            AwsChunkedStreamError::SignatureMismatch => S3Error::with_message(
+                S3ErrorCode::SignatureDoesNotMatch,
+                "The chunk signature we calculated does not match the signature you provided.",
+            ),
+            AwsChunkedStreamError::FormatError => {
+                S3Error::with_message(S3ErrorCode::IncompleteBody, "The aws-chunked payload is malformed.")
+            }
+            AwsChunkedStreamError::Incomplete => S3Error::with_message(
+                S3ErrorCode::IncompleteBody,
+                "The aws-chunked payload is shorter than x-amz-decoded-content-length.",
+            ),
+            AwsChunkedStreamError::MalformedTrailer => S3Error::with_message(
+                S3ErrorCode::Custom("MalformedTrailerError".into()),
+                "The request contained trailing data that was not well-formed or did not conform to our published schema.",
+            ),
+        }
+    }
 }
 
 /// Chunk meta
 struct ChunkMeta<'a> {
     /// chunk size
     size: usize,
-    /// chunk signature
-    signature: &'a [u8],
+    /// chunk signature, which is absent in unsigned payloads
+    signature: Option<&'a [u8]>,
 }
 
 /// nom parser
@@ -89,7 +151,39 @@ fn parse_chunk_meta(mut input: &[u8]) -> nom::IResult<&[u8], ChunkMeta<'_>> {
 
     let signature = consume(s, all_consuming(delimited(tag(b";chunk-signature="), take(64_usize), tag(b"\r\n"))))?;
 
-    Ok((input, ChunkMeta { size, signature }))
+    Ok((
+        input,
+        ChunkMeta {
+            size,
+            signature: Some(signature),
+        },
+    ))
+}
+
+/// nom parser of unsigned chunk meta
+fn parse_unsigned_chunk_meta(mut input: &[u8]) -> nom::IResult<&[u8], ChunkMeta<'_>> {
+    use crate::utils::parser::consume;
+
+    use nom::bytes::complete::{tag, take_till1};
+    use nom::combinator::{all_consuming, map_res};
+    use nom::number::complete::hex_u32;
+
+    let s = &mut input;
+
+    let size = consume(s, take_till1(|c| c == b'\r'))?;
+    let (_, size) = map_res(hex_u32, TryInto::try_into)(size)?;
+
+    consume(s, all_consuming(tag(b"\r\n")))?;
+
+    Ok((input, ChunkMeta { size, signature: None }))
+}
+
+/// parses a trailing header line without CRLF
+fn parse_trailer_line(line: &[u8]) -> Option<(HeaderName, HeaderValue)> {
+    let idx = memchr(b':', line)?;
+    let name = HeaderName::from_bytes(&line[..idx]).ok()?;
+    let value = HeaderValue::from_bytes(line[idx + 1..].trim_ascii()).ok()?;
+    Some((name, value))
 }
 
 /// check signature
@@ -101,6 +195,50 @@ fn check_signature(ctx: &SignatureCtx, expected_signature: &[u8], chunk_data: &[
     (chunk_signature.as_bytes() == expected_signature).then(|| chunk_signature.into())
 }
 
+/// check the trailing headers and collect them into a header map
+fn check_trailers(
+    sig_ctx: Option<&SignatureCtx>,
+    trailer_ctx: &TrailerCtx,
+    trailers: Vec<(HeaderName, HeaderValue)>,
+) -> Result<HeaderMap, AwsChunkedStreamError> {
+    let mut headers = HeaderMap::new();
+    let mut signature = None;
+    for (name, value) in trailers {
+        if name == X_AMZ_TRAILER_SIGNATURE {
+            signature = Some(value);
+        } else if trailer_ctx.declared.contains(&name) {
+            headers.append(name, value);
+        } else {
+            return Err(AwsChunkedStreamError::MalformedTrailer);
+        }
+    }
+
+    if trailer_ctx.declared.iter().any(|name| !headers.contains_key(name)) {
+        return Err(AwsChunkedStreamError::MalformedTrailer);
+    }
+
+    if let Some(ctx) = sig_ctx {
+        let Some(signature) = signature else { return Err(AwsChunkedStreamError::MalformedTrailer) };
+
+        let mut canonical_trailers = Vec::new();
+        for (name, value) in &headers {
+            canonical_trailers.extend_from_slice(name.as_str().as_bytes());
+            canonical_trailers.push(b':');
+            canonical_trailers.extend_from_slice(value.as_bytes());
+            canonical_trailers.push(b'\n');
+        }
+
+        let string_to_sign =
+            sig_v4::create_trailer_string_to_sign(&ctx.amz_date, &ctx.region, &ctx.prev_signature, &canonical_trailers);
+        let trailer_signature = sig_v4::calculate_signature(&string_to_sign, &ctx.secret_key, &ctx.amz_date, &ctx.region);
+        if trailer_signature.as_bytes() != signature.as_bytes() {
+            return Err(AwsChunkedStreamError::SignatureMismatch);
+        }
+    }
+
+    Ok(headers)
+}
+
 impl AwsChunkedStream {
     /// Constructs a `ChunkedStream`
     pub fn new<S>(
@@ -111,6 +249,21 @@ impl AwsChunkedStream {
         secret_key: SecretKey,
         decoded_content_length: usize,
     ) -> Self
+    where
+        S: Stream<Item = Result<Bytes, StdError>> + Send + Sync + 'static,
+    {
+        let sig_ctx = SignatureCtx::new(seed_signature, amz_date, region, secret_key);
+        Self::with_trailer(body, Some(sig_ctx), None, decoded_content_length)
+    }
+
+    /// Constructs a `ChunkedStream` whose chunks are signed if `sig_ctx` is given,
+    /// and whose last chunk is followed by trailing headers if `trailer_ctx` is given.
+    pub(crate) fn with_trailer<S>(
+        body: S,
+        mut sig_ctx: Option<SignatureCtx>,
+        mut trailer_ctx: Option<TrailerCtx>,
+        decoded_content_length: usize,
+    ) -> Self
     where
         S: Stream<Item = Result<Bytes, StdError>> + Send + Sync + 'static,
     {
@@ -120,12 +273,6 @@ impl AwsChunkedStream {
                 pin_mut!(body);
                 let mut prev_bytes = Bytes::new();
                 let mut buf: Vec<u8> = Vec::new();
-                let mut ctx = SignatureCtx {
-                    amz_date,
-                    region,
-                    secret_key,
-                    prev_signature: seed_signature,
-                };
 
                 loop {
                     let meta = {
@@ -134,14 +281,24 @@ impl AwsChunkedStream {
                             Some(Err(e)) => return Err(AwsChunkedStreamError::Underlying(e)),
                             Some(Ok(remaining_bytes)) => prev_bytes = remaining_bytes,
                         };
-                        if let Ok((_, meta)) = parse_chunk_meta(&buf) {
+                        let parsed = if sig_ctx.is_some() {
+                            parse_chunk_meta(&buf)
+                        } else {
+                            parse_unsigned_chunk_meta(&buf)
+                        };
+                        if let Ok((_, meta)) = parsed {
                             meta
                         } else {
                             return Err(AwsChunkedStreamError::FormatError);
                         }
                     };
 
-                    let data: Vec<Bytes> = {
+                    // the last chunk is followed by the trailing headers instead of CRLF
+                    let is_trailer_next = meta.size == 0 && trailer_ctx.is_some();
+
+                    let data: Vec<Bytes> = if is_trailer_next {
+                        Vec::new()
+                    } else {
                         match Self::read_data(body.as_mut(), prev_bytes, meta.size).await {
                             None => return Err(AwsChunkedStreamError::Incomplete),
                             Some(Err(e)) => return Err(e),
@@ -152,14 +309,31 @@ impl AwsChunkedStream {
                         }
                     };
 
-                    match check_signature(&ctx, meta.signature, &data) {
-                        None => return Err(AwsChunkedStreamError::SignatureMismatch),
-                        Some(signature) => ctx.prev_signature = signature,
+                    if let Some(ref mut ctx) = sig_ctx {
+                        let Some(expected_signature) = meta.signature else {
+                            return Err(AwsChunkedStreamError::FormatError);
+                        };
+                        match check_signature(ctx, expected_signature, &data) {
+                            None => return Err(AwsChunkedStreamError::SignatureMismatch),
+                            Some(signature) => ctx.prev_signature = signature,
+                        }
                     }
 
                     for bytes in data {
                         y.yield_ok(bytes).await;
                     }
+
+                    if is_trailer_next {
+                        let trailers = Self::read_trailers(body.as_mut(), prev_bytes, &mut buf).await?;
+                        let ctx = trailer_ctx.take().unwrap_or_else(|| unreachable!());
+                        let headers = check_trailers(sig_ctx.as_ref(), &ctx, trailers)?;
+                        ctx.headers.set(headers);
+                        return Ok(());
+                    }
+                }
+
+                if trailer_ctx.is_some() {
+                    return Err(AwsChunkedStreamError::Incomplete);
                 }
 
                 Ok(())
@@ -171,6 +345,36 @@ impl AwsChunkedStream {
         }
     }
 
+    /// read trailing header lines until an empty line
+    async fn read_trailers<S>(
+        mut body: Pin<&mut S>,
+        mut prev_bytes: Bytes,
+        buf: &mut Vec<u8>,
+    ) -> Result<Vec<(HeaderName, HeaderValue)>, AwsChunkedStreamError>
+    where
+        S: Stream<Item = Result<Bytes, StdError>> + Send + 'static,
+    {
+        let mut trailers = Vec::new();
+        loop {
+            match Self::read_meta_bytes(body.as_mut(), prev_bytes, buf).await {
+                None => return Err(AwsChunkedStreamError::Incomplete),
+                Some(Err(e)) => return Err(AwsChunkedStreamError::Underlying(e)),
+                Some(Ok(remaining_bytes)) => prev_bytes = remaining_bytes,
+            }
+
+            let Some(line) = buf.strip_suffix(b"\r\n") else {
+                return Err(AwsChunkedStreamError::MalformedTrailer);
+            };
+            if line.is_empty() {
+                return Ok(trailers);
+            }
+            let Some(trailer) = parse_trailer_line(line) else {
+                return Err(AwsChunkedStreamError::MalformedTrailer);
+            };
+            trailers.push(trailer);
+        }
+    }
+
     /// read meta bytes and return remaining bytes
     async fn read_meta_bytes<S>(mut body: Pin<&mut S>, prev_bytes: Bytes, buf: &mut Vec<u8>) -> Option<Result<Bytes, StdError>>
     where
@@ -286,8 +490,29 @@ impl AwsChunkedStream {
         self.remaining_length
     }
 
+    /// The errors of the byte stream are [`S3Error`]s, so that handlers can return them to the client.
     pub fn into_byte_stream(self) -> DynByteStream {
-        crate::stream::into_dyn(self)
+        crate::stream::into_dyn(S3ErrorStream(self))
+    }
+}
+
+struct S3ErrorStream(AwsChunkedStream);
+
+impl Stream for S3ErrorStream {
+    type Item = Result<Bytes, S3Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx).map_err(S3Error::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ByteStream for S3ErrorStream {
+    fn remaining_length(&self) -> RemainingLength {
+        self.0.remaining_length()
     }
 }
 
@@ -367,4 +592,133 @@ mod tests {
             assert!(chunked_stream.next().await.is_none());
         }
     }
+
+    #[tokio::test]
+    async fn chunk_signature_mismatch() {
+        let chunk1_meta = b"400;chunk-signature=0055627c9e194cb4542bae2aa5492e3c1575bbb81b612b7d234b86a503ef5497\r\n";
+        let chunk1_data = vec![b'a'; 1024];
+        let chunk1 = join(&[chunk1_meta, &chunk1_data, b"\r\n"]);
+
+        let seed_signature = "4f232c4386841ef735655705268965c44a0e4690baa4adea153f7db9fa80a0a9";
+        let date = AmzDate::parse("20130524T000000Z").unwrap();
+        let secret_access_key = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+
+        let stream = futures::stream::iter(vec![Ok(chunk1)]);
+        let chunked_stream = AwsChunkedStream::new(
+            stream,
+            seed_signature.into(),
+            date,
+            "us-east-1".into(),
+            secret_access_key.into(),
+            chunk1_data.len(),
+        );
+
+        let mut byte_stream = chunked_stream.into_byte_stream();
+        let err = byte_stream.next().await.unwrap().unwrap_err();
+        let err = err.downcast::<S3Error>().unwrap();
+        assert_eq!(*err.code(), S3ErrorCode::SignatureDoesNotMatch);
+    }
+
+    #[tokio::test]
+    async fn example_put_object_chunked_stream_with_trailer() {
+        let chunk1_meta = b"10000;chunk-signature=b474d8862b1487a5145d686f57f013e54db672cee1c953b3010fb58501ef5aa2\r\n";
+        let chunk2_meta = b"400;chunk-signature=1c1344b170168f8e65b41376b44b20fe354e373826ccbbe2c1d40a8cae51e5c7\r\n";
+        let chunk3_meta = b"0;chunk-signature=2ca2aba2005185cf7159c6277faf83795951dd77a3a99e6e65d5c9f85863f992\r\n";
+        let trailer = b"x-amz-checksum-crc32c:sOO8/Q==\r\n";
+        let trailer_signature =
+            b"x-amz-trailer-signature:d81f82fc3505edab99d459891051a732e8730629a2e4a59689829ca17fe2e435\r\n\r\n";
+
+        let chunk1_data = vec![b'a'; 0x10000]; // 65536
+        let chunk2_data = vec![b'a'; 1024];
+        let decoded_content_length = chunk1_data.len() + chunk2_data.len();
+
+        let chunk1 = join(&[chunk1_meta, &chunk1_data, b"\r\n"]);
+        let chunk2 = join(&[chunk2_meta, &chunk2_data, b"\r\n"]);
+        let chunk3 = join(&[chunk3_meta, trailer, trailer_signature]);
+
+        let chunk_results: Vec<Result<Bytes, _>> = vec![Ok(chunk1), Ok(chunk2), Ok(chunk3)];
+
+        let seed_signature = "106e2a8a18243abcf37539882f36619c00e2dfc72633413f02d3b74544bfeb8e";
+        let date = AmzDate::parse("20130524T000000Z").unwrap();
+        let secret_access_key = "wJalrXUtnFEMI/K7MDENG/bPxRfiCYEXAMPLEKEY";
+
+        let sig_ctx = SignatureCtx::new(seed_signature.into(), date, "us-east-1".into(), secret_access_key.into());
+        let trailing_headers = TrailingHeaders::default();
+        let declared = vec![HeaderName::from_static("x-amz-checksum-crc32c")];
+        let trailer_ctx = TrailerCtx::new(declared, trailing_headers.clone());
+
+        let stream = futures::stream::iter(chunk_results);
+        let mut chunked_stream = AwsChunkedStream::with_trailer(stream, Some(sig_ctx), Some(trailer_ctx), decoded_content_length);
+
+        let ans1 = chunked_stream.next().await.unwrap();
+        assert_eq!(ans1.unwrap(), chunk1_data.as_slice());
+
+        let ans2 = chunked_stream.next().await.unwrap();
+        assert_eq!(ans2.unwrap(), chunk2_data.as_slice());
+
+        assert!(chunked_stream.next().await.is_none());
+
+        let headers = trailing_headers.take().unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers.get("x-amz-checksum-crc32c").unwrap(), "sOO8/Q==");
+    }
+
+    #[tokio::test]
+    async fn unsigned_chunked_stream_with_trailer() {
+        let body = Bytes::from_static(b"B\r\nHello world\r\n0\r\nx-amz-checksum-crc32:i9aeUg==\r\n\r\n");
+
+        let trailing_headers = TrailingHeaders::default();
+        let declared = vec![HeaderName::from_static("x-amz-checksum-crc32")];
+        let trailer_ctx = TrailerCtx::new(declared, trailing_headers.clone());
+
+        // split the body to exercise the buffering of trailing headers
+        let (head, tail) = (body.slice(..20), body.slice(20..));
+        let stream = futures::stream::iter(vec![Ok(head), Ok(tail)]);
+        let mut chunked_stream = AwsChunkedStream::with_trailer(stream, None, Some(trailer_ctx), 11);
+
+        let ans = chunked_stream.next().await.unwrap();
+        assert_eq!(ans.unwrap(), b"Hello world".as_slice());
+        assert!(chunked_stream.next().await.is_none());
+
+        let headers = trailing_headers.take().unwrap();
+        assert_eq!(headers.get("x-amz-checksum-crc32").unwrap(), "i9aeUg==");
+    }
+
+    #[tokio::test]
+    async fn malformed_trailer() {
+        let bodies: [&[u8]; 4] = [
+            // missing trailer
+            b"B\r\nHello world\r\n0\r\n\r\n",
+            // undeclared trailer
+            b"B\r\nHello world\r\n0\r\nx-amz-checksum-sha1:aGVsbG8=\r\n\r\n",
+            // not a header
+            b"B\r\nHello world\r\n0\r\nx-amz-checksum-crc32\r\n\r\n",
+            // missing terminating line
+            b"B\r\nHello world\r\n0\r\nx-amz-checksum-crc32:i9aeUg==\r\n",
+        ];
+        let codes = [
+            S3ErrorCode::Custom("MalformedTrailerError".into()),
+            S3ErrorCode::Custom("MalformedTrailerError".into()),
+            S3ErrorCode::Custom("MalformedTrailerError".into()),
+            S3ErrorCode::IncompleteBody,
+        ];
+
+        for (body, code) in bodies.into_iter().zip(codes) {
+            let trailing_headers = TrailingHeaders::default();
+            let declared = vec![HeaderName::from_static("x-amz-checksum-crc32")];
+            let trailer_ctx = TrailerCtx::new(declared, trailing_headers.clone());
+
+            let stream = futures::stream::iter(vec![Ok(Bytes::from_static(body))]);
+            let chunked_stream = AwsChunkedStream::with_trailer(stream, None, Some(trailer_ctx), 11);
+            let mut byte_stream = chunked_stream.into_byte_stream();
+
+            let ans = byte_stream.next().await.unwrap();
+            assert_eq!(ans.unwrap(), b"Hello world".as_slice());
+
+            let err = byte_stream.next().await.unwrap().unwrap_err();
+            let err = err.downcast::<S3Error>().unwrap();
+            assert_eq!(*err.code(), code);
+            assert!(trailing_headers.take().is_none());
+        }
+    }
 }
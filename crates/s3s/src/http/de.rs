@@ -1,3 +1,4 @@
+use super::metadata::{decode_metadata_value, has_control_chars};
 use super::{Multipart, Request};
 
 use crate::dto::{List, Metadata, StreamingBlob, Timestamp, TimestampFormat};
@@ -7,6 +8,7 @@ use crate::path::S3Path;
 use crate::xml;
 
 use std::fmt;
+use std::ops::Not;
 use std::str::FromStr;
 
 use rust_utils::string::StringExt;
@@ -73,8 +75,13 @@ where
 {
     let mut list = List::new();
     for val in req.headers.get_all(name) {
-        let ans = T::try_from_header_value(val).map_err(|err| invalid_header(err, name, val))?;
-        list.push(ans);
+        // a list may be sent as repeated headers or as comma-separated values
+        let s = val.to_str().map_err(|err| invalid_header(err, name, val))?;
+        for item in s.split(',').map(str::trim).filter(|x| x.is_empty().not()) {
+            let item = HeaderValue::from_str(item).map_err(|err| invalid_header(err, name, val))?;
+            let ans = T::try_from_header_value(&item).map_err(|err| invalid_header(err, name, val))?;
+            list.push(ans);
+        }
     }
     if required && list.is_empty() {
         return Err(missing_header(name));
@@ -135,16 +142,16 @@ pub fn parse_opt_query_timestamp(req: &Request, name: &str, fmt: TimestampFormat
 
 #[track_caller]
 pub fn unwrap_bucket(req: &mut Request) -> String {
-    match req.s3ext.s3_path.take() {
-        Some(S3Path::Bucket { bucket }) => bucket.into(),
+    match &req.s3ext.s3_path {
+        Some(S3Path::Bucket { bucket }) => bucket.as_ref().into(),
         _ => panic!("s3 path not found, expected bucket"),
     }
 }
 
 #[track_caller]
 pub fn unwrap_object(req: &mut Request) -> (String, String) {
-    match req.s3ext.s3_path.take() {
-        Some(S3Path::Object { bucket, key }) => (bucket.into(), key.into()),
+    match &req.s3ext.s3_path {
+        Some(S3Path::Object { bucket, key }) => (bucket.as_ref().into(), key.as_ref().into()),
         _ => panic!("s3 path not found, expected object"),
     }
 }
@@ -220,8 +227,13 @@ pub fn parse_opt_metadata(req: &Request) -> S3Result<Option<Metadata>> {
         let val = iter.next().unwrap();
         let None = iter.next() else { return Err(duplicate_header(name)) };
 
-        let val = val.to_str().map_err(|err| invalid_header(err, name, val))?;
-        metadata.insert(key.into(), val.into());
+        // some clients send UTF-8 values without encoding them
+        let val = std::str::from_utf8(val.as_bytes()).map_err(|err| invalid_header(err, name, val))?;
+        let val = decode_metadata_value(val);
+        if has_control_chars(&val) {
+            return Err(invalid_request!("invalid header: {}: control characters are not allowed", name.as_str()));
+        }
+        metadata.insert(key.into(), val.into_owned());
     }
     if metadata.is_empty() {
         return Ok(None);
@@ -0,0 +1,122 @@
+//! Encoding of user metadata values in headers
+//!
+//! Header values are ASCII, so a value with other characters is sent as a MIME encoded-word of RFC 2047
+//! (`=?UTF-8?B?<base64>?=`), like S3 does. Encoded words in requests are decoded,
+//! so that S3 implementations only see raw values.
+
+use std::borrow::Cow;
+use std::ops::Not;
+
+/// Whether a character can be sent in a header value as is
+fn is_plain(c: char) -> bool {
+    c == '\t' || (' '..='~').contains(&c)
+}
+
+/// Whether a decoded value contains characters which are not allowed in metadata
+pub(crate) fn has_control_chars(value: &str) -> bool {
+    value.chars().any(|c| c.is_control() && c != '\t')
+}
+
+pub(crate) fn encode_metadata_value(value: &str) -> Cow<'_, str> {
+    if value.chars().all(is_plain) {
+        return Cow::Borrowed(value);
+    }
+    let encoded = base64_simd::STANDARD.encode_to_string(value);
+    Cow::Owned(format!("=?UTF-8?B?{encoded}?="))
+}
+
+/// Decodes a value which consists of encoded words separated by whitespace.
+/// Other values are returned as is.
+pub(crate) fn decode_metadata_value(value: &str) -> Cow<'_, str> {
+    let mut decoded = String::new();
+    let mut words = value.split_ascii_whitespace().peekable();
+    if words.peek().is_none() {
+        return Cow::Borrowed(value);
+    }
+    for word in words {
+        let Some(text) = decode_encoded_word(word) else { return Cow::Borrowed(value) };
+        decoded.push_str(&text);
+    }
+    Cow::Owned(decoded)
+}
+
+/// Decodes `=?UTF-8?B?<base64>?=` or `=?UTF-8?Q?<quoted-printable>?=`
+fn decode_encoded_word(word: &str) -> Option<String> {
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let charset = parts.next()?;
+    let encoding = parts.next()?;
+    let text = parts.next()?;
+
+    if charset.eq_ignore_ascii_case("UTF-8").not() {
+        return None;
+    }
+    let bytes = if encoding.eq_ignore_ascii_case("B") {
+        base64_simd::STANDARD.decode_to_vec(text).ok()?
+    } else if encoding.eq_ignore_ascii_case("Q") {
+        decode_q(text)?
+    } else {
+        return None;
+    };
+    String::from_utf8(bytes).ok()
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let hex_digit = |b: u8| char::from(b).to_digit(16).and_then(|d| u8::try_from(d).ok());
+
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hi = hex_digit(iter.next()?)?;
+                let lo = hex_digit(iter.next()?)?;
+                bytes.push(hi << 4 | lo);
+            }
+            _ => bytes.push(b),
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        assert_eq!(encode_metadata_value("data team"), "data team");
+        assert_eq!(encode_metadata_value("café"), "=?UTF-8?B?Y2Fmw6k=?=");
+        assert_eq!(encode_metadata_value("東京"), "=?UTF-8?B?5p2x5Lqs?=");
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(decode_metadata_value("data team"), "data team");
+        assert_eq!(decode_metadata_value(""), "");
+        assert_eq!(decode_metadata_value("=?UTF-8?B?Y2Fmw6k=?="), "café");
+        assert_eq!(decode_metadata_value("=?utf-8?q?caf=C3=A9_au_lait?="), "café au lait");
+        assert_eq!(decode_metadata_value("=?UTF-8?B?5p2x?= =?UTF-8?B?5Lqs?="), "東京");
+
+        // not encoded words
+        assert_eq!(decode_metadata_value("=?ISO-8859-1?Q?caf=E9?="), "=?ISO-8859-1?Q?caf=E9?=");
+        assert_eq!(decode_metadata_value("a =?UTF-8?B?Y2Fmw6k=?="), "a =?UTF-8?B?Y2Fmw6k=?=");
+        assert_eq!(decode_metadata_value("=?UTF-8?B?!!!?="), "=?UTF-8?B?!!!?=");
+    }
+
+    #[test]
+    fn round_trip() {
+        for value in ["café", "naïve résumé", "東京", "emoji 🦀", "plain"] {
+            assert_eq!(decode_metadata_value(&encode_metadata_value(value)), value);
+        }
+    }
+
+    #[test]
+    fn control_chars() {
+        assert!(has_control_chars("a\u{0}b"));
+        assert!(has_control_chars("a\nb"));
+        assert!(has_control_chars("a\tb").not());
+        assert!(has_control_chars("café").not());
+    }
+}
@@ -4,6 +4,8 @@ pub use self::ser::*;
 mod de;
 pub use self::de::*;
 
+mod metadata;
+
 mod ordered_qs;
 pub use self::ordered_qs::*;
 
@@ -13,6 +15,9 @@ pub use self::ordered_headers::*;
 mod aws_chunked_stream;
 pub use self::aws_chunked_stream::*;
 
+mod trailing_headers;
+pub use self::trailing_headers::*;
+
 mod multipart;
 pub use self::multipart::*;
 
@@ -58,6 +58,19 @@ impl Multipart {
         }
         Some(pair.1.as_str())
     }
+
+    /// Finds field value to modify
+    pub fn find_field_value_mut(&mut self, name: &str) -> Option<&mut String> {
+        let upper_bound = self.fields.partition_point(|x| x.0.as_str() <= name);
+        let pair = self.fields.get_mut(upper_bound.checked_sub(1)?)?;
+        (pair.0 == name).then_some(&mut pair.1)
+    }
+
+    /// Inserts a field, which keeps the fields sorted
+    pub fn insert_field(&mut self, name: String, value: String) {
+        let idx = self.fields.partition_point(|x| x.0 <= name);
+        self.fields.insert(idx, (name, value));
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
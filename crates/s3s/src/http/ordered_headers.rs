@@ -1,9 +1,10 @@
 //! Ordered headers
 
-use hyper::header::ToStrError;
 use hyper::http::HeaderValue;
 use hyper::HeaderMap;
 
+use std::str::Utf8Error;
+
 use crate::utils::stable_sort_by_first;
 
 /// Immutable http header container
@@ -34,12 +35,13 @@ impl<'a> OrderedHeaders<'a> {
     /// Constructs [`OrderedHeaders`] from a header map
     ///
     /// # Errors
-    /// Returns [`ToStrError`] if header value cannot be converted to string slice
-    pub fn from_headers(map: &'a HeaderMap<HeaderValue>) -> Result<Self, ToStrError> {
+    /// Returns [`Utf8Error`] if header value is not UTF-8.
+    /// Unlike [`HeaderValue::to_str`], non-ASCII values are accepted, which some clients send in user metadata.
+    pub fn from_headers(map: &'a HeaderMap<HeaderValue>) -> Result<Self, Utf8Error> {
         let mut headers: Vec<(&'a str, &'a str)> = Vec::with_capacity(map.len());
 
         for (name, value) in map {
-            headers.push((name.as_str(), value.to_str()?));
+            headers.push((name.as_str(), std::str::from_utf8(value.as_bytes())?));
         }
         stable_sort_by_first(&mut headers);
 
@@ -1,10 +1,13 @@
 use super::Body;
 use super::Multipart;
 use super::OrderedQs;
+use super::TrailingHeaders;
 
 use crate::auth::Credentials;
+use crate::dto::StreamingBlob;
+use crate::error::S3ErrorCode;
+use crate::ops::PostObjectSuccess;
 use crate::path::S3Path;
-use crate::stream::VecByteStream;
 
 use hyper::http::Extensions;
 use hyper::http::HeaderValue;
@@ -27,9 +30,15 @@ pub(crate) struct S3Extensions {
     pub qs: Option<OrderedQs>,
 
     pub multipart: Option<Multipart>,
-    pub vec_stream: Option<VecByteStream>,
+    pub file_stream: Option<StreamingBlob>,
+    pub post_object_success: Option<PostObjectSuccess>,
 
     pub credentials: Option<Credentials>,
+    pub trailing_headers: Option<TrailingHeaders>,
+
+    pub request_id: Option<String>,
+    pub op_name: Option<&'static str>,
+    pub error_code: Option<S3ErrorCode>,
 }
 
 impl From<hyper::Request<Body>> for Request {
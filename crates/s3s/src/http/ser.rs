@@ -1,3 +1,4 @@
+use super::metadata::encode_metadata_value;
 use super::Body;
 use super::Response;
 
@@ -121,7 +122,7 @@ pub fn add_opt_metadata(res: &mut Response, metadata: Option<Metadata>) -> S3Res
         for (key, val) in map {
             write!(&mut buf, "x-amz-meta-{key}").unwrap();
             let name = HeaderName::from_bytes(buf.as_bytes()).map_err(S3Error::internal_error)?;
-            let value = HeaderValue::try_from(val).map_err(S3Error::internal_error)?;
+            let value = HeaderValue::try_from(encode_metadata_value(&val).as_ref()).map_err(S3Error::internal_error)?;
             res.headers.insert(name, value);
             buf.clear();
         }
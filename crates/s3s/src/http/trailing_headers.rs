@@ -0,0 +1,25 @@
+//! Trailing headers of a request body
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use hyper::HeaderMap;
+
+/// The trailing headers of a request body, such as the trailing checksum of an `aws-chunked` payload
+///
+/// The headers are available after the body has been read to the end.
+#[derive(Debug, Clone, Default)]
+pub struct TrailingHeaders(Arc<Mutex<Option<HeaderMap>>>);
+
+impl TrailingHeaders {
+    pub(crate) fn set(&self, headers: HeaderMap) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(headers);
+    }
+
+    /// Takes the trailing headers.
+    ///
+    /// Returns `None` if the body has not been read to the end, or if the headers have been taken.
+    #[must_use]
+    pub fn take(&self) -> Option<HeaderMap> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+}
@@ -37,12 +37,15 @@ mod xml;
 
 pub mod auth;
 pub mod dto;
+pub mod event_stream;
+pub mod metrics;
 pub mod path;
 pub mod service;
 pub mod stream;
 
 pub use self::error::*;
 pub use self::http::Body;
+pub use self::http::TrailingHeaders;
 pub use self::request::S3Request;
 pub use self::response::S3Response;
 pub use self::s3_trait::S3;
@@ -0,0 +1,66 @@
+//! S3 Metrics
+
+use crate::error::S3ErrorCode;
+
+use std::time::Duration;
+
+use hyper::Method;
+use hyper::StatusCode;
+
+/// Metrics of a completed request
+pub struct RequestMetrics<'a> {
+    pub(crate) method: &'a Method,
+    pub(crate) op_name: Option<&'static str>,
+    pub(crate) status: StatusCode,
+    pub(crate) error_code: Option<&'a S3ErrorCode>,
+    pub(crate) duration: Duration,
+}
+
+impl RequestMetrics<'_> {
+    #[must_use]
+    pub fn method(&self) -> &Method {
+        self.method
+    }
+
+    /// Returns the name of the operation, such as `PutObject` or `ListObjects`.
+    ///
+    /// `None` means the request is rejected before its operation is resolved.
+    #[must_use]
+    pub fn op_name(&self) -> Option<&'static str> {
+        self.op_name
+    }
+
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the error code if the request failed.
+    #[must_use]
+    pub fn error_code(&self) -> Option<&S3ErrorCode> {
+        self.error_code
+    }
+
+    /// Returns the time between the start and the completion of the request.
+    ///
+    /// The time of streaming the response body is not included.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// S3 Metrics Hook
+///
+/// All methods do nothing by default.
+pub trait S3Metrics: Send + Sync + 'static {
+    /// Called when a request starts.
+    fn on_request_start(&self, method: &Method) {
+        let _ = method;
+    }
+
+    /// Called when a request completes.
+    fn on_request_end(&self, metrics: &RequestMetrics<'_>) {
+        let _ = metrics;
+    }
+}
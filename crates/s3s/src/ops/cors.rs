@@ -0,0 +1,215 @@
+//! CORS handling of the request router
+//!
+//! The rules of a bucket are fetched with [`S3::get_bucket_cors`],
+//! so every backend which stores a CORS configuration supports CORS.
+//! The lookup is issued as an unauthenticated backend call, because preflight requests are not signed,
+//! and a lookup which fails for any reason is treated like a bucket without rules.
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/userguide/cors.html>
+
+use super::{extract_host, extract_s3_path};
+
+use crate::dto::{CORSRule, GetBucketCorsInput};
+use crate::error::*;
+use crate::http::{Request, Response};
+use crate::request::S3Request;
+use crate::s3_trait::S3;
+use crate::service::CorsPreflightDefault;
+
+use std::ops::Not;
+use std::sync::Arc;
+
+use hyper::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+    VARY,
+};
+use hyper::http::{HeaderName, HeaderValue};
+use hyper::{HeaderMap, StatusCode};
+use tracing::debug;
+
+/// A cross-origin request to a bucket
+pub struct CorsRequest {
+    bucket: String,
+    origin: String,
+    method: String,
+}
+
+fn header_str(headers: &HeaderMap, name: HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn request_bucket(req: &Request, base_domain: Option<&str>) -> S3Result<Option<String>> {
+    let decoded_uri_path = urlencoding::decode(req.uri.path()).map_err(|_| S3ErrorCode::InvalidURI)?;
+    let host = extract_host(req)?;
+    let s3_path = extract_s3_path(host.as_deref(), &decoded_uri_path, base_domain)?;
+    let bucket = s3_path.as_bucket().or_else(|| s3_path.as_object().map(|(bucket, _)| bucket));
+    Ok(bucket.map(ToOwned::to_owned))
+}
+
+/// Matches a pattern with at most one `*` wildcard
+fn wildcard_match(pattern: &str, s: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == s,
+        Some((prefix, suffix)) => s.len() >= prefix.len() + suffix.len() && s.starts_with(prefix) && s.ends_with(suffix),
+    }
+}
+
+/// Finds the first rule which allows the origin, the method and all request headers
+fn find_rule<'a>(rules: &'a [CORSRule], origin: &str, method: &str, request_headers: &[String]) -> Option<&'a CORSRule> {
+    rules.iter().find(|rule| {
+        let origin_allowed = rule.allowed_origins.iter().any(|o| wildcard_match(o, origin));
+        let method_allowed = rule.allowed_methods.iter().any(|m| m == method);
+        let allowed_headers = rule.allowed_headers.as_deref().unwrap_or_default();
+        let headers_allowed = request_headers
+            .iter()
+            .all(|h| allowed_headers.iter().any(|a| wildcard_match(&a.to_ascii_lowercase(), h)));
+        origin_allowed && method_allowed && headers_allowed
+    })
+}
+
+fn forbidden(message: &'static str) -> S3Error {
+    S3Error::with_message(S3ErrorCode::AccessForbidden, message)
+}
+
+fn set_header(headers: &mut HeaderMap, name: HeaderName, value: &str) {
+    if let Ok(value) = HeaderValue::try_from(value) {
+        headers.insert(name, value);
+    }
+}
+
+/// Adds the `Access-Control-Allow-*` headers of a matched rule
+fn set_allow_headers(headers: &mut HeaderMap, rule: &CORSRule, origin: &str) {
+    if rule.allowed_origins.iter().any(|o| o == "*") {
+        set_header(headers, ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    } else {
+        set_header(headers, ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        set_header(headers, ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+    }
+    set_header(headers, ACCESS_CONTROL_ALLOW_METHODS, &rule.allowed_methods.join(", "));
+    if let Some(ref expose_headers) = rule.expose_headers {
+        if expose_headers.is_empty().not() {
+            set_header(headers, ACCESS_CONTROL_EXPOSE_HEADERS, &expose_headers.join(", "));
+        }
+    }
+    if rule.max_age_seconds > 0 {
+        set_header(headers, ACCESS_CONTROL_MAX_AGE, &rule.max_age_seconds.to_string());
+    }
+    set_header(headers, VARY, "Origin, Access-Control-Request-Headers, Access-Control-Request-Method");
+}
+
+/// Returns `None` if the bucket has no CORS configuration,
+/// or if the backend fails to return it, such as when a bucket policy denies anonymous reads.
+async fn get_rules(s3: &Arc<dyn S3>, bucket: &str) -> Option<Vec<CORSRule>> {
+    let input = GetBucketCorsInput {
+        bucket: bucket.to_owned(),
+        expected_bucket_owner: None,
+    };
+    match s3.get_bucket_cors(S3Request::new(input)).await {
+        Ok(resp) => resp.output.cors_rules,
+        Err(err) => {
+            if *err.code() != S3ErrorCode::NoSuchCORSConfiguration {
+                debug!(?err, "failed to get CORS rules");
+            }
+            None
+        }
+    }
+}
+
+/// Extracts the origin, the method and the lowercase headers of a preflight request
+fn preflight_headers(req: &Request) -> S3Result<(&str, &str, Vec<String>)> {
+    let origin = header_str(&req.headers, ORIGIN);
+    let method = header_str(&req.headers, ACCESS_CONTROL_REQUEST_METHOD);
+    let (Some(origin), Some(method)) = (origin, method) else {
+        return Err(s3_error!(
+            InvalidRequest,
+            "Insufficient information. Origin and Access-Control-Request-Method request headers needed."
+        ));
+    };
+    let request_headers = header_str(&req.headers, ACCESS_CONTROL_REQUEST_HEADERS)
+        .unwrap_or_default()
+        .split(',')
+        .map(|h| h.trim().to_ascii_lowercase())
+        .filter(|h| h.is_empty().not())
+        .collect();
+    Ok((origin, method, request_headers))
+}
+
+/// Allows everything which a preflight request asks for
+fn allow_all(req: &Request) -> S3Result<Response> {
+    let (origin, method, request_headers) = preflight_headers(req)?;
+
+    let mut res = Response::with_status(StatusCode::OK);
+    set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+    set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_METHODS, method);
+    if request_headers.is_empty().not() {
+        set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_HEADERS, &request_headers.join(", "));
+    }
+    set_header(
+        &mut res.headers,
+        VARY,
+        "Origin, Access-Control-Request-Headers, Access-Control-Request-Method",
+    );
+    Ok(res)
+}
+
+/// Answers a preflight `OPTIONS` request from the stored rules,
+/// or by `default` if no rule allows it.
+///
+/// Preflight requests are not signed, so this happens before authentication.
+pub async fn preflight(
+    req: &Request,
+    s3: &Arc<dyn S3>,
+    base_domain: Option<&str>,
+    default: CorsPreflightDefault,
+) -> S3Result<Response> {
+    let not_allowed = |message: &'static str| match default {
+        CorsPreflightDefault::Deny => Err(forbidden(message)),
+        CorsPreflightDefault::AllowAll => allow_all(req),
+    };
+
+    let Some(bucket) = request_bucket(req, base_domain)? else {
+        return not_allowed("CORSResponse: Bucket not found");
+    };
+    let (origin, method, request_headers) = preflight_headers(req)?;
+
+    let Some(rules) = get_rules(s3, &bucket).await else {
+        return not_allowed("CORSResponse: CORS is not enabled for this bucket.");
+    };
+    let Some(rule) = find_rule(&rules, origin, method, &request_headers) else {
+        return not_allowed("CORSResponse: This CORS request is not allowed.");
+    };
+
+    debug!(?bucket, ?origin, ?method, ?rule, "preflight request matched");
+
+    let mut res = Response::with_status(StatusCode::OK);
+    set_allow_headers(&mut res.headers, rule, origin);
+    if request_headers.is_empty().not() {
+        set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_HEADERS, &request_headers.join(", "));
+    }
+    Ok(res)
+}
+
+impl CorsRequest {
+    /// Returns `None` if the request is not a cross-origin request to a bucket.
+    pub fn extract(req: &Request, base_domain: Option<&str>) -> Option<Self> {
+        let origin = header_str(&req.headers, ORIGIN)?.to_owned();
+        let bucket = request_bucket(req, base_domain).ok()??;
+        let method = req.method.as_str().to_owned();
+        Some(Self { bucket, origin, method })
+    }
+
+    /// Adds the CORS headers to the response of an actual request if a rule matches,
+    /// or if `default` allows all requests.
+    pub async fn apply(&self, s3: &Arc<dyn S3>, default: CorsPreflightDefault, res: &mut Response) {
+        let rules = get_rules(s3, &self.bucket).await.unwrap_or_default();
+        if let Some(rule) = find_rule(&rules, &self.origin, &self.method, &[]) {
+            set_allow_headers(&mut res.headers, rule, &self.origin);
+        } else if default == CorsPreflightDefault::AllowAll {
+            set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_ORIGIN, &self.origin);
+            set_header(&mut res.headers, ACCESS_CONTROL_ALLOW_CREDENTIALS, "true");
+            set_header(&mut res.headers, VARY, "Origin");
+        }
+    }
+}
@@ -359,12 +359,11 @@ impl super::Operation for AbortMultipartUpload {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.abort_multipart_upload(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.abort_multipart_upload(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -440,12 +439,11 @@ impl super::Operation for CompleteMultipartUpload {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.complete_multipart_upload(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.complete_multipart_upload(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -622,12 +620,11 @@ impl super::Operation for CopyObject {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.copy_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.copy_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -689,12 +686,11 @@ impl super::Operation for CreateBucket {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.create_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.create_bucket(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -830,12 +826,11 @@ impl super::Operation for CreateMultipartUpload {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.create_multipart_upload(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.create_multipart_upload(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -870,12 +865,11 @@ impl super::Operation for DeleteBucket {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -913,12 +907,11 @@ impl super::Operation for DeleteBucketAnalyticsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_analytics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -953,12 +946,11 @@ impl super::Operation for DeleteBucketCors {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_cors(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -993,12 +985,11 @@ impl super::Operation for DeleteBucketEncryption {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_encryption(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1030,12 +1021,11 @@ impl super::Operation for DeleteBucketIntelligentTieringConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_intelligent_tiering_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1073,12 +1063,11 @@ impl super::Operation for DeleteBucketInventoryConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_inventory_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1113,12 +1102,11 @@ impl super::Operation for DeleteBucketLifecycle {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_lifecycle(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_lifecycle(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1156,12 +1144,11 @@ impl super::Operation for DeleteBucketMetricsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_metrics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1196,12 +1183,11 @@ impl super::Operation for DeleteBucketOwnershipControls {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_ownership_controls(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1236,12 +1222,11 @@ impl super::Operation for DeleteBucketPolicy {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_policy(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1276,12 +1261,11 @@ impl super::Operation for DeleteBucketReplication {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_replication(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1316,12 +1300,11 @@ impl super::Operation for DeleteBucketTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1356,12 +1339,11 @@ impl super::Operation for DeleteBucketWebsite {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_bucket_website(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1379,6 +1361,8 @@ impl DeleteObject {
 
         let expected_bucket_owner: Option<AccountId> = http::parse_opt_header(req, &X_AMZ_EXPECTED_BUCKET_OWNER)?;
 
+        let if_match: Option<IfMatch> = http::parse_opt_header(req, &IF_MATCH)?;
+
         let mfa: Option<MFA> = http::parse_opt_header(req, &X_AMZ_MFA)?;
 
         let request_payer: Option<RequestPayer> = http::parse_opt_header(req, &X_AMZ_REQUEST_PAYER)?;
@@ -1389,6 +1373,7 @@ impl DeleteObject {
             bucket,
             bypass_governance_retention,
             expected_bucket_owner,
+            if_match,
             key,
             mfa,
             request_payer,
@@ -1414,12 +1399,11 @@ impl super::Operation for DeleteObject {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1460,12 +1444,11 @@ impl super::Operation for DeleteObjectTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_object_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1519,12 +1502,11 @@ impl super::Operation for DeleteObjects {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_objects(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_objects(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1559,12 +1541,11 @@ impl super::Operation for DeletePublicAccessBlock {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.delete_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.delete_public_access_block(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1605,12 +1586,11 @@ impl super::Operation for GetBucketAccelerateConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_accelerate_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_accelerate_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1647,12 +1627,11 @@ impl super::Operation for GetBucketAcl {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_acl(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1694,12 +1673,11 @@ impl super::Operation for GetBucketAnalyticsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_analytics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1736,12 +1714,11 @@ impl super::Operation for GetBucketCors {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_cors(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1780,12 +1757,11 @@ impl super::Operation for GetBucketEncryption {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_encryption(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1821,12 +1797,11 @@ impl super::Operation for GetBucketIntelligentTieringConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_intelligent_tiering_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1868,12 +1843,11 @@ impl super::Operation for GetBucketInventoryConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_inventory_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1910,12 +1884,11 @@ impl super::Operation for GetBucketLifecycleConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_lifecycle_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_lifecycle_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1952,12 +1925,11 @@ impl super::Operation for GetBucketLocation {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_location(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_location(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -1994,12 +1966,11 @@ impl super::Operation for GetBucketLogging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_logging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_logging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2041,12 +2012,11 @@ impl super::Operation for GetBucketMetricsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_metrics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2083,12 +2053,11 @@ impl super::Operation for GetBucketNotificationConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_notification_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_notification_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2127,12 +2096,11 @@ impl super::Operation for GetBucketOwnershipControls {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_ownership_controls(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2171,12 +2139,11 @@ impl super::Operation for GetBucketPolicy {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_policy(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2215,12 +2182,11 @@ impl super::Operation for GetBucketPolicyStatus {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_policy_status(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_policy_status(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2259,12 +2225,11 @@ impl super::Operation for GetBucketReplication {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_replication(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2301,12 +2266,11 @@ impl super::Operation for GetBucketRequestPayment {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_request_payment(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_request_payment(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2343,12 +2307,11 @@ impl super::Operation for GetBucketTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2385,12 +2348,11 @@ impl super::Operation for GetBucketVersioning {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_versioning(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_versioning(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2427,12 +2389,11 @@ impl super::Operation for GetBucketWebsite {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_bucket_website(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2573,12 +2534,11 @@ impl super::Operation for GetObject {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
         let overrided_headers = super::get_object::extract_overrided_response_headers(&s3_req)?;
-        let result = s3.get_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(overrided_headers);
         super::get_object::merge_custom_headers(&mut resp, s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
@@ -2624,12 +2584,11 @@ impl super::Operation for GetObjectAcl {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_acl(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2697,12 +2656,11 @@ impl super::Operation for GetObjectAttributes {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_attributes(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_attributes(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2748,12 +2706,11 @@ impl super::Operation for GetObjectLegalHold {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_legal_hold(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_legal_hold(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2792,12 +2749,11 @@ impl super::Operation for GetObjectLockConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_lock_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_lock_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2843,12 +2799,11 @@ impl super::Operation for GetObjectRetention {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_retention(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_retention(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2893,12 +2848,11 @@ impl super::Operation for GetObjectTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2942,12 +2896,11 @@ impl super::Operation for GetObjectTorrent {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_object_torrent(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_object_torrent(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -2986,12 +2939,11 @@ impl super::Operation for GetPublicAccessBlock {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.get_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.get_public_access_block(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3026,12 +2978,11 @@ impl super::Operation for HeadBucket {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.head_bucket(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.head_bucket(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3147,12 +3098,11 @@ impl super::Operation for HeadObject {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.head_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.head_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3192,12 +3142,11 @@ impl super::Operation for ListBucketAnalyticsConfigurations {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_bucket_analytics_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_bucket_analytics_configurations(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3234,12 +3183,11 @@ impl super::Operation for ListBucketIntelligentTieringConfigurations {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_bucket_intelligent_tiering_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_bucket_intelligent_tiering_configurations(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3279,12 +3227,11 @@ impl super::Operation for ListBucketInventoryConfigurations {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_bucket_inventory_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_bucket_inventory_configurations(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3324,12 +3271,11 @@ impl super::Operation for ListBucketMetricsConfigurations {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_bucket_metrics_configurations(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_bucket_metrics_configurations(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3359,12 +3305,11 @@ impl super::Operation for ListBuckets {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_buckets(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_buckets(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3423,12 +3368,11 @@ impl super::Operation for ListMultipartUploads {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_multipart_uploads(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_multipart_uploads(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3491,12 +3435,11 @@ impl super::Operation for ListObjectVersions {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_object_versions(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_object_versions(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3556,12 +3499,11 @@ impl super::Operation for ListObjects {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_objects(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_objects(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3627,12 +3569,11 @@ impl super::Operation for ListObjectsV2 {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_objects_v2(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_objects_v2(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3696,12 +3637,11 @@ impl super::Operation for ListParts {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.list_parts(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.list_parts(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3742,12 +3682,11 @@ impl super::Operation for PutBucketAccelerateConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_accelerate_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_accelerate_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3809,12 +3748,11 @@ impl super::Operation for PutBucketAcl {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_acl(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3855,12 +3793,11 @@ impl super::Operation for PutBucketAnalyticsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_analytics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_analytics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3904,12 +3841,11 @@ impl super::Operation for PutBucketCors {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_cors(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_cors(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3953,12 +3889,11 @@ impl super::Operation for PutBucketEncryption {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_encryption(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_encryption(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -3996,12 +3931,11 @@ impl super::Operation for PutBucketIntelligentTieringConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_intelligent_tiering_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_intelligent_tiering_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4042,12 +3976,11 @@ impl super::Operation for PutBucketInventoryConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_inventory_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_inventory_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4088,12 +4021,11 @@ impl super::Operation for PutBucketLifecycleConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_lifecycle_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_lifecycle_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4137,12 +4069,11 @@ impl super::Operation for PutBucketLogging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_logging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_logging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4183,12 +4114,11 @@ impl super::Operation for PutBucketMetricsConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_metrics_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_metrics_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4230,12 +4160,11 @@ impl super::Operation for PutBucketNotificationConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_notification_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_notification_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4276,12 +4205,11 @@ impl super::Operation for PutBucketOwnershipControls {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_ownership_controls(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_ownership_controls(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4329,12 +4257,11 @@ impl super::Operation for PutBucketPolicy {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_policy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_policy(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4381,12 +4308,11 @@ impl super::Operation for PutBucketReplication {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_replication(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_replication(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4430,12 +4356,11 @@ impl super::Operation for PutBucketRequestPayment {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_request_payment(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_request_payment(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4479,12 +4404,11 @@ impl super::Operation for PutBucketTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4531,12 +4455,11 @@ impl super::Operation for PutBucketVersioning {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_versioning(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_versioning(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4580,12 +4503,11 @@ impl super::Operation for PutBucketWebsite {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_bucket_website(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_bucket_website(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4645,6 +4567,8 @@ impl PutObject {
 
         let grant_write_acp: Option<GrantWriteACP> = http::parse_opt_header(req, &X_AMZ_GRANT_WRITE_ACP)?;
 
+        let if_none_match: Option<IfNoneMatch> = http::parse_opt_header(req, &IF_NONE_MATCH)?;
+
         let metadata: Option<Metadata> = http::parse_opt_metadata(req)?;
 
         let object_lock_legal_hold_status: Option<ObjectLockLegalHoldStatus> =
@@ -4702,6 +4626,7 @@ impl PutObject {
             grant_read,
             grant_read_acp,
             grant_write_acp,
+            if_none_match,
             key,
             metadata,
             object_lock_legal_hold_status,
@@ -4724,13 +4649,10 @@ impl PutObject {
         let bucket = http::unwrap_bucket(req);
         let key = http::parse_field_value(&m, "key")?.ok_or_else(|| invalid_request!("missing key"))?;
 
-        let vec_stream = req.s3ext.vec_stream.take().expect("missing vec stream");
+        let body: Option<StreamingBlob> = req.s3ext.file_stream.take();
 
-        let content_length = i64::try_from(vec_stream.exact_remaining_length())
-            .map_err(|e| s3_error!(e, InvalidArgument, "content-length overflow"))?;
-        let content_length = (content_length != 0).then_some(content_length);
-
-        let body: Option<StreamingBlob> = Some(StreamingBlob::new(vec_stream));
+        // the size of the file is unknown until it is streamed
+        let content_length: Option<ContentLength> = None;
 
         let acl: Option<ObjectCannedACL> = http::parse_field_value(&m, "x-amz-acl")?;
 
@@ -4771,6 +4693,8 @@ impl PutObject {
 
         let grant_write_acp: Option<GrantWriteACP> = http::parse_field_value(&m, "x-amz-grant-write-acp")?;
 
+        let if_none_match: Option<IfNoneMatch> = http::parse_field_value(&m, "if-none-match")?;
+
         let metadata: Option<Metadata> = {
             let mut metadata = Metadata::default();
             for (name, value) in m.fields() {
@@ -4843,6 +4767,7 @@ impl PutObject {
             grant_read,
             grant_read_acp,
             grant_write_acp,
+            if_none_match,
             key,
             metadata,
             object_lock_legal_hold_status,
@@ -4890,12 +4815,11 @@ impl super::Operation for PutObject {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -4966,12 +4890,11 @@ impl super::Operation for PutObjectAcl {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object_acl(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object_acl(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5024,12 +4947,11 @@ impl super::Operation for PutObjectLegalHold {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object_legal_hold(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object_legal_hold(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5081,12 +5003,11 @@ impl super::Operation for PutObjectLockConfiguration {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object_lock_configuration(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object_lock_configuration(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5143,12 +5064,11 @@ impl super::Operation for PutObjectRetention {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object_retention(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object_retention(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5201,12 +5121,11 @@ impl super::Operation for PutObjectTagging {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_object_tagging(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_object_tagging(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5250,12 +5169,11 @@ impl super::Operation for PutPublicAccessBlock {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.put_public_access_block(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.put_public_access_block(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5306,12 +5224,11 @@ impl super::Operation for RestoreObject {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.restore_object(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.restore_object(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5365,12 +5282,11 @@ impl super::Operation for SelectObjectContent {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.select_object_content(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.select_object_content(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5462,12 +5378,11 @@ impl super::Operation for UploadPart {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.upload_part(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.upload_part(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5570,12 +5485,11 @@ impl super::Operation for UploadPartCopy {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.upload_part_copy(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.upload_part_copy(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
@@ -5737,12 +5651,11 @@ impl super::Operation for WriteGetObjectResponse {
     async fn call(&self, s3: &Arc<dyn S3>, req: &mut http::Request) -> S3Result<http::Response> {
         let input = Self::deserialize_http(req)?;
         let s3_req = super::build_s3_request(input, req);
-        let result = s3.write_get_object_response(s3_req).await;
-        let s3_resp = match result {
-            Ok(val) => val,
-            Err(err) => return super::serialize_error(err),
-        };
+        let s3_resp = s3.write_get_object_response(s3_req).await?;
         let mut resp = Self::serialize_http(s3_resp.output)?;
+        if let Some(status) = s3_resp.status {
+            resp.status = status;
+        }
         resp.headers.extend(s3_resp.headers);
         resp.extensions.extend(s3_resp.extensions);
         Ok(resp)
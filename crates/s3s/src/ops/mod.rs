@@ -6,6 +6,12 @@ use self::signature::SignatureContext;
 
 mod get_object;
 
+mod cors;
+use self::cors::CorsRequest;
+
+mod post_object;
+pub(crate) use self::post_object::PostObjectSuccess;
+
 #[cfg(test)]
 mod tests;
 
@@ -20,12 +26,12 @@ use crate::http::{Request, Response};
 use crate::path::{ParseS3PathError, S3Path};
 use crate::request::S3Request;
 use crate::s3_trait::S3;
-use crate::stream::aggregate_unlimited;
-use crate::stream::VecByteStream;
+use crate::service::CorsPreflightDefault;
 
 use std::mem;
 use std::ops::Not;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 use hyper::http::HeaderValue;
@@ -48,6 +54,7 @@ fn build_s3_request<T>(input: T, req: &mut Request) -> S3Request<T> {
     let extensions = mem::take(&mut req.extensions);
     let headers = mem::take(&mut req.headers);
     let uri = mem::take(&mut req.uri);
+    let trailing_headers = req.s3ext.trailing_headers.take();
 
     S3Request {
         input,
@@ -55,13 +62,29 @@ fn build_s3_request<T>(input: T, req: &mut Request) -> S3Request<T> {
         extensions,
         headers,
         uri,
+        trailing_headers,
     }
 }
 
-fn serialize_error(x: S3Error) -> S3Result<Response> {
+/// Serializes an error with the resource and the request id of the request
+fn serialize_error(mut x: S3Error, req: &mut Request) -> S3Result<Response> {
+    req.s3ext.error_code = Some(x.code().clone());
+
+    if x.resource().is_none() {
+        x.set_resource(req.uri.path());
+    }
+    if let (None, Some(request_id)) = (x.request_id(), req.s3ext.request_id.as_deref()) {
+        x.set_request_id(request_id);
+    }
+
     let status = x.status_code().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
     let mut res = Response::with_status(status);
-    http::set_xml_body(&mut res, &x)?;
+    if let Some(headers) = x.headers() {
+        res.headers.extend(headers.clone());
+    }
+    if status != StatusCode::NOT_MODIFIED {
+        http::set_xml_body(&mut res, &x)?;
+    }
     drop(x);
     Ok(res)
 }
@@ -90,12 +113,24 @@ fn extract_s3_path(host: Option<&str>, uri_path: &str, base_domain: Option<&str>
     };
 
     result.map_err(|err| match err {
-        ParseS3PathError::InvalidPath => s3_error!(InvalidURI),
-        ParseS3PathError::InvalidBucketName => s3_error!(InvalidBucketName),
-        ParseS3PathError::KeyTooLong => s3_error!(KeyTooLongError),
+        ParseS3PathError::InvalidPath => s3_error!(InvalidURI, "Couldn't parse the specified URI."),
+        ParseS3PathError::InvalidBucketName => s3_error!(InvalidBucketName, "The specified bucket is not valid."),
+        ParseS3PathError::KeyTooLong => s3_error!(KeyTooLongError, "Your key is too long"),
     })
 }
 
+/// The path of `WriteGetObjectResponse`, which is not a valid bucket name
+const WRITE_GET_OBJECT_RESPONSE_PATH: &str = "/WriteGetObjectResponse";
+
+/// Whether a request is a `WriteGetObjectResponse` of an Object Lambda function,
+/// which is sent to `{RequestRoute}.<host>/WriteGetObjectResponse`
+fn is_write_get_object_response(req: &Request, decoded_uri_path: &str) -> bool {
+    req.method == Method::POST
+        && decoded_uri_path == WRITE_GET_OBJECT_RESPONSE_PATH
+        && req.headers.contains_key("x-amz-request-route")
+        && req.headers.contains_key("x-amz-request-token")
+}
+
 fn extract_qs(req_uri: &Uri) -> S3Result<Option<OrderedQs>> {
     let Some(query) = req_uri.query() else { return Ok(None) };
     match OrderedQs::parse(query) {
@@ -142,10 +177,10 @@ async fn extract_full_body(content_length: Option<u64>, body: &mut Body) -> S3Re
         return Ok(bytes);
     }
 
-    let bytes = body
-        .store_all_unlimited()
-        .await
-        .map_err(|e| S3Error::with_source(S3ErrorCode::InternalError, e))?;
+    let bytes = body.store_all_unlimited().await.map_err(|e| match e.downcast::<S3Error>() {
+        Ok(err) => *err,
+        Err(e) => S3Error::with_source(S3ErrorCode::InternalError, e),
+    })?;
 
     if bytes.is_empty().not() {
         let content_length = content_length.ok_or(S3ErrorCode::MissingContentLength)?;
@@ -167,34 +202,104 @@ fn fmt_content_length(len: usize) -> http::HeaderValue {
     }
 }
 
-pub async fn call(
-    req: &mut Request,
-    s3: &Arc<dyn S3>,
-    auth: Option<&dyn S3Auth>,
-    base_domain: Option<&str>,
-) -> S3Result<Response> {
-    let op = match prepare(req, auth, base_domain).await {
-        Ok(op) => op,
+/// Removes `aws-chunked` from `Content-Encoding` after the chunks are decoded,
+/// so that only the content encoding of the user is stored.
+fn remove_aws_chunked_encoding(headers: &mut HeaderMap) {
+    let Some(val) = headers.get(header::CONTENT_ENCODING) else { return };
+    let Ok(val) = val.to_str() else { return };
+    let encodings: Vec<&str> = val
+        .split(',')
+        .map(str::trim)
+        .filter(|s| s.is_empty().not() && s.eq_ignore_ascii_case("aws-chunked").not())
+        .collect();
+    if encodings.is_empty() {
+        headers.remove(header::CONTENT_ENCODING);
+    } else if let Ok(val) = HeaderValue::try_from(encodings.join(",")) {
+        headers.insert(header::CONTENT_ENCODING, val);
+    }
+}
+
+/// Checks the `Expect` header of a request, which only supports `100-continue`.
+///
+/// The `100 Continue` answer is sent by the HTTP server when the body is first polled.
+/// The body is only polled after the request is authenticated and authorized,
+/// and after the operation has checked its preconditions, so a rejected request
+/// gets its final status instead and the client does not send the payload.
+fn check_expect(req: &Request) -> S3Result<()> {
+    let Some(val) = req.headers.get(hyper::header::EXPECT) else { return Ok(()) };
+    if val.as_bytes().eq_ignore_ascii_case(b"100-continue") {
+        return Ok(());
+    }
+    let mut err: S3Error = S3ErrorCode::Custom("ExpectationFailed".into()).into();
+    err.set_message("Only the 100-continue expectation is supported");
+    err.set_status_code(StatusCode::EXPECTATION_FAILED);
+    Err(err)
+}
+
+/// The configuration of a service which is needed to handle a request
+pub struct CallContext<'a> {
+    pub s3: &'a Arc<dyn S3>,
+    pub auth: Option<&'a dyn S3Auth>,
+    pub base_domain: Option<&'a str>,
+    pub clock_skew: Duration,
+    pub signature_debug: bool,
+    pub unsigned_payload_requires_tls: bool,
+    pub cors_preflight_default: CorsPreflightDefault,
+}
+
+pub async fn call(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<Response> {
+    let s3 = ccx.s3;
+    let base_domain = ccx.base_domain;
+
+    if req.method == Method::OPTIONS {
+        return match cors::preflight(req, s3, base_domain, ccx.cors_preflight_default).await {
+            Ok(resp) => Ok(resp),
+            Err(err) => {
+                debug!(?err, "preflight request rejected");
+                serialize_error(err, req)
+            }
+        };
+    }
+
+    let cors = CorsRequest::extract(req, base_domain);
+
+    let mut resp = match prepare(req, ccx).await {
+        Ok(op) => {
+            req.s3ext.op_name = Some(op.name());
+            let result = match op.call(s3, req).await {
+                Ok(resp) => match req.s3ext.post_object_success.take() {
+                    Some(success) => success.respond(resp),
+                    None => Ok(resp),
+                },
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(resp) => resp,
+                Err(err) => {
+                    debug!(op = %op.name(), ?err, "op returns error");
+                    serialize_error(err, req)?
+                }
+            }
+        }
         Err(err) => {
             debug!(?err, "failed to prepare");
-            return serialize_error(err);
+            serialize_error(err, req)?
         }
     };
 
-    let resp = match op.call(s3, req).await {
-        Ok(resp) => resp,
-        Err(err) => {
-            debug!(op = %op.name(), ?err, "op returns error");
-            return serialize_error(err);
-        }
-    };
+    if let Some(cors) = cors {
+        cors.apply(s3, ccx.cors_preflight_default, &mut resp).await;
+    }
 
     Ok(resp)
 }
 
-async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Option<&str>) -> S3Result<&'static dyn Operation> {
+#[allow(clippy::too_many_lines)]
+async fn prepare(req: &mut Request, ccx: &CallContext<'_>) -> S3Result<&'static dyn Operation> {
+    let base_domain = ccx.base_domain;
     let s3_path;
     let mut content_length;
+    check_expect(req)?;
     {
         let decoded_uri_path = urlencoding::decode(req.uri.path())
             .map_err(|_| S3ErrorCode::InvalidURI)?
@@ -202,7 +307,12 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
 
         let host = extract_host(req)?;
 
-        req.s3ext.s3_path = Some(extract_s3_path(host.as_deref(), &decoded_uri_path, base_domain)?);
+        req.s3ext.s3_path = Some(if is_write_get_object_response(req, &decoded_uri_path) {
+            // the request route is in the host, which is not a bucket
+            S3Path::bucket(WRITE_GET_OBJECT_RESPONSE_PATH.trim_start_matches('/'))
+        } else {
+            extract_s3_path(host.as_deref(), &decoded_uri_path, base_domain)?
+        });
         s3_path = req.s3ext.s3_path.as_ref().unwrap();
 
         req.s3ext.qs = extract_qs(&req.uri)?;
@@ -216,8 +326,11 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
         let transformed_body;
         {
             let mut scx = SignatureContext {
-                auth,
+                auth: ccx.auth,
                 base_domain,
+                clock_skew: ccx.clock_skew,
+                signature_debug: ccx.signature_debug,
+                unsigned_payload_requires_tls: ccx.unsigned_payload_requires_tls,
 
                 req_method: &req.method,
                 req_uri: &req.uri,
@@ -229,12 +342,12 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
                 decoded_uri_path,
 
                 host: host.as_deref(),
-                content_length,
                 decoded_content_length,
                 mime,
 
                 multipart: None,
                 transformed_body: None,
+                trailing_headers: None,
             };
 
             let credentials = scx.check().await?;
@@ -244,9 +357,10 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
 
             req.s3ext.multipart = scx.multipart;
             req.s3ext.credentials = credentials;
+            req.s3ext.trailing_headers = scx.trailing_headers;
         }
 
-        if let Some(auth) = auth {
+        if let Some(auth) = ccx.auth {
             let mut cx = S3AuthContext {
                 credentials: req.s3ext.credentials.as_ref(),
                 s3_path,
@@ -269,6 +383,7 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
         }
         if let Some(body) = transformed_body {
             req.body = body;
+            remove_aws_chunked_encoding(&mut req.headers);
         }
 
         let has_multipart = req.s3ext.multipart.is_some();
@@ -280,13 +395,12 @@ async fn prepare(req: &mut Request, auth: Option<&dyn S3Auth>, base_domain: Opti
             if req.method == Method::POST {
                 match s3_path {
                     S3Path::Root => return Err(unknown_operation()),
-                    S3Path::Bucket { .. } => {
+                    S3Path::Bucket { bucket } => {
                         // POST object
                         debug!(?multipart);
-                        let file_stream = multipart.take_file_stream().expect("missing file stream");
-                        let vec_bytes = aggregate_unlimited(file_stream).await.map_err(S3Error::internal_error)?;
-                        let vec_stream = VecByteStream::new(vec_bytes);
-                        req.s3ext.vec_stream = Some(vec_stream);
+                        let (file, success) = post_object::prepare(multipart, bucket, &req.uri, &req.headers)?;
+                        req.s3ext.file_stream = Some(file);
+                        req.s3ext.post_object_success = Some(success);
                         break 'resolve (&PutObject as &'static dyn Operation, false);
                     }
                     // FIXME: POST /bucket/key hits this branch
@@ -0,0 +1,342 @@
+//! POST Object, which uploads a file with an HTML form
+//!
+//! The signature of the policy is checked with the other signatures.
+//! The policy restricts the form fields and the size of the file, which is streamed to [`S3::put_object`](crate::S3::put_object).
+//!
+//! See <https://docs.aws.amazon.com/AmazonS3/latest/API/sigv4-HTTPPOSTConstructPolicy.html>
+
+use crate::dto::{StreamingBlob, Timestamp, TimestampFormat};
+use crate::error::*;
+use crate::http::{self, Body, Multipart, Response};
+use crate::stream::ByteStream;
+use crate::xml;
+
+use std::fmt;
+use std::io::Write;
+use std::ops::Not;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::Stream;
+use hyper::header::{ETAG, HOST, LOCATION};
+use hyper::http::HeaderValue;
+use hyper::{HeaderMap, StatusCode, Uri};
+use serde_json::Value;
+
+/// The form fields which are not covered by the policy
+const EXEMPT_FIELDS: &[&str] = &["file", "policy", "x-amz-signature"];
+
+/// A condition of a policy, whose field is a lowercase form field name
+#[derive(Debug)]
+enum Condition {
+    Eq { field: String, value: String },
+    StartsWith { field: String, prefix: String },
+    ContentLengthRange { min: u64, max: u64 },
+}
+
+#[derive(Debug)]
+struct PostPolicy {
+    expiration: time::OffsetDateTime,
+    conditions: Vec<Condition>,
+}
+
+fn invalid_policy(msg: &str) -> S3Error {
+    s3_error!(InvalidPolicyDocument, "Invalid Policy: {}", msg)
+}
+
+fn policy_denied(msg: impl fmt::Display) -> S3Error {
+    s3_error!(AccessDenied, "Invalid according to Policy: {}", msg)
+}
+
+/// Parses a `$field` of a condition
+fn parse_field(name: &str) -> S3Result<String> {
+    match name.strip_prefix('$') {
+        Some(field) => Ok(field.to_ascii_lowercase()),
+        None => Err(invalid_policy("Invalid Condition: the field name must start with '$'")),
+    }
+}
+
+/// Parses a bound of `content-length-range`, which may be a number or a string
+fn parse_length(value: &Value) -> Option<u64> {
+    value.as_u64().or_else(|| value.as_str()?.parse().ok())
+}
+
+impl Condition {
+    fn parse(value: &Value) -> S3Result<Self> {
+        match value {
+            Value::Object(map) => {
+                let mut iter = map.iter();
+                let (Some((field, Value::String(value))), None) = (iter.next(), iter.next()) else {
+                    return Err(invalid_policy(
+                        "Invalid Simple-Condition: Simple-Conditions must have exactly one property specified.",
+                    ));
+                };
+                let field = field.strip_prefix('$').unwrap_or(field).to_ascii_lowercase();
+                Ok(Self::Eq {
+                    field,
+                    value: value.clone(),
+                })
+            }
+            Value::Array(items) => match items.as_slice() {
+                [Value::String(op), Value::String(field), Value::String(value)] if op.eq_ignore_ascii_case("eq") => {
+                    Ok(Self::Eq {
+                        field: parse_field(field)?,
+                        value: value.clone(),
+                    })
+                }
+                [Value::String(op), Value::String(field), Value::String(prefix)] if op.eq_ignore_ascii_case("starts-with") => {
+                    Ok(Self::StartsWith {
+                        field: parse_field(field)?,
+                        prefix: prefix.clone(),
+                    })
+                }
+                [Value::String(op), min, max] if op.eq_ignore_ascii_case("content-length-range") => {
+                    match (parse_length(min), parse_length(max)) {
+                        (Some(min), Some(max)) if min <= max => Ok(Self::ContentLengthRange { min, max }),
+                        _ => Err(invalid_policy("Invalid content-length-range")),
+                    }
+                }
+                _ => Err(invalid_policy("Invalid Condition")),
+            },
+            _ => Err(invalid_policy("Invalid Condition")),
+        }
+    }
+
+    fn field(&self) -> Option<&str> {
+        match self {
+            Self::Eq { field, .. } | Self::StartsWith { field, .. } => Some(field),
+            Self::ContentLengthRange { .. } => None,
+        }
+    }
+}
+
+impl PostPolicy {
+    fn parse(policy: &str) -> S3Result<Self> {
+        let bytes = base64_simd::STANDARD
+            .decode_to_vec(policy)
+            .map_err(|_| invalid_policy("Policy is not base64 encoded"))?;
+        let doc: Value = serde_json::from_slice(&bytes).map_err(|_| invalid_policy("Could not parse the policy document"))?;
+
+        let Some(expiration) = doc.get("expiration").and_then(Value::as_str) else {
+            return Err(invalid_policy("Policy missing expiration"));
+        };
+        let expiration = Timestamp::parse(TimestampFormat::DateTime, expiration)
+            .map_err(|_| invalid_policy("Invalid expiration"))?
+            .into();
+
+        let Some(conditions) = doc.get("conditions").and_then(Value::as_array) else {
+            return Err(invalid_policy("Policy missing conditions"));
+        };
+        let conditions = conditions.iter().map(Condition::parse).collect::<S3Result<_>>()?;
+
+        Ok(Self { expiration, conditions })
+    }
+
+    /// Checks the form fields and returns the range of the file size
+    fn check(&self, m: &Multipart, bucket: &str) -> S3Result<Option<(u64, u64)>> {
+        if self.expiration <= time::OffsetDateTime::now_utc() {
+            return Err(policy_denied("Policy expired."));
+        }
+
+        let field_value = |field: &str| match field {
+            "bucket" => bucket,
+            _ => m.find_field_value(field).unwrap_or_default(),
+        };
+
+        let mut length_range = None;
+        for condition in &self.conditions {
+            match condition {
+                Condition::Eq { field, value } => {
+                    if field_value(field) != value {
+                        return Err(policy_denied(format_args!(
+                            "Policy Condition failed: [\"eq\", \"${field}\", \"{value}\"]"
+                        )));
+                    }
+                }
+                Condition::StartsWith { field, prefix } => {
+                    if field_value(field).starts_with(prefix.as_str()).not() {
+                        return Err(policy_denied(format_args!(
+                            "Policy Condition failed: [\"starts-with\", \"${field}\", \"{prefix}\"]"
+                        )));
+                    }
+                }
+                Condition::ContentLengthRange { min, max } => length_range = Some((*min, *max)),
+            }
+        }
+
+        for (name, _) in m.fields() {
+            if EXEMPT_FIELDS.contains(&name.as_str()) || name.starts_with("x-ignore-") {
+                continue;
+            }
+            if self.conditions.iter().any(|c| c.field() == Some(name)).not() {
+                return Err(policy_denied(format_args!("Extra input fields: {name}")));
+            }
+        }
+
+        Ok(length_range)
+    }
+}
+
+/// Checks the file size against `content-length-range` while the file is streamed
+struct LengthRange {
+    inner: StreamingBlob,
+    size: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Stream for LengthRange {
+    type Item = Result<Bytes, StdError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(bytes))) => {
+                this.size = this.size.saturating_add(bytes.len() as u64);
+                if this.size > this.max {
+                    let err = s3_error!(EntityTooLarge, "Your proposed upload exceeds the maximum allowed size");
+                    return Poll::Ready(Some(Err(Box::new(err))));
+                }
+                Poll::Ready(Some(Ok(bytes)))
+            }
+            Poll::Ready(None) if this.size < this.min => {
+                this.min = 0;
+                let err = s3_error!(EntityTooSmall, "Your proposed upload is smaller than the minimum allowed size");
+                Poll::Ready(Some(Err(Box::new(err))))
+            }
+            poll => poll,
+        }
+    }
+}
+
+impl ByteStream for LengthRange {}
+
+/// The response of a successful POST Object request
+#[derive(Debug)]
+pub struct PostObjectSuccess {
+    bucket: String,
+    key: String,
+    /// The URL of the object
+    location: String,
+    /// `success_action_redirect`
+    redirect: Option<String>,
+    /// `success_action_status`, which is `204 No Content` by default
+    status: StatusCode,
+}
+
+struct PostResponse<'a>(&'a PostObjectSuccess, &'a str);
+
+impl xml::Serialize for PostResponse<'_> {
+    fn serialize<W: Write>(&self, s: &mut xml::Serializer<W>) -> xml::SerResult {
+        s.content("PostResponse", self)
+    }
+}
+
+impl xml::SerializeContent for PostResponse<'_> {
+    fn serialize_content<W: Write>(&self, s: &mut xml::Serializer<W>) -> xml::SerResult {
+        s.content("Location", &self.0.location)?;
+        s.content("Bucket", &self.0.bucket)?;
+        s.content("Key", &self.0.key)?;
+        s.content("ETag", self.1)
+    }
+}
+
+impl PostObjectSuccess {
+    fn new(m: &Multipart, bucket: &str, key: &str, uri: &Uri, headers: &HeaderMap) -> Self {
+        let host = headers.get(HOST).and_then(|v| v.to_str().ok()).unwrap_or("localhost");
+        let scheme = uri.scheme_str().unwrap_or("http");
+        let path = uri.path().trim_end_matches('/');
+        let location = format!("{scheme}://{host}{path}/{}", urlencoding::encode(key));
+
+        // an invalid redirect is ignored like AWS
+        let redirect = m
+            .find_field_value("success_action_redirect")
+            .filter(|url| url.parse::<Uri>().is_ok_and(|uri| uri.scheme().is_some()))
+            .map(ToOwned::to_owned);
+
+        let status = match m.find_field_value("success_action_status") {
+            Some("200") => StatusCode::OK,
+            Some("201") => StatusCode::CREATED,
+            _ => StatusCode::NO_CONTENT,
+        };
+
+        Self {
+            bucket: bucket.to_owned(),
+            key: key.to_owned(),
+            location,
+            redirect,
+            status,
+        }
+    }
+
+    /// Replaces the response of `PutObject`
+    pub fn respond(self, mut res: Response) -> S3Result<Response> {
+        let e_tag = res
+            .headers
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        res.body = Body::empty();
+
+        if let Some(ref redirect) = self.redirect {
+            let sep = if redirect.contains('?') { '&' } else { '?' };
+            let location = format!(
+                "{redirect}{sep}bucket={}&key={}&etag={}",
+                urlencoding::encode(&self.bucket),
+                urlencoding::encode(&self.key),
+                urlencoding::encode(&e_tag)
+            );
+            res.status = StatusCode::SEE_OTHER;
+            res.headers
+                .insert(LOCATION, HeaderValue::try_from(location).map_err(S3Error::internal_error)?);
+            return Ok(res);
+        }
+
+        res.status = self.status;
+        if self.status == StatusCode::CREATED {
+            res.headers
+                .insert(LOCATION, HeaderValue::try_from(&self.location).map_err(S3Error::internal_error)?);
+            http::set_xml_body(&mut res, &PostResponse(&self, &e_tag))?;
+        }
+        Ok(res)
+    }
+}
+
+/// Checks the policy of a POST Object request, and prepares the file and the response
+pub fn prepare(m: &mut Multipart, bucket: &str, uri: &Uri, headers: &HeaderMap) -> S3Result<(StreamingBlob, PostObjectSuccess)> {
+    let file_name = m.file.name.clone();
+    if let Some(key) = m.find_field_value_mut("key") {
+        *key = key.replace("${filename}", &file_name);
+    }
+
+    let policy = m
+        .find_field_value("policy")
+        .ok_or_else(|| invalid_request!("missing policy"))?;
+    let length_range = PostPolicy::parse(policy)?.check(m, bucket)?;
+
+    // the content type of the file is used if the form does not specify one
+    if m.find_field_value("content-type").is_none() && m.file.content_type.is_empty().not() {
+        let content_type = m.file.content_type.clone();
+        m.insert_field("content-type".to_owned(), content_type);
+    }
+
+    let key = m.find_field_value("key").ok_or_else(|| invalid_request!("missing key"))?;
+    let success = PostObjectSuccess::new(m, bucket, key, uri, headers);
+
+    let file_stream = m
+        .take_file_stream()
+        .ok_or_else(|| s3_error!(IncorrectNumberOfFilesInPostRequest))?;
+    let mut file = StreamingBlob::wrap(file_stream);
+    if let Some((min, max)) = length_range {
+        file = StreamingBlob::new(LengthRange {
+            inner: file,
+            size: 0,
+            min,
+            max,
+        });
+    }
+
+    Ok((file, success))
+}
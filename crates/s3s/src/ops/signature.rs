@@ -1,9 +1,11 @@
 use crate::auth::Credentials;
 use crate::auth::S3Auth;
+use crate::auth::SecretKey;
 use crate::error::*;
 use crate::http;
 use crate::http::{AwsChunkedStream, Body, Multipart};
 use crate::http::{OrderedHeaders, OrderedQs};
+use crate::http::{SignatureCtx, TrailerCtx, TrailingHeaders};
 use crate::sig_v2;
 use crate::sig_v2::{AuthorizationV2, PresignedUrlV2};
 use crate::sig_v4;
@@ -11,24 +13,49 @@ use crate::sig_v4::PostSignatureInfo;
 use crate::sig_v4::PresignedUrlV4;
 use crate::sig_v4::{AmzContentSha256, AmzDate};
 use crate::sig_v4::{AuthorizationV4, CredentialV4};
+use crate::stream::{ByteStream, DynByteStream, RemainingLength};
 use crate::utils::is_base64_encoded;
 
 use std::mem;
 use std::ops::Not;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
+use bytes::Bytes;
 use bytestring::ByteString;
+use futures::Stream;
+use hyper::header::HeaderName;
 use hyper::Method;
+use hyper::StatusCode;
 use hyper::Uri;
 use mime::Mime;
+use sha2::{Digest, Sha256};
 use tracing::debug;
 
+fn sha256_mismatch(message: &'static str) -> S3Error {
+    let mut err: S3Error = S3ErrorCode::Custom(ByteString::from_static("XAmzContentSHA256Mismatch")).into();
+    err.set_message(message);
+    err.set_status_code(StatusCode::BAD_REQUEST);
+    err
+}
+
+fn authorization_query_parameters_error(message: &'static str) -> S3Error {
+    let mut err: S3Error = S3ErrorCode::Custom(ByteString::from_static("AuthorizationQueryParametersError")).into();
+    err.set_message(message);
+    err.set_status_code(StatusCode::BAD_REQUEST);
+    err
+}
+
+/// The maximum lifetime of a presigned url, which is seven days
+const MAX_PRESIGNED_URL_EXPIRES: time::Duration = time::Duration::days(7);
+
 fn extract_amz_content_sha256<'a>(hs: &'_ OrderedHeaders<'a>) -> S3Result<Option<AmzContentSha256<'a>>> {
     let Some(val) = hs.get_unique(crate::header::X_AMZ_CONTENT_SHA256) else { return Ok(None) };
     match AmzContentSha256::parse(val) {
         Ok(x) => Ok(Some(x)),
         Err(e) => {
-            let mut err: S3Error = S3ErrorCode::Custom(ByteString::from_static("XAmzContentSHA256Mismatch")).into();
-            err.set_message("invalid header: x-amz-content-sha256");
+            let mut err = sha256_mismatch("invalid header: x-amz-content-sha256");
             err.set_source(Box::new(e));
             Err(err)
         }
@@ -51,9 +78,28 @@ fn extract_amz_date(hs: &'_ OrderedHeaders<'_>) -> S3Result<Option<AmzDate>> {
     }
 }
 
+fn extract_amz_trailer(hs: &'_ OrderedHeaders<'_>) -> S3Result<Vec<HeaderName>> {
+    let val = hs
+        .get_unique(crate::header::X_AMZ_TRAILER)
+        .ok_or_else(|| invalid_request!("missing header: x-amz-trailer"))?;
+    let mut names = Vec::new();
+    for name in val.split(',').map(str::trim).filter(|s| s.is_empty().not()) {
+        match HeaderName::from_bytes(name.as_bytes()) {
+            Ok(x) => names.push(x),
+            Err(e) => return Err(invalid_request!(e, "invalid header: x-amz-trailer")),
+        }
+    }
+    Ok(names)
+}
+
 pub struct SignatureContext<'a> {
     pub auth: Option<&'a dyn S3Auth>,
     pub base_domain: Option<&'a str>,
+    pub clock_skew: Duration,
+    /// Whether to log the canonical requests and the strings to sign of signature mismatches
+    pub signature_debug: bool,
+    /// Whether unsigned payloads are only allowed over TLS
+    pub unsigned_payload_requires_tls: bool,
 
     pub req_method: &'a Method,
     pub req_uri: &'a Uri,
@@ -65,18 +111,35 @@ pub struct SignatureContext<'a> {
     pub decoded_uri_path: String,
 
     pub host: Option<&'a str>,
-    pub content_length: Option<u64>,
     pub mime: Option<Mime>,
     pub decoded_content_length: Option<usize>,
 
     pub transformed_body: Option<Body>,
+    pub trailing_headers: Option<TrailingHeaders>,
     pub multipart: Option<Multipart>,
 }
 
+/// The service of the credential scope of a signature, which is `s3-object-lambda` for `WriteGetObjectResponse`
+fn signing_service(aws_service: &str) -> &str {
+    if aws_service == "s3-object-lambda" {
+        aws_service
+    } else {
+        "s3"
+    }
+}
+
 fn require_auth(auth: Option<&dyn S3Auth>) -> S3Result<&dyn S3Auth> {
     auth.ok_or_else(|| s3_error!(NotImplemented, "This service has no authentication provider"))
 }
 
+/// Looks up the secret key of the credentials, which are temporary if a session token is given
+async fn get_secret_key(auth: &dyn S3Auth, access_key: &str, session_token: Option<&str>) -> S3Result<SecretKey> {
+    match session_token {
+        Some(session_token) => auth.get_secret_key_with_session_token(access_key, session_token).await,
+        None => auth.get_secret_key(access_key).await,
+    }
+}
+
 impl SignatureContext<'_> {
     pub async fn check(&mut self) -> S3Result<Option<Credentials>> {
         if let Some(result) = self.v2_check().await {
@@ -156,17 +219,24 @@ impl SignatureContext<'_> {
         let amz_date = AmzDate::parse(info.x_amz_date).map_err(|_| invalid_request!("invalid field: x-amz-date"))?;
 
         let access_key = credential.access_key_id.to_owned();
-        let secret_key = auth.get_secret_key(&access_key).await?;
+        let session_token = info.x_amz_security_token;
+        let secret_key = get_secret_key(auth, &access_key, session_token).await?;
 
         let string_to_sign = info.policy;
         let signature = sig_v4::calculate_signature(string_to_sign, &secret_key, &amz_date, credential.aws_region);
 
         if signature != info.x_amz_signature {
+            self.log_signature_mismatch(None, string_to_sign);
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
+        let session_token = session_token.map(ToOwned::to_owned);
         self.multipart = Some(multipart);
-        Ok(Credentials { access_key, secret_key })
+        Ok(Credentials {
+            access_key,
+            secret_key,
+            session_token,
+        })
     }
 
     pub async fn v4_check_presigned_url(&mut self) -> S3Result<Credentials> {
@@ -177,6 +247,16 @@ impl SignatureContext<'_> {
         // ASK: how to use it?
         let _content_sha256: Option<AmzContentSha256<'_>> = extract_amz_content_sha256(&self.hs)?;
 
+        if presigned_url.algorithm != "AWS4-HMAC-SHA256" {
+            return Err(authorization_query_parameters_error("X-Amz-Algorithm only supports \"AWS4-HMAC-SHA256\""));
+        }
+
+        if presigned_url.expires > MAX_PRESIGNED_URL_EXPIRES {
+            return Err(authorization_query_parameters_error(
+                "X-Amz-Expires must be less than a week (in seconds) that is, the given X-Amz-Expires must be less than 604800 seconds",
+            ));
+        }
+
         {
             // check expiration
             let now = time::OffsetDateTime::now_utc();
@@ -187,8 +267,8 @@ impl SignatureContext<'_> {
                 .ok_or_else(|| invalid_request!("invalid amz date"))?;
 
             let duration = now - date;
-            if duration.is_positive().not() {
-                return Err(invalid_request!("invalid presigned url date"))?;
+            if duration.is_negative() && duration.unsigned_abs() > self.clock_skew {
+                return Err(s3_error!(AccessDenied, "Request is not valid yet"));
             }
 
             if duration > presigned_url.expires {
@@ -198,9 +278,10 @@ impl SignatureContext<'_> {
 
         let auth = require_auth(self.auth)?;
         let access_key = presigned_url.credential.access_key_id;
-        let secret_key = auth.get_secret_key(access_key).await?;
+        let session_token = qs.get_unique("X-Amz-Security-Token");
+        let secret_key = get_secret_key(auth, access_key, session_token).await?;
 
-        let signature = {
+        let (signature, canonical_request, string_to_sign) = {
             let headers = self.hs.find_multiple(&presigned_url.signed_headers);
             let method = &self.req_method;
             let uri_path = &self.decoded_uri_path;
@@ -211,19 +292,109 @@ impl SignatureContext<'_> {
             let amz_date = &presigned_url.amz_date;
             let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, amz_date, region);
 
-            sig_v4::calculate_signature(&string_to_sign, &secret_key, amz_date, region)
+            let signature = sig_v4::calculate_signature(&string_to_sign, &secret_key, amz_date, region);
+            (signature, canonical_request, string_to_sign)
         };
 
         if signature != presigned_url.signature {
+            self.log_signature_mismatch(Some(&canonical_request), &string_to_sign);
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
         Ok(Credentials {
             access_key: access_key.into(),
             secret_key,
+            session_token: session_token.map(ToOwned::to_owned),
         })
     }
 
+    /// Rejects an unsigned payload which is not sent over TLS, if TLS is required
+    fn check_unsigned_payload_transport(&self, amz_content_sha256: &AmzContentSha256<'_>) -> S3Result<()> {
+        let is_unsigned = matches!(
+            amz_content_sha256,
+            AmzContentSha256::UnsignedPayload | AmzContentSha256::UnsignedMultipleChunksWithTrailer
+        );
+        if self.unsigned_payload_requires_tls.not() || is_unsigned.not() {
+            return Ok(());
+        }
+
+        let is_https = |scheme: &str| scheme.eq_ignore_ascii_case("https");
+        let forwarded_proto = self.hs.get_unique(crate::header::X_FORWARDED_PROTO);
+        if self.req_uri.scheme_str().is_some_and(is_https) || forwarded_proto.is_some_and(is_https) {
+            return Ok(());
+        }
+        Err(s3_error!(AccessDenied, "Unsigned payloads are only allowed over TLS"))
+    }
+
+    /// Logs what the server signed when a signature does not match, if enabled.
+    /// Neither of them contains the secret key.
+    fn log_signature_mismatch(&self, canonical_request: Option<&str>, string_to_sign: &str) {
+        if self.signature_debug {
+            debug!(?canonical_request, ?string_to_sign, "signature mismatch details");
+        }
+    }
+
+    /// Rejects a request whose date is out of the allowed clock skew
+    fn check_clock_skew(&self, amz_date: &AmzDate) -> S3Result<()> {
+        let date = amz_date.to_time().ok_or_else(|| invalid_request!("invalid amz date"))?;
+        let now = time::OffsetDateTime::now_utc();
+        if (now - date).unsigned_abs() <= self.clock_skew {
+            return Ok(());
+        }
+
+        let format = time::macros::format_description!("[year][month][day]T[hour][minute][second]Z");
+        let server_time = now.format(&format).map_err(S3Error::internal_error)?;
+        Err(s3_error!(
+            RequestTimeTooSkewed,
+            "The difference between the request time and the server's time is too large. RequestTime: {}, ServerTime: {}",
+            amz_date.fmt_iso8601(),
+            server_time
+        ))
+    }
+
+    /// Replaces the request body with a decoded `aws-chunked` stream
+    fn transform_aws_chunked_body(
+        &mut self,
+        amz_content_sha256: &AmzContentSha256<'_>,
+        seed_signature: Box<str>,
+        amz_date: AmzDate,
+        region: &str,
+        secret_key: &SecretKey,
+    ) -> S3Result<()> {
+        let decoded_content_length = self
+            .decoded_content_length
+            .ok_or_else(|| s3_error!(MissingContentLength, "missing header: x-amz-decoded-content-length"))?;
+
+        let stream = match amz_content_sha256 {
+            AmzContentSha256::MultipleChunks => AwsChunkedStream::new(
+                mem::take(self.req_body),
+                seed_signature,
+                amz_date,
+                region.into(),
+                secret_key.clone(),
+                decoded_content_length,
+            ),
+            _ => {
+                let sig_ctx = match amz_content_sha256 {
+                    AmzContentSha256::UnsignedMultipleChunksWithTrailer => None,
+                    _ => Some(SignatureCtx::new(seed_signature, amz_date, region.into(), secret_key.clone())),
+                };
+
+                let declared = extract_amz_trailer(&self.hs)?;
+                let trailing_headers = TrailingHeaders::default();
+                self.trailing_headers = Some(trailing_headers.clone());
+                let trailer_ctx = TrailerCtx::new(declared, trailing_headers);
+
+                AwsChunkedStream::with_trailer(mem::take(self.req_body), sig_ctx, Some(trailer_ctx), decoded_content_length)
+            }
+        };
+
+        debug!(len=?stream.exact_remaining_length(), "aws-chunked");
+
+        self.transformed_body = Some(Body::from(stream.into_byte_stream()));
+        Ok(())
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn v4_check_header_auth(&mut self) -> S3Result<Credentials> {
         let authorization: AuthorizationV4<'_> = {
@@ -237,15 +408,30 @@ impl SignatureContext<'_> {
 
         let amz_content_sha256 =
             extract_amz_content_sha256(&self.hs)?.ok_or_else(|| invalid_request!("missing header: x-amz-content-sha256"))?;
+        self.check_unsigned_payload_transport(&amz_content_sha256)?;
 
         let access_key = authorization.credential.access_key_id;
-        let secret_key = auth.get_secret_key(access_key).await?;
+        let session_token = self.hs.get_unique(crate::header::X_AMZ_SECURITY_TOKEN);
+        if session_token.is_some() && authorization.signed_headers.contains(&"x-amz-security-token").not() {
+            return Err(s3_error!(
+                AccessDenied,
+                "There were headers present in the request which were not signed: x-amz-security-token"
+            ));
+        }
+        let secret_key = get_secret_key(auth, access_key, session_token).await?;
 
         let amz_date = extract_amz_date(&self.hs)?.ok_or_else(|| invalid_request!("missing header: x-amz-date"))?;
+        self.check_clock_skew(&amz_date)?;
 
-        let is_stream = matches!(amz_content_sha256, AmzContentSha256::MultipleChunks);
+        let is_stream = matches!(
+            amz_content_sha256,
+            AmzContentSha256::MultipleChunks
+                | AmzContentSha256::MultipleChunksWithTrailer
+                | AmzContentSha256::UnsignedMultipleChunksWithTrailer
+        );
+        let mut verify_payload = None;
 
-        let signature = {
+        let (signature, canonical_request, string_to_sign) = {
             let method = &self.req_method;
             let uri_path = &self.decoded_uri_path;
             let query_strings: &[(String, String)] = self.qs.as_ref().map_or(&[], AsRef::as_ref);
@@ -254,7 +440,11 @@ impl SignatureContext<'_> {
             let headers = self.hs.find_multiple(&authorization.signed_headers);
 
             let canonical_request = if is_stream {
-                let payload = sig_v4::Payload::MultipleChunks;
+                let payload = match amz_content_sha256 {
+                    AmzContentSha256::MultipleChunksWithTrailer => sig_v4::Payload::MultipleChunksWithTrailer,
+                    AmzContentSha256::UnsignedMultipleChunksWithTrailer => sig_v4::Payload::UnsignedMultipleChunksWithTrailer,
+                    _ => sig_v4::Payload::MultipleChunks,
+                };
                 sig_v4::create_canonical_request(method, uri_path, query_strings, &headers, payload)
             } else if matches!(*self.req_method, Method::GET | Method::HEAD) {
                 let payload = if matches!(amz_content_sha256, AmzContentSha256::UnsignedPayload) {
@@ -264,57 +454,54 @@ impl SignatureContext<'_> {
                 };
                 sig_v4::create_canonical_request(method, uri_path, query_strings, &headers, payload)
             } else {
-                let bytes = super::extract_full_body(self.content_length, self.req_body).await?;
-
-                if bytes.len() < 1024 {
-                    debug!(len=?bytes.len(), body=?bytes, "extracted full body");
-                } else {
-                    debug!(len=?bytes.len(), "extracted full body");
-                }
-
-                let payload = if matches!(amz_content_sha256, AmzContentSha256::UnsignedPayload) {
-                    sig_v4::Payload::Unsigned
-                } else if bytes.is_empty() {
-                    sig_v4::Payload::Empty
-                } else {
-                    sig_v4::Payload::SingleChunk(&bytes)
+                // a payload in memory is hashed here,
+                // other payloads are verified while they are streamed to the operation
+                let bytes = self.req_body.bytes();
+                let payload = match (&amz_content_sha256, &bytes) {
+                    (AmzContentSha256::UnsignedPayload, _) => sig_v4::Payload::Unsigned,
+                    (_, Some(bytes)) if bytes.is_empty() => sig_v4::Payload::Empty,
+                    (_, Some(bytes)) => sig_v4::Payload::SingleChunk(bytes),
+                    (AmzContentSha256::SingleChunk { payload_checksum }, None) => {
+                        verify_payload = Some(*payload_checksum);
+                        sig_v4::Payload::SingleChunkChecksum(payload_checksum)
+                    }
+                    (
+                        AmzContentSha256::MultipleChunks
+                        | AmzContentSha256::MultipleChunksWithTrailer
+                        | AmzContentSha256::UnsignedMultipleChunksWithTrailer,
+                        None,
+                    ) => unreachable!(),
                 };
-
                 sig_v4::create_canonical_request(method, uri_path, query_strings, &headers, payload)
             };
 
             let region = authorization.credential.aws_region;
-            let string_to_sign = sig_v4::create_string_to_sign(&canonical_request, &amz_date, region);
-            sig_v4::calculate_signature(&string_to_sign, &secret_key, &amz_date, region)
+            let service = signing_service(authorization.credential.aws_service);
+            let string_to_sign = sig_v4::create_string_to_sign_for_service(&canonical_request, &amz_date, region, service);
+            let signature = sig_v4::calculate_signature_for_service(&string_to_sign, &secret_key, &amz_date, region, service);
+            (signature, canonical_request, string_to_sign)
         };
 
         if signature != authorization.signature {
             debug!(?signature, expected=?authorization.signature, "signature mismatch");
+            self.log_signature_mismatch(Some(&canonical_request), &string_to_sign);
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
-        if is_stream {
-            let decoded_content_length = self
-                .decoded_content_length
-                .ok_or_else(|| s3_error!(MissingContentLength, "missing header: x-amz-decoded-content-length"))?;
-
-            let stream = AwsChunkedStream::new(
-                mem::take(self.req_body),
-                signature.into(),
-                amz_date,
-                authorization.credential.aws_region.into(),
-                secret_key.clone(),
-                decoded_content_length,
-            );
-
-            debug!(len=?stream.exact_remaining_length(), "aws-chunked");
+        if let Some(payload_checksum) = verify_payload {
+            let body = mem::take(self.req_body);
+            *self.req_body = Body::from(Sha256VerifiedStream::new(body, payload_checksum).into_byte_stream());
+        }
 
-            self.transformed_body = Some(Body::from(stream.into_byte_stream()));
+        if is_stream {
+            let region = authorization.credential.aws_region;
+            self.transform_aws_chunked_body(&amz_content_sha256, signature.into(), amz_date, region, &secret_key)?;
         }
 
         Ok(Credentials {
             access_key: access_key.into(),
             secret_key,
+            session_token: session_token.map(ToOwned::to_owned),
         })
     }
 
@@ -345,18 +532,21 @@ impl SignatureContext<'_> {
 
         let auth = require_auth(self.auth)?;
         let access_key = auth_v2.access_key;
-        let secret_key = auth.get_secret_key(access_key).await?;
+        let session_token = self.hs.get_unique(crate::header::X_AMZ_SECURITY_TOKEN);
+        let secret_key = get_secret_key(auth, access_key, session_token).await?;
 
         let string_to_sign = sig_v2::create_string_to_sign(method, date, &self.hs, &uri_s3_path, self.qs);
         let signature = sig_v2::calculate_signature(&secret_key, &string_to_sign);
 
         if signature != auth_v2.signature {
+            self.log_signature_mismatch(None, &string_to_sign);
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
         Ok(Credentials {
             access_key: access_key.into(),
             secret_key,
+            session_token: session_token.map(ToOwned::to_owned),
         })
     }
 
@@ -372,19 +562,76 @@ impl SignatureContext<'_> {
 
         let auth = require_auth(self.auth)?;
         let access_key = presigned_url.access_key;
-        let secret_key = auth.get_secret_key(access_key).await?;
+        let session_token = qs.get_unique("x-amz-security-token");
+        let secret_key = get_secret_key(auth, access_key, session_token).await?;
 
         let string_to_sign =
             sig_v2::create_string_to_sign(self.req_method, presigned_url.expires_str, &self.hs, &uri_s3_path, self.qs);
         let signature = sig_v2::calculate_signature(&secret_key, &string_to_sign);
 
         if signature != presigned_url.signature {
+            self.log_signature_mismatch(None, &string_to_sign);
             return Err(s3_error!(SignatureDoesNotMatch));
         }
 
         Ok(Credentials {
             access_key: access_key.into(),
             secret_key,
+            session_token: session_token.map(ToOwned::to_owned),
         })
     }
 }
+
+/// A payload which is verified against the checksum of `x-amz-content-sha256` when it ends
+struct Sha256VerifiedStream {
+    body: Body,
+    hasher: Option<Sha256>,
+    expected: String,
+}
+
+impl Sha256VerifiedStream {
+    fn new(body: Body, expected: &str) -> Self {
+        Self {
+            body,
+            hasher: Some(Sha256::new()),
+            expected: expected.to_owned(),
+        }
+    }
+
+    fn into_byte_stream(self) -> DynByteStream {
+        Box::pin(self)
+    }
+}
+
+impl Stream for Sha256VerifiedStream {
+    type Item = Result<Bytes, StdError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.body).poll_next(cx) {
+            Poll::Ready(Some(Ok(bytes))) => {
+                if let Some(ref mut hasher) = this.hasher {
+                    hasher.update(&bytes);
+                }
+                Poll::Ready(Some(Ok(bytes)))
+            }
+            Poll::Ready(None) => {
+                let Some(hasher) = this.hasher.take() else { return Poll::Ready(None) };
+                let checksum = hex_simd::encode_to_string(hasher.finalize(), hex_simd::AsciiCase::Lower);
+                if checksum == this.expected {
+                    return Poll::Ready(None);
+                }
+                debug!(?checksum, expected = ?this.expected, "payload checksum mismatch");
+                let err = sha256_mismatch("The provided 'x-amz-content-sha256' header does not match what was computed.");
+                Poll::Ready(Some(Err(Box::new(err))))
+            }
+            other => other,
+        }
+    }
+}
+
+impl ByteStream for Sha256VerifiedStream {
+    fn remaining_length(&self) -> RemainingLength {
+        self.body.remaining_length()
+    }
+}
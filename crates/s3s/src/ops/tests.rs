@@ -33,3 +33,225 @@ fn track_future_size() {
         assert_eq!(size, expected, "{name:?} size changed: prev {expected}, now {size}");
     }
 }
+
+#[tokio::test]
+async fn error_xml() {
+    struct Unimplemented;
+    impl crate::S3 for Unimplemented {}
+
+    let service = crate::service::S3ServiceBuilder::new(Unimplemented).build();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri("http://localhost/_invalid_/key")
+        .body(Body::empty())
+        .unwrap();
+    let mut res = service.call(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let request_id = res.headers()[crate::header::X_AMZ_REQUEST_ID].to_str().unwrap().to_owned();
+    assert_eq!(request_id.len(), 16);
+
+    let body = res.body_mut().store_all_unlimited().await.unwrap();
+    let expected = format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "<Error>",
+            "<Code>InvalidBucketName</Code>",
+            "<Message>The specified bucket is not valid.</Message>",
+            "<Resource>/_invalid_/key</Resource>",
+            "<RequestId>{}</RequestId>",
+            "</Error>"
+        ),
+        request_id
+    );
+    assert_eq!(std::str::from_utf8(&body).unwrap(), expected);
+}
+
+#[test]
+fn aws_chunked_encoding() {
+    let cases = [
+        ("aws-chunked", None),
+        ("aws-chunked,gzip", Some("gzip")),
+        ("gzip, aws-chunked", Some("gzip")),
+        ("aws-chunked, gzip, br", Some("gzip,br")),
+        ("gzip", Some("gzip")),
+    ];
+
+    for (input, expected) in cases {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(input));
+        remove_aws_chunked_encoding(&mut headers);
+        let ans = headers.get(header::CONTENT_ENCODING).map(|v| v.to_str().unwrap());
+        assert_eq!(ans, expected, "input: {input:?}");
+    }
+}
+
+#[tokio::test]
+async fn unsigned_payload() {
+    use crate::auth::SimpleAuth;
+    use crate::dto::{PutObjectInput, PutObjectOutput};
+    use crate::service::S3ServiceBuilder;
+    use crate::sig_v4::AmzDate;
+    use crate::{S3Request, S3Response};
+
+    use futures::StreamExt;
+
+    /// Accepts objects whose bodies are `hello`
+    struct Hello;
+
+    #[async_trait::async_trait]
+    impl crate::S3 for Hello {
+        async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+            let mut body = req.input.body.unwrap();
+            let mut bytes = Vec::new();
+            while let Some(chunk) = body.next().await {
+                bytes.extend_from_slice(&chunk.unwrap());
+            }
+            assert_eq!(bytes, b"hello");
+            Ok(S3Response::new(PutObjectOutput::default()))
+        }
+    }
+
+    let (access_key, secret_key, region) = ("AKIDUNSIGNED", "secret", "us-east-1");
+
+    let signed_request = |uri: &str, forwarded_proto: Option<&str>| {
+        let format = time::macros::format_description!("[year][month][day]T[hour][minute][second]Z");
+        let x_amz_date = time::OffsetDateTime::now_utc().format(&format).unwrap();
+        let amz_date = AmzDate::parse(&x_amz_date).unwrap();
+
+        let signed_headers = [
+            ("host", "localhost"),
+            ("x-amz-content-sha256", "UNSIGNED-PAYLOAD"),
+            ("x-amz-date", x_amz_date.as_str()),
+        ];
+        let headers = OrderedHeaders::from_slice_unchecked(&signed_headers);
+        let canonical_request = crate::sig_v4::create_canonical_request(
+            &Method::PUT,
+            "/bucket/key",
+            &[] as &[(String, String)],
+            &headers,
+            crate::sig_v4::Payload::Unsigned,
+        );
+        let string_to_sign = crate::sig_v4::create_string_to_sign(&canonical_request, &amz_date, region);
+        let signature = crate::sig_v4::calculate_signature(&string_to_sign, &secret_key.into(), &amz_date, region);
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={access_key}/{}/{region}/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={signature}",
+            amz_date.fmt_date()
+        );
+
+        let body = hyper::Body::wrap_stream(futures::stream::iter([Ok::<_, std::io::Error>(Bytes::from_static(b"hello"))]));
+        let mut req = hyper::Request::builder().method(Method::PUT).uri(uri);
+        for (name, value) in signed_headers {
+            req = req.header(name, value);
+        }
+        if let Some(proto) = forwarded_proto {
+            req = req.header("x-forwarded-proto", proto);
+        }
+        req.header("authorization", authorization).body(Body::from(body)).unwrap()
+    };
+
+    let service = |requires_tls: bool| {
+        let mut b = S3ServiceBuilder::new(Hello);
+        b.set_auth(SimpleAuth::from_single(access_key, secret_key));
+        b.set_unsigned_payload_requires_tls(requires_tls);
+        b.build()
+    };
+
+    {
+        let service = service(false);
+        let res = service
+            .call(signed_request("http://localhost/bucket/key", None))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    {
+        let service = service(true);
+        let res = service
+            .call(signed_request("http://localhost/bucket/key", None))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = service
+            .call(signed_request("https://localhost/bucket/key", None))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = service
+            .call(signed_request("http://localhost/bucket/key", Some("https")))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn expect_continue() {
+    use crate::auth::SimpleAuth;
+    use crate::dto::{PutObjectInput, PutObjectOutput};
+    use crate::service::{S3Service, S3ServiceBuilder};
+    use crate::{S3Request, S3Response};
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use futures::StreamExt;
+
+    /// Accepts objects in `bucket` only
+    struct OneBucket;
+
+    #[async_trait::async_trait]
+    impl crate::S3 for OneBucket {
+        async fn put_object(&self, req: S3Request<PutObjectInput>) -> S3Result<S3Response<PutObjectOutput>> {
+            if req.input.bucket != "bucket" {
+                return Err(s3_error!(NoSuchBucket));
+            }
+            let mut body = req.input.body.unwrap();
+            while let Some(chunk) = body.next().await {
+                chunk.unwrap();
+            }
+            Ok(S3Response::new(PutObjectOutput::default()))
+        }
+    }
+
+    /// Returns the status of the response and whether the body was polled
+    async fn put(service: &S3Service, uri: &str, expect: &str) -> (StatusCode, bool) {
+        let polled = Arc::new(AtomicBool::new(false));
+        let body = {
+            let polled = Arc::clone(&polled);
+            futures::stream::once(async move {
+                polled.store(true, Ordering::SeqCst);
+                Ok::<_, std::io::Error>(Bytes::from_static(b"hello"))
+            })
+        };
+        let req = hyper::Request::builder()
+            .method(Method::PUT)
+            .uri(uri)
+            .header(hyper::header::EXPECT, expect)
+            .body(Body::from(hyper::Body::wrap_stream(body)))
+            .unwrap();
+        let res = service.call(req).await.unwrap();
+        (res.status(), polled.load(Ordering::SeqCst))
+    }
+
+    let service = S3ServiceBuilder::new(OneBucket).build();
+    assert_eq!(put(&service, "http://localhost/bucket/key", "100-continue").await, (StatusCode::OK, true));
+    assert_eq!(put(&service, "http://localhost/bucket/key", "100-Continue").await, (StatusCode::OK, true));
+
+    // the payload of a rejected request is never polled
+    let res = put(&service, "http://localhost/missing/key", "100-continue").await;
+    assert_eq!(res, (StatusCode::NOT_FOUND, false));
+    let res = put(&service, "http://localhost/bucket/key", "200-ok").await;
+    assert_eq!(res, (StatusCode::EXPECTATION_FAILED, false));
+
+    let service = {
+        let mut b = S3ServiceBuilder::new(OneBucket);
+        b.set_auth(SimpleAuth::from_single("AKIDEXPECT", "secret"));
+        b.build()
+    };
+    let res = put(&service, "http://localhost/bucket/key", "100-continue").await;
+    assert_eq!(res, (StatusCode::FORBIDDEN, false));
+}
@@ -6,6 +6,7 @@
 //! + [Bucket nameing rules](https://docs.aws.amazon.com/AmazonS3/latest/userguide/bucketnamingrules.html)
 
 use std::net::IpAddr;
+use std::ops::Not;
 
 /// A path in the S3 storage
 #[derive(Debug, PartialEq, Eq)]
@@ -120,7 +121,11 @@ pub fn check_bucket_name(name: &str) -> bool {
         return false;
     }
 
-    if name.starts_with("xn--") {
+    if name.starts_with("xn--") || name.starts_with("sthree-") {
+        return false;
+    }
+
+    if name.ends_with("-s3alias") || name.ends_with("--ol-s3") {
         return false;
     }
 
@@ -164,21 +169,28 @@ pub fn parse_path_style(uri_path: &str) -> Result<S3Path, ParseS3PathError> {
 }
 
 /// Parses a virtual-hosted-style request
+///
+/// The bucket is the subdomain of `base_domain` in the host.
+/// A host which is not a subdomain of `base_domain` falls back to a path-style request.
 /// # Errors
 /// Returns an `Err` if the s3 path is invalid
 pub fn parse_virtual_hosted_style<'a>(base_domain: &str, host: &'a str, uri_path: &'a str) -> Result<S3Path, ParseS3PathError> {
-    if host == base_domain {
-        return parse_path_style(uri_path);
-    }
+    // the port of the host is ignored if the base domain has no port
+    let host = match host.rsplit_once(':') {
+        Some((h, port)) if base_domain.contains(':').not() && port.bytes().all(|b| b.is_ascii_digit()) => h,
+        _ => host,
+    };
 
-    let Some(key) = uri_path.strip_prefix('/') else { return Err(ParseS3PathError::InvalidPath) };
+    let host = host.to_ascii_lowercase();
+    let base_domain = base_domain.to_ascii_lowercase();
 
-    let bucket = match host.strip_suffix(base_domain).and_then(|h| h.strip_suffix('.')) {
-        Some(b) => b.to_owned(),
-        None => host.to_ascii_lowercase(),
+    let Some(bucket) = host.strip_suffix(base_domain.as_str()).and_then(|h| h.strip_suffix('.')) else {
+        return parse_path_style(uri_path);
     };
 
-    if !check_bucket_name(&bucket) {
+    let Some(key) = uri_path.strip_prefix('/') else { return Err(ParseS3PathError::InvalidPath) };
+
+    if !check_bucket_name(bucket) {
         return Err(ParseS3PathError::InvalidBucketName);
     }
 
@@ -212,6 +224,12 @@ mod tests {
             ("doc_example_bucket", false),
             ("DocExampleBucket", false),
             ("doc-example-bucket-", false),
+            ("doc..example", false),
+            ("192.168.5.4", false),
+            ("xn--example", false),
+            ("sthree-example", false),
+            ("example-s3alias", false),
+            ("example--ol-s3", false),
         ];
 
         for (input, expected) in cases {
@@ -271,9 +289,36 @@ mod tests {
         {
             let base_domain = "s3.us-east-1.amazonaws.com";
             let host = "example.com";
-            let uri_path = "/homepage.html";
+            let uri_path = "/bucket/homepage.html";
             let ans = parse_virtual_hosted_style(base_domain, host, uri_path);
-            let expected = Ok(S3Path::object("example.com", "homepage.html"));
+            let expected = Ok(S3Path::object("bucket", "homepage.html"));
+            assert_eq!(ans, expected);
+        }
+
+        {
+            let base_domain = "s3.example.com";
+            let host = "MyBucket.S3.example.com:9000";
+            let uri_path = "/dir/key";
+            let ans = parse_virtual_hosted_style(base_domain, host, uri_path);
+            let expected = Ok(S3Path::object("mybucket", "dir/key"));
+            assert_eq!(ans, expected);
+        }
+
+        {
+            let base_domain = "localhost:8014";
+            let host = "127.0.0.1:8014";
+            let uri_path = "/bucket/key";
+            let ans = parse_virtual_hosted_style(base_domain, host, uri_path);
+            let expected = Ok(S3Path::object("bucket", "key"));
+            assert_eq!(ans, expected);
+        }
+
+        {
+            let base_domain = "localhost:8014";
+            let host = "bucket.localhost:8014";
+            let uri_path = "/key";
+            let ans = parse_virtual_hosted_style(base_domain, host, uri_path);
+            let expected = Ok(S3Path::object("bucket", "key"));
             assert_eq!(ans, expected);
         }
     }
@@ -1,4 +1,5 @@
 use crate::auth::Credentials;
+use crate::http::TrailingHeaders;
 
 use hyper::http::{Extensions, HeaderValue};
 use hyper::{HeaderMap, Uri};
@@ -25,6 +26,11 @@ pub struct S3Request<T> {
 
     // Raw URI
     pub uri: Uri,
+
+    /// The trailing headers of an `aws-chunked` body, which are declared by `x-amz-trailer`.
+    ///
+    /// The headers are available after the body has been read to the end.
+    pub trailing_headers: Option<TrailingHeaders>,
 }
 
 impl<T> S3Request<T> {
@@ -35,6 +41,7 @@ impl<T> S3Request<T> {
             extensions: default(),
             headers: default(),
             uri: default(),
+            trailing_headers: default(),
         }
     }
 
@@ -45,6 +52,7 @@ impl<T> S3Request<T> {
             extensions: self.extensions,
             headers: self.headers,
             uri: self.uri,
+            trailing_headers: self.trailing_headers,
         }
     }
 }
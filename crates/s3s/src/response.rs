@@ -1,12 +1,16 @@
 use hyper::http::Extensions;
 use hyper::http::HeaderValue;
 use hyper::HeaderMap;
+use hyper::StatusCode;
 
 #[non_exhaustive]
 pub struct S3Response<T> {
     /// Operation output
     pub output: T,
 
+    /// Response status code, overrides the default status code of the operation.
+    pub status: Option<StatusCode>,
+
     /// Response headers, overrides the headers in `output`.
     pub headers: HeaderMap<HeaderValue>,
 
@@ -20,6 +24,7 @@ impl<T> S3Response<T> {
     pub fn new(output: T) -> Self {
         Self {
             output,
+            status: None,
             headers: HeaderMap::new(),
             extensions: Extensions::new(),
         }
@@ -28,6 +33,7 @@ impl<T> S3Response<T> {
     pub fn map_output<U>(self, f: impl FnOnce(T) -> U) -> S3Response<U> {
         S3Response {
             output: f(self.output),
+            status: self.status,
             headers: self.headers,
             extensions: self.extensions,
         }
@@ -1,21 +1,49 @@
 use crate::auth::S3Auth;
-use crate::error::{S3Error, S3Result};
+use crate::error::{S3Error, S3ErrorCode, S3Result};
+use crate::header::{X_AMZ_ID_2, X_AMZ_REQUEST_ID};
 use crate::http::{Body, Request};
+use crate::metrics::{RequestMetrics, S3Metrics};
+use crate::ops::CallContext;
+use crate::path::S3Path;
 use crate::s3_trait::S3;
 
 use std::convert::Infallible;
 use std::future::{ready, Ready};
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 use futures::future::BoxFuture;
+use hyper::header::HeaderValue;
 use hyper::service::Service;
-use tracing::{debug, error};
+use hyper::HeaderMap;
+use hyper::StatusCode;
+use tracing::{debug, error, Level};
+
+/// The default tolerance of the difference between the request time and the server time, which is 15 minutes like AWS
+const DEFAULT_CLOCK_SKEW: Duration = Duration::from_mins(15);
+
+/// The answer to a CORS request which no CORS rule of the bucket allows,
+/// including requests to buckets without a CORS configuration
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CorsPreflightDefault {
+    /// Rejects preflight requests with `403 Forbidden` and adds no CORS headers to other responses, like AWS
+    #[default]
+    Deny,
+    /// Allows any origin, method and headers, which is meant for local development only
+    AllowAll,
+}
 
 pub struct S3ServiceBuilder {
     s3: Arc<dyn S3>,
     auth: Option<Box<dyn S3Auth>>,
     base_domain: Option<String>,
+    clock_skew: Duration,
+    signature_debug: bool,
+    unsigned_payload_requires_tls: bool,
+    cors_preflight_default: CorsPreflightDefault,
+    access_log: Option<Level>,
+    metrics: Option<Box<dyn S3Metrics>>,
 }
 
 impl S3ServiceBuilder {
@@ -25,6 +53,12 @@ impl S3ServiceBuilder {
             s3: Arc::new(s3),
             auth: None,
             base_domain: None,
+            clock_skew: DEFAULT_CLOCK_SKEW,
+            signature_debug: false,
+            unsigned_payload_requires_tls: false,
+            cors_preflight_default: CorsPreflightDefault::Deny,
+            access_log: None,
+            metrics: None,
         }
     }
 
@@ -36,43 +70,206 @@ impl S3ServiceBuilder {
         self.base_domain = Some(base_domain.into());
     }
 
+    /// Sets how far the date of a signed request may differ from the server time.
+    ///
+    /// Requests outside the window are rejected with `RequestTimeTooSkewed`. The default is 15 minutes.
+    pub fn set_clock_skew(&mut self, clock_skew: Duration) {
+        self.clock_skew = clock_skew;
+    }
+
+    /// Enables the logs of signature mismatches.
+    ///
+    /// When the signature of a request does not match, the canonical request and the string to sign
+    /// computed by the server are logged at debug level, so that they can be compared with the ones of the client.
+    /// The secret key is never logged. This is disabled by default.
+    pub fn set_signature_debug(&mut self, enabled: bool) {
+        self.signature_debug = enabled;
+    }
+
+    /// Requires `UNSIGNED-PAYLOAD` and `STREAMING-UNSIGNED-PAYLOAD-TRAILER` requests to be sent over TLS.
+    ///
+    /// Such requests are rejected with `AccessDenied` unless the scheme of the request URI is `https`,
+    /// or a proxy terminating TLS sets `X-Forwarded-Proto: https`. Unsigned payloads are allowed over any transport by default.
+    pub fn set_unsigned_payload_requires_tls(&mut self, enabled: bool) {
+        self.unsigned_payload_requires_tls = enabled;
+    }
+
+    /// Sets how CORS requests which no CORS rule of the bucket allows are answered.
+    ///
+    /// The default is [`CorsPreflightDefault::Deny`], like AWS.
+    pub fn set_cors_preflight_default(&mut self, default: CorsPreflightDefault) {
+        self.cors_preflight_default = default;
+    }
+
+    /// Enables access logs, which are emitted at the given level when each request completes.
+    ///
+    /// An access log records the method, the bucket, the key, the operation, the status, the response size,
+    /// the duration and the error code of a request. Access logs are disabled by default.
+    pub fn set_access_log(&mut self, level: Level) {
+        self.access_log = Some(level);
+    }
+
+    /// Sets a hook which is called when each request starts and completes.
+    pub fn set_metrics(&mut self, metrics: impl S3Metrics) {
+        self.metrics = Some(Box::new(metrics));
+    }
+
     #[must_use]
     pub fn build(self) -> S3Service {
         S3Service {
             s3: self.s3,
             auth: self.auth,
             base_domain: self.base_domain,
+            clock_skew: self.clock_skew,
+            signature_debug: self.signature_debug,
+            unsigned_payload_requires_tls: self.unsigned_payload_requires_tls,
+            cors_preflight_default: self.cors_preflight_default,
+            access_log: self.access_log,
+            metrics: self.metrics,
+        }
+    }
+}
+
+/// Generates a request id like AWS, which is 16 uppercase hex digits
+fn generate_request_id() -> String {
+    let (id, _) = uuid::Uuid::new_v4().as_u64_pair();
+    format!("{id:016X}")
+}
+
+/// Generates an extended request id like AWS, which is a base64 string
+fn generate_id_2() -> String {
+    let bytes = [uuid::Uuid::new_v4().into_bytes(), uuid::Uuid::new_v4().into_bytes()].concat();
+    base64_simd::STANDARD.encode_to_string(bytes)
+}
+
+/// Sets `x-amz-request-id` and `x-amz-id-2` of a response
+fn set_request_ids(headers: &mut HeaderMap, request_id: &str, id_2: &str) {
+    for (name, value) in [(X_AMZ_REQUEST_ID, request_id), (X_AMZ_ID_2, id_2)] {
+        if let Ok(value) = HeaderValue::try_from(value) {
+            headers.insert(name, value);
         }
     }
 }
 
+/// Returns the status and the error code of a completed request
+fn outcome<'a>(req: &'a Request, result: &'a S3Result<hyper::Response<Body>>) -> (StatusCode, Option<&'a S3ErrorCode>) {
+    match result {
+        Ok(res) => (res.status(), req.s3ext.error_code.as_ref()),
+        Err(err) => (err.status_code().unwrap_or(StatusCode::INTERNAL_SERVER_ERROR), Some(err.code())),
+    }
+}
+
+/// Emits an access log of a completed request
+fn log_access(level: Level, req: &Request, result: &S3Result<hyper::Response<Body>>, duration: Duration) {
+    let (bucket, key) = match req.s3ext.s3_path {
+        Some(S3Path::Bucket { ref bucket }) => (Some(&**bucket), None),
+        Some(S3Path::Object { ref bucket, ref key }) => (Some(&**bucket), Some(&**key)),
+        _ => (None, None),
+    };
+    let (status, error_code) = outcome(req, result);
+    let error_code = error_code.map(S3ErrorCode::as_str);
+    let bytes = result.as_ref().ok().and_then(|res| {
+        let val = res.headers().get(hyper::header::CONTENT_LENGTH)?;
+        atoi::atoi::<u64>(val.as_bytes())
+    });
+
+    macro_rules! emit {
+        ($level:expr) => {
+            tracing::event!(
+                $level,
+                method = %req.method,
+                bucket,
+                key,
+                op = req.s3ext.op_name,
+                status = status.as_u16(),
+                bytes,
+                ?duration,
+                error_code,
+                request_id = req.s3ext.request_id.as_deref(),
+                "request completed"
+            )
+        };
+    }
+
+    match level {
+        Level::ERROR => emit!(Level::ERROR),
+        Level::WARN => emit!(Level::WARN),
+        Level::INFO => emit!(Level::INFO),
+        Level::DEBUG => emit!(Level::DEBUG),
+        Level::TRACE => emit!(Level::TRACE),
+    }
+}
+
 pub struct S3Service {
     s3: Arc<dyn S3>,
     auth: Option<Box<dyn S3Auth>>,
     base_domain: Option<String>,
+    clock_skew: Duration,
+    signature_debug: bool,
+    unsigned_payload_requires_tls: bool,
+    cors_preflight_default: CorsPreflightDefault,
+    access_log: Option<Level>,
+    metrics: Option<Box<dyn S3Metrics>>,
 }
 
 impl S3Service {
     #[tracing::instrument(
         level = "debug",
         skip(self, req),
-        fields(start_time=?time::OffsetDateTime::now_utc())
+        fields(start_time=?time::OffsetDateTime::now_utc(), request_id, id_2)
     )]
     pub async fn call(&self, req: hyper::Request<Body>) -> S3Result<hyper::Response<Body>> {
         debug!(?req);
 
+        let start = Instant::now();
         let mut req = Request::from(req);
 
-        let s3 = &self.s3;
-        let auth = self.auth.as_deref();
-        let base_domain = self.base_domain.as_deref();
-        let result = crate::ops::call(&mut req, s3, auth, base_domain).await.map(Into::into);
+        if let Some(metrics) = &self.metrics {
+            metrics.on_request_start(&req.method);
+        }
+
+        let request_id = generate_request_id();
+        let id_2 = generate_id_2();
+        let span = tracing::Span::current();
+        span.record("request_id", request_id.as_str());
+        span.record("id_2", id_2.as_str());
+        req.s3ext.request_id = Some(request_id.clone());
+
+        let ccx = CallContext {
+            s3: &self.s3,
+            auth: self.auth.as_deref(),
+            base_domain: self.base_domain.as_deref(),
+            clock_skew: self.clock_skew,
+            signature_debug: self.signature_debug,
+            unsigned_payload_requires_tls: self.unsigned_payload_requires_tls,
+            cors_preflight_default: self.cors_preflight_default,
+        };
+        let result = crate::ops::call(&mut req, &ccx).await.map(|res| {
+            let mut res: hyper::Response<Body> = res.into();
+            set_request_ids(res.headers_mut(), &request_id, &id_2);
+            res
+        });
 
         match result {
             Ok(ref res) => debug!(?res),
             Err(ref err) => error!(?err),
         }
 
+        let duration = start.elapsed();
+        if let Some(level) = self.access_log {
+            log_access(level, &req, &result, duration);
+        }
+        if let Some(metrics) = &self.metrics {
+            let (status, error_code) = outcome(&req, &result);
+            metrics.on_request_end(&RequestMetrics {
+                method: &req.method,
+                op_name: req.s3ext.op_name,
+                status,
+                error_code,
+                duration,
+            });
+        }
+
         result
     }
 
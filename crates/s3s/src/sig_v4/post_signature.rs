@@ -6,6 +6,7 @@ pub struct PostSignatureInfo<'a> {
     pub x_amz_credential: &'a str,
     pub x_amz_date: &'a str,
     pub x_amz_signature: &'a str,
+    pub x_amz_security_token: Option<&'a str>,
 }
 
 impl<'a> PostSignatureInfo<'a> {
@@ -15,12 +16,14 @@ impl<'a> PostSignatureInfo<'a> {
         let x_amz_credential = m.find_field_value("x-amz-credential")?;
         let x_amz_date = m.find_field_value("x-amz-date")?;
         let x_amz_signature = m.find_field_value("x-amz-signature")?;
+        let x_amz_security_token = m.find_field_value("x-amz-security-token");
         Some(Self {
             policy,
             x_amz_algorithm,
             x_amz_credential,
             x_amz_date,
             x_amz_signature,
+            x_amz_security_token,
         })
     }
 }
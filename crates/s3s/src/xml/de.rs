@@ -39,6 +39,9 @@ pub struct Deserializer<'xml> {
 
     /// store an extra event
     next_slot: Option<DeEvent<'xml>>,
+
+    /// the last start tag, for reading attributes
+    last_start: Option<BytesStart<'xml>>,
 }
 
 /// XML deserialization result
@@ -101,6 +104,7 @@ impl<'xml> Deserializer<'xml> {
             inner: Reader::from_reader(xml),
             peeked: None,
             next_slot: None,
+            last_start: None,
         }
     }
 
@@ -160,6 +164,7 @@ impl<'xml> Deserializer<'xml> {
                     if x.name().as_ref() != name {
                         return Err(unexpected_tag_name());
                     }
+                    self.last_start = Some(x);
                     return Ok(());
                 }
                 DeEvent::End(_) => return Err(unexpected_end()),
@@ -214,6 +219,7 @@ impl<'xml> Deserializer<'xml> {
             match self.peek_event()? {
                 DeEvent::Start(start) => {
                     self.consume_peeked();
+                    self.last_start = Some(start.clone());
                     let name = start.name();
                     let ans = f(self, name.as_ref())?;
                     self.expect_end(name.as_ref())?;
@@ -238,6 +244,7 @@ impl<'xml> Deserializer<'xml> {
             match self.peek_event()? {
                 DeEvent::Start(start) => {
                     self.consume_peeked();
+                    self.last_start = Some(start.clone());
 
                     let name = start.name();
                     f(self, name.as_ref())?;
@@ -256,6 +263,21 @@ impl<'xml> Deserializer<'xml> {
         }
     }
 
+    /// Reads an attribute of the current element
+    ///
+    /// It must be called before any child element is deserialized.
+    ///
+    /// # Errors
+    /// Returns an error if the attribute is invalid.
+    pub fn attribute(&self, name: &str) -> DeResult<Option<String>> {
+        let Some(ref start) = self.last_start else { return Ok(None) };
+        let attr = start.try_get_attribute(name).map_err(invalid_xml)?;
+        match attr {
+            Some(attr) => Ok(Some(attr.unescape_value().map_err(invalid_xml)?.into_owned())),
+            None => Ok(None),
+        }
+    }
+
     /// Deserializes text
     ///
     /// # Errors
@@ -505,7 +505,7 @@ impl SerializeContent for GetBucketLifecycleConfigurationOutput {
 impl SerializeContent for GetBucketLocationOutput {
     fn serialize_content<W: Write>(&self, s: &mut Serializer<W>) -> SerResult {
         if let Some(ref val) = self.location_constraint {
-            s.content("LocationConstraint", val)?;
+            val.serialize_content(s)?;
         }
         Ok(())
     }
@@ -660,6 +660,8 @@ impl SerializeContent for Grant {
 
 impl SerializeContent for Grantee {
     fn serialize_content<W: Write>(&self, s: &mut Serializer<W>) -> SerResult {
+        s.attribute("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance");
+        s.attribute("xsi:type", self.type_.as_str());
         if let Some(ref val) = self.display_name {
             s.content("DisplayName", val)?;
         }
@@ -669,7 +671,6 @@ impl SerializeContent for Grantee {
         if let Some(ref val) = self.id {
             s.content("ID", val)?;
         }
-        s.content("xsi:type", &self.type_)?;
         if let Some(ref val) = self.uri {
             s.content("URI", val)?;
         }
@@ -3167,7 +3168,7 @@ impl<'xml> DeserializeContent<'xml> for Grantee {
         let mut display_name: Option<DisplayName> = None;
         let mut email_address: Option<EmailAddress> = None;
         let mut id: Option<ID> = None;
-        let mut type_: Option<Type> = None;
+        let type_: Option<Type> = d.attribute("xsi:type")?.map(Type::from);
         let mut uri: Option<URI> = None;
         d.for_each_element(|d, x| match x {
             b"DisplayName" => {
@@ -3191,13 +3192,6 @@ impl<'xml> DeserializeContent<'xml> for Grantee {
                 id = Some(d.content()?);
                 Ok(())
             }
-            b"xsi:type" => {
-                if type_.is_some() {
-                    return Err(DeError::DuplicateField);
-                }
-                type_ = Some(d.content()?);
-                Ok(())
-            }
             b"URI" => {
                 if uri.is_some() {
                     return Err(DeError::DuplicateField);
@@ -35,6 +35,9 @@ pub trait SerializeContent {
 pub struct Serializer<W: Write> {
     /// inner writer
     inner: Writer<W>,
+
+    /// start tag which is not written yet, so that attributes can still be added
+    pending: Option<BytesStart<'static>>,
 }
 
 /// XML serialization error
@@ -51,21 +54,43 @@ pub type SerResult<T = (), E = SerError> = std::result::Result<T, E>;
 impl<W: Write> Serializer<W> {
     /// Creates a new serializer
     pub fn new(w: W) -> Self {
-        Self { inner: Writer::new(w) }
+        Self {
+            inner: Writer::new(w),
+            pending: None,
+        }
+    }
+
+    /// Writes the pending start tag
+    fn flush_start(&mut self) -> SerResult {
+        match self.pending.take() {
+            Some(start) => self.inner.write_event(Event::Start(start)).map_err(wrap_xml),
+            None => Ok(()),
+        }
     }
 
     /// Writes an event
     fn event(&mut self, event: Event<'_>) -> SerResult {
+        self.flush_start()?;
         self.inner.write_event(event).map_err(wrap_xml)
     }
 
     /// Writes an element
     fn element(&mut self, name: &str, f: impl FnOnce(&mut Self) -> SerResult) -> SerResult {
-        self.event(start(name))?;
+        self.flush_start()?;
+        self.pending = Some(BytesStart::new(name.to_owned()));
         f(self)?;
         self.event(end(name))
     }
 
+    /// Adds an attribute to the current element
+    ///
+    /// It must be called before any content of the element is written.
+    pub fn attribute(&mut self, name: &str, value: &str) {
+        if let Some(ref mut start) = self.pending {
+            start.push_attribute((name, value));
+        }
+    }
+
     /// Serializes a type
     ///
     /// # Errors
@@ -173,11 +198,6 @@ const fn wrap_xml(inner: quick_xml::Error) -> SerError {
     SerError { inner }
 }
 
-/// start event
-fn start(name: &str) -> Event<'_> {
-    Event::Start(BytesStart::new(name))
-}
-
 /// end event
 fn end(name: &str) -> Event<'_> {
     Event::End(BytesEnd::new(name))
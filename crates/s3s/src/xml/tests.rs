@@ -133,6 +133,31 @@ fn d004() {
     assert!(ans.output_serialization.csv.is_some());
 }
 
+#[test]
+fn d005() {
+    let input = r#"
+        <AccessControlPolicy xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <AccessControlList>
+                <Grant>
+                    <Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="CanonicalUser">
+                        <ID>someone</ID>
+                    </Grantee>
+                    <Permission>READ</Permission>
+                </Grant>
+            </AccessControlList>
+        </AccessControlPolicy>
+    "#;
+
+    let ans = deserialize::<crate::dto::AccessControlPolicy>(input.as_bytes()).unwrap();
+
+    let grants = ans.grants.as_deref().unwrap();
+    assert_eq!(grants.len(), 1);
+
+    let grantee = grants[0].grantee.as_ref().unwrap();
+    assert_eq!(grantee.type_.as_str(), "CanonicalUser");
+    assert_eq!(grantee.id.as_deref(), Some("someone"));
+}
+
 #[test]
 fn s001() {
     let val = crate::dto::LifecycleExpiration {
@@ -146,3 +171,41 @@ fn s001() {
 
     assert_eq!(ans, expected);
 }
+
+#[test]
+fn s002() {
+    let mut buf = Vec::with_capacity(256);
+    {
+        let mut ser = xml::Serializer::new(&mut buf);
+        let val = crate::dto::GetBucketLocationOutput {
+            location_constraint: Some(crate::dto::BucketLocationConstraint::from_static("eu-west-1")),
+        };
+        xml::Serialize::serialize(&val, &mut ser).unwrap();
+    }
+    let ans = String::from_utf8(buf).unwrap();
+    assert_eq!(ans, "<LocationConstraint>eu-west-1</LocationConstraint>");
+}
+
+#[test]
+fn s003() {
+    let val = crate::dto::Grant {
+        grantee: Some(crate::dto::Grantee {
+            display_name: None,
+            email_address: None,
+            id: None,
+            type_: crate::dto::Type::from_static(crate::dto::Type::GROUP),
+            uri: Some("http://acs.amazonaws.com/groups/global/AllUsers".to_owned()),
+        }),
+        permission: Some(crate::dto::Permission::from_static(crate::dto::Permission::READ)),
+    };
+
+    let ans = serialize_content(&val).unwrap();
+    let expected = concat!(
+        r#"<Grantee xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="Group">"#,
+        "<URI>http://acs.amazonaws.com/groups/global/AllUsers</URI>",
+        "</Grantee>",
+        "<Permission>READ</Permission>",
+    );
+
+    assert_eq!(ans, expected);
+}